@@ -0,0 +1,39 @@
+//! Detects the version of the compiler to enable faster implementations
+//! that rely on newer `core` APIs. Without them (or if the version cannot
+//! be detected) the code sticks to what the MSRV (1.46) provides.
+
+use std::env;
+use std::process::Command;
+
+/// `(minor version, cfg)`: each cfg is enabled when the compiler is at
+/// least `1.<minor version>`.
+const PROBES: &[(u32, &str)] = &[
+    // `{integer}::carrying_add`, `{integer}::carrying_mul`,
+    // `{integer}::carrying_mul_add`
+    (91, "flt2dec2flt_bigint_helpers"),
+];
+
+fn rustc_minor_version() -> Option<u32> {
+    let rustc = env::var_os("RUSTC")?;
+    let output = Command::new(rustc).arg("--version").output().ok()?;
+    let version = String::from_utf8(output.stdout).ok()?;
+    // "rustc 1.xx.y (...)"
+    let mut parts = version.split_whitespace().nth(1)?.split('.');
+    if parts.next()? != "1" {
+        return None;
+    }
+    parts.next()?.parse().ok()
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    let minor = rustc_minor_version().unwrap_or(0);
+    for &(min_minor, cfg) in PROBES {
+        if minor >= 80 {
+            println!("cargo:rustc-check-cfg=cfg({})", cfg);
+        }
+        if minor >= min_minor {
+            println!("cargo:rustc-cfg={}", cfg);
+        }
+    }
+}
@@ -0,0 +1,71 @@
+//! Conversions between floating point numbers and `bigdecimal::BigDecimal`.
+//!
+//! Requires the `bigdecimal` feature.
+//!
+//! # Example
+//!
+//! ```
+//! use flt2dec2flt::bigdecimal::{from_bigdecimal, to_bigdecimal};
+//!
+//! // 0.1f32 is not exactly 0.1
+//! let exact = to_bigdecimal(0.1f32).unwrap();
+//! assert_eq!(exact.to_string(), "0.100000001490116119384765625");
+//!
+//! // Converting back is correctly rounded
+//! assert_eq!(from_bigdecimal::<f32>(&exact), 0.1);
+//! assert_eq!(from_bigdecimal::<f64>(&exact), 0.100000001490116119384765625);
+//! ```
+
+use alloc::string::String;
+
+use ::bigdecimal_crate::BigDecimal;
+use ::num_bigint::{BigInt, Sign};
+
+use crate::{FloatExt, PreFormatted, PreParsed};
+
+/// Converts `v` into a `BigDecimal` with exactly the same value.
+///
+/// Returns `None` if `v` is NaN or infinite. Negative zero becomes zero.
+pub fn to_bigdecimal<T: FloatExt>(v: T) -> Option<BigDecimal> {
+    // Enough to hold every significant digit of any `f32` or `f64`.
+    let mut buf = [0; crate::PREFORMAT_EXACT_FIXED_BASE_BUF_LEN];
+    match v.preformat_exact_exp(&mut buf, crate::PREFORMAT_EXACT_FIXED_BASE_BUF_LEN) {
+        PreFormatted::NaN | PreFormatted::Inf(_) => None,
+        PreFormatted::Zero(_) => Some(BigDecimal::from(0)),
+        PreFormatted::Finite(sign, digits, _, exp) => {
+            // Digits past the exact expansion are zeros, drop them.
+            let len = digits.iter().rposition(|&c| c != b'0').map_or(0, |i| i + 1);
+            let mut int = BigInt::parse_bytes(&digits[..len], 10).unwrap();
+            if sign {
+                int = -int;
+            }
+            // 0.digits * 10^exp == digits * 10^(exp - len)
+            let scale = len as i64 - i64::from(exp);
+            Some(BigDecimal::new(int, scale))
+        }
+    }
+}
+
+/// Converts `v` into the nearest floating point number (rounding half
+/// to even).
+pub fn from_bigdecimal<T: FloatExt>(v: &BigDecimal) -> T {
+    let (int, scale) = v.as_bigint_and_exponent();
+    let sign = int.sign() == Sign::Minus;
+    let digits: String = int.magnitude().to_str_radix(10);
+    // `0.digits * 10^exp`, so the exponent reflects the magnitude.
+    let exp = (digits.len() as i64).saturating_sub(scale);
+    if exp > 400 {
+        return if sign { T::NEG_INFINITY } else { T::INFINITY };
+    } else if exp < -400 {
+        return T::from_bits_u64(u64::from(sign) << (T::BITS - 1));
+    }
+
+    let preparsed = PreParsed {
+        sign,
+        int_digits: b"",
+        frac_digits: digits.as_bytes(),
+        exp: exp as i32,
+    };
+    // There are always digits and all of them are ASCII digits.
+    T::from_preparsed(preparsed).unwrap()
+}
@@ -0,0 +1,278 @@
+//! Stack-allocated arbitrary-precision unsigned integers.
+//!
+//! Exact float-string conversion needs integers larger than any native
+//! type (for example, to compare a long decimal string with a binary
+//! float without rounding). This module exposes the bignum used internally
+//! by the conversion algorithms, so custom strategies can reuse it without
+//! pulling a heap-allocated bignum crate in `no_std` environments.
+
+use crate::core_num;
+
+/// Stack-allocated unsigned integer of up to 1280 bits (40 digits of 32 bits).
+///
+/// Operations panic if the result does not fit in 1280 bits (or, for
+/// subtractions, if the result would be negative). Constructors that take
+/// arbitrary input return `None` instead.
+///
+/// This type is intentionally not `Copy`, as it takes 160+ bytes.
+///
+/// # Example
+///
+/// ```
+/// use flt2dec2flt::bignum::Big32x40;
+///
+/// let mut x = Big32x40::from_u64(1);
+/// x.mul_pow10(30).add_small(7);
+///
+/// let mut buf = [0; Big32x40::MAX_DECIMAL_LEN];
+/// assert_eq!(x.to_decimal(&mut buf), b"1000000000000000000000000000007");
+///
+/// let rem = x.div_rem_small(1000);
+/// assert_eq!(rem, 7);
+/// assert_eq!(x, Big32x40::from_decimal(b"1000000000000000000000000000").unwrap());
+/// ```
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Big32x40(core_num::bignum::Big32x40);
+
+impl Big32x40 {
+    /// Number of 32-bit digits available.
+    pub const NUM_DIGITS: usize = 40;
+
+    /// Maximum number of bits that can be represented.
+    pub const MAX_BITS: usize = 32 * Self::NUM_DIGITS;
+
+    /// Maximum number of decimal digits produced by `to_decimal`
+    /// (`ceil(1280 * log_10 2)`).
+    pub const MAX_DECIMAL_LEN: usize = 386;
+
+    /// Makes a bignum equal to zero.
+    pub fn zero() -> Self {
+        Self(core_num::bignum::Big32x40::from_small(0))
+    }
+
+    /// Makes a bignum from a `u32` value.
+    pub fn from_u32(v: u32) -> Self {
+        Self(core_num::bignum::Big32x40::from_small(v))
+    }
+
+    /// Makes a bignum from a `u64` value.
+    pub fn from_u64(v: u64) -> Self {
+        Self(core_num::bignum::Big32x40::from_u64(v))
+    }
+
+    /// Makes a bignum from 32-bit digits `[a, b, c, ...]`, which represent
+    /// `a + b * 2^32 + c * 2^64 + ...`.
+    ///
+    /// Returns `None` if the value does not fit.
+    pub fn from_digits(digits: &[u32]) -> Option<Self> {
+        let used = digits.len() - digits.iter().rev().take_while(|&&d| d == 0).count();
+        if used > Self::NUM_DIGITS {
+            return None;
+        }
+        // Start with no digits in use, so each shift adds exactly one.
+        let mut r = Self::from_u64(0);
+        for &d in digits[..used].iter().rev() {
+            r.0.mul_pow2(32);
+            r.0.add_small(d);
+        }
+        Some(r)
+    }
+
+    /// Makes a bignum from a string of ASCII decimal digits, most
+    /// significant first. An empty string represents zero.
+    ///
+    /// Returns `None` if `digits` contains a non-digit byte or if the
+    /// value does not fit.
+    pub fn from_decimal(digits: &[u8]) -> Option<Self> {
+        let mut r = Self::zero();
+        for &c in digits {
+            if !c.is_ascii_digit() {
+                return None;
+            }
+            r.checked_mul_small(10)?;
+            r.0.add_small(u32::from(c - b'0'));
+        }
+        Some(r)
+    }
+
+    /// Returns the 32-bit digits `[a, b, c, ...]`, such that the value is
+    /// `a + b * 2^32 + c * 2^64 + ...`. The slice may contain leading
+    /// (most significant) zeros.
+    pub fn digits(&self) -> &[u32] {
+        self.0.digits()
+    }
+
+    /// Returns `true` if the bignum is zero.
+    pub fn is_zero(&self) -> bool {
+        self.0.is_zero()
+    }
+
+    /// Returns the number of bits necessary to represent this value. Zero
+    /// is considered to need 0 bits.
+    pub fn bit_length(&self) -> usize {
+        self.0.bit_length()
+    }
+
+    /// Returns the bit with weight `2^i`.
+    pub fn get_bit(&self, i: usize) -> bool {
+        i < Self::MAX_BITS && self.0.get_bit(i) != 0
+    }
+
+    /// Adds `other` to itself and returns its own mutable reference.
+    pub fn add(&mut self, other: &Self) -> &mut Self {
+        self.0.add(&other.0);
+        self
+    }
+
+    /// Adds a `u32` to itself and returns its own mutable reference.
+    pub fn add_small(&mut self, other: u32) -> &mut Self {
+        self.0.add_small(other);
+        self
+    }
+
+    /// Subtracts `other` from itself and returns its own mutable reference.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `other` is greater than `self`.
+    pub fn sub(&mut self, other: &Self) -> &mut Self {
+        self.0.sub(&other.0);
+        self
+    }
+
+    /// Multiplies itself by a `u32` and returns its own mutable reference.
+    pub fn mul_small(&mut self, other: u32) -> &mut Self {
+        self.0.mul_small(other);
+        self
+    }
+
+    /// Multiplies itself by a `u32`, returning `None` (and leaving `self`
+    /// in an unspecified state) if the result does not fit.
+    pub fn checked_mul_small(&mut self, other: u32) -> Option<&mut Self> {
+        let digits = self.0.digits();
+        if digits.len() == Self::NUM_DIGITS {
+            let carry = digits.iter().fold(0, |carry, &d| {
+                (u64::from(d) * u64::from(other) + carry) >> 32
+            });
+            if carry != 0 {
+                return None;
+            }
+        }
+        self.0.mul_small(other);
+        Some(self)
+    }
+
+    /// Multiplies itself by `2^bits` and returns its own mutable reference.
+    pub fn mul_pow2(&mut self, bits: usize) -> &mut Self {
+        assert!(
+            self.is_zero() || self.bit_length() + bits <= Self::MAX_BITS,
+            "bignum overflow"
+        );
+        if !self.is_zero() {
+            self.0.mul_pow2(bits);
+        }
+        self
+    }
+
+    /// Multiplies itself by `5^e` and returns its own mutable reference.
+    pub fn mul_pow5(&mut self, e: usize) -> &mut Self {
+        self.0.mul_pow5(e);
+        self
+    }
+
+    /// Multiplies itself by `10^e` and returns its own mutable reference.
+    pub fn mul_pow10(&mut self, e: usize) -> &mut Self {
+        self.mul_pow5(e).mul_pow2(e)
+    }
+
+    /// Multiplies itself by `other` and returns its own mutable reference.
+    pub fn mul(&mut self, other: &Self) -> &mut Self {
+        self.0.mul_digits(other.0.digits());
+        self
+    }
+
+    /// Divides itself by a `u32` and returns the remainder.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `other` is zero.
+    pub fn div_rem_small(&mut self, other: u32) -> u32 {
+        self.0.div_rem_small(other).1
+    }
+
+    /// Returns the remainder of dividing itself by a `u32`, without
+    /// modifying `self`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `other` is zero.
+    pub fn rem_small(&self, other: u32) -> u32 {
+        assert!(other > 0);
+        self.0.digits().iter().rev().fold(0, |rem, &d| {
+            ((u64::from(rem) << 32 | u64::from(d)) % u64::from(other)) as u32
+        })
+    }
+
+    /// Returns the quotient and the remainder of dividing itself by `other`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `other` is zero.
+    pub fn div_rem(&self, other: &Self) -> (Self, Self) {
+        let mut q = Self::zero();
+        let mut r = Self::zero();
+        self.0.div_rem(&other.0, &mut q.0, &mut r.0);
+        (q, r)
+    }
+
+    /// Writes the decimal representation of `self` into `buf` and returns
+    /// the written part. Zero is written as `"0"`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `buf` is shorter than the number of decimal digits of
+    /// `self` (`Self::MAX_DECIMAL_LEN` is always enough).
+    pub fn to_decimal<'a>(&self, buf: &'a mut [u8]) -> &'a [u8] {
+        let mut x = self.clone();
+        let mut n = 0;
+        loop {
+            // Extract 9 digits at once.
+            let mut chunk = x.div_rem_small(1_000_000_000);
+            let last = x.is_zero();
+            for _ in 0..9 {
+                buf[n] = b'0' + (chunk % 10) as u8;
+                n += 1;
+                chunk /= 10;
+                if last && chunk == 0 {
+                    break;
+                }
+            }
+            if last {
+                break;
+            }
+        }
+        buf[..n].reverse();
+        &buf[..n]
+    }
+}
+
+impl Default for Big32x40 {
+    fn default() -> Self {
+        Self::zero()
+    }
+}
+
+impl core::fmt::Display for Big32x40 {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let mut buf = [0; Self::MAX_DECIMAL_LEN];
+        let digits = self.to_decimal(&mut buf);
+        // Digits are always ASCII.
+        f.pad_integral(true, "", core::str::from_utf8(digits).unwrap())
+    }
+}
+
+impl core::fmt::Debug for Big32x40 {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        core::fmt::Debug::fmt(&self.0, f)
+    }
+}
@@ -0,0 +1,153 @@
+//! Support for IEEE 754 binary128 (quadruple precision) numbers, given as
+//! their `u128` bit patterns.
+//!
+//! The bit pattern has the sign in bit 127, a 15-bit biased exponent and
+//! a 112-bit fraction. The digits are generated from the exact value, and
+//! parsing rounds once, directly to the 113 bits of precision.
+//!
+//! # Example
+//!
+//! ```
+//! use flt2dec2flt::binary128::{self, SHORTEST_BUF_LEN};
+//! use flt2dec2flt::{PreFormatted, PreParsed};
+//!
+//! let pi = 0x4000_921F_B544_42D1_8469_898C_C517_01B8;
+//! let mut buf = [0; SHORTEST_BUF_LEN];
+//! assert_eq!(
+//!     binary128::preformat_shortest(pi, &mut buf),
+//!     PreFormatted::Finite(false, b"31415926535897932384626433832795028", 0, 1),
+//! );
+//!
+//! let preparsed = PreParsed {
+//!     sign: false,
+//!     int_digits: b"0",
+//!     frac_digits: b"1",
+//!     exp: 0,
+//! };
+//! let bits = binary128::from_preparsed(preparsed).unwrap();
+//! assert_eq!(bits, 0x3FFB_9999_9999_9999_9999_9999_9999_999A);
+//! // The exact value is 0.1000000000000000000000000000000000048148...
+//! let mut buf = [0; 36];
+//! assert_eq!(
+//!     binary128::preformat_exact_exp(bits, &mut buf, 36),
+//!     PreFormatted::Finite(false, b"100000000000000000000000000000000005", 0, 0),
+//! );
+//! ```
+
+use crate::wide::{self, Decoded, FullDecoded, Rounded};
+use crate::{ParseFloatError, PreFormatted, PreParsed};
+
+/// Minimum buffer size that has to be passed to `preformat_shortest`.
+pub const SHORTEST_BUF_LEN: usize = 36;
+
+/// Minimum base buffer size that has to be passed to
+/// `preformat_exact_fixed` (see `PREFORMAT_EXACT_FIXED_BASE_BUF_LEN`).
+// `wide::estimate_max_buf_len` of the smallest exponent
+// (-16495, for the smallest positive normal binary128 number).
+pub const EXACT_FIXED_BASE_BUF_LEN: usize = 12411;
+
+const_assert!(40 + ((12 * 16495) >> 4) == EXACT_FIXED_BASE_BUF_LEN as i32);
+
+/// The bit pattern of positive infinity.
+pub const INFINITY: u128 = 0x7FFF << 112;
+
+/// The bit pattern of negative infinity.
+pub const NEG_INFINITY: u128 = 0xFFFF << 112;
+
+/// The bit pattern of the default quiet NaN.
+pub const NAN: u128 = 0x7FFF8 << 108;
+
+const FRAC_MASK: u128 = (1 << 112) - 1;
+
+const FORMAT: wide::Format = wide::Format {
+    sig_bits: 113,
+    min_lsb_exp: -16494,
+    max_exp: 16383,
+};
+
+fn decode(bits: u128) -> (bool, FullDecoded) {
+    let sign = bits >> 127 != 0;
+    let biased_exp = ((bits >> 112) & 0x7FFF) as i32;
+    let frac = bits & FRAC_MASK;
+    let full_decoded = if biased_exp == 0x7FFF {
+        if frac == 0 {
+            FullDecoded::Infinite
+        } else {
+            FullDecoded::Nan
+        }
+    } else if biased_exp == 0 {
+        if frac == 0 {
+            FullDecoded::Zero
+        } else {
+            FullDecoded::Finite(Decoded::new(frac, FORMAT.min_lsb_exp, false))
+        }
+    } else {
+        let m = frac | 1 << 112;
+        let lower_closer = frac == 0 && biased_exp > 1;
+        FullDecoded::Finite(Decoded::new(m, biased_exp - 16495, lower_closer))
+    };
+    (sign, full_decoded)
+}
+
+/// Pre-formats the number with the lowest number of significant digits
+/// that round-trip, like `FloatExt::preformat_shortest`.
+///
+/// `buf` must be at least `SHORTEST_BUF_LEN` long.
+pub fn preformat_shortest(bits: u128, buf: &mut [u8]) -> PreFormatted<'_> {
+    let (sign, full_decoded) = decode(bits);
+    wide::preformat_shortest(sign, full_decoded, buf)
+}
+
+/// Pre-formats the number with an exact number of significant digits,
+/// like `FloatExt::preformat_exact_exp`.
+///
+/// `buf` must be at least `num_digits` long (or `EXACT_FIXED_BASE_BUF_LEN`,
+/// if that is smaller), and `num_digits` must not be zero.
+pub fn preformat_exact_exp(bits: u128, buf: &mut [u8], num_digits: usize) -> PreFormatted<'_> {
+    let (sign, full_decoded) = decode(bits);
+    wide::preformat_exact_exp(sign, full_decoded, buf, num_digits)
+}
+
+/// Pre-formats the number with an exact number of fractional digits, like
+/// `FloatExt::preformat_exact_fixed`.
+///
+/// `buf` must be at least `EXACT_FIXED_BASE_BUF_LEN` long, whatever
+/// `num_frac_digits` is.
+pub fn preformat_exact_fixed(
+    bits: u128,
+    buf: &mut [u8],
+    num_frac_digits: isize,
+) -> PreFormatted<'_> {
+    let (sign, full_decoded) = decode(bits);
+    wide::preformat_exact_fixed(sign, full_decoded, buf, num_frac_digits)
+}
+
+/// Pre-formats the number with the digits above `10^limit`, like
+/// `FloatExt::preformat_exact_limit`.
+///
+/// `buf` must be at least `EXACT_FIXED_BASE_BUF_LEN` long.
+pub fn preformat_exact_limit(bits: u128, buf: &mut [u8], limit: i16) -> PreFormatted<'_> {
+    let (sign, full_decoded) = decode(bits);
+    wide::preformat_exact_limit(sign, full_decoded, buf, limit)
+}
+
+/// Converts a pre-parsed decimal into the bit pattern of the nearest
+/// binary128 number, rounding half to even.
+///
+/// Any number of digits is accepted.
+pub fn from_preparsed(preparsed: PreParsed<'_>) -> Result<u128, ParseFloatError> {
+    let sign_bit = u128::from(preparsed.sign) << 127;
+    let bits = match wide::from_preparsed(preparsed, &FORMAT)? {
+        Rounded::Zero => 0,
+        Rounded::Finite(q, k) => {
+            if q >> 112 != 0 {
+                ((k + 16495) as u128) << 112 | (q & FRAC_MASK)
+            } else {
+                // Subnormal.
+                q
+            }
+        }
+        Rounded::Infinite => INFINITY,
+    };
+    Ok(sign_bit | bits)
+}
@@ -0,0 +1,168 @@
+//! Formatting and parsing of `f32` and `f64` numbers given by their IEEE
+//! 754 bit patterns.
+//!
+//! These functions give the same results as the `FloatExt` methods of the
+//! same name, but only use integer arithmetic, so on targets without a
+//! floating point unit they do not pull in the soft-float routines of
+//! the compiler.
+//!
+//! # Example
+//!
+//! ```
+//! use flt2dec2flt::{bits, PreFormatted, PreParsed};
+//!
+//! let mut buf = [0; flt2dec2flt::PREFORMAT_SHORTEST_BUF_LEN];
+//! assert_eq!(
+//!     bits::preformat_shortest_f64(0x3FB9_9999_9999_999A, &mut buf),
+//!     PreFormatted::Finite(false, b"1", 0, 0),
+//! );
+//!
+//! let preparsed = PreParsed {
+//!     sign: true,
+//!     int_digits: b"2",
+//!     frac_digits: b"5",
+//!     exp: 0,
+//! };
+//! assert_eq!(bits::from_preparsed_f32(preparsed), Ok(0xC020_0000));
+//! ```
+
+use crate::core_num::flt2dec::decoder::{Decoded, FullDecoded};
+use crate::sealed::Sealed;
+use crate::{generic, ParseFloatError, PreFormatted, PreParsed};
+
+/// Like `core_num::flt2dec::decoder::decode`, from the bits of a `T`.
+fn decode<T: Sealed>(bits: u64) -> (bool, FullDecoded) {
+    let frac_bits = T::SIG_BITS - 1;
+    let exp_bits = T::BITS - T::SIG_BITS;
+    let sign = (bits >> (T::BITS - 1)) & 1 != 0;
+    let biased_exp = (bits >> frac_bits) & ((1 << exp_bits) - 1);
+    let frac = bits & ((1 << frac_bits) - 1);
+    let full_decoded = if biased_exp == (1 << exp_bits) - 1 {
+        if frac == 0 {
+            FullDecoded::Infinite
+        } else {
+            FullDecoded::Nan
+        }
+    } else if biased_exp == 0 {
+        if frac == 0 {
+            FullDecoded::Zero
+        } else {
+            // The significand is scaled like in `RawFloat::integer_decode`.
+            FullDecoded::Finite(Decoded {
+                mant: frac << 1,
+                minus: 1,
+                plus: 1,
+                exp: (T::MIN_LSB_EXP - 1) as i16,
+                inclusive: true,
+            })
+        }
+    } else {
+        let mant = frac | 1 << frac_bits;
+        let exp = (biased_exp as i32 + T::MIN_LSB_EXP - 1) as i16;
+        let inclusive = mant & 1 == 0;
+        if frac == 0 {
+            FullDecoded::Finite(Decoded {
+                mant: mant << 2,
+                minus: 1,
+                plus: 2,
+                exp: exp - 2,
+                inclusive,
+            })
+        } else {
+            FullDecoded::Finite(Decoded {
+                mant: mant << 1,
+                minus: 1,
+                plus: 1,
+                exp: exp - 1,
+                inclusive,
+            })
+        }
+    };
+    (sign, full_decoded)
+}
+
+fn preformat_shortest<T: Sealed>(bits: u64, buf: &mut [u8]) -> PreFormatted<'_> {
+    let (sign, full_decoded) = decode::<T>(bits);
+    match full_decoded {
+        FullDecoded::Finite(ref decoded) => {
+            // The strategies only look at the bits of the value.
+            let v = T::from_bits_u64(bits);
+            let (digits, exp) = crate::strategy::format_shortest(v, decoded, buf);
+            PreFormatted::Finite(sign, digits, 0, exp)
+        }
+        _ => generic::preformat_shortest_decoded(sign, full_decoded, buf),
+    }
+}
+
+fn from_preparsed<T>(preparsed: PreParsed<'_>) -> Result<u64, ParseFloatError>
+where
+    T: Sealed + crate::core_num::dec2flt::float::RawFloat,
+{
+    let sign_bit = u64::from(preparsed.sign) << (<T as Sealed>::BITS - 1);
+    let v = generic::from_preparsed_abs::<T>(preparsed)?;
+    Ok(sign_bit | v.to_bits_u64())
+}
+
+/// Like `FloatExt::preformat_shortest`, for the `f32` with bits `bits`.
+pub fn preformat_shortest_f32(bits: u32, buf: &mut [u8]) -> PreFormatted<'_> {
+    preformat_shortest::<f32>(u64::from(bits), buf)
+}
+
+/// Like `FloatExt::preformat_shortest`, for the `f64` with bits `bits`.
+pub fn preformat_shortest_f64(bits: u64, buf: &mut [u8]) -> PreFormatted<'_> {
+    preformat_shortest::<f64>(bits, buf)
+}
+
+/// Like `FloatExt::preformat_exact_exp`, for the `f32` with bits `bits`.
+pub fn preformat_exact_exp_f32(bits: u32, buf: &mut [u8], num_digits: usize) -> PreFormatted<'_> {
+    let (sign, full_decoded) = decode::<f32>(u64::from(bits));
+    generic::preformat_exact_exp_decoded(sign, full_decoded, buf, num_digits)
+}
+
+/// Like `FloatExt::preformat_exact_exp`, for the `f64` with bits `bits`.
+pub fn preformat_exact_exp_f64(bits: u64, buf: &mut [u8], num_digits: usize) -> PreFormatted<'_> {
+    let (sign, full_decoded) = decode::<f64>(bits);
+    generic::preformat_exact_exp_decoded(sign, full_decoded, buf, num_digits)
+}
+
+/// Like `FloatExt::preformat_exact_fixed`, for the `f32` with bits `bits`.
+pub fn preformat_exact_fixed_f32(
+    bits: u32,
+    buf: &mut [u8],
+    num_frac_digits: isize,
+) -> PreFormatted<'_> {
+    let (sign, full_decoded) = decode::<f32>(u64::from(bits));
+    generic::preformat_exact_fixed_decoded(sign, full_decoded, buf, num_frac_digits)
+}
+
+/// Like `FloatExt::preformat_exact_fixed`, for the `f64` with bits `bits`.
+pub fn preformat_exact_fixed_f64(
+    bits: u64,
+    buf: &mut [u8],
+    num_frac_digits: isize,
+) -> PreFormatted<'_> {
+    let (sign, full_decoded) = decode::<f64>(bits);
+    generic::preformat_exact_fixed_decoded(sign, full_decoded, buf, num_frac_digits)
+}
+
+/// Like `FloatExt::preformat_exact_limit`, for the `f32` with bits `bits`.
+pub fn preformat_exact_limit_f32(bits: u32, buf: &mut [u8], limit: i16) -> PreFormatted<'_> {
+    let (sign, full_decoded) = decode::<f32>(u64::from(bits));
+    generic::preformat_exact_limit_decoded(sign, full_decoded, buf, limit)
+}
+
+/// Like `FloatExt::preformat_exact_limit`, for the `f64` with bits `bits`.
+pub fn preformat_exact_limit_f64(bits: u64, buf: &mut [u8], limit: i16) -> PreFormatted<'_> {
+    let (sign, full_decoded) = decode::<f64>(bits);
+    generic::preformat_exact_limit_decoded(sign, full_decoded, buf, limit)
+}
+
+/// Like `FloatExt::from_preparsed`, returning the bits of the `f32`.
+pub fn from_preparsed_f32(preparsed: PreParsed<'_>) -> Result<u32, ParseFloatError> {
+    from_preparsed::<f32>(preparsed).map(|bits| bits as u32)
+}
+
+/// Like `FloatExt::from_preparsed`, returning the bits of the `f64`.
+pub fn from_preparsed_f64(preparsed: PreParsed<'_>) -> Result<u64, ParseFloatError> {
+    from_preparsed::<f64>(preparsed)
+}
@@ -0,0 +1,119 @@
+//! Conversions between floating point numbers and CBOR decimal fractions
+//! (RFC 8949, tag 4).
+//!
+//! A decimal fraction is the array `[exponent, mantissa]`, representing
+//! `mantissa * 10^exponent`. This module only computes the two integers,
+//! encoding them is left to the CBOR library. Mantissas that do not fit in
+//! 64 bits have to be encoded as bignums (tags 2 and 3).
+//!
+//! Both directions report whether the conversion was exact.
+//!
+//! # Example
+//!
+//! ```
+//! use flt2dec2flt::cbor::{from_decimal_fraction, to_decimal_fraction, DecimalFraction};
+//!
+//! // 273.15 from the RFC
+//! let df = DecimalFraction {
+//!     exponent: -2,
+//!     mantissa: 27315,
+//! };
+//! assert_eq!(from_decimal_fraction::<f64>(df), (273.15, false));
+//!
+//! // The exact value of 273.15f64 needs 46 digits, so the shortest
+//! // representation is used
+//! assert_eq!(to_decimal_fraction(273.15f64), Some((df, false)));
+//!
+//! let df = DecimalFraction {
+//!     exponent: -3,
+//!     mantissa: -1125,
+//! };
+//! assert_eq!(to_decimal_fraction(-1.125f32), Some((df, true)));
+//! assert_eq!(from_decimal_fraction::<f32>(df), (-1.125, true));
+//! ```
+
+use crate::scaled::{exact_expansion, from_scaled, split_i128, write_u128};
+use crate::{FloatExt, PreFormatted};
+
+/// A CBOR decimal fraction, with value `mantissa * 10^exponent`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct DecimalFraction {
+    pub exponent: i64,
+    pub mantissa: i128,
+}
+
+impl DecimalFraction {
+    /// Builds a decimal fraction from `0.digits * 10^exp`.
+    fn from_digits(sign: bool, digits: &[u8], exp: i16) -> Self {
+        let magnitude = digits
+            .iter()
+            .fold(0i128, |acc, &c| acc * 10 + i128::from(c - b'0'));
+        DecimalFraction {
+            exponent: i64::from(exp) - digits.len() as i64,
+            mantissa: if sign { -magnitude } else { magnitude },
+        }
+    }
+}
+
+/// Converts `v` into a decimal fraction.
+///
+/// The boolean is `true` when the decimal fraction has exactly the value
+/// of `v`, which is the case when the exact decimal expansion of `v` has at
+/// most 38 significant digits. Otherwise, the shortest representation
+/// that round-trips is used.
+///
+/// Returns `None` if `v` is NaN or infinite. Negative zero becomes zero.
+pub fn to_decimal_fraction<T: FloatExt>(v: T) -> Option<(DecimalFraction, bool)> {
+    let mut buf = [0; crate::PREFORMAT_EXACT_FIXED_BASE_BUF_LEN];
+    match exact_expansion(v, &mut buf) {
+        PreFormatted::NaN | PreFormatted::Inf(_) => None,
+        PreFormatted::Zero(_) => Some((
+            DecimalFraction {
+                exponent: 0,
+                mantissa: 0,
+            },
+            true,
+        )),
+        PreFormatted::Finite(sign, digits, _, exp) if digits.len() <= 38 => {
+            Some((DecimalFraction::from_digits(sign, digits, exp), true))
+        }
+        PreFormatted::Finite(..) => {
+            let mut buf = [0; crate::PREFORMAT_SHORTEST_BUF_LEN];
+            match v.preformat_shortest(&mut buf) {
+                PreFormatted::Finite(sign, digits, _, exp) => {
+                    Some((DecimalFraction::from_digits(sign, digits, exp), false))
+                }
+                _ => unreachable!(),
+            }
+        }
+    }
+}
+
+/// Converts a decimal fraction into the nearest floating point number
+/// (rounding half to even).
+///
+/// The boolean is `true` when the result has exactly the value of `df`.
+/// Values too large to be represented become infinity and values too
+/// small become zero, which are never exact.
+pub fn from_decimal_fraction<T: FloatExt>(df: DecimalFraction) -> (T, bool) {
+    let (sign, magnitude) = split_i128(df.mantissa);
+    let v: T = from_scaled(sign, magnitude, df.exponent.saturating_neg());
+    if magnitude == 0 {
+        return (v, true);
+    }
+
+    let mut digits_buf = [0; 39];
+    let digits = write_u128(&mut digits_buf, magnitude);
+    // 0.digits * 10^exp
+    let exp = df.exponent.saturating_add(digits.len() as i64);
+    let len = digits.iter().rposition(|&c| c != b'0').map_or(0, |i| i + 1);
+
+    let mut buf = [0; crate::PREFORMAT_EXACT_FIXED_BASE_BUF_LEN];
+    let exact = match exact_expansion(v, &mut buf) {
+        PreFormatted::Finite(_, v_digits, _, v_exp) => {
+            v_digits == &digits[..len] && i64::from(v_exp) == exp
+        }
+        _ => false,
+    };
+    (v, exact)
+}
@@ -0,0 +1,64 @@
+use core::cmp::Ordering;
+
+use crate::generic::abs;
+use crate::interval::exact_digits;
+use crate::{FloatExt, ParseFloatError, PreParsed, PREFORMAT_EXACT_FIXED_BASE_BUF_LEN};
+
+/// A number parsed by `FloatExt::from_preparsed_clamped`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Clamped<T> {
+    /// The parsed number, clamped to the range.
+    pub value: T,
+    /// Whether the rounded number was out of the range and has been
+    /// replaced by one of its bounds.
+    pub clamped: bool,
+    /// Whether the exact value of the text is out of the range, even if
+    /// it rounds to one of its bounds.
+    pub out_of_range: bool,
+}
+
+/// Implementation of `FloatExt::from_preparsed_clamped`.
+pub(crate) fn from_preparsed_clamped<T: FloatExt + PartialOrd>(
+    preparsed: PreParsed<'_>,
+    min: T,
+    max: T,
+) -> Result<Clamped<T>, ParseFloatError> {
+    assert!(min <= max, "invalid range");
+    let value = T::from_preparsed(preparsed)?;
+    let (value, clamped) = if value < min {
+        (min, true)
+    } else if value > max {
+        (max, true)
+    } else {
+        (value, false)
+    };
+    let out_of_range = cmp_exact(&preparsed, min) == Some(Ordering::Less)
+        || cmp_exact(&preparsed, max) == Some(Ordering::Greater);
+    Ok(Clamped {
+        value,
+        clamped,
+        out_of_range,
+    })
+}
+
+/// Compares `preparsed` with the exact value of `v`, or returns `None` if
+/// `v` is NaN or infinite.
+pub(crate) fn cmp_exact<T: FloatExt>(preparsed: &PreParsed<'_>, v: T) -> Option<Ordering> {
+    let v = v.to_f64();
+    if !v.is_finite() {
+        return None;
+    }
+    let mut buf = [0; PREFORMAT_EXACT_FIXED_BASE_BUF_LEN];
+    let (len, exp) = if v == 0.0 {
+        (0, 0)
+    } else {
+        exact_digits(abs(v), &mut buf)
+    };
+    let v = PreParsed {
+        sign: v.is_sign_negative(),
+        int_digits: b"",
+        frac_digits: &buf[..len],
+        exp,
+    };
+    Some(preparsed.cmp_exact(&v))
+}
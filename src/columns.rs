@@ -0,0 +1,154 @@
+//! Columns of numbers with aligned decimal points.
+//!
+//! A `Column` chooses a number of fractional digits shared by a set of
+//! values (the fewest that shows all of them exactly as with
+//! `FloatExt::preformat_shortest`, up to a maximum) and the width of the
+//! widest integer part. Each value is then written with that number of
+//! fractional digits, and left-padded by `Column::padding` to align the
+//! decimal points.
+//!
+//! # Example
+//!
+//! ```
+//! use flt2dec2flt::columns::Column;
+//! use flt2dec2flt::write::WriteOptions;
+//!
+//! let values = [1.5, -20.0, 0.125, 300.0];
+//! let column = Column::new(&values, 2, &WriteOptions::DEFAULT);
+//! assert_eq!(column, Column { frac_digits: 2, int_width: 3 });
+//!
+//! let mut table = String::new();
+//! let mut buf = [0; 32];
+//! for &v in values.iter() {
+//!     let cell = column.write(&mut buf, v, &WriteOptions::DEFAULT).unwrap();
+//!     for _ in 0..column.padding(&cell) {
+//!         table.push(' ');
+//!     }
+//!     table.push_str(cell.text);
+//!     table.push('\n');
+//! }
+//! assert_eq!(table, "  1.50\n-20.00\n  0.12\n300.00\n");
+//! ```
+
+use crate::write::{positional, CharCount, Cursor, WriteOptions};
+use crate::{BufferTooSmall, FloatExt, PreFormatted};
+use crate::{PREFORMAT_EXACT_FIXED_BASE_BUF_LEN, PREFORMAT_SHORTEST_BUF_LEN};
+
+/// The shared layout of a column of numbers.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Column {
+    /// Number of fractional digits of every value.
+    pub frac_digits: usize,
+    /// Width (in `char`s) of the widest integer part, including the sign.
+    pub int_width: usize,
+}
+
+/// A value written by `Column::write`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Cell<'a> {
+    /// The written value.
+    pub text: &'a str,
+    /// Width (in `char`s) of the integer part, including the sign.
+    ///
+    /// NaN and infinities do not have a fractional part, their integer
+    /// part is the whole text.
+    pub int_width: usize,
+}
+
+impl Column {
+    /// Chooses the layout of `values` when written with `options`.
+    ///
+    /// The number of fractional digits is the smallest that shows every
+    /// value exactly as `FloatExt::preformat_shortest`, but no more than
+    /// `max_frac_digits`, in which case the values with more digits are
+    /// rounded.
+    pub fn new<T: FloatExt>(
+        values: &[T],
+        max_frac_digits: usize,
+        options: &WriteOptions<'_>,
+    ) -> Self {
+        let mut buf = [0; PREFORMAT_SHORTEST_BUF_LEN];
+        let frac_digits = values
+            .iter()
+            .map(|&v| match v.preformat_shortest(&mut buf) {
+                PreFormatted::Finite(_, digits, _, exp) => {
+                    (digits.len() as isize - isize::from(exp)).max(0) as usize
+                }
+                _ => 0,
+            })
+            .fold(0, usize::max)
+            .min(max_frac_digits);
+
+        let mut column = Column {
+            frac_digits,
+            int_width: 0,
+        };
+        let mut buf = [0; PREFORMAT_EXACT_FIXED_BASE_BUF_LEN];
+        for &v in values {
+            let preformatted = preformat(v, &mut buf, frac_digits);
+            let mut count = CharCount(0);
+            positional(&mut count, preformatted, frac_digits, options);
+            let int_width = column.int_width_of(&preformatted, count.0, options);
+            column.int_width = column.int_width.max(int_width);
+        }
+        column
+    }
+
+    /// Writes `v` with the fractional digits of the column.
+    ///
+    /// Since the values are written at their shortest representation when
+    /// possible, `v` should be one of the values given to `Column::new`.
+    /// Otherwise it might be rounded or have a wider integer part.
+    pub fn write<'a, T: FloatExt>(
+        &self,
+        buf: &'a mut [u8],
+        v: T,
+        options: &WriteOptions<'_>,
+    ) -> Result<Cell<'a>, BufferTooSmall> {
+        let mut digits_buf = [0; PREFORMAT_EXACT_FIXED_BASE_BUF_LEN];
+        let preformatted = preformat(v, &mut digits_buf, self.frac_digits);
+        let mut cursor = Cursor::new(buf);
+        positional(&mut cursor, preformatted, self.frac_digits, options);
+        let text = cursor.finish()?;
+        let int_width = self.int_width_of(&preformatted, text.chars().count(), options);
+        Ok(Cell { text, int_width })
+    }
+
+    /// Returns the number of spaces to write before `cell` to align its
+    /// decimal point with the rest of the column.
+    pub fn padding(&self, cell: &Cell<'_>) -> usize {
+        self.int_width.saturating_sub(cell.int_width)
+    }
+
+    /// Width of the integer part of `preformatted`, written with `len`
+    /// `char`s.
+    fn int_width_of(
+        &self,
+        preformatted: &PreFormatted<'_>,
+        len: usize,
+        options: &WriteOptions<'_>,
+    ) -> usize {
+        match *preformatted {
+            PreFormatted::Zero(_) | PreFormatted::Finite(..) if self.frac_digits != 0 => {
+                len - options.decimal_point.chars().count() - self.frac_digits
+            }
+            _ => len,
+        }
+    }
+}
+
+/// Pre-formats `v` at its shortest representation if it does not have
+/// more than `frac_digits` fractional digits, or rounded otherwise.
+fn preformat<T: FloatExt>(v: T, buf: &mut [u8], frac_digits: usize) -> PreFormatted<'_> {
+    let mut shortest_buf = [0; PREFORMAT_SHORTEST_BUF_LEN];
+    match v.preformat_shortest(&mut shortest_buf) {
+        PreFormatted::Finite(sign, digits, _, exp)
+            if digits.len() as isize - isize::from(exp)
+                <= frac_digits.min(isize::MAX as usize) as isize =>
+        {
+            buf[..digits.len()].copy_from_slice(digits);
+            PreFormatted::Finite(sign, &buf[..digits.len()], 0, exp)
+        }
+        _ => v.preformat_exact_fixed(buf, frac_digits.min(isize::MAX as usize) as isize),
+    }
+}
@@ -0,0 +1,157 @@
+//! Compact notation of large numbers, such as `1.2K` or `3.4M`.
+//!
+//! A number is divided by the power of ten of the largest suffix that is
+//! not greater than it, and written with a bounded number of fractional
+//! digits. The rounding is done on the exact digits of the number, before
+//! the suffix is chosen for good, so `999_950` becomes `1.0M` (and not
+//! `1000.0K`) and `999_949` becomes `999.9K`.
+//!
+//! The suffixes are a table given by the caller. `SHORT_SCALE` has the
+//! usual ones of the short scale (thousand, million, billion, trillion).
+//!
+//! # Example
+//!
+//! ```
+//! use flt2dec2flt::compact::{write_compact, CompactFormat};
+//! use flt2dec2flt::write::WriteOptions;
+//!
+//! let format = CompactFormat::SHORT_SCALE;
+//! let options = WriteOptions::DEFAULT;
+//! let mut buf = [0; 32];
+//! assert_eq!(write_compact(&mut buf, 999.0, &format, &options), Ok("999"));
+//! assert_eq!(write_compact(&mut buf, 1234.0, &format, &options), Ok("1.2K"));
+//! assert_eq!(write_compact(&mut buf, -3.45e6, &format, &options), Ok("-3.4M"));
+//! assert_eq!(write_compact(&mut buf, 5.6e9, &format, &options), Ok("5.6B"));
+//! assert_eq!(write_compact(&mut buf, 2e6, &format, &options), Ok("2M"));
+//!
+//! let format = CompactFormat {
+//!     min_frac_digits: 1,
+//!     ..CompactFormat::SHORT_SCALE
+//! };
+//! assert_eq!(write_compact(&mut buf, 999_949.0, &format, &options), Ok("999.9K"));
+//! assert_eq!(write_compact(&mut buf, 999_950.0, &format, &options), Ok("1.0M"));
+//! ```
+
+use crate::rounding::round_digits;
+use crate::write::{positional, Cursor, Output, WriteOptions};
+use crate::{BufferTooSmall, FloatExt, PreFormatted, RoundingMode};
+
+/// A suffix of compact notation.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Suffix<'a> {
+    /// The number is divided by `10^exp` when written with this suffix.
+    pub exp: i16,
+    /// Written after the number, such as `K` or ` thousand`.
+    pub text: &'a str,
+}
+
+/// `K` for thousands, `M` for millions, `B` for billions and `T` for
+/// trillions.
+pub const SHORT_SCALE: [Suffix<'static>; 4] = [
+    Suffix { exp: 3, text: "K" },
+    Suffix { exp: 6, text: "M" },
+    Suffix { exp: 9, text: "B" },
+    Suffix { exp: 12, text: "T" },
+];
+
+/// How numbers are written in compact notation.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CompactFormat<'a> {
+    /// The available suffixes, in any order.
+    pub suffixes: &'a [Suffix<'a>],
+    /// Numbers (after rounding) whose magnitude is below `10^min_exp` are
+    /// written without suffix, such as `9999` rather than `10.0K` with `4`.
+    pub min_exp: i16,
+    /// Minimum number of fractional digits, padding with zeros.
+    pub min_frac_digits: usize,
+    /// Maximum number of fractional digits, rounding the rest.
+    pub max_frac_digits: usize,
+    /// How the number is rounded to `max_frac_digits`.
+    pub rounding: RoundingMode,
+}
+
+impl CompactFormat<'static> {
+    /// The `SHORT_SCALE` suffixes from thousands, with at most one
+    /// fractional digit (such as `1.2K` or `3M`), rounding to nearest with
+    /// ties to even.
+    pub const SHORT_SCALE: Self = Self {
+        suffixes: &SHORT_SCALE,
+        min_exp: 3,
+        min_frac_digits: 0,
+        max_frac_digits: 1,
+        rounding: RoundingMode::NearestEven,
+    };
+}
+
+impl<'a> CompactFormat<'a> {
+    /// Returns the suffix of a number of magnitude `10^magnitude`.
+    fn suffix(&self, magnitude: i32) -> Option<&Suffix<'a>> {
+        if magnitude < i32::from(self.min_exp) {
+            return None;
+        }
+        self.suffixes
+            .iter()
+            .filter(|suffix| i32::from(suffix.exp) <= magnitude)
+            .max_by_key(|suffix| suffix.exp)
+    }
+}
+
+/// Writes `v` in compact notation.
+///
+/// Small numbers, NaN and infinities are written as in
+/// `write::write_positional`, without suffix.
+pub fn write_compact<'a, T: FloatExt>(
+    buf: &'a mut [u8],
+    v: T,
+    format: &CompactFormat<'_>,
+    options: &WriteOptions<'_>,
+) -> Result<&'a str, BufferTooSmall> {
+    let mut digits_buf = [0; crate::PREFORMAT_EXACT_FIXED_BASE_BUF_LEN];
+    let mut cursor = Cursor::new(buf);
+    let len = digits_buf.len();
+    let (sign, len, exp) = match v.preformat_exact_exp(&mut digits_buf, len) {
+        PreFormatted::Finite(sign, digits, _, exp) => {
+            let len = digits.iter().rposition(|&c| c != b'0').map_or(0, |i| i + 1);
+            (sign, len, i32::from(exp))
+        }
+        preformatted => {
+            positional(&mut cursor, preformatted, format.min_frac_digits, options);
+            return cursor.finish();
+        }
+    };
+
+    // Round at the position of the last fractional digit with the suffix
+    // of the exact value. If that carries into a new leading digit, the
+    // result is a power of ten, which stays the same when rounded at the
+    // coarser position of a larger suffix.
+    let suffix_exp = |suffix: Option<&Suffix<'_>>| suffix.map_or(0, |s| i32::from(s.exp));
+    let max_frac_digits = format.max_frac_digits.min(digits_buf.len()) as i32;
+    let keep = exp - suffix_exp(format.suffix(exp - 1)) + max_frac_digits;
+    let (len, exp) = round_digits(&mut digits_buf[..len], exp, keep, sign, format.rounding);
+    let len = digits_buf[..len]
+        .iter()
+        .rposition(|&c| c != b'0')
+        .map_or(0, |i| i + 1);
+    if len == 0 {
+        positional(
+            &mut cursor,
+            PreFormatted::Zero(sign),
+            format.min_frac_digits,
+            options,
+        );
+        return cursor.finish();
+    }
+
+    let suffix = format.suffix(exp - 1);
+    let preformatted = PreFormatted::Finite(
+        sign,
+        &digits_buf[..len],
+        0,
+        (exp - suffix_exp(suffix)) as i16,
+    );
+    positional(&mut cursor, preformatted, format.min_frac_digits, options);
+    if let Some(suffix) = suffix {
+        cursor.push_str(suffix.text);
+    }
+    cursor.finish()
+}
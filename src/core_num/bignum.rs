@@ -37,6 +37,33 @@ pub trait FullOps: Sized {
 macro_rules! impl_full_ops {
     ($($ty:ty: add($addfn:path), mul/div($bigty:ident);)*) => (
         $(
+            #[cfg(flt2dec2flt_bigint_helpers)]
+            impl FullOps for $ty {
+                fn full_add(self, other: $ty, carry: bool) -> (bool, $ty) {
+                    let (v, carry) = self.carrying_add(other, carry);
+                    (carry, v)
+                }
+
+                fn full_mul(self, other: $ty, carry: $ty) -> ($ty, $ty) {
+                    let (lo, hi) = self.carrying_mul(other, carry);
+                    (hi, lo)
+                }
+
+                fn full_mul_add(self, other: $ty, other2: $ty, carry: $ty) -> ($ty, $ty) {
+                    let (lo, hi) = self.carrying_mul_add(other, other2, carry);
+                    (hi, lo)
+                }
+
+                fn full_div_rem(self, other: $ty, borrow: $ty) -> ($ty, $ty) {
+                    debug_assert!(borrow < other);
+                    // This cannot overflow; the output is between `0` and `other * (2^nbits - 1)`.
+                    let lhs = ((borrow as $bigty) << (core::mem::size_of::<$ty>() * 8)) | (self as $bigty);
+                    let rhs = other as $bigty;
+                    ((lhs / rhs) as $ty, (lhs % rhs) as $ty)
+                }
+            }
+
+            #[cfg(not(flt2dec2flt_bigint_helpers))]
             impl FullOps for $ty {
                 fn full_add(self, other: $ty, carry: bool) -> (bool, $ty) {
                     // This cannot overflow; the output is between `0` and `2 * 2^nbits - 1`.
@@ -161,14 +188,9 @@ macro_rules! define_bignum {
                     // There are no non-zero digits, i.e., the number is zero.
                     return 0;
                 }
-                // This could be optimized with leading_zeros() and bit shifts, but that's
-                // probably not worth the hassle.
                 let digitbits = core::mem::size_of::<$ty>() * 8;
-                let mut i = nonzero.len() * digitbits - 1;
-                while self.get_bit(i) == 0 {
-                    i -= 1;
-                }
-                i + 1
+                let msd = end - 1;
+                (msd + 1) * digitbits - nonzero[msd].leading_zeros() as usize
             }
 
             /// Adds `other` to itself and returns its own mutable reference.
@@ -458,3 +480,21 @@ macro_rules! define_bignum {
 pub type Digit32 = u32;
 
 define_bignum!(Big32x40: type=Digit32, n=40);
+
+// 32 × 530 = 16,960 bits, for the formats with 15-bit exponents (see
+// `crate::wide`, which does not need `mul_digits` and `div_rem`).
+#[allow(dead_code)]
+mod wide {
+    use super::Digit32;
+    define_bignum!(Big32x530: type=Digit32, n=530);
+}
+pub use self::wide::Big32x530;
+
+// 32 × 80 = 2,560 bits, for the exact value of any `f64` as an integer
+// number of units in its last decimal place (see `crate::sink`).
+#[allow(dead_code)]
+mod exact {
+    use super::Digit32;
+    define_bignum!(Big32x80: type=Digit32, n=80);
+}
+pub use self::exact::Big32x80;
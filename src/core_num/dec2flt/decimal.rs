@@ -0,0 +1,76 @@
+//! Representation of a float as the significant digits and exponent.
+
+use crate::core_num::dec2flt::common::BiasedFp;
+use crate::core_num::dec2flt::float::RawFloat;
+
+const INT_POW10: [u64; 16] = [
+    1,
+    10,
+    100,
+    1000,
+    10000,
+    100000,
+    1000000,
+    10000000,
+    100000000,
+    1000000000,
+    10000000000,
+    100000000000,
+    1000000000000,
+    10000000000000,
+    100000000000000,
+    1000000000000000,
+];
+
+/// A floating point number with up to 64 bits of mantissa and an `i64` exponent.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Decimal {
+    pub exponent: i64,
+    pub mantissa: u64,
+    pub negative: bool,
+    pub many_digits: bool,
+}
+
+impl Decimal {
+    /// Try turning the decimal into an exact float representation, using
+    /// machine-sized integers only.
+    ///
+    /// This is extracted into a separate function so that it can be attempted before constructing
+    /// a Decimal. This only works if the decimal is an integer that can be
+    /// exactly represented as a machine float, in which case its bits are
+    /// assembled directly.
+    ///
+    /// Upstream multiplies or divides native floats here (Clinger's fast
+    /// path), whose result depends on the FPU: x87 extended precision rounds
+    /// twice and flush-to-zero modes alter subnormals. Every other case is
+    /// left to the Eisel-Lemire algorithm, which only uses integers too.
+    ///
+    /// This includes disguised fast-path cases, where powers-of-10 are
+    /// shifted from the exponent to the significant digits.
+    pub fn try_fast_path<F: RawFloat>(&self) -> Option<BiasedFp> {
+        if self.many_digits || self.exponent < 0 || self.exponent >= INT_POW10.len() as i64 {
+            return None;
+        }
+        let value = self.mantissa.checked_mul(INT_POW10[self.exponent as usize])?;
+        if value == 0 {
+            return Some(BiasedFp::zero_pow2(0));
+        }
+        if value > F::MAX_MANTISSA_FAST_PATH {
+            return None;
+        }
+
+        // Position of the most significant bit, at most `SIG_BITS + 1`
+        // (only when `value` is exactly `MAX_MANTISSA_FAST_PATH`, whose
+        // shifted-out bits are zero).
+        let msb = 63 - value.leading_zeros();
+        let m = if msb <= F::SIG_BITS {
+            value << (F::SIG_BITS - msb)
+        } else {
+            value >> (msb - F::SIG_BITS)
+        };
+        Some(BiasedFp {
+            m: m & !(1_u64 << F::SIG_BITS),
+            p_biased: (msb + F::EXP_BIAS) as i32,
+        })
+    }
+}
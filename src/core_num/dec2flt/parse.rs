@@ -1,27 +1,109 @@
-//! Validating and decomposing a decimal string of the form:
-//!
-//! `(digits | digits? '.'? digits?) (('e' | 'E') ('+' | '-')? digits)?`
-//!
-//! In other words, standard floating-point syntax, with two exceptions: No sign, and no
-//! handling of "inf" and "NaN". These are handled by the driver function (super::dec2flt).
-//!
-//! Although recognizing valid inputs is relatively easy, this module also has to reject the
-//! countless invalid variations, never panic, and perform numerous checks that the other
-//! modules rely on to not panic (or overflow) in turn.
-//! To make matters worse, all that happens in a single pass over the input.
-//! So, be careful when modifying anything, and double-check with the other modules.
-
-#[derive(Debug, PartialEq, Eq)]
-/// The interesting parts of a decimal string.
-pub struct Decimal<'a> {
-    pub integral: &'a [u8],
-    pub fractional: &'a [u8],
-    /// The decimal exponent, guaranteed to have fewer than 18 decimal digits.
-    pub exp: i64,
+//! Functions to parse floating-point numbers.
+
+use crate::core_num::dec2flt::common::{is_8digits, ByteSlice};
+use crate::core_num::dec2flt::decimal::Decimal;
+
+const MIN_19DIGIT_INT: u64 = 100_0000_0000_0000_0000;
+
+/// Parse 8 digits, loaded as bytes in little-endian order.
+///
+/// This uses the trick where every digit is in [0x030, 0x39],
+/// and therefore can be parsed in 3 multiplications, much
+/// faster than the normal 8.
+///
+/// This is based off the algorithm described in "Fast numeric string to
+/// int", available here: <https://johnnylee-sde.github.io/Fast-numeric-string-to-int/>.
+fn parse_8digits(mut v: u64) -> u64 {
+    const MASK: u64 = 0x0000_00FF_0000_00FF;
+    const MUL1: u64 = 0x000F_4240_0000_0064;
+    const MUL2: u64 = 0x0000_2710_0000_0001;
+    v -= 0x3030_3030_3030_3030;
+    v = (v * 10) + (v >> 8); // will not overflow, fits in 63 bits
+    let v1 = (v & MASK).wrapping_mul(MUL1);
+    let v2 = ((v >> 16) & MASK).wrapping_mul(MUL2);
+    ((v1.wrapping_add(v2) >> 32) as u32) as u64
 }
 
-impl<'a> Decimal<'a> {
-    pub fn new(integral: &'a [u8], fractional: &'a [u8], exp: i64) -> Decimal<'a> {
-        Decimal { integral, fractional, exp }
+/// Parse digits until a non-digit character is found.
+fn try_parse_digits(mut s: &[u8], mut x: u64) -> (&[u8], u64) {
+    // may cause overflows, to be handled later
+
+    while s.len() >= 8 {
+        let num = s.read_u64();
+        if is_8digits(num) {
+            x = x.wrapping_mul(1_0000_0000).wrapping_add(parse_8digits(num));
+            s = &s[8..];
+        } else {
+            break;
+        }
     }
+
+    s = s.parse_digits(|digit| {
+        x = x.wrapping_mul(10).wrapping_add(digit as _);
+    });
+
+    (s, x)
+}
+
+/// Parse up to 19 digits (the max that can be stored in a 64-bit integer).
+fn try_parse_19digits(s_ref: &mut &[u8], x: &mut u64) {
+    let mut s = *s_ref;
+
+    while *x < MIN_19DIGIT_INT {
+        if let Some((c, s_next)) = s.split_first() {
+            let digit = c.wrapping_sub(b'0');
+
+            if digit < 10 {
+                *x = (*x * 10) + digit as u64; // no overflows here
+                s = s_next;
+            } else {
+                break;
+            }
+        } else {
+            break;
+        }
+    }
+
+    *s_ref = s;
+}
+
+/// Creates a representation of the float as the significant digits and
+/// the decimal exponent from the integral and fractional digits and the
+/// exponent of a number, such as `12`, `34` and `56` for `12.34e56`.
+///
+/// `integral` and `fractional` must only contain ASCII digits.
+pub fn parse_number(integral: &[u8], fractional: &[u8], exp: i64) -> Decimal {
+    // parse all digits, ignoring the dot
+    let (_, mantissa) = try_parse_digits(integral, 0);
+    let (_, mut mantissa) = try_parse_digits(fractional, mantissa);
+    let mut n_digits = integral.len() + fractional.len();
+    let mut exponent = exp.saturating_sub(fractional.len() as i64);
+
+    // handle uncommon case with many digits
+    if n_digits <= 19 {
+        return Decimal { exponent, mantissa, negative: false, many_digits: false };
+    }
+
+    n_digits -= 19;
+    n_digits -= integral.iter().chain(fractional).take_while(|&&c| c == b'0').count().min(n_digits);
+    let mut many_digits = false;
+    if n_digits > 0 {
+        // at this point we have more than 19 significant digits, let's try again
+        many_digits = true;
+        mantissa = 0;
+        let mut s = integral;
+        try_parse_19digits(&mut s, &mut mantissa);
+        exponent = if mantissa >= MIN_19DIGIT_INT {
+            // big int
+            s.len() as i64
+        } else {
+            let mut s = fractional;
+            try_parse_19digits(&mut s, &mut mantissa);
+            -((fractional.len() - s.len()) as i64)
+        };
+        // add back the explicit part
+        exponent = exponent.saturating_add(exp);
+    }
+
+    Decimal { exponent, mantissa, negative: false, many_digits }
 }
@@ -1,6 +1,6 @@
 //! Decodes a floating-point value into individual parts and error ranges.
 
-use crate::core_num::dec2flt::rawfp::RawFloat;
+use crate::core_num::dec2flt::float::RawFloat;
 use core::num::FpCategory;
 
 /// Decoded unsigned finite value, such that:
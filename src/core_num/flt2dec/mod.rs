@@ -85,6 +85,12 @@ They try to fill the `u8` buffer with digits and returns the number of digits
 written and the exponent `k`. They are total for all finite `f32` and `f64`
 inputs (Grisu internally falls back to Dragon if necessary).
 
+The algorithms themselves (`format_shortest_into` and `format_exact_into`)
+emit the digits into a `Sink`, of which the buffer (`SliceSink`) is just the
+default implementation. Digits are only appended, and only the last ones are
+modified afterwards (when rounding up), so other sinks can be added without
+forking the algorithms.
+
 The rendered digits are formatted into the actual string form with
 four functions:
 
@@ -117,6 +123,8 @@ functions.
 // only made public for testing. do not expose us.
 #![doc(hidden)]
 
+// `decode` and `DecodableFloat` are kept for upstream diffability.
+#[allow(unused_imports)]
 pub use self::decoder::{decode, DecodableFloat, Decoded, FullDecoded};
 
 pub mod decoder;
@@ -163,6 +171,115 @@ pub fn round_up(d: &mut [u8]) -> Option<u8> {
     }
 }
 
+/// A destination for the digits generated by the strategies.
+///
+/// Digits are ASCII (`b'0'` to `b'9'`) and appended one by one. Once
+/// appended, they are only modified by `set_last` and `round_up`, and all of
+/// them are discarded by `clear` (when Grisu falls back to Dragon).
+///
+/// `SliceSink` writes the digits into a buffer. The public `DigitSink`s are
+/// driven through an adapter in `crate::sink`, which passes the digits on as
+/// soon as no rounding can change them, so it only supports Dragon (Grisu is
+/// the only user of `set_last` and `clear`, and `set_last` is only compiled
+/// when Grisu is the shortest mode strategy).
+pub trait Sink {
+    /// The maximum number of digits, which is like the length of the buffer.
+    fn capacity(&self) -> usize;
+
+    /// The number of digits appended so far.
+    fn len(&self) -> usize;
+
+    /// Appends a digit.
+    fn push(&mut self, d: u8);
+
+    /// Appends `n` zeroes.
+    fn zeros(&mut self, n: usize) {
+        for _ in 0..n {
+            self.push(b'0');
+        }
+    }
+
+    /// Returns the last digit, if any.
+    fn last(&self) -> Option<u8>;
+
+    /// Replaces the last digit, which must exist.
+    #[cfg(not(any(feature = "strategy-ryu", feature = "strategy-dragonbox")))]
+    fn set_last(&mut self, d: u8);
+
+    /// Increases the digits like `round_up`, without appending the returned
+    /// digit.
+    fn round_up(&mut self) -> Option<u8>;
+
+    /// Discards all digits.
+    fn clear(&mut self);
+}
+
+/// The default sink, which writes the digits into a buffer.
+pub struct SliceSink<'a> {
+    buf: &'a mut [u8],
+    len: usize,
+}
+
+impl<'a> SliceSink<'a> {
+    #[inline]
+    pub fn new(buf: &'a mut [u8]) -> Self {
+        SliceSink { buf, len: 0 }
+    }
+
+    /// Returns the digits appended so far.
+    #[inline]
+    pub fn into_digits(self) -> &'a [u8] {
+        &self.buf[..self.len]
+    }
+}
+
+impl Sink for SliceSink<'_> {
+    #[inline]
+    fn capacity(&self) -> usize {
+        self.buf.len()
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    fn push(&mut self, d: u8) {
+        self.buf[self.len] = d;
+        self.len += 1;
+    }
+
+    #[inline]
+    fn zeros(&mut self, n: usize) {
+        for c in &mut self.buf[self.len..self.len + n] {
+            *c = b'0';
+        }
+        self.len += n;
+    }
+
+    #[inline]
+    fn last(&self) -> Option<u8> {
+        self.buf[..self.len].last().copied()
+    }
+
+    #[cfg(not(any(feature = "strategy-ryu", feature = "strategy-dragonbox")))]
+    #[inline]
+    fn set_last(&mut self, d: u8) {
+        self.buf[self.len - 1] = d;
+    }
+
+    #[inline]
+    fn round_up(&mut self) -> Option<u8> {
+        round_up(&mut self.buf[..self.len])
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        self.len = 0;
+    }
+}
+
 /// Returns a rather crude approximation (upper bound) for the maximum buffer size
 /// calculated from the given decoded exponent.
 ///
@@ -181,8 +298,12 @@ pub fn round_up(d: &mut [u8]) -> Option<u8> {
 /// the number of digits requested is ridiculously large (say, 30,000 digits).
 /// The vast majority of buffer will be filled with zeroes, so we don't want to
 /// allocate all the buffer beforehand. Consequently, for any given arguments,
-/// 826 bytes of buffer should be sufficient for `f64`. Compare this with
-/// the actual number for the worst case: 770 bytes (when `exp = -1074`).
+/// 828 bytes of buffer should be sufficient for `f64` (the smallest `exp` is
+/// -1076, for the smallest normal number). Compare this with the actual number
+/// for the worst case: 770 bytes (when `exp = -1074`).
 pub(crate) fn estimate_max_buf_len(exp: i16) -> usize {
-    21 + ((if exp < 0 { -12 } else { 5 } * exp as i32) as usize >> 4)
+    21 + ((if exp < 0 { -12 } else { 5 } * exp as i32) >> 4) as usize
 }
+
+// The result fits in a 16-bit `usize` for any `exp`.
+const_assert!(21 + ((12 * 32768) >> 4) <= u16::MAX as i32);
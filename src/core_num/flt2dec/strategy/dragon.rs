@@ -9,22 +9,22 @@ use core::cmp::Ordering;
 use crate::core_num::bignum::Big32x40 as Big;
 use crate::core_num::bignum::Digit32 as Digit;
 use crate::core_num::flt2dec::estimator::estimate_scaling_factor;
-use crate::core_num::flt2dec::{round_up, Decoded, MAX_SIG_DIGITS};
+use crate::core_num::flt2dec::{Decoded, Sink, SliceSink, MAX_SIG_DIGITS};
 
-static POW10: [Digit; 10] =
+pub(crate) static POW10: [Digit; 10] =
     [1, 10, 100, 1000, 10000, 100000, 1000000, 10000000, 100000000, 1000000000];
-static TWOPOW10: [Digit; 10] =
+pub(crate) static TWOPOW10: [Digit; 10] =
     [2, 20, 200, 2000, 20000, 200000, 2000000, 20000000, 200000000, 2000000000];
 
 // precalculated arrays of `Digit`s for 10^(2^n)
-static POW10TO16: [Digit; 2] = [0x6fc10000, 0x2386f2];
-static POW10TO32: [Digit; 4] = [0, 0x85acef81, 0x2d6d415b, 0x4ee];
-static POW10TO64: [Digit; 7] = [0, 0, 0xbf6a1f01, 0x6e38ed64, 0xdaa797ed, 0xe93ff9f4, 0x184f03];
-static POW10TO128: [Digit; 14] = [
+pub(crate) static POW10TO16: [Digit; 2] = [0x6fc10000, 0x2386f2];
+pub(crate) static POW10TO32: [Digit; 4] = [0, 0x85acef81, 0x2d6d415b, 0x4ee];
+pub(crate) static POW10TO64: [Digit; 7] = [0, 0, 0xbf6a1f01, 0x6e38ed64, 0xdaa797ed, 0xe93ff9f4, 0x184f03];
+pub(crate) static POW10TO128: [Digit; 14] = [
     0, 0, 0, 0, 0x2e953e01, 0x3df9909, 0xf1538fd, 0x2374e42f, 0xd3cff5ec, 0xc404dc08, 0xbccdb0da,
     0xa6337f19, 0xe91f2603, 0x24e,
 ];
-static POW10TO256: [Digit; 27] = [
+pub(crate) static POW10TO256: [Digit; 27] = [
     0, 0, 0, 0, 0, 0, 0, 0, 0x982e7c01, 0xbed3875b, 0xd8d99f72, 0x12152f87, 0x6bde50c6, 0xcf4a6e70,
     0xd595d80f, 0x26b2716e, 0xadc666b0, 0x1d153624, 0x3c42d35a, 0x63ff540e, 0xcc5573c0, 0x65f9ef17,
     0x55bc28f2, 0x80dcc7f7, 0xf46eeddc, 0x5fdcefce, 0x553f7,
@@ -101,6 +101,13 @@ pub fn format_shortest<'a>(
     d: &Decoded,
     buf: &'a mut [u8],
 ) -> (/*digits*/ &'a [u8], /*exp*/ i16) {
+    let mut sink = SliceSink::new(buf);
+    let exp = format_shortest_into(d, &mut sink);
+    (sink.into_digits(), exp)
+}
+
+/// The shortest mode implementation for Dragon, emitting into `sink` (which must be empty).
+pub fn format_shortest_into<S: Sink + ?Sized>(d: &Decoded, sink: &mut S) -> /*exp*/ i16 {
     // the number `v` to format is known to be:
     // - equal to `mant * 2^exp`;
     // - preceded by `(mant - 2 * minus) * 2^exp` in the original type; and
@@ -118,7 +125,7 @@ pub fn format_shortest<'a>(
     assert!(d.plus > 0);
     assert!(d.mant.checked_add(d.plus).is_some());
     assert!(d.mant.checked_sub(d.minus).is_some());
-    assert!(buf.len() >= MAX_SIG_DIGITS);
+    assert!(sink.capacity() >= MAX_SIG_DIGITS);
 
     // `a.cmp(&b) < rounding` is `if d.inclusive {a <= b} else {a < b}`
     let rounding = if d.inclusive { Ordering::Greater } else { Ordering::Equal };
@@ -177,7 +184,6 @@ pub fn format_shortest<'a>(
 
     let mut down;
     let mut up;
-    let mut i = 0;
     loop {
         // invariants, where `d[0..n-1]` are digits generated so far:
         // - `v = mant / scale * 10^(k-n-1) + d[0..n-1] * 10^(k-n)`
@@ -189,8 +195,7 @@ pub fn format_shortest<'a>(
         // generate one digit: `d[n] = floor(mant / scale) < 10`.
         let (d, _) = div_rem_upto_16(&mut mant, &scale, &scale2, &scale4, &scale8);
         debug_assert!(d < 10);
-        buf[i] = b'0' + d;
-        i += 1;
+        sink.push(b'0' + d);
 
         // this is a simplified description of the modified Dragon algorithm.
         // many intermediate derivations and completeness arguments are omitted for convenience.
@@ -244,22 +249,18 @@ pub fn format_shortest<'a>(
         // if rounding up changes the length, the exponent should also change.
         // it seems that this condition is very hard to satisfy (possibly impossible),
         // but we are just being safe and consistent here.
-        if let Some(c) = round_up(&mut buf[..i]) {
-            buf[i] = c;
-            i += 1;
+        if let Some(c) = sink.round_up() {
+            sink.push(c);
             k += 1;
         }
     }
 
-    (&buf[..i], k)
+    k
 }
 
-/// The exact and fixed mode implementation for Dragon.
-pub fn format_exact<'a>(
-    d: &Decoded,
-    buf: &'a mut [u8],
-    limit: i16,
-) -> (/*digits*/ &'a [u8], /*exp*/ i16) {
+/// The exact and fixed mode implementation for Dragon, emitting into `sink` (which must be
+/// empty).
+pub fn format_exact_into<S: Sink + ?Sized>(d: &Decoded, sink: &mut S, limit: i16) -> /*exp*/ i16 {
     assert!(d.mant > 0);
     assert!(d.minus > 0);
     assert!(d.plus > 0);
@@ -285,11 +286,11 @@ pub fn format_exact<'a>(
         mul_pow10(&mut mant, -k as usize);
     }
 
-    // fixup when `mant + plus >= scale`, where `plus / scale = 10^-buf.len() / 2`.
+    // fixup when `mant + plus >= scale`, where `plus / scale = 10^-sink.capacity() / 2`.
     // in order to keep the fixed-size bignum, we actually use `mant + floor(plus) >= scale`.
     // we are not actually modifying `scale`, since we can skip the initial multiplication instead.
     // again with the shortest algorithm, `d[0]` can be zero but will be eventually rounded up.
-    if *div_2pow10(&mut scale.clone(), buf.len()).add(&mant) >= scale {
+    if *div_2pow10(&mut scale.clone(), sink.capacity()).add(&mant) >= scale {
         // equivalent to scaling `scale` by 10
         k += 1;
     } else {
@@ -299,16 +300,16 @@ pub fn format_exact<'a>(
     // if we are working with the last-digit limitation, we need to shorten the buffer
     // before the actual rendering in order to avoid double rounding.
     // note that we have to enlarge the buffer again when rounding up happens!
-    let mut len = if k < limit {
+    let len = if k < limit {
         // oops, we cannot even produce *one* digit.
         // this is possible when, say, we've got something like 9.5 and it's being rounded to 10.
         // we return an empty buffer, with an exception of the later rounding-up case
         // which occurs when `k == limit` and has to produce exactly one digit.
         0
-    } else if ((k as i32 - limit as i32) as usize) < buf.len() {
+    } else if ((k as i32 - limit as i32) as usize) < sink.capacity() {
         (k - limit) as usize
     } else {
-        buf.len()
+        sink.capacity()
     };
 
     if len > 0 {
@@ -325,10 +326,8 @@ pub fn format_exact<'a>(
             if mant.is_zero() {
                 // following digits are all zeroes, we stop here
                 // do *not* try to perform rounding! rather, fill remaining digits.
-                for c in &mut buf[i..len] {
-                    *c = b'0';
-                }
-                return (&buf[..len], k);
+                sink.zeros(len - i);
+                return k;
             }
 
             let mut d = 0;
@@ -350,7 +349,7 @@ pub fn format_exact<'a>(
             }
             debug_assert!(mant < scale);
             debug_assert!(d < 10);
-            buf[i] = b'0' + d;
+            sink.push(b'0' + d);
             mant.mul_small(10);
         }
     }
@@ -361,21 +360,20 @@ pub fn format_exact<'a>(
     let order = mant.cmp(scale.mul_small(5));
     if order == Ordering::Greater
         || (order == Ordering::Equal
-            && (len == 0 || buf[len - 1] & 1 == 1))
+            && sink.last().map_or(true, |c| c & 1 == 1))
     {
         // if rounding up changes the length, the exponent should also change.
         // but we've been requested a fixed number of digits, so do not alter the buffer...
-        if let Some(c) = round_up(&mut buf[..len]) {
+        if let Some(c) = sink.round_up() {
             // ...unless we've been requested the fixed precision instead.
             // we also need to check that, if the original buffer was empty,
             // the additional digit can only be added when `k == limit` (edge case).
             k += 1;
-            if k > limit && len < buf.len() {
-                buf[len] = c;
-                len += 1;
+            if k > limit && len < sink.capacity() {
+                sink.push(c);
             }
         }
     }
 
-    (&buf[..len], k)
+    k
 }
@@ -6,7 +6,9 @@
 //!   accurately with integers. SIGPLAN Not. 45, 6 (June 2010), 233-243.
 
 use crate::core_num::diy_float::Fp;
-use crate::core_num::flt2dec::{round_up, Decoded, MAX_SIG_DIGITS};
+#[cfg(not(any(feature = "strategy-ryu", feature = "strategy-dragonbox")))]
+use crate::core_num::flt2dec::MAX_SIG_DIGITS;
+use crate::core_num::flt2dec::{Decoded, Sink, SliceSink};
 
 // see the comments in `format_shortest_opt` for the rationale.
 #[doc(hidden)]
@@ -160,17 +162,18 @@ pub fn max_pow10_no_more_than(x: u32) -> (u8, u32) {
 
 /// The shortest mode implementation for Grisu.
 ///
-/// It returns `None` when it would return an inexact representation otherwise.
-pub fn format_shortest_opt<'a>(
-    d: &Decoded,
-    buf: &'a mut [u8],
-) -> Option<(/*digits*/ &'a [u8], /*exp*/ i16)> {
+/// It emits into `sink` (which must be empty) and returns `None` when it would return an
+/// inexact representation otherwise, in which case the emitted digits must be discarded.
+#[cfg(not(any(feature = "strategy-ryu", feature = "strategy-dragonbox")))]
+// `kappa` is updated after the last digit too, kept for upstream diffability.
+#[allow(unused_assignments)]
+pub fn format_shortest_opt<S: Sink + ?Sized>(d: &Decoded, sink: &mut S) -> Option</*exp*/ i16> {
     assert!(d.mant > 0);
     assert!(d.minus > 0);
     assert!(d.plus > 0);
     assert!(d.mant.checked_add(d.plus).is_some());
     assert!(d.mant.checked_sub(d.minus).is_some());
-    assert!(buf.len() >= MAX_SIG_DIGITS);
+    assert!(sink.capacity() >= MAX_SIG_DIGITS);
     assert!(d.mant + d.plus < (1 << 61)); // we need at least three bits of additional precision
 
     // start with the normalized values with the shared exponent
@@ -195,9 +198,9 @@ pub fn format_shortest_opt<'a>(
     let (minusk, cached) = cached_power(ALPHA - plus.e - 64, GAMMA - plus.e - 64);
 
     // scale fps. this gives the maximal error of 1 ulp (proved from Theorem 5.1).
-    let plus = plus.mul(&cached);
-    let minus = minus.mul(&cached);
-    let v = v.mul(&cached);
+    let plus = plus.mul(cached);
+    let minus = minus.mul(cached);
+    let v = v.mul(cached);
     debug_assert_eq!(plus.e, minus.e);
     debug_assert_eq!(plus.e, v.e);
 
@@ -236,7 +239,6 @@ pub fn format_shortest_opt<'a>(
     // this is an upper bound of `kappa` below.
     let (max_kappa, max_ten_kappa) = max_pow10_no_more_than(plus1int);
 
-    let mut i = 0;
     let exp = max_kappa as i16 - minusk + 1;
 
     // Theorem 6.2: if `k` is the greatest integer s.t. `0 <= y mod 10^k <= y - x`,
@@ -266,15 +268,14 @@ pub fn format_shortest_opt<'a>(
         let q = remainder / ten_kappa;
         let r = remainder % ten_kappa;
         debug_assert!(q < 10);
-        buf[i] = b'0' + q as u8;
-        i += 1;
+        sink.push(b'0' + q as u8);
 
         let plus1rem = ((r as u64) << e) + plus1frac; // == (plus1 % 10^kappa) * 2^e
         if plus1rem < delta1 {
             // `plus1 % 10^kappa < delta1 = plus1 - minus1`; we've found the correct `kappa`.
             let ten_kappa = (ten_kappa as u64) << e; // scale 10^kappa back to the shared exponent
             return round_and_weed(
-                &mut buf[..i],
+                sink,
                 exp,
                 plus1rem,
                 delta1,
@@ -286,7 +287,7 @@ pub fn format_shortest_opt<'a>(
 
         // break the loop when we have rendered all integral digits.
         // the exact number of digits is `max_kappa + 1` as `plus1 < 10^(max_kappa+1)`.
-        if i > max_kappa as usize {
+        if sink.len() > max_kappa as usize {
             debug_assert_eq!(ten_kappa, 1);
             debug_assert_eq!(kappa, 0);
             break;
@@ -318,13 +319,12 @@ pub fn format_shortest_opt<'a>(
         let q = remainder >> e;
         let r = remainder & ((1 << e) - 1);
         debug_assert!(q < 10);
-        buf[i] = b'0' + q as u8;
-        i += 1;
+        sink.push(b'0' + q as u8);
 
         if r < threshold {
             let ten_kappa = 1 << e; // implicit divisor
             return round_and_weed(
-                &mut buf[..i],
+                sink,
                 exp,
                 r,
                 threshold,
@@ -355,16 +355,16 @@ pub fn format_shortest_opt<'a>(
     // - `plus1v = (plus1 - v) * k` (and also, `threshold > plus1v` from prior invariants)
     // - `ten_kappa = 10^kappa * k`
     // - `ulp = 2^-e * k`
-    fn round_and_weed(
-        buf: &mut [u8],
+    fn round_and_weed<S: Sink + ?Sized>(
+        sink: &mut S,
         exp: i16,
         remainder: u64,
         threshold: u64,
         plus1v: u64,
         ten_kappa: u64,
         ulp: u64,
-    ) -> Option<(&[u8], i16)> {
-        assert!(!buf.is_empty());
+    ) -> Option<i16> {
+        assert!(sink.len() > 0);
 
         // produce two approximations to `v` (actually `plus1 - v`) within 1.5 ulps.
         // the resulting representation should be the closest representation to both.
@@ -377,7 +377,7 @@ pub fn format_shortest_opt<'a>(
         // decrease the last digit and stop at the closest representation to `v + 1 ulp`.
         let mut plus1w = remainder; // plus1w(n) = plus1 - w(n)
         {
-            let last = buf.last_mut().unwrap();
+            let mut last = sink.last().unwrap();
 
             // we work with the approximated digits `w(n)`, which is initially equal to `plus1 -
             // plus1 % 10^kappa`. after running the loop body `n` times, `w(n) = plus1 -
@@ -423,10 +423,11 @@ pub fn format_shortest_opt<'a>(
                 && (plus1w + ten_kappa < plus1v_up
                     || plus1v_up - plus1w >= plus1w + ten_kappa - plus1v_up)
             {
-                *last -= 1;
-                debug_assert!(*last > b'0'); // the shortest repr cannot end with `0`
+                last -= 1;
+                debug_assert!(last > b'0'); // the shortest repr cannot end with `0`
                 plus1w += ten_kappa;
             }
+            sink.set_last(last);
         }
 
         // check if this representation is also the closest representation to `v - 1 ulp`.
@@ -445,43 +446,54 @@ pub fn format_shortest_opt<'a>(
         // this is too liberal, though, so we reject any `w(n)` not between `plus0` and `minus0`,
         // i.e., `plus1 - plus1w(n) <= minus0` or `plus1 - plus1w(n) >= plus0`. we utilize the facts
         // that `threshold = plus1 - minus1` and `plus1 - plus0 = minus0 - minus1 = 2 ulp`.
-        if 2 * ulp <= plus1w && plus1w <= threshold - 4 * ulp { Some((buf, exp)) } else { None }
+        if 2 * ulp <= plus1w && plus1w <= threshold - 4 * ulp { Some(exp) } else { None }
     }
 }
 
 /// The shortest mode implementation for Grisu with Dragon fallback.
 ///
 /// This should be used for most cases.
+#[cfg(not(any(feature = "strategy-ryu", feature = "strategy-dragonbox")))]
 pub fn format_shortest<'a>(
     d: &Decoded,
     buf: &'a mut [u8],
 ) -> (/*digits*/ &'a [u8], /*exp*/ i16) {
-    use crate::core_num::flt2dec::strategy::dragon::format_shortest as fallback;
-    // SAFETY: The borrow checker is not smart enough to let us use `buf`
-    // in the second branch, so we launder the lifetime here. But we only re-use
-    // `buf` if `format_shortest_opt` returned `None` so this is okay.
-    match format_shortest_opt(d, unsafe { &mut *(buf as *mut _) }) {
-        Some(ret) => ret,
-        None => fallback(d, buf),
+    let mut sink = SliceSink::new(buf);
+    let exp = format_shortest_into(d, &mut sink);
+    (sink.into_digits(), exp)
+}
+
+/// The shortest mode implementation for Grisu with Dragon fallback, emitting into `sink`
+/// (which must be empty).
+#[cfg(not(any(feature = "strategy-ryu", feature = "strategy-dragonbox")))]
+pub fn format_shortest_into<S: Sink + ?Sized>(d: &Decoded, sink: &mut S) -> /*exp*/ i16 {
+    use crate::core_num::flt2dec::strategy::dragon::format_shortest_into as fallback;
+    match format_shortest_opt(d, sink) {
+        Some(exp) => exp,
+        None => {
+            sink.clear();
+            fallback(d, sink)
+        }
     }
 }
 
 /// The exact and fixed mode implementation for Grisu.
 ///
-/// It returns `None` when it would return an inexact representation otherwise.
-pub fn format_exact_opt<'a>(
+/// It emits into `sink` (which must be empty) and returns `None` when it would return an
+/// inexact representation otherwise, in which case the emitted digits must be discarded.
+pub fn format_exact_opt<S: Sink + ?Sized>(
     d: &Decoded,
-    buf: &'a mut [u8],
+    sink: &mut S,
     limit: i16,
-) -> Option<(/*digits*/ &'a [u8], /*exp*/ i16)> {
+) -> Option</*exp*/ i16> {
     assert!(d.mant > 0);
     assert!(d.mant < (1 << 61)); // we need at least three bits of additional precision
-    assert!(!buf.is_empty());
+    assert!(sink.capacity() > 0);
 
     // normalize and scale `v`.
     let v = Fp { f: d.mant, e: d.exp }.normalize();
     let (minusk, cached) = cached_power(ALPHA - v.e - 64, GAMMA - v.e - 64);
-    let v = v.mul(&cached);
+    let v = v.mul(cached);
 
     // divide `v` into integral and fractional parts.
     let e = -v.e as usize;
@@ -505,7 +517,6 @@ pub fn format_exact_opt<'a>(
     // this is an upper bound of `kappa` below.
     let (max_kappa, max_ten_kappa) = max_pow10_no_more_than(vint);
 
-    let mut i = 0;
     let exp = max_kappa as i16 - minusk + 1;
 
     // if we are working with the last-digit limitation, we need to shorten the buffer
@@ -520,13 +531,11 @@ pub fn format_exact_opt<'a>(
         // thus we are being sloppy here and widen the error range by a factor of 10.
         // this will increase the false negative rate, but only very, *very* slightly;
         // it can only matter noticeably when the mantissa is bigger than 60 bits.
-        //
-        // SAFETY: `len=0`, so the obligation of having initialized this memory is trivial.
-        return possibly_round(buf, 0, exp, limit, v.f / 10, (max_ten_kappa as u64) << e, err << e);
-    } else if ((exp as i32 - limit as i32) as usize) < buf.len() {
+        return possibly_round(sink, exp, limit, v.f / 10, (max_ten_kappa as u64) << e, err << e);
+    } else if ((exp as i32 - limit as i32) as usize) < sink.capacity() {
         (exp - limit) as usize
     } else {
-        buf.len()
+        sink.capacity()
     };
     debug_assert!(len > 0);
 
@@ -546,19 +555,17 @@ pub fn format_exact_opt<'a>(
         let q = remainder / ten_kappa;
         let r = remainder % ten_kappa;
         debug_assert!(q < 10);
-        buf[i] = b'0' + q as u8;
-        i += 1;
+        sink.push(b'0' + q as u8);
 
         // is the buffer full? run the rounding pass with the remainder.
-        if i == len {
+        if sink.len() == len {
             let vrem = ((r as u64) << e) + vfrac; // == (v % 10^kappa) * 2^e
-            // SAFETY: we have initialized `len` many bytes.
-            return possibly_round(buf, len, exp, limit, vrem, (ten_kappa as u64) << e, err << e);
+            return possibly_round(sink, exp, limit, vrem, (ten_kappa as u64) << e, err << e);
         }
 
         // break the loop when we have rendered all integral digits.
         // the exact number of digits is `max_kappa + 1` as `plus1 < 10^(max_kappa+1)`.
-        if i > max_kappa as usize {
+        if sink.len() > max_kappa as usize {
             debug_assert_eq!(ten_kappa, 1);
             debug_assert_eq!(kappa, 0);
             break;
@@ -598,13 +605,11 @@ pub fn format_exact_opt<'a>(
         let q = remainder >> e;
         let r = remainder & ((1 << e) - 1);
         debug_assert!(q < 10);
-        buf[i] = b'0' + q as u8;
-        i += 1;
+        sink.push(b'0' + q as u8);
 
         // is the buffer full? run the rounding pass with the remainder.
-        if i == len {
-            // SAFETY: we have initialized `len` many bytes.
-            return possibly_round(buf, len, exp, limit, r, 1 << e, err);
+        if sink.len() == len {
+            return possibly_round(sink, exp, limit, r, 1 << e, err);
         }
 
         // restore invariants
@@ -624,17 +629,14 @@ pub fn format_exact_opt<'a>(
     // - `remainder = (v % 10^kappa) * k`
     // - `ten_kappa = 10^kappa * k`
     // - `ulp = 2^-e * k`
-    //
-    // SAFETY: the first `len` bytes of `buf` must be initialized.
-    fn possibly_round(
-        buf: &mut [u8],
-        mut len: usize,
+    fn possibly_round<S: Sink + ?Sized>(
+        sink: &mut S,
         mut exp: i16,
         limit: i16,
         remainder: u64,
         ten_kappa: u64,
         ulp: u64,
-    ) -> Option<(&[u8], i16)> {
+    ) -> Option<i16> {
         debug_assert!(remainder < ten_kappa);
 
         //           10^kappa
@@ -682,7 +684,7 @@ pub fn format_exact_opt<'a>(
         //     |     v     |
         // v - 1 ulp   v + 1 ulp
         //
-        // if `v + 1 ulp` is closer to the rounded-down representation (which is already in `buf`),
+        // if `v + 1 ulp` is closer to the rounded-down representation (which is already in `sink`),
         // then we can safely return. note that `v - 1 ulp` *can* be less than the current
         // representation, but as `1 ulp < 10^kappa / 2`, this condition is enough:
         // the distance between `v - 1 ulp` and the current representation
@@ -693,8 +695,7 @@ pub fn format_exact_opt<'a>(
         // we've already verified that `ulp < 10^kappa / 2`, so as long as
         // `10^kappa` did not overflow after all, the second check is fine.
         if ten_kappa - remainder > remainder && ten_kappa - 2 * remainder >= 2 * ulp {
-            // SAFETY: our caller initialized that memory.
-            return Some((&buf[..len], exp));
+            return Some(exp);
         }
 
         //   :<------- remainder ------>|   :
@@ -715,21 +716,16 @@ pub fn format_exact_opt<'a>(
         // as `10^kappa` is never zero). also note that `remainder - ulp <= 10^kappa`,
         // so the second check does not overflow.
         if remainder > ulp && ten_kappa - (remainder - ulp) <= remainder - ulp {
-            if let Some(c) =
-                // SAFETY: our caller must have initialized that memory.
-                round_up(&mut buf[..len])
-            {
+            if let Some(c) = sink.round_up() {
                 // only add an additional digit when we've been requested the fixed precision.
                 // we also need to check that, if the original buffer was empty,
                 // the additional digit can only be added when `exp == limit` (edge case).
                 exp += 1;
-                if exp > limit && len < buf.len() {
-                    buf[len] = c;
-                    len += 1;
+                if exp > limit && sink.len() < sink.capacity() {
+                    sink.push(c);
                 }
             }
-            // SAFETY: we and our caller initialized that memory.
-            return Some((&buf[..len], exp));
+            return Some(exp);
         }
 
         // otherwise we are doomed (i.e., some values between `v - 1 ulp` and `v + 1 ulp` are
@@ -746,12 +742,20 @@ pub fn format_exact<'a>(
     buf: &'a mut [u8],
     limit: i16,
 ) -> (/*digits*/ &'a [u8], /*exp*/ i16) {
-    use crate::core_num::flt2dec::strategy::dragon::format_exact as fallback;
-    // SAFETY: The borrow checker is not smart enough to let us use `buf`
-    // in the second branch, so we launder the lifetime here. But we only re-use
-    // `buf` if `format_exact_opt` returned `None` so this is okay.
-    match format_exact_opt(d, unsafe { &mut *(buf as *mut _) }, limit) {
-        Some(ret) => ret,
-        None => fallback(d, buf, limit),
+    let mut sink = SliceSink::new(buf);
+    let exp = format_exact_into(d, &mut sink, limit);
+    (sink.into_digits(), exp)
+}
+
+/// The exact and fixed mode implementation for Grisu with Dragon fallback, emitting into `sink`
+/// (which must be empty).
+pub fn format_exact_into<S: Sink + ?Sized>(d: &Decoded, sink: &mut S, limit: i16) -> /*exp*/ i16 {
+    use crate::core_num::flt2dec::strategy::dragon::format_exact_into as fallback;
+    match format_exact_opt(d, sink, limit) {
+        Some(exp) => exp,
+        None => {
+            sink.clear();
+            fallback(d, sink, limit)
+        }
     }
 }
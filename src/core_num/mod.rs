@@ -1,5 +1,57 @@
-// Taken from libcore::num from Rust commit d416093209d0dd77a4cdeb5a2f1b5de1316787ec.
-// `algorithm::fpu_precision` has been removed
+// Vendored from libcore::num. Each module is synced separately, so the
+// upstream version is recorded per module:
+//
+// - `dec2flt` and `diy_float`: Rust 1.95.0 (commit 59807616e).
+// - `bignum` and `flt2dec`: Rust commit d416093209d0dd77a4cdeb5a2f1b5de1316787ec.
+//
+// Local modifications, which have to be preserved when syncing:
+//
+// - `#![unstable]`/`#[stable]` attributes, `f16` support and `FromStr`
+//   implementations are removed, and `crate::num` paths become
+//   `crate::core_num`.
+// - The code has to build with the MSRV of the crate (1.46), so let-else,
+//   `if let` guards, `widening_mul`, `ilog2`, `<uN>::BITS` and floating
+//   point arithmetic in `const fn` are rewritten (the affected constants of
+//   `dec2flt::float::RawFloat` are spelled out in each implementation), and
+//   `decimal_seq::DecimalSeq` does not derive `Clone`, `Debug` and
+//   `PartialEq` (arrays of 768 digits do not implement them).
+// - `dec2flt` works on already validated digit slices instead of strings:
+//   `parse::parse_number` and `decimal_seq::parse_decimal_seq` take the
+//   integral and fractional digits and the exponent, `dec2flt::dec2flt`
+//   does not handle signs, infinities or NaN, and the string-only helpers
+//   (`parse_inf_nan`, `parse_scientific`, ...) are removed.
+// - `dec2flt::fpu` is removed: the fast path in `dec2flt::decimal` only
+//   handles exact integers and builds their bits without float arithmetic,
+//   so results do not depend on x87 precision or flush-to-zero modes.
+// - `flt2dec` emits digits into a `Sink` instead of a `MaybeUninit` buffer,
+//   and `bignum` does not use unstable intrinsics. Upstream changes to these
+//   modules have to be merged by hand.
+// - `bignum::FullOps` only uses `carrying_add`, `carrying_mul` and
+//   `carrying_mul_add`, like upstream, when the build script detects Rust
+//   1.91 (`cfg(flt2dec2flt_bigint_helpers)`), and widens to the next integer
+//   type otherwise.
+// - Upstream items that are not used here are kept, under a scoped
+//   `#[allow(dead_code)]` or `#[allow(unused_imports)]`.
+// - `bignum` also defines `Big32x530`, used by `crate::wide`, and
+//   `Big32x80`, used by `crate::sink`.
+// - The tables checked by `crate::verify_tables` are `pub(crate)`.
+// - Arithmetic that could overflow a 16-bit `usize` is done with wider
+//   integers (`flt2dec::estimate_max_buf_len`) and checked with
+//   `const_assert!`.
+//
+// Update procedure:
+//
+// 1. Copy `library/core/src/num/{dec2flt,diy_float.rs}` (and optionally
+//    `flt2dec` and `bignum.rs`) from the new Rust release over the vendored
+//    files.
+// 2. Re-apply the modifications listed above (`git diff` against the
+//    previous vendored version shows them) and update the versions here.
+// 3. `dec2flt/table.rs` is generated by `src/etc/dec2flt_table.py` upstream
+//    and must be copied verbatim.
+// 4. Run the test suite; `generic::from_preparsed_abs` is the only caller
+//    of `dec2flt`, so the public API of the crate does not change. If a table
+//    changed, `test_verify_tables` fails and the checksum in
+//    `crate::tables` has to be updated.
 
 //! Numeric traits and functions for the built-in numeric types.
 
@@ -8,5 +60,3 @@ pub mod bignum;
 pub mod dec2flt;
 pub mod diy_float;
 pub mod flt2dec;
-
-pub use dec2flt::ParseFloatError;
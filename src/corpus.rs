@@ -0,0 +1,403 @@
+//! Generator of hard inputs for parsers and formatters.
+//!
+//! A `Generator` emits, from a seed, an endless sequence of `HardCase`s:
+//! decimal texts paired with the `f64` they must be parsed into (rounding
+//! to nearest, ties to even). They are chosen among the inputs that
+//! conversion algorithms tend to get wrong, to be used as fuzz seeds or
+//! regression corpora by other implementations. Formatters can use
+//! `HardCase::value` as input, and check that its shortest representation
+//! parses back into it.
+//!
+//! The sequence only depends on the seed, and is the same on every
+//! platform.
+//!
+//! # Example
+//!
+//! ```
+//! use flt2dec2flt::corpus::Generator;
+//! use flt2dec2flt::grammar::Grammar;
+//!
+//! for case in Generator::new(42).take(1000) {
+//!     let parsed = Grammar::RUST.parse(case.text().as_bytes()).unwrap();
+//!     let parsed: f64 = parsed.to_float().unwrap();
+//!     assert_eq!(parsed.to_bits(), case.value().to_bits(), "{}", case.text());
+//! }
+//! ```
+
+use core::fmt;
+
+use crate::core_num::flt2dec::decoder::{Decoded, FullDecoded};
+use crate::{generic, FloatExt, PreFormatted, PREFORMAT_EXACT_FIXED_BASE_BUF_LEN};
+
+/// Maximum length of `HardCase::text`.
+///
+/// The longest texts are those close to the point halfway between two
+/// subnormal numbers, which has 768 significant digits.
+pub const MAX_TEXT_LEN: usize = 832;
+
+/// Maximum number of digits added to the point halfway between two
+/// numbers to move slightly away from it.
+const MAX_HALFWAY_OFFSET_DIGITS: usize = 40;
+
+/// Maximum length of the runs of zeros of `HardCaseKind::LongZeroRun`.
+const MAX_ZERO_RUN: usize = 700;
+
+/// Why an input is hard.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum HardCaseKind {
+    /// The exact point halfway between two consecutive numbers, or a
+    /// number that differs from it in a digit far beyond the precision of
+    /// `f64`.
+    NearHalfway,
+    /// A number that overflows or underflows, one close to the largest
+    /// finite number, or an exponent with many leading zeros.
+    ExponentExtreme,
+    /// A number with a long run of leading or trailing zeros.
+    LongZeroRun,
+    /// A number close to the smallest subnormal or the smallest normal
+    /// number, or halfway between two of them.
+    SubnormalBoundary,
+}
+
+/// A hard input, emitted by `Generator`.
+pub struct HardCase {
+    kind: HardCaseKind,
+    value: f64,
+    text: [u8; MAX_TEXT_LEN],
+    len: usize,
+}
+
+impl HardCase {
+    /// Returns why the input is hard.
+    pub fn kind(&self) -> HardCaseKind {
+        self.kind
+    }
+
+    /// Returns the number as text: an optional `-` sign, decimal digits
+    /// with an optional `.`, and an optional exponent (`e` followed by an
+    /// optional `-` sign and decimal digits).
+    pub fn text(&self) -> &str {
+        // Only ASCII is written.
+        core::str::from_utf8(&self.text[..self.len]).unwrap()
+    }
+
+    /// Returns the correctly rounded value of `text`, which can be zero or
+    /// infinite.
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+
+    fn push(&mut self, c: u8) {
+        self.text[self.len] = c;
+        self.len += 1;
+    }
+
+    fn push_digits(&mut self, digits: &[u8]) {
+        self.text[self.len..][..digits.len()].copy_from_slice(digits);
+        self.len += digits.len();
+    }
+
+    fn push_zeros(&mut self, n: usize) {
+        for c in self.text[self.len..][..n].iter_mut() {
+            *c = b'0';
+        }
+        self.len += n;
+    }
+
+    /// Pushes `e`, the exponent, and `leading_zeros` zeros before it.
+    fn push_exp(&mut self, exp: i32, leading_zeros: usize) {
+        self.push(b'e');
+        if exp < 0 {
+            self.push(b'-');
+        }
+        self.push_zeros(leading_zeros);
+        let mut digits = [0; 10];
+        let mut n = 0;
+        let mut abs_exp = i64::from(exp).abs() as u32;
+        loop {
+            digits[n] = b'0' + (abs_exp % 10) as u8;
+            n += 1;
+            abs_exp /= 10;
+            if abs_exp == 0 {
+                break;
+            }
+        }
+        for i in (0..n).rev() {
+            self.push(digits[i]);
+        }
+    }
+
+    /// Pushes the digits of a number in scientific notation (with the
+    /// decimal point after the first one), followed by `extra`.
+    fn push_significand(&mut self, digits: &[u8], extra: &[u8]) {
+        self.push(digits[0]);
+        if digits.len() > 1 || !extra.is_empty() {
+            self.push(b'.');
+            self.push_digits(&digits[1..]);
+            self.push_digits(extra);
+        }
+    }
+}
+
+impl Clone for HardCase {
+    fn clone(&self) -> Self {
+        HardCase {
+            kind: self.kind,
+            value: self.value,
+            text: self.text,
+            len: self.len,
+        }
+    }
+}
+
+impl fmt::Debug for HardCase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HardCase")
+            .field("kind", &self.kind)
+            .field("value", &self.value)
+            .field("text", &self.text())
+            .finish()
+    }
+}
+
+/// Seedable generator of `HardCase`s (see the module documentation).
+///
+/// It is an endless iterator.
+#[derive(Clone, Debug)]
+pub struct Generator {
+    state: u64,
+}
+
+impl Generator {
+    /// Creates a generator. Equal seeds give equal sequences.
+    pub fn new(seed: u64) -> Self {
+        // xorshift cannot start from zero.
+        let state = seed ^ 0x9e37_79b9_7f4a_7c15;
+        Self {
+            state: if state == 0 { 1 } else { state },
+        }
+    }
+
+    /// Returns a random number (xorshift64).
+    fn random(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+
+    /// Returns a random number in `lo..=hi`.
+    fn random_in(&mut self, lo: u64, hi: u64) -> u64 {
+        lo + self.random() % (hi - lo + 1)
+    }
+
+    /// Returns the next hard case.
+    pub fn next_case(&mut self) -> HardCase {
+        let kind = match self.random() % 4 {
+            0 => HardCaseKind::NearHalfway,
+            1 => HardCaseKind::ExponentExtreme,
+            2 => HardCaseKind::LongZeroRun,
+            _ => HardCaseKind::SubnormalBoundary,
+        };
+        let mut case = HardCase {
+            kind,
+            value: 0.0,
+            text: [0; MAX_TEXT_LEN],
+            len: 0,
+        };
+        let negative = self.random() % 2 == 0;
+        if negative {
+            case.push(b'-');
+        }
+        case.value = match kind {
+            HardCaseKind::NearHalfway => {
+                // Any finite number
+                let bits = self.random() % 0x7FF0_0000_0000_0000;
+                self.near_halfway(&mut case, bits)
+            }
+            HardCaseKind::ExponentExtreme => self.exponent_extreme(&mut case),
+            HardCaseKind::LongZeroRun => self.long_zero_run(&mut case),
+            HardCaseKind::SubnormalBoundary => {
+                let bits = if self.random() % 2 == 0 {
+                    self.random_in(0, 16)
+                } else {
+                    self.random_in(0x000F_FFFF_FFFF_FFF0, 0x0010_0000_0000_0010)
+                };
+                if self.random() % 2 == 0 {
+                    self.near_halfway(&mut case, bits)
+                } else {
+                    push_shortest(&mut case, f64::from_bits(bits), 0);
+                    f64::from_bits(bits)
+                }
+            }
+        };
+        if negative {
+            case.value = -case.value;
+        }
+        case
+    }
+
+    /// Pushes the point halfway between the non-negative number with
+    /// `bits` and the next one, or a number slightly below or above it,
+    /// and returns the value it rounds to.
+    fn near_halfway(&mut self, case: &mut HardCase, bits: u64) -> f64 {
+        let mut buf = [0; PREFORMAT_EXACT_FIXED_BASE_BUF_LEN];
+        let (len, exp) = halfway_digits(bits, &mut buf);
+        let digits = &mut buf[..len];
+        let (lower, upper) = (f64::from_bits(bits), f64::from_bits(bits + 1));
+        let offset_len = self.random_in(1, MAX_HALFWAY_OFFSET_DIGITS as u64) as usize;
+        let mut offset = [0; MAX_HALFWAY_OFFSET_DIGITS];
+        let offset = &mut offset[..offset_len];
+        match self.random() % 3 {
+            0 => {
+                case.push_significand(digits, &[]);
+                case.push_exp(exp - 1, 0);
+                if bits % 2 == 0 {
+                    lower
+                } else {
+                    upper
+                }
+            }
+            1 => {
+                // The last digit is not zero, so subtracting one from the
+                // following ones does not borrow further.
+                digits[len - 1] -= 1;
+                for c in offset.iter_mut() {
+                    *c = b'9';
+                }
+                case.push_significand(digits, offset);
+                case.push_exp(exp - 1, 0);
+                lower
+            }
+            _ => {
+                for c in offset.iter_mut() {
+                    *c = b'0';
+                }
+                offset[offset_len - 1] = b'1';
+                case.push_significand(digits, offset);
+                case.push_exp(exp - 1, 0);
+                upper
+            }
+        }
+    }
+
+    fn exponent_extreme(&mut self, case: &mut HardCase) -> f64 {
+        match self.random() % 3 {
+            0 => {
+                // Up to 19 digits, times `10^±(400..)`
+                let mut mantissa = self.random() >> self.random_in(1, 63);
+                mantissa += (mantissa == 0) as u64;
+                let mut digits = [0; 20];
+                let mut len = 0;
+                while mantissa != 0 {
+                    digits[len] = b'0' + (mantissa % 10) as u8;
+                    mantissa /= 10;
+                    len += 1;
+                }
+                digits[..len].reverse();
+                case.push_digits(&digits[..len]);
+                let exp = self.random_in(400, 99_999) as i32;
+                if self.random() % 2 == 0 {
+                    case.push_exp(exp, 0);
+                    f64::INFINITY
+                } else {
+                    case.push_exp(-exp, 0);
+                    0.0
+                }
+            }
+            1 => {
+                let v = f64::from_bits(0x7FEF_FFFF_FFFF_FFFF - self.random_in(0, 16));
+                push_shortest(case, v, 0);
+                v
+            }
+            _ => {
+                let v = f64::from_bits(self.random() % 0x7FF0_0000_0000_0000);
+                let leading_zeros = self.random_in(1, 30) as usize;
+                push_shortest(case, v, leading_zeros);
+                v
+            }
+        }
+    }
+
+    fn long_zero_run(&mut self, case: &mut HardCase) -> f64 {
+        let v = f64::from_bits(self.random() % 0x7FF0_0000_0000_0000);
+        let zeros = self.random_in(20, MAX_ZERO_RUN as u64) as usize;
+        let mut buf = [0; crate::PREFORMAT_SHORTEST_BUF_LEN];
+        match v.preformat_shortest(&mut buf) {
+            PreFormatted::Finite(_, digits, _, exp) => {
+                let exp = i32::from(exp);
+                if self.random() % 2 == 0 {
+                    // 0.000ddd
+                    case.push_digits(b"0.");
+                    case.push_zeros(zeros);
+                    case.push_digits(digits);
+                    case.push_exp(exp + zeros as i32, 0);
+                } else {
+                    // ddd000
+                    case.push_digits(digits);
+                    case.push_zeros(zeros);
+                    case.push_exp(exp - (digits.len() + zeros) as i32, 0);
+                }
+            }
+            _ => {
+                case.push_digits(b"0.");
+                case.push_zeros(zeros);
+            }
+        }
+        v
+    }
+}
+
+impl Iterator for Generator {
+    type Item = HardCase;
+
+    fn next(&mut self) -> Option<HardCase> {
+        Some(self.next_case())
+    }
+}
+
+/// Pushes the shortest representation of the non-negative `v` in
+/// scientific notation, with `leading_zeros` zeros in the exponent.
+fn push_shortest(case: &mut HardCase, v: f64, leading_zeros: usize) {
+    let mut buf = [0; crate::PREFORMAT_SHORTEST_BUF_LEN];
+    match v.preformat_shortest(&mut buf) {
+        PreFormatted::Finite(_, digits, _, exp) => {
+            case.push_significand(digits, &[]);
+            case.push_exp(i32::from(exp) - 1, leading_zeros);
+        }
+        _ => {
+            case.push(b'0');
+            case.push_exp(0, leading_zeros);
+        }
+    }
+}
+
+/// Writes the significant digits of the point halfway between the
+/// non-negative finite number with `bits` and the next one into `buf`,
+/// returning their number and the exponent (the point is
+/// `0.digits * 10^exp`).
+fn halfway_digits(bits: u64, buf: &mut [u8]) -> (usize, i32) {
+    let biased_exp = (bits >> 52) as i16;
+    let frac = bits & ((1 << 52) - 1);
+    let (mant, exp) = if biased_exp == 0 {
+        (frac, -1074)
+    } else {
+        (frac | 1 << 52, biased_exp - 1075)
+    };
+    let decoded = Decoded {
+        mant: 2 * mant + 1,
+        minus: 1,
+        plus: 1,
+        exp: exp - 1,
+        inclusive: true,
+    };
+    let full_decoded = FullDecoded::Finite(decoded);
+    match generic::preformat_exact_exp_decoded(false, full_decoded, buf, buf.len()) {
+        PreFormatted::Finite(_, digits, _, exp) => {
+            let len = digits.iter().rposition(|&c| c != b'0').unwrap() + 1;
+            (len, i32::from(exp))
+        }
+        _ => unreachable!(),
+    }
+}
@@ -0,0 +1,242 @@
+//! Formatting and parsing of user-defined binary floating point formats,
+//! such as FPGA minifloats or DSP formats.
+//!
+//! A format implements `CustomFloat` by describing its precision and
+//! exponent range and by converting its values from and to `Unpacked`.
+//! The functions of this module then work like the methods of `FloatExt`,
+//! with the precision of the format (so the shortest digits are the fewest
+//! that round-trip through the format itself).
+//!
+//! Formats up to the size of binary128 are supported: at most 113
+//! significand bits, with exponents within those of binary128.
+//!
+//! # Example
+//!
+//! An 8-bit float with 4 exponent bits and 3 fraction bits (OCP E4M3),
+//! which has no infinities and saturates on overflow:
+//!
+//! ```
+//! use flt2dec2flt::custom::{self, CustomFloat, Unpacked};
+//! use flt2dec2flt::{PreFormatted, PreParsed};
+//!
+//! #[derive(Copy, Clone, Debug, PartialEq)]
+//! struct E4M3(u8);
+//!
+//! impl CustomFloat for E4M3 {
+//!     const SIG_BITS: u32 = 4;
+//!     const MIN_LSB_EXP: i32 = -9;
+//!     const MAX_EXP: i32 = 8;
+//!
+//!     fn unpack(self) -> Unpacked {
+//!         let sign = self.0 >> 7 != 0;
+//!         let biased_exp = i32::from(self.0 >> 3 & 0xF);
+//!         let frac = u128::from(self.0 & 0x7);
+//!         if self.0 & 0x7F == 0x7F {
+//!             Unpacked::NaN
+//!         } else if biased_exp == 0 {
+//!             Unpacked::Finite(sign, frac, -9)
+//!         } else {
+//!             Unpacked::Finite(sign, frac | 0x8, biased_exp - 10)
+//!         }
+//!     }
+//!
+//!     fn pack(v: Unpacked) -> Self {
+//!         match v {
+//!             Unpacked::NaN => E4M3(0x7F),
+//!             // Saturates to the largest finite number.
+//!             Unpacked::Inf(sign) => E4M3(u8::from(sign) << 7 | 0x7E),
+//!             Unpacked::Finite(sign, q, k) => {
+//!                 let bits = if q >> 3 == 0 {
+//!                     q as u8
+//!                 } else {
+//!                     (((k + 10) as u8) << 3 | (q as u8 & 0x7)).min(0x7E)
+//!                 };
+//!                 E4M3(u8::from(sign) << 7 | bits)
+//!             }
+//!         }
+//!     }
+//! }
+//!
+//! let mut buf = [0; custom::SHORTEST_BUF_LEN];
+//! assert_eq!(
+//!     custom::preformat_shortest(E4M3(0x7E), &mut buf),
+//!     PreFormatted::Finite(false, b"45", 0, 3),
+//! );
+//!
+//! let preparsed = PreParsed {
+//!     sign: false,
+//!     int_digits: b"0",
+//!     frac_digits: b"3",
+//!     exp: 0,
+//! };
+//! // 0.3 is between 0.28125 and 0.3125, and closer to the latter.
+//! assert_eq!(custom::from_preparsed(preparsed), Ok(E4M3(0x2A)));
+//! ```
+
+use crate::wide::{self, Decoded, FullDecoded, Rounded};
+use crate::{ParseFloatError, PreFormatted, PreParsed};
+
+/// Minimum buffer size that has to be passed to `preformat_shortest`, for
+/// any supported format.
+pub const SHORTEST_BUF_LEN: usize = crate::binary128::SHORTEST_BUF_LEN;
+
+/// Minimum base buffer size that has to be passed to
+/// `preformat_exact_fixed`, for any supported format (see
+/// `PREFORMAT_EXACT_FIXED_BASE_BUF_LEN`).
+pub const EXACT_FIXED_BASE_BUF_LEN: usize = crate::binary128::EXACT_FIXED_BASE_BUF_LEN;
+
+/// The value of a number of a `CustomFloat` format.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Unpacked {
+    /// The number is NaN.
+    NaN,
+    /// The number is infinity. The boolean specifies the sign.
+    Inf(bool),
+    /// The number is finite. The boolean specifies the sign, and the value
+    /// is `mant * 2^exp` (zero if `mant` is zero).
+    Finite(bool, u128, i32),
+}
+
+/// A binary floating point format.
+///
+/// The format has `SIG_BITS` bits of precision, with gradual underflow:
+/// its finite numbers are `q * 2^k`, where `q < 2^SIG_BITS` and
+/// `MIN_LSB_EXP <= k <= MAX_EXP - (SIG_BITS - 1)`. Formats must satisfy
+/// `1 <= SIG_BITS <= 113`, `MIN_LSB_EXP >= -16494`, `MAX_EXP <= 16383`
+/// and `MIN_LSB_EXP + SIG_BITS - 1 <= MAX_EXP`; the functions of this
+/// module panic otherwise.
+pub trait CustomFloat: Copy {
+    /// Number of significand bits, including the integer bit.
+    const SIG_BITS: u32;
+    /// Binary exponent of the least significant bit of subnormals.
+    const MIN_LSB_EXP: i32;
+    /// Binary exponent of the most significant bit of the largest finite
+    /// numbers.
+    const MAX_EXP: i32;
+
+    /// Returns the value of `self`.
+    ///
+    /// `Unpacked::Finite` values must be numbers of the format, in any
+    /// representation (such as `mant * 2^exp` with `mant` shifted to the
+    /// left), or the formatting functions panic.
+    fn unpack(self) -> Unpacked;
+
+    /// Builds a number from a rounded value.
+    ///
+    /// The parsing functions only pass `Unpacked::Finite(sign, q, k)` with
+    /// `q < 2^SIG_BITS` and `k >= MIN_LSB_EXP`, and `q >= 2^(SIG_BITS - 1)`
+    /// unless `k == MIN_LSB_EXP` (for subnormals and zeros), and
+    /// `Unpacked::Inf` on overflow (formats without infinities can
+    /// saturate or return NaN instead).
+    fn pack(v: Unpacked) -> Self;
+}
+
+fn format<T: CustomFloat>() -> wide::Format {
+    let sig_bits = i64::from(T::SIG_BITS);
+    assert!(
+        (1..=113).contains(&sig_bits)
+            && T::MIN_LSB_EXP >= -16494
+            && T::MAX_EXP <= 16383
+            && i64::from(T::MIN_LSB_EXP) + sig_bits - 1 <= i64::from(T::MAX_EXP),
+        "unsupported custom float format"
+    );
+    wide::Format {
+        sig_bits: T::SIG_BITS,
+        min_lsb_exp: T::MIN_LSB_EXP,
+        max_exp: T::MAX_EXP,
+    }
+}
+
+fn decode<T: CustomFloat>(v: T) -> (bool, FullDecoded) {
+    let format = format::<T>();
+    let (sign, mant, exp) = match v.unpack() {
+        Unpacked::NaN => return (false, FullDecoded::Nan),
+        Unpacked::Inf(sign) => return (sign, FullDecoded::Infinite),
+        Unpacked::Finite(sign, 0, _) => return (sign, FullDecoded::Zero),
+        Unpacked::Finite(sign, mant, exp) => (sign, mant, i64::from(exp)),
+    };
+
+    // Normalize to `mant * 2^exp` with `SIG_BITS` bits, or less for
+    // subnormals.
+    let sig_bits = i64::from(format.sig_bits);
+    let bits = 128 - i64::from(mant.leading_zeros());
+    let k = (exp + bits - sig_bits).max(i64::from(format.min_lsb_exp));
+    let m = if k >= exp {
+        let shift = k - exp;
+        assert!(
+            shift < 128 && mant.trailing_zeros() >= shift as u32,
+            "value not representable in the custom float format"
+        );
+        mant >> shift
+    } else {
+        mant << (exp - k)
+    };
+    assert!(
+        k + sig_bits - 1 <= i64::from(format.max_exp),
+        "value not representable in the custom float format"
+    );
+    let k = k as i32;
+    let lower_closer = m == 1 << (sig_bits - 1) && k > format.min_lsb_exp;
+    (sign, FullDecoded::Finite(Decoded::new(m, k, lower_closer)))
+}
+
+/// Pre-formats `v` with the lowest number of significant digits that
+/// round-trip, like `FloatExt::preformat_shortest`.
+///
+/// `buf` must be at least `SHORTEST_BUF_LEN` long.
+pub fn preformat_shortest<T: CustomFloat>(v: T, buf: &mut [u8]) -> PreFormatted<'_> {
+    let (sign, full_decoded) = decode(v);
+    wide::preformat_shortest(sign, full_decoded, buf)
+}
+
+/// Pre-formats `v` with an exact number of significant digits, like
+/// `FloatExt::preformat_exact_exp`.
+///
+/// `buf` must be at least `num_digits` long (or `EXACT_FIXED_BASE_BUF_LEN`,
+/// if that is smaller), and `num_digits` must not be zero.
+pub fn preformat_exact_exp<T: CustomFloat>(
+    v: T,
+    buf: &mut [u8],
+    num_digits: usize,
+) -> PreFormatted<'_> {
+    let (sign, full_decoded) = decode(v);
+    wide::preformat_exact_exp(sign, full_decoded, buf, num_digits)
+}
+
+/// Pre-formats `v` with an exact number of fractional digits, like
+/// `FloatExt::preformat_exact_fixed`.
+///
+/// `buf` must be at least `EXACT_FIXED_BASE_BUF_LEN` long, whatever
+/// `num_frac_digits` is.
+pub fn preformat_exact_fixed<T: CustomFloat>(
+    v: T,
+    buf: &mut [u8],
+    num_frac_digits: isize,
+) -> PreFormatted<'_> {
+    let (sign, full_decoded) = decode(v);
+    wide::preformat_exact_fixed(sign, full_decoded, buf, num_frac_digits)
+}
+
+/// Pre-formats `v` with the digits above `10^limit`, like
+/// `FloatExt::preformat_exact_limit`.
+///
+/// `buf` must be at least `EXACT_FIXED_BASE_BUF_LEN` long.
+pub fn preformat_exact_limit<T: CustomFloat>(v: T, buf: &mut [u8], limit: i16) -> PreFormatted<'_> {
+    let (sign, full_decoded) = decode(v);
+    wide::preformat_exact_limit(sign, full_decoded, buf, limit)
+}
+
+/// Converts a pre-parsed decimal into the nearest number of the format,
+/// rounding half to even.
+///
+/// Any number of digits is accepted.
+pub fn from_preparsed<T: CustomFloat>(preparsed: PreParsed<'_>) -> Result<T, ParseFloatError> {
+    let format = format::<T>();
+    let sign = preparsed.sign;
+    let v = match wide::from_preparsed(preparsed, &format)? {
+        Rounded::Zero => Unpacked::Finite(sign, 0, format.min_lsb_exp),
+        Rounded::Finite(q, k) => Unpacked::Finite(sign, q, k),
+        Rounded::Infinite => Unpacked::Inf(sign),
+    };
+    Ok(T::pack(v))
+}
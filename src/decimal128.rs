@@ -0,0 +1,86 @@
+//! Conversions between floating point numbers and the 128-bit decimals of
+//! Arrow and Parquet.
+//!
+//! A `DECIMAL128(precision, scale)` value is stored as an `i128` with at
+//! most `precision` decimal digits, representing `value * 10^-scale`.
+//! `precision` is between 1 and 38, and `scale` may be negative.
+//!
+//! Unlike multiplying by `10^scale` in floating point, the conversions of
+//! this module are correctly rounded (half to even) in both directions.
+//!
+//! # Example
+//!
+//! ```
+//! use flt2dec2flt::decimal128::{from_decimal128, to_decimal128};
+//! use flt2dec2flt::DecimalOverflow;
+//!
+//! // 1.005f64 is slightly below 1.005
+//! assert_eq!(to_decimal128(1.005f64, 10, 2), Ok(100));
+//! // 0.125 is exact, so it is rounded to even
+//! assert_eq!(to_decimal128(0.125f64, 10, 2), Ok(12));
+//! assert_eq!(to_decimal128(-1234.0f64, 5, -2), Ok(-12));
+//! assert_eq!(to_decimal128(1000.0f64, 5, 2), Err(DecimalOverflow));
+//! assert_eq!(to_decimal128(f64::NAN, 5, 2), Err(DecimalOverflow));
+//!
+//! assert_eq!(from_decimal128::<f64>(12345, 10, 2), Ok(123.45));
+//! assert_eq!(from_decimal128::<f32>(-7, 10, -30), Ok(-7e30));
+//! assert_eq!(from_decimal128::<f32>(1, 10, -39), Err(DecimalOverflow));
+//! ```
+
+use crate::scaled::{from_scaled, split_i128, to_scaled};
+use crate::{DecimalOverflow, FloatExt, RoundingMode};
+
+/// Largest precision of a `DECIMAL128`.
+pub const MAX_PRECISION: u8 = 38;
+
+fn max_magnitude(precision: u8) -> u128 {
+    assert!(
+        (1..=MAX_PRECISION).contains(&precision),
+        "invalid DECIMAL128 precision {}",
+        precision,
+    );
+    10u128.pow(u32::from(precision)) - 1
+}
+
+/// Converts `v` into a `DECIMAL128(precision, scale)`, rounding half to
+/// even.
+///
+/// Fails if `v` is NaN or infinite, or if the rounded value has more than
+/// `precision` digits.
+///
+/// Panics if `precision` is not between 1 and `MAX_PRECISION`.
+pub fn to_decimal128<T: FloatExt>(v: T, precision: u8, scale: i8) -> Result<i128, DecimalOverflow> {
+    let max = max_magnitude(precision);
+    let (sign, magnitude) =
+        to_scaled(v, i32::from(scale), RoundingMode::NearestEven).ok_or(DecimalOverflow)?;
+    if magnitude > max {
+        return Err(DecimalOverflow);
+    }
+    let value = magnitude as i128;
+    Ok(if sign { -value } else { value })
+}
+
+/// Converts a `DECIMAL128(precision, scale)` into the nearest floating
+/// point number (rounding half to even).
+///
+/// Fails if `value` has more than `precision` digits, or if it is too
+/// large for `T`.
+///
+/// Panics if `precision` is not between 1 and `MAX_PRECISION`.
+pub fn from_decimal128<T: FloatExt>(
+    value: i128,
+    precision: u8,
+    scale: i8,
+) -> Result<T, DecimalOverflow> {
+    let max = max_magnitude(precision);
+    let (sign, magnitude) = split_i128(value);
+    if magnitude > max {
+        return Err(DecimalOverflow);
+    }
+    let v: T = from_scaled(sign, magnitude, i64::from(scale));
+    if v.to_f64().is_infinite() {
+        Err(DecimalOverflow)
+    } else {
+        Ok(v)
+    }
+}
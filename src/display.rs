@@ -0,0 +1,198 @@
+//! Wrappers that format floating point numbers when displayed.
+//!
+//! `Shortest`, `Fixed` and `Exp` implement `core::fmt::Display`, writing
+//! with `WriteOptions::DEFAULT` directly into the formatter, without
+//! intermediate buffers for the text. With the `ufmt` feature, they also
+//! implement `ufmt::uDisplay` and `ufmt::uDebug`, which is the only way to
+//! print floats with `ufmt`.
+//!
+//! `DebugFloats` displays slices with `Shortest`, for dumping buffers in
+//! tests and logs without the 17 digits of `{:?}` per element.
+//!
+//! Formatting flags (such as width or precision) are ignored.
+//!
+//! # Example
+//!
+//! ```
+//! use flt2dec2flt::display::{DebugFloats, Exp, Fixed, Shortest};
+//!
+//! assert_eq!(format!("{}", Shortest(0.1f32)), "0.1");
+//! assert_eq!(format!("{}", Shortest(1.5e20f64)), "1.5e20");
+//! assert_eq!(format!("{}", Fixed(2.0f64 / 3.0, 3)), "0.667");
+//! assert_eq!(format!("{}", Exp(1234.5f64, 3)), "1.23e3");
+//!
+//! let values = [0.1f32 + 0.2, 1e-7, -3.0];
+//! assert_eq!(format!("{:?}", DebugFloats::new(&values)), "[0.3, 1e-7, -3]");
+//! let dump = DebugFloats {
+//!     separator: "\n",
+//!     indices: true,
+//!     ..DebugFloats::new(&values)
+//! };
+//! assert_eq!(format!("{}", dump), "0: 0.3\n1: 1e-7\n2: -3");
+//! ```
+
+use core::fmt;
+
+use crate::write::{minimal, positional, scientific, FmtOutput, Output, WriteOptions};
+use crate::{FloatExt, PREFORMAT_EXACT_FIXED_BASE_BUF_LEN, PREFORMAT_SHORTEST_BUF_LEN};
+
+/// Displays a number with the shortest representation that round-trips,
+/// in positional or scientific notation, whichever is shorter (see
+/// `write::write_minimal`).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Shortest<T>(pub T);
+
+/// Displays a number in positional notation, with the given number of
+/// fractional digits.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Fixed<T>(pub T, pub usize);
+
+/// Displays a number in scientific notation, with the given number of
+/// significant digits (at least one).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Exp<T>(pub T, pub usize);
+
+/// Displays a slice of numbers, each one as `Shortest`.
+///
+/// `Display` writes the numbers separated by `separator`, and `Debug`
+/// also encloses them in brackets.
+#[derive(Copy, Clone, PartialEq)]
+pub struct DebugFloats<'a, T> {
+    /// The numbers.
+    pub values: &'a [T],
+    /// Written between numbers.
+    pub separator: &'a str,
+    /// Whether each number is preceded by its index and `: `.
+    pub indices: bool,
+}
+
+impl<'a, T> DebugFloats<'a, T> {
+    /// Displays `values` separated by `, `, without indices.
+    pub fn new(values: &'a [T]) -> Self {
+        Self {
+            values,
+            separator: ", ",
+            indices: false,
+        }
+    }
+}
+
+impl<T: FloatExt> Shortest<T> {
+    fn write_to(&self, out: &mut impl Output) {
+        let mut buf = [0; PREFORMAT_SHORTEST_BUF_LEN];
+        let preformatted = self.0.preformat_shortest(&mut buf);
+        minimal(out, preformatted, &WriteOptions::DEFAULT);
+    }
+}
+
+impl<T: FloatExt> Fixed<T> {
+    fn write_to(&self, out: &mut impl Output) {
+        // Digits beyond the base length are always zeros, which are not
+        // stored in the buffer.
+        let mut buf = [0; PREFORMAT_EXACT_FIXED_BASE_BUF_LEN];
+        let preformatted = self
+            .0
+            .preformat_exact_fixed(&mut buf, self.1.min(isize::MAX as usize) as isize);
+        positional(out, preformatted, self.1, &WriteOptions::DEFAULT);
+    }
+}
+
+impl<T: FloatExt> Exp<T> {
+    fn write_to(&self, out: &mut impl Output) {
+        let num_digits = self.1.max(1);
+        let mut buf = [0; PREFORMAT_EXACT_FIXED_BASE_BUF_LEN];
+        let preformatted = self.0.preformat_exact_exp(&mut buf, num_digits);
+        scientific(out, preformatted, num_digits - 1, &WriteOptions::DEFAULT);
+    }
+}
+
+macro_rules! impl_display {
+    ($name:ident) => {
+        impl<T: FloatExt> fmt::Display for $name<T> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                let mut out = FmtOutput::new(f);
+                self.write_to(&mut out);
+                out.result
+            }
+        }
+
+        #[cfg(feature = "ufmt")]
+        impl<T: FloatExt> ::ufmt::uDisplay for $name<T> {
+            fn fmt<W>(&self, f: &mut ::ufmt::Formatter<'_, W>) -> Result<(), W::Error>
+            where
+                W: ::ufmt::uWrite + ?Sized,
+            {
+                let mut out = UfmtOutput {
+                    formatter: f,
+                    result: Ok(()),
+                };
+                self.write_to(&mut out);
+                out.result
+            }
+        }
+
+        #[cfg(feature = "ufmt")]
+        impl<T: FloatExt> ::ufmt::uDebug for $name<T> {
+            fn fmt<W>(&self, f: &mut ::ufmt::Formatter<'_, W>) -> Result<(), W::Error>
+            where
+                W: ::ufmt::uWrite + ?Sized,
+            {
+                ::ufmt::uDisplay::fmt(self, f)
+            }
+        }
+    };
+}
+
+impl_display!(Shortest);
+impl_display!(Fixed);
+impl_display!(Exp);
+
+impl<T: FloatExt> DebugFloats<'_, T> {
+    fn write_to(&self, out: &mut impl Output) {
+        for (i, &v) in self.values.iter().enumerate() {
+            if i != 0 {
+                out.push_str(self.separator);
+            }
+            if self.indices {
+                out.push_u64(i as u64);
+                out.push_str(": ");
+            }
+            Shortest(v).write_to(out);
+        }
+    }
+}
+
+impl<T: FloatExt> fmt::Display for DebugFloats<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut out = FmtOutput::new(f);
+        self.write_to(&mut out);
+        out.result
+    }
+}
+
+impl<T: FloatExt> fmt::Debug for DebugFloats<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut out = FmtOutput::new(f);
+        out.push(b'[');
+        self.write_to(&mut out);
+        out.push(b']');
+        out.result
+    }
+}
+
+/// Forwards bytes to a `ufmt::Formatter`, keeping the first error.
+#[cfg(feature = "ufmt")]
+struct UfmtOutput<'a, 'b, W: ::ufmt::uWrite + ?Sized> {
+    formatter: &'a mut ::ufmt::Formatter<'b, W>,
+    result: Result<(), W::Error>,
+}
+
+#[cfg(feature = "ufmt")]
+impl<W: ::ufmt::uWrite + ?Sized> Output for UfmtOutput<'_, '_, W> {
+    fn push_bytes(&mut self, s: &[u8]) {
+        if self.result.is_ok() {
+            // Only `str`s and ASCII bytes are written.
+            self.result = self.formatter.write_str(core::str::from_utf8(s).unwrap());
+        }
+    }
+}
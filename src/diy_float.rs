@@ -0,0 +1,134 @@
+//! Extended precision "do-it-yourself" floating point numbers.
+//!
+//! Grisu-family algorithms perform most of their work with a simple
+//! floating point type with a 64-bit significand and no hidden bit,
+//! together with a table of cached powers of ten. This module exposes
+//! both, so they can be reused to implement custom strategies.
+
+use crate::core_num;
+
+/// A floating point number with a 64-bit significand, representing `f * 2^e`.
+///
+/// There is no hidden bit, no special values and no sign.
+///
+/// # Example
+///
+/// ```
+/// use flt2dec2flt::diy_float::Fp;
+///
+/// let a = Fp { f: 3, e: 0 }.normalize();
+/// assert_eq!(a, Fp { f: 3 << 62, e: -62 });
+///
+/// // 0.75 * 2^64 * 2^-2 = 3 * 2^60, times 0.75 * 2^64 * 2^-62 = 3
+/// let b = a.mul(&a);
+/// assert_eq!(b, Fp { f: 9 << 60, e: -60 });
+/// assert_eq!(b.normalize(), Fp { f: 9 << 60, e: -60 });
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Fp {
+    /// The integer significand.
+    pub f: u64,
+    /// The exponent in base 2.
+    pub e: i16,
+}
+
+impl Fp {
+    /// Returns a correctly rounded (half-up) product of itself and `other`,
+    /// keeping the upper 64 bits of the product of the significands.
+    ///
+    /// When both operands are normalized, the significand of the result is
+    /// at least `2^62`.
+    pub fn mul(&self, other: &Fp) -> Fp {
+        core_num::diy_float::Fp::from(*self)
+            .mul((*other).into())
+            .into()
+    }
+
+    /// Normalizes itself so that the resulting significand is at least `2^63`.
+    ///
+    /// The significand must not be zero.
+    pub fn normalize(&self) -> Fp {
+        debug_assert!(self.f != 0);
+        core_num::diy_float::Fp::from(*self).normalize().into()
+    }
+
+    /// Normalizes itself to have the given exponent, which can only decrease
+    /// the exponent (and thus increase the significand).
+    ///
+    /// Returns `None` if `e` is greater than the current exponent or if the
+    /// significand would overflow.
+    pub fn normalize_to(&self, e: i16) -> Option<Fp> {
+        let edelta = i32::from(self.e) - i32::from(e);
+        if edelta < 0 {
+            None
+        } else if self.f == 0 {
+            Some(Fp { f: 0, e })
+        } else if edelta >= 64 || self.f.leading_zeros() < edelta as u32 {
+            None
+        } else {
+            Some(Fp {
+                f: self.f << edelta,
+                e,
+            })
+        }
+    }
+}
+
+impl From<Fp> for core_num::diy_float::Fp {
+    fn from(v: Fp) -> Self {
+        Self { f: v.f, e: v.e }
+    }
+}
+
+impl From<core_num::diy_float::Fp> for Fp {
+    fn from(v: core_num::diy_float::Fp) -> Self {
+        Self { f: v.f, e: v.e }
+    }
+}
+
+/// Smallest exponent of the normalized powers of ten returned by `cached_power`.
+pub const CACHED_POW10_FIRST_E: i16 = core_num::flt2dec::strategy::grisu::CACHED_POW10_FIRST_E;
+
+/// Largest exponent of the normalized powers of ten returned by `cached_power`.
+pub const CACHED_POW10_LAST_E: i16 = core_num::flt2dec::strategy::grisu::CACHED_POW10_LAST_E;
+
+/// Looks up a cached power of ten whose binary exponent lies within
+/// `alpha..=gamma`.
+///
+/// Returns `(k, c)` where `c` is normalized and equals `10^k` rounded to
+/// 64 significant bits. The table contains every eighth power of ten from
+/// `10^-308` to `10^332`, which means a power is always found when
+/// `gamma - alpha >= 27` and `gamma` is within `CACHED_POW10_FIRST_E..=CACHED_POW10_LAST_E`.
+/// Returns `None` otherwise.
+///
+/// This is the lookup Grisu uses to bring the scaled value into a
+/// convenient range, with `alpha = -60` and `gamma = -32`.
+///
+/// # Example
+///
+/// ```
+/// use flt2dec2flt::diy_float::{cached_power, Fp};
+///
+/// let (k, c) = cached_power(-60, -32).unwrap();
+/// assert_eq!(k, 4);
+/// assert_eq!(c, Fp { f: 10000 << 50, e: -50 });
+///
+/// assert!(cached_power(-20, -10).is_none());
+/// ```
+pub fn cached_power(alpha: i16, gamma: i16) -> Option<(i16, Fp)> {
+    use core_num::flt2dec::strategy::grisu::CACHED_POW10;
+
+    if !(CACHED_POW10_FIRST_E..=CACHED_POW10_LAST_E).contains(&gamma) || alpha > gamma {
+        return None;
+    }
+    let offset = i32::from(CACHED_POW10_FIRST_E);
+    let range = (CACHED_POW10.len() as i32) - 1;
+    let domain = i32::from(CACHED_POW10_LAST_E - CACHED_POW10_FIRST_E);
+    let idx = (i32::from(gamma) - offset) * range / domain;
+    let (f, e, k) = CACHED_POW10[idx as usize];
+    if alpha <= e && e <= gamma {
+        Some((k, Fp { f, e }))
+    } else {
+        None
+    }
+}
@@ -0,0 +1,279 @@
+//! Support for double-double numbers, the unevaluated sum of two `f64`.
+//!
+//! The value of a `DoubleDouble` is `hi + lo` rounded to 107 significant
+//! bits (half to even), which is exact when `lo` is within about 54 bits
+//! of `hi` (such as the results of `from_preparsed`). Its digits are
+//! generated from that value, with the rounding interval of a 107-bit
+//! format with the exponent range of `f64`, so the shortest digits are the
+//! fewest that round-trip through `from_preparsed`.
+//!
+//! Parsing rounds once, directly to 107 bits, and splits the result into
+//! `hi`, the nearest `f64`, and `lo`, the exact remainder.
+//!
+//! # Example
+//!
+//! ```
+//! use flt2dec2flt::double_double::{DoubleDouble, SHORTEST_BUF_LEN};
+//! use flt2dec2flt::{PreFormatted, PreParsed};
+//!
+//! let preparsed = PreParsed {
+//!     sign: false,
+//!     int_digits: b"0",
+//!     frac_digits: b"1",
+//!     exp: 0,
+//! };
+//! let v = DoubleDouble::from_preparsed(preparsed).unwrap();
+//! assert_eq!(v, DoubleDouble { hi: 0.1, lo: -5.551115123125783e-18 });
+//!
+//! let mut buf = [0; SHORTEST_BUF_LEN];
+//! assert_eq!(v.preformat_shortest(&mut buf), PreFormatted::Finite(false, b"1", 0, 0));
+//!
+//! // 1 + 2^-80
+//! let v = DoubleDouble { hi: 1.0, lo: 8.271806125530277e-25 };
+//! let mut buf = [0; 30];
+//! assert_eq!(
+//!     v.preformat_exact_exp(&mut buf, 30),
+//!     PreFormatted::Finite(false, b"100000000000000000000000082718", 0, 1),
+//! );
+//! ```
+
+use crate::wide::{self, Decoded, FullDecoded, Rounded};
+use crate::{ParseFloatError, PreFormatted, PreParsed};
+
+/// Minimum buffer size that has to be passed to
+/// `DoubleDouble::preformat_shortest`.
+pub const SHORTEST_BUF_LEN: usize = 34;
+
+/// Minimum base buffer size that has to be passed to
+/// `DoubleDouble::preformat_exact_fixed` (see
+/// `PREFORMAT_EXACT_FIXED_BASE_BUF_LEN`).
+// `wide::estimate_max_buf_len` of the smallest exponent (-1075).
+pub const EXACT_FIXED_BASE_BUF_LEN: usize = 846;
+
+const_assert!(40 + ((12 * 1075) >> 4) == EXACT_FIXED_BASE_BUF_LEN as i32);
+
+const FORMAT: wide::Format = wide::Format {
+    sig_bits: 107,
+    min_lsb_exp: -1074,
+    max_exp: 1023,
+};
+
+/// A double-double number, whose value is `hi + lo`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct DoubleDouble {
+    /// The high part.
+    pub hi: f64,
+    /// The low part.
+    pub lo: f64,
+}
+
+/// Splits the finite `v` into its sign, significand and exponent.
+fn split_f64(v: f64) -> (bool, u64, i32) {
+    let bits = v.to_bits();
+    let biased_exp = ((bits >> 52) & 0x7FF) as i32;
+    let frac = bits & ((1 << 52) - 1);
+    let (m, e) = if biased_exp == 0 {
+        (frac, -1074)
+    } else {
+        (frac | 1 << 52, biased_exp - 1075)
+    };
+    (bits >> 63 != 0, m, e)
+}
+
+/// Builds the `f64` `m * 2^e`, which must be representable.
+fn make_f64(sign: bool, mut m: u64, mut e: i32) -> f64 {
+    let sign_bit = u64::from(sign) << 63;
+    if m == 0 {
+        return f64::from_bits(sign_bit);
+    }
+    while m >> 53 != 0 {
+        debug_assert!(m & 1 == 0);
+        m >>= 1;
+        e += 1;
+    }
+    while m >> 52 == 0 && e > -1074 {
+        m <<= 1;
+        e -= 1;
+    }
+    let bits = if m >> 52 == 0 {
+        // Subnormal.
+        m
+    } else {
+        ((e + 1075) as u64) << 52 | (m & ((1 << 52) - 1))
+    };
+    f64::from_bits(sign_bit | bits)
+}
+
+/// Rounds `m * 2^e` half to even, keeping at most `sig_bits` bits and no
+/// bits below `2^min_lsb_exp`, and normalizes it to `sig_bits` bits
+/// unless that would go below `2^min_lsb_exp`.
+fn round_bits(m: u128, e: i32, sig_bits: u32, min_lsb_exp: i32) -> (u128, i32) {
+    let bits = (128 - m.leading_zeros()) as i32;
+    let shift = (bits - sig_bits as i32).max(min_lsb_exp - e);
+    if shift <= 0 {
+        return (m << -shift, e + shift);
+    }
+    let q = m >> shift;
+    let rem = m & ((1 << shift) - 1);
+    let half = 1 << (shift - 1);
+    let q = if rem > half || (rem == half && q & 1 != 0) {
+        q + 1
+    } else {
+        q
+    };
+    if q >> sig_bits != 0 {
+        (q >> 1, e + shift + 1)
+    } else {
+        (q, e + shift)
+    }
+}
+
+impl DoubleDouble {
+    fn decode(self) -> (bool, FullDecoded) {
+        let (hi, lo) = (self.hi, self.lo);
+        if hi.is_nan()
+            || lo.is_nan()
+            || (hi.is_infinite()
+                && lo.is_infinite()
+                && hi.is_sign_negative() != lo.is_sign_negative())
+        {
+            return (false, FullDecoded::Nan);
+        } else if hi.is_infinite() {
+            return (hi.is_sign_negative(), FullDecoded::Infinite);
+        } else if lo.is_infinite() {
+            return (lo.is_sign_negative(), FullDecoded::Infinite);
+        }
+
+        let (hi_sign, hi_m, hi_e) = split_f64(hi);
+        let (lo_sign, lo_m, lo_e) = split_f64(lo);
+        if hi_m == 0 && lo_m == 0 {
+            return (hi_sign && lo_sign, FullDecoded::Zero);
+        }
+        // Order by magnitude, so the exponent of `big` is not smaller.
+        let abs_bits = |v: f64| v.to_bits() & !(1 << 63);
+        let ((sign, big_m, big_e), (small_sign, small_m, small_e)) = if abs_bits(hi) >= abs_bits(lo)
+        {
+            ((hi_sign, hi_m, hi_e), (lo_sign, lo_m, lo_e))
+        } else {
+            ((lo_sign, lo_m, lo_e), (hi_sign, hi_m, hi_e))
+        };
+
+        // With an extra bit, the bits of `small` that do not fit (more
+        // than 71 bits below `big`) are replaced by a 1. The sum is then
+        // strictly between the same rounding points as the exact sum.
+        let gap = big_e - small_e;
+        let shift = gap.min(71);
+        let e = big_e - shift - 1;
+        let big = u128::from(big_m) << (shift + 1);
+        let small = if gap <= 71 {
+            u128::from(small_m) << (small_e - e)
+        } else {
+            let lost = gap - 71;
+            let (kept, sticky) = if lost >= 64 {
+                (0, small_m != 0)
+            } else {
+                (small_m >> lost, small_m & ((1 << lost) - 1) != 0)
+            };
+            u128::from(kept) << 1 | u128::from(sticky)
+        };
+        let m = if sign == small_sign {
+            big + small
+        } else {
+            big - small
+        };
+        if m == 0 {
+            return (false, FullDecoded::Zero);
+        }
+
+        let (q, k) = round_bits(m, e, FORMAT.sig_bits, FORMAT.min_lsb_exp);
+        let lower_closer = q == 1 << (FORMAT.sig_bits - 1) && k > FORMAT.min_lsb_exp;
+        (sign, FullDecoded::Finite(Decoded::new(q, k, lower_closer)))
+    }
+
+    /// Pre-formats `self` with the lowest number of significant digits
+    /// that round-trip, like `FloatExt::preformat_shortest`.
+    ///
+    /// `buf` must be at least `SHORTEST_BUF_LEN` long.
+    pub fn preformat_shortest(self, buf: &mut [u8]) -> PreFormatted<'_> {
+        let (sign, full_decoded) = self.decode();
+        wide::preformat_shortest(sign, full_decoded, buf)
+    }
+
+    /// Pre-formats `self` with an exact number of significant digits, like
+    /// `FloatExt::preformat_exact_exp`.
+    ///
+    /// `buf` must be at least `num_digits` long (or
+    /// `EXACT_FIXED_BASE_BUF_LEN`, if that is smaller), and `num_digits`
+    /// must not be zero.
+    pub fn preformat_exact_exp(self, buf: &mut [u8], num_digits: usize) -> PreFormatted<'_> {
+        let (sign, full_decoded) = self.decode();
+        wide::preformat_exact_exp(sign, full_decoded, buf, num_digits)
+    }
+
+    /// Pre-formats `self` with an exact number of fractional digits, like
+    /// `FloatExt::preformat_exact_fixed`.
+    ///
+    /// `buf` must be at least `EXACT_FIXED_BASE_BUF_LEN` long, whatever
+    /// `num_frac_digits` is.
+    pub fn preformat_exact_fixed(self, buf: &mut [u8], num_frac_digits: isize) -> PreFormatted<'_> {
+        let (sign, full_decoded) = self.decode();
+        wide::preformat_exact_fixed(sign, full_decoded, buf, num_frac_digits)
+    }
+
+    /// Pre-formats `self` with the digits above `10^limit`, like
+    /// `FloatExt::preformat_exact_limit`.
+    ///
+    /// `buf` must be at least `EXACT_FIXED_BASE_BUF_LEN` long.
+    pub fn preformat_exact_limit(self, buf: &mut [u8], limit: i16) -> PreFormatted<'_> {
+        let (sign, full_decoded) = self.decode();
+        wide::preformat_exact_limit(sign, full_decoded, buf, limit)
+    }
+
+    /// Converts a pre-parsed decimal into the nearest 107-bit number
+    /// (rounding half to even), split into `hi` and `lo`.
+    ///
+    /// Numbers whose `hi` overflows become infinity (with a zero `lo`).
+    /// Any number of digits is accepted.
+    pub fn from_preparsed(preparsed: PreParsed<'_>) -> Result<Self, ParseFloatError> {
+        let sign = preparsed.sign;
+        let zero = make_f64(sign, 0, 0);
+        let infinity = Self {
+            hi: if sign {
+                f64::NEG_INFINITY
+            } else {
+                f64::INFINITY
+            },
+            lo: zero,
+        };
+        let (q, k) = match wide::from_preparsed(preparsed, &FORMAT)? {
+            Rounded::Zero => return Ok(Self { hi: zero, lo: zero }),
+            Rounded::Finite(q, k) => (q, k),
+            Rounded::Infinite => return Ok(infinity),
+        };
+
+        let (hi_m, hi_e) = round_bits(q, k, 53, -1074);
+        if hi_e + 52 > 1023 {
+            return Ok(infinity);
+        }
+        // `|q - hi_m * 2^(hi_e - k)|` is at most half a unit of `hi`, so
+        // it fits in 53 bits.
+        let hi_q = if hi_e >= k {
+            hi_m << (hi_e - k)
+        } else {
+            hi_m >> (k - hi_e)
+        };
+        let (lo_sign, lo_m) = if q >= hi_q {
+            (sign, q - hi_q)
+        } else {
+            (!sign, hi_q - q)
+        };
+        Ok(Self {
+            hi: make_f64(sign, hi_m as u64, hi_e),
+            lo: if lo_m == 0 {
+                zero
+            } else {
+                make_f64(lo_sign, lo_m as u64, k)
+            },
+        })
+    }
+}
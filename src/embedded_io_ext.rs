@@ -0,0 +1,129 @@
+//! Writing into `embedded_io::Write` sinks, such as UARTs or flash logs.
+//!
+//! Requires the `embedded-io` feature, which needs Rust 1.60 or newer.
+//!
+//! The text is assembled in chunks of `CHUNK_LEN` bytes on the stack and
+//! passed to `embedded_io::Write::write_all` as each chunk fills up, so
+//! the length of the text is not bounded by any buffer. The extra zeros of
+//! a `PreFormatted` are generated while writing, so a number like `1e300`
+//! in positional notation only needs the buffer of its significant digits.
+//!
+//! # Example
+//!
+//! ```
+//! use flt2dec2flt::embedded_io_ext::{write_positional, write_shortest};
+//! use flt2dec2flt::write::WriteOptions;
+//! use flt2dec2flt::FloatExt as _;
+//!
+//! let mut uart = [0u8; 64];
+//! let mut out = &mut uart[..];
+//! write_shortest(&mut out, 1.5e-7f64).unwrap();
+//! let len = 64 - out.len();
+//! assert_eq!(&uart[..len], b"1.5e-7");
+//!
+//! let mut log = [0u8; 64];
+//! let mut out = &mut log[..];
+//! let mut buf = [0; 2];
+//! let preformatted = 1e40f64.preformat_exact_exp(&mut buf, 2);
+//! write_positional(&mut out, preformatted, 1, &WriteOptions::DEFAULT).unwrap();
+//! let len = 64 - out.len();
+//! assert_eq!(&log[..len], &b"10000000000000000000000000000000000000000.0"[..]);
+//! ```
+
+use ::embedded_io::Write;
+
+use crate::write::{minimal, positional, scientific, Output, WriteOptions};
+use crate::{FloatExt, PreFormatted};
+
+/// The size of the chunks passed to `embedded_io::Write::write_all`. Only
+/// the last one can be shorter.
+pub const CHUNK_LEN: usize = 32;
+
+/// Collects bytes into chunks, keeping the first error.
+struct IoOutput<'a, W: Write + ?Sized> {
+    writer: &'a mut W,
+    chunk: [u8; CHUNK_LEN],
+    len: usize,
+    result: Result<(), W::Error>,
+}
+
+impl<'a, W: Write + ?Sized> IoOutput<'a, W> {
+    fn new(writer: &'a mut W) -> Self {
+        Self {
+            writer,
+            chunk: [0; CHUNK_LEN],
+            len: 0,
+            result: Ok(()),
+        }
+    }
+
+    /// Writes the last chunk.
+    fn finish(mut self) -> Result<(), W::Error> {
+        if self.result.is_ok() && self.len != 0 {
+            self.result = self.writer.write_all(&self.chunk[..self.len]);
+        }
+        self.result
+    }
+}
+
+impl<W: Write + ?Sized> Output for IoOutput<'_, W> {
+    fn push_bytes(&mut self, mut s: &[u8]) {
+        while self.result.is_ok() && !s.is_empty() {
+            let n = s.len().min(CHUNK_LEN - self.len);
+            self.chunk[self.len..(self.len + n)].copy_from_slice(&s[..n]);
+            self.len += n;
+            s = &s[n..];
+            if self.len == CHUNK_LEN {
+                self.result = self.writer.write_all(&self.chunk);
+                self.len = 0;
+            }
+        }
+    }
+}
+
+/// Writes `preformatted` like `write::write_positional`.
+pub fn write_positional<W: Write + ?Sized>(
+    writer: &mut W,
+    preformatted: PreFormatted<'_>,
+    min_frac_digits: usize,
+    options: &WriteOptions<'_>,
+) -> Result<(), W::Error> {
+    let mut out = IoOutput::new(writer);
+    positional(&mut out, preformatted, min_frac_digits, options);
+    out.finish()
+}
+
+/// Writes `preformatted` like `write::write_scientific`.
+pub fn write_scientific<W: Write + ?Sized>(
+    writer: &mut W,
+    preformatted: PreFormatted<'_>,
+    min_frac_digits: usize,
+    options: &WriteOptions<'_>,
+) -> Result<(), W::Error> {
+    let mut out = IoOutput::new(writer);
+    scientific(&mut out, preformatted, min_frac_digits, options);
+    out.finish()
+}
+
+/// Writes `preformatted` like `write::write_minimal`.
+pub fn write_minimal<W: Write + ?Sized>(
+    writer: &mut W,
+    preformatted: PreFormatted<'_>,
+    options: &WriteOptions<'_>,
+) -> Result<(), W::Error> {
+    let mut out = IoOutput::new(writer);
+    minimal(&mut out, preformatted, options);
+    out.finish()
+}
+
+/// Writes `v` with the shortest representation that round-trips, in
+/// positional or scientific notation, whichever is shorter (see
+/// `write::write_minimal`).
+pub fn write_shortest<W: Write + ?Sized, T: FloatExt>(
+    writer: &mut W,
+    v: T,
+) -> Result<(), W::Error> {
+    let mut buf = [0; crate::PREFORMAT_SHORTEST_BUF_LEN];
+    let preformatted = v.preformat_shortest(&mut buf);
+    write_minimal(writer, preformatted, &WriteOptions::DEFAULT)
+}
@@ -0,0 +1,196 @@
+use core::fmt;
+
+/// An error which can be returned when converting a `PreParsed` into
+/// a floating point number.
+///
+/// Returned by `FloatExt::from_preparsed`.
+///
+/// Numbers too large or too small to be represented are not errors: they
+/// become infinity or zero. `FloatExt::from_preparsed_report` tells them
+/// apart from exact infinities and zeros.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ParseFloatError {
+    /// Both `int_digits` and `frac_digits` are empty.
+    EmptyDigits,
+    /// A byte of `int_digits` or `frac_digits` is not an ASCII decimal
+    /// digit.
+    InvalidDigit {
+        /// The offending byte.
+        byte: u8,
+        /// The position of the byte, counting the bytes of `int_digits`
+        /// followed by the bytes of `frac_digits`.
+        index: usize,
+    },
+    /// The exponent, combined with the number of digits, is outside the
+    /// range the conversion can handle.
+    ExponentOutOfRange,
+}
+
+impl fmt::Display for ParseFloatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            ParseFloatError::EmptyDigits => f.write_str("no digits to parse"),
+            ParseFloatError::InvalidDigit { byte, index } => {
+                write!(f, "invalid digit {:?} at index {}", char::from(byte), index)
+            }
+            ParseFloatError::ExponentOutOfRange => f.write_str("exponent out of range"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseFloatError {}
+
+/// An error returned when a buffer is too small to hold a written number.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct BufferTooSmall {
+    /// The number of bytes needed.
+    pub required: usize,
+    /// The length of the provided buffer.
+    pub actual: usize,
+}
+
+impl fmt::Display for BufferTooSmall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "buffer too small: needs {} bytes, got {}",
+            self.required, self.actual
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for BufferTooSmall {}
+
+/// An error returned by the `FloatExt::try_preformat_*` methods when their
+/// arguments do not meet the requirements of the panicking versions.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PreformatError {
+    /// The buffer is shorter than required.
+    BufferTooSmall(BufferTooSmall),
+    /// Zero significant digits were requested.
+    ZeroDigits,
+    /// The digits found by the shortest-mode strategy differ from those
+    /// found by Dragon (see `FloatExt::preformat_shortest_checked`).
+    StrategyMismatch,
+}
+
+impl From<BufferTooSmall> for PreformatError {
+    fn from(e: BufferTooSmall) -> Self {
+        PreformatError::BufferTooSmall(e)
+    }
+}
+
+impl fmt::Display for PreformatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            PreformatError::BufferTooSmall(ref e) => fmt::Display::fmt(e, f),
+            PreformatError::ZeroDigits => f.write_str("zero significant digits requested"),
+            PreformatError::StrategyMismatch => {
+                f.write_str("shortest-mode strategy disagrees with the reference")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PreformatError {}
+
+/// An error returned when a text does not match a `Grammar`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SyntaxError {
+    /// The position of the first byte that could not be parsed, which is
+    /// the length of the text if it ended unexpectedly.
+    pub index: usize,
+}
+
+impl fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid number syntax at index {}", self.index)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SyntaxError {}
+
+/// An error returned when a number does not fit in a decimal type (or a
+/// decimal does not fit in a floating point type).
+///
+/// NaN and infinities never fit in a decimal type.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct DecimalOverflow;
+
+impl fmt::Display for DecimalOverflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("number out of range of the decimal type")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DecimalOverflow {}
+
+/// An error returned by `verify_tables` when a lookup table does not hold
+/// the expected values.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TableCorruption {
+    /// The name of the table.
+    pub table: &'static str,
+    /// The index of the first wrong entry, if known. Tables checked with
+    /// a checksum do not tell which entry is wrong.
+    pub index: Option<usize>,
+}
+
+impl fmt::Display for TableCorruption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "lookup table {} is corrupted", self.table)?;
+        if let Some(index) = self.index {
+            write!(f, " at index {}", index)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TableCorruption {}
+
+/// An error returned when scanning a text of numbers (see the `scan`
+/// module).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ScanError {
+    /// The position of the first byte of the offending token.
+    pub index: usize,
+    /// What went wrong with the token.
+    pub kind: ScanErrorKind,
+}
+
+/// The kind of a `ScanError`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ScanErrorKind {
+    /// The token is not a number.
+    Syntax,
+    /// The token is a number that cannot be converted.
+    Parse(ParseFloatError),
+    /// The token is a number, but there is no room left for it.
+    Full,
+    /// The field is empty: a delimiter follows another one, or starts the
+    /// text.
+    Empty,
+}
+
+impl fmt::Display for ScanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            ScanErrorKind::Syntax => write!(f, "invalid number at index {}", self.index),
+            ScanErrorKind::Parse(ref e) => write!(f, "{} in number at index {}", e, self.index),
+            ScanErrorKind::Full => write!(f, "no room for number at index {}", self.index),
+            ScanErrorKind::Empty => write!(f, "empty field at index {}", self.index),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ScanError {}
@@ -0,0 +1,115 @@
+use crate::generic::abs;
+use crate::interval::exact_digits;
+use crate::{FloatExt, PreFormatted, PREFORMAT_EXACT_FIXED_BASE_BUF_LEN};
+
+/// Implementation of `FloatExt::preformat_exact_exp_with_error`.
+pub(crate) fn preformat_exact_exp_with_error<T: FloatExt>(
+    v: T,
+    buf: &mut [u8],
+    num_digits: usize,
+) -> (PreFormatted<'_>, u64) {
+    let preformatted = v.preformat_exact_exp(buf, num_digits);
+    let (digits, num_zeros, exp) = match preformatted {
+        PreFormatted::Finite(_, digits, num_zeros, exp) => (digits, num_zeros, i32::from(exp)),
+        _ => return (preformatted, 0),
+    };
+    let len = digits.len() + num_zeros;
+    let digit = |i: usize| digits.get(i).copied().unwrap_or(b'0');
+
+    // The exact value.
+    let v = abs(v.to_f64());
+    let mut v_buf = [0; PREFORMAT_EXACT_FIXED_BASE_BUF_LEN];
+    let (v_len, v_exp) = exact_digits(v, &mut v_buf);
+    let v_digits = &v_buf[..v_len];
+    let v_digit = |i: usize| v_digits.get(i).copied().unwrap_or(b'0');
+
+    // Distance from the exact value to the result, as a fraction of the
+    // last digit of the result (at most one half), in `delta_buf`.
+    let mut delta_buf = [0; PREFORMAT_EXACT_FIXED_BASE_BUF_LEN + 1];
+    let tail = v_digits.get(len..).unwrap_or(&[]);
+    let delta = if exp == v_exp && (0..len).all(|i| digit(i) == v_digit(i)) {
+        // Rounded down.
+        delta_buf[..tail.len()].copy_from_slice(tail);
+        &delta_buf[..tail.len()]
+    } else {
+        // Rounded up, `1 - 0.tail`, divided by 10 when the rounding carried
+        // into a new leading digit.
+        let start = (exp - v_exp) as usize;
+        let delta = &mut delta_buf[..start + tail.len()];
+        if start != 0 {
+            delta[0] = b'0';
+        }
+        for (d, &t) in delta[start..].iter_mut().zip(tail) {
+            *d = b'9' - t + b'0';
+        }
+        *delta.last_mut().unwrap() += 1;
+        delta
+    };
+
+    // Half of the distance to the next float, the largest distance from
+    // `v` to a number that rounds to it, is `2^(ulp_exp - 1)`.
+    let ulp_exp =
+        (((v.to_bits() >> 52) as i32 - 1023) - (T::SIG_BITS as i32 - 1)).max(T::MIN_LSB_EXP);
+    let ulp = if ulp_exp >= -1022 {
+        f64::from_bits(((ulp_exp + 1023) as u64) << 52)
+    } else {
+        f64::from_bits(1 << (ulp_exp + 1074))
+    };
+    let mut half_ulp_buf = [0; PREFORMAT_EXACT_FIXED_BASE_BUF_LEN + 1];
+    let (ulp_len, ulp_exp) = exact_digits(ulp, &mut half_ulp_buf[1..]);
+    // 2^(ulp_exp - 1) = 2^ulp_exp * 5 / 10
+    let mut carry = 0;
+    for d in half_ulp_buf[1..=ulp_len].iter_mut().rev() {
+        let x = (*d - b'0') * 5 + carry;
+        *d = x % 10 + b'0';
+        carry = x / 10;
+    }
+    half_ulp_buf[0] = carry + b'0';
+    let (half_ulp, half_ulp_exp) = if carry == 0 {
+        (&half_ulp_buf[1..=ulp_len], ulp_exp - 1)
+    } else {
+        (&half_ulp_buf[..=ulp_len], ulp_exp)
+    };
+
+    // The half ULP in units of the last digit of the result, split into
+    // its integer and fractional parts.
+    let int_len = half_ulp_exp - exp + len as i32;
+    if int_len > 20 {
+        return (preformatted, u64::MAX);
+    }
+    let (int_part, frac_part, frac_zeros) = if int_len >= 0 {
+        let (int_part, frac_part) = half_ulp.split_at((int_len as usize).min(half_ulp.len()));
+        (int_part, frac_part, 0)
+    } else {
+        (&[][..], half_ulp, -int_len as usize)
+    };
+    let int_value = int_part
+        .iter()
+        .chain(core::iter::repeat(&b'0'))
+        .take(int_len.max(0) as usize)
+        .try_fold(0u64, |acc, &d| {
+            acc.checked_mul(10)?.checked_add(u64::from(d - b'0'))
+        });
+    let int_value = match int_value {
+        Some(int_value) => int_value,
+        None => return (preformatted, u64::MAX),
+    };
+
+    // Rounds up the sum of both fractions.
+    let frac_digit = |i: usize| {
+        i.checked_sub(frac_zeros)
+            .and_then(|i| frac_part.get(i))
+            .map_or(0, |&d| d - b'0')
+    };
+    let delta_digit = |i: usize| delta.get(i).map_or(0, |&d| d - b'0');
+    let mut carry = 0;
+    let mut nonzero = false;
+    for i in (0..(frac_zeros + frac_part.len()).max(delta.len())).rev() {
+        let x = frac_digit(i) + delta_digit(i) + carry;
+        nonzero |= x % 10 != 0;
+        carry = x / 10;
+    }
+    let frac_value = u64::from(carry) + u64::from(nonzero);
+
+    (preformatted, int_value.saturating_add(frac_value))
+}
@@ -0,0 +1,106 @@
+//! Exponent estimation between base 2 and base 10.
+//!
+//! Conversion algorithms need to know, before doing any expensive work,
+//! the approximate decimal exponent of a binary float (to scale it) or the
+//! approximate binary exponent of a decimal number (to choose a table
+//! entry or a big integer size). These helpers are correct by construction
+//! and only use integer arithmetic.
+//!
+//! * `estimate_scaling_factor`: the decimal exponent of `mant * 2^exp`,
+//!   off by at most one.
+//! * `estimate_decimal_exponent`: the decimal exponents bounding every
+//!   value in a binary octave, one or two apart.
+//! * `estimate_binary_exponent`: the binary exponents bounding every value
+//!   in a decimal decade, four or five apart.
+
+use crate::core_num;
+
+/// `floor(2^32 * log_2 10)`
+const LOG2_10_Q32: i64 = 14_267_572_527;
+
+/// `floor(2^32 * log_10 2)`
+const LOG10_2_Q32: i64 = 1_292_913_986;
+
+/// Estimates the decimal exponent of `mant * 2^exp`.
+///
+/// Returns `k_0` such that `10^(k_0 - 1) < mant * 2^exp <= 10^(k_0 + 1)`.
+/// In other words, the true `k = ceil(log_10 (mant * 2^exp))` is either
+/// `k_0` or `k_0 + 1`.
+///
+/// `mant` must not be zero.
+///
+/// # Example
+///
+/// ```
+/// use flt2dec2flt::estimator::estimate_scaling_factor;
+///
+/// // 1000 = 1000 * 2^0, k = 3
+/// let k0 = estimate_scaling_factor(1000, 0);
+/// assert!(k0 == 2 || k0 == 3);
+///
+/// // 2^-20 = 9.5367431640625e-7, k = -6
+/// let k0 = estimate_scaling_factor(1, -20);
+/// assert!(k0 == -7 || k0 == -6);
+/// ```
+pub fn estimate_scaling_factor(mant: u64, exp: i16) -> i16 {
+    assert!(mant != 0);
+    core_num::flt2dec::estimator::estimate_scaling_factor(mant, exp)
+}
+
+/// Estimates the decimal exponent of a value with a known binary exponent.
+///
+/// Given `e`, returns `(k_lo, k_hi)` such that every `v` with
+/// `2^(e - 1) <= v <= 2^e` satisfies `10^k_lo <= v <= 10^k_hi`.
+/// The range is tight: `k_lo = floor((e - 1) * log_10 2)` and
+/// `k_hi = ceil(e * log_10 2)`, so `k_hi - k_lo` is 1 or 2 (1 when the
+/// octave does not contain a power of ten).
+///
+/// # Example
+///
+/// ```
+/// use flt2dec2flt::estimator::estimate_decimal_exponent;
+///
+/// // 512 <= v <= 1024 implies 100 <= v <= 10^4
+/// assert_eq!(estimate_decimal_exponent(10), (2, 4));
+///
+/// // 2^-7 <= v <= 2^-6 implies 0.001 <= v <= 0.1
+/// assert_eq!(estimate_decimal_exponent(-6), (-3, -1));
+///
+/// // 16 <= v <= 32 implies 10 <= v <= 100
+/// assert_eq!(estimate_decimal_exponent(5), (1, 2));
+/// ```
+pub fn estimate_decimal_exponent(e: i16) -> (i32, i32) {
+    let e = i64::from(e);
+    // As in `estimate_binary_exponent`, the floor is exact for all `e` in
+    // the `i16` range.
+    let k_lo = ((e - 1) * LOG10_2_Q32) >> 32;
+    let k_hi = -((-e * LOG10_2_Q32) >> 32);
+    (k_lo as i32, k_hi as i32)
+}
+
+/// Estimates the binary exponent of a value with a known decimal exponent.
+///
+/// Given `k`, returns `(e_lo, e_hi)` such that every `v` with
+/// `10^(k - 1) <= v <= 10^k` satisfies `2^e_lo <= v <= 2^e_hi`.
+/// This is the inverse of `estimate_scaling_factor`, and the range is
+/// tight: `e_lo = floor((k - 1) * log_2 10)` and `e_hi = ceil(k * log_2 10)`.
+///
+/// # Example
+///
+/// ```
+/// use flt2dec2flt::estimator::estimate_binary_exponent;
+///
+/// // 100 <= v <= 1000 implies 2^6 <= v <= 2^10
+/// assert_eq!(estimate_binary_exponent(3), (6, 10));
+///
+/// // 0.001 <= v <= 0.01 implies 2^-10 <= v <= 2^-6
+/// assert_eq!(estimate_binary_exponent(-2), (-10, -6));
+/// ```
+pub fn estimate_binary_exponent(k: i16) -> (i32, i32) {
+    let k = i64::from(k);
+    // The fixed-point approximation of `log_2 10` is accurate enough
+    // that the floor is exact for all `k` in the `i16` range.
+    let e_lo = ((k - 1) * LOG2_10_Q32) >> 32;
+    let e_hi = -((-k * LOG2_10_Q32) >> 32);
+    (e_lo as i32, e_hi as i32)
+}
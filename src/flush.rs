@@ -0,0 +1,85 @@
+//! Flushing tiny numbers to zero when formatting.
+//!
+//! Results such as `-0.00` or `1e-17` are usually noise when displaying
+//! computed values. A `FlushToZero` replaces the pre-formatted numbers
+//! whose magnitude is below a decimal threshold (compared exactly) with
+//! zero, optionally dropping their sign.
+//!
+//! # Example
+//!
+//! ```
+//! use flt2dec2flt::flush::FlushToZero;
+//! use flt2dec2flt::write::{write_positional, WriteOptions};
+//! use flt2dec2flt::{FloatExt as _, PreParsed};
+//!
+//! let flush = FlushToZero {
+//!     // 0.005
+//!     threshold: PreParsed {
+//!         sign: false,
+//!         int_digits: b"",
+//!         frac_digits: b"5",
+//!         exp: -2,
+//!     },
+//!     signed_zero: false,
+//! };
+//!
+//! let mut digits_buf = [0; flt2dec2flt::PREFORMAT_EXACT_FIXED_BASE_BUF_LEN + 2];
+//! let mut buf = [0; 32];
+//!
+//! let v = -0.001;
+//! let preformatted = flush.apply(v, v.preformat_exact_fixed(&mut digits_buf, 2));
+//! assert_eq!(write_positional(&mut buf, preformatted, 2, &WriteOptions::DEFAULT), Ok("0.00"));
+//!
+//! let v = 1.0 - 0.9 - 0.1;
+//! let preformatted = flush.apply(v, v.preformat_shortest(&mut digits_buf));
+//! assert_eq!(write_positional(&mut buf, preformatted, 0, &WriteOptions::DEFAULT), Ok("0"));
+//!
+//! let v = -0.005;
+//! let preformatted = flush.apply(v, v.preformat_shortest(&mut digits_buf));
+//! assert_eq!(write_positional(&mut buf, preformatted, 0, &WriteOptions::DEFAULT), Ok("-0.005"));
+//! ```
+
+use core::cmp::Ordering;
+
+use crate::clamp::cmp_exact;
+use crate::generic::abs;
+use crate::{FloatExt, PreFormatted, PreParsed};
+
+/// Flushes numbers whose magnitude is below a threshold to zero.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FlushToZero<'a> {
+    /// The smallest magnitude that is not flushed. Its sign is ignored.
+    pub threshold: PreParsed<'a>,
+    /// Whether the zero keeps the sign of the number. Otherwise, it is
+    /// always positive.
+    pub signed_zero: bool,
+}
+
+impl FlushToZero<'_> {
+    /// Returns whether the exact magnitude of `v` is below the threshold.
+    ///
+    /// NaN and infinities are never flushed, but zeros are.
+    pub fn flushes<T: FloatExt>(&self, v: T) -> bool {
+        let threshold = PreParsed {
+            sign: false,
+            ..self.threshold
+        };
+        cmp_exact(&threshold, abs(v.to_f64())) == Some(Ordering::Greater)
+    }
+
+    /// Returns `preformatted` (which has to be a pre-formatted `v`), or zero
+    /// if `v` is flushed.
+    ///
+    /// A zero `preformatted` (such as `v` rounded to a few fractional
+    /// digits) loses its sign when `signed_zero` is `false`, even if `v`
+    /// is not flushed.
+    pub fn apply<'b, T: FloatExt>(&self, v: T, preformatted: PreFormatted<'b>) -> PreFormatted<'b> {
+        match preformatted {
+            PreFormatted::Zero(sign) => PreFormatted::Zero(sign && self.signed_zero),
+            PreFormatted::Finite(sign, ..) if self.flushes(v) => {
+                PreFormatted::Zero(sign && self.signed_zero)
+            }
+            other => other,
+        }
+    }
+}
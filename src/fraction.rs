@@ -0,0 +1,138 @@
+//! Simplest fractions of floating point numbers.
+//!
+//! # Example
+//!
+//! ```
+//! use flt2dec2flt::fraction::{simplest_fraction, Fraction};
+//!
+//! assert_eq!(
+//!     simplest_fraction(1.0f64 / 3.0),
+//!     Some(Fraction { sign: false, num: 1, den: 3 })
+//! );
+//! assert_eq!(
+//!     simplest_fraction(-0.1f32),
+//!     Some(Fraction { sign: true, num: 1, den: 10 })
+//! );
+//! assert_eq!(
+//!     simplest_fraction(core::f64::consts::PI),
+//!     Some(Fraction { sign: false, num: 245850922, den: 78256779 })
+//! );
+//! ```
+
+use crate::bignum::Big32x40;
+use crate::core_num::flt2dec::decoder::FullDecoded;
+use crate::generic::decode_scale2;
+use crate::FloatExt;
+
+/// A fraction `sign num / den`, in lowest terms.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Fraction {
+    /// Whether the fraction is negative.
+    pub sign: bool,
+    /// The numerator.
+    pub num: u128,
+    /// The denominator, which is never zero.
+    pub den: u128,
+}
+
+/// Returns the simplest fraction (the one with the smallest denominator,
+/// and then the smallest numerator) that rounds to `v`.
+///
+/// The fraction is found with a walk of the Stern–Brocot tree within the
+/// rounding interval of `v`, with the bounds included when `v` is even as
+/// when parsing. Zero is `0 / 1`.
+///
+/// Returns `None` if `v` is NaN or infinite, or if the numerator or the
+/// denominator do not fit in a `u128` (such as for very large or very
+/// small values).
+pub fn simplest_fraction<T: FloatExt>(v: T) -> Option<Fraction> {
+    let (sign, decoded) = match decode_scale2(v, 0) {
+        (_, FullDecoded::Nan) | (_, FullDecoded::Infinite) => return None,
+        (sign, FullDecoded::Zero) => {
+            return Some(Fraction {
+                sign,
+                num: 0,
+                den: 1,
+            })
+        }
+        (sign, FullDecoded::Finite(decoded)) => (sign, decoded),
+    };
+
+    // The bounds are `(mant -/+ minus/plus) * 2^exp`.
+    let mut lo_num = Big32x40::from_u64(decoded.mant - decoded.minus);
+    let mut hi_num = Big32x40::from_u64(decoded.mant + decoded.plus);
+    let mut den = Big32x40::from_u32(1);
+    if decoded.exp >= 0 {
+        lo_num.mul_pow2(decoded.exp as usize);
+        hi_num.mul_pow2(decoded.exp as usize);
+    } else {
+        den.mul_pow2(-i32::from(decoded.exp) as usize);
+    }
+    let mut lo = (lo_num, den.clone(), decoded.inclusive);
+    // `None` when the upper bound is infinite.
+    let mut hi = Some((hi_num, den, decoded.inclusive));
+
+    // Convergents of the continued fraction `a0 + 1 / (a1 + 1 / ...)`.
+    let (mut num, mut prev_num) = (1u128, 0u128);
+    let (mut den, mut prev_den) = (0u128, 1u128);
+    loop {
+        let (lo_num, lo_den, lo_inclusive) = lo;
+        let (a, lo_rem) = lo_num.div_rem(&lo_den);
+
+        // The smallest integer in the interval, if any, is the last term.
+        let last = if lo_rem.is_zero() && lo_inclusive {
+            Some(a.clone())
+        } else {
+            let mut next = a.clone();
+            next.add_small(1);
+            match hi {
+                None => Some(next),
+                Some((ref hi_num, ref hi_den, hi_inclusive)) => {
+                    let (b, hi_rem) = hi_num.div_rem(hi_den);
+                    if next < b || (next == b && (!hi_rem.is_zero() || hi_inclusive)) {
+                        Some(next)
+                    } else {
+                        None
+                    }
+                }
+            }
+        };
+
+        let term = to_u128(last.as_ref().unwrap_or(&a))?;
+        let next_num = term.checked_mul(num)?.checked_add(prev_num)?;
+        let next_den = term.checked_mul(den)?.checked_add(prev_den)?;
+        prev_num = num;
+        prev_den = den;
+        num = next_num;
+        den = next_den;
+        if last.is_some() {
+            return Some(Fraction { sign, num, den });
+        }
+
+        // The interval is within `(a, a + 1]`, continue with the inverse of
+        // its fractional part.
+        let (hi_num, hi_den, hi_inclusive) = hi.unwrap();
+        let (b, hi_rem) = hi_num.div_rem(&hi_den);
+        let hi_frac = if b == a { hi_rem } else { hi_den.clone() };
+        let next_lo = (hi_den, hi_frac, hi_inclusive);
+        hi = if lo_rem.is_zero() {
+            None
+        } else {
+            Some((lo_den, lo_rem, lo_inclusive))
+        };
+        lo = next_lo;
+    }
+}
+
+fn to_u128(v: &Big32x40) -> Option<u128> {
+    if v.bit_length() > 128 {
+        return None;
+    }
+    Some(
+        v.digits()
+            .iter()
+            .take(4)
+            .rev()
+            .fold(0, |acc, &d| acc << 32 | u128::from(d)),
+    )
+}
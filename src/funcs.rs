@@ -0,0 +1,206 @@
+//! The main operations of `FloatExt` as free functions.
+//!
+//! Each function calls the `FloatExt` method of the same name, so they give
+//! the same results. They are useful when importing the trait is awkward,
+//! such as in code generated by macros or in FFI shims. The generic
+//! functions take any `FloatExt` type, and the functions ending in `_f32`
+//! and `_f64` take a concrete type.
+//!
+//! # Example
+//!
+//! ```
+//! use flt2dec2flt::{funcs, PreFormatted, PreParsed};
+//!
+//! let mut buf = [0; flt2dec2flt::PREFORMAT_SHORTEST_BUF_LEN];
+//! assert_eq!(
+//!     funcs::preformat_shortest_f64(1.5e-7, &mut buf),
+//!     PreFormatted::Finite(false, b"15", 0, -6),
+//! );
+//! assert_eq!(
+//!     funcs::preformat_shortest(0.25f32, &mut buf),
+//!     PreFormatted::Finite(false, b"25", 0, 0),
+//! );
+//!
+//! let preparsed = PreParsed {
+//!     sign: false,
+//!     int_digits: b"15",
+//!     frac_digits: b"",
+//!     exp: -8,
+//! };
+//! assert_eq!(funcs::from_preparsed_f64(preparsed), Ok(1.5e-7));
+//! ```
+
+use crate::{FloatExt, ParseFloatError, PreFormatted, PreParsed, PreformatError};
+
+/// Calls `FloatExt::preformat_shortest`.
+pub fn preformat_shortest<F: FloatExt>(v: F, buf: &mut [u8]) -> PreFormatted<'_> {
+    v.preformat_shortest(buf)
+}
+
+/// Calls `FloatExt::preformat_exact_exp`.
+pub fn preformat_exact_exp<F: FloatExt>(
+    v: F,
+    buf: &mut [u8],
+    num_digits: usize,
+) -> PreFormatted<'_> {
+    v.preformat_exact_exp(buf, num_digits)
+}
+
+/// Calls `FloatExt::preformat_exact_fixed`.
+pub fn preformat_exact_fixed<F: FloatExt>(
+    v: F,
+    buf: &mut [u8],
+    num_frac_digits: isize,
+) -> PreFormatted<'_> {
+    v.preformat_exact_fixed(buf, num_frac_digits)
+}
+
+/// Calls `FloatExt::preformat_exact_limit`.
+pub fn preformat_exact_limit<F: FloatExt>(v: F, buf: &mut [u8], limit: i16) -> PreFormatted<'_> {
+    v.preformat_exact_limit(buf, limit)
+}
+
+/// Calls `FloatExt::try_preformat_shortest`.
+pub fn try_preformat_shortest<F: FloatExt>(
+    v: F,
+    buf: &mut [u8],
+) -> Result<PreFormatted<'_>, PreformatError> {
+    v.try_preformat_shortest(buf)
+}
+
+/// Calls `FloatExt::try_preformat_exact_exp`.
+pub fn try_preformat_exact_exp<F: FloatExt>(
+    v: F,
+    buf: &mut [u8],
+    num_digits: usize,
+) -> Result<PreFormatted<'_>, PreformatError> {
+    v.try_preformat_exact_exp(buf, num_digits)
+}
+
+/// Calls `FloatExt::try_preformat_exact_fixed`.
+pub fn try_preformat_exact_fixed<F: FloatExt>(
+    v: F,
+    buf: &mut [u8],
+    num_frac_digits: isize,
+) -> Result<PreFormatted<'_>, PreformatError> {
+    v.try_preformat_exact_fixed(buf, num_frac_digits)
+}
+
+/// Calls `FloatExt::try_preformat_exact_limit`.
+pub fn try_preformat_exact_limit<F: FloatExt>(
+    v: F,
+    buf: &mut [u8],
+    limit: i16,
+) -> Result<PreFormatted<'_>, PreformatError> {
+    v.try_preformat_exact_limit(buf, limit)
+}
+
+/// Calls `FloatExt::from_preparsed`.
+pub fn from_preparsed<F: FloatExt>(preparsed: PreParsed<'_>) -> Result<F, ParseFloatError> {
+    F::from_preparsed(preparsed)
+}
+
+macro_rules! concrete_funcs {
+    (
+        $t:ty,
+        $preformat_shortest:ident,
+        $preformat_exact_exp:ident,
+        $preformat_exact_fixed:ident,
+        $preformat_exact_limit:ident,
+        $try_preformat_shortest:ident,
+        $try_preformat_exact_exp:ident,
+        $try_preformat_exact_fixed:ident,
+        $try_preformat_exact_limit:ident,
+        $from_preparsed:ident $(,)?
+    ) => {
+        /// Calls `FloatExt::preformat_shortest`.
+        pub fn $preformat_shortest(v: $t, buf: &mut [u8]) -> PreFormatted<'_> {
+            v.preformat_shortest(buf)
+        }
+
+        /// Calls `FloatExt::preformat_exact_exp`.
+        pub fn $preformat_exact_exp(v: $t, buf: &mut [u8], num_digits: usize) -> PreFormatted<'_> {
+            v.preformat_exact_exp(buf, num_digits)
+        }
+
+        /// Calls `FloatExt::preformat_exact_fixed`.
+        pub fn $preformat_exact_fixed(
+            v: $t,
+            buf: &mut [u8],
+            num_frac_digits: isize,
+        ) -> PreFormatted<'_> {
+            v.preformat_exact_fixed(buf, num_frac_digits)
+        }
+
+        /// Calls `FloatExt::preformat_exact_limit`.
+        pub fn $preformat_exact_limit(v: $t, buf: &mut [u8], limit: i16) -> PreFormatted<'_> {
+            v.preformat_exact_limit(buf, limit)
+        }
+
+        /// Calls `FloatExt::try_preformat_shortest`.
+        pub fn $try_preformat_shortest(
+            v: $t,
+            buf: &mut [u8],
+        ) -> Result<PreFormatted<'_>, PreformatError> {
+            v.try_preformat_shortest(buf)
+        }
+
+        /// Calls `FloatExt::try_preformat_exact_exp`.
+        pub fn $try_preformat_exact_exp(
+            v: $t,
+            buf: &mut [u8],
+            num_digits: usize,
+        ) -> Result<PreFormatted<'_>, PreformatError> {
+            v.try_preformat_exact_exp(buf, num_digits)
+        }
+
+        /// Calls `FloatExt::try_preformat_exact_fixed`.
+        pub fn $try_preformat_exact_fixed(
+            v: $t,
+            buf: &mut [u8],
+            num_frac_digits: isize,
+        ) -> Result<PreFormatted<'_>, PreformatError> {
+            v.try_preformat_exact_fixed(buf, num_frac_digits)
+        }
+
+        /// Calls `FloatExt::try_preformat_exact_limit`.
+        pub fn $try_preformat_exact_limit(
+            v: $t,
+            buf: &mut [u8],
+            limit: i16,
+        ) -> Result<PreFormatted<'_>, PreformatError> {
+            v.try_preformat_exact_limit(buf, limit)
+        }
+
+        /// Calls `FloatExt::from_preparsed`.
+        pub fn $from_preparsed(preparsed: PreParsed<'_>) -> Result<$t, ParseFloatError> {
+            <$t>::from_preparsed(preparsed)
+        }
+    };
+}
+
+concrete_funcs!(
+    f32,
+    preformat_shortest_f32,
+    preformat_exact_exp_f32,
+    preformat_exact_fixed_f32,
+    preformat_exact_limit_f32,
+    try_preformat_shortest_f32,
+    try_preformat_exact_exp_f32,
+    try_preformat_exact_fixed_f32,
+    try_preformat_exact_limit_f32,
+    from_preparsed_f32,
+);
+
+concrete_funcs!(
+    f64,
+    preformat_shortest_f64,
+    preformat_exact_exp_f64,
+    preformat_exact_fixed_f64,
+    preformat_exact_limit_f64,
+    try_preformat_shortest_f64,
+    try_preformat_exact_exp_f64,
+    try_preformat_exact_fixed_f64,
+    try_preformat_exact_limit_f64,
+    from_preparsed_f64,
+);
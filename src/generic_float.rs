@@ -0,0 +1,76 @@
+//! Adapters for code generic over `num_traits::Float`.
+//!
+//! Requires the `num-traits` feature.
+//!
+//! Generic numeric code is usually written against `num_traits::Float`.
+//! Adding a `GenericFloat` bound (or `FloatExt`, which it implies) is
+//! enough to call into this crate, without dispatching on the concrete
+//! type.
+//!
+//! # Example
+//!
+//! ```
+//! use flt2dec2flt::generic_float::{format_shortest_generic, GenericFloat};
+//! use flt2dec2flt::PreFormatted;
+//!
+//! fn halve_and_format<T: GenericFloat>(v: T, buf: &mut [u8]) -> PreFormatted<'_> {
+//!     let two = T::one() + T::one();
+//!     format_shortest_generic(v / two, buf)
+//! }
+//!
+//! let mut buf = [0; flt2dec2flt::PREFORMAT_SHORTEST_BUF_LEN];
+//! assert_eq!(halve_and_format(3.0f32, &mut buf), PreFormatted::Finite(false, b"15", 0, 1));
+//! assert_eq!(halve_and_format(0.2f64, &mut buf), PreFormatted::Finite(false, b"1", 0, 0));
+//! ```
+
+use crate::{FloatExt, ParseFloatError, PreFormatted, PreParsed};
+
+/// A `num_traits::Float` supported by this crate.
+///
+/// Implemented for every type that implements both `num_traits::Float`
+/// and `FloatExt` (that is, `f32` and `f64`).
+pub trait GenericFloat: ::num_traits::Float + FloatExt {}
+
+impl<T: ::num_traits::Float + FloatExt> GenericFloat for T {}
+
+/// Generic version of `FloatExt::preformat_shortest`.
+pub fn format_shortest_generic<T: ::num_traits::Float + FloatExt>(
+    v: T,
+    buf: &mut [u8],
+) -> PreFormatted<'_> {
+    v.preformat_shortest(buf)
+}
+
+/// Generic version of `FloatExt::preformat_exact_exp`.
+pub fn format_exact_exp_generic<T: ::num_traits::Float + FloatExt>(
+    v: T,
+    buf: &mut [u8],
+    num_digits: usize,
+) -> PreFormatted<'_> {
+    v.preformat_exact_exp(buf, num_digits)
+}
+
+/// Generic version of `FloatExt::preformat_exact_fixed`.
+pub fn format_exact_fixed_generic<T: ::num_traits::Float + FloatExt>(
+    v: T,
+    buf: &mut [u8],
+    num_frac_digits: isize,
+) -> PreFormatted<'_> {
+    v.preformat_exact_fixed(buf, num_frac_digits)
+}
+
+/// Generic version of `FloatExt::preformat_exact_limit`.
+pub fn format_exact_limit_generic<T: ::num_traits::Float + FloatExt>(
+    v: T,
+    buf: &mut [u8],
+    limit: i16,
+) -> PreFormatted<'_> {
+    v.preformat_exact_limit(buf, limit)
+}
+
+/// Generic version of `FloatExt::from_preparsed`.
+pub fn parse_generic<T: ::num_traits::Float + FloatExt>(
+    preparsed: PreParsed<'_>,
+) -> Result<T, ParseFloatError> {
+    T::from_preparsed(preparsed)
+}
@@ -0,0 +1,234 @@
+//! Formatting and parsing of numbers like Go's `strconv.FormatFloat` and
+//! `strconv.ParseFloat`.
+//!
+//! `format_float` supports the `'b'`, `'e'`, `'E'`, `'f'`, `'g'` and `'G'`
+//! formats, with a precision of -1 for the shortest representation that
+//! round-trips (the even one when two are equally close). The bit size is
+//! the one of the floating point type, so `format_float(buf, v as f32,
+//! b'g', -1)` is `FormatFloat(v, 'g', -1, 32)`.
+//!
+//! # Example
+//!
+//! ```
+//! use flt2dec2flt::go::{format_float, parse_float, ParseError};
+//!
+//! let mut buf = [0; 32];
+//! assert_eq!(format_float(&mut buf, 1.5f64, b'e', -1), Ok("1.5e+00"));
+//! assert_eq!(format_float(&mut buf, 1.5f64, b'E', 3), Ok("1.500E+00"));
+//! assert_eq!(format_float(&mut buf, 0.125f64, b'f', 2), Ok("0.12"));
+//! assert_eq!(format_float(&mut buf, 1e21f64, b'f', -1), Ok("1000000000000000000000"));
+//! assert_eq!(format_float(&mut buf, 1e6f64, b'g', -1), Ok("1e+06"));
+//! assert_eq!(format_float(&mut buf, 1.0f64, b'b', -1), Ok("4503599627370496p-52"));
+//! assert_eq!(format_float(&mut buf, 0.1f32, b'g', -1), Ok("0.1"));
+//! assert_eq!(format_float(&mut buf, f64::INFINITY, b'g', -1), Ok("+Inf"));
+//!
+//! assert_eq!(parse_float::<f64>(b"0x1.8p1"), Ok(3.0));
+//! assert_eq!(parse_float::<f64>(b"-Infinity"), Ok(f64::NEG_INFINITY));
+//! assert_eq!(parse_float::<f64>(b"1e400"), Err(ParseError::Range { sign: false }));
+//! assert!(parse_float::<f64>(b"0x1.8").is_err());
+//! ```
+
+use core::convert::TryFrom;
+use core::fmt;
+
+use crate::grammar::{Grammar, Parsed};
+use crate::write::{
+    write_general, write_positional, write_scientific, Cursor, ExpFormat, Output, WriteOptions,
+};
+use crate::{BufferTooSmall, FloatExt, ParseFloatError, PreFormatted, SyntaxError};
+
+/// Length of the longest string written by `format_float` with a
+/// precision of -1.
+///
+/// That is a sign, `0.` and 324 fractional digits, written with the `'f'`
+/// format.
+pub const SHORTEST_BUFFER_LEN: usize = 327;
+
+const OPTIONS: WriteOptions<'static> = WriteOptions {
+    exp_format: ExpFormat {
+        plus_sign: true,
+        min_digits: 2,
+    },
+    ..WriteOptions::DEFAULT
+};
+
+/// Writes `v` like `strconv.FormatFloat(v, fmt, prec, bitSize)`.
+///
+/// `fmt` is one of `b'b'`, `b'e'`, `b'E'`, `b'f'`, `b'g'` or `b'G'`, and a
+/// negative `prec` selects the shortest representation that round-trips.
+///
+/// Fails if `buf` is too small, which can only happen with a precision of
+/// -1 if it is shorter than `SHORTEST_BUFFER_LEN`.
+///
+/// Panics if `fmt` is not supported.
+pub fn format_float<T: FloatExt>(
+    buf: &mut [u8],
+    v: T,
+    fmt: u8,
+    prec: i32,
+) -> Result<&str, BufferTooSmall> {
+    let mut shortest_buf = [0; crate::PREFORMAT_SHORTEST_BUF_LEN];
+    let shortest = crate::generic::preformat_shortest_even(v, &mut shortest_buf);
+    match shortest {
+        PreFormatted::NaN => return Ok("NaN"),
+        PreFormatted::Inf(false) => return Ok("+Inf"),
+        PreFormatted::Inf(true) => return Ok("-Inf"),
+        _ => {}
+    }
+    let options = WriteOptions {
+        exp_marker: if fmt.is_ascii_uppercase() { "E" } else { "e" },
+        ..OPTIONS
+    };
+
+    let mut digits_buf = [0; crate::PREFORMAT_EXACT_FIXED_BASE_BUF_LEN];
+    // `prec` may not fit in `usize` or `isize` on 16-bit targets.
+    let uprec = usize::try_from(prec.max(0)).unwrap_or(usize::MAX);
+    match fmt {
+        b'b' => write_binary(buf, v),
+        b'e' | b'E' => {
+            let preformatted = if prec < 0 {
+                shortest
+            } else {
+                v.preformat_exact_exp(&mut digits_buf, uprec.saturating_add(1))
+            };
+            write_scientific(buf, preformatted, uprec, &options)
+        }
+        b'f' => {
+            let preformatted = if prec < 0 {
+                shortest
+            } else {
+                v.preformat_exact_fixed(
+                    &mut digits_buf,
+                    isize::try_from(prec).unwrap_or(isize::MAX),
+                )
+            };
+            write_positional(buf, preformatted, uprec, &options)
+        }
+        b'g' | b'G' => {
+            // Trailing zeros are omitted, and the notation is chosen with
+            // a precision of 6 for the shortest representation.
+            let (preformatted, eprec) = if prec < 0 {
+                (shortest, 6)
+            } else {
+                let prec = prec.max(1);
+                let preformatted = match v.preformat_exact_exp(&mut digits_buf, uprec.max(1)) {
+                    PreFormatted::Finite(sign, digits, _, exp) => {
+                        let len = digits.iter().rposition(|&c| c != b'0').map_or(0, |i| i + 1);
+                        PreFormatted::Finite(sign, &digits[..len], 0, exp)
+                    }
+                    other => other,
+                };
+                (preformatted, prec)
+            };
+            write_general(buf, preformatted, -4..=eprec - 1, 0, &options)
+        }
+        _ => panic!("unsupported format {:?}", char::from(fmt)),
+    }
+}
+
+/// Writes `v` as `-ddddp±ddd`, the significand as an integer and the binary
+/// exponent.
+fn write_binary<T: FloatExt>(buf: &mut [u8], v: T) -> Result<&str, BufferTooSmall> {
+    // `f64` can represent exactly every `T`, as `m * 2^e`.
+    let bits = v.to_f64().to_bits();
+    let biased_exp = ((bits >> 52) & 0x7FF) as i32;
+    let frac = bits & ((1 << 52) - 1);
+    let (m, e) = if biased_exp == 0 {
+        (frac, -1074)
+    } else {
+        (frac | (1 << 52), biased_exp - 1075)
+    };
+
+    let (mant, exp) = if m == 0 {
+        (0, T::MIN_LSB_EXP)
+    } else {
+        let top_exp = e + 63 - m.leading_zeros() as i32;
+        let exp = (top_exp - (T::SIG_BITS as i32 - 1)).max(T::MIN_LSB_EXP);
+        if e >= exp {
+            (m << (e - exp), exp)
+        } else {
+            (m >> (exp - e), exp)
+        }
+    };
+
+    let mut cursor = Cursor::new(buf);
+    if bits >> 63 != 0 {
+        cursor.push(b'-');
+    }
+    cursor.push_u64(mant);
+    cursor.push(b'p');
+    let exp_format = ExpFormat {
+        plus_sign: true,
+        min_digits: 1,
+    };
+    cursor.push_exponent(exp, &exp_format);
+    cursor.finish()
+}
+
+/// An error returned by `parse_float`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ParseError {
+    /// The text is not a number, like `strconv.ErrSyntax`.
+    Syntax(SyntaxError),
+    /// The number is too large in magnitude, like `strconv.ErrRange`. Go
+    /// returns an infinity with the sign of the number along with this
+    /// error.
+    Range {
+        /// The sign of the number.
+        sign: bool,
+    },
+    /// The number could not be converted (see `FloatExt::from_preparsed`).
+    Float(ParseFloatError),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            ParseError::Syntax(ref e) => e.fmt(f),
+            ParseError::Range { .. } => f.write_str("value out of range"),
+            ParseError::Float(ref e) => e.fmt(f),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseError {}
+
+/// Parses `s` like `strconv.ParseFloat(s, bitSize)`, rounding to the
+/// nearest value.
+///
+/// Accepts decimal numbers (such as `-1.5e3`, `.5` or `5.`), hexadecimal
+/// numbers with a mandatory binary exponent (such as `0x1.8p3`), and
+/// `inf`, `infinity` and `nan` in any case, where only infinities may be
+/// signed. Unlike Go, underscores between digits are not accepted.
+pub fn parse_float<T: FloatExt>(s: &[u8]) -> Result<T, ParseError> {
+    parse_float_with(&Grammar::GO, s)
+}
+
+/// Like `parse_float`, with a variant of `Grammar::GO`.
+pub(crate) fn parse_float_with<T: FloatExt>(
+    grammar: &Grammar<'_>,
+    s: &[u8],
+) -> Result<T, ParseError> {
+    let parsed = grammar.parse(s).map_err(ParseError::Syntax)?;
+    let sign = match parsed {
+        Parsed::NaN if s[0] == b'+' || s[0] == b'-' => {
+            return Err(ParseError::Syntax(SyntaxError { index: 0 }))
+        }
+        Parsed::NaN | Parsed::Inf(_) => return parsed.to_float().map_err(ParseError::Float),
+        Parsed::Hex(preparsed) => {
+            if !s.iter().any(|&c| c == b'p' || c == b'P') {
+                return Err(ParseError::Syntax(SyntaxError { index: s.len() }));
+            }
+            preparsed.sign
+        }
+        Parsed::Finite(preparsed) => preparsed.sign,
+    };
+    let v: T = parsed.to_float().map_err(ParseError::Float)?;
+    if v.to_f64().is_infinite() {
+        Err(ParseError::Range { sign })
+    } else {
+        Ok(v)
+    }
+}
@@ -0,0 +1,518 @@
+//! Parsing of decimal number text into `PreParsed`.
+//!
+//! A `Grammar` describes the accepted syntax (signs, decimal point,
+//! exponent markers, spellings of special values). Parsing only splits the
+//! text into its parts, the conversion is done by
+//! `FloatExt::from_preparsed` (or `Parsed::to_float`).
+
+use crate::{FloatExt, ParseFloatError, PreParsed, PreParsedHex, SyntaxError};
+
+/// The syntax accepted when parsing a number.
+///
+/// # Example
+///
+/// ```
+/// use flt2dec2flt::grammar::{Grammar, Parsed};
+/// use flt2dec2flt::PreParsed;
+///
+/// let grammar = Grammar {
+///     decimal_point: ",",
+///     ..Grammar::RUST
+/// };
+/// assert_eq!(
+///     grammar.parse(b"-12,5e3"),
+///     Ok(Parsed::Finite(PreParsed {
+///         sign: true,
+///         int_digits: b"12",
+///         frac_digits: b"5",
+///         exp: 3,
+///     })),
+/// );
+/// assert_eq!(grammar.parse(b"-12,5e3").unwrap().to_float::<f64>(), Ok(-12500.0));
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Grammar<'a> {
+    /// Whether a leading `+` is accepted.
+    pub plus_sign: bool,
+    /// Separator between the integer and the fractional digits.
+    pub decimal_point: &'a str,
+    /// Bytes accepted as exponent marker.
+    pub exp_markers: &'a [u8],
+    /// Whether at least one digit is required before the decimal point
+    /// (rejects `.5`).
+    pub require_int_digits: bool,
+    /// Whether at least one digit is required after the decimal point
+    /// (rejects `5.`).
+    pub require_frac_digits: bool,
+    /// Accepted spellings of NaN, which may be preceded by a sign.
+    pub nan: &'a [&'a str],
+    /// Accepted spellings of infinity, which may be preceded by a sign.
+    /// They are tried in order, so longer spellings must come first
+    /// when one is a prefix of another.
+    pub inf: &'a [&'a str],
+    /// Whether `nan` and `inf` are matched ignoring ASCII case.
+    pub special_ignore_case: bool,
+    /// Whether NaN may be followed by a parenthesized payload of ASCII
+    /// letters, digits and underscores, such as `nan(0x7ff)`, which is
+    /// ignored.
+    pub nan_payload: bool,
+    /// Whether leading ASCII whitespace is skipped.
+    pub skip_whitespace: bool,
+    /// Whether hexadecimal numbers such as `0x1.8p3` are accepted, with
+    /// an optional binary exponent introduced by `p` or `P`.
+    pub hex: bool,
+    /// A byte accepted between two decimal digits, such as `_` in
+    /// `1_000.5`, which `Parsed::to_float` ignores.
+    pub digit_separator: Option<u8>,
+    /// Signs accepted after a decimal number, each with the power of ten
+    /// the number is divided by, such as `PERCENT_SIGNS`. They are tried
+    /// in order, and applied to the exponent of the `PreParsed`, so
+    /// `12.5%` is converted exactly into the number closest to `0.125`,
+    /// without a second rounding.
+    pub percent_signs: &'a [(&'a str, i16)],
+}
+
+/// Percent (`%`), permille (`‰`) and per ten thousand (`‱`) signs, to use
+/// as `Grammar::percent_signs`.
+///
+/// # Example
+///
+/// ```
+/// use flt2dec2flt::grammar::{Grammar, PERCENT_SIGNS};
+///
+/// let grammar = Grammar {
+///     percent_signs: &PERCENT_SIGNS,
+///     ..Grammar::RUST
+/// };
+/// let parse = |s: &str| grammar.parse(s.as_bytes()).unwrap().to_float::<f64>();
+/// assert_eq!(parse("12.5%"), Ok(0.125));
+/// assert_eq!(parse("-3\u{2030}"), Ok(-0.003));
+/// // `1.1 / 100.0` would be `0.011000000000000001`
+/// assert_eq!(parse("1.1%"), Ok(0.011));
+/// assert_eq!(parse("1.1"), Ok(1.1));
+/// ```
+pub const PERCENT_SIGNS: [(&str, i16); 3] = [("%", 2), ("\u{2030}", 3), ("\u{2031}", 4)];
+
+impl Grammar<'static> {
+    /// The syntax accepted by `str::parse::<f64>`, such as `-1.5e3`, `.5`,
+    /// `5.`, `+inf`, `infinity` or `NaN`.
+    pub const RUST: Self = Self {
+        plus_sign: true,
+        decimal_point: ".",
+        exp_markers: b"eE",
+        require_int_digits: false,
+        require_frac_digits: false,
+        nan: &["nan"],
+        inf: &["infinity", "inf"],
+        special_ignore_case: true,
+        nan_payload: false,
+        skip_whitespace: false,
+        hex: false,
+        digit_separator: None,
+        percent_signs: &[],
+    };
+
+    /// The syntax of JSON numbers, such as `-1.5e3` or `0.25`. `.5`, `5.`,
+    /// `+1` and special values are rejected.
+    pub const JSON: Self = Self {
+        plus_sign: false,
+        decimal_point: ".",
+        exp_markers: b"eE",
+        require_int_digits: true,
+        require_frac_digits: true,
+        nan: &[],
+        inf: &[],
+        special_ignore_case: false,
+        nan_payload: false,
+        skip_whitespace: false,
+        hex: false,
+        digit_separator: None,
+        percent_signs: &[],
+    };
+
+    /// The syntax accepted by C's `strtod`, such as ` -1.5e3`, `.5`,
+    /// `0x1.8p3`, `INF`, `infinity` or `nan(123)`, after any leading
+    /// whitespace. See also `strtod`.
+    pub const STRTOD: Self = Self {
+        plus_sign: true,
+        decimal_point: ".",
+        exp_markers: b"eE",
+        require_int_digits: false,
+        require_frac_digits: false,
+        nan: &["nan"],
+        inf: &["infinity", "inf"],
+        special_ignore_case: true,
+        nan_payload: true,
+        skip_whitespace: true,
+        hex: true,
+        digit_separator: None,
+        percent_signs: &[],
+    };
+
+    /// The syntax accepted by Go's `strconv.ParseFloat`, such as `-1.5e3`,
+    /// `.5`, `0x1.8p3`, `+Inf` or `NaN`. See also `go::parse_float`, which
+    /// also rejects hexadecimal numbers without exponent and signed NaNs.
+    pub const GO: Self = Self {
+        plus_sign: true,
+        decimal_point: ".",
+        exp_markers: b"eE",
+        require_int_digits: false,
+        require_frac_digits: false,
+        nan: &["nan"],
+        inf: &["infinity", "inf"],
+        special_ignore_case: true,
+        nan_payload: false,
+        skip_whitespace: false,
+        hex: true,
+        digit_separator: None,
+        percent_signs: &[],
+    };
+
+    /// A lenient syntax in the style of JSON5, such as `+1.5e3`, `.5`,
+    /// `5.`, `0x1F`, `Infinity` or `-NaN`, which also accepts underscores
+    /// between decimal digits, such as `1_000.5`.
+    pub const LENIENT: Self = Self {
+        plus_sign: true,
+        decimal_point: ".",
+        exp_markers: b"eE",
+        require_int_digits: false,
+        require_frac_digits: false,
+        nan: &["NaN"],
+        inf: &["Infinity"],
+        special_ignore_case: false,
+        nan_payload: false,
+        skip_whitespace: false,
+        hex: true,
+        digit_separator: Some(b'_'),
+        percent_signs: &[],
+    };
+}
+
+impl Default for Grammar<'static> {
+    fn default() -> Self {
+        Self::RUST
+    }
+}
+
+/// A parsed number.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Parsed<'a> {
+    /// The number is NaN.
+    NaN,
+    /// The number is infinity. The boolean specifies the sign.
+    Inf(bool),
+    /// The number is finite.
+    Finite(PreParsed<'a>),
+    /// The number is finite and hexadecimal.
+    Hex(PreParsedHex<'a>),
+}
+
+impl Parsed<'_> {
+    /// Converts the parsed number into a floating point number.
+    ///
+    /// See `FloatExt::from_preparsed`. Digit separators (see
+    /// `Grammar::digit_separator`) are ignored.
+    pub fn to_float<T: FloatExt>(self) -> Result<T, ParseFloatError> {
+        match self {
+            Parsed::NaN => Ok(T::NAN),
+            Parsed::Inf(false) => Ok(T::INFINITY),
+            Parsed::Inf(true) => Ok(T::NEG_INFINITY),
+            Parsed::Finite(preparsed) => {
+                let is_digit = |c: &u8| c.is_ascii_digit();
+                if preparsed.int_digits.iter().all(is_digit)
+                    && preparsed.frac_digits.iter().all(is_digit)
+                {
+                    T::from_preparsed(preparsed)
+                } else {
+                    from_separated(preparsed)
+                }
+            }
+            Parsed::Hex(preparsed) => crate::generic::from_preparsed_hex(preparsed),
+        }
+    }
+}
+
+/// Converts a number whose digits contain separators, skipping them.
+fn from_separated<T: FloatExt>(preparsed: PreParsed<'_>) -> Result<T, ParseFloatError> {
+    crate::stream::from_digits_iter(
+        preparsed.sign,
+        preparsed
+            .int_digits
+            .iter()
+            .copied()
+            .filter(u8::is_ascii_digit),
+        preparsed
+            .frac_digits
+            .iter()
+            .copied()
+            .filter(u8::is_ascii_digit),
+        preparsed.exp,
+    )
+}
+
+/// Skips decimal digits, and separators between them.
+fn skip_digits(s: &[u8], i: usize, separator: Option<u8>) -> usize {
+    let mut j = i;
+    while let Some(&c) = s.get(j) {
+        let is_separator = Some(c) == separator
+            && j > i
+            && s[j - 1].is_ascii_digit()
+            && s.get(j + 1).map_or(false, u8::is_ascii_digit);
+        if !c.is_ascii_digit() && !is_separator {
+            break;
+        }
+        j += 1;
+    }
+    j
+}
+
+fn skip_hex_digits(s: &[u8], i: usize) -> usize {
+    i + s[i..].iter().take_while(|c| c.is_ascii_hexdigit()).count()
+}
+
+/// Parses an optionally signed decimal exponent at `s[i..]`, saturated to
+/// an `i32`, returning it with the position after it.
+fn parse_exp(s: &[u8], i: usize, separator: Option<u8>) -> Option<(i32, usize)> {
+    let mut j = i;
+    let exp_sign = match s.get(j) {
+        Some(b'-') => {
+            j += 1;
+            true
+        }
+        Some(b'+') => {
+            j += 1;
+            false
+        }
+        _ => false,
+    };
+    let exp_start = j;
+    j = skip_digits(s, j, separator);
+    if j == exp_start {
+        return None;
+    }
+    let exp_digits = s[exp_start..j].iter().filter(|c| c.is_ascii_digit());
+    let abs_exp = exp_digits.fold(0i32, |acc, &c| {
+        acc.saturating_mul(10).saturating_add(i32::from(c - b'0'))
+    });
+    Some((if exp_sign { -abs_exp } else { abs_exp }, j))
+}
+
+impl<'a> Grammar<'a> {
+    fn match_special(&self, s: &[u8], spellings: &[&str]) -> Option<usize> {
+        spellings.iter().map(|w| w.as_bytes()).find_map(|w| {
+            let head = s.get(..w.len())?;
+            let matches = if self.special_ignore_case {
+                head.eq_ignore_ascii_case(w)
+            } else {
+                head == w
+            };
+            if matches {
+                Some(w.len())
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Parses the longest prefix of `s` that is a number, returning the
+    /// number and the length of the prefix.
+    ///
+    /// Exponents that do not fit in an `i32` are saturated.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use flt2dec2flt::grammar::Grammar;
+    ///
+    /// let (parsed, len) = Grammar::RUST.parse_prefix(b"1.5e+2xyz").unwrap();
+    /// assert_eq!(len, 6);
+    /// assert_eq!(parsed.to_float::<f32>(), Ok(150.0));
+    ///
+    /// // An exponent marker without digits is not part of the number
+    /// let (parsed, len) = Grammar::RUST.parse_prefix(b"7em").unwrap();
+    /// assert_eq!(len, 1);
+    /// assert_eq!(parsed.to_float::<f32>(), Ok(7.0));
+    ///
+    /// assert!(Grammar::RUST.parse_prefix(b"-x").is_err());
+    /// ```
+    pub fn parse_prefix(&self, s: &'a [u8]) -> Result<(Parsed<'a>, usize), SyntaxError> {
+        let mut i = 0;
+        if self.skip_whitespace {
+            i = s
+                .iter()
+                .take_while(|c| b" \t\n\x0b\x0c\r".contains(c))
+                .count();
+        }
+        let sign = match s.get(i) {
+            Some(b'-') => {
+                i += 1;
+                true
+            }
+            Some(b'+') if self.plus_sign => {
+                i += 1;
+                false
+            }
+            _ => false,
+        };
+
+        if let Some(len) = self.match_special(&s[i..], self.nan) {
+            let mut end = i + len;
+            if self.nan_payload && s.get(end) == Some(&b'(') {
+                let payload_len = s[end + 1..]
+                    .iter()
+                    .take_while(|&&c| c.is_ascii_alphanumeric() || c == b'_')
+                    .count();
+                if s.get(end + 1 + payload_len) == Some(&b')') {
+                    end += payload_len + 2;
+                }
+            }
+            return Ok((Parsed::NaN, end));
+        }
+        if let Some(len) = self.match_special(&s[i..], self.inf) {
+            return Ok((Parsed::Inf(sign), i + len));
+        }
+
+        if self.hex {
+            if let Some(r) = self.parse_hex(s, i, sign) {
+                return Ok(r);
+            }
+        }
+
+        let int_start = i;
+        i = skip_digits(s, i, self.digit_separator);
+        let int_digits = &s[int_start..i];
+        if int_digits.is_empty() && self.require_int_digits {
+            return Err(SyntaxError { index: i });
+        }
+
+        let mut frac_digits = &s[i..i];
+        let point = self.decimal_point.as_bytes();
+        if s[i..].starts_with(point) {
+            let frac_start = i + point.len();
+            let frac_end = skip_digits(s, frac_start, self.digit_separator);
+            let has_frac = frac_end != frac_start;
+            if has_frac || (!self.require_frac_digits && !int_digits.is_empty()) {
+                frac_digits = &s[frac_start..frac_end];
+                i = frac_end;
+            }
+        }
+        if int_digits.is_empty() && frac_digits.is_empty() {
+            return Err(SyntaxError { index: int_start });
+        }
+
+        let mut exp = 0i32;
+        if s.get(i).map_or(false, |c| self.exp_markers.contains(c)) {
+            if let Some((e, j)) = parse_exp(s, i + 1, self.digit_separator) {
+                exp = e;
+                i = j;
+            }
+        }
+        let percent = self
+            .percent_signs
+            .iter()
+            .find(|&&(sign, _)| s[i..].starts_with(sign.as_bytes()));
+        if let Some(&(sign, scale)) = percent {
+            exp = exp.saturating_sub(i32::from(scale));
+            i += sign.len();
+        }
+
+        let preparsed = PreParsed {
+            sign,
+            int_digits,
+            frac_digits,
+            exp,
+        };
+        Ok((Parsed::Finite(preparsed), i))
+    }
+
+    /// Parses a hexadecimal number starting at `s[i..]`, after the sign.
+    fn parse_hex(&self, s: &'a [u8], i: usize, sign: bool) -> Option<(Parsed<'a>, usize)> {
+        match s.get(i..i + 2) {
+            Some(b"0x") | Some(b"0X") => {}
+            _ => return None,
+        }
+        let int_start = i + 2;
+        let mut i = skip_hex_digits(s, int_start);
+        let int_digits = &s[int_start..i];
+
+        let mut frac_digits = &s[i..i];
+        let point = self.decimal_point.as_bytes();
+        if s[i..].starts_with(point) {
+            let frac_start = i + point.len();
+            let frac_end = skip_hex_digits(s, frac_start);
+            if frac_end != frac_start || !int_digits.is_empty() {
+                frac_digits = &s[frac_start..frac_end];
+                i = frac_end;
+            }
+        }
+        if int_digits.is_empty() && frac_digits.is_empty() {
+            // Only the `0` is a number.
+            return None;
+        }
+
+        let mut exp = 0i32;
+        if let Some(b'p') | Some(b'P') = s.get(i) {
+            if let Some((e, j)) = parse_exp(s, i + 1, self.digit_separator) {
+                exp = e;
+                i = j;
+            }
+        }
+
+        let preparsed = PreParsedHex {
+            sign,
+            int_digits,
+            frac_digits,
+            exp,
+        };
+        Some((Parsed::Hex(preparsed), i))
+    }
+
+    /// Parses `s`, which must be entirely a number.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use flt2dec2flt::grammar::{Grammar, Parsed};
+    /// use flt2dec2flt::SyntaxError;
+    ///
+    /// assert_eq!(Grammar::RUST.parse(b"-Infinity"), Ok(Parsed::Inf(true)));
+    /// assert_eq!(Grammar::RUST.parse(b"1.5x"), Err(SyntaxError { index: 3 }));
+    /// ```
+    pub fn parse(&self, s: &'a [u8]) -> Result<Parsed<'a>, SyntaxError> {
+        let (parsed, len) = self.parse_prefix(s)?;
+        if len == s.len() {
+            Ok(parsed)
+        } else {
+            Err(SyntaxError { index: len })
+        }
+    }
+}
+
+/// Parses the longest prefix of `s` that is a number like C's `strtod`,
+/// returning the number and the length of the prefix (including any
+/// leading whitespace).
+///
+/// As with `strtod`, if `s` does not start with a number, the result is
+/// zero and the length is 0. Fails in the same cases as
+/// `FloatExt::from_preparsed`, which can only happen with very long
+/// numbers.
+///
+/// # Example
+///
+/// ```
+/// use flt2dec2flt::grammar::strtod;
+///
+/// assert_eq!(strtod::<f64>(b"  -1.5e3xyz"), Ok((-1500.0, 8)));
+/// assert_eq!(strtod::<f64>(b"0x1.8p3"), Ok((12.0, 7)));
+/// assert_eq!(strtod::<f64>(b"0x"), Ok((0.0, 1)));
+/// assert_eq!(strtod::<f64>(b"1e"), Ok((1.0, 1)));
+/// assert_eq!(strtod::<f32>(b"infinit"), Ok((f32::INFINITY, 3)));
+/// assert_eq!(strtod::<f64>(b"nan(1)").unwrap().1, 6);
+/// assert_eq!(strtod::<f64>(b" x"), Ok((0.0, 0)));
+/// ```
+pub fn strtod<T: FloatExt>(s: &[u8]) -> Result<(T, usize), ParseFloatError> {
+    match Grammar::STRTOD.parse_prefix(s) {
+        Ok((parsed, len)) => Ok((parsed.to_float()?, len)),
+        Err(_) => Ok((T::from_bits_u64(0), 0)),
+    }
+}
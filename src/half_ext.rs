@@ -0,0 +1,200 @@
+//! Support for the half-precision types of the `half` crate.
+//!
+//! Requires the `half` feature.
+//!
+//! `half::f16` (IEEE binary16) and `half::bf16` (bfloat16) implement
+//! `FloatExt`, with their own precision: the shortest digits are the fewest
+//! that round-trip to the same number, and parsing rounds once, directly
+//! to the half-precision type.
+//!
+//! Half-precision numbers need smaller buffers than `f32` and `f64`:
+//! `F16_SHORTEST_BUF_LEN` and `BF16_SHORTEST_BUF_LEN` for
+//! `FloatExt::preformat_shortest`, and `F16_EXACT_FIXED_BASE_BUF_LEN` and
+//! `BF16_EXACT_FIXED_BASE_BUF_LEN` as the base length for the exact modes.
+//!
+//! # Example
+//!
+//! ```
+//! use flt2dec2flt::half_ext::F16_SHORTEST_BUF_LEN;
+//! use flt2dec2flt::{FloatExt as _, PreFormatted, PreParsed};
+//! use half::f16;
+//!
+//! let mut buf = [0; F16_SHORTEST_BUF_LEN];
+//! let v = f16::from_f32(0.1);
+//! assert_eq!(v.preformat_shortest(&mut buf), PreFormatted::Finite(false, b"1", 0, 0));
+//! // The exact value is 0.0999755859375
+//! let mut buf = [0; 8];
+//! assert_eq!(v.preformat_exact_exp(&mut buf, 8), PreFormatted::Finite(false, b"99975586", 0, -1));
+//!
+//! let preparsed = PreParsed {
+//!     sign: false,
+//!     int_digits: b"65519",
+//!     frac_digits: b"",
+//!     exp: 0,
+//! };
+//! assert_eq!(f16::from_preparsed(preparsed), Ok(f16::MAX));
+//!
+//! // bfloat16 has the range of `f32`, with less than 3 digits of precision.
+//! let v = half::bf16::from_f32(1.0e30);
+//! assert_eq!(v.preformat_shortest(&mut buf), PreFormatted::Finite(false, b"1", 0, 31));
+//! assert_eq!(v.preformat_exact_exp(&mut buf, 8), PreFormatted::Finite(false, b"10002556", 0, 31));
+//! ```
+
+use ::half::{bf16, f16};
+
+use crate::core_num::flt2dec::decoder::FullDecoded;
+use crate::{generic, FloatExt, ParseFloatError, PreFormatted, PreParsed};
+
+/// Minimum buffer size that has to be passed to
+/// `FloatExt::preformat_shortest` for `f16`.
+pub const F16_SHORTEST_BUF_LEN: usize = 5;
+
+/// Minimum base buffer size that has to be passed to
+/// `FloatExt::preformat_exact_fixed` for `f16` (see
+/// `PREFORMAT_EXACT_FIXED_BASE_BUF_LEN`).
+// `core_num::flt2dec::estimate_max_buf_len` of the smallest exponent
+// (-26, for the smallest positive normal `f16`).
+pub const F16_EXACT_FIXED_BASE_BUF_LEN: usize = 40;
+
+const_assert!(21 + ((12 * 26) >> 4) == F16_EXACT_FIXED_BASE_BUF_LEN);
+
+/// Minimum buffer size that has to be passed to
+/// `FloatExt::preformat_shortest` for `bf16`.
+pub const BF16_SHORTEST_BUF_LEN: usize = 4;
+
+/// Minimum base buffer size that has to be passed to
+/// `FloatExt::preformat_exact_fixed` for `bf16` (see
+/// `PREFORMAT_EXACT_FIXED_BASE_BUF_LEN`).
+// `core_num::flt2dec::estimate_max_buf_len` of the smallest exponent
+// (-135, for the smallest positive normal `bf16`).
+pub const BF16_EXACT_FIXED_BASE_BUF_LEN: usize = 122;
+
+const_assert!(21 + ((12 * 135) >> 4) == BF16_EXACT_FIXED_BASE_BUF_LEN);
+
+impl crate::sealed::Sealed for f16 {
+    const NAN: Self = f16::NAN;
+    const INFINITY: Self = f16::INFINITY;
+    const NEG_INFINITY: Self = f16::NEG_INFINITY;
+    const BITS: u32 = 16;
+    const SIG_BITS: u32 = 11;
+    const MIN_LSB_EXP: i32 = -24;
+    const MAX_EXP: i32 = 15;
+
+    fn from_bits_u64(bits: u64) -> Self {
+        f16::from_bits(bits as u16)
+    }
+
+    fn to_bits_u64(self) -> u64 {
+        u64::from(self.to_bits())
+    }
+
+    fn to_f64(self) -> f64 {
+        // Widened bit by bit, like `f32`.
+        let bits = self.to_bits();
+        let sign = u64::from(bits >> 15) << 63;
+        let exp = (bits >> 10) & 0x1F;
+        let mant = u64::from(bits & 0x3FF);
+        let bits = if exp == 0x1F {
+            0x7FF0_0000_0000_0000 | (mant << 42)
+        } else if exp != 0 {
+            (u64::from(exp) + 1023 - 15) << 52 | (mant << 42)
+        } else if mant != 0 {
+            // Subnormal `f16`: `mant * 2^-24`.
+            let msb = 63 - mant.leading_zeros();
+            (u64::from(msb) + 1023 - 24) << 52 | ((mant << (52 - msb)) & 0xF_FFFF_FFFF_FFFF)
+        } else {
+            0
+        };
+        f64::from_bits(sign | bits)
+    }
+}
+
+impl crate::sealed::Sealed for bf16 {
+    const NAN: Self = bf16::NAN;
+    const INFINITY: Self = bf16::INFINITY;
+    const NEG_INFINITY: Self = bf16::NEG_INFINITY;
+    const BITS: u32 = 16;
+    const SIG_BITS: u32 = 8;
+    const MIN_LSB_EXP: i32 = -133;
+    const MAX_EXP: i32 = 127;
+
+    fn from_bits_u64(bits: u64) -> Self {
+        bf16::from_bits(bits as u16)
+    }
+
+    fn to_bits_u64(self) -> u64 {
+        u64::from(self.to_bits())
+    }
+
+    fn to_f64(self) -> f64 {
+        // The upper half of an `f32`.
+        crate::sealed::Sealed::to_f64(f32::from_bits(u32::from(self.to_bits()) << 16))
+    }
+}
+
+macro_rules! impl_half {
+    ($t:ty) => {
+        impl FloatExt for $t {
+            fn preformat_shortest(self, buf: &mut [u8]) -> PreFormatted<'_> {
+                let (sign, full_decoded) = generic::decode_scale2(self, 0);
+                preformat_shortest_small(sign, full_decoded, buf)
+            }
+
+            fn preformat_exact_exp(self, buf: &mut [u8], num_digits: usize) -> PreFormatted<'_> {
+                let (sign, full_decoded) = generic::decode_scale2(self, 0);
+                generic::preformat_exact_exp_decoded(sign, full_decoded, buf, num_digits)
+            }
+
+            fn preformat_exact_fixed(
+                self,
+                buf: &mut [u8],
+                num_frac_digits: isize,
+            ) -> PreFormatted<'_> {
+                let (sign, full_decoded) = generic::decode_scale2(self, 0);
+                generic::preformat_exact_fixed_decoded(sign, full_decoded, buf, num_frac_digits)
+            }
+
+            fn preformat_exact_limit(self, buf: &mut [u8], limit: i16) -> PreFormatted<'_> {
+                let (sign, full_decoded) = generic::decode_scale2(self, 0);
+                generic::preformat_exact_limit_decoded(sign, full_decoded, buf, limit)
+            }
+
+            fn from_preparsed(preparsed: PreParsed<'_>) -> Result<Self, ParseFloatError> {
+                generic::from_preparsed_scale2(preparsed, 0)
+            }
+
+            #[cfg(feature = "num")]
+            fn to_exact_rational(self) -> Option<::num_rational::BigRational> {
+                crate::sealed::Sealed::to_f64(self).to_exact_rational()
+            }
+
+            #[cfg(feature = "num")]
+            fn from_rational(v: &::num_rational::BigRational) -> Self {
+                crate::rational::from_rational(v)
+            }
+        }
+    };
+}
+
+impl_half!(f16);
+impl_half!(bf16);
+
+/// Like `generic::preformat_shortest_decoded`, with a buffer that may be
+/// shorter than `PREFORMAT_SHORTEST_BUF_LEN`, as long as the digits fit.
+fn preformat_shortest_small(
+    sign: bool,
+    full_decoded: FullDecoded,
+    buf: &mut [u8],
+) -> PreFormatted<'_> {
+    let mut tmp = [0; crate::PREFORMAT_SHORTEST_BUF_LEN];
+    match generic::preformat_shortest_decoded(sign, full_decoded, &mut tmp) {
+        PreFormatted::NaN => PreFormatted::NaN,
+        PreFormatted::Inf(sign) => PreFormatted::Inf(sign),
+        PreFormatted::Zero(sign) => PreFormatted::Zero(sign),
+        PreFormatted::Finite(sign, digits, num_zeros, exp) => {
+            let buf = &mut buf[..digits.len()];
+            buf.copy_from_slice(digits);
+            PreFormatted::Finite(sign, buf, num_zeros, exp)
+        }
+    }
+}
@@ -0,0 +1,101 @@
+//! Writing into `heapless::String` and `heapless::Vec`.
+//!
+//! Requires the `heapless` feature, which needs Rust 1.59 or newer.
+//!
+//! The functions that write the shortest representation check at compile
+//! time that the capacity is at least `write::MINIMAL_SHORTEST_LEN`, so
+//! they cannot fail.
+//!
+//! # Example
+//!
+//! ```
+//! use flt2dec2flt::heapless_ext::to_shortest_string;
+//!
+//! let s = to_shortest_string::<_, 24>(1.25e20f64);
+//! assert_eq!(s, "1.25e20");
+//! ```
+//!
+//! A capacity that is too small is rejected:
+//!
+//! ```compile_fail
+//! use flt2dec2flt::heapless_ext::to_shortest_string;
+//!
+//! let s = to_shortest_string::<_, 8>(1.25e20f64);
+//! ```
+
+use ::heapless::{String, Vec};
+
+use crate::write::{write_minimal, WriteOptions, MINIMAL_SHORTEST_LEN};
+use crate::{BufferTooSmall, FloatExt, PreFormatted};
+
+struct Capacity<const N: usize>;
+
+impl<const N: usize> Capacity<N> {
+    const SHORTEST: () = assert!(
+        N >= MINIMAL_SHORTEST_LEN,
+        "capacity is smaller than `write::MINIMAL_SHORTEST_LEN`"
+    );
+}
+
+/// Writes `v` with the shortest representation that round-trips, in
+/// positional or scientific notation, whichever is shorter (see
+/// `write::write_minimal`).
+///
+/// `N` must be at least `write::MINIMAL_SHORTEST_LEN`.
+pub fn to_shortest_string<T: FloatExt, const N: usize>(v: T) -> String<N> {
+    let () = Capacity::<N>::SHORTEST;
+    let mut s = String::new();
+    s.push_str(shortest(v, &mut [0; MINIMAL_SHORTEST_LEN]))
+        .unwrap();
+    s
+}
+
+/// Same as `to_shortest_string`, returning bytes.
+///
+/// `N` must be at least `write::MINIMAL_SHORTEST_LEN`.
+pub fn to_shortest_vec<T: FloatExt, const N: usize>(v: T) -> Vec<u8, N> {
+    let () = Capacity::<N>::SHORTEST;
+    let mut vec = Vec::new();
+    vec.extend_from_slice(shortest(v, &mut [0; MINIMAL_SHORTEST_LEN]).as_bytes())
+        .unwrap();
+    vec
+}
+
+fn shortest<T: FloatExt>(v: T, buf: &mut [u8; MINIMAL_SHORTEST_LEN]) -> &str {
+    let mut digits_buf = [0; crate::PREFORMAT_SHORTEST_BUF_LEN];
+    let preformatted = v.preformat_shortest(&mut digits_buf);
+    write_minimal(buf, preformatted, &WriteOptions::DEFAULT).unwrap()
+}
+
+/// Writes `preformatted` like `write::write_minimal`, failing if it does
+/// not fit in `N` bytes.
+///
+/// # Example
+///
+/// ```
+/// use flt2dec2flt::heapless_ext::write_minimal_string;
+/// use flt2dec2flt::write::WriteOptions;
+/// use flt2dec2flt::{BufferTooSmall, PreFormatted};
+///
+/// let options = WriteOptions {
+///     exp_marker: " x 10^",
+///     ..WriteOptions::DEFAULT
+/// };
+/// let preformatted = PreFormatted::Finite(false, b"15", 0, 31);
+/// let s = write_minimal_string::<16>(preformatted, &options);
+/// assert_eq!(s.unwrap(), "1.5 x 10^30");
+///
+/// let s = write_minimal_string::<8>(preformatted, &options);
+/// assert_eq!(s, Err(BufferTooSmall { required: 11, actual: 8 }));
+/// ```
+pub fn write_minimal_string<const N: usize>(
+    preformatted: PreFormatted<'_>,
+    options: &WriteOptions<'_>,
+) -> Result<String<N>, BufferTooSmall> {
+    let mut vec = Vec::<u8, N>::new();
+    vec.resize_default(N).unwrap();
+    let len = write_minimal(&mut vec, preformatted, options)?.len();
+    vec.truncate(len);
+    // Only `str`s and ASCII bytes are written.
+    Ok(String::from_utf8(vec).unwrap())
+}
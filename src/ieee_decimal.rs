@@ -0,0 +1,334 @@
+//! Conversions between pre-formatted and pre-parsed numbers and the IEEE
+//! 754 decimal interchange formats (decimal64 and decimal128, such as
+//! `DECFLOAT` columns), in both the binary (BID) and the densely packed
+//! decimal (DPD) encodings.
+//!
+//! Encoding is exact: a `PreFormatted` is encoded only if its digits fit
+//! in the precision of the format (16 or 34 digits) and its exponent in
+//! its range. The trailing zeros of the digits are kept when they fit, so
+//! the number of digits of `FloatExt::preformat_exact_exp` becomes the
+//! quantum of the decimal. Decoding gives a `grammar::Parsed`, which
+//! `Parsed::to_float` rounds correctly to a binary float.
+//!
+//! # Example
+//!
+//! ```
+//! use flt2dec2flt::ieee_decimal::{self, Encoding};
+//! use flt2dec2flt::grammar::Parsed;
+//! use flt2dec2flt::{FloatExt as _, PreParsed};
+//!
+//! let mut buf = [0; 16];
+//! let preformatted = 0.1f64.preformat_exact_exp(&mut buf, 16);
+//! // 1000000000000000 * 10^-16
+//! let bits = ieee_decimal::encode_decimal64(preformatted, Encoding::Bid).unwrap();
+//! assert_eq!(bits, 0x2FC3_8D7E_A4C6_8000);
+//!
+//! let mut buf = [0; ieee_decimal::BUF_LEN];
+//! let parsed = ieee_decimal::decode_decimal64(0x2230_0000_0000_00A5, Encoding::Dpd, &mut buf);
+//! assert_eq!(
+//!     parsed,
+//!     Parsed::Finite(PreParsed {
+//!         sign: false,
+//!         int_digits: b"125",
+//!         frac_digits: b"",
+//!         exp: -2,
+//!     }),
+//! );
+//! assert_eq!(parsed.to_float::<f64>(), Ok(1.25));
+//! ```
+
+use crate::grammar::Parsed;
+use crate::{DecimalOverflow, PreFormatted, PreParsed};
+
+/// Minimum buffer size that has to be passed to `decode_decimal64` and
+/// `decode_decimal128`.
+pub const BUF_LEN: usize = 34;
+
+/// The encoding of the significand of a decimal interchange format.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Encoding {
+    /// Binary integer decimal, the significand is a binary integer.
+    Bid,
+    /// Densely packed decimal, the significand is a sequence of 10-bit
+    /// groups of 3 digits.
+    Dpd,
+}
+
+struct Format {
+    /// Total number of bits.
+    bits: u32,
+    /// Number of significand digits.
+    digits: u32,
+    /// Number of bits of the biased exponent.
+    exp_bits: u32,
+    /// Exponent bias, for significands that are integers.
+    bias: i32,
+}
+
+const DECIMAL64: Format = Format {
+    bits: 64,
+    digits: 16,
+    exp_bits: 10,
+    bias: 398,
+};
+
+const DECIMAL128: Format = Format {
+    bits: 128,
+    digits: 34,
+    exp_bits: 14,
+    bias: 6176,
+};
+
+impl Format {
+    fn max_biased_exp(&self) -> i32 {
+        (3 << (self.exp_bits - 2)) - 1
+    }
+
+    /// Number of bits of the significand in the BID encoding, when it does
+    /// not start with `11`.
+    fn bid_sig_bits(&self) -> u32 {
+        self.bits - 1 - self.exp_bits
+    }
+
+    /// Number of bits of the declets in the DPD encoding.
+    fn dpd_declet_bits(&self) -> u32 {
+        (self.digits - 1) / 3 * 10
+    }
+}
+
+/// Encodes a number of 3 digits as a declet.
+fn encode_declet(n: u32) -> u128 {
+    let (d1, d2, d3) = (n / 100, n / 10 % 10, n % 10);
+    // The low bits of each digit are kept when it is below 8, and its
+    // lowest bit otherwise.
+    let (d, h, m) = (d1 & 1, d2 & 1, d3 & 1);
+    let (large1, large2, large3) = (d1 >= 8, d2 >= 8, d3 >= 8);
+    let declet = match (large1, large2, large3) {
+        (false, false, false) => d1 << 7 | d2 << 4 | d3,
+        (false, false, true) => d1 << 7 | d2 << 4 | 0b1000 | m,
+        (false, true, false) => d1 << 7 | (d3 & 6) << 4 | h << 4 | 0b1010 | m,
+        (true, false, false) => (d3 & 6) << 7 | d << 7 | d2 << 4 | 0b1100 | m,
+        (true, true, false) => (d3 & 6) << 7 | d << 7 | h << 4 | 0b1110 | m,
+        (true, false, true) => (d2 & 6) << 7 | d << 7 | 0b010_0000 | h << 4 | 0b1110 | m,
+        (false, true, true) => d1 << 7 | 0b100_0000 | h << 4 | 0b1110 | m,
+        (true, true, true) => d << 7 | 0b110_0000 | h << 4 | 0b1110 | m,
+    };
+    u128::from(declet)
+}
+
+/// Decodes a declet (including the non-canonical ones) into a number of
+/// 3 digits.
+fn decode_declet(declet: u32) -> u32 {
+    let (pq, r) = (declet >> 8 & 3, declet >> 7 & 1);
+    let (st, u) = (declet >> 5 & 3, declet >> 4 & 1);
+    let (v, wx, y) = (declet >> 3 & 1, declet >> 1 & 3, declet & 1);
+    let (pqr, stu) = (declet >> 7 & 7, declet >> 4 & 7);
+    let (d1, d2, d3) = if v == 0 {
+        (pqr, stu, wx << 1 | y)
+    } else {
+        match (wx, st) {
+            (0, _) => (pqr, stu, 8 | y),
+            (1, _) => (pqr, 8 | u, st << 1 | y),
+            (2, _) => (8 | r, stu, pq << 1 | y),
+            (_, 0) => (8 | r, 8 | u, pq << 1 | y),
+            (_, 1) => (8 | r, pq << 1 | u, 8 | y),
+            (_, 2) => (pqr, 8 | u, 8 | y),
+            (_, _) => (8 | r, 8 | u, 8 | y),
+        }
+    };
+    d1 * 100 + d2 * 10 + d3
+}
+
+/// Converts the digits of a `PreFormatted::Finite` into a significand and
+/// an exponent of `format`.
+fn significand(
+    digits: &[u8],
+    num_zeros: usize,
+    exp: i16,
+    format: &Format,
+) -> Result<(u128, i32), DecimalOverflow> {
+    let sig_len = digits.len() - crate::trailing_zeros(digits);
+    let max_len = format.digits as usize;
+    if sig_len > max_len {
+        return Err(DecimalOverflow);
+    }
+    let zeros = (digits.len() - sig_len).saturating_add(num_zeros);
+    let room = max_len - sig_len;
+
+    // The value is `digits[..sig_len] * 10^(exp0 - z)` with `z` zeros
+    // appended to the digits. Keep as many zeros as possible, within the
+    // exponent range.
+    let exp0 = i32::from(exp) - sig_len as i32;
+    let min_exp = -format.bias;
+    let max_exp = format.max_biased_exp() - format.bias;
+    let mut z = zeros.min(room) as i32;
+    if exp0 - z > max_exp {
+        z = exp0 - max_exp;
+        if z > room as i32 {
+            return Err(DecimalOverflow);
+        }
+    } else if exp0 - z < min_exp {
+        z = exp0 - min_exp;
+        if z < 0 {
+            return Err(DecimalOverflow);
+        }
+    }
+
+    let mut sig = 0;
+    for &digit in &digits[..sig_len] {
+        sig = sig * 10 + u128::from(digit - b'0');
+    }
+    Ok((sig * 10u128.pow(z as u32), exp0 - z))
+}
+
+fn encode(
+    v: PreFormatted<'_>,
+    encoding: Encoding,
+    format: &Format,
+) -> Result<u128, DecimalOverflow> {
+    let special_shift = format.bits - 6;
+    let (sign, sig, exp) = match v {
+        PreFormatted::NaN => return Ok(0b11111 << special_shift),
+        PreFormatted::Inf(sign) => {
+            return Ok(u128::from(sign) << (format.bits - 1) | 0b11110 << special_shift)
+        }
+        PreFormatted::Zero(sign) => (sign, 0, 0),
+        PreFormatted::Finite(sign, digits, num_zeros, exp) => {
+            let (sig, exp) = significand(digits, num_zeros, exp, format)?;
+            (sign, sig, exp)
+        }
+    };
+    let biased_exp = (exp + format.bias) as u128;
+    let sign_bit = u128::from(sign) << (format.bits - 1);
+    let bits = match encoding {
+        Encoding::Bid => {
+            let sig_bits = format.bid_sig_bits();
+            if sig >> sig_bits == 0 {
+                biased_exp << sig_bits | sig
+            } else {
+                // The significand is `0b100` followed by `sig_bits - 2`
+                // bits, and `0b11` goes before the exponent.
+                0b11 << (format.bits - 3)
+                    | biased_exp << (sig_bits - 2)
+                    | (sig & ((1 << (sig_bits - 2)) - 1))
+            }
+        }
+        Encoding::Dpd => {
+            let declet_bits = format.dpd_declet_bits();
+            let low_exp_bits = format.exp_bits - 2;
+            let scale = 10u128.pow(format.digits - 1);
+            let (lead, mut rest) = ((sig / scale) as u32, sig % scale);
+            let high_exp = (biased_exp >> low_exp_bits) as u32;
+            let combination = if lead < 8 {
+                high_exp << 3 | lead
+            } else {
+                0b11000 | high_exp << 1 | (lead & 1)
+            };
+            let mut declets = 0;
+            for i in 0..declet_bits / 10 {
+                declets |= encode_declet((rest % 1000) as u32) << (10 * i);
+                rest /= 1000;
+            }
+            u128::from(combination) << special_shift
+                | (biased_exp & ((1 << low_exp_bits) - 1)) << declet_bits
+                | declets
+        }
+    };
+    Ok(sign_bit | bits)
+}
+
+fn decode<'a>(bits: u128, encoding: Encoding, format: &Format, buf: &'a mut [u8]) -> Parsed<'a> {
+    let sign = bits >> (format.bits - 1) & 1 != 0;
+    let combination = (bits >> (format.bits - 6) & 0x1F) as u32;
+    if combination == 0b11111 {
+        return Parsed::NaN;
+    } else if combination == 0b11110 {
+        return Parsed::Inf(sign);
+    }
+    let exp_mask = (1 << format.exp_bits) - 1;
+    let (biased_exp, sig) = match encoding {
+        Encoding::Bid => {
+            let sig_bits = format.bid_sig_bits();
+            let (biased_exp, sig) = if combination >> 3 != 0b11 {
+                (bits >> sig_bits & exp_mask, bits & ((1 << sig_bits) - 1))
+            } else {
+                let sig_bits = sig_bits - 2;
+                let sig = 0b100 << sig_bits | (bits & ((1 << sig_bits) - 1));
+                (bits >> sig_bits & exp_mask, sig)
+            };
+            // Non-canonical significands are zero.
+            if sig >= 10u128.pow(format.digits) {
+                (biased_exp, 0)
+            } else {
+                (biased_exp, sig)
+            }
+        }
+        Encoding::Dpd => {
+            let declet_bits = format.dpd_declet_bits();
+            let low_exp_bits = format.exp_bits - 2;
+            let (high_exp, lead) = if combination >> 3 != 0b11 {
+                (combination >> 3, combination & 7)
+            } else {
+                (combination >> 1 & 3, 8 | (combination & 1))
+            };
+            let low_exp = bits >> declet_bits & ((1 << low_exp_bits) - 1);
+            let mut sig = u128::from(lead);
+            for i in (0..declet_bits / 10).rev() {
+                let declet = (bits >> (10 * i) & 0x3FF) as u32;
+                sig = sig * 1000 + u128::from(decode_declet(declet));
+            }
+            (u128::from(high_exp) << low_exp_bits | low_exp, sig)
+        }
+    };
+
+    let mut len = 1;
+    while len < format.digits && sig >= 10u128.pow(len) {
+        len += 1;
+    }
+    let digits = &mut buf[..len as usize];
+    let mut rest = sig;
+    for digit in digits.iter_mut().rev() {
+        *digit = b'0' + (rest % 10) as u8;
+        rest /= 10;
+    }
+    Parsed::Finite(PreParsed {
+        sign,
+        int_digits: digits,
+        frac_digits: b"",
+        exp: biased_exp as i32 - format.bias,
+    })
+}
+
+/// Encodes `v` as a decimal64.
+///
+/// NaN becomes a quiet NaN. Fails if the digits of `v` (without trailing
+/// zeros) do not fit in 16 digits or its exponent is out of range (the
+/// significand times `10^-398` to `10^369`).
+pub fn encode_decimal64(v: PreFormatted<'_>, encoding: Encoding) -> Result<u64, DecimalOverflow> {
+    encode(v, encoding, &DECIMAL64).map(|bits| bits as u64)
+}
+
+/// Encodes `v` as a decimal128.
+///
+/// NaN becomes a quiet NaN. Fails if the digits of `v` (without trailing
+/// zeros) do not fit in 34 digits or its exponent is out of range (the
+/// significand times `10^-6176` to `10^6111`).
+pub fn encode_decimal128(v: PreFormatted<'_>, encoding: Encoding) -> Result<u128, DecimalOverflow> {
+    encode(v, encoding, &DECIMAL128)
+}
+
+/// Decodes a decimal64, whose significand digits are written into `buf`.
+///
+/// Finite numbers are decoded as an integer (without leading zeros) and an
+/// exponent. `buf` must be at least `BUF_LEN` long.
+pub fn decode_decimal64(bits: u64, encoding: Encoding, buf: &mut [u8]) -> Parsed<'_> {
+    decode(u128::from(bits), encoding, &DECIMAL64, buf)
+}
+
+/// Decodes a decimal128, whose significand digits are written into `buf`.
+///
+/// Finite numbers are decoded as an integer (without leading zeros) and an
+/// exponent. `buf` must be at least `BUF_LEN` long.
+pub fn decode_decimal128(bits: u128, encoding: Encoding, buf: &mut [u8]) -> Parsed<'_> {
+    decode(bits, encoding, &DECIMAL128, buf)
+}
@@ -0,0 +1,160 @@
+//! Decimal representations of intervals.
+//!
+//! # Example
+//!
+//! ```
+//! use flt2dec2flt::interval::shortest_in_interval;
+//! use flt2dec2flt::PreFormatted;
+//!
+//! let mut buf = [0; flt2dec2flt::PREFORMAT_EXACT_FIXED_BASE_BUF_LEN];
+//!
+//! // Shows ~π as 3.14
+//! let pi = core::f64::consts::PI;
+//! let preformatted = shortest_in_interval(pi - 0.005, pi + 0.005, &mut buf);
+//! assert_eq!(preformatted, PreFormatted::Finite(false, b"314", 0, 1));
+//!
+//! let preformatted = shortest_in_interval(-0.5f32, 20.0, &mut buf);
+//! assert_eq!(preformatted, PreFormatted::Zero(false));
+//!
+//! let preformatted = shortest_in_interval(0.5f32, 20.0, &mut buf);
+//! assert_eq!(preformatted, PreFormatted::Finite(false, b"1", 0, 2));
+//! ```
+
+use core::cmp::Ordering;
+
+use crate::rounding::round_digits;
+use crate::{FloatExt, PreFormatted, RoundingMode, PREFORMAT_EXACT_FIXED_BASE_BUF_LEN};
+
+/// Returns the decimal number with the fewest significant digits within
+/// `[lo, hi]` (bounds included).
+///
+/// When several numbers have that many digits, the one nearest to the
+/// middle of the interval (computed in floating point) is returned. Zero is
+/// returned when the interval contains it, and infinity when both bounds
+/// are the same infinity.
+///
+/// `buf` must be at least `flt2dec2flt::PREFORMAT_EXACT_FIXED_BASE_BUF_LEN`.
+///
+/// Panics if `lo > hi` or if any of them is NaN.
+pub fn shortest_in_interval<T: FloatExt>(lo: T, hi: T, buf: &mut [u8]) -> PreFormatted<'_> {
+    // `f64` can represent exactly every `T`.
+    let (lo, hi) = (lo.to_f64(), hi.to_f64());
+    assert!(lo <= hi, "invalid interval");
+    if lo <= 0.0 && hi >= 0.0 {
+        PreFormatted::Zero(lo.is_sign_negative() && hi.is_sign_negative())
+    } else if hi < 0.0 {
+        match shortest_in_positive_interval(-hi, -lo, buf) {
+            PreFormatted::Finite(_, digits, num_zeros, exp) => {
+                PreFormatted::Finite(true, digits, num_zeros, exp)
+            }
+            PreFormatted::Inf(_) => PreFormatted::Inf(true),
+            other => other,
+        }
+    } else {
+        shortest_in_positive_interval(lo, hi, buf)
+    }
+}
+
+fn shortest_in_positive_interval(lo: f64, hi: f64, buf: &mut [u8]) -> PreFormatted<'_> {
+    if lo.is_infinite() {
+        return PreFormatted::Inf(false);
+    }
+    let mut lo_buf = [0; PREFORMAT_EXACT_FIXED_BASE_BUF_LEN];
+    let mut hi_buf = [0; PREFORMAT_EXACT_FIXED_BASE_BUF_LEN];
+    let (lo_len, lo_exp) = exact_digits(lo, &mut lo_buf);
+    let hi_digits = if hi.is_finite() {
+        Some(exact_digits(hi, &mut hi_buf))
+    } else {
+        None
+    };
+
+    // Number of digits of the result.
+    let num_digits = match hi_digits {
+        Some((hi_len, hi_exp)) if hi_exp == lo_exp => {
+            // Every number in the interval starts with the common prefix,
+            // so the result has one more digit, unless `lo` is the prefix.
+            let lo_digits = &lo_buf[..lo_len];
+            let hi_digits = &hi_buf[..hi_len];
+            let prefix_len = lo_digits
+                .iter()
+                .zip(hi_digits)
+                .take_while(|(a, b)| a == b)
+                .count();
+            if lo_len <= prefix_len {
+                buf[..lo_len].copy_from_slice(lo_digits);
+                return PreFormatted::Finite(false, &buf[..lo_len], 0, lo_exp as i16);
+            }
+            prefix_len + 1
+        }
+        // There is a power of ten between `lo` and `hi`.
+        _ => 1,
+    };
+    let keep = num_digits as i32;
+
+    // The smallest and largest candidates.
+    let (low_len, low_exp) =
+        round_digits(&mut lo_buf[..lo_len], lo_exp, keep, false, RoundingMode::Up);
+    let (hi_len, hi_exp) = match hi_digits {
+        Some(hi_digits) => hi_digits,
+        // The interval is unbounded, there is no middle.
+        None => {
+            buf[..low_len].copy_from_slice(&lo_buf[..low_len]);
+            return PreFormatted::Finite(false, &buf[..low_len], 0, low_exp as i16);
+        }
+    };
+    let (high_len, high_exp) = round_digits(
+        &mut hi_buf[..hi_len],
+        hi_exp,
+        keep,
+        false,
+        RoundingMode::Down,
+    );
+    let low = (&lo_buf[..low_len], low_exp);
+    let high = (&hi_buf[..high_len], high_exp);
+
+    // The middle, rounded to `num_digits` digits (at the start of `buf`)
+    // and clamped to the candidates.
+    let mid = lo / 2.0 + hi / 2.0;
+    let (mid_len, mid_exp) =
+        match mid.preformat_exact_exp_rounded(buf, num_digits, RoundingMode::NearestEven) {
+            PreFormatted::Finite(_, digits, _, exp) => (digits.len(), i32::from(exp)),
+            // `mid` can underflow.
+            _ => (0, i32::MIN),
+        };
+    let (len, exp) =
+        if mid_len == 0 || cmp_digits((&buf[..mid_len], mid_exp), low) == Ordering::Less {
+            buf[..low_len].copy_from_slice(low.0);
+            (low_len, low_exp)
+        } else if cmp_digits((&buf[..mid_len], mid_exp), high) == Ordering::Greater {
+            buf[..high_len].copy_from_slice(high.0);
+            (high_len, high_exp)
+        } else {
+            (mid_len, mid_exp)
+        };
+    PreFormatted::Finite(false, &buf[..len], 0, exp as i16)
+}
+
+/// Writes every significant digit of `v` (which must be finite and
+/// positive) into `buf`, returning their number and the exponent.
+pub(crate) fn exact_digits(v: f64, buf: &mut [u8]) -> (usize, i32) {
+    match v.preformat_exact_exp(buf, PREFORMAT_EXACT_FIXED_BASE_BUF_LEN) {
+        PreFormatted::Finite(_, digits, _, exp) => {
+            let len = digits.iter().rposition(|&c| c != b'0').map_or(0, |i| i + 1);
+            (len, i32::from(exp))
+        }
+        _ => unreachable!(),
+    }
+}
+
+/// Compares `0.digits * 10^exp` of two positive numbers with a non-zero
+/// leading digit.
+fn cmp_digits(a: (&[u8], i32), b: (&[u8], i32)) -> Ordering {
+    a.1.cmp(&b.1).then_with(|| {
+        let len = a.0.len().max(b.0.len());
+        let digit = |digits: &[u8], i| digits.get(i).copied().unwrap_or(b'0');
+        (0..len)
+            .map(|i| digit(a.0, i).cmp(&digit(b.0, i)))
+            .find(|&ord| ord != Ordering::Equal)
+            .unwrap_or(Ordering::Equal)
+    })
+}
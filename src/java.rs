@@ -0,0 +1,97 @@
+//! Formatting of numbers like Java's `Double.toString` and
+//! `Float.toString`.
+//!
+//! Values with a magnitude between `1e-3` (inclusive) and `1e7`
+//! (exclusive) are written in positional notation and the rest in
+//! "computerized scientific notation", both with at least one fractional
+//! digit, such as `100.0`, `0.001`, `1.0E7` or `1.25E-4`. NaN and
+//! infinities are written as `NaN`, `Infinity` and `-Infinity`.
+//!
+//! The digits are the shortest that round-trip (the even ones when two are
+//! equally close), which is what Java does since JDK 19, except that when a
+//! single digit would be enough, the closest two digits are used instead
+//! (so `Double.MIN_VALUE` is `4.9E-324` rather than `5.0E-324`). Older
+//! versions sometimes wrote one more digit than needed (such as `2.0E-3`
+//! for `0.002`).
+//!
+//! # Example
+//!
+//! ```
+//! use flt2dec2flt::java::{double_to_string, float_to_string, BUFFER_LEN};
+//!
+//! let mut buf = [0; BUFFER_LEN];
+//! assert_eq!(double_to_string(&mut buf, 100.0), "100.0");
+//! assert_eq!(double_to_string(&mut buf, 1e7), "1.0E7");
+//! assert_eq!(double_to_string(&mut buf, -1.25e-4), "-1.25E-4");
+//! assert_eq!(double_to_string(&mut buf, -0.0), "-0.0");
+//! assert_eq!(float_to_string(&mut buf, 0.1), "0.1");
+//! assert_eq!(float_to_string(&mut buf, f32::INFINITY), "Infinity");
+//! ```
+
+use crate::write::{write_general, ExpFormat, WriteOptions};
+use crate::{FloatExt, PreFormatted, PreParsed};
+
+/// Length of the longest string written by `double_to_string` and
+/// `float_to_string`.
+///
+/// That is a sign, 17 digits, a decimal point, `E` and a 4 characters
+/// exponent.
+pub const BUFFER_LEN: usize = 25;
+
+const OPTIONS: WriteOptions<'static> = WriteOptions {
+    inf: "Infinity",
+    exp_marker: "E",
+    exp_format: ExpFormat::DEFAULT,
+    ..WriteOptions::DEFAULT
+};
+
+/// Writes `v` like `Double.toString(v)`.
+///
+/// Panics if `buf` is shorter than `BUFFER_LEN`.
+pub fn double_to_string(buf: &mut [u8], v: f64) -> &str {
+    write(buf, v)
+}
+
+/// Writes `v` like `Float.toString(v)`.
+///
+/// Panics if `buf` is shorter than `BUFFER_LEN`.
+pub fn float_to_string(buf: &mut [u8], v: f32) -> &str {
+    write(buf, v)
+}
+
+fn write<T: FloatExt>(buf: &mut [u8], v: T) -> &str {
+    let mut digits_buf = [0; crate::PREFORMAT_SHORTEST_BUF_LEN];
+    let mut two_digits_buf = [0; 2];
+    let mut preformatted = crate::generic::preformat_shortest_even(v, &mut digits_buf);
+    if let PreFormatted::Finite(_, digits, _, _) = preformatted {
+        if digits.len() == 1 {
+            let two_digits = match v.preformat_exact_exp(&mut two_digits_buf, 2) {
+                PreFormatted::Finite(sign, [d, b'0'], _, exp) => {
+                    PreFormatted::Finite(sign, core::slice::from_ref(d), 0, exp)
+                }
+                other => other,
+            };
+            if round_trips(v, two_digits) {
+                preformatted = two_digits;
+            }
+        }
+    }
+    write_general(buf, preformatted, -3..=6, 1, &OPTIONS).expect("buffer too small")
+}
+
+fn round_trips<T: FloatExt>(v: T, preformatted: PreFormatted<'_>) -> bool {
+    match preformatted {
+        PreFormatted::Finite(sign, digits, _, exp) => {
+            let preparsed = PreParsed {
+                sign,
+                int_digits: &[],
+                frac_digits: digits,
+                exp: exp.into(),
+            };
+            T::from_preparsed(preparsed).map_or(false, |parsed: T| {
+                parsed.to_f64().to_bits() == v.to_f64().to_bits()
+            })
+        }
+        _ => false,
+    }
+}
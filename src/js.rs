@@ -0,0 +1,145 @@
+//! Formatting of numbers like JavaScript's `Number.prototype.toString`,
+//! `toFixed` and `toPrecision`.
+//!
+//! `toString` writes the shortest representation that round-trips (the
+//! even one when two are equally close), in positional notation when the
+//! decimal exponent is between -6 and 20.
+//! `toFixed` and `toPrecision` round the exact value of the number, with
+//! ties going away from zero, so `(1.005).toFixed(2)` is `"1.00"` (1.005
+//! is slightly below 1.005) and `(0.125).toFixed(2)` is `"0.13"`.
+//!
+//! # Example
+//!
+//! ```
+//! use flt2dec2flt::js::{to_fixed, to_precision, to_string, BUFFER_LEN};
+//!
+//! let mut buf = [0; BUFFER_LEN];
+//! assert_eq!(to_string(&mut buf, 1e21), "1e+21");
+//! assert_eq!(to_string(&mut buf, 1.5e-7), "1.5e-7");
+//! assert_eq!(to_string(&mut buf, -0.0), "0");
+//! assert_eq!(to_fixed(&mut buf, 1.005, 2), "1.00");
+//! assert_eq!(to_fixed(&mut buf, 0.125, 2), "0.13");
+//! assert_eq!(to_fixed(&mut buf, -0.0001, 2), "-0.00");
+//! assert_eq!(to_fixed(&mut buf, 1e21, 2), "1e+21");
+//! assert_eq!(to_precision(&mut buf, 123.456, 4), "123.5");
+//! assert_eq!(to_precision(&mut buf, 123.456, 2), "1.2e+2");
+//! assert_eq!(to_precision(&mut buf, 0.000001234, 2), "0.0000012");
+//! assert_eq!(to_precision(&mut buf, 0.0, 3), "0.00");
+//! ```
+
+use crate::rounding::round_digits;
+use crate::scaled::exact_expansion;
+use crate::write::{write_general, write_positional, write_scientific, ExpFormat, WriteOptions};
+use crate::{PreFormatted, RoundingMode};
+
+/// Length of the longest string written by the functions of this module.
+///
+/// That is a sign, 21 integer digits, a decimal point and 100 fractional
+/// digits, written by `to_fixed`.
+pub const BUFFER_LEN: usize = 123;
+
+/// Largest number of digits accepted by `to_fixed` and `to_precision`.
+pub const MAX_DIGITS: usize = 100;
+
+const OPTIONS: WriteOptions<'static> = WriteOptions {
+    inf: "Infinity",
+    signed_zero: false,
+    exp_format: ExpFormat {
+        plus_sign: true,
+        min_digits: 1,
+    },
+    ..WriteOptions::DEFAULT
+};
+
+/// Writes `v` like `v.toString()`.
+///
+/// Panics if `buf` is shorter than `BUFFER_LEN`.
+pub fn to_string(buf: &mut [u8], v: f64) -> &str {
+    let mut digits_buf = [0; crate::PREFORMAT_SHORTEST_BUF_LEN];
+    let preformatted = crate::generic::preformat_shortest_even(v, &mut digits_buf);
+    write_general(buf, preformatted, -6..=20, 0, &OPTIONS).expect("buffer too small")
+}
+
+/// Writes `v` like `v.toFixed(fraction_digits)`.
+///
+/// Panics if `fraction_digits` is greater than `MAX_DIGITS` (where
+/// JavaScript throws a `RangeError`), or if `buf` is shorter than
+/// `BUFFER_LEN`.
+pub fn to_fixed(buf: &mut [u8], v: f64, fraction_digits: usize) -> &str {
+    assert!(fraction_digits <= MAX_DIGITS, "too many fraction digits");
+    if v.is_nan() || v >= 1e21 || v <= -1e21 {
+        return to_string(buf, v);
+    }
+    let mut digits_buf = [0; crate::PREFORMAT_EXACT_FIXED_BASE_BUF_LEN];
+    let preformatted = round_exact(&mut digits_buf, v, |exp| exp + fraction_digits as i32);
+    // Only the sign of negative numbers is written, even if they are
+    // rounded to zero.
+    let preformatted = match preformatted {
+        PreFormatted::Zero(true) => PreFormatted::Zero(v < 0.0),
+        other => other,
+    };
+    let options = WriteOptions {
+        signed_zero: true,
+        ..OPTIONS
+    };
+    write_positional(buf, preformatted, fraction_digits, &options).expect("buffer too small")
+}
+
+/// Writes `v` like `v.toPrecision(precision)`.
+///
+/// Panics if `precision` is 0 or greater than `MAX_DIGITS` (where
+/// JavaScript throws a `RangeError`), or if `buf` is shorter than
+/// `BUFFER_LEN`.
+pub fn to_precision(buf: &mut [u8], v: f64, precision: usize) -> &str {
+    assert!((1..=MAX_DIGITS).contains(&precision), "invalid precision");
+    if !v.is_finite() {
+        return to_string(buf, v);
+    }
+    let mut digits_buf = [0; crate::PREFORMAT_EXACT_FIXED_BASE_BUF_LEN];
+    let preformatted = match round_exact(&mut digits_buf, v, |_| precision as i32) {
+        PreFormatted::Finite(sign, digits, _, exp) => {
+            PreFormatted::Finite(sign, digits, precision - digits.len(), exp)
+        }
+        other => other,
+    };
+    // `e` is the exponent in scientific notation.
+    let e = match preformatted {
+        PreFormatted::Finite(_, _, _, exp) => i32::from(exp) - 1,
+        _ => 0,
+    };
+    let s = if e < -6 || e >= precision as i32 {
+        write_scientific(buf, preformatted, precision - 1, &OPTIONS)
+    } else {
+        write_positional(
+            buf,
+            preformatted,
+            (precision as i32 - e - 1) as usize,
+            &OPTIONS,
+        )
+    };
+    s.expect("buffer too small")
+}
+
+/// Rounds the exact value of `v` (which must be finite) to its first
+/// `keep(exp)` digits, with ties away from zero.
+fn round_exact(
+    buf: &mut [u8; crate::PREFORMAT_EXACT_FIXED_BASE_BUF_LEN],
+    v: f64,
+    keep: impl FnOnce(i32) -> i32,
+) -> PreFormatted<'_> {
+    if v == 0.0 {
+        return PreFormatted::Zero(v.is_sign_negative());
+    }
+    let (sign, len, exp) = match exact_expansion(v, buf) {
+        PreFormatted::Finite(sign, digits, _, exp) => (sign, digits.len(), i32::from(exp)),
+        _ => unreachable!(),
+    };
+    // The digits are at the start of `buf`.
+    let digits = &mut buf[..len];
+    let (len, exp) = round_digits(digits, exp, keep(exp), sign, RoundingMode::NearestAway);
+    if len == 0 {
+        PreFormatted::Zero(sign)
+    } else {
+        PreFormatted::Finite(sign, &buf[..len], 0, exp as i16)
+    }
+}
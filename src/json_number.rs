@@ -0,0 +1,169 @@
+//! Conversions between floating point numbers and `serde_json::Number`.
+//!
+//! Requires the `serde_json` feature.
+//!
+//! The value of a `Number` is taken to be its textual representation.
+//! With the `arbitrary_precision` feature of `serde_json`, a `Number`
+//! keeps the original text of the number, so converting it into a float
+//! may lose information. The functions of this module use the exact
+//! decimal value and report whether the conversion was lossless.
+//!
+//! Without `arbitrary_precision`, non-integer numbers are stored as `f64`
+//! and written with the shortest representation that round-trips.
+//!
+//! # Example
+//!
+//! ```
+//! # extern crate serde_json_crate as serde_json;
+//! use flt2dec2flt::json_number::{from_number, from_number_lossless, to_number_shortest};
+//!
+//! let n: serde_json::Number = serde_json::from_str("0.5").unwrap();
+//! assert_eq!(from_number::<f32>(&n), Ok((0.5, true)));
+//! assert_eq!(from_number_lossless::<f32>(&n), Some(0.5));
+//!
+//! let n: serde_json::Number = serde_json::from_str("16777217").unwrap();
+//! assert_eq!(from_number::<f32>(&n), Ok((16777216.0, false)));
+//! assert_eq!(from_number_lossless::<f32>(&n), None);
+//!
+//! assert_eq!(to_number_shortest(0.1f32).unwrap().to_string(), "0.1");
+//! ```
+
+use alloc::string::ToString as _;
+use alloc::vec::Vec;
+
+use ::serde_json::Number;
+
+use crate::grammar::{Grammar, Parsed};
+use crate::write::{write_minimal, WriteOptions};
+use crate::{FloatExt, ParseFloatError, PreFormatted, PreParsed};
+
+/// Large enough for the exact expansion of any `f32` or `f64` written by
+/// `write_minimal`.
+const EXACT_BUF_LEN: usize = crate::PREFORMAT_EXACT_FIXED_BASE_BUF_LEN + 16;
+
+/// Converts `n` into the nearest floating point number (rounding half to
+/// even).
+///
+/// The boolean is `true` when the result has exactly the value of `n`.
+///
+/// Fails in the same cases as `FloatExt::from_preparsed`, which cannot
+/// happen with a valid `Number`.
+pub fn from_number<T: FloatExt>(n: &Number) -> Result<(T, bool), ParseFloatError> {
+    let s = n.to_string();
+    let preparsed = match Grammar::JSON.parse(s.as_bytes()) {
+        Ok(Parsed::Finite(preparsed)) => preparsed,
+        _ => unreachable!("invalid JSON number {:?}", s),
+    };
+    let v = T::from_preparsed(preparsed)?;
+    let exact = is_exact(v, preparsed);
+    Ok((v, exact))
+}
+
+/// Converts `n` into a floating point number, only if it can be done
+/// without losing information.
+pub fn from_number_lossless<T: FloatExt>(n: &Number) -> Option<T> {
+    match from_number(n) {
+        Ok((v, true)) => Some(v),
+        _ => None,
+    }
+}
+
+/// Converts `v` into a `Number` with the shortest representation that
+/// round-trips (see `FloatExt::preformat_shortest`).
+///
+/// Returns `None` if `v` is NaN or infinite.
+pub fn to_number_shortest<T: FloatExt>(v: T) -> Option<Number> {
+    let mut digits_buf = [0; crate::PREFORMAT_SHORTEST_BUF_LEN];
+    let preformatted = v.preformat_shortest(&mut digits_buf);
+    if arbitrary_precision() {
+        to_number(preformatted)
+    } else {
+        // Go through the decimal representation so `f32` values get
+        // their shortest representation.
+        let v = match preformatted {
+            PreFormatted::Finite(sign, digits, _, exp) => {
+                let preparsed = PreParsed {
+                    sign,
+                    int_digits: b"",
+                    frac_digits: digits,
+                    exp: i32::from(exp),
+                };
+                f64::from_preparsed(preparsed).ok()?
+            }
+            _ => v.to_f64(),
+        };
+        Number::from_f64(v)
+    }
+}
+
+/// Converts `v` into a `Number` with its exact decimal expansion.
+///
+/// Without the `arbitrary_precision` feature of `serde_json`, the number
+/// is stored as an `f64`, which can hold the exact value of any `f32` or
+/// `f64`.
+///
+/// Returns `None` if `v` is NaN or infinite.
+pub fn to_number_exact<T: FloatExt>(v: T) -> Option<Number> {
+    if arbitrary_precision() {
+        let mut digits_buf = [0; crate::PREFORMAT_EXACT_FIXED_BASE_BUF_LEN];
+        let preformatted =
+            v.preformat_exact_exp(&mut digits_buf, crate::PREFORMAT_EXACT_FIXED_BASE_BUF_LEN);
+        to_number(trim_zeros(preformatted))
+    } else {
+        Number::from_f64(v.to_f64())
+    }
+}
+
+/// Whether `serde_json` keeps the text of numbers (its
+/// `arbitrary_precision` feature).
+fn arbitrary_precision() -> bool {
+    ::serde_json::from_str::<Number>("0.50").map_or(false, |n| n.to_string() == "0.50")
+}
+
+fn to_number(preformatted: PreFormatted<'_>) -> Option<Number> {
+    if let PreFormatted::NaN | PreFormatted::Inf(_) = preformatted {
+        return None;
+    }
+    let mut buf = [0; EXACT_BUF_LEN];
+    let s = write_minimal(&mut buf, preformatted, &WriteOptions::DEFAULT).unwrap();
+    Some(::serde_json::from_str(s).unwrap())
+}
+
+/// Removes the trailing zeros of an exact expansion.
+fn trim_zeros(preformatted: PreFormatted<'_>) -> PreFormatted<'_> {
+    match preformatted {
+        PreFormatted::Finite(sign, digits, _, exp) => {
+            let len = digits.iter().rposition(|&c| c != b'0').map_or(0, |i| i + 1);
+            PreFormatted::Finite(sign, &digits[..len], 0, exp)
+        }
+        other => other,
+    }
+}
+
+/// Checks whether `v` has exactly the value of `preparsed`.
+fn is_exact<T: FloatExt>(v: T, preparsed: PreParsed<'_>) -> bool {
+    let mut digits: Vec<u8> = preparsed
+        .int_digits
+        .iter()
+        .chain(preparsed.frac_digits)
+        .copied()
+        .collect();
+    let leading = digits.iter().take_while(|&&c| c == b'0').count();
+    digits.drain(..leading);
+    while digits.last() == Some(&b'0') {
+        digits.pop();
+    }
+    // 0.digits * 10^exp
+    let exp = i64::from(preparsed.exp) + preparsed.int_digits.len() as i64 - leading as i64;
+
+    let mut digits_buf = [0; crate::PREFORMAT_EXACT_FIXED_BASE_BUF_LEN];
+    let preformatted =
+        v.preformat_exact_exp(&mut digits_buf, crate::PREFORMAT_EXACT_FIXED_BASE_BUF_LEN);
+    match trim_zeros(preformatted) {
+        PreFormatted::Zero(_) => digits.is_empty(),
+        PreFormatted::Finite(_, v_digits, _, v_exp) => {
+            v_digits == &digits[..] && i64::from(v_exp) == exp
+        }
+        PreFormatted::NaN | PreFormatted::Inf(_) => false,
+    }
+}
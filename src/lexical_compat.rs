@@ -0,0 +1,253 @@
+//! A façade mirroring the float functions of `lexical-core`.
+//!
+//! Projects migrating from `lexical-core` can replace
+//! `lexical_core::write`, `lexical_core::parse` and friends with the
+//! functions of this module. Options are plain structs instead of
+//! builders, and the number format is not a const generic parameter.
+//!
+//! The output follows the defaults of `lexical-core`: positional notation
+//! with at least one fractional digit (`1.0`, `0.001`) for exponents in
+//! `-5..=9`, scientific notation (`1.0e10`, `1.5e-7`) otherwise.
+//!
+//! # Example
+//!
+//! ```
+//! use flt2dec2flt::lexical_compat as lexical_core;
+//!
+//! let mut buf = [0; lexical_core::BUFFER_SIZE];
+//! assert_eq!(lexical_core::write(1.5f64, &mut buf), b"1.5");
+//! assert_eq!(lexical_core::write(1e20f32, &mut buf), b"1.0e20");
+//!
+//! assert_eq!(lexical_core::parse::<f32>(b"1.5"), Ok(1.5));
+//! assert_eq!(lexical_core::parse_partial::<f64>(b"1.5,2"), Ok((1.5, 3)));
+//! assert_eq!(
+//!     lexical_core::parse::<f64>(b"1.5x"),
+//!     Err(lexical_core::Error::InvalidDigit(3)),
+//! );
+//! ```
+
+use core::fmt;
+
+use crate::grammar::Grammar;
+use crate::write::{write_general, ExpFormat, WriteOptions};
+use crate::{FloatExt, ParseFloatError, SyntaxError};
+
+/// A buffer size that is always enough to write a float with the default
+/// options.
+pub const BUFFER_SIZE: usize = 64;
+
+/// A float that can be written by this module.
+pub trait ToLexical: FloatExt {
+    /// Maximum number of bytes written with the default options.
+    const FORMATTED_SIZE_DECIMAL: usize;
+}
+
+impl ToLexical for f32 {
+    const FORMATTED_SIZE_DECIMAL: usize = BUFFER_SIZE;
+}
+
+impl ToLexical for f64 {
+    const FORMATTED_SIZE_DECIMAL: usize = BUFFER_SIZE;
+}
+
+/// A float that can be parsed by this module.
+pub trait FromLexical: FloatExt {}
+
+impl FromLexical for f32 {}
+impl FromLexical for f64 {}
+
+/// Options for `write_with_options`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct WriteFloatOptions<'a> {
+    /// Exponent marker.
+    pub exponent: u8,
+    /// Decimal point.
+    pub decimal_point: u8,
+    /// Written for NaN.
+    pub nan_string: &'a str,
+    /// Written for infinity.
+    pub inf_string: &'a str,
+    /// Whether to omit the `.0` of integral values.
+    pub trim_floats: bool,
+}
+
+impl WriteFloatOptions<'static> {
+    /// Default options.
+    pub const fn new() -> Self {
+        Self {
+            exponent: b'e',
+            decimal_point: b'.',
+            nan_string: "NaN",
+            inf_string: "inf",
+            trim_floats: false,
+        }
+    }
+}
+
+impl Default for WriteFloatOptions<'static> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Options for `parse_with_options`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ParseFloatOptions<'a> {
+    /// Exponent marker, matched ignoring ASCII case.
+    pub exponent: u8,
+    /// Decimal point.
+    pub decimal_point: u8,
+    /// Accepted for NaN, ignoring ASCII case.
+    pub nan_string: &'a str,
+    /// Accepted for infinity, ignoring ASCII case.
+    pub inf_string: &'a str,
+    /// Also accepted for infinity, ignoring ASCII case.
+    pub infinity_string: &'a str,
+}
+
+impl ParseFloatOptions<'static> {
+    /// Default options.
+    pub const fn new() -> Self {
+        Self {
+            exponent: b'e',
+            decimal_point: b'.',
+            nan_string: "NaN",
+            inf_string: "inf",
+            infinity_string: "infinity",
+        }
+    }
+}
+
+impl Default for ParseFloatOptions<'static> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// An error returned when parsing fails.
+///
+/// The `usize` is the position of the error.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error {
+    /// The input does not contain any number.
+    Empty(usize),
+    /// An unexpected byte was found.
+    InvalidDigit(usize),
+    /// The number has too many digits to be converted.
+    Overflow(usize),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Error::Empty(index) => write!(f, "empty number at index {}", index),
+            Error::InvalidDigit(index) => write!(f, "invalid digit at index {}", index),
+            Error::Overflow(index) => write!(f, "number too long at index {}", index),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+/// A result with the error type of this module.
+pub type Result<T> = core::result::Result<T, Error>;
+
+/// Writes `n` into `bytes` with the default options, returning the
+/// written part.
+///
+/// Panics if `bytes` is shorter than `N::FORMATTED_SIZE_DECIMAL`.
+pub fn write<N: ToLexical>(n: N, bytes: &mut [u8]) -> &mut [u8] {
+    write_with_options(n, bytes, &WriteFloatOptions::new())
+}
+
+/// Writes `n` into `bytes` with the given options, returning the written
+/// part.
+///
+/// Panics if `bytes` is too small.
+pub fn write_with_options<'a, N: ToLexical>(
+    n: N,
+    bytes: &'a mut [u8],
+    options: &WriteFloatOptions<'_>,
+) -> &'a mut [u8] {
+    let mut point_buf = [0; 4];
+    let mut exp_buf = [0; 4];
+    let write_options = WriteOptions {
+        nan: options.nan_string,
+        inf: options.inf_string,
+        decimal_point: char::from(options.decimal_point).encode_utf8(&mut point_buf),
+        exp_marker: char::from(options.exponent).encode_utf8(&mut exp_buf),
+        exp_format: ExpFormat::DEFAULT,
+        ..WriteOptions::DEFAULT
+    };
+    let min_frac_digits = if options.trim_floats { 0 } else { 1 };
+
+    let mut digits_buf = [0; crate::PREFORMAT_SHORTEST_BUF_LEN];
+    let preformatted = n.preformat_shortest(&mut digits_buf);
+    let len = write_general(bytes, preformatted, -5..=9, min_frac_digits, &write_options)
+        .expect("buffer too small")
+        .len();
+    &mut bytes[..len]
+}
+
+/// Parses `bytes`, which must be entirely a number, with the default
+/// options.
+pub fn parse<N: FromLexical>(bytes: &[u8]) -> Result<N> {
+    parse_with_options(bytes, &ParseFloatOptions::new())
+}
+
+/// Parses the longest prefix of `bytes` that is a number with the default
+/// options, returning the number and the length of the prefix.
+pub fn parse_partial<N: FromLexical>(bytes: &[u8]) -> Result<(N, usize)> {
+    parse_partial_with_options(bytes, &ParseFloatOptions::new())
+}
+
+/// Parses `bytes`, which must be entirely a number, with the given options.
+pub fn parse_with_options<N: FromLexical>(
+    bytes: &[u8],
+    options: &ParseFloatOptions<'_>,
+) -> Result<N> {
+    let (n, len) = parse_partial_with_options(bytes, options)?;
+    if len == bytes.len() {
+        Ok(n)
+    } else {
+        Err(Error::InvalidDigit(len))
+    }
+}
+
+/// Parses the longest prefix of `bytes` that is a number with the given
+/// options, returning the number and the length of the prefix.
+pub fn parse_partial_with_options<N: FromLexical>(
+    bytes: &[u8],
+    options: &ParseFloatOptions<'_>,
+) -> Result<(N, usize)> {
+    let mut point_buf = [0; 4];
+    let exp_markers = [
+        options.exponent.to_ascii_lowercase(),
+        options.exponent.to_ascii_uppercase(),
+    ];
+    let inf = [options.infinity_string, options.inf_string];
+    let grammar = Grammar {
+        decimal_point: char::from(options.decimal_point).encode_utf8(&mut point_buf),
+        exp_markers: &exp_markers,
+        nan: &[options.nan_string],
+        inf: &inf,
+        ..Grammar::RUST
+    };
+
+    let (parsed, len) = grammar
+        .parse_prefix(bytes)
+        .map_err(|SyntaxError { index }| {
+            if index == bytes.len() {
+                Error::Empty(index)
+            } else {
+                Error::InvalidDigit(index)
+            }
+        })?;
+    match parsed.to_float() {
+        Ok(n) => Ok((n, len)),
+        Err(ParseFloatError::ExponentOutOfRange) => Err(Error::Overflow(0)),
+        Err(_) => unreachable!(),
+    }
+}
@@ -18,11 +18,73 @@
 //! mathematical part.
 //!
 //! The functionality of this crate is provided through the `FloatExt`
-//! trait, which is implemented for `f32` and `f64`.
+//! trait, which is implemented for `f32` and `f64`. The `write` and
+//! `grammar` modules provide common building blocks on top of it, to
+//! assemble pre-formatted numbers into strings and to split strings into
+//! pre-parsed numbers. The `bits` module does the same conversions on the
+//! bit patterns of `f32` and `f64`, with integer arithmetic only, and the
+//! `stream` module parses digits that are not in contiguous slices.
+//!
+//! Formats without a Rust primitive type are handled by the `x87`,
+//! `binary128` and `double_double` modules, and formats defined by the
+//! user (implementing `custom::CustomFloat`) by the `custom` module.
+//! Numbers that are not held in a float type at all can be formatted from
+//! their significand, exponent and rounding interval with the `parts`
+//! module.
 //!
 //! # Minimum Rust version
 //!
 //! The minimum Rust version required by this crate is 1.46.
+//! This covers the features that only enable code of this crate (`std`,
+//! `alloc`, `strategy-*` and `test-vectors`). The ones that integrate with
+//! other crates need the Rust version required by those, and are only
+//! tested with recent compilers.
+//!
+//! The build script detects newer compilers and switches some internal
+//! routines to faster implementations based on more recent `core` APIs,
+//! without changing any result.
+//!
+//! # Cargo features
+//!
+//! * `std`: implements `std::error::Error` for the error types. Implies `alloc`.
+//! * `alloc`: enables items that need heap allocation, such as
+//!   `OwnedPreFormatted`, `OwnedPreParsed` and the `FloatExt::*_vec`
+//!   methods, for exact expansions of any length.
+//! * `serde`: implements `Serialize` and `Deserialize` for `OwnedPreFormatted`
+//!   and `OwnedPreParsed`, and enables the `serde_helpers` module. Implies
+//!   `alloc`.
+//! * `bigdecimal`: enables the `bigdecimal` module, with exact conversions
+//!   to and from `bigdecimal::BigDecimal`. Implies `alloc`.
+//! * `num`: enables `FloatExt::to_exact_rational` and
+//!   `FloatExt::from_rational`, with exact conversions to and from
+//!   `num_rational::BigRational`. Implies `alloc`.
+//! * `serde_json`: enables the `json_number` module, with conversions to and
+//!   from `serde_json::Number`. Implies `alloc`.
+//! * `ordered-float`: implements `FloatExt` for `ordered_float::OrderedFloat`
+//!   and enables the `ordered_float_ext` module, for `ordered_float::NotNan`.
+//! * `half`: implements `FloatExt` for `half::f16` and `half::bf16`, and
+//!   enables the `half_ext` module, with their buffer sizes.
+//! * `heapless`: enables the `heapless_ext` module, to write into
+//!   `heapless::String` and `heapless::Vec`. Requires Rust 1.59.
+//! * `embedded-io`: enables the `embedded_io_ext` module, to write into
+//!   `embedded_io::Write` sinks. Requires Rust 1.60.
+//! * `num-traits`: enables the `generic_float` module, to use this crate
+//!   from code generic over `num_traits::Float`.
+//! * `ufmt`: implements `ufmt::uDisplay` and `ufmt::uDebug` for the
+//!   wrappers of the `display` module.
+//! * `strategy-grisu`, `strategy-ryu`, `strategy-dragonbox`: select the
+//!   algorithm of `FloatExt::preformat_shortest` (and everything built on
+//!   it). Grisu (with a Dragon fallback) is the default, and is always
+//!   compiled since the exact modes use it. Ryu and Dragonbox are only
+//!   compiled when selected, are usually faster and add their own tables
+//!   of powers (about 10 KiB). All of them find the shortest digits that
+//!   round-trip, and the closest of them to the value; when two are equally
+//!   close, Grisu picks the one with the larger magnitude, Ryu and Dragonbox
+//!   the even one. If several are enabled, Dragonbox takes precedence over
+//!   Ryu, and Ryu over Grisu. `FloatExt::preformat_shortest_checked`
+//!   cross-checks the selected algorithm against Dragon.
+//! * `test-vectors`: enables the `test_vectors` module, with classic hard
+//!   cases for parsers and formatters.
 //!
 //! # Example (float to string)
 //!
@@ -110,18 +172,139 @@
 //#![forbid(unsafe_code)]
 #![no_std]
 
-#[cfg(test)]
+#[cfg(any(test, feature = "std"))]
 extern crate std;
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "serde")]
+extern crate serde_crate as serde;
+
+#[cfg(any(all(test, feature = "serde"), feature = "serde_json"))]
+extern crate serde_json_crate as serde_json;
+
+/// Fails to compile if the constant expression `$cond` is false.
+///
+/// Used to check at compile time that computations cannot overflow on any
+/// target (including those with a 16-bit `usize`).
+macro_rules! const_assert {
+    ($cond:expr) => {
+        const _: [(); 0 - !{ $cond } as usize] = [];
+    };
+}
+
 #[rustfmt::skip]
 #[allow(clippy::all, trivial_numeric_casts, unreachable_pub, unused_qualifications)]
 mod core_num;
 
+#[cfg(feature = "bigdecimal")]
+pub mod bigdecimal;
+pub mod bignum;
+pub mod binary128;
+pub mod bits;
+pub mod cbor;
+mod clamp;
+pub mod columns;
+pub mod compact;
+pub mod corpus;
+pub mod custom;
+pub mod decimal128;
+pub mod display;
+pub mod diy_float;
+pub mod double_double;
+#[cfg(feature = "embedded-io")]
+pub mod embedded_io_ext;
+mod error;
+mod error_bound;
+pub mod estimator;
+pub mod flush;
+pub mod fraction;
+pub mod funcs;
+#[cfg(feature = "num-traits")]
+pub mod generic_float;
+pub mod go;
+pub mod grammar;
+#[cfg(feature = "half")]
+pub mod half_ext;
+#[cfg(feature = "heapless")]
+pub mod heapless_ext;
+pub mod ieee_decimal;
+pub mod interval;
+pub mod java;
+pub mod js;
+#[cfg(feature = "serde_json")]
+pub mod json_number;
+pub mod lexical_compat;
+pub mod nice;
+pub mod openmetrics;
+#[cfg(feature = "ordered-float")]
+pub mod ordered_float_ext;
+#[cfg(feature = "alloc")]
+mod owned;
+pub mod parts;
+pub mod postgres;
+mod predecoded;
+pub mod proto3_json;
+pub mod qformat;
+#[cfg(feature = "num")]
+mod ratio;
+#[cfg(feature = "num")]
+mod rational;
+mod rounding;
+pub mod ryu_compat;
+mod scaled;
+pub mod scaled_decimal;
+pub mod scan;
+#[cfg(feature = "serde")]
+pub mod serde_helpers;
+pub mod sink;
+pub mod sql_decimal;
+mod strategy;
+pub mod stream;
+pub mod style;
+mod tables;
+#[cfg(feature = "test-vectors")]
+pub mod test_vectors;
+mod wide;
+pub mod write;
+pub mod x87;
+
+use core::cmp::Ordering;
+
+pub use clamp::Clamped;
+pub use error::{
+    BufferTooSmall, DecimalOverflow, ParseFloatError, PreformatError, ScanError, ScanErrorKind,
+    SyntaxError, TableCorruption,
+};
+#[cfg(feature = "alloc")]
+pub use owned::{OwnedPreFormatted, OwnedPreParsed};
+pub use predecoded::PreDecoded;
+pub use rounding::{Exactness, RoundingMode, SubnormalPolicy};
+pub use tables::verify_tables;
+
 #[cfg(test)]
 mod tests;
 
 mod sealed {
-    pub trait Sealed {}
+    pub trait Sealed: Copy {
+        const NAN: Self;
+        const INFINITY: Self;
+        const NEG_INFINITY: Self;
+        /// Total number of bits.
+        const BITS: u32;
+        /// Number of significand bits, including the hidden bit.
+        const SIG_BITS: u32;
+        /// Binary exponent of the least significant bit of subnormals.
+        const MIN_LSB_EXP: i32;
+        /// Binary exponent of the largest finite value.
+        const MAX_EXP: i32;
+
+        fn from_bits_u64(bits: u64) -> Self;
+        fn to_bits_u64(self) -> u64;
+        /// Converts to `f64`, which is always exact.
+        fn to_f64(self) -> f64;
+    }
 }
 
 /// Minimum buffer size that has to be passed to `FloatExt::preformat_shortest`.
@@ -129,7 +312,21 @@ pub const PREFORMAT_SHORTEST_BUF_LEN: usize = core_num::flt2dec::MAX_SIG_DIGITS;
 
 /// Minimum base buffer size that has to be passed to `FloatExt::preformat_exact_fixed`.
 // See comment in `core_num::flt2dec::estimate_max_buf_len` for the origin of the value
-pub const PREFORMAT_EXACT_FIXED_BASE_BUF_LEN: usize = 826;
+// (the smallest exponent is -1076, for the smallest positive normal `f64`)
+pub const PREFORMAT_EXACT_FIXED_BASE_BUF_LEN: usize = 828;
+
+const_assert!(21 + ((12 * 1076) >> 4) == PREFORMAT_EXACT_FIXED_BASE_BUF_LEN);
+
+fn check_buf_len(buf: &[u8], required: usize) -> Result<(), BufferTooSmall> {
+    if buf.len() < required {
+        Err(BufferTooSmall {
+            required,
+            actual: buf.len(),
+        })
+    } else {
+        Ok(())
+    }
+}
 
 /// Represents a pre-formatted floating point number.
 ///
@@ -151,17 +348,423 @@ pub enum PreFormatted<'a> {
     Finite(bool, &'a [u8], usize, i16),
 }
 
+/// A pre-formatted number laid out for positional notation.
+///
+/// Converted from a `PreFormatted` with `From`, it tells where the
+/// decimal point goes without exponent arithmetic.
+///
+/// # Example
+///
+/// ```
+/// use flt2dec2flt::{FloatExt as _, PreFormattedFixed};
+///
+/// let mut buf = [0; flt2dec2flt::PREFORMAT_SHORTEST_BUF_LEN];
+///
+/// // 0.0030
+/// let fixed = PreFormattedFixed::from(0.003.preformat_shortest(&mut buf));
+/// assert_eq!(
+///     fixed,
+///     PreFormattedFixed::Finite {
+///         sign: false,
+///         digits: b"3",
+///         num_zeros: 0,
+///         point_index: 0,
+///         int_len: 0,
+///         frac_leading_zeros: 2,
+///     },
+/// );
+/// assert_eq!(fixed.frac_len(), 3);
+///
+/// // 1200
+/// let fixed = PreFormattedFixed::from(1200.0.preformat_shortest(&mut buf));
+/// assert_eq!(
+///     fixed,
+///     PreFormattedFixed::Finite {
+///         sign: false,
+///         digits: b"12",
+///         num_zeros: 0,
+///         point_index: 2,
+///         int_len: 4,
+///         frac_leading_zeros: 0,
+///     },
+/// );
+/// assert_eq!(fixed.int_zeros(), 2);
+/// assert_eq!(fixed.frac_len(), 0);
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PreFormattedFixed<'a> {
+    /// The number is NaN.
+    NaN,
+    /// The number is infinity. The boolean specifies the sign.
+    Inf(bool),
+    /// The number is zero. The boolean specifies the sign.
+    Zero(bool),
+    /// The number is finite, with the digits and the zero run of
+    /// `PreFormatted::Finite`.
+    ///
+    /// The integer part is `digits[..point_index]` followed by zeros up to
+    /// `int_len` digits (none when it is zero). The fractional part is
+    /// `frac_leading_zeros` zeros, `digits[point_index..]` and the rest of
+    /// the zero run.
+    Finite {
+        sign: bool,
+        digits: &'a [u8],
+        num_zeros: usize,
+        /// Index of the first fractional digit in `digits`.
+        point_index: usize,
+        /// Number of integer digits.
+        int_len: usize,
+        /// Number of zeros between the decimal point and `digits`.
+        frac_leading_zeros: usize,
+    },
+}
+
+impl PreFormattedFixed<'_> {
+    /// Returns the number of zeros written after `digits[..point_index]`
+    /// in the integer part (`0` if not finite).
+    pub fn int_zeros(&self) -> usize {
+        match *self {
+            PreFormattedFixed::Finite {
+                point_index,
+                int_len,
+                ..
+            } => int_len - point_index,
+            _ => 0,
+        }
+    }
+
+    /// Returns the number of zeros written after `digits[point_index..]`
+    /// in the fractional part (`0` if not finite).
+    pub fn frac_trailing_zeros(&self) -> usize {
+        match *self {
+            PreFormattedFixed::Finite {
+                digits,
+                num_zeros,
+                int_len,
+                ..
+            } => digits
+                .len()
+                .saturating_add(num_zeros)
+                .saturating_sub(int_len.max(digits.len())),
+            _ => 0,
+        }
+    }
+
+    /// Returns the number of fractional digits (`0` if not finite).
+    pub fn frac_len(&self) -> usize {
+        match *self {
+            PreFormattedFixed::Finite {
+                digits,
+                point_index,
+                frac_leading_zeros,
+                ..
+            } => frac_leading_zeros
+                .saturating_add(digits.len() - point_index)
+                .saturating_add(self.frac_trailing_zeros()),
+            _ => 0,
+        }
+    }
+}
+
+impl<'a> From<PreFormatted<'a>> for PreFormattedFixed<'a> {
+    fn from(preformatted: PreFormatted<'a>) -> Self {
+        match preformatted {
+            PreFormatted::NaN => PreFormattedFixed::NaN,
+            PreFormatted::Inf(sign) => PreFormattedFixed::Inf(sign),
+            PreFormatted::Zero(sign) => PreFormattedFixed::Zero(sign),
+            PreFormatted::Finite(sign, digits, num_zeros, exp) => {
+                let (int_len, frac_leading_zeros) = if exp > 0 {
+                    (exp as usize, 0)
+                } else {
+                    (0, (-i32::from(exp)) as usize)
+                };
+                PreFormattedFixed::Finite {
+                    sign,
+                    digits,
+                    num_zeros,
+                    point_index: int_len.min(digits.len()),
+                    int_len,
+                    frac_leading_zeros,
+                }
+            }
+        }
+    }
+}
+
 /// A pre-parsed decimal floating point number.
 ///
 /// The represented value is `sign int_digits.frac_digits * 10 ^ exp`.
 ///
+/// Any exponent is accepted: the conversions clamp values that are too
+/// large or too small for the target type to infinity or zero, so
+/// exponents such as `-40000` can be combined with many digits.
+///
 /// Passed to `flt2dec2flt::f{32,64}::from_preparsed`.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct PreParsed<'a> {
     pub sign: bool,
     pub int_digits: &'a [u8],
     pub frac_digits: &'a [u8],
-    pub exp: i16,
+    pub exp: i32,
+}
+
+impl<'a> PreParsed<'a> {
+    /// Removes the leading zeros of the digits, decrementing the exponent
+    /// for the zeros removed from `frac_digits` (`0.05e0` becomes `.5e-1`).
+    ///
+    /// Zero is left as a single `0` with exponent `0`.
+    ///
+    /// Fails with `ParseFloatError::ExponentOutOfRange` if the exponent
+    /// does not fit in an `i32`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use flt2dec2flt::PreParsed;
+    ///
+    /// let preparsed = PreParsed {
+    ///     sign: false,
+    ///     int_digits: b"00",
+    ///     frac_digits: b"0250",
+    ///     exp: 3,
+    /// };
+    /// let trimmed = preparsed.trim_leading_zeros().unwrap();
+    /// assert_eq!(trimmed.int_digits, b"");
+    /// assert_eq!(trimmed.frac_digits, b"250");
+    /// assert_eq!(trimmed.exp, 2);
+    /// ```
+    pub fn trim_leading_zeros(self) -> Result<Self, ParseFloatError> {
+        let int_zeros = leading_zeros(self.int_digits);
+        if int_zeros != self.int_digits.len() {
+            return Ok(Self {
+                int_digits: &self.int_digits[int_zeros..],
+                ..self
+            });
+        }
+        let frac_zeros = leading_zeros(self.frac_digits);
+        if frac_zeros == self.frac_digits.len() {
+            return Ok(self.zero());
+        }
+        Ok(Self {
+            int_digits: &[],
+            frac_digits: &self.frac_digits[frac_zeros..],
+            exp: shift_exp(self.exp, -(frac_zeros as i64))?,
+            ..self
+        })
+    }
+
+    /// Removes the trailing zeros of the digits, incrementing the exponent
+    /// for the zeros removed from `int_digits` (`1200.0e0` becomes
+    /// `12.e2`).
+    ///
+    /// Zero is left as a single `0` with exponent `0`.
+    ///
+    /// Fails with `ParseFloatError::ExponentOutOfRange` if the exponent
+    /// does not fit in an `i32`.
+    pub fn trim_trailing_zeros(self) -> Result<Self, ParseFloatError> {
+        let frac_zeros = trailing_zeros(self.frac_digits);
+        if frac_zeros != self.frac_digits.len() {
+            return Ok(Self {
+                frac_digits: &self.frac_digits[..self.frac_digits.len() - frac_zeros],
+                ..self
+            });
+        }
+        let int_zeros = trailing_zeros(self.int_digits);
+        if int_zeros == self.int_digits.len() {
+            return Ok(self.zero());
+        }
+        Ok(Self {
+            int_digits: &self.int_digits[..self.int_digits.len() - int_zeros],
+            frac_digits: &[],
+            exp: shift_exp(self.exp, int_zeros as i64)?,
+            ..self
+        })
+    }
+
+    /// Copies the integer and fractional digits into `buf` as integer
+    /// digits, subtracting the number of fractional digits from the
+    /// exponent (`1.25e0` becomes `125.e-2`).
+    ///
+    /// Fails with `ParseFloatError::ExponentOutOfRange` if the exponent
+    /// does not fit in an `i32`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `buf` is shorter than the number of digits.
+    pub fn merge_digits<'b>(&self, buf: &'b mut [u8]) -> Result<PreParsed<'b>, ParseFloatError> {
+        let int_len = self.int_digits.len();
+        let len = int_len + self.frac_digits.len();
+        let exp = shift_exp(self.exp, -(self.frac_digits.len() as i64))?;
+        buf[..int_len].copy_from_slice(self.int_digits);
+        buf[int_len..len].copy_from_slice(self.frac_digits);
+        Ok(PreParsed {
+            sign: self.sign,
+            int_digits: &buf[..len],
+            frac_digits: &[],
+            exp,
+        })
+    }
+
+    /// Returns the number of significant digits, from the first nonzero
+    /// digit to the last one (`0` for zero).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use flt2dec2flt::PreParsed;
+    ///
+    /// let preparsed = PreParsed {
+    ///     sign: false,
+    ///     int_digits: b"0120",
+    ///     frac_digits: b"0500",
+    ///     exp: 0,
+    /// };
+    /// assert_eq!(preparsed.significant_digits(), 5);
+    /// ```
+    pub fn significant_digits(&self) -> usize {
+        let digits = || self.int_digits.iter().chain(self.frac_digits);
+        let len = self.int_digits.len() + self.frac_digits.len();
+        match digits().position(|&c| c != b'0') {
+            Some(first) => len - first - digits().rev().position(|&c| c != b'0').unwrap(),
+            None => 0,
+        }
+    }
+
+    /// Compares the exact values of two decimal numbers, without
+    /// converting them to floating point. Zero and negative zero are
+    /// equal.
+    ///
+    /// The digits are expected to be ASCII digits.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use core::cmp::Ordering;
+    /// use flt2dec2flt::{FloatExt as _, PreParsed};
+    ///
+    /// let bound = PreParsed {
+    ///     sign: false,
+    ///     int_digits: b"0",
+    ///     frac_digits: b"1",
+    ///     exp: 0,
+    /// };
+    /// // The nearest `f64` to 0.1 is larger than 0.1
+    /// let input = PreParsed {
+    ///     sign: false,
+    ///     int_digits: b"",
+    ///     frac_digits: b"1000000000000000055511151231257827",
+    ///     exp: 0,
+    /// };
+    /// assert_eq!(f64::from_preparsed(input), f64::from_preparsed(bound));
+    /// assert_eq!(input.cmp_exact(&bound), Ordering::Greater);
+    ///
+    /// let input = PreParsed {
+    ///     sign: false,
+    ///     int_digits: b"100",
+    ///     frac_digits: b"",
+    ///     exp: -3,
+    /// };
+    /// assert_eq!(input.cmp_exact(&bound), Ordering::Equal);
+    /// ```
+    pub fn cmp_exact(&self, other: &PreParsed<'_>) -> Ordering {
+        let (a, b) = (self.significand(), other.significand());
+        let ordering = match (a, b) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => {
+                return if other.sign {
+                    Ordering::Greater
+                } else {
+                    Ordering::Less
+                }
+            }
+            (Some(_), None) => {
+                return if self.sign {
+                    Ordering::Less
+                } else {
+                    Ordering::Greater
+                }
+            }
+            _ if self.sign != other.sign => {
+                return if self.sign {
+                    Ordering::Less
+                } else {
+                    Ordering::Greater
+                };
+            }
+            (Some((a_order, a_digits)), Some((b_order, b_digits))) => {
+                a_order.cmp(&b_order).then_with(|| {
+                    let len = a_digits.clone().count().max(b_digits.clone().count());
+                    let zeros = core::iter::repeat(&b'0');
+                    a_digits
+                        .chain(zeros.clone())
+                        .take(len)
+                        .cmp(b_digits.chain(zeros).take(len))
+                })
+            }
+        };
+        if self.sign {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    }
+
+    /// Returns the position of the first significant digit (`n` for
+    /// `0.d * 10^n`) and the digits from it, or `None` for zero.
+    fn significand(&self) -> Option<(i64, impl Iterator<Item = &'a u8> + Clone)> {
+        let digits = self.int_digits.iter().chain(self.frac_digits);
+        let leading_zeros = digits.clone().take_while(|&&c| c == b'0').count();
+        if leading_zeros == self.int_digits.len() + self.frac_digits.len() {
+            return None;
+        }
+        let order = i64::from(self.exp) + self.int_digits.len() as i64 - leading_zeros as i64;
+        Some((order, digits.skip(leading_zeros)))
+    }
+
+    /// Zero with the same sign, as a single `0` (or without digits if there
+    /// are none).
+    fn zero(self) -> Self {
+        let digits = if self.int_digits.is_empty() {
+            self.frac_digits
+        } else {
+            self.int_digits
+        };
+        Self {
+            sign: self.sign,
+            int_digits: &digits[..digits.len().min(1)],
+            frac_digits: &[],
+            exp: 0,
+        }
+    }
+}
+
+fn leading_zeros(digits: &[u8]) -> usize {
+    digits.iter().take_while(|&&c| c == b'0').count()
+}
+
+fn trailing_zeros(digits: &[u8]) -> usize {
+    digits.iter().rev().take_while(|&&c| c == b'0').count()
+}
+
+fn shift_exp(exp: i32, shift: i64) -> Result<i32, ParseFloatError> {
+    let exp = i64::from(exp) + shift;
+    if exp < i64::from(i32::MIN) || exp > i64::from(i32::MAX) {
+        Err(ParseFloatError::ExponentOutOfRange)
+    } else {
+        Ok(exp as i32)
+    }
+}
+
+/// A pre-parsed hexadecimal floating point number, such as `0x1.8p3`.
+///
+/// The represented value is `sign 0xint_digits.frac_digits * 2 ^ exp`,
+/// where the digits are ASCII hexadecimal digits (in either case).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PreParsedHex<'a> {
+    pub sign: bool,
+    pub int_digits: &'a [u8],
+    pub frac_digits: &'a [u8],
+    pub exp: i32,
 }
 
 /// This trait is used to extend `f32` and `f64`.
@@ -215,7 +818,9 @@ pub trait FloatExt: sealed::Sealed + Sized {
 
     /// Pre-formats a `f32` with an exact number of significant digits.
     ///
-    /// `buf` must be at least `num_digits` long.
+    /// `buf` must be at least `num_digits` long (or
+    /// `flt2dec2flt::PREFORMAT_EXACT_FIXED_BASE_BUF_LEN`, if that is
+    /// smaller), and `num_digits` must not be zero.
     ///
     /// # Example
     ///
@@ -264,7 +869,12 @@ pub trait FloatExt: sealed::Sealed + Sized {
 
     /// Pre-formats a `f32` with an exact number of fractional digits.
     ///
-    /// `buf` must be at least `flt2dec2flt::PREFORMAT_EXACT_FIXED_BASE_BUF_LEN + num_frac_digits`.
+    /// A negative `num_frac_digits` rounds to the left of the decimal point,
+    /// such as to hundreds with `-2`, and the rounded integer digits are
+    /// returned as extra zeros.
+    ///
+    /// `buf` must be at least `flt2dec2flt::PREFORMAT_EXACT_FIXED_BASE_BUF_LEN`
+    /// long, whatever `num_frac_digits` is.
     ///
     /// ```
     /// use flt2dec2flt::FloatExt as _;
@@ -298,105 +908,1373 @@ pub trait FloatExt: sealed::Sealed + Sized {
     ///
     /// let preformatted = f32::preformat_exact_fixed(0.3e-4, &mut buf, 2);
     /// assert_eq!(preformatted, flt2dec2flt::PreFormatted::Zero(false));
+    ///
+    /// // Rounded to thousands.
+    /// let preformatted = f32::preformat_exact_fixed(123456.0, &mut buf, -3);
+    /// assert_eq!(
+    ///     preformatted,
+    ///     flt2dec2flt::PreFormatted::Finite(false, b"123", 3, 6),
+    /// );
+    ///
+    /// let preformatted = f32::preformat_exact_fixed(400.0, &mut buf, -3);
+    /// assert_eq!(preformatted, flt2dec2flt::PreFormatted::Zero(false));
     /// ```
-    fn preformat_exact_fixed(self, buf: &mut [u8], num_frac_digits: usize) -> PreFormatted<'_>;
+    fn preformat_exact_fixed(self, buf: &mut [u8], num_frac_digits: isize) -> PreFormatted<'_>;
 
-    /// Creates a floating point number from a pre-parsed decimal
-    /// floating point number (see `PreParsed`).
+    /// Pre-formats a `f32` rounded at the `10^limit` place, that is, with
+    /// `-limit` fractional digits when `limit` is negative, or rounded to
+    /// a multiple of `10^limit` when it is positive (such as to hundreds
+    /// with a `limit` of 2). Rounds half to even.
     ///
-    /// # Example
+    /// The last digit of the result (including the extra zeros) is at the
+    /// `10^limit` place.
+    ///
+    /// `buf` must be at least `flt2dec2flt::PREFORMAT_EXACT_FIXED_BASE_BUF_LEN`.
     ///
     /// ```
     /// use flt2dec2flt::FloatExt as _;
     ///
-    /// let v = f32::from_preparsed(flt2dec2flt::PreParsed {
-    ///     sign: false,
-    ///     int_digits: b"12",
-    ///     frac_digits: b"34",
-    ///     exp: 0,
-    /// });
-    /// assert!((v.unwrap() - 12.34).abs() < 1e-9);
+    /// let mut buf = [0; flt2dec2flt::PREFORMAT_EXACT_FIXED_BASE_BUF_LEN];
     ///
-    /// let v = f32::from_preparsed(flt2dec2flt::PreParsed {
-    ///     sign: false,
-    ///     int_digits: b"0",
-    ///     frac_digits: b"41",
-    ///     exp: -4,
-    /// });
-    /// assert!((v.unwrap() - 0.41e-4).abs() < 1e-12);
+    /// let preformatted = f32::preformat_exact_limit(12345.0, &mut buf, 2);
+    /// assert_eq!(
+    ///     preformatted,
+    ///     flt2dec2flt::PreFormatted::Finite(false, b"123", 0, 5),
+    /// );
+    ///
+    /// let preformatted = f32::preformat_exact_limit(12.3456, &mut buf, -2);
+    /// assert_eq!(
+    ///     preformatted,
+    ///     flt2dec2flt::PreFormatted::Finite(false, b"1235", 0, 2),
+    /// );
+    ///
+    /// // Rounding can add a digit.
+    /// let preformatted = f32::preformat_exact_limit(960.0, &mut buf, 2);
+    /// assert_eq!(
+    ///     preformatted,
+    ///     flt2dec2flt::PreFormatted::Finite(false, b"10", 0, 4),
+    /// );
+    ///
+    /// let preformatted = f32::preformat_exact_limit(40.0, &mut buf, 2);
+    /// assert_eq!(preformatted, flt2dec2flt::PreFormatted::Zero(false));
     /// ```
-    fn from_preparsed(preparsed: PreParsed<'_>) -> Option<Self>;
-}
+    fn preformat_exact_limit(self, buf: &mut [u8], limit: i16) -> PreFormatted<'_>;
 
-mod generic {
-    use crate::core_num::flt2dec::decoder::DecodableFloat;
-    use crate::{core_num, PreFormatted, PreParsed};
+    /// Like `preformat_shortest`, but fails instead of panicking if `buf` is
+    /// shorter than `flt2dec2flt::PREFORMAT_SHORTEST_BUF_LEN`.
+    ///
+    /// ```
+    /// use flt2dec2flt::{BufferTooSmall, FloatExt as _, PreFormatted, PreformatError};
+    ///
+    /// let mut buf = [0; 10];
+    /// assert_eq!(
+    ///     1.5f64.try_preformat_shortest(&mut buf),
+    ///     Err(PreformatError::BufferTooSmall(BufferTooSmall {
+    ///         required: flt2dec2flt::PREFORMAT_SHORTEST_BUF_LEN,
+    ///         actual: 10,
+    ///     })),
+    /// );
+    ///
+    /// let mut buf = [0; flt2dec2flt::PREFORMAT_SHORTEST_BUF_LEN];
+    /// assert_eq!(
+    ///     1.5f64.try_preformat_shortest(&mut buf),
+    ///     Ok(PreFormatted::Finite(false, b"15", 0, 1)),
+    /// );
+    /// ```
+    fn try_preformat_shortest(self, buf: &mut [u8]) -> Result<PreFormatted<'_>, PreformatError> {
+        check_buf_len(buf, PREFORMAT_SHORTEST_BUF_LEN)?;
+        Ok(self.preformat_shortest(buf))
+    }
 
-    pub(crate) fn preformat_shortest<T: DecodableFloat>(v: T, buf: &mut [u8]) -> PreFormatted<'_> {
-        let (sign, full_decoded) = core_num::flt2dec::decoder::decode(v);
-        match full_decoded {
-            core_num::flt2dec::decoder::FullDecoded::Nan => PreFormatted::NaN,
-            core_num::flt2dec::decoder::FullDecoded::Infinite => PreFormatted::Inf(sign),
-            core_num::flt2dec::decoder::FullDecoded::Zero => PreFormatted::Zero(sign),
-            core_num::flt2dec::decoder::FullDecoded::Finite(ref decoded) => {
-                let (digits, exp) =
-                    core_num::flt2dec::strategy::grisu::format_shortest(decoded, buf);
-                PreFormatted::Finite(sign, digits, 0, exp)
-            }
+    /// Like `preformat_exact_exp`, but fails instead of panicking if
+    /// `num_digits` is zero or `buf` is too short. `buf` must be at least
+    /// `num_digits` or `flt2dec2flt::PREFORMAT_EXACT_FIXED_BASE_BUF_LEN`
+    /// long, whichever is smaller (no number has more significant digits).
+    ///
+    /// ```
+    /// use flt2dec2flt::{BufferTooSmall, FloatExt as _, PreFormatted, PreformatError};
+    ///
+    /// let mut buf = [0; 4];
+    /// assert_eq!(
+    ///     0.1f32.try_preformat_exact_exp(&mut buf, 10),
+    ///     Err(PreformatError::BufferTooSmall(BufferTooSmall { required: 10, actual: 4 })),
+    /// );
+    /// assert_eq!(
+    ///     0.1f32.try_preformat_exact_exp(&mut buf, 0),
+    ///     Err(PreformatError::ZeroDigits),
+    /// );
+    /// assert_eq!(
+    ///     0.1f32.try_preformat_exact_exp(&mut buf, 3),
+    ///     Ok(PreFormatted::Finite(false, b"100", 0, 0)),
+    /// );
+    /// ```
+    fn try_preformat_exact_exp(
+        self,
+        buf: &mut [u8],
+        num_digits: usize,
+    ) -> Result<PreFormatted<'_>, PreformatError> {
+        if num_digits == 0 {
+            return Err(PreformatError::ZeroDigits);
         }
+        check_buf_len(buf, num_digits.min(PREFORMAT_EXACT_FIXED_BASE_BUF_LEN))?;
+        Ok(self.preformat_exact_exp(buf, num_digits))
     }
 
-    pub(crate) fn preformat_exact_exp<T: DecodableFloat>(
-        v: T,
+    /// Like `preformat_exact_fixed`, but fails instead of panicking if `buf`
+    /// is shorter than `flt2dec2flt::PREFORMAT_EXACT_FIXED_BASE_BUF_LEN`
+    /// (which is enough for any `num_frac_digits`).
+    ///
+    /// ```
+    /// use flt2dec2flt::{BufferTooSmall, FloatExt as _, PreFormatted, PreformatError};
+    ///
+    /// let mut buf = [0; 100];
+    /// assert_eq!(
+    ///     0.5f64.try_preformat_exact_fixed(&mut buf, 2),
+    ///     Err(PreformatError::BufferTooSmall(BufferTooSmall {
+    ///         required: flt2dec2flt::PREFORMAT_EXACT_FIXED_BASE_BUF_LEN,
+    ///         actual: 100,
+    ///     })),
+    /// );
+    ///
+    /// let mut buf = [0; flt2dec2flt::PREFORMAT_EXACT_FIXED_BASE_BUF_LEN];
+    /// assert_eq!(
+    ///     0.5f64.try_preformat_exact_fixed(&mut buf, 2),
+    ///     Ok(PreFormatted::Finite(false, b"50", 0, 0)),
+    /// );
+    /// ```
+    fn try_preformat_exact_fixed(
+        self,
         buf: &mut [u8],
-        ndigits: usize,
-    ) -> PreFormatted<'_> {
-        let (sign, full_decoded) = core_num::flt2dec::decoder::decode(v);
-        match full_decoded {
-            core_num::flt2dec::decoder::FullDecoded::Nan => PreFormatted::NaN,
-            core_num::flt2dec::decoder::FullDecoded::Infinite => PreFormatted::Inf(sign),
-            core_num::flt2dec::decoder::FullDecoded::Zero => PreFormatted::Zero(sign),
-            core_num::flt2dec::decoder::FullDecoded::Finite(ref decoded) => {
-                // Similar as done in `core::num::flt2dec::to_exact_exp_str`
-
-                let maxlen = core_num::flt2dec::estimate_max_buf_len(decoded.exp);
-                let trunc = if ndigits < maxlen { ndigits } else { maxlen };
-
-                let (digits, exp) = core_num::flt2dec::strategy::grisu::format_exact(
-                    decoded,
-                    &mut buf[..trunc],
-                    i16::min_value(),
-                );
-                PreFormatted::Finite(sign, digits, ndigits - digits.len(), exp)
-            }
-        }
+        num_frac_digits: isize,
+    ) -> Result<PreFormatted<'_>, PreformatError> {
+        check_buf_len(buf, PREFORMAT_EXACT_FIXED_BASE_BUF_LEN)?;
+        Ok(self.preformat_exact_fixed(buf, num_frac_digits))
     }
 
-    pub(crate) fn preformat_exact_fixed<T: DecodableFloat>(
-        v: T,
+    /// Like `preformat_exact_limit`, but fails instead of panicking if
+    /// `buf` is shorter than `flt2dec2flt::PREFORMAT_EXACT_FIXED_BASE_BUF_LEN`.
+    fn try_preformat_exact_limit(
+        self,
         buf: &mut [u8],
-        frac_digits: usize,
-    ) -> PreFormatted<'_> {
-        let (sign, full_decoded) = core_num::flt2dec::decoder::decode(v);
-        match full_decoded {
-            core_num::flt2dec::decoder::FullDecoded::Nan => PreFormatted::NaN,
-            core_num::flt2dec::decoder::FullDecoded::Infinite => PreFormatted::Inf(sign),
-            core_num::flt2dec::decoder::FullDecoded::Zero => PreFormatted::Zero(sign),
-            core_num::flt2dec::decoder::FullDecoded::Finite(ref decoded) => {
-                // Similar as done in `core::num::flt2dec::to_exact_fixed_str`
+        limit: i16,
+    ) -> Result<PreFormatted<'_>, PreformatError> {
+        check_buf_len(buf, PREFORMAT_EXACT_FIXED_BASE_BUF_LEN)?;
+        Ok(self.preformat_exact_limit(buf, limit))
+    }
 
-                let maxlen = core_num::flt2dec::estimate_max_buf_len(decoded.exp);
+    /// Like `try_preformat_shortest`, also finding the digits with Dragon,
+    /// a slower but simpler algorithm, and failing if they differ from
+    /// those of the selected strategy (see the crate documentation).
+    ///
+    /// This takes more than twice as long, but does not trust a single
+    /// implementation, as required by some certification regimes.
+    ///
+    /// ```
+    /// use flt2dec2flt::{FloatExt as _, PreFormatted};
+    ///
+    /// let mut buf = [0; flt2dec2flt::PREFORMAT_SHORTEST_BUF_LEN];
+    /// assert_eq!(
+    ///     0.3f64.preformat_shortest_checked(&mut buf),
+    ///     Ok(PreFormatted::Finite(false, b"3", 0, 0)),
+    /// );
+    /// ```
+    fn preformat_shortest_checked(
+        self,
+        buf: &mut [u8],
+    ) -> Result<PreFormatted<'_>, PreformatError> {
+        check_buf_len(buf, PREFORMAT_SHORTEST_BUF_LEN)?;
+        generic::preformat_shortest_checked(self, buf)
+    }
 
-                // it *is* possible that `frac_digits` is ridiculously large.
-                // `format_exact` will end rendering digits much earlier in this case,
-                // because we are strictly limited by `maxlen`.
-                let limit = if frac_digits < 0x8000 {
-                    -(frac_digits as i16)
-                } else {
-                    i16::min_value()
-                };
-                let (digits, exp) = core_num::flt2dec::strategy::grisu::format_exact(
-                    decoded,
+    /// Pre-formats `self * 2^scale2` like `preformat_shortest`.
+    ///
+    /// The scaling is exact (only the binary exponent changes) and the
+    /// precision of `Self` is kept, so the digits are the shortest that
+    /// identify `self` among the numbers of its type, all of them scaled.
+    /// This allows showing fixed-point values stored in floats (such as
+    /// audio samples) at their true scale.
+    ///
+    /// Panics if `self * 2^scale2` or its precision are out of the range
+    /// of `f64`, that is, if it is `2^1024` or larger, or if the unit in
+    /// the last place of `self`, scaled, is smaller than `2^-1074`.
+    ///
+    /// ```
+    /// use flt2dec2flt::FloatExt as _;
+    /// use flt2dec2flt::PreFormatted;
+    ///
+    /// let mut buf = [0; flt2dec2flt::PREFORMAT_SHORTEST_BUF_LEN];
+    ///
+    /// let preformatted = 0.1f32.preformat_shortest_scale2(&mut buf, -3);
+    /// assert_eq!(preformatted, PreFormatted::Finite(false, b"125", 0, -1));
+    ///
+    /// // Beyond the range of `f32`
+    /// let preformatted = 1.5f32.preformat_shortest_scale2(&mut buf, 200);
+    /// assert_eq!(preformatted, PreFormatted::Finite(false, b"2410407", 0, 61));
+    /// ```
+    fn preformat_shortest_scale2(self, buf: &mut [u8], scale2: i32) -> PreFormatted<'_> {
+        let (sign, full_decoded) = generic::decode_scale2(self, scale2);
+        generic::preformat_shortest_decoded(sign, full_decoded, buf)
+    }
+
+    /// Pre-formats `self * 2^scale2` like `preformat_exact_exp`.
+    ///
+    /// Panics if `self * 2^scale2` or its precision are out of the range
+    /// of `f64` (see `preformat_shortest_scale2`).
+    ///
+    /// ```
+    /// use flt2dec2flt::FloatExt as _;
+    /// use flt2dec2flt::PreFormatted;
+    ///
+    /// let mut buf = [0; flt2dec2flt::PREFORMAT_EXACT_FIXED_BASE_BUF_LEN];
+    ///
+    /// // A Q15 sample
+    /// let preformatted = 12345.0f32.preformat_exact_exp_scale2(&mut buf, 20, -15);
+    /// assert_eq!(preformatted, PreFormatted::Finite(false, b"37673950195312500000", 0, 0));
+    /// ```
+    fn preformat_exact_exp_scale2(
+        self,
+        buf: &mut [u8],
+        num_digits: usize,
+        scale2: i32,
+    ) -> PreFormatted<'_> {
+        let (sign, full_decoded) = generic::decode_scale2(self, scale2);
+        generic::preformat_exact_exp_decoded(sign, full_decoded, buf, num_digits)
+    }
+
+    /// Pre-formats `self * 2^scale2` like `preformat_exact_fixed`.
+    ///
+    /// Panics if `self * 2^scale2` or its precision are out of the range
+    /// of `f64` (see `preformat_shortest_scale2`).
+    ///
+    /// ```
+    /// use flt2dec2flt::FloatExt as _;
+    /// use flt2dec2flt::PreFormatted;
+    ///
+    /// let mut buf = [0; flt2dec2flt::PREFORMAT_EXACT_FIXED_BASE_BUF_LEN + 4];
+    ///
+    /// // A Q15 sample
+    /// let preformatted = (-12345.0f32).preformat_exact_fixed_scale2(&mut buf, 4, -15);
+    /// assert_eq!(preformatted, PreFormatted::Finite(true, b"3767", 0, 0));
+    /// ```
+    fn preformat_exact_fixed_scale2(
+        self,
+        buf: &mut [u8],
+        num_frac_digits: isize,
+        scale2: i32,
+    ) -> PreFormatted<'_> {
+        let (sign, full_decoded) = generic::decode_scale2(self, scale2);
+        generic::preformat_exact_fixed_decoded(sign, full_decoded, buf, num_frac_digits)
+    }
+
+    /// Pre-formats `self` like `preformat_exact_exp`, rounding the exact
+    /// value with `mode` instead of to nearest.
+    ///
+    /// Rounding with `RoundingMode::Down` and `RoundingMode::Up` gives
+    /// decimal lower and upper bounds of `self`.
+    ///
+    /// `buf` must be at least `flt2dec2flt::PREFORMAT_EXACT_FIXED_BASE_BUF_LEN`
+    /// and `num_digits` must not be zero.
+    ///
+    /// ```
+    /// use flt2dec2flt::{FloatExt as _, PreFormatted, RoundingMode};
+    ///
+    /// let mut buf = [0; flt2dec2flt::PREFORMAT_EXACT_FIXED_BASE_BUF_LEN];
+    ///
+    /// // 0.1000000000000000055511151231257827...
+    /// let preformatted = 0.1f64.preformat_exact_exp_rounded(&mut buf, 3, RoundingMode::Down);
+    /// assert_eq!(preformatted, PreFormatted::Finite(false, b"100", 0, 0));
+    /// let preformatted = 0.1f64.preformat_exact_exp_rounded(&mut buf, 3, RoundingMode::Up);
+    /// assert_eq!(preformatted, PreFormatted::Finite(false, b"101", 0, 0));
+    ///
+    /// let preformatted = (-0.1f64).preformat_exact_exp_rounded(&mut buf, 3, RoundingMode::Down);
+    /// assert_eq!(preformatted, PreFormatted::Finite(true, b"101", 0, 0));
+    ///
+    /// let preformatted = 9.99f32.preformat_exact_exp_rounded(&mut buf, 2, RoundingMode::Up);
+    /// assert_eq!(preformatted, PreFormatted::Finite(false, b"1", 1, 2));
+    /// ```
+    fn preformat_exact_exp_rounded(
+        self,
+        buf: &mut [u8],
+        num_digits: usize,
+        mode: RoundingMode,
+    ) -> PreFormatted<'_> {
+        assert!(num_digits != 0, "cannot round to zero digits");
+        // Every significant digit, without trailing zeros.
+        let buf = &mut buf[..PREFORMAT_EXACT_FIXED_BASE_BUF_LEN];
+        let (sign, len, exp) = match self.preformat_exact_exp(buf, buf.len()) {
+            PreFormatted::Finite(sign, digits, _, exp) => {
+                let len = digits.iter().rposition(|&c| c != b'0').map_or(0, |i| i + 1);
+                (sign, len, exp)
+            }
+            PreFormatted::NaN => return PreFormatted::NaN,
+            PreFormatted::Inf(sign) => return PreFormatted::Inf(sign),
+            PreFormatted::Zero(sign) => return PreFormatted::Zero(sign),
+        };
+        let keep = num_digits.min(i32::MAX as usize) as i32;
+        let (len, exp) = rounding::round_digits(&mut buf[..len], exp.into(), keep, sign, mode);
+        PreFormatted::Finite(sign, &buf[..len], num_digits - len, exp as i16)
+    }
+
+    /// Pre-formats `self` like `preformat_exact_exp`, also returning a
+    /// bound on the representation error, in units of the last digit.
+    ///
+    /// Every number that rounds to `self` is within that many units of the
+    /// returned digits, so `(digits, bound)` can be shown in the concise
+    /// notation of uncertainties, such as `0.1000000015(38)`. The bound is
+    /// rounded up to an integer, so it is never zero for finite non-zero
+    /// numbers. It is zero for zero and non-finite numbers, and saturates
+    /// to `u64::MAX`.
+    ///
+    /// `buf` must be at least `num_digits`, which must not be zero.
+    ///
+    /// ```
+    /// use flt2dec2flt::FloatExt as _;
+    /// use flt2dec2flt::PreFormatted;
+    ///
+    /// let mut buf = [0; 32];
+    ///
+    /// let preformatted = 0.1f32.preformat_exact_exp_with_error(&mut buf, 10);
+    /// assert_eq!(preformatted, (PreFormatted::Finite(false, b"1000000015", 0, 0), 38));
+    ///
+    /// // The last digit can be off by 2
+    /// let preformatted = 0.1f64.preformat_exact_exp_with_error(&mut buf, 17);
+    /// assert_eq!(preformatted, (PreFormatted::Finite(false, b"10000000000000001", 0, 0), 2));
+    /// ```
+    fn preformat_exact_exp_with_error(
+        self,
+        buf: &mut [u8],
+        num_digits: usize,
+    ) -> (PreFormatted<'_>, u64) {
+        error_bound::preformat_exact_exp_with_error(self, buf, num_digits)
+    }
+
+    /// Pre-formats `self` like `preformat_shortest`, unless the shortest
+    /// digits extend beyond the `10^limit` place, in which case the exact
+    /// value is rounded at that place like `preformat_exact_limit`.
+    ///
+    /// The boolean is `true` when the result was rounded at the `10^limit`
+    /// place, which is never the case for zero and non-finite numbers.
+    ///
+    /// `buf` must be at least `flt2dec2flt::PREFORMAT_EXACT_FIXED_BASE_BUF_LEN`.
+    ///
+    /// ```
+    /// use flt2dec2flt::FloatExt as _;
+    /// use flt2dec2flt::PreFormatted;
+    ///
+    /// let mut buf = [0; flt2dec2flt::PREFORMAT_EXACT_FIXED_BASE_BUF_LEN];
+    ///
+    /// // Short enough, kept as is
+    /// let preformatted = 12.5f64.preformat_shortest_limit(&mut buf, -2);
+    /// assert_eq!(preformatted, (PreFormatted::Finite(false, b"125", 0, 2), false));
+    ///
+    /// // Rounded to 2 decimals
+    /// let preformatted = (0.1f64 + 0.2).preformat_shortest_limit(&mut buf, -2);
+    /// assert_eq!(preformatted, (PreFormatted::Finite(false, b"30", 0, 0), true));
+    ///
+    /// // 2.675 is slightly below 2.675
+    /// let preformatted = 2.675f64.preformat_shortest_limit(&mut buf, -2);
+    /// assert_eq!(preformatted, (PreFormatted::Finite(false, b"267", 0, 1), true));
+    /// ```
+    fn preformat_shortest_limit(self, buf: &mut [u8], limit: i16) -> (PreFormatted<'_>, bool) {
+        let mut shortest_buf = [0; PREFORMAT_SHORTEST_BUF_LEN];
+        match self.preformat_shortest(&mut shortest_buf) {
+            PreFormatted::NaN => (PreFormatted::NaN, false),
+            PreFormatted::Inf(sign) => (PreFormatted::Inf(sign), false),
+            PreFormatted::Zero(sign) => (PreFormatted::Zero(sign), false),
+            PreFormatted::Finite(sign, digits, _, exp)
+                if i32::from(exp) - digits.len() as i32 >= i32::from(limit) =>
+            {
+                buf[..digits.len()].copy_from_slice(digits);
+                (
+                    PreFormatted::Finite(sign, &buf[..digits.len()], 0, exp),
+                    false,
+                )
+            }
+            PreFormatted::Finite(..) => (self.preformat_exact_limit(buf, limit), true),
+        }
+    }
+
+    /// Returns the shortest representation of `self` that round-trips as
+    /// `(digits, exp, sign)`, with value `sign digits * 10^exp`, where
+    /// `digits` has no trailing zeros.
+    ///
+    /// Zero is `(0, 0, sign)`. Returns `None` if `self` is NaN or infinite.
+    ///
+    /// ```
+    /// use flt2dec2flt::FloatExt as _;
+    ///
+    /// assert_eq!(12.5f64.to_shortest_parts(), Some((125, -1, false)));
+    /// assert_eq!((-1e300f64).to_shortest_parts(), Some((1, 300, true)));
+    /// assert_eq!(f32::MIN_POSITIVE.to_shortest_parts(), Some((11754944, -45, false)));
+    /// assert_eq!((-0.0f32).to_shortest_parts(), Some((0, 0, true)));
+    /// assert_eq!(f64::NAN.to_shortest_parts(), None);
+    /// ```
+    fn to_shortest_parts(self) -> Option<(u64, i32, bool)> {
+        let mut buf = [0; PREFORMAT_SHORTEST_BUF_LEN];
+        match self.preformat_shortest(&mut buf) {
+            PreFormatted::NaN | PreFormatted::Inf(_) => None,
+            PreFormatted::Zero(sign) => Some((0, 0, sign)),
+            PreFormatted::Finite(sign, digits, _, exp) => {
+                let m = digits
+                    .iter()
+                    .fold(0, |acc, &c| acc * 10 + u64::from(c - b'0'));
+                Some((m, i32::from(exp) - digits.len() as i32, sign))
+            }
+        }
+    }
+
+    /// Returns the exact value of `self` as `(mantissa, exp)`, with value
+    /// `mantissa * 10^exp`, if `mantissa` fits in an `i64`.
+    ///
+    /// `mantissa` has no trailing zeros, and zero is `(0, 0)`. Returns
+    /// `None` if `self` is NaN or infinite, or if its exact value has too
+    /// many digits (such as `0.1`, which is
+    /// `0.1000000000000000055511151231257827...`), in which case
+    /// `to_shortest_parts` gives a representation that round-trips. This
+    /// only looks at the bits of `self`, without computing any digit.
+    ///
+    /// ```
+    /// use flt2dec2flt::FloatExt as _;
+    ///
+    /// assert_eq!(0.25f64.to_exact_decimal_parts(), Some((25, -2)));
+    /// assert_eq!((-1.5e10f64).to_exact_decimal_parts(), Some((-15, 9)));
+    /// assert_eq!(0.1f64.to_exact_decimal_parts(), None);
+    /// assert_eq!(0.1f32.to_exact_decimal_parts(), None);
+    /// assert_eq!(1e30f64.to_exact_decimal_parts(), None);
+    /// ```
+    fn to_exact_decimal_parts(self) -> Option<(i64, i8)> {
+        let v = self.to_f64();
+        if !v.is_finite() {
+            return None;
+        } else if v == 0.0 {
+            return Some((0, 0));
+        }
+
+        let (m, e) = generic::odd_significand(v);
+        let m = u128::from(m);
+        let max = if v < 0.0 { 1 << 63 } else { i64::MAX as u128 };
+        let (mantissa, exp) = if e >= 0 {
+            // `m = m5 * 5^k`, so `v = m5 * 5^(k - t) * 2^(e - t) * 10^t`,
+            // which has no trailing zeros when `t` is the smallest of `k`
+            // and `e`.
+            let mut m5 = m;
+            let mut k = 0;
+            while m5 % 5 == 0 {
+                m5 /= 5;
+                k += 1;
+            }
+            let t = k.min(e);
+            if e - t >= 64 {
+                return None;
+            }
+            let mantissa = (m5 * 5u128.pow((k - t) as u32)) << (e - t);
+            if mantissa > max {
+                return None;
+            }
+            (mantissa, t)
+        } else {
+            // `m * 5^-e * 10^e`, without trailing zeros because `m` is odd.
+            // `5^28` does not fit in an `i64`.
+            if e < -27 {
+                return None;
+            }
+            (
+                m.checked_mul(5u128.pow(-e as u32)).filter(|&x| x <= max)?,
+                e,
+            )
+        };
+        let mantissa = if v < 0.0 {
+            (mantissa as i64).wrapping_neg()
+        } else {
+            mantissa as i64
+        };
+        Some((mantissa, exp as i8))
+    }
+
+    /// Returns whether the exact value of `self` has at most `max_digits`
+    /// significant decimal digits.
+    ///
+    /// This only looks at the bits of `self`, so formatters can choose
+    /// between showing the exact value and an approximation (such as the
+    /// shortest representation) before computing any digit. Zero has one
+    /// digit, NaN and infinities never have an exact decimal value.
+    ///
+    /// ```
+    /// use flt2dec2flt::FloatExt as _;
+    ///
+    /// assert!(0.375f64.has_exact_decimal_within(3));
+    /// assert!(!0.375f64.has_exact_decimal_within(2));
+    /// // 0.1000000000000000055511151231257827021181583404541015625
+    /// assert!(0.1f64.has_exact_decimal_within(55));
+    /// assert!(!0.1f64.has_exact_decimal_within(54));
+    /// assert!(1e22f64.has_exact_decimal_within(1));
+    /// assert!(!f64::NAN.has_exact_decimal_within(1000));
+    /// ```
+    fn has_exact_decimal_within(self, max_digits: usize) -> bool {
+        let v = self.to_f64();
+        if !v.is_finite() {
+            false
+        } else if v == 0.0 {
+            max_digits != 0
+        } else {
+            generic::exact_digits_within(v, max_digits)
+        }
+    }
+
+    /// Rounds `self` to `n` significant decimal digits (half to even) and
+    /// returns the nearest float to the rounded decimal value.
+    ///
+    /// Zero, NaN and infinities are returned as is. The result can be
+    /// infinite when rounding up the largest finite numbers.
+    ///
+    /// Panics if `n` is zero.
+    ///
+    /// ```
+    /// use flt2dec2flt::FloatExt as _;
+    ///
+    /// assert_eq!(1234.5678f64.round_to_sig_figs(3), 1230.0);
+    /// assert_eq!((-0.00123456f64).round_to_sig_figs(2), -0.0012);
+    /// assert_eq!(2.5f32.round_to_sig_figs(1), 2.0);
+    /// assert_eq!(9.96f32.round_to_sig_figs(2), 10.0);
+    /// assert_eq!(f64::MAX.round_to_sig_figs(1), f64::INFINITY);
+    /// ```
+    fn round_to_sig_figs(self, n: usize) -> Self {
+        assert!(n != 0, "cannot round to zero significant digits");
+        // `PREFORMAT_SHORTEST_BUF_LEN` digits always round-trip, and more
+        // digits are even closer to `self`.
+        if n >= PREFORMAT_SHORTEST_BUF_LEN {
+            return self;
+        }
+        let mut buf = [0; PREFORMAT_SHORTEST_BUF_LEN];
+        match self.preformat_exact_exp(&mut buf[..n], n) {
+            PreFormatted::Finite(sign, digits, _, exp) => {
+                let preparsed = PreParsed {
+                    sign,
+                    int_digits: &[],
+                    frac_digits: digits,
+                    exp: exp.into(),
+                };
+                // The exponent and the number of digits are small enough to
+                // never fail.
+                Self::from_preparsed(preparsed).unwrap()
+            }
+            _ => self,
+        }
+    }
+
+    /// Converts an amount into an integer number of minor units (such as
+    /// cents with `scale` 2, or satoshis with `scale` 8), rounding the
+    /// exact value of `self` times `10^scale` with `mode`.
+    ///
+    /// Unlike `(self * 100.0).round() as i64`, there is no rounding before
+    /// `mode` is applied, and no saturation. Fails if `self` is NaN or
+    /// infinite, or if the result does not fit in an `i64`.
+    ///
+    /// ```
+    /// use flt2dec2flt::{DecimalOverflow, FloatExt as _, RoundingMode};
+    ///
+    /// // 0.015 is 0.01499999999999999944..., but `0.015 * 100.0` is 1.5
+    /// assert_eq!(0.015f64.to_minor_units(2, RoundingMode::NearestAway), Ok(1));
+    /// assert_eq!(0.015f64.to_minor_units(2, RoundingMode::Up), Ok(2));
+    /// assert_eq!((-0.1f64).to_minor_units(8, RoundingMode::TowardZero), Ok(-10_000_000));
+    /// assert_eq!(1e19f64.to_minor_units(0, RoundingMode::NearestEven), Err(DecimalOverflow));
+    /// assert_eq!(f64::NAN.to_minor_units(2, RoundingMode::NearestEven), Err(DecimalOverflow));
+    /// ```
+    fn to_minor_units(self, scale: u32, mode: RoundingMode) -> Result<i64, DecimalOverflow> {
+        // Larger scales overflow with any non-zero number anyway.
+        let scale = scale.min(1000) as i32;
+        let (sign, magnitude) = scaled::to_scaled(self, scale, mode).ok_or(DecimalOverflow)?;
+        if sign {
+            if magnitude > 1 << 63 {
+                return Err(DecimalOverflow);
+            }
+            Ok((magnitude as i64).wrapping_neg())
+        } else {
+            if magnitude > i64::MAX as u128 {
+                return Err(DecimalOverflow);
+            }
+            Ok(magnitude as i64)
+        }
+    }
+
+    /// Converts an integer number of minor units into an amount, which is
+    /// the nearest number to `units * 10^-scale` (rounding half to even).
+    ///
+    /// ```
+    /// use flt2dec2flt::FloatExt as _;
+    ///
+    /// assert_eq!(f64::from_minor_units(12345, 2), 123.45);
+    /// assert_eq!(f64::from_minor_units(-1, 8), -0.00000001);
+    /// assert_eq!(f32::from_minor_units(i64::MIN, 0), -9.223372e18);
+    /// ```
+    fn from_minor_units(units: i64, scale: u32) -> Self {
+        let (sign, magnitude) = scaled::split_i128(i128::from(units));
+        scaled::from_scaled(sign, magnitude, i64::from(scale))
+    }
+
+    /// Like `preformat_shortest`, pushing the digits into `sink` instead
+    /// of writing them into a buffer (see the `sink` module).
+    fn preformat_shortest_into(self, sink: &mut impl sink::DigitSink) -> sink::Formatted {
+        let mut buf = [0; PREFORMAT_SHORTEST_BUF_LEN];
+        sink::push(self.preformat_shortest(&mut buf), sink)
+    }
+
+    /// Like `preformat_exact_exp`, pushing the digits into `sink` instead
+    /// of writing them into a buffer (see the `sink` module).
+    fn preformat_exact_exp_into(
+        self,
+        sink: &mut impl sink::DigitSink,
+        num_digits: usize,
+    ) -> sink::Formatted {
+        let (sign, full_decoded) = generic::decode_scale2(self, 0);
+        sink::format_exact_exp(sign, full_decoded, sink, num_digits)
+    }
+
+    /// Like `preformat_exact_fixed`, pushing the digits into `sink`
+    /// instead of writing them into a buffer (see the `sink` module).
+    fn preformat_exact_fixed_into(
+        self,
+        sink: &mut impl sink::DigitSink,
+        num_frac_digits: isize,
+    ) -> sink::Formatted {
+        let (sign, full_decoded) = generic::decode_scale2(self, 0);
+        sink::format_exact_fixed(sign, full_decoded, sink, num_frac_digits)
+    }
+
+    /// Like `preformat_exact_exp`, returning the digits in a `Vec` that
+    /// grows as needed, so no buffer has to be sized beforehand. The extra
+    /// zeros are included in the digits.
+    ///
+    /// Panics if `num_digits` is zero. Like any growing `Vec`, it also
+    /// panics or aborts if the digits do not fit in memory (such as with
+    /// `usize::MAX`).
+    ///
+    /// ```
+    /// use flt2dec2flt::{FloatExt as _, OwnedPreFormatted};
+    ///
+    /// let owned = 0.1f32.preformat_exact_exp_vec(3000);
+    /// if let OwnedPreFormatted::Finite(false, digits, 0, 0) = owned {
+    ///     assert_eq!(digits.len(), 3000);
+    ///     assert!(digits.starts_with(b"100000001490116119384765625000"));
+    /// } else {
+    ///     panic!();
+    /// }
+    /// ```
+    #[cfg(feature = "alloc")]
+    fn preformat_exact_exp_vec(self, num_digits: usize) -> OwnedPreFormatted {
+        let mut digits = alloc::vec::Vec::new();
+        let formatted = self.preformat_exact_exp_into(&mut digits, num_digits);
+        OwnedPreFormatted::from_sink(formatted, digits)
+    }
+
+    /// Like `preformat_exact_fixed`, returning the digits in a `Vec` that
+    /// grows as needed, so no buffer has to be sized beforehand. The extra
+    /// zeros are included in the digits.
+    ///
+    /// Like any growing `Vec`, it panics or aborts if the digits do not fit
+    /// in memory (such as with `isize::MAX`).
+    ///
+    /// ```
+    /// use flt2dec2flt::{FloatExt as _, OwnedPreFormatted};
+    ///
+    /// assert_eq!(
+    ///     1.5f64.preformat_exact_fixed_vec(5),
+    ///     OwnedPreFormatted::Finite(false, b"150000".to_vec(), 0, 1),
+    /// );
+    /// ```
+    #[cfg(feature = "alloc")]
+    fn preformat_exact_fixed_vec(self, num_frac_digits: isize) -> OwnedPreFormatted {
+        let mut digits = alloc::vec::Vec::new();
+        let formatted = self.preformat_exact_fixed_into(&mut digits, num_frac_digits);
+        OwnedPreFormatted::from_sink(formatted, digits)
+    }
+
+    /// Pre-formats the full decimal expansion of `self`, which is always
+    /// finite, without trailing zeros. This can take hundreds of digits
+    /// (up to 767 for an `f64`, and 715 for the smallest normal one).
+    ///
+    /// ```
+    /// use flt2dec2flt::{FloatExt as _, OwnedPreFormatted};
+    ///
+    /// assert_eq!(
+    ///     0.1f32.preformat_exact_full_vec(),
+    ///     OwnedPreFormatted::Finite(false, b"100000001490116119384765625".to_vec(), 0, 0),
+    /// );
+    /// assert_eq!(
+    ///     (-1e20f64).preformat_exact_full_vec(),
+    ///     OwnedPreFormatted::Finite(true, b"1".to_vec(), 0, 21),
+    /// );
+    ///
+    /// let owned = f64::MIN_POSITIVE.preformat_exact_full_vec();
+    /// if let OwnedPreFormatted::Finite(false, digits, 0, -307) = owned {
+    ///     assert_eq!(digits.len(), 715);
+    /// } else {
+    ///     panic!();
+    /// }
+    /// ```
+    #[cfg(feature = "alloc")]
+    fn preformat_exact_full_vec(self) -> OwnedPreFormatted {
+        // The least significant bit of any number is at `2^MIN_LSB_EXP` or
+        // above, and `2^-n` has `n` fractional digits.
+        let mut owned = self.preformat_exact_fixed_vec(-Self::MIN_LSB_EXP as isize);
+        if let OwnedPreFormatted::Finite(_, ref mut digits, _, _) = owned {
+            while digits.last() == Some(&b'0') {
+                digits.pop();
+            }
+        }
+        owned
+    }
+
+    /// Creates a floating point number from a pre-parsed decimal
+    /// floating point number (see `PreParsed`).
+    ///
+    /// The result is correctly rounded (half to even). Values too large
+    /// to be represented become infinity and values too small become zero.
+    ///
+    /// Any number of digits is accepted. Fails if there are no digits at
+    /// all or if a digit is not an ASCII decimal digit.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use flt2dec2flt::FloatExt as _;
+    ///
+    /// let v = f32::from_preparsed(flt2dec2flt::PreParsed {
+    ///     sign: false,
+    ///     int_digits: b"12",
+    ///     frac_digits: b"34",
+    ///     exp: 0,
+    /// });
+    /// assert!((v.unwrap() - 12.34).abs() < 1e-9);
+    ///
+    /// let v = f32::from_preparsed(flt2dec2flt::PreParsed {
+    ///     sign: false,
+    ///     int_digits: b"0",
+    ///     frac_digits: b"41",
+    ///     exp: -4,
+    /// });
+    /// assert!((v.unwrap() - 0.41e-4).abs() < 1e-12);
+    ///
+    /// let v = f32::from_preparsed(flt2dec2flt::PreParsed {
+    ///     sign: false,
+    ///     int_digits: b"1",
+    ///     frac_digits: b"2x",
+    ///     exp: 0,
+    /// });
+    /// assert_eq!(
+    ///     v,
+    ///     Err(flt2dec2flt::ParseFloatError::InvalidDigit { byte: b'x', index: 2 }),
+    /// );
+    /// ```
+    fn from_preparsed(preparsed: PreParsed<'_>) -> Result<Self, ParseFloatError>;
+
+    /// Converts `mantissa * 10^exp10` into the nearest floating point
+    /// number (rounding half to even), like `from_preparsed` with the
+    /// digits of `mantissa`, without going through digits.
+    ///
+    /// Values too large to be represented become infinity and values too
+    /// small become zero. The result is never negative (negate it for
+    /// negative numbers).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use flt2dec2flt::FloatExt as _;
+    ///
+    /// assert_eq!(f64::from_decimal_parts(1234, -2), 12.34);
+    /// assert_eq!(f32::from_decimal_parts(17, 37), 1.7e38);
+    /// assert_eq!(f64::from_decimal_parts(1, 400), f64::INFINITY);
+    /// ```
+    fn from_decimal_parts(mantissa: u64, exp10: i32) -> Self {
+        let mut buf = [0; 20];
+        let preparsed = PreParsed {
+            sign: false,
+            int_digits: core_num::dec2flt::u64_digits(mantissa, &mut buf),
+            frac_digits: b"",
+            exp: exp10,
+        };
+        // Only fails when trimming the trailing zeros overflows the
+        // exponent.
+        Self::from_preparsed(preparsed).unwrap_or(<Self as sealed::Sealed>::INFINITY)
+    }
+
+    /// Like `from_preparsed`, rounding with `mode` instead of to the
+    /// nearest value (half to even).
+    ///
+    /// Values too large to be represented become infinity, or the
+    /// largest finite number when `mode` rounds them toward zero. Values
+    /// too small become zero, or the smallest subnormal when `mode` rounds
+    /// them away from zero.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use flt2dec2flt::{FloatExt as _, PreParsed, RoundingMode};
+    ///
+    /// let preparsed = PreParsed {
+    ///     sign: false,
+    ///     int_digits: b"0",
+    ///     frac_digits: b"1",
+    ///     exp: 0,
+    /// };
+    /// let lo = f64::from_preparsed_rounded(preparsed, RoundingMode::Down).unwrap();
+    /// let hi = f64::from_preparsed_rounded(preparsed, RoundingMode::Up).unwrap();
+    /// assert_eq!(lo, 0.09999999999999999);
+    /// assert_eq!(hi, 0.1);
+    ///
+    /// let preparsed = PreParsed {
+    ///     sign: true,
+    ///     int_digits: b"1",
+    ///     frac_digits: b"",
+    ///     exp: 400,
+    /// };
+    /// let v = f64::from_preparsed_rounded(preparsed, RoundingMode::TowardZero);
+    /// assert_eq!(v, Ok(-f64::MAX));
+    /// ```
+    fn from_preparsed_rounded(
+        preparsed: PreParsed<'_>,
+        mode: RoundingMode,
+    ) -> Result<Self, ParseFloatError> {
+        if mode == RoundingMode::NearestEven {
+            Self::from_preparsed(preparsed)
+        } else {
+            generic::from_preparsed_rounded(preparsed, mode)
+        }
+    }
+
+    /// Converts `preparsed * 2^scale2` into the nearest floating point
+    /// number (rounding half to even), such as decimal mantissas with
+    /// binary exponents found in ASN.1 `REAL` encodings.
+    ///
+    /// Values too large to be represented become infinity and values too
+    /// small become zero. Any number of digits is accepted. Fails with
+    /// `ParseFloatError::ExponentOutOfRange` when the decimal exponent is
+    /// too large to handle, which only happens when `scale2` cancels
+    /// thousands of orders of magnitude.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use flt2dec2flt::{FloatExt as _, PreParsed};
+    ///
+    /// // 1.5 * 2^-3
+    /// let preparsed = PreParsed {
+    ///     sign: false,
+    ///     int_digits: b"1",
+    ///     frac_digits: b"5",
+    ///     exp: 0,
+    /// };
+    /// assert_eq!(f64::from_preparsed_scale2(preparsed, -3), Ok(0.1875));
+    ///
+    /// // -0.1 * 2^100
+    /// let preparsed = PreParsed {
+    ///     sign: true,
+    ///     int_digits: b"",
+    ///     frac_digits: b"1",
+    ///     exp: 0,
+    /// };
+    /// assert_eq!(f32::from_preparsed_scale2(preparsed, 100), Ok(-0.1 * 2f32.powi(100)));
+    /// ```
+    fn from_preparsed_scale2(
+        preparsed: PreParsed<'_>,
+        scale2: i32,
+    ) -> Result<Self, ParseFloatError> {
+        generic::from_preparsed_scale2(preparsed, scale2)
+    }
+
+    /// Creates a floating point number from a pre-parsed hexadecimal
+    /// floating point number (see `PreParsedHex`), such as the C99
+    /// `0x1.8p3` literals.
+    ///
+    /// The result is correctly rounded (half to even), including
+    /// subnormals. Values too large to be represented become infinity and
+    /// values too small become zero.
+    ///
+    /// Any number of digits is accepted. Fails if there are no digits at
+    /// all or if a digit is not an ASCII hexadecimal digit.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use flt2dec2flt::{FloatExt as _, PreParsedHex};
+    ///
+    /// let preparsed = PreParsedHex {
+    ///     sign: false,
+    ///     int_digits: b"1",
+    ///     frac_digits: b"8",
+    ///     exp: 3,
+    /// };
+    /// assert_eq!(f64::from_preparsed_hex(preparsed), Ok(12.0));
+    ///
+    /// // Halfway between 1 and the next `f32`, rounded to even.
+    /// let preparsed = PreParsedHex {
+    ///     sign: true,
+    ///     int_digits: b"1",
+    ///     frac_digits: b"000001",
+    ///     exp: 0,
+    /// };
+    /// assert_eq!(f32::from_preparsed_hex(preparsed), Ok(-1.0));
+    /// ```
+    fn from_preparsed_hex(preparsed: PreParsedHex<'_>) -> Result<Self, ParseFloatError> {
+        generic::from_preparsed_hex(preparsed)
+    }
+
+    /// Creates a floating point number from a pre-parsed number in base
+    /// `radix`, that is, `sign int_digits.frac_digits * radix ^ exp`, where
+    /// the digits are ASCII digits and letters (in either case) below
+    /// `radix`.
+    ///
+    /// The result is correctly rounded (half to even), like
+    /// `from_preparsed`, which is used for base 10. Powers of two are
+    /// converted directly, other bases with bignums, which fail with
+    /// `ParseFloatError::ExponentOutOfRange` when the exponent or (for odd
+    /// bases) the number of digits is too large for them.
+    ///
+    /// Panics if `radix` is not between 2 and 36.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use flt2dec2flt::{FloatExt as _, PreParsed};
+    ///
+    /// let preparsed = PreParsed {
+    ///     sign: false,
+    ///     int_digits: b"101",
+    ///     frac_digits: b"1",
+    ///     exp: 2,
+    /// };
+    /// assert_eq!(f64::from_preparsed_radix(preparsed, 2), Ok(22.0));
+    ///
+    /// let preparsed = PreParsed {
+    ///     sign: true,
+    ///     int_digits: b"0",
+    ///     frac_digits: b"1",
+    ///     exp: 0,
+    /// };
+    /// assert_eq!(f32::from_preparsed_radix(preparsed, 3), Ok(-1.0 / 3.0));
+    /// ```
+    fn from_preparsed_radix(preparsed: PreParsed<'_>, radix: u32) -> Result<Self, ParseFloatError> {
+        if radix == 10 {
+            Self::from_preparsed(preparsed)
+        } else {
+            generic::from_preparsed_radix(preparsed, radix)
+        }
+    }
+
+    /// Converts a pre-parsed number like `from_preparsed`, reporting
+    /// whether it has been rounded (compared exactly with the text).
+    ///
+    /// Numbers that overflow to infinity are reported as rounded away
+    /// from zero, and numbers that underflow to zero as rounded toward
+    /// zero.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use flt2dec2flt::{Exactness, FloatExt as _, PreParsed};
+    ///
+    /// let preparsed = PreParsed {
+    ///     sign: false,
+    ///     int_digits: b"0",
+    ///     frac_digits: b"1",
+    ///     exp: 0,
+    /// };
+    /// assert_eq!(
+    ///     f64::from_preparsed_report(preparsed),
+    ///     Ok((0.1, Exactness::RoundedUp)),
+    /// );
+    ///
+    /// let preparsed = PreParsed {
+    ///     sign: true,
+    ///     int_digits: b"2",
+    ///     frac_digits: b"5",
+    ///     exp: 0,
+    /// };
+    /// assert_eq!(f32::from_preparsed_report(preparsed), Ok((-2.5, Exactness::Exact)));
+    /// ```
+    fn from_preparsed_report(
+        preparsed: PreParsed<'_>,
+    ) -> Result<(Self, Exactness), ParseFloatError> {
+        rounding::from_preparsed_report(preparsed)
+    }
+
+    /// Returns the two consecutive floating point numbers that enclose the
+    /// exact value of `preparsed`, which are equal if it is exactly
+    /// representable.
+    ///
+    /// Infinity is the neighbor of the largest finite number, so numbers
+    /// beyond it are enclosed by both.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use flt2dec2flt::{FloatExt as _, PreParsed};
+    ///
+    /// let preparsed = PreParsed {
+    ///     sign: false,
+    ///     int_digits: b"0",
+    ///     frac_digits: b"1",
+    ///     exp: 0,
+    /// };
+    /// assert_eq!(f64::from_preparsed_interval(preparsed), Ok((0.09999999999999999, 0.1)));
+    ///
+    /// let preparsed = PreParsed {
+    ///     sign: true,
+    ///     int_digits: b"0",
+    ///     frac_digits: b"5",
+    ///     exp: 0,
+    /// };
+    /// assert_eq!(f32::from_preparsed_interval(preparsed), Ok((-0.5, -0.5)));
+    /// ```
+    fn from_preparsed_interval(preparsed: PreParsed<'_>) -> Result<(Self, Self), ParseFloatError> {
+        rounding::from_preparsed_interval(preparsed)
+    }
+
+    /// Converts a pre-parsed number like `from_preparsed`, replacing the
+    /// subnormal results as specified by `policy`, for environments that
+    /// flush subnormals to zero.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use flt2dec2flt::{FloatExt as _, PreParsed, SubnormalPolicy};
+    ///
+    /// let preparsed = PreParsed {
+    ///     sign: true,
+    ///     int_digits: b"1",
+    ///     frac_digits: b"2",
+    ///     exp: -308,
+    /// };
+    /// assert_eq!(
+    ///     f64::from_preparsed_subnormals(preparsed, SubnormalPolicy::Gradual),
+    ///     Ok(-1.2e-308),
+    /// );
+    /// let v = f64::from_preparsed_subnormals(preparsed, SubnormalPolicy::FlushToZero);
+    /// assert_eq!(v.map(f64::to_bits), Ok((-0.0f64).to_bits()));
+    /// assert_eq!(
+    ///     f64::from_preparsed_subnormals(preparsed, SubnormalPolicy::RoundToNormal),
+    ///     Ok(-f64::MIN_POSITIVE),
+    /// );
+    /// ```
+    fn from_preparsed_subnormals(
+        preparsed: PreParsed<'_>,
+        policy: SubnormalPolicy,
+    ) -> Result<Self, ParseFloatError> {
+        rounding::from_preparsed_subnormals(preparsed, policy)
+    }
+
+    /// Converts a pre-parsed number like `from_preparsed` and clamps it
+    /// to `[min, max]`.
+    ///
+    /// Besides the clamped value, reports whether the rounded number was
+    /// clamped and whether the exact value of `preparsed` is out of the
+    /// range (compared exactly, so text that rounds to a bound but is
+    /// beyond it is reported too).
+    ///
+    /// Panics if `min > max` or if any of them is NaN.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use flt2dec2flt::{Clamped, FloatExt as _, PreParsed};
+    ///
+    /// let preparsed = PreParsed {
+    ///     sign: false,
+    ///     int_digits: b"1",
+    ///     frac_digits: b"5",
+    ///     exp: 0,
+    /// };
+    /// assert_eq!(
+    ///     f64::from_preparsed_clamped(preparsed, 0.0, 1.0),
+    ///     Ok(Clamped { value: 1.0, clamped: true, out_of_range: true }),
+    /// );
+    ///
+    /// // Rounds to 1.0, but is larger
+    /// let preparsed = PreParsed {
+    ///     sign: false,
+    ///     int_digits: b"1",
+    ///     frac_digits: b"00000000000000000001",
+    ///     exp: 0,
+    /// };
+    /// assert_eq!(
+    ///     f64::from_preparsed_clamped(preparsed, 0.0, 1.0),
+    ///     Ok(Clamped { value: 1.0, clamped: false, out_of_range: true }),
+    /// );
+    /// ```
+    fn from_preparsed_clamped(
+        preparsed: PreParsed<'_>,
+        min: Self,
+        max: Self,
+    ) -> Result<Clamped<Self>, ParseFloatError>
+    where
+        Self: PartialOrd,
+    {
+        clamp::from_preparsed_clamped(preparsed, min, max)
+    }
+
+    /// Returns the exact value of `self` as a rational number, whose
+    /// denominator is a power of two.
+    ///
+    /// Returns `None` if `self` is NaN or infinite. Negative zero becomes
+    /// zero.
+    ///
+    /// Requires the `num` feature.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use flt2dec2flt::FloatExt as _;
+    /// use num_rational::BigRational;
+    ///
+    /// let r = 0.1f32.to_exact_rational().unwrap();
+    /// assert_eq!(r, BigRational::new(13421773.into(), 134217728.into()));
+    /// assert_eq!(f32::from_rational(&r), 0.1);
+    /// assert_eq!(f32::NAN.to_exact_rational(), None);
+    /// ```
+    #[cfg(feature = "num")]
+    fn to_exact_rational(self) -> Option<::num_rational::BigRational>;
+
+    /// Converts a rational number into the nearest floating point number
+    /// (rounding half to even).
+    ///
+    /// Values too large to be represented become infinity and values too
+    /// small become zero.
+    ///
+    /// Requires the `num` feature.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use flt2dec2flt::FloatExt as _;
+    /// use num_rational::BigRational;
+    ///
+    /// let r = BigRational::new((-1).into(), 3.into());
+    /// assert_eq!(f64::from_rational(&r), -1.0 / 3.0);
+    /// ```
+    #[cfg(feature = "num")]
+    fn from_rational(v: &::num_rational::BigRational) -> Self;
+
+    /// Converts a pre-parsed number into a floating point number with
+    /// stochastic rounding.
+    ///
+    /// When the number cannot be represented exactly, it is rounded up (in
+    /// magnitude) with a probability equal to the fraction of the unit in
+    /// the last place that is dropped, and down otherwise, so the result
+    /// is unbiased. `random` is only called in that case, and has to
+    /// return a uniformly distributed `u64`, which gives a resolution of
+    /// 2^-64 to the probabilities.
+    ///
+    /// Finite numbers beyond the largest float may become infinity.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use flt2dec2flt::{FloatExt as _, PreParsed};
+    ///
+    /// // 0.25, exactly representable
+    /// let preparsed = PreParsed {
+    ///     sign: false,
+    ///     int_digits: b"0",
+    ///     frac_digits: b"25",
+    ///     exp: 0,
+    /// };
+    /// let v = f32::from_preparsed_stochastic(preparsed, || unreachable!());
+    /// assert_eq!(v, Ok(0.25));
+    ///
+    /// // 1 + 2^-24, halfway between 1 and the next `f32`
+    /// let preparsed = PreParsed {
+    ///     sign: false,
+    ///     int_digits: b"1",
+    ///     frac_digits: b"000000059604644775390625",
+    ///     exp: 0,
+    /// };
+    /// let up = 1.0 + f32::EPSILON;
+    /// assert_eq!(f32::from_preparsed_stochastic(preparsed, || 0), Ok(up));
+    /// assert_eq!(f32::from_preparsed_stochastic(preparsed, || 1 << 63), Ok(1.0));
+    /// ```
+    fn from_preparsed_stochastic(
+        preparsed: PreParsed<'_>,
+        random: impl FnOnce() -> u64,
+    ) -> Result<Self, ParseFloatError> {
+        generic::from_preparsed_stochastic(preparsed, random)
+    }
+}
+
+mod generic {
+    use crate::bignum::Big32x40;
+    use crate::core_num::flt2dec::decoder::{DecodableFloat, Decoded, FullDecoded};
+    use crate::{
+        core_num, wide, ParseFloatError, PreFormatted, PreParsed, PreParsedHex, PreformatError,
+        RoundingMode,
+    };
+
+    pub(crate) fn preformat_shortest<T: DecodableFloat + crate::sealed::Sealed>(
+        v: T,
+        buf: &mut [u8],
+    ) -> PreFormatted<'_> {
+        let (sign, full_decoded) = core_num::flt2dec::decoder::decode(v);
+        match full_decoded {
+            FullDecoded::Finite(ref decoded) => {
+                let (digits, exp) = crate::strategy::format_shortest(v, decoded, buf);
+                PreFormatted::Finite(sign, digits, 0, exp)
+            }
+            _ => preformat_shortest_decoded(sign, full_decoded, buf),
+        }
+    }
+
+    pub(crate) fn preformat_shortest_even<T: crate::sealed::Sealed>(
+        v: T,
+        buf: &mut [u8],
+    ) -> PreFormatted<'_> {
+        // Scaling by `2^0` never fails.
+        let (sign, full_decoded) = decode_scale2(v, 0);
+        match full_decoded {
+            FullDecoded::Finite(ref decoded) => {
+                let (digits, exp) = crate::strategy::format_shortest_even(v, decoded, buf);
+                PreFormatted::Finite(sign, digits, 0, exp)
+            }
+            _ => preformat_shortest_decoded(sign, full_decoded, buf),
+        }
+    }
+
+    pub(crate) fn preformat_shortest_checked<T: crate::sealed::Sealed>(
+        v: T,
+        buf: &mut [u8],
+    ) -> Result<PreFormatted<'_>, PreformatError> {
+        // Scaling by `2^0` never fails.
+        let (sign, full_decoded) = decode_scale2(v, 0);
+        match full_decoded {
+            FullDecoded::Finite(ref decoded) => {
+                let mut ref_buf = [0; crate::PREFORMAT_SHORTEST_BUF_LEN];
+                let (ref_digits, ref_exp) =
+                    crate::strategy::format_shortest_reference(decoded, &mut ref_buf);
+                let (digits, exp) = crate::strategy::format_shortest(v, decoded, buf);
+                if digits != ref_digits || exp != ref_exp {
+                    return Err(PreformatError::StrategyMismatch);
+                }
+                Ok(PreFormatted::Finite(sign, digits, 0, exp))
+            }
+            _ => Ok(preformat_shortest_decoded(sign, full_decoded, buf)),
+        }
+    }
+
+    pub(crate) fn preformat_shortest_decoded(
+        sign: bool,
+        full_decoded: FullDecoded,
+        buf: &mut [u8],
+    ) -> PreFormatted<'_> {
+        match full_decoded {
+            FullDecoded::Nan => PreFormatted::NaN,
+            FullDecoded::Infinite => PreFormatted::Inf(sign),
+            FullDecoded::Zero => PreFormatted::Zero(sign),
+            FullDecoded::Finite(ref decoded) => {
+                let (digits, exp) = crate::strategy::format_shortest_decoded(decoded, buf);
+                PreFormatted::Finite(sign, digits, 0, exp)
+            }
+        }
+    }
+
+    pub(crate) fn preformat_exact_exp<T: DecodableFloat>(
+        v: T,
+        buf: &mut [u8],
+        ndigits: usize,
+    ) -> PreFormatted<'_> {
+        let (sign, full_decoded) = core_num::flt2dec::decoder::decode(v);
+        preformat_exact_exp_decoded(sign, full_decoded, buf, ndigits)
+    }
+
+    pub(crate) fn preformat_exact_exp_decoded(
+        sign: bool,
+        full_decoded: FullDecoded,
+        buf: &mut [u8],
+        ndigits: usize,
+    ) -> PreFormatted<'_> {
+        match full_decoded {
+            FullDecoded::Nan => PreFormatted::NaN,
+            FullDecoded::Infinite => PreFormatted::Inf(sign),
+            FullDecoded::Zero => PreFormatted::Zero(sign),
+            FullDecoded::Finite(ref decoded) => {
+                // Similar as done in `core::num::flt2dec::to_exact_exp_str`
+
+                let maxlen = core_num::flt2dec::estimate_max_buf_len(decoded.exp);
+                let trunc = if ndigits < maxlen { ndigits } else { maxlen };
+
+                let (digits, exp) = core_num::flt2dec::strategy::grisu::format_exact(
+                    decoded,
+                    &mut buf[..trunc],
+                    i16::min_value(),
+                );
+                PreFormatted::Finite(sign, digits, ndigits - digits.len(), exp)
+            }
+        }
+    }
+
+    pub(crate) fn preformat_exact_fixed<T: DecodableFloat>(
+        v: T,
+        buf: &mut [u8],
+        frac_digits: isize,
+    ) -> PreFormatted<'_> {
+        let (sign, full_decoded) = core_num::flt2dec::decoder::decode(v);
+        preformat_exact_fixed_decoded(sign, full_decoded, buf, frac_digits)
+    }
+
+    pub(crate) fn preformat_exact_fixed_decoded(
+        sign: bool,
+        full_decoded: FullDecoded,
+        buf: &mut [u8],
+        frac_digits: isize,
+    ) -> PreFormatted<'_> {
+        let limit = fixed_limit(frac_digits);
+        match preformat_exact_limit_decoded(sign, full_decoded, buf, limit) {
+            PreFormatted::Finite(sign, digits, _, exp) => {
+                // the zeros go down to the units when rounding to the left
+                // of the decimal point.
+                let num_zeros = if exp > 0 {
+                    let ndigits = (frac_digits.max(0) as usize).saturating_add(exp as usize);
+                    ndigits - digits.len()
+                } else {
+                    0
+                };
+                PreFormatted::Finite(sign, digits, num_zeros, exp)
+            }
+            other => other,
+        }
+    }
+
+    /// Returns the last-digit limitation for `frac_digits` fractional digits.
+    pub(crate) fn fixed_limit(frac_digits: isize) -> i16 {
+        // it *is* possible that `frac_digits` is ridiculously large.
+        // `format_exact` will end rendering digits much earlier in this case,
+        // because we are strictly limited by `maxlen`.
+        // (`isize` may be 16-bit, so the clamping is done with `i64`.)
+        -(frac_digits as i64).max(-0x7FFF).min(0x8000) as i16
+    }
+
+    pub(crate) fn preformat_exact_limit<T: DecodableFloat>(
+        v: T,
+        buf: &mut [u8],
+        limit: i16,
+    ) -> PreFormatted<'_> {
+        let (sign, full_decoded) = core_num::flt2dec::decoder::decode(v);
+        preformat_exact_limit_decoded(sign, full_decoded, buf, limit)
+    }
+
+    pub(crate) fn preformat_exact_limit_decoded(
+        sign: bool,
+        full_decoded: FullDecoded,
+        buf: &mut [u8],
+        limit: i16,
+    ) -> PreFormatted<'_> {
+        match full_decoded {
+            FullDecoded::Nan => PreFormatted::NaN,
+            FullDecoded::Infinite => PreFormatted::Inf(sign),
+            FullDecoded::Zero => PreFormatted::Zero(sign),
+            FullDecoded::Finite(ref decoded) => {
+                // Similar as done in `core::num::flt2dec::to_exact_fixed_str`
+
+                let maxlen = core_num::flt2dec::estimate_max_buf_len(decoded.exp);
+                let (digits, exp) = core_num::flt2dec::strategy::grisu::format_exact(
+                    decoded,
                     &mut buf[..maxlen],
                     limit,
                 );
@@ -406,40 +2284,600 @@ mod generic {
                     // only after the final rounding-up; it's a regular case with `exp = limit + 1`.
                     PreFormatted::Zero(sign)
                 } else {
-                    let num_zeros = if exp > 0 {
-                        let ndigits = frac_digits + exp as usize;
-                        ndigits - digits.len()
-                    } else {
-                        0
-                    };
-                    PreFormatted::Finite(sign, digits, num_zeros, exp)
+                    // Fits in a 16-bit `usize`.
+                    const_assert!(i16::MAX as i32 - i16::MIN as i32 <= u16::MAX as i32);
+                    let ndigits = (i32::from(exp) - i32::from(limit)) as usize;
+                    PreFormatted::Finite(sign, digits, ndigits.saturating_sub(digits.len()), exp)
                 }
             }
         }
     }
 
-    pub(crate) fn from_preparsed<T: core_num::dec2flt::rawfp::RawFloat>(
-        preparsed: PreParsed<'_>,
-    ) -> Option<T> {
-        // `core_num::dec2flt` does not handle cases where `exp` has
-        // more than 18 digits, but it cannot be the case here because
-        // we use `i16`.
-        let parsed = core_num::dec2flt::parse::Decimal::new(
+    /// The magnitude of `v` (`f64::abs` needs `std` before Rust 1.85).
+    pub(crate) fn abs(v: f64) -> f64 {
+        f64::from_bits(v.to_bits() & !(1 << 63))
+    }
+
+    /// Splits the finite non-zero `v` into `m * 2^e`, with `m` odd.
+    pub(crate) fn odd_significand(v: f64) -> (u64, i32) {
+        let bits = v.to_bits();
+        let biased_exp = ((bits >> 52) & 0x7FF) as i32;
+        let (m, e) = if biased_exp == 0 {
+            (bits & ((1 << 52) - 1), -1074)
+        } else {
+            ((bits & ((1 << 52) - 1)) | 1 << 52, biased_exp - 1075)
+        };
+        let tz = m.trailing_zeros();
+        (m >> tz, e + tz as i32)
+    }
+
+    /// Whether the exact value of the finite non-zero `v` has at most
+    /// `max_digits` significant decimal digits.
+    pub(crate) fn exact_digits_within(v: f64, max_digits: usize) -> bool {
+        use crate::core_num::bignum::Big32x40 as Big;
+
+        // No `f64` has more significant digits.
+        if max_digits >= 767 {
+            return true;
+        }
+        let d = max_digits;
+        let (mut mant, exp) = odd_significand(v);
+        // Without trailing zeros, the significant digits are the integer
+        // `mant * 5^p * 2^q`, which has at most `d` digits if it is below
+        // `10^d = 5^d * 2^d`.
+        let (p, q) = if exp < 0 {
+            (-exp as usize, 0)
+        } else {
+            let mut fives = 0;
+            while mant % 5 == 0 {
+                mant /= 5;
+                fives += 1;
+            }
+            let tens = fives.min(exp as usize);
+            (fives - tens, exp as usize - tens)
+        };
+        // Cancel the common factors, and compare the sides by their binary
+        // logarithms, with `2.32 < log2(5) < 2.33`, when they are far
+        // enough apart. Otherwise both sides fit in a `Big32x40`.
+        let (p, d5) = (p.saturating_sub(d), d.saturating_sub(p));
+        let (q, d2) = (q.saturating_sub(d), d.saturating_sub(q));
+        let mant_bits = 64 - mant.leading_zeros() as usize;
+        let lhs_min = mant_bits - 1 + p * 232 / 100 + q;
+        let lhs_max = mant_bits + (p * 233 + 99) / 100 + q;
+        let rhs_min = d2 + d5 * 232 / 100;
+        let rhs_max = d2 + (d5 * 233 + 99) / 100 + 1;
+        if lhs_min >= rhs_max {
+            return false;
+        } else if lhs_max <= rhs_min {
+            return true;
+        }
+        let mut lhs = Big::from_u64(mant);
+        lhs.mul_pow5(p).mul_pow2(q);
+        let mut rhs = Big::from_small(1);
+        rhs.mul_pow5(d5).mul_pow2(d2);
+        lhs < rhs
+    }
+
+    /// Decodes `v * 2^scale2`, keeping the precision (and so the rounding
+    /// interval) of `T`.
+    ///
+    /// Panics if the result or its unit in the last place are out of the
+    /// range of `f64`.
+    pub(crate) fn decode_scale2<T: crate::sealed::Sealed>(
+        v: T,
+        scale2: i32,
+    ) -> (bool, FullDecoded) {
+        let v = v.to_f64();
+        let sign = v.is_sign_negative();
+        if v.is_nan() {
+            return (sign, FullDecoded::Nan);
+        } else if v.is_infinite() {
+            return (sign, FullDecoded::Infinite);
+        } else if v == 0.0 {
+            return (sign, FullDecoded::Zero);
+        }
+
+        // `v = m * 2^e`, with the precision of `T`
+        let bits = v.to_bits();
+        let biased_exp = ((bits >> 52) & 0x7FF) as i32;
+        let (m, e) = if biased_exp == 0 {
+            (bits & ((1 << 52) - 1), -1074)
+        } else {
+            ((bits & ((1 << 52) - 1)) | 1 << 52, biased_exp - 1075)
+        };
+        let log2 = 63 - m.leading_zeros() as i32 + e;
+        let k = (log2 + 1 - T::SIG_BITS as i32).max(T::MIN_LSB_EXP);
+        let m = m >> (k - e);
+        let e = k;
+
+        let scaled_e = i64::from(e) + i64::from(scale2);
+        let scaled_log2 = i64::from(log2) + i64::from(scale2);
+        assert!(
+            scaled_e >= -1074 && scaled_log2 < 1024,
+            "scaled value out of range"
+        );
+        let e = scaled_e as i16;
+        let even = m & 1 == 0;
+        // Like `core_num::flt2dec::decoder::decode`
+        let decoded = if m == 1 << (T::SIG_BITS - 1) {
+            // The lower neighbor is closer.
+            Decoded {
+                mant: m << 2,
+                minus: 1,
+                plus: 2,
+                exp: e - 2,
+                inclusive: even,
+            }
+        } else {
+            Decoded {
+                mant: m << 1,
+                minus: 1,
+                plus: 1,
+                exp: e - 1,
+                inclusive: even,
+            }
+        };
+        (sign, FullDecoded::Finite(decoded))
+    }
+
+    /// Builds `sign q * 2^k`, where `q < 2^SIG_BITS` and `k >= MIN_LSB_EXP`
+    /// (the value is exactly representable unless it overflows, in which
+    /// case infinity is returned).
+    pub(crate) fn compose<T: crate::sealed::Sealed>(sign: bool, q: u64, k: i32) -> T {
+        debug_assert!(q < 1 << T::SIG_BITS && k >= T::MIN_LSB_EXP);
+        let frac_bits = T::SIG_BITS - 1;
+        let sign_bit = u64::from(sign) << (T::BITS - 1);
+        if q == 0 {
+            return T::from_bits_u64(sign_bit);
+        }
+        // Normalize so that the hidden bit is set, unless it is subnormal.
+        let shift = (q.leading_zeros() as i32 - (64 - T::SIG_BITS as i32))
+            .min(k - T::MIN_LSB_EXP)
+            .max(0);
+        let q = q << shift;
+        let k = k - shift;
+        if k + frac_bits as i32 > T::MAX_EXP {
+            return if sign { T::NEG_INFINITY } else { T::INFINITY };
+        }
+        let bits = if q >> frac_bits == 0 {
+            q
+        } else {
+            // The biased exponent starts at 1 for `k == MIN_LSB_EXP`
+            let biased_exp = (k - T::MIN_LSB_EXP + 1) as u64;
+            (biased_exp << frac_bits) | (q & ((1 << frac_bits) - 1))
+        };
+        T::from_bits_u64(sign_bit | bits)
+    }
+
+    /// Converts a hexadecimal number, rounding half to even.
+    pub(crate) fn from_preparsed_hex<T: crate::sealed::Sealed>(
+        preparsed: PreParsedHex<'_>,
+    ) -> Result<T, ParseFloatError> {
+        from_preparsed_pow2(
+            preparsed.sign,
             preparsed.int_digits,
             preparsed.frac_digits,
+            4,
             i64::from(preparsed.exp),
+        )
+    }
+
+    /// Converts `preparsed`, whose digits and exponent are in base `radix`
+    /// (between 2 and 36), rounding half to even.
+    pub(crate) fn from_preparsed_radix<T: crate::sealed::Sealed>(
+        preparsed: PreParsed<'_>,
+        radix: u32,
+    ) -> Result<T, ParseFloatError> {
+        assert!(
+            (2..=36).contains(&radix),
+            "from_preparsed_radix: radix must be between 2 and 36",
         );
-        let v = core_num::dec2flt::convert::<T>(parsed).ok()?;
+        if radix.is_power_of_two() {
+            let log2_radix = radix.trailing_zeros();
+            return from_preparsed_pow2(
+                preparsed.sign,
+                preparsed.int_digits,
+                preparsed.frac_digits,
+                log2_radix,
+                i64::from(log2_radix) * i64::from(preparsed.exp),
+            );
+        }
+        let format = wide::Format {
+            sig_bits: T::SIG_BITS,
+            min_lsb_exp: T::MIN_LSB_EXP,
+            max_exp: T::MAX_EXP,
+        };
+        let sign = preparsed.sign;
+        let rounded = wide::from_preparsed_radix(preparsed, radix, &format)?;
+        Ok(from_rounded(sign, rounded))
+    }
+
+    /// Converts `sign int_digits.frac_digits * 2^exp2`, whose digits are
+    /// in base `2^log2_radix`, rounding half to even.
+    fn from_preparsed_pow2<T: crate::sealed::Sealed>(
+        sign: bool,
+        int_digits: &[u8],
+        frac_digits: &[u8],
+        log2_radix: u32,
+        exp2: i64,
+    ) -> Result<T, ParseFloatError> {
+        if int_digits.is_empty() && frac_digits.is_empty() {
+            return Err(ParseFloatError::EmptyDigits);
+        }
+        let all_digits = int_digits.iter().chain(frac_digits);
+
+        // The first significant digits that fit in 64 bits (at least 56
+        // significant bits), the others only matter for rounding.
+        let max_sig_digits = 64 / log2_radix;
+        let mut m = 0u64;
+        let mut num_sig_digits = 0;
+        let mut num_used_digits = 0;
+        let mut sticky = false;
+        for (index, &byte) in all_digits.enumerate() {
+            let d = match char::from(byte).to_digit(1 << log2_radix) {
+                Some(d) => u64::from(d),
+                None => return Err(ParseFloatError::InvalidDigit { byte, index }),
+            };
+            if num_sig_digits < max_sig_digits {
+                m = (m << log2_radix) | d;
+                num_used_digits += 1;
+                if m != 0 {
+                    num_sig_digits += 1;
+                }
+            } else {
+                sticky |= d != 0;
+            }
+        }
+        let sign_bit = u64::from(sign) << (T::BITS - 1);
+        if m == 0 {
+            return Ok(T::from_bits_u64(sign_bit));
+        }
+
+        // value = (m + sticky) * 2^e, with `m` normalized to 64 bits
+        let num_digits = int_digits.len() + frac_digits.len();
+        let log2_radix = i64::from(log2_radix);
+        let lz = m.leading_zeros();
+        let m = m << lz;
+        let e = exp2 - log2_radix * frac_digits.len() as i64
+            + log2_radix * (num_digits - num_used_digits) as i64
+            - i64::from(lz);
+        if e + 63 > i64::from(T::MAX_EXP) {
+            return Ok(if sign { T::NEG_INFINITY } else { T::INFINITY });
+        } else if e + 64 < i64::from(T::MIN_LSB_EXP) - 1 {
+            // Less than a quarter of the smallest subnormal.
+            return Ok(T::from_bits_u64(sign_bit));
+        }
+        let e = e as i32;
+
+        // Keep `SIG_BITS` bits, or less for subnormals (`shift` is
+        // between 11 and 65).
+        let k = (e + 64 - T::SIG_BITS as i32).max(T::MIN_LSB_EXP);
+        let shift = (k - e) as u32;
+        let m = u128::from(m);
+        let mut q = m >> shift;
+        let rem = m & ((1 << shift) - 1);
+        let half = 1 << (shift - 1);
+        if rem > half || (rem == half && (sticky || q & 1 == 1)) {
+            q += 1;
+        }
+        let (q, k) = if q >> T::SIG_BITS != 0 {
+            (q >> 1, k + 1)
+        } else {
+            (q, k)
+        };
+        Ok(compose(sign, q as u64, k))
+    }
+
+    /// Converts `preparsed * 2^scale2`, rounding half to even.
+    pub(crate) fn from_preparsed_scale2<T: crate::sealed::Sealed>(
+        preparsed: PreParsed<'_>,
+        scale2: i32,
+    ) -> Result<T, ParseFloatError> {
+        if preparsed.int_digits.is_empty() && preparsed.frac_digits.is_empty() {
+            return Err(ParseFloatError::EmptyDigits);
+        }
+        let all_digits = preparsed.int_digits.iter().chain(preparsed.frac_digits);
+        if let Some((index, &byte)) = all_digits.enumerate().find(|(_, c)| !c.is_ascii_digit()) {
+            return Err(ParseFloatError::InvalidDigit { byte, index });
+        }
+
+        let sign_bit = u64::from(preparsed.sign) << (T::BITS - 1);
+        let preparsed = preparsed.trim_leading_zeros()?.trim_trailing_zeros()?;
+        let num_digits = preparsed.int_digits.len() + preparsed.frac_digits.len();
+        if preparsed.significant_digits() == 0 {
+            return Ok(T::from_bits_u64(sign_bit));
+        }
+
+        // value = d * 10^exp10 * 2^scale2, with `d` of `num_digits` digits
+        let exp10 = i64::from(preparsed.exp) - preparsed.frac_digits.len() as i64;
+        let scale2 = i64::from(scale2);
+        // Bounds of the base 2 logarithm of the value, with
+        // 3.32 < log2(10) < 3.33.
+        let log10_lo = exp10 + num_digits as i64 - 1;
+        let log10_hi = log10_lo + 1;
+        let log2_lo = if log10_lo >= 0 {
+            log10_lo * 332 / 100
+        } else {
+            log10_lo * 333 / 100 - 1
+        } + scale2;
+        let log2_hi = if log10_hi >= 0 {
+            log10_hi * 333 / 100 + 1
+        } else {
+            log10_hi * 332 / 100
+        } + scale2;
+        if log2_lo > i64::from(T::MAX_EXP) + 1 {
+            return Ok(if preparsed.sign {
+                T::NEG_INFINITY
+            } else {
+                T::INFINITY
+            });
+        } else if log2_hi < i64::from(T::MIN_LSB_EXP) - 1 {
+            // Less than half of the smallest subnormal.
+            return Ok(T::from_bits_u64(sign_bit));
+        }
+
+        // value = u / v * 2^exp2, where `u` and `v` have at most
+        // `MAX_BITS` bits (with 2.32 < log2(5) < 2.33), which leaves room
+        // for the shifts below.
+        const MAX_BITS: i64 = 1200;
+        let pow5_bits = exp10.abs() * 233 / 100 + 1;
+        let digits_bits = num_digits as i64 * 333 / 100 + 1;
+        let (u_bits, v_bits) = if exp10 >= 0 {
+            (digits_bits + pow5_bits, 1)
+        } else {
+            (digits_bits, pow5_bits)
+        };
+        if u_bits > MAX_BITS || v_bits > MAX_BITS {
+            // Too large for `Big32x40`, with the wider bignums (which
+            // only compare the digits they cannot hold).
+            let format = wide::Format {
+                sig_bits: T::SIG_BITS,
+                min_lsb_exp: T::MIN_LSB_EXP,
+                max_exp: T::MAX_EXP,
+            };
+            let sign = preparsed.sign;
+            let rounded =
+                wide::from_preparsed_scaled(preparsed, scale2, &format, RoundingMode::NearestEven)?;
+            return Ok(from_rounded(sign, rounded));
+        }
+        let mut u = Big32x40::from_u32(0);
+        for &digit in preparsed.int_digits.iter().chain(preparsed.frac_digits) {
+            u.mul_small(10).add_small(u32::from(digit - b'0'));
+        }
+        let mut v = Big32x40::from_u32(1);
+        if exp10 >= 0 {
+            u.mul_pow5(exp10 as usize);
+        } else {
+            v.mul_pow5(-exp10 as usize);
+        }
+        let exp2 = exp10 + scale2;
+
+        // q = floor(u / v * 2^(exp2 - k)), with `SIG_BITS` bits (or less
+        // for subnormals), since `u / v` is between 2^(bits(u) - bits(v) - 1)
+        // and 2^(bits(u) - bits(v) + 1).
+        let log2_ratio = u.bit_length() as i64 - v.bit_length() as i64;
+        let mut k = (log2_ratio + exp2 - i64::from(T::SIG_BITS)).max(i64::from(T::MIN_LSB_EXP));
+        let shift = exp2 - k;
+        if shift >= 0 {
+            u.mul_pow2(shift as usize);
+        } else {
+            v.mul_pow2(-shift as usize);
+        }
+        let (mut q, mut rem) = u.div_rem(&v);
+        if q.bit_length() > T::SIG_BITS as usize {
+            v.mul_pow2(1);
+            k += 1;
+            let (q1, rem1) = u.div_rem(&v);
+            q = q1;
+            rem = rem1;
+        }
+        let mut q = q
+            .digits()
+            .iter()
+            .take(2)
+            .rev()
+            .fold(0, |acc, &d| acc << 32 | u64::from(d));
+
+        // Rounds half to even.
+        rem.mul_pow2(1);
+        if rem > v || (rem == v && q & 1 == 1) {
+            q += 1;
+            if q >> T::SIG_BITS != 0 {
+                q >>= 1;
+                k += 1;
+            }
+        }
+        if k > i64::from(T::MAX_EXP) {
+            return Ok(if preparsed.sign {
+                T::NEG_INFINITY
+            } else {
+                T::INFINITY
+            });
+        }
+        Ok(compose(preparsed.sign, q, k as i32))
+    }
+
+    /// Converts `preparsed`, rounding with `mode`.
+    pub(crate) fn from_preparsed_rounded<T: crate::sealed::Sealed>(
+        preparsed: PreParsed<'_>,
+        mode: RoundingMode,
+    ) -> Result<T, ParseFloatError> {
+        if preparsed.int_digits.is_empty() && preparsed.frac_digits.is_empty() {
+            return Err(ParseFloatError::EmptyDigits);
+        }
+        let all_digits = || preparsed.int_digits.iter().chain(preparsed.frac_digits);
+        if let Some((index, &byte)) = all_digits().enumerate().find(|(_, c)| !c.is_ascii_digit()) {
+            return Err(ParseFloatError::InvalidDigit { byte, index });
+        }
+
+        // More digits only matter to know whether the number is above a
+        // float or a halfway point between two, which have at most 768
+        // significant digits, so the others are replaced by a single `1`
+        // when any of them is not zero.
+        const MAX_DIGITS: usize = 800;
+        let mut buf = [0; MAX_DIGITS + 1];
+        let num_leading_zeros = all_digits().take_while(|&&c| c == b'0').count();
+        let num_digits = preparsed.int_digits.len() + preparsed.frac_digits.len();
+        let preparsed = if num_digits - num_leading_zeros > MAX_DIGITS {
+            let mut len = 0;
+            for &c in all_digits().skip(num_leading_zeros).take(MAX_DIGITS) {
+                buf[len] = c;
+                len += 1;
+            }
+            if all_digits()
+                .skip(num_leading_zeros + MAX_DIGITS)
+                .any(|&c| c != b'0')
+            {
+                buf[len] = b'1';
+                len += 1;
+            }
+            // 0.digits * 10^exp
+            let exp = i64::from(preparsed.exp) + preparsed.int_digits.len() as i64
+                - num_leading_zeros as i64;
+            PreParsed {
+                sign: preparsed.sign,
+                int_digits: &[],
+                frac_digits: &buf[..len],
+                exp: exp.max(i64::from(i32::MIN)).min(i64::from(i32::MAX)) as i32,
+            }
+        } else {
+            preparsed
+        };
+
+        let format = wide::Format {
+            sig_bits: T::SIG_BITS,
+            min_lsb_exp: T::MIN_LSB_EXP,
+            max_exp: T::MAX_EXP,
+        };
+        let sign = preparsed.sign;
+        let rounded = wide::from_preparsed_rounded(preparsed, &format, mode)?;
+        Ok(from_rounded(sign, rounded))
+    }
+
+    /// Converts `preparsed` with stochastic rounding.
+    pub(crate) fn from_preparsed_stochastic<T: crate::sealed::Sealed>(
+        preparsed: PreParsed<'_>,
+        random: impl FnOnce() -> u64,
+    ) -> Result<T, ParseFloatError> {
+        let format = wide::Format {
+            sig_bits: T::SIG_BITS,
+            min_lsb_exp: T::MIN_LSB_EXP,
+            max_exp: T::MAX_EXP,
+        };
+        let sign = preparsed.sign;
+        let mut rounding = wide::Rounding::Stochastic(wide::Random::Pending(random));
+        let rounded = wide::from_preparsed_rounding(preparsed, 0, &format, &mut rounding)?;
+        Ok(from_rounded(sign, rounded))
+    }
+
+    /// Builds a number rounded to the format of `T`.
+    fn from_rounded<T: crate::sealed::Sealed>(sign: bool, rounded: wide::Rounded) -> T {
+        match rounded {
+            wide::Rounded::Zero => T::from_bits_u64(u64::from(sign) << (T::BITS - 1)),
+            wide::Rounded::Finite(q, k) => compose(sign, q as u64, k),
+            wide::Rounded::Infinite => {
+                if sign {
+                    T::NEG_INFINITY
+                } else {
+                    T::INFINITY
+                }
+            }
+        }
+    }
+
+    pub(crate) fn from_preparsed<T: core_num::dec2flt::float::RawFloat>(
+        preparsed: PreParsed<'_>,
+    ) -> Result<T, ParseFloatError> {
+        let v = from_preparsed_abs::<T>(preparsed)?;
         if preparsed.sign {
-            Some(-v)
+            Ok(-v)
         } else {
-            Some(v)
+            Ok(v)
         }
     }
+
+    /// Like `from_preparsed`, ignoring the sign. Only uses integer
+    /// arithmetic.
+    pub(crate) fn from_preparsed_abs<T: core_num::dec2flt::float::RawFloat>(
+        preparsed: PreParsed<'_>,
+    ) -> Result<T, ParseFloatError> {
+        if preparsed.int_digits.is_empty() && preparsed.frac_digits.is_empty() {
+            return Err(ParseFloatError::EmptyDigits);
+        }
+        // `core_num::dec2flt` relies on the parser to weed out non-digits.
+        let all_digits = preparsed.int_digits.iter().chain(preparsed.frac_digits);
+        if let Some((index, &byte)) = all_digits.enumerate().find(|(_, c)| !c.is_ascii_digit()) {
+            return Err(ParseFloatError::InvalidDigit { byte, index });
+        }
+
+        Ok(core_num::dec2flt::dec2flt::<T>(
+            preparsed.int_digits,
+            preparsed.frac_digits,
+            i64::from(preparsed.exp),
+        ))
+    }
+}
+
+impl sealed::Sealed for f32 {
+    const NAN: Self = f32::NAN;
+    const INFINITY: Self = f32::INFINITY;
+    const NEG_INFINITY: Self = f32::NEG_INFINITY;
+    const BITS: u32 = 32;
+    const SIG_BITS: u32 = 24;
+    const MIN_LSB_EXP: i32 = -149;
+    const MAX_EXP: i32 = 127;
+
+    fn from_bits_u64(bits: u64) -> Self {
+        f32::from_bits(bits as u32)
+    }
+
+    fn to_bits_u64(self) -> u64 {
+        u64::from(self.to_bits())
+    }
+
+    fn to_f64(self) -> f64 {
+        // Widened bit by bit: a hardware conversion would flush subnormals
+        // to zero when denormals-are-zero mode is enabled.
+        let bits = self.to_bits();
+        let sign = u64::from(bits >> 31) << 63;
+        let exp = (bits >> 23) & 0xFF;
+        let mant = u64::from(bits & 0x7F_FFFF);
+        let bits = if exp == 0xFF {
+            // Infinity or NaN, keeping the payload.
+            0x7FF0_0000_0000_0000 | (mant << 29)
+        } else if exp != 0 {
+            (u64::from(exp) + 1023 - 127) << 52 | (mant << 29)
+        } else if mant != 0 {
+            // Subnormal `f32`, normal `f64`: `mant * 2^-149`.
+            let msb = 63 - mant.leading_zeros();
+            (u64::from(msb) + 1023 - 149) << 52 | ((mant << (52 - msb)) & 0xF_FFFF_FFFF_FFFF)
+        } else {
+            0
+        };
+        f64::from_bits(sign | bits)
+    }
 }
+impl sealed::Sealed for f64 {
+    const NAN: Self = f64::NAN;
+    const INFINITY: Self = f64::INFINITY;
+    const NEG_INFINITY: Self = f64::NEG_INFINITY;
+    const BITS: u32 = 64;
+    const SIG_BITS: u32 = 53;
+    const MIN_LSB_EXP: i32 = -1074;
+    const MAX_EXP: i32 = 1023;
+
+    fn from_bits_u64(bits: u64) -> Self {
+        f64::from_bits(bits)
+    }
 
-impl sealed::Sealed for f32 {}
-impl sealed::Sealed for f64 {}
+    fn to_bits_u64(self) -> u64 {
+        self.to_bits()
+    }
+
+    fn to_f64(self) -> f64 {
+        self
+    }
+}
 
 impl FloatExt for f32 {
     fn preformat_shortest(self, buf: &mut [u8]) -> PreFormatted<'_> {
@@ -450,13 +2888,31 @@ impl FloatExt for f32 {
         generic::preformat_exact_exp(self, buf, num_digits)
     }
 
-    fn preformat_exact_fixed(self, buf: &mut [u8], num_frac_digits: usize) -> PreFormatted<'_> {
+    fn preformat_exact_fixed(self, buf: &mut [u8], num_frac_digits: isize) -> PreFormatted<'_> {
         generic::preformat_exact_fixed(self, buf, num_frac_digits)
     }
 
-    fn from_preparsed(preparsed: PreParsed<'_>) -> Option<Self> {
+    fn preformat_exact_limit(self, buf: &mut [u8], limit: i16) -> PreFormatted<'_> {
+        generic::preformat_exact_limit(self, buf, limit)
+    }
+
+    fn from_preparsed(preparsed: PreParsed<'_>) -> Result<Self, ParseFloatError> {
         generic::from_preparsed(preparsed)
     }
+
+    fn from_decimal_parts(mantissa: u64, exp10: i32) -> Self {
+        core_num::dec2flt::dec2flt_parts(mantissa, exp10.into())
+    }
+
+    #[cfg(feature = "num")]
+    fn to_exact_rational(self) -> Option<::num_rational::BigRational> {
+        rational::to_exact_rational(self)
+    }
+
+    #[cfg(feature = "num")]
+    fn from_rational(v: &::num_rational::BigRational) -> Self {
+        rational::from_rational(v)
+    }
 }
 
 impl FloatExt for f64 {
@@ -468,11 +2924,29 @@ impl FloatExt for f64 {
         generic::preformat_exact_exp(self, buf, num_digits)
     }
 
-    fn preformat_exact_fixed(self, buf: &mut [u8], num_frac_digits: usize) -> PreFormatted<'_> {
+    fn preformat_exact_fixed(self, buf: &mut [u8], num_frac_digits: isize) -> PreFormatted<'_> {
         generic::preformat_exact_fixed(self, buf, num_frac_digits)
     }
 
-    fn from_preparsed(preparsed: PreParsed<'_>) -> Option<Self> {
+    fn preformat_exact_limit(self, buf: &mut [u8], limit: i16) -> PreFormatted<'_> {
+        generic::preformat_exact_limit(self, buf, limit)
+    }
+
+    fn from_preparsed(preparsed: PreParsed<'_>) -> Result<Self, ParseFloatError> {
         generic::from_preparsed(preparsed)
     }
+
+    fn from_decimal_parts(mantissa: u64, exp10: i32) -> Self {
+        core_num::dec2flt::dec2flt_parts(mantissa, exp10.into())
+    }
+
+    #[cfg(feature = "num")]
+    fn to_exact_rational(self) -> Option<::num_rational::BigRational> {
+        rational::to_exact_rational(self)
+    }
+
+    #[cfg(feature = "num")]
+    fn from_rational(v: &::num_rational::BigRational) -> Self {
+        rational::from_rational(v)
+    }
 }
@@ -0,0 +1,192 @@
+//! Snapping of numbers to "nice" decimals, such as `1`, `2`, `2.5` or `5`
+//! times a power of ten.
+//!
+//! Nice numbers are described by their significant digits (the steps), so
+//! `[1, 2, 25, 5]` means `1`, `2`, `2.5` and `5` times any power of ten.
+//! `snap` finds the nice number below, above or nearest to a value,
+//! comparing the exact decimal values, which is the building block of axis
+//! ranges, slider steps and chart bounds.
+//!
+//! # Example
+//!
+//! ```
+//! use flt2dec2flt::nice::{snap, STEPS_1_2_25_5};
+//! use flt2dec2flt::RoundingMode;
+//!
+//! let nice = snap(0.37f64, &STEPS_1_2_25_5, RoundingMode::Up).unwrap();
+//! assert_eq!((nice.step, nice.exp), (5, -1));
+//! assert_eq!(nice.to_float::<f64>(), 0.5);
+//!
+//! let nice = snap(-2100.0f64, &STEPS_1_2_25_5, RoundingMode::NearestEven).unwrap();
+//! assert_eq!(nice.to_float::<f64>(), -2000.0);
+//! let nice = snap(-2100.0f64, &STEPS_1_2_25_5, RoundingMode::Down).unwrap();
+//! assert_eq!(nice.to_float::<f64>(), -2500.0);
+//! ```
+
+use core::cmp::Ordering;
+
+use crate::scaled::{exact_expansion, write_u128};
+use crate::{FloatExt, PreFormatted, PreParsed, RoundingMode};
+
+/// `1`, `2` and `5` times a power of ten.
+pub const STEPS_1_2_5: [u32; 3] = [1, 2, 5];
+
+/// `1`, `2`, `2.5` and `5` times a power of ten.
+pub const STEPS_1_2_25_5: [u32; 4] = [1, 2, 25, 5];
+
+/// A nice number, `step * 10^exp`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct NiceNumber {
+    /// Whether the number is negative.
+    pub sign: bool,
+    /// One of the steps given to `snap`.
+    pub step: u32,
+    /// The power of ten.
+    pub exp: i16,
+}
+
+impl NiceNumber {
+    /// Converts the number into the nearest floating point number, which
+    /// may be infinite.
+    pub fn to_float<T: FloatExt>(&self) -> T {
+        let mut buf = [0; 39];
+        let digits = write_u128(&mut buf, u128::from(self.step));
+        let preparsed = PreParsed {
+            sign: self.sign,
+            int_digits: digits,
+            frac_digits: b"",
+            exp: self.exp.into(),
+        };
+        // At most 10 digits, which are always handled.
+        T::from_preparsed(preparsed).unwrap()
+    }
+
+    /// Pre-formats the exact value of the number, to be written with the
+    /// `write` module.
+    ///
+    /// `buf` must be at least 10 bytes long.
+    pub fn preformat<'a>(&self, buf: &'a mut [u8]) -> PreFormatted<'a> {
+        let mut tmp = [0; 39];
+        let (digits, exp) = decimal(u128::from(self.step), i32::from(self.exp), &mut tmp);
+        buf[..digits.len()].copy_from_slice(digits);
+        PreFormatted::Finite(self.sign, &buf[..digits.len()], 0, exp as i16)
+    }
+}
+
+/// Returns the nice number made of one of `steps` that is closest to `v`
+/// in the direction of `mode`, or `v` itself if it is nice.
+///
+/// With `RoundingMode::NearestEven`, ties go to the number whose step is
+/// even, or with the larger magnitude if both or none are (and likewise
+/// with odd steps for `RoundingMode::NearestOdd`). With
+/// `RoundingMode::NearestAway`, they go to the larger magnitude.
+/// `RoundingMode::ToOdd` only goes to the larger magnitude when its step
+/// is odd and the other one is even.
+///
+/// Returns `None` if `v` is zero, NaN or infinite.
+///
+/// Panics if `steps` is empty or has zeros.
+pub fn snap<T: FloatExt>(v: T, steps: &[u32], mode: RoundingMode) -> Option<NiceNumber> {
+    assert!(
+        !steps.is_empty() && !steps.contains(&0),
+        "invalid nice number steps"
+    );
+    let mut buf = [0; crate::PREFORMAT_EXACT_FIXED_BASE_BUF_LEN];
+    let (sign, digits, exp) = match exact_expansion(v, &mut buf) {
+        PreFormatted::Finite(sign, digits, _, exp) => (sign, digits, i32::from(exp)),
+        _ => return None,
+    };
+    let value = (digits, exp);
+
+    // `|v|` is in `[10^(exp - 1), 10^exp)`, so the candidates below it
+    // start at `10^(exp - 2)` and those above it end at `10^(exp + 1)`.
+    let mut below: Option<(u32, i32)> = None;
+    let mut above: Option<(u32, i32)> = None;
+    for &step in steps {
+        let mut tmp = [0; 39];
+        let num_digits = write_u128(&mut tmp, u128::from(step)).len() as i32;
+        for decade in exp - 1..=exp + 1 {
+            let candidate = (step, decade - num_digits);
+            let c = compare(candidate, value);
+            if c != Ordering::Greater
+                && below.map_or(true, |b| compare_nice(b, candidate) == Ordering::Less)
+            {
+                below = Some(candidate);
+            }
+            if c != Ordering::Less
+                && above.map_or(true, |a| compare_nice(candidate, a) == Ordering::Less)
+            {
+                above = Some(candidate);
+            }
+        }
+    }
+    let (below, above) = (below.unwrap(), above.unwrap());
+
+    let larger = match mode {
+        _ if below == above => false,
+        RoundingMode::TowardZero => false,
+        RoundingMode::Down => sign,
+        RoundingMode::Up => !sign,
+        RoundingMode::ToOdd => below.0 % 2 == 0 && above.0 % 2 != 0,
+        _ => {
+            // Compare `|v|` with the midpoint, `(below + above) / 2`.
+            let k = below.1.min(above.1);
+            let sum = u128::from(below.0) * 10u128.pow((below.1 - k) as u32)
+                + u128::from(above.0) * 10u128.pow((above.1 - k) as u32);
+            let mut tmp = [0; 39];
+            let midpoint = decimal(sum * 5, k - 1, &mut tmp);
+            match compare_decimal(value, midpoint) {
+                Ordering::Less => false,
+                Ordering::Greater => true,
+                Ordering::Equal => match mode {
+                    RoundingMode::NearestEven => above.0 % 2 == 0 || below.0 % 2 != 0,
+                    RoundingMode::NearestOdd => above.0 % 2 != 0 || below.0 % 2 == 0,
+                    _ => true,
+                },
+            }
+        }
+    };
+    let (step, exp) = if larger { above } else { below };
+    Some(NiceNumber {
+        sign,
+        step,
+        exp: exp as i16,
+    })
+}
+
+/// Converts `x * 10^k` into its digits without trailing zeros and the
+/// exponent of `0.digits * 10^exp`.
+fn decimal(x: u128, k: i32, buf: &mut [u8; 39]) -> (&[u8], i32) {
+    let digits = write_u128(buf, x);
+    let exp = digits.len() as i32 + k;
+    let len = digits.iter().rposition(|&c| c != b'0').unwrap() + 1;
+    (&digits[..len], exp)
+}
+
+/// Compares the nice numbers `step * 10^exp`.
+fn compare_nice(a: (u32, i32), b: (u32, i32)) -> Ordering {
+    let (mut tmp_a, mut tmp_b) = ([0; 39], [0; 39]);
+    compare_decimal(
+        decimal(u128::from(a.0), a.1, &mut tmp_a),
+        decimal(u128::from(b.0), b.1, &mut tmp_b),
+    )
+}
+
+/// Compares the nice number `step * 10^exp` with a decimal.
+fn compare(nice: (u32, i32), decimal_b: (&[u8], i32)) -> Ordering {
+    let mut tmp = [0; 39];
+    compare_decimal(decimal(u128::from(nice.0), nice.1, &mut tmp), decimal_b)
+}
+
+/// Compares the positive numbers `0.digits * 10^exp`, whose first digit is
+/// not zero.
+fn compare_decimal(a: (&[u8], i32), b: (&[u8], i32)) -> Ordering {
+    a.1.cmp(&b.1).then_with(|| {
+        let len = a.0.len().max(b.0.len());
+        let digit = |digits: &[u8], i: usize| digits.get(i).copied().unwrap_or(b'0');
+        (0..len)
+            .map(|i| digit(a.0, i).cmp(&digit(b.0, i)))
+            .find(|&c| c != Ordering::Equal)
+            .unwrap_or(Ordering::Equal)
+    })
+}
@@ -0,0 +1,73 @@
+//! Formatting and parsing of sample values in the Prometheus and
+//! OpenMetrics text exposition formats.
+//!
+//! Values are written like the official Go client: with the shortest
+//! representation that round-trips, like Go's `strconv.FormatFloat(v, 'g',
+//! -1, 64)` (in scientific notation when the decimal exponent is below -4
+//! or above 5), with `.0` appended to integers (as OpenMetrics requires),
+//! and `+Inf`, `-Inf` and `NaN` for special values.
+//!
+//! # Example
+//!
+//! ```
+//! use flt2dec2flt::openmetrics::{parse, write, BUFFER_LEN};
+//!
+//! let mut buf = [0; BUFFER_LEN];
+//! assert_eq!(write(&mut buf, 42.0), "42.0");
+//! assert_eq!(write(&mut buf, 0.25), "0.25");
+//! assert_eq!(write(&mut buf, 1.5e-7), "1.5e-07");
+//! assert_eq!(write(&mut buf, 1e6), "1e+06");
+//! assert_eq!(write(&mut buf, f64::INFINITY), "+Inf");
+//!
+//! assert_eq!(parse(b"+Inf"), Ok(f64::INFINITY));
+//! assert_eq!(parse(b"1.5e-07"), Ok(1.5e-7));
+//! assert_eq!(parse(b".5"), Ok(0.5));
+//! assert!(parse(b"0x1p3").is_err());
+//! ```
+
+use crate::go::{format_float, parse_float_with, ParseError};
+use crate::grammar::Grammar;
+
+/// Length of the longest string written by `write`.
+///
+/// That is a sign, 17 digits, a decimal point and an exponent such as
+/// `e-308`.
+pub const BUFFER_LEN: usize = 24;
+
+/// Writes `v` as an OpenMetrics sample value.
+///
+/// Panics if `buf` is shorter than `BUFFER_LEN`.
+pub fn write(buf: &mut [u8], v: f64) -> &str {
+    if v == 0.0 {
+        return "0.0";
+    }
+    if !v.is_finite() {
+        return format_float(buf, v, b'g', -1).expect("buffer too small");
+    }
+    let len = format_float(buf, v, b'g', -1)
+        .expect("buffer too small")
+        .len();
+    let is_integer = buf[..len].iter().all(|&c| c == b'-' || c.is_ascii_digit());
+    let len = if is_integer {
+        buf[len..len + 2].copy_from_slice(b".0");
+        len + 2
+    } else {
+        len
+    };
+    // Only ASCII bytes are written.
+    core::str::from_utf8(&buf[..len]).unwrap()
+}
+
+/// Parses a sample value like the Prometheus server.
+///
+/// Accepts what Go's `strconv.ParseFloat` accepts (see
+/// `go::parse_float`), except hexadecimal numbers, so `.5`, `5.`,
+/// `+1.5E3`, `inf` or `-Infinity` are accepted. Numbers too large to be
+/// represented are rejected.
+pub fn parse(s: &[u8]) -> Result<f64, ParseError> {
+    let grammar = Grammar {
+        hex: false,
+        ..Grammar::GO
+    };
+    parse_float_with(&grammar, s)
+}
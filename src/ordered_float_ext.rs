@@ -0,0 +1,141 @@
+//! Support for the wrappers of the `ordered-float` crate.
+//!
+//! Requires the `ordered-float` feature.
+//!
+//! `OrderedFloat<f32>` and `OrderedFloat<f64>` implement `FloatExt`, so they
+//! can be used anywhere `f32` and `f64` can. `NotNan` cannot represent
+//! every value `FloatExt` works with (such as a parsed NaN), so it gets the
+//! same methods through `NotNanExt` instead.
+//!
+//! # Example
+//!
+//! ```
+//! use flt2dec2flt::ordered_float_ext::NotNanExt as _;
+//! use flt2dec2flt::FloatExt as _;
+//! use ordered_float::{NotNan, OrderedFloat};
+//!
+//! let mut buf = [0; flt2dec2flt::PREFORMAT_SHORTEST_BUF_LEN];
+//! assert_eq!(
+//!     OrderedFloat(1.5f32).preformat_shortest(&mut buf),
+//!     flt2dec2flt::PreFormatted::Finite(false, b"15", 0, 1),
+//! );
+//! assert_eq!(
+//!     NotNan::new(0.25f64).unwrap().preformat_shortest(&mut buf),
+//!     flt2dec2flt::PreFormatted::Finite(false, b"25", 0, 0),
+//! );
+//! ```
+
+use ::ordered_float::{NotNan, OrderedFloat};
+
+use crate::{FloatExt, ParseFloatError, PreFormatted, PreParsed};
+
+macro_rules! impl_ordered_float {
+    ($t:ty) => {
+        impl crate::sealed::Sealed for OrderedFloat<$t> {
+            const NAN: Self = OrderedFloat(<$t>::NAN);
+            const INFINITY: Self = OrderedFloat(<$t>::INFINITY);
+            const NEG_INFINITY: Self = OrderedFloat(<$t>::NEG_INFINITY);
+            const BITS: u32 = <$t as crate::sealed::Sealed>::BITS;
+            const SIG_BITS: u32 = <$t as crate::sealed::Sealed>::SIG_BITS;
+            const MIN_LSB_EXP: i32 = <$t as crate::sealed::Sealed>::MIN_LSB_EXP;
+            const MAX_EXP: i32 = <$t as crate::sealed::Sealed>::MAX_EXP;
+
+            fn from_bits_u64(bits: u64) -> Self {
+                OrderedFloat(crate::sealed::Sealed::from_bits_u64(bits))
+            }
+
+            fn to_bits_u64(self) -> u64 {
+                crate::sealed::Sealed::to_bits_u64(self.0)
+            }
+
+            fn to_f64(self) -> f64 {
+                crate::sealed::Sealed::to_f64(self.0)
+            }
+        }
+
+        impl FloatExt for OrderedFloat<$t> {
+            fn preformat_shortest(self, buf: &mut [u8]) -> PreFormatted<'_> {
+                self.0.preformat_shortest(buf)
+            }
+
+            fn preformat_exact_exp(self, buf: &mut [u8], num_digits: usize) -> PreFormatted<'_> {
+                self.0.preformat_exact_exp(buf, num_digits)
+            }
+
+            fn preformat_exact_fixed(
+                self,
+                buf: &mut [u8],
+                num_frac_digits: isize,
+            ) -> PreFormatted<'_> {
+                self.0.preformat_exact_fixed(buf, num_frac_digits)
+            }
+
+            fn preformat_exact_limit(self, buf: &mut [u8], limit: i16) -> PreFormatted<'_> {
+                self.0.preformat_exact_limit(buf, limit)
+            }
+
+            fn from_preparsed(preparsed: PreParsed<'_>) -> Result<Self, ParseFloatError> {
+                <$t>::from_preparsed(preparsed).map(OrderedFloat)
+            }
+
+            fn from_decimal_parts(mantissa: u64, exp10: i32) -> Self {
+                OrderedFloat(<$t>::from_decimal_parts(mantissa, exp10))
+            }
+
+            #[cfg(feature = "num")]
+            fn to_exact_rational(self) -> Option<::num_rational::BigRational> {
+                self.0.to_exact_rational()
+            }
+
+            #[cfg(feature = "num")]
+            fn from_rational(v: &::num_rational::BigRational) -> Self {
+                OrderedFloat(<$t>::from_rational(v))
+            }
+        }
+    };
+}
+
+impl_ordered_float!(f32);
+impl_ordered_float!(f64);
+
+/// The methods of `FloatExt`, for `NotNan<f32>` and `NotNan<f64>`.
+pub trait NotNanExt: Sized {
+    /// See `FloatExt::preformat_shortest`.
+    fn preformat_shortest(self, buf: &mut [u8]) -> PreFormatted<'_>;
+
+    /// See `FloatExt::preformat_exact_exp`.
+    fn preformat_exact_exp(self, buf: &mut [u8], num_digits: usize) -> PreFormatted<'_>;
+
+    /// See `FloatExt::preformat_exact_fixed`.
+    fn preformat_exact_fixed(self, buf: &mut [u8], num_frac_digits: isize) -> PreFormatted<'_>;
+
+    /// See `FloatExt::preformat_exact_limit`.
+    fn preformat_exact_limit(self, buf: &mut [u8], limit: i16) -> PreFormatted<'_>;
+
+    /// See `FloatExt::from_preparsed`, which never returns NaN.
+    fn from_preparsed(preparsed: PreParsed<'_>) -> Result<Self, ParseFloatError>;
+}
+
+impl<T: FloatExt + ::ordered_float::FloatCore> NotNanExt for NotNan<T> {
+    fn preformat_shortest(self, buf: &mut [u8]) -> PreFormatted<'_> {
+        self.into_inner().preformat_shortest(buf)
+    }
+
+    fn preformat_exact_exp(self, buf: &mut [u8], num_digits: usize) -> PreFormatted<'_> {
+        self.into_inner().preformat_exact_exp(buf, num_digits)
+    }
+
+    fn preformat_exact_fixed(self, buf: &mut [u8], num_frac_digits: isize) -> PreFormatted<'_> {
+        self.into_inner()
+            .preformat_exact_fixed(buf, num_frac_digits)
+    }
+
+    fn preformat_exact_limit(self, buf: &mut [u8], limit: i16) -> PreFormatted<'_> {
+        self.into_inner().preformat_exact_limit(buf, limit)
+    }
+
+    fn from_preparsed(preparsed: PreParsed<'_>) -> Result<Self, ParseFloatError> {
+        let v = T::from_preparsed(preparsed)?;
+        Ok(NotNan::new(v).expect("from_preparsed returned NaN"))
+    }
+}
@@ -0,0 +1,169 @@
+//! Owned versions of `PreFormatted` and `PreParsed`.
+
+use alloc::vec::Vec;
+
+use crate::sink::Formatted;
+use crate::{PreFormatted, PreParsed};
+
+/// An owned version of `PreFormatted`, which can be stored or
+/// transmitted without the buffer it was formatted into.
+///
+/// With the `serde` feature, it implements `Serialize` and `Deserialize`.
+/// Digits are represented as strings.
+///
+/// # Example
+///
+/// ```
+/// use flt2dec2flt::FloatExt as _;
+///
+/// let mut buf = [0; flt2dec2flt::PREFORMAT_SHORTEST_BUF_LEN];
+/// let owned = flt2dec2flt::OwnedPreFormatted::from(1.5f32.preformat_shortest(&mut buf));
+/// assert_eq!(
+///     owned,
+///     flt2dec2flt::OwnedPreFormatted::Finite(false, b"15".to_vec(), 0, 1),
+/// );
+/// assert_eq!(
+///     owned.as_preformatted(),
+///     flt2dec2flt::PreFormatted::Finite(false, b"15", 0, 1),
+/// );
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(crate = "serde"))]
+pub enum OwnedPreFormatted {
+    /// The number is NaN.
+    NaN,
+    /// The is infinity. The boolean specifies the sign.
+    Inf(bool),
+    /// The number is absolute zero. The boolean specifies the sign.
+    Zero(bool),
+    /// The number is finite. See `PreFormatted::Finite`.
+    Finite(
+        bool,
+        #[cfg_attr(feature = "serde", serde(with = "serde_digits"))] Vec<u8>,
+        usize,
+        i16,
+    ),
+}
+
+impl OwnedPreFormatted {
+    /// Borrows `self` as a `PreFormatted`.
+    pub fn as_preformatted(&self) -> PreFormatted<'_> {
+        match *self {
+            OwnedPreFormatted::NaN => PreFormatted::NaN,
+            OwnedPreFormatted::Inf(sign) => PreFormatted::Inf(sign),
+            OwnedPreFormatted::Zero(sign) => PreFormatted::Zero(sign),
+            OwnedPreFormatted::Finite(sign, ref digits, zeros, exp) => {
+                PreFormatted::Finite(sign, digits, zeros, exp)
+            }
+        }
+    }
+
+    /// Builds an `OwnedPreFormatted` from the result of a `preformat_*_into`
+    /// method and the digits it pushed.
+    pub(crate) fn from_sink(formatted: Formatted, digits: Vec<u8>) -> Self {
+        match formatted {
+            Formatted::NaN => OwnedPreFormatted::NaN,
+            Formatted::Inf(sign) => OwnedPreFormatted::Inf(sign),
+            Formatted::Zero(sign) => OwnedPreFormatted::Zero(sign),
+            Formatted::Finite(sign, exp) => OwnedPreFormatted::Finite(sign, digits, 0, exp),
+        }
+    }
+}
+
+impl From<PreFormatted<'_>> for OwnedPreFormatted {
+    fn from(v: PreFormatted<'_>) -> Self {
+        match v {
+            PreFormatted::NaN => OwnedPreFormatted::NaN,
+            PreFormatted::Inf(sign) => OwnedPreFormatted::Inf(sign),
+            PreFormatted::Zero(sign) => OwnedPreFormatted::Zero(sign),
+            PreFormatted::Finite(sign, digits, zeros, exp) => {
+                OwnedPreFormatted::Finite(sign, digits.to_vec(), zeros, exp)
+            }
+        }
+    }
+}
+
+/// An owned version of `PreParsed`.
+///
+/// With the `serde` feature, it implements `Serialize` and `Deserialize`.
+/// Digits are represented as strings.
+///
+/// # Example
+///
+/// ```
+/// use flt2dec2flt::FloatExt as _;
+///
+/// let owned = flt2dec2flt::OwnedPreParsed {
+///     sign: true,
+///     int_digits: b"12".to_vec(),
+///     frac_digits: b"5".to_vec(),
+///     exp: 0,
+/// };
+/// assert_eq!(f64::from_preparsed(owned.as_preparsed()), Ok(-12.5));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(crate = "serde"))]
+pub struct OwnedPreParsed {
+    pub sign: bool,
+    #[cfg_attr(feature = "serde", serde(with = "serde_digits"))]
+    pub int_digits: Vec<u8>,
+    #[cfg_attr(feature = "serde", serde(with = "serde_digits"))]
+    pub frac_digits: Vec<u8>,
+    pub exp: i32,
+}
+
+impl OwnedPreParsed {
+    /// Borrows `self` as a `PreParsed`.
+    pub fn as_preparsed(&self) -> PreParsed<'_> {
+        PreParsed {
+            sign: self.sign,
+            int_digits: &self.int_digits,
+            frac_digits: &self.frac_digits,
+            exp: self.exp,
+        }
+    }
+}
+
+impl From<PreParsed<'_>> for OwnedPreParsed {
+    fn from(v: PreParsed<'_>) -> Self {
+        Self {
+            sign: v.sign,
+            int_digits: v.int_digits.to_vec(),
+            frac_digits: v.frac_digits.to_vec(),
+            exp: v.exp,
+        }
+    }
+}
+
+/// (De)serializes ASCII digits as a string.
+#[cfg(feature = "serde")]
+mod serde_digits {
+    use alloc::string::String;
+    use alloc::vec::Vec;
+
+    use serde::de::Error as _;
+    use serde::ser::Error as _;
+
+    pub(super) fn serialize<S: serde::Serializer>(
+        digits: &[u8],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        if !digits.iter().all(u8::is_ascii_digit) {
+            return Err(S::Error::custom("digits must be ASCII decimal digits"));
+        }
+        // ASCII is valid UTF-8
+        serializer.serialize_str(core::str::from_utf8(digits).unwrap())
+    }
+
+    pub(super) fn deserialize<'de, D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<u8>, D::Error> {
+        let s: String = serde::Deserialize::deserialize(deserializer)?;
+        if !s.bytes().all(|c| c.is_ascii_digit()) {
+            return Err(D::Error::custom("digits must be ASCII decimal digits"));
+        }
+        Ok(s.into_bytes())
+    }
+}
@@ -0,0 +1,150 @@
+//! Formatting of numbers given by their binary significand and exponent,
+//! for values that are never held in a float type (such as the
+//! accumulators of fixed-point or soft-float code).
+//!
+//! A `Parts` is the finite number `mant * 2^exp`. Its rounding interval,
+//! from `(mant - minus) * 2^exp` to `(mant + plus) * 2^exp`, is the set
+//! of values that the shortest digits may denote, so it can describe the
+//! precision of any format, or a known error bound.
+//!
+//! The exponent range is that of binary128: `exp` must be at least
+//! `-16495` and `(mant + plus) * 2^exp` must be below `2^16384`.
+//!
+//! # Example
+//!
+//! ```
+//! use flt2dec2flt::parts::{self, Parts};
+//! use flt2dec2flt::PreFormatted;
+//!
+//! // 0.1 with 32 fractional bits, accurate to 16 units in the last place.
+//! let v = Parts {
+//!     sign: false,
+//!     mant: 0x1999_999A,
+//!     minus: 16,
+//!     plus: 16,
+//!     exp: -32,
+//!     inclusive: true,
+//! };
+//! let mut buf = [0; parts::EXACT_FIXED_BASE_BUF_LEN];
+//! assert_eq!(
+//!     parts::preformat_shortest_from_parts(v, &mut buf),
+//!     PreFormatted::Finite(false, b"1", 0, 0),
+//! );
+//! // The exact value is 0.1000000000931322574615478515625.
+//! assert_eq!(
+//!     parts::preformat_exact_fixed_from_parts(v, &mut buf, 12),
+//!     PreFormatted::Finite(false, b"100000000093", 0, 0),
+//! );
+//! ```
+
+use crate::wide::{self, Decoded, FullDecoded};
+use crate::PreFormatted;
+
+/// Minimum buffer size that has to be passed to
+/// `preformat_shortest_from_parts`.
+// A 65-bit `mant + plus` over an interval of at least 1 unit has at most
+// 21 significant digits.
+pub const SHORTEST_BUF_LEN: usize = 21;
+
+/// Minimum base buffer size that has to be passed to
+/// `preformat_exact_fixed_from_parts` (see
+/// `PREFORMAT_EXACT_FIXED_BASE_BUF_LEN`).
+// `wide::estimate_max_buf_len` of the smallest exponent, as in binary128.
+pub const EXACT_FIXED_BASE_BUF_LEN: usize = crate::binary128::EXACT_FIXED_BASE_BUF_LEN;
+
+/// A finite number given by its components, like the result of decoding
+/// a float.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Parts {
+    /// Whether the number is negative.
+    pub sign: bool,
+    /// The significand. The number is zero if it is zero.
+    pub mant: u64,
+    /// The lower width of the rounding interval, in units of `2^exp`.
+    ///
+    /// Must be at least 1 and at most `mant` (unless `mant` is zero).
+    pub minus: u64,
+    /// The upper width of the rounding interval, in units of `2^exp`.
+    ///
+    /// Must be at least 1 (unless `mant` is zero).
+    pub plus: u64,
+    /// The binary exponent.
+    pub exp: i16,
+    /// Whether the bounds of the rounding interval are included in it.
+    ///
+    /// For IEEE 754 formats, this is true when the significand is even.
+    pub inclusive: bool,
+}
+
+impl Parts {
+    fn decode(self) -> (bool, FullDecoded) {
+        if self.mant == 0 {
+            return (self.sign, FullDecoded::Zero);
+        }
+        assert!(
+            self.minus != 0 && self.minus <= self.mant && self.plus != 0,
+            "invalid rounding interval"
+        );
+        let high = u128::from(self.mant) + u128::from(self.plus);
+        let high_bits = 128 - high.leading_zeros() as i32;
+        assert!(
+            self.exp >= -16495 && i32::from(self.exp) + high_bits <= 16384,
+            "parts out of range"
+        );
+        let decoded = Decoded {
+            mant: u128::from(self.mant),
+            minus: u128::from(self.minus),
+            plus: u128::from(self.plus),
+            exp: i32::from(self.exp),
+            inclusive: self.inclusive,
+        };
+        (self.sign, FullDecoded::Finite(decoded))
+    }
+}
+
+/// Pre-formats `v` with the lowest number of significant digits that are
+/// within its rounding interval (and closest to its value), like
+/// `FloatExt::preformat_shortest`.
+///
+/// `buf` must be at least `SHORTEST_BUF_LEN` long.
+pub fn preformat_shortest_from_parts(v: Parts, buf: &mut [u8]) -> PreFormatted<'_> {
+    let (sign, full_decoded) = v.decode();
+    wide::preformat_shortest(sign, full_decoded, buf)
+}
+
+/// Pre-formats `v` with an exact number of significant digits, like
+/// `FloatExt::preformat_exact_exp`. The rounding interval is not used.
+///
+/// `buf` must be at least `num_digits` long (or `EXACT_FIXED_BASE_BUF_LEN`,
+/// if that is smaller), and `num_digits` must not be zero.
+pub fn preformat_exact_exp_from_parts(
+    v: Parts,
+    buf: &mut [u8],
+    num_digits: usize,
+) -> PreFormatted<'_> {
+    let (sign, full_decoded) = v.decode();
+    wide::preformat_exact_exp(sign, full_decoded, buf, num_digits)
+}
+
+/// Pre-formats `v` with an exact number of fractional digits, like
+/// `FloatExt::preformat_exact_fixed`. The rounding interval is not used.
+///
+/// `buf` must be at least `EXACT_FIXED_BASE_BUF_LEN` long, whatever
+/// `num_frac_digits` is.
+pub fn preformat_exact_fixed_from_parts(
+    v: Parts,
+    buf: &mut [u8],
+    num_frac_digits: isize,
+) -> PreFormatted<'_> {
+    let (sign, full_decoded) = v.decode();
+    wide::preformat_exact_fixed(sign, full_decoded, buf, num_frac_digits)
+}
+
+/// Pre-formats `v` with the digits above `10^limit`, like
+/// `FloatExt::preformat_exact_limit`. The rounding interval is not used.
+///
+/// `buf` must be at least `EXACT_FIXED_BASE_BUF_LEN` long.
+pub fn preformat_exact_limit_from_parts(v: Parts, buf: &mut [u8], limit: i16) -> PreFormatted<'_> {
+    let (sign, full_decoded) = v.decode();
+    wide::preformat_exact_limit(sign, full_decoded, buf, limit)
+}
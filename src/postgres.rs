@@ -0,0 +1,79 @@
+//! Text output of PostgreSQL's `float4` and `float8` types.
+//!
+//! With a positive `extra_float_digits` (the default since PostgreSQL 12),
+//! values are written with the shortest representation that round-trips
+//! (rounding ties to even, as PostgreSQL's Ryu does), in positional
+//! notation when their decimal exponent is between -4 and 14 (5 for
+//! `float4`), and in scientific notation otherwise. With zero or a negative
+//! `extra_float_digits`, values are written like `printf`'s `%.*g`, with
+//! `15 + extra_float_digits` significant digits (6 for `float4`).
+//!
+//! # Example
+//!
+//! ```
+//! use flt2dec2flt::postgres::{write_float4, write_float8, BUFFER_LEN};
+//!
+//! let mut buf = [0; BUFFER_LEN];
+//! assert_eq!(write_float8(&mut buf, 0.1 + 0.2, 1), "0.30000000000000004");
+//! assert_eq!(write_float8(&mut buf, 0.1 + 0.2, 0), "0.3");
+//! assert_eq!(write_float8(&mut buf, 1e15, 1), "1e+15");
+//! assert_eq!(write_float8(&mut buf, 1.5e-5, 1), "1.5e-05");
+//! assert_eq!(write_float4(&mut buf, 123456.0, 1), "123456");
+//! assert_eq!(write_float4(&mut buf, 1234567.0, 1), "1.234567e+06");
+//! assert_eq!(write_float8(&mut buf, f64::NEG_INFINITY, 1), "-Infinity");
+//! ```
+
+use crate::write::{write_general, ExpFormat, WriteOptions};
+use crate::{FloatExt, PreFormatted};
+
+/// Length of the longest string written by `write_float4` and
+/// `write_float8`.
+pub const BUFFER_LEN: usize = crate::write::MINIMAL_SHORTEST_LEN;
+
+const OPTIONS: WriteOptions<'static> = WriteOptions {
+    nan: "NaN",
+    inf: "Infinity",
+    exp_format: ExpFormat {
+        plus_sign: true,
+        min_digits: 2,
+    },
+    ..WriteOptions::DEFAULT
+};
+
+/// Writes a `float4` value as PostgreSQL does with the given
+/// `extra_float_digits` setting (between -15 and 3).
+///
+/// Panics if `buf` is shorter than `BUFFER_LEN`.
+pub fn write_float4(buf: &mut [u8], v: f32, extra_float_digits: i32) -> &str {
+    write(buf, v, 6, extra_float_digits)
+}
+
+/// Writes a `float8` value as PostgreSQL does with the given
+/// `extra_float_digits` setting (between -15 and 3).
+///
+/// Panics if `buf` is shorter than `BUFFER_LEN`.
+pub fn write_float8(buf: &mut [u8], v: f64, extra_float_digits: i32) -> &str {
+    write(buf, v, 15, extra_float_digits)
+}
+
+/// `dig` is `FLT_DIG` or `DBL_DIG`.
+fn write<T: FloatExt>(buf: &mut [u8], v: T, dig: i32, extra_float_digits: i32) -> &str {
+    let mut digits_buf = [0; crate::PREFORMAT_SHORTEST_BUF_LEN];
+    let s = if extra_float_digits > 0 {
+        let preformatted = crate::generic::preformat_shortest_even(v, &mut digits_buf);
+        write_general(buf, preformatted, -4..=dig - 1, 0, &OPTIONS)
+    } else {
+        // `%.*g`, which omits trailing zeros
+        let precision = (dig + extra_float_digits).max(1);
+        let preformatted = v.preformat_exact_exp(&mut digits_buf, precision as usize);
+        let preformatted = match preformatted {
+            PreFormatted::Finite(sign, digits, _, exp) => {
+                let len = digits.iter().rposition(|&c| c != b'0').map_or(0, |i| i + 1);
+                PreFormatted::Finite(sign, &digits[..len], 0, exp)
+            }
+            other => other,
+        };
+        write_general(buf, preformatted, -4..=precision - 1, 0, &OPTIONS)
+    };
+    s.expect("buffer too small")
+}
@@ -0,0 +1,85 @@
+use crate::core_num::flt2dec::decoder::FullDecoded;
+use crate::{generic, FloatExt, PreFormatted};
+
+/// A number decoded once to be pre-formatted several times.
+///
+/// The `preformat_*` methods give the same results as the `FloatExt`
+/// methods of the same name, but the sign, significand, exponent and
+/// rounding interval of the number are only extracted by `new`. This
+/// avoids redundant work when a value is shown at several precisions
+/// (such as in a tooltip, an axis and a table).
+///
+/// # Example
+///
+/// ```
+/// use flt2dec2flt::{PreDecoded, PreFormatted};
+///
+/// let v = PreDecoded::new(-2.675f64);
+/// let mut buf = [0; flt2dec2flt::PREFORMAT_EXACT_FIXED_BASE_BUF_LEN];
+///
+/// assert_eq!(v.preformat_shortest(&mut buf), PreFormatted::Finite(true, b"2675", 0, 1));
+/// assert_eq!(v.preformat_exact_fixed(&mut buf, 2), PreFormatted::Finite(true, b"267", 0, 1));
+/// assert_eq!(
+///     v.preformat_exact_exp(&mut buf, 20),
+///     PreFormatted::Finite(true, b"26749999999999998224", 0, 1),
+/// );
+/// assert_eq!(v.value(), -2.675);
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct PreDecoded<F> {
+    value: F,
+    sign: bool,
+    full_decoded: FullDecoded,
+}
+
+impl<F: FloatExt> PreDecoded<F> {
+    /// Decodes `value`.
+    pub fn new(value: F) -> Self {
+        // Scaling by `2^0` never fails.
+        let (sign, full_decoded) = generic::decode_scale2(value, 0);
+        Self {
+            value,
+            sign,
+            full_decoded,
+        }
+    }
+
+    /// Returns the decoded number.
+    pub fn value(&self) -> F {
+        self.value
+    }
+
+    /// Like `FloatExt::preformat_shortest`.
+    pub fn preformat_shortest<'a>(&self, buf: &'a mut [u8]) -> PreFormatted<'a> {
+        match self.full_decoded {
+            FullDecoded::Finite(ref decoded) => {
+                let (digits, exp) = crate::strategy::format_shortest(self.value, decoded, buf);
+                PreFormatted::Finite(self.sign, digits, 0, exp)
+            }
+            full_decoded => generic::preformat_shortest_decoded(self.sign, full_decoded, buf),
+        }
+    }
+
+    /// Like `FloatExt::preformat_exact_exp`.
+    pub fn preformat_exact_exp<'a>(
+        &self,
+        buf: &'a mut [u8],
+        num_digits: usize,
+    ) -> PreFormatted<'a> {
+        generic::preformat_exact_exp_decoded(self.sign, self.full_decoded, buf, num_digits)
+    }
+
+    /// Like `FloatExt::preformat_exact_fixed`.
+    pub fn preformat_exact_fixed<'a>(
+        &self,
+        buf: &'a mut [u8],
+        num_frac_digits: isize,
+    ) -> PreFormatted<'a> {
+        generic::preformat_exact_fixed_decoded(self.sign, self.full_decoded, buf, num_frac_digits)
+    }
+
+    /// Like `FloatExt::preformat_exact_limit`.
+    pub fn preformat_exact_limit<'a>(&self, buf: &'a mut [u8], limit: i16) -> PreFormatted<'a> {
+        generic::preformat_exact_limit_decoded(self.sign, self.full_decoded, buf, limit)
+    }
+}
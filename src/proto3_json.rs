@@ -0,0 +1,70 @@
+//! Formatting of `float` and `double` fields in the JSON mapping of
+//! Protocol Buffers (proto3).
+//!
+//! Finite values are written as JSON numbers with the shortest
+//! representation that round-trips (the even one when two are equally
+//! close), in positional notation when their magnitude is between `1e-6`
+//! and `1e21` (like JavaScript and the official Go implementation), such as
+//! `0.000001`, `123.5` or `1e+21`.
+//! NaN and infinities are written as the JSON strings `"NaN"`,
+//! `"Infinity"` and `"-Infinity"`.
+//!
+//! # Example
+//!
+//! ```
+//! use flt2dec2flt::proto3_json::{write, BUFFER_LEN};
+//!
+//! let mut buf = [0; BUFFER_LEN];
+//! assert_eq!(write(&mut buf, 0.1f32), "0.1");
+//! assert_eq!(write(&mut buf, 1e21f64), "1e+21");
+//! assert_eq!(write(&mut buf, 1.5e-7f64), "1.5e-7");
+//! assert_eq!(write(&mut buf, f64::NEG_INFINITY), "\"-Infinity\"");
+//! ```
+
+use crate::generic::abs;
+use crate::write::{write_positional, write_scientific, ExpFormat, WriteOptions};
+use crate::{FloatExt, PreFormatted};
+
+/// Length of the longest string written by `write`.
+///
+/// That is a sign, `0.00000` and 17 digits.
+pub const BUFFER_LEN: usize = 25;
+
+const OPTIONS: WriteOptions<'static> = WriteOptions {
+    exp_format: ExpFormat {
+        plus_sign: true,
+        min_digits: 1,
+    },
+    ..WriteOptions::DEFAULT
+};
+
+/// Writes `v` as the value of a proto3 JSON field.
+///
+/// Panics if `buf` is shorter than `BUFFER_LEN`.
+pub fn write<T: FloatExt>(buf: &mut [u8], v: T) -> &str {
+    let mut digits_buf = [0; crate::PREFORMAT_SHORTEST_BUF_LEN];
+    let preformatted = crate::generic::preformat_shortest_even(v, &mut digits_buf);
+    let s = match preformatted {
+        PreFormatted::NaN => Ok("\"NaN\""),
+        PreFormatted::Inf(false) => Ok("\"Infinity\""),
+        PreFormatted::Inf(true) => Ok("\"-Infinity\""),
+        PreFormatted::Zero(_) => write_positional(buf, preformatted, 0, &OPTIONS),
+        PreFormatted::Finite(..) => {
+            // The thresholds are compared with the value itself rather
+            // than with its shortest representation, like the Go
+            // implementation (which matters for `f32` near `1e21`).
+            let abs = abs(v.to_f64());
+            let (min, max) = if T::BITS == 32 {
+                (f64::from(1e-6f32), f64::from(1e21f32))
+            } else {
+                (1e-6, 1e21)
+            };
+            if abs < min || abs >= max {
+                write_scientific(buf, preformatted, 0, &OPTIONS)
+            } else {
+                write_positional(buf, preformatted, 0, &OPTIONS)
+            }
+        }
+    };
+    s.expect("buffer too small")
+}
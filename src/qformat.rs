@@ -0,0 +1,430 @@
+//! Conversions between fixed-point Q-format numbers and decimal strings.
+//!
+//! A Qm.n number is an integer `q` (`i32`, `i64` or `i128`) representing
+//! `q * 2^-n`, where `n` is the number of fractional bits. Converting
+//! through `f64` is not exact for wide formats (such as Q31.32), so this
+//! module converts directly, with big integers.
+//!
+//! # Example
+//!
+//! ```
+//! use flt2dec2flt::qformat;
+//! use flt2dec2flt::{PreFormatted, PreParsed, RoundingMode};
+//!
+//! let mut buf = [0; qformat::EXACT_BUF_LEN];
+//!
+//! // 0x4000 in Q1.15 is 0.5
+//! let preformatted = qformat::preformat_exact(0x4000i32, 15, &mut buf);
+//! assert_eq!(preformatted, PreFormatted::Finite(false, b"5", 0, 0));
+//!
+//! // 1 in Q0.15 is exactly 0.000030517578125, but 0.00003 identifies it
+//! let preformatted = qformat::preformat_shortest(1i32, 15, &mut buf);
+//! assert_eq!(preformatted, PreFormatted::Finite(false, b"3", 0, -4));
+//!
+//! // 205887 in Q15.16 is 3.1415863037109375
+//! let preformatted = qformat::preformat_exact_fixed(205887i32, 16, &mut buf, 4);
+//! assert_eq!(preformatted, PreFormatted::Finite(false, b"31416", 0, 1));
+//!
+//! let preparsed = PreParsed {
+//!     sign: true,
+//!     int_digits: b"0",
+//!     frac_digits: b"1",
+//!     exp: 0,
+//! };
+//! let q = qformat::from_preparsed::<i64>(preparsed, 32, RoundingMode::NearestEven);
+//! assert_eq!(q, Ok(-429496730));
+//! ```
+
+use crate::bignum::Big32x40;
+use crate::rounding::{round_digits, Remainder};
+use crate::scaled::split_i128;
+use crate::{ParseFloatError, PreFormatted, PreParsed, RoundingMode};
+
+/// Minimum buffer size that has to be passed to the `preformat_*`
+/// functions.
+pub const EXACT_BUF_LEN: usize = 128;
+
+/// Largest supported number of fractional bits.
+pub const MAX_FRAC_BITS: u32 = 128;
+
+/// An integer type that can hold Q-format numbers.
+///
+/// Implemented for `i32`, `i64` and `i128`.
+pub trait QInt: sealed::Sealed {}
+
+impl QInt for i32 {}
+impl QInt for i64 {}
+impl QInt for i128 {}
+
+mod sealed {
+    pub trait Sealed: Copy {
+        fn to_i128(self) -> i128;
+        fn from_i128(v: i128) -> Option<Self>;
+    }
+
+    impl Sealed for i32 {
+        fn to_i128(self) -> i128 {
+            self.into()
+        }
+
+        fn from_i128(v: i128) -> Option<Self> {
+            if v < i128::from(i32::MIN) || v > i128::from(i32::MAX) {
+                None
+            } else {
+                Some(v as i32)
+            }
+        }
+    }
+
+    impl Sealed for i64 {
+        fn to_i128(self) -> i128 {
+            self.into()
+        }
+
+        fn from_i128(v: i128) -> Option<Self> {
+            if v < i128::from(i64::MIN) || v > i128::from(i64::MAX) {
+                None
+            } else {
+                Some(v as i64)
+            }
+        }
+    }
+
+    impl Sealed for i128 {
+        fn to_i128(self) -> i128 {
+            self
+        }
+
+        fn from_i128(v: i128) -> Option<Self> {
+            Some(v)
+        }
+    }
+}
+
+/// Pre-formats the exact value of `q * 2^-frac_bits`, without trailing
+/// zeros.
+///
+/// `buf` must be at least `EXACT_BUF_LEN`.
+///
+/// Panics if `frac_bits` is larger than `MAX_FRAC_BITS`.
+pub fn preformat_exact<T: QInt>(q: T, frac_bits: u32, buf: &mut [u8]) -> PreFormatted<'_> {
+    assert!(frac_bits <= MAX_FRAC_BITS, "too many fractional bits");
+    let (sign, magnitude) = split_i128(q.to_i128());
+    if magnitude == 0 {
+        return PreFormatted::Zero(false);
+    }
+    let (len, exp) = exact_digits(magnitude, frac_bits, buf);
+    PreFormatted::Finite(sign, &buf[..len], 0, exp as i16)
+}
+
+/// Pre-formats `q * 2^-frac_bits` with the fewest digits that convert back
+/// to `q` with `from_preparsed` and `RoundingMode::NearestEven`.
+///
+/// Among the candidates with that many digits, the nearest to the exact
+/// value is chosen.
+///
+/// `buf` must be at least `EXACT_BUF_LEN`.
+///
+/// Panics if `frac_bits` is larger than `MAX_FRAC_BITS`.
+pub fn preformat_shortest<T: QInt>(q: T, frac_bits: u32, buf: &mut [u8]) -> PreFormatted<'_> {
+    assert!(frac_bits <= MAX_FRAC_BITS, "too many fractional bits");
+    let (sign, magnitude) = split_i128(q.to_i128());
+    if magnitude == 0 {
+        return PreFormatted::Zero(false);
+    }
+    // The numbers within half a unit of `q` convert back to it (including
+    // the bounds if `q` is even), that is, `(2q - 1) * 2^-(n+1)` to
+    // `(2q + 1) * 2^-(n+1)`, which are `lo * 10^-(n+1)` and `hi * 10^-(n+1)`.
+    let inclusive = magnitude % 2 == 0;
+    let mut mid = big_from_u128(magnitude);
+    mid.mul_small(2).mul_pow5(frac_bits as usize + 1);
+    let mut half = Big32x40::from_u32(1);
+    half.mul_pow5(frac_bits as usize + 1);
+    let mut lo = mid.clone();
+    lo.sub(&half);
+    let mut hi = mid.clone();
+    hi.add(&half);
+
+    // Finds the largest `k` such that a multiple of `10^k` is within the
+    // interval, keeping `lo`, `hi` and `mid` divided by `10^k` (rounded
+    // down), with the remainders.
+    let mut k = 0;
+    let mut lo_exact = true;
+    let mut hi_exact = true;
+    let mut mid_remainder = Remainder::Zero;
+    let mut candidate = mid.clone();
+    loop {
+        let mut next_lo = lo.clone();
+        let next_lo_exact = next_lo.div_rem_small(10) == 0 && lo_exact;
+        let mut next_hi = hi.clone();
+        let next_hi_exact = next_hi.div_rem_small(10) == 0 && hi_exact;
+        let min = bound_above(next_lo.clone(), next_lo_exact, inclusive);
+        let max = bound_below(next_hi.clone(), next_hi_exact, inclusive);
+        if min > max {
+            break;
+        }
+        let mut next_mid = mid.clone();
+        let digit = next_mid.div_rem_small(10);
+        mid_remainder = match digit {
+            0 if mid_remainder == Remainder::Zero => Remainder::Zero,
+            0..=4 => Remainder::BelowHalf,
+            5 if mid_remainder == Remainder::Zero => Remainder::Half,
+            _ => Remainder::AboveHalf,
+        };
+
+        let odd = next_mid.get_bit(0);
+        candidate = next_mid.clone();
+        if RoundingMode::NearestEven.round_up(false, odd, mid_remainder) {
+            candidate.add_small(1);
+        }
+        if candidate < min {
+            candidate = min;
+        } else if candidate > max {
+            candidate = max;
+        }
+
+        lo = next_lo;
+        lo_exact = next_lo_exact;
+        hi = next_hi;
+        hi_exact = next_hi_exact;
+        mid = next_mid;
+        k += 1;
+    }
+    finish(sign, &candidate, k - (frac_bits as i32 + 1), buf)
+}
+
+/// Pre-formats `q * 2^-frac_bits` with an exact number of significant
+/// digits, rounding half to even, like `FloatExt::preformat_exact_exp`.
+///
+/// Trailing zeros are given as the number of zeros of `PreFormatted`.
+/// `buf` must be at least `EXACT_BUF_LEN`, and `num_digits` must not be
+/// zero.
+///
+/// Panics if `frac_bits` is larger than `MAX_FRAC_BITS`.
+pub fn preformat_exact_exp<T: QInt>(
+    q: T,
+    frac_bits: u32,
+    buf: &mut [u8],
+    num_digits: usize,
+) -> PreFormatted<'_> {
+    assert!(frac_bits <= MAX_FRAC_BITS, "too many fractional bits");
+    assert!(num_digits > 0, "num_digits must not be zero");
+    let (sign, magnitude) = split_i128(q.to_i128());
+    if magnitude == 0 {
+        return PreFormatted::Zero(false);
+    }
+    let (len, exp) = exact_digits(magnitude, frac_bits, buf);
+    let keep = num_digits.min(len) as i32;
+    let (len, exp) = round_digits(&mut buf[..len], exp, keep, sign, RoundingMode::NearestEven);
+    PreFormatted::Finite(sign, &buf[..len], num_digits - len, exp as i16)
+}
+
+/// Pre-formats `q * 2^-frac_bits` with an exact number of fractional
+/// digits (which can be negative), rounding half to even, like
+/// `FloatExt::preformat_exact_fixed`.
+///
+/// As with floats, a tie is rounded up when no digit is kept (0.5 gives 1
+/// with no fractional digits), and a negative `num_frac_digits` gives the
+/// zeros down to the units (123456 gives 123000 with -3).
+///
+/// Trailing zeros are given as the number of zeros of `PreFormatted`.
+/// `buf` must be at least `EXACT_BUF_LEN`, whatever `num_frac_digits` is.
+///
+/// Panics if `frac_bits` is larger than `MAX_FRAC_BITS`.
+pub fn preformat_exact_fixed<T: QInt>(
+    q: T,
+    frac_bits: u32,
+    buf: &mut [u8],
+    num_frac_digits: isize,
+) -> PreFormatted<'_> {
+    assert!(frac_bits <= MAX_FRAC_BITS, "too many fractional bits");
+    let (sign, magnitude) = split_i128(q.to_i128());
+    if magnitude == 0 {
+        return PreFormatted::Zero(false);
+    }
+    let (len, exp) = exact_digits(magnitude, frac_bits, buf);
+    // All the digits above `10^-num_frac_digits`.
+    let keep = (exp as isize).saturating_add(num_frac_digits);
+    let keep = keep.min(len as isize).max(-1) as i32;
+    // Like Dragon, a tie with no kept digit is rounded up.
+    let mode = if keep == 0 {
+        RoundingMode::NearestAway
+    } else {
+        RoundingMode::NearestEven
+    };
+    let (len, exp) = round_digits(&mut buf[..len], exp, keep, sign, mode);
+    if len == 0 {
+        return PreFormatted::Zero(sign);
+    }
+    // Down to the units when rounding to the left of the decimal point.
+    let num_digits = num_frac_digits.max(0).saturating_add(exp as isize) as usize;
+    PreFormatted::Finite(sign, &buf[..len], num_digits - len, exp as i16)
+}
+
+/// Converts `preparsed` into a Q-format number with `frac_bits`
+/// fractional bits, rounding with `mode`.
+///
+/// Fails with `ParseFloatError::ExponentOutOfRange` if the rounded value
+/// does not fit in `T`.
+///
+/// Panics if `frac_bits` is larger than `MAX_FRAC_BITS`.
+pub fn from_preparsed<T: QInt>(
+    preparsed: PreParsed<'_>,
+    frac_bits: u32,
+    mode: RoundingMode,
+) -> Result<T, ParseFloatError> {
+    assert!(frac_bits <= MAX_FRAC_BITS, "too many fractional bits");
+    if preparsed.int_digits.is_empty() && preparsed.frac_digits.is_empty() {
+        return Err(ParseFloatError::EmptyDigits);
+    }
+    let all_digits = || preparsed.int_digits.iter().chain(preparsed.frac_digits);
+    if let Some((index, &byte)) = all_digits().enumerate().find(|(_, c)| !c.is_ascii_digit()) {
+        return Err(ParseFloatError::InvalidDigit { byte, index });
+    }
+
+    // The value is `digits * 10^scale`. Only the first `MAX_DIGITS`
+    // significant digits are kept, the rest only matter for rounding.
+    const MAX_DIGITS: usize = 200;
+    let num_digits = preparsed.int_digits.len() + preparsed.frac_digits.len();
+    let num_leading_zeros = all_digits().take_while(|&&c| c == b'0').count();
+    let num_sig_digits = num_digits - num_leading_zeros;
+    let num_kept_digits = num_sig_digits.min(MAX_DIGITS);
+    let mut digits = Big32x40::zero();
+    for &c in all_digits().skip(num_leading_zeros).take(num_kept_digits) {
+        digits.mul_small(10).add_small(u32::from(c - b'0'));
+    }
+    let sticky = all_digits()
+        .skip(num_leading_zeros + num_kept_digits)
+        .any(|&c| c != b'0');
+    let scale = i64::from(preparsed.exp) - preparsed.frac_digits.len() as i64
+        + (num_sig_digits - num_kept_digits) as i64;
+
+    // The value is below `10^magnitude`.
+    let magnitude = num_kept_digits as i64 + scale;
+    let (q, remainder) = if digits.is_zero() {
+        (Big32x40::zero(), Remainder::Zero)
+    } else if magnitude > 40 {
+        // At least 10^40, which does not fit even without fractional bits.
+        return Err(ParseFloatError::ExponentOutOfRange);
+    } else if magnitude < -60 {
+        // Below 10^-60 * 2^128, less than half a unit.
+        (Big32x40::zero(), Remainder::BelowHalf)
+    } else if scale >= 0 {
+        // `sticky` is false, since all the digits are kept.
+        digits
+            .mul_pow10(scale as usize)
+            .mul_pow2(frac_bits as usize);
+        (digits, Remainder::Zero)
+    } else {
+        // digits * 2^n / 10^-scale
+        digits.mul_pow2(frac_bits as usize);
+        let mut divisor = Big32x40::from_u32(1);
+        divisor.mul_pow10(-scale as usize);
+        let (q, r) = digits.div_rem(&divisor);
+        let mut twice_r = r.clone();
+        twice_r.mul_small(2);
+        // When digits have been dropped, `-scale` is larger than the
+        // number of fractional bits, so `r` and `divisor / 2` are
+        // multiples of `2^n` and the dropped digits (less than `2^n`
+        // after scaling) cannot move `r` beyond `divisor / 2`.
+        let remainder = if r.is_zero() && !sticky {
+            Remainder::Zero
+        } else if twice_r < divisor {
+            Remainder::BelowHalf
+        } else if twice_r == divisor && !sticky {
+            Remainder::Half
+        } else {
+            Remainder::AboveHalf
+        };
+        (q, remainder)
+    };
+
+    let mut q = q;
+    if mode.round_up(preparsed.sign, q.get_bit(0), remainder) {
+        q.add_small(1);
+    }
+    if q.bit_length() > 128 {
+        return Err(ParseFloatError::ExponentOutOfRange);
+    }
+    let q = q
+        .digits()
+        .iter()
+        .take(4)
+        .rev()
+        .fold(0u128, |acc, &d| acc << 32 | u128::from(d));
+    let q = if preparsed.sign {
+        if q > 1 << 127 {
+            return Err(ParseFloatError::ExponentOutOfRange);
+        }
+        (q as i128).wrapping_neg()
+    } else {
+        if q > i128::MAX as u128 {
+            return Err(ParseFloatError::ExponentOutOfRange);
+        }
+        q as i128
+    };
+    T::from_i128(q).ok_or(ParseFloatError::ExponentOutOfRange)
+}
+
+fn big_from_u128(v: u128) -> Big32x40 {
+    Big32x40::from_digits(&[
+        v as u32,
+        (v >> 32) as u32,
+        (v >> 64) as u32,
+        (v >> 96) as u32,
+    ])
+    .unwrap()
+}
+
+/// The smallest integer above `x` (or at `x` if `exact` and `inclusive`),
+/// where `x` is `floor_x` or, if not `exact`, a bit more.
+fn bound_above(mut floor_x: Big32x40, exact: bool, inclusive: bool) -> Big32x40 {
+    if !(exact && inclusive) {
+        floor_x.add_small(1);
+    }
+    floor_x
+}
+
+/// The largest integer below `x` (or at `x` if `exact` and `inclusive`),
+/// where `x` is `floor_x` or, if not `exact`, a bit more.
+fn bound_below(mut floor_x: Big32x40, exact: bool, inclusive: bool) -> Big32x40 {
+    if exact && !inclusive {
+        if floor_x.is_zero() {
+            // No non-negative integer, returns something below any
+            // `bound_above`.
+            return floor_x;
+        }
+        floor_x.sub(&Big32x40::from_u32(1));
+    }
+    floor_x
+}
+
+/// Writes `x` (not zero) into `buf`, without trailing zeros.
+///
+/// Returns the number of written digits and the number of digits of `x`.
+fn write_digits(x: &Big32x40, buf: &mut [u8]) -> (usize, i32) {
+    let len = x.to_decimal(buf).len();
+    let trimmed_len = buf[..len].iter().rposition(|&c| c != b'0').unwrap() + 1;
+    (trimmed_len, len as i32)
+}
+
+/// Writes `sign x * 10^exp` (with `x` not zero) into `buf`, without
+/// trailing zeros.
+fn finish<'a>(sign: bool, x: &Big32x40, exp: i32, buf: &'a mut [u8]) -> PreFormatted<'a> {
+    let (len, x_len) = write_digits(x, buf);
+    // 0.digits * 10^(exp + x_len)
+    PreFormatted::Finite(sign, &buf[..len], 0, (exp + x_len) as i16)
+}
+
+/// Writes the exact digits of `magnitude * 2^-frac_bits` (not zero) into
+/// `buf`, without trailing zeros.
+///
+/// Returns the number of written digits and the exponent `exp` of the
+/// value `0.digits * 10^exp`.
+fn exact_digits(magnitude: u128, frac_bits: u32, buf: &mut [u8]) -> (usize, i32) {
+    // q * 2^-n = q * 5^n * 10^-n
+    let mut x = big_from_u128(magnitude);
+    x.mul_pow5(frac_bits as usize);
+    let (len, x_len) = write_digits(&x, buf);
+    (len, x_len - frac_bits as i32)
+}
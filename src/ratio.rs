@@ -0,0 +1,57 @@
+//! Correctly rounded conversion of big rationals into floating point
+//! numbers.
+
+use core::cmp::Ordering;
+
+use ::num_bigint::BigUint;
+
+use crate::generic::compose;
+use crate::sealed::Sealed;
+
+/// Returns `sign num / den` rounded to nearest (ties to even).
+///
+/// `den` must not be zero.
+pub(crate) fn ratio_to_float<T: Sealed>(sign: bool, num: &BigUint, den: &BigUint) -> T {
+    assert!(den.bits() != 0);
+    if num.bits() == 0 {
+        return compose(sign, 0, T::MIN_LSB_EXP);
+    }
+
+    // num / den is within [2^(e - 1), 2^(e + 1))
+    let e = num.bits() as i64 - den.bits() as i64;
+    if e - 1 > i64::from(T::MAX_EXP) {
+        return if sign { T::NEG_INFINITY } else { T::INFINITY };
+    }
+    if e + 1 < i64::from(T::MIN_LSB_EXP) - 1 {
+        // Less than half the smallest subnormal.
+        return compose(sign, 0, T::MIN_LSB_EXP);
+    }
+
+    let sig_bits = u64::from(T::SIG_BITS);
+    let mut k = (e as i32 - T::SIG_BITS as i32).max(T::MIN_LSB_EXP);
+    loop {
+        // q = floor(num / (den * 2^k)), which has at most `SIG_BITS + 1` bits
+        let (n, d) = if k < 0 {
+            (num << (-k) as usize, den.clone())
+        } else {
+            (num.clone(), den << k as usize)
+        };
+        let q = &n / &d;
+        if q.bits() > sig_bits {
+            k += 1;
+            continue;
+        }
+        let r = n - &q * &d;
+        let mut q = q.iter_u64_digits().next().unwrap_or(0);
+        match (r << 1usize).cmp(&d) {
+            Ordering::Less => {}
+            Ordering::Equal => q += q & 1,
+            Ordering::Greater => q += 1,
+        }
+        if q == 1 << T::SIG_BITS {
+            q >>= 1;
+            k += 1;
+        }
+        return compose(sign, q, k);
+    }
+}
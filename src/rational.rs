@@ -0,0 +1,37 @@
+//! Exact conversions between floating point numbers and
+//! `num_rational::BigRational`.
+
+use core::num::FpCategory;
+
+use ::num_bigint::{BigInt, Sign};
+use ::num_rational::BigRational;
+
+use crate::core_num::dec2flt::float::RawFloat;
+use crate::ratio::ratio_to_float;
+use crate::sealed::Sealed;
+
+pub(crate) fn to_exact_rational<T: RawFloat>(v: T) -> Option<BigRational> {
+    match v.classify() {
+        FpCategory::Nan | FpCategory::Infinite => None,
+        FpCategory::Zero => Some(BigRational::from_integer(BigInt::from(0))),
+        FpCategory::Subnormal | FpCategory::Normal => {
+            let (mant, exp, sign) = v.integer_decode();
+            let mut mant = BigInt::from(mant);
+            if sign < 0 {
+                mant = -mant;
+            }
+            let one = BigInt::from(1);
+            // mant * 2^exp, reduced by `BigRational::new`
+            Some(if exp >= 0 {
+                BigRational::from_integer(mant << exp as usize)
+            } else {
+                BigRational::new(mant, one << (-i32::from(exp)) as usize)
+            })
+        }
+    }
+}
+
+pub(crate) fn from_rational<T: Sealed>(v: &BigRational) -> T {
+    let sign = (v.numer().sign() == Sign::Minus) != (v.denom().sign() == Sign::Minus);
+    ratio_to_float(sign, v.numer().magnitude(), v.denom().magnitude())
+}
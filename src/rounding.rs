@@ -0,0 +1,238 @@
+use core::cmp::Ordering;
+
+use crate::clamp::cmp_exact;
+use crate::{FloatExt, ParseFloatError, PreParsed};
+
+/// How a number is rounded when it cannot be represented exactly.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum RoundingMode {
+    /// To the nearest value, choosing the even one on ties.
+    NearestEven,
+    /// To the nearest value, choosing the one with the larger magnitude
+    /// on ties.
+    NearestAway,
+    /// Toward zero (truncation).
+    TowardZero,
+    /// Toward negative infinity (floor).
+    Down,
+    /// Toward positive infinity (ceiling).
+    Up,
+    /// To the nearest value, choosing the one with an odd last digit (or
+    /// significand) on ties.
+    NearestOdd,
+    /// To the neighbor with an odd last digit (or significand) when not
+    /// exact (round to odd).
+    ///
+    /// A number rounded to odd with two or more bits than a narrower
+    /// format is rounded to that format like the exact number, so parsing
+    /// into `f64` this way and converting with `as f32` does not round
+    /// twice.
+    ToOdd,
+}
+
+impl Default for RoundingMode {
+    fn default() -> Self {
+        RoundingMode::NearestEven
+    }
+}
+
+/// How a parsed number compares with the exact value of its text, as
+/// reported by `FloatExt::from_preparsed_report`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Exactness {
+    /// The number is exactly the value of the text.
+    Exact,
+    /// The number is below the value of the text (toward negative
+    /// infinity).
+    RoundedDown,
+    /// The number is above the value of the text (toward positive
+    /// infinity).
+    RoundedUp,
+}
+
+/// What becomes of the numbers that round to a subnormal, as done by
+/// `FloatExt::from_preparsed_subnormals`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum SubnormalPolicy {
+    /// They are kept (gradual underflow).
+    Gradual,
+    /// They become zero, keeping their sign.
+    FlushToZero,
+    /// They become zero or the smallest normal number, whichever is
+    /// nearer to the exact value (zero on ties).
+    RoundToNormal,
+}
+
+impl Default for SubnormalPolicy {
+    fn default() -> Self {
+        SubnormalPolicy::Gradual
+    }
+}
+
+/// Implementation of `FloatExt::from_preparsed_report`.
+pub(crate) fn from_preparsed_report<T: FloatExt>(
+    preparsed: PreParsed<'_>,
+) -> Result<(T, Exactness), ParseFloatError> {
+    let value = T::from_preparsed(preparsed)?;
+    let exactness = match cmp_exact(&preparsed, value) {
+        Some(Ordering::Equal) => Exactness::Exact,
+        Some(Ordering::Less) => Exactness::RoundedUp,
+        Some(Ordering::Greater) => Exactness::RoundedDown,
+        // Overflow to infinity.
+        None if preparsed.sign => Exactness::RoundedDown,
+        None => Exactness::RoundedUp,
+    };
+    Ok((value, exactness))
+}
+
+/// Implementation of `FloatExt::from_preparsed_interval`.
+pub(crate) fn from_preparsed_interval<T: FloatExt>(
+    preparsed: PreParsed<'_>,
+) -> Result<(T, T), ParseFloatError> {
+    let (value, exactness) = from_preparsed_report::<T>(preparsed)?;
+    let bits = value.to_bits_u64();
+    let sign_bit = 1 << (T::BITS - 1);
+    let neighbor = |up: bool| {
+        let bits = if bits & !sign_bit == 0 {
+            // The smallest subnormal of the sign of the direction.
+            if up {
+                1
+            } else {
+                sign_bit | 1
+            }
+        } else if (bits & sign_bit == 0) == up {
+            bits + 1
+        } else {
+            bits - 1
+        };
+        T::from_bits_u64(bits)
+    };
+    Ok(match exactness {
+        Exactness::Exact => (value, value),
+        Exactness::RoundedDown => (value, neighbor(true)),
+        Exactness::RoundedUp => (neighbor(false), value),
+    })
+}
+
+/// Implementation of `FloatExt::from_preparsed_subnormals`.
+pub(crate) fn from_preparsed_subnormals<T: FloatExt>(
+    preparsed: PreParsed<'_>,
+    policy: SubnormalPolicy,
+) -> Result<T, ParseFloatError> {
+    let value = T::from_preparsed(preparsed)?;
+    let bits = value.to_bits_u64();
+    let sign_bit = 1 << (T::BITS - 1);
+    let min_normal = 1 << (T::SIG_BITS - 1);
+    let magnitude = bits & !sign_bit;
+    if magnitude == 0 || magnitude >= min_normal {
+        return Ok(value);
+    }
+    Ok(match policy {
+        SubnormalPolicy::Gradual => value,
+        SubnormalPolicy::FlushToZero => T::from_bits_u64(bits & sign_bit),
+        SubnormalPolicy::RoundToNormal => {
+            let half_min_normal = T::from_bits_u64(min_normal >> 1).to_f64();
+            let abs = PreParsed {
+                sign: false,
+                ..preparsed
+            };
+            if cmp_exact(&abs, half_min_normal) == Some(Ordering::Greater) {
+                T::from_bits_u64(bits & sign_bit | min_normal)
+            } else {
+                T::from_bits_u64(bits & sign_bit)
+            }
+        }
+    })
+}
+
+/// The part of a number dropped by rounding, relative to one unit of the
+/// last kept digit.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Remainder {
+    Zero,
+    BelowHalf,
+    Half,
+    AboveHalf,
+}
+
+impl Remainder {
+    /// Classifies the dropped decimal digits `first` (followed by `rest`).
+    pub(crate) fn from_digits(first: u8, rest: &[u8]) -> Self {
+        let rest_nonzero = rest.iter().any(|&c| c != b'0');
+        match first {
+            b'0' if !rest_nonzero => Remainder::Zero,
+            b'5' if !rest_nonzero => Remainder::Half,
+            b'0'..=b'4' => Remainder::BelowHalf,
+            _ => Remainder::AboveHalf,
+        }
+    }
+}
+
+impl RoundingMode {
+    /// Whether the magnitude of a number has to be incremented by one unit
+    /// of its last kept digit, which is odd if `odd`.
+    pub(crate) fn round_up(self, sign: bool, odd: bool, remainder: Remainder) -> bool {
+        if remainder == Remainder::Zero {
+            return false;
+        }
+        match self {
+            RoundingMode::NearestEven => {
+                remainder == Remainder::AboveHalf || (remainder == Remainder::Half && odd)
+            }
+            RoundingMode::NearestAway => remainder != Remainder::BelowHalf,
+            RoundingMode::TowardZero => false,
+            RoundingMode::Down => sign,
+            RoundingMode::Up => !sign,
+            RoundingMode::NearestOdd => {
+                remainder == Remainder::AboveHalf || (remainder == Remainder::Half && !odd)
+            }
+            RoundingMode::ToOdd => !odd,
+        }
+    }
+}
+
+/// Rounds `sign 0.digits * 10^exp` (the exact value, with at least one
+/// digit) to its first `keep` digits with `mode`, in place.
+///
+/// Returns the number of kept digits (0 when the result is zero) and the
+/// new exponent, which only changes when the rounding carries into a new
+/// leading digit.
+pub(crate) fn round_digits(
+    digits: &mut [u8],
+    exp: i32,
+    keep: i32,
+    sign: bool,
+    mode: RoundingMode,
+) -> (usize, i32) {
+    if keep >= digits.len() as i32 {
+        return (digits.len(), exp);
+    }
+    let (remainder, odd) = if keep < 0 {
+        (Remainder::BelowHalf, false)
+    } else {
+        let keep = keep as usize;
+        let remainder = Remainder::from_digits(digits[keep], &digits[keep + 1..]);
+        let odd = keep > 0 && (digits[keep - 1] - b'0') % 2 == 1;
+        (remainder, odd)
+    };
+    if !mode.round_up(sign, odd, remainder) {
+        return (keep.max(0) as usize, exp);
+    }
+    if keep <= 0 {
+        // One unit at the last kept position.
+        digits[0] = b'1';
+        return (1, exp - keep + 1);
+    }
+    let keep = keep as usize;
+    match digits[..keep].iter().rposition(|&c| c != b'9') {
+        Some(i) => {
+            digits[i] += 1;
+            (i + 1, exp)
+        }
+        None => {
+            digits[0] = b'1';
+            (1, exp + 1)
+        }
+    }
+}
@@ -0,0 +1,86 @@
+//! A façade mirroring the API of `ryu`.
+//!
+//! Code written against `ryu::Buffer` can use `ryu_compat::Buffer` instead,
+//! and produces the same output: `1.0`, `0.001`, `1.5e-7`, `1e30`, `NaN`,
+//! `inf`. The only difference is when two shortest representations are
+//! equally close to the value, where this crate picks the one with the
+//! larger magnitude and `ryu` the even one, unless the `strategy-ryu` or
+//! `strategy-dragonbox` feature is enabled.
+//!
+//! # Example
+//!
+//! ```
+//! use flt2dec2flt::ryu_compat as ryu;
+//!
+//! let mut buffer = ryu::Buffer::new();
+//! assert_eq!(buffer.format(1.234f64), "1.234");
+//! assert_eq!(buffer.format(1e30f64), "1e30");
+//! assert_eq!(buffer.format(2.0f32), "2.0");
+//! assert_eq!(buffer.format(f64::NAN), "NaN");
+//! ```
+
+use crate::write::{write_positional, write_scientific, WriteOptions};
+use crate::{FloatExt, PreFormatted};
+
+/// Length of the longest string written by `Buffer`.
+const BUFFER_LEN: usize = 24;
+
+/// A float that can be written by `Buffer`.
+pub trait Float: FloatExt {
+    /// Decimal point positions (the exponent of `PreFormatted`) written in
+    /// positional notation.
+    #[doc(hidden)]
+    const POSITIONAL_EXPS: (i16, i16);
+}
+
+impl Float for f32 {
+    const POSITIONAL_EXPS: (i16, i16) = (-5, 13);
+}
+
+impl Float for f64 {
+    const POSITIONAL_EXPS: (i16, i16) = (-4, 16);
+}
+
+/// A buffer that floats are written into.
+#[derive(Copy, Clone)]
+pub struct Buffer {
+    bytes: [u8; BUFFER_LEN],
+}
+
+impl Buffer {
+    /// Creates a new buffer.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            bytes: [0; BUFFER_LEN],
+        }
+    }
+
+    /// Writes `f` with the shortest representation that round-trips.
+    ///
+    /// NaN and infinities are written as `NaN`, `inf` and `-inf`.
+    pub fn format<F: Float>(&mut self, f: F) -> &str {
+        let mut digits_buf = [0; crate::PREFORMAT_SHORTEST_BUF_LEN];
+        let preformatted = f.preformat_shortest(&mut digits_buf);
+        let options = &WriteOptions::DEFAULT;
+        let (min_exp, max_exp) = F::POSITIONAL_EXPS;
+        let s = match preformatted {
+            PreFormatted::Finite(_, _, _, exp) if exp < min_exp || exp > max_exp => {
+                write_scientific(&mut self.bytes, preformatted, 0, options)
+            }
+            _ => write_positional(&mut self.bytes, preformatted, 1, options),
+        };
+        s.unwrap()
+    }
+
+    /// Same as `format`, which `ryu` only provides for finite numbers.
+    pub fn format_finite<F: Float>(&mut self, f: F) -> &str {
+        self.format(f)
+    }
+}
+
+impl Default for Buffer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
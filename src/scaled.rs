@@ -0,0 +1,111 @@
+//! Conversions between floating point numbers and scaled integers, whose
+//! value is `magnitude * 10^-scale`.
+
+use core::cmp::Ordering;
+
+use crate::rounding::Remainder;
+use crate::{FloatExt, PreFormatted, PreParsed, RoundingMode};
+
+/// Rounds `v * 10^scale` to an integer with `mode`, returning its sign and
+/// magnitude.
+///
+/// Returns `None` if `v` is NaN or infinite, or if the magnitude does not
+/// fit in a `u128`.
+pub(crate) fn to_scaled<T: FloatExt>(v: T, scale: i32, mode: RoundingMode) -> Option<(bool, u128)> {
+    let mut buf = [0; crate::PREFORMAT_EXACT_FIXED_BASE_BUF_LEN];
+    let (sign, digits, exp) = match exact_expansion(v, &mut buf) {
+        PreFormatted::NaN | PreFormatted::Inf(_) => return None,
+        PreFormatted::Zero(sign) => return Some((sign, 0)),
+        PreFormatted::Finite(sign, digits, _, exp) => (sign, digits, exp),
+    };
+
+    // 0.digits * 10^(exp + scale)
+    let int_len = i32::from(exp) + scale;
+    let (mut magnitude, first_dropped, rest) = match int_len.cmp(&0) {
+        Ordering::Less => (0, b'0', digits),
+        Ordering::Equal => (0, digits[0], &digits[1..]),
+        Ordering::Greater => {
+            let int_len = int_len as usize;
+            // At most 39 digits fit in a `u128`.
+            if int_len > 39 {
+                return None;
+            }
+            let mut magnitude = 0u128;
+            for i in 0..int_len {
+                let d = digits.get(i).map_or(0, |&c| c - b'0');
+                magnitude = magnitude.checked_mul(10)?.checked_add(u128::from(d))?;
+            }
+            match digits.get(int_len..) {
+                Some(dropped) if !dropped.is_empty() => (magnitude, dropped[0], &dropped[1..]),
+                _ => (magnitude, b'0', &b""[..]),
+            }
+        }
+    };
+
+    let remainder = Remainder::from_digits(first_dropped, rest);
+    if mode.round_up(sign, magnitude % 2 == 1, remainder) {
+        magnitude = magnitude.checked_add(1)?;
+    }
+    Some((sign, magnitude))
+}
+
+/// Converts `magnitude * 10^-scale` into the nearest floating point number
+/// (rounding half to even).
+pub(crate) fn from_scaled<T: FloatExt>(sign: bool, magnitude: u128, scale: i64) -> T {
+    let mut buf = [0; 39];
+    let digits = write_u128(&mut buf, magnitude);
+    // `0.digits * 10^exp`, so the exponent reflects the magnitude.
+    let exp = (digits.len() as i64).saturating_sub(scale);
+    if magnitude == 0 || exp < -400 {
+        return T::from_bits_u64(u64::from(sign) << (T::BITS - 1));
+    } else if exp > 400 {
+        return if sign { T::NEG_INFINITY } else { T::INFINITY };
+    }
+    let preparsed = PreParsed {
+        sign,
+        int_digits: b"",
+        frac_digits: digits,
+        exp: exp as i32,
+    };
+    // At most 39 digits and a small exponent, which are always handled.
+    T::from_preparsed(preparsed).unwrap()
+}
+
+/// Pre-formats the exact decimal expansion of `v`, without trailing
+/// zeros.
+pub(crate) fn exact_expansion<T: FloatExt>(
+    v: T,
+    buf: &mut [u8; crate::PREFORMAT_EXACT_FIXED_BASE_BUF_LEN],
+) -> PreFormatted<'_> {
+    // Enough to hold every significant digit of any `f32` or `f64`.
+    match v.preformat_exact_exp(buf, crate::PREFORMAT_EXACT_FIXED_BASE_BUF_LEN) {
+        PreFormatted::Finite(sign, digits, _, exp) => {
+            let len = digits.iter().rposition(|&c| c != b'0').map_or(0, |i| i + 1);
+            PreFormatted::Finite(sign, &digits[..len], 0, exp)
+        }
+        other => other,
+    }
+}
+
+/// Writes the decimal digits of `v` at the end of `buf`.
+pub(crate) fn write_u128(buf: &mut [u8; 39], mut v: u128) -> &[u8] {
+    let mut i = buf.len();
+    loop {
+        i -= 1;
+        buf[i] = b'0' + (v % 10) as u8;
+        v /= 10;
+        if v == 0 {
+            break;
+        }
+    }
+    &buf[i..]
+}
+
+/// Splits `v` into its sign and magnitude.
+pub(crate) fn split_i128(v: i128) -> (bool, u128) {
+    if v < 0 {
+        (true, (v as u128).wrapping_neg())
+    } else {
+        (false, v as u128)
+    }
+}
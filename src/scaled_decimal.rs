@@ -0,0 +1,253 @@
+//! Decimal fixed-point numbers, without floating point.
+//!
+//! A `ScaledDecimal` is an `i128` mantissa with a `u8` scale, representing
+//! `mantissa * 10^-scale`, such as `12345` with scale `2` for `123.45`.
+//! They are written with the spellings of `WriteOptions` (and optionally
+//! with grouped integer digits) and parsed with a `Grammar`, rounding with
+//! a `RoundingMode` the digits beyond the scale.
+//!
+//! # Example
+//!
+//! ```
+//! use flt2dec2flt::grammar::Grammar;
+//! use flt2dec2flt::scaled_decimal::ScaledDecimal;
+//! use flt2dec2flt::write::WriteOptions;
+//! use flt2dec2flt::RoundingMode;
+//!
+//! let mut buf = [0; 64];
+//!
+//! let price = ScaledDecimal::new(-123456789, 2);
+//! assert_eq!(price.write(&mut buf, Some(","), &WriteOptions::DEFAULT), Ok("-1,234,567.89"));
+//!
+//! let options = WriteOptions {
+//!     decimal_point: ",",
+//!     ..WriteOptions::DEFAULT
+//! };
+//! let price = ScaledDecimal::new(5, 3);
+//! assert_eq!(price.write(&mut buf, Some("."), &options), Ok("0,005"));
+//!
+//! let parsed = ScaledDecimal::parse(&Grammar::RUST, b"2.675", 2, RoundingMode::NearestEven);
+//! assert_eq!(parsed, Ok(ScaledDecimal::new(268, 2)));
+//! let parsed = ScaledDecimal::parse(&Grammar::RUST, b"-1e-9", 4, RoundingMode::Down);
+//! assert_eq!(parsed, Ok(ScaledDecimal::new(-1, 4)));
+//! ```
+
+use core::cmp::Ordering;
+use core::fmt;
+
+use crate::grammar::{Grammar, Parsed};
+use crate::rounding::Remainder;
+use crate::scaled::{split_i128, write_u128};
+use crate::write::{Cursor, Output, WriteOptions};
+use crate::{BufferTooSmall, ParseFloatError, PreFormatted, PreParsed, RoundingMode, SyntaxError};
+
+/// Minimum buffer size that has to be passed to `ScaledDecimal::preformat`.
+pub const PREFORMAT_BUF_LEN: usize = 39;
+
+/// A decimal number `mantissa * 10^-scale`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ScaledDecimal {
+    /// The digits of the number, as an integer.
+    pub mantissa: i128,
+    /// The number of fractional digits.
+    pub scale: u8,
+}
+
+/// An error returned by `ScaledDecimal::parse`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ParseError {
+    /// The text is not a number.
+    Syntax(SyntaxError),
+    /// The number is NaN, infinite or hexadecimal.
+    NotDecimal,
+    /// The number could not be converted (see
+    /// `ScaledDecimal::from_preparsed`).
+    Decimal(ParseFloatError),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            ParseError::Syntax(ref e) => e.fmt(f),
+            ParseError::NotDecimal => f.write_str("not a decimal number"),
+            ParseError::Decimal(ref e) => e.fmt(f),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseError {}
+
+impl ScaledDecimal {
+    /// Makes a `ScaledDecimal` representing `mantissa * 10^-scale`.
+    pub const fn new(mantissa: i128, scale: u8) -> Self {
+        Self { mantissa, scale }
+    }
+
+    /// Pre-formats the number, without trailing zeros.
+    ///
+    /// `buf` must be at least `PREFORMAT_BUF_LEN`.
+    pub fn preformat<'a>(&self, buf: &'a mut [u8]) -> PreFormatted<'a> {
+        let (sign, magnitude) = split_i128(self.mantissa);
+        if magnitude == 0 {
+            return PreFormatted::Zero(false);
+        }
+        let mut tmp = [0; 39];
+        let digits = write_u128(&mut tmp, magnitude);
+        let len = digits.iter().rposition(|&c| c != b'0').unwrap() + 1;
+        buf[..len].copy_from_slice(&digits[..len]);
+        let exp = digits.len() as i16 - i16::from(self.scale);
+        PreFormatted::Finite(sign, &buf[..len], 0, exp)
+    }
+
+    /// Writes the number in positional notation with exactly `scale`
+    /// fractional digits, such as `1234.50`.
+    ///
+    /// If `group_separator` is given, it is written between groups of
+    /// three integer digits, such as `1,234.50`.
+    pub fn write<'a>(
+        &self,
+        buf: &'a mut [u8],
+        group_separator: Option<&str>,
+        options: &WriteOptions<'_>,
+    ) -> Result<&'a str, BufferTooSmall> {
+        let (sign, magnitude) = split_i128(self.mantissa);
+        let mut tmp = [0; 39];
+        let digits = write_u128(&mut tmp, magnitude);
+        let scale = usize::from(self.scale);
+        let (int_digits, frac_zeros, frac_digits) = if digits.len() > scale {
+            let (int_digits, frac_digits) = digits.split_at(digits.len() - scale);
+            (int_digits, 0, frac_digits)
+        } else {
+            (&b"0"[..], scale - digits.len(), digits)
+        };
+
+        let mut cursor = Cursor::new(buf);
+        cursor.push_str(if sign {
+            options.minus_sign
+        } else {
+            options.plus_sign
+        });
+        for (i, &d) in int_digits.iter().enumerate() {
+            let remaining = int_digits.len() - i;
+            if let Some(separator) = group_separator {
+                if i != 0 && remaining % 3 == 0 {
+                    cursor.push_str(separator);
+                }
+            }
+            cursor.push(d);
+        }
+        if scale != 0 {
+            cursor.push_str(options.decimal_point);
+            cursor.push_zeros(frac_zeros);
+            cursor.push_bytes(frac_digits);
+        }
+        cursor.finish()
+    }
+
+    /// Converts `preparsed` into a `ScaledDecimal` with `scale` fractional
+    /// digits, rounding with `mode`.
+    ///
+    /// Fails with `ParseFloatError::ExponentOutOfRange` if the mantissa
+    /// does not fit in an `i128`.
+    pub fn from_preparsed(
+        preparsed: PreParsed<'_>,
+        scale: u8,
+        mode: RoundingMode,
+    ) -> Result<Self, ParseFloatError> {
+        if preparsed.int_digits.is_empty() && preparsed.frac_digits.is_empty() {
+            return Err(ParseFloatError::EmptyDigits);
+        }
+        let all_digits = preparsed.int_digits.iter().chain(preparsed.frac_digits);
+        if let Some((index, &byte)) = all_digits.enumerate().find(|(_, c)| !c.is_ascii_digit()) {
+            return Err(ParseFloatError::InvalidDigit { byte, index });
+        }
+        from_digits(preparsed, |_| true, scale, mode)
+    }
+
+    /// Parses `s` with `grammar`, rounding with `mode` to `scale`
+    /// fractional digits. Digit separators (see
+    /// `Grammar::digit_separator`) are ignored.
+    pub fn parse<'a>(
+        grammar: &Grammar<'a>,
+        s: &'a [u8],
+        scale: u8,
+        mode: RoundingMode,
+    ) -> Result<Self, ParseError> {
+        match grammar.parse(s).map_err(ParseError::Syntax)? {
+            Parsed::Finite(preparsed) => {
+                from_digits(preparsed, |c| c.is_ascii_digit(), scale, mode)
+                    .map_err(ParseError::Decimal)
+            }
+            Parsed::NaN | Parsed::Inf(_) | Parsed::Hex(_) => Err(ParseError::NotDecimal),
+        }
+    }
+}
+
+/// Converts the digits of `preparsed` that satisfy `is_digit` (which
+/// must all be ASCII digits).
+fn from_digits(
+    preparsed: PreParsed<'_>,
+    is_digit: impl Fn(u8) -> bool,
+    scale: u8,
+    mode: RoundingMode,
+) -> Result<ScaledDecimal, ParseFloatError> {
+    let count = |digits: &[u8]| digits.iter().filter(|&&c| is_digit(c)).count() as i64;
+    let digits = preparsed
+        .int_digits
+        .iter()
+        .chain(preparsed.frac_digits)
+        .filter(|&&c| is_digit(c))
+        .map(|&c| c - b'0');
+
+    // The number of digits that make the integer mantissa.
+    let num_digits = count(preparsed.int_digits) + count(preparsed.frac_digits);
+    let num_int_digits =
+        num_digits - count(preparsed.frac_digits) + i64::from(preparsed.exp) + i64::from(scale);
+
+    let overflow = ParseFloatError::ExponentOutOfRange;
+    let mut magnitude = 0u128;
+    let mut first_dropped = None;
+    let mut rest_nonzero = false;
+    for (i, d) in (0..).zip(digits) {
+        match i.cmp(&num_int_digits) {
+            Ordering::Less => {
+                magnitude = magnitude
+                    .checked_mul(10)
+                    .and_then(|m| m.checked_add(u128::from(d)))
+                    .ok_or(overflow)?;
+            }
+            Ordering::Equal => first_dropped = Some(d),
+            Ordering::Greater => rest_nonzero |= d != 0,
+        }
+    }
+    if magnitude != 0 {
+        for _ in num_digits..num_int_digits {
+            magnitude = magnitude.checked_mul(10).ok_or(overflow)?;
+        }
+    }
+    let rest: &[u8] = if rest_nonzero { b"1" } else { b"" };
+    let remainder = if num_int_digits < 0 {
+        // Every digit is below the first dropped position.
+        Remainder::from_digits(b'0', rest)
+    } else {
+        first_dropped.map_or(Remainder::Zero, |d| Remainder::from_digits(b'0' + d, rest))
+    };
+    if mode.round_up(preparsed.sign, magnitude % 2 == 1, remainder) {
+        magnitude = magnitude.checked_add(1).ok_or(overflow)?;
+    }
+
+    let mantissa = if preparsed.sign {
+        if magnitude > 1 << 127 {
+            return Err(overflow);
+        }
+        (magnitude as i128).wrapping_neg()
+    } else {
+        if magnitude > i128::MAX as u128 {
+            return Err(overflow);
+        }
+        magnitude as i128
+    };
+    Ok(ScaledDecimal { mantissa, scale })
+}
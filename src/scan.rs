@@ -0,0 +1,190 @@
+//! Parsing of whole texts of delimited numbers, such as the rows of a CSV
+//! file or the columns of a scientific data file.
+//!
+//! The text is split into tokens at the bytes of `ScanFormat::delimiters`
+//! and `ScanFormat::whitespace`, and each token is parsed with
+//! `ScanFormat::grammar`. Runs of whitespace count as one, but each
+//! delimiter ends one field, so an empty field (such as between two commas
+//! of a CSV row) is an error instead of being skipped. A delimiter after
+//! the last field is allowed. Delimiters are searched for eight bytes at a
+//! time, and numbers are converted without going through any intermediate
+//! buffer.
+//!
+//! Scanning stops at the first token that is not a number, at the first
+//! empty field, or at the first number that does not fit in the output,
+//! reporting its position with a `ScanError`.
+//!
+//! # Example
+//!
+//! ```
+//! use flt2dec2flt::scan::{scan_into, ScanFormat};
+//! use flt2dec2flt::{ScanError, ScanErrorKind};
+//!
+//! let mut values = [0.0f64; 8];
+//! let text = b"1.5, 2.25,\n-3e2, 0.1\n";
+//! assert_eq!(scan_into(text, &mut values, &ScanFormat::CSV), Ok(4));
+//! assert_eq!(values[..4], [1.5, 2.25, -300.0, 0.1]);
+//!
+//! assert_eq!(
+//!     scan_into(b"-3e2,, 0.1", &mut values, &ScanFormat::CSV),
+//!     Err(ScanError {
+//!         index: 5,
+//!         kind: ScanErrorKind::Empty,
+//!     }),
+//! );
+//!
+//! assert_eq!(
+//!     scan_into(b"1 2 x3 4", &mut values, &ScanFormat::WHITESPACE),
+//!     Err(ScanError {
+//!         index: 4,
+//!         kind: ScanErrorKind::Syntax,
+//!     }),
+//! );
+//! ```
+
+use crate::grammar::Grammar;
+use crate::{FloatExt, ScanError, ScanErrorKind};
+
+/// How numbers are delimited and written in a text.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ScanFormat<'a> {
+    /// The bytes that end a field. There must be a number before each of
+    /// them.
+    pub delimiters: &'a [u8],
+    /// The bytes that are skipped around numbers. They also separate
+    /// numbers, with any number of them in a row.
+    pub whitespace: &'a [u8],
+    /// The syntax of each number.
+    pub grammar: Grammar<'a>,
+}
+
+impl ScanFormat<'static> {
+    /// Numbers in the syntax of `Grammar::RUST`, separated by ASCII
+    /// whitespace.
+    pub const WHITESPACE: Self = Self {
+        delimiters: b"",
+        whitespace: b" \t\n\x0b\x0c\r",
+        grammar: Grammar::RUST,
+    };
+
+    /// Numbers in the syntax of `Grammar::RUST`, separated by commas or
+    /// ASCII whitespace. Two commas with only whitespace between them
+    /// enclose an empty field, which is an error.
+    pub const CSV: Self = Self {
+        delimiters: b",",
+        whitespace: b" \t\n\x0b\x0c\r",
+        grammar: Grammar::RUST,
+    };
+}
+
+/// Parses the numbers of `text` into `out`, returning how many there are.
+///
+/// Fails if a token is not a number, or if there are more than
+/// `out.len()` numbers. The numbers before the failure are stored anyway.
+pub fn scan_into<T: FloatExt>(
+    text: &[u8],
+    out: &mut [T],
+    format: &ScanFormat<'_>,
+) -> Result<usize, ScanError> {
+    let mut n = 0;
+    scan(text, format, |v, index| {
+        let slot = out.get_mut(n).ok_or(ScanError {
+            index,
+            kind: ScanErrorKind::Full,
+        })?;
+        *slot = v;
+        n += 1;
+        Ok(())
+    })?;
+    Ok(n)
+}
+
+/// Parses the numbers of `text`, appending them to `out` and returning
+/// how many there are.
+///
+/// Fails if a token is not a number. The numbers before it are appended
+/// anyway.
+#[cfg(feature = "alloc")]
+pub fn scan_vec<T: FloatExt>(
+    text: &[u8],
+    out: &mut alloc::vec::Vec<T>,
+    format: &ScanFormat<'_>,
+) -> Result<usize, ScanError> {
+    let start = out.len();
+    scan(text, format, |v, _| {
+        out.push(v);
+        Ok(())
+    })?;
+    Ok(out.len() - start)
+}
+
+/// Calls `f` with each number of `text` and the position of its token.
+fn scan<T: FloatExt>(
+    text: &[u8],
+    format: &ScanFormat<'_>,
+    mut f: impl FnMut(T, usize) -> Result<(), ScanError>,
+) -> Result<(), ScanError> {
+    let skip_whitespace = |i: usize| {
+        i + text[i..]
+            .iter()
+            .take_while(|c| format.whitespace.contains(c))
+            .count()
+    };
+    let mut i = 0;
+    loop {
+        i = skip_whitespace(i);
+        if i == text.len() {
+            return Ok(());
+        }
+        if format.delimiters.contains(&text[i]) {
+            return Err(ScanError {
+                index: i,
+                kind: ScanErrorKind::Empty,
+            });
+        }
+        let end = i + find_delimiter(&text[i..], format);
+        let v = format
+            .grammar
+            .parse(&text[i..end])
+            .map_err(|_| ScanError {
+                index: i,
+                kind: ScanErrorKind::Syntax,
+            })?
+            .to_float()
+            .map_err(|e| ScanError {
+                index: i,
+                kind: ScanErrorKind::Parse(e),
+            })?;
+        f(v, i)?;
+        i = skip_whitespace(end);
+        if i < text.len() && format.delimiters.contains(&text[i]) {
+            i += 1;
+        }
+    }
+}
+
+/// Returns the position of the first byte of `s` that is a delimiter or
+/// whitespace, or the length of `s`.
+fn find_delimiter(s: &[u8], format: &ScanFormat<'_>) -> usize {
+    let is_delimiter = |c: &u8| format.delimiters.contains(c) || format.whitespace.contains(c);
+    const LOW: u64 = 0x0101_0101_0101_0101;
+    const HIGH: u64 = 0x8080_8080_8080_8080;
+
+    let mut i = 0;
+    while let Some(chunk) = s.get(i..(i + 8)) {
+        let mut word = [0; 8];
+        word.copy_from_slice(chunk);
+        let word = u64::from_le_bytes(word);
+        // A byte of `x` is zero where `word` has the delimiter, and the
+        // expression is non-zero if and only if one of them is.
+        let found = format.delimiters.iter().chain(format.whitespace).any(|&d| {
+            let x = word ^ (LOW * u64::from(d));
+            x.wrapping_sub(LOW) & !x & HIGH != 0
+        });
+        if found {
+            break;
+        }
+        i += 8;
+    }
+    i + s[i..].iter().position(is_delimiter).unwrap_or(s.len() - i)
+}
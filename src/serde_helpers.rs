@@ -0,0 +1,193 @@
+//! Functions to be used with `#[serde(with = "...")]`.
+//!
+//! Requires the `serde` feature.
+
+/// Serializes `f32` or `f64` as the shortest string that round-trips
+/// and deserializes it with `FloatExt::from_preparsed`.
+///
+/// The output does not depend on the locale and is never longer than
+/// needed: `1.5`, `1e100`, `1.25e-7`, `NaN`, `inf` or `-inf`.
+///
+/// # Example
+///
+/// ```
+/// # extern crate serde_crate as serde;
+/// # extern crate serde_json_crate as serde_json;
+/// #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+/// # #[serde(crate = "serde")]
+/// struct Point {
+///     #[serde(with = "flt2dec2flt::serde_helpers::shortest")]
+///     x: f64,
+///     #[serde(with = "flt2dec2flt::serde_helpers::shortest")]
+///     y: f32,
+/// }
+///
+/// let point = Point { x: 0.1, y: 1e30 };
+/// let json = serde_json::to_string(&point).unwrap();
+/// assert_eq!(json, r#"{"x":"0.1","y":"1e30"}"#);
+/// assert_eq!(serde_json::from_str::<Point>(&json).unwrap(), point);
+/// ```
+pub mod shortest {
+    use core::fmt;
+    use core::marker::PhantomData;
+
+    use crate::{FloatExt, PreFormatted, PreParsed};
+
+    /// Serializes `value` as the shortest string that round-trips.
+    pub fn serialize<T: FloatExt, S: serde::Serializer>(
+        value: &T,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut digits_buf = [0; crate::PREFORMAT_SHORTEST_BUF_LEN];
+        let preformatted = value.preformat_shortest(&mut digits_buf);
+        serializer.collect_str(&Shortest(preformatted))
+    }
+
+    /// Deserializes a string written by `serialize` (or any other string
+    /// accepted by `str::parse::<f64>`).
+    pub fn deserialize<'de, T: FloatExt, D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<T, D::Error> {
+        deserializer.deserialize_str(FloatStrVisitor(PhantomData))
+    }
+
+    struct FloatStrVisitor<T>(PhantomData<T>);
+
+    impl<T: FloatExt> serde::de::Visitor<'_> for FloatStrVisitor<T> {
+        type Value = T;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("a string containing a floating point number")
+        }
+
+        fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<T, E> {
+            parse(v.as_bytes())
+                .ok_or_else(|| E::invalid_value(serde::de::Unexpected::Str(v), &self))
+        }
+    }
+
+    /// Displays a number in positional or scientific notation, whichever
+    /// is shorter (positional in case of a tie).
+    ///
+    /// The digits must not be followed by zeros, which is the case for
+    /// those of `FloatExt::preformat_shortest`.
+    struct Shortest<'a>(PreFormatted<'a>);
+
+    impl fmt::Display for Shortest<'_> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            let (digits, exp) = match self.0 {
+                PreFormatted::NaN => return f.write_str("NaN"),
+                PreFormatted::Inf(false) => return f.write_str("inf"),
+                PreFormatted::Inf(true) => return f.write_str("-inf"),
+                PreFormatted::Zero(false) => return f.write_str("0"),
+                PreFormatted::Zero(true) => return f.write_str("-0"),
+                PreFormatted::Finite(sign, digits, zeros, exp) => {
+                    debug_assert_eq!(zeros, 0);
+                    if sign {
+                        f.write_str("-")?;
+                    }
+                    (digits, i32::from(exp))
+                }
+            };
+            // Only ASCII digits.
+            let digits = core::str::from_utf8(digits).unwrap();
+            let len = digits.len() as i32;
+
+            let positional_len = if exp <= 0 {
+                // 0.000ddd
+                2 - exp + len
+            } else if exp < len {
+                // ddd.ddd
+                len + 1
+            } else {
+                // ddd000
+                exp
+            };
+            let sci_exp = exp - 1;
+            let mut scientific_len = len + i32::from(len > 1) + 2 + i32::from(sci_exp < 0);
+            let mut rest = sci_exp.abs() / 10;
+            while rest != 0 {
+                scientific_len += 1;
+                rest /= 10;
+            }
+
+            if positional_len <= scientific_len {
+                if exp <= 0 {
+                    f.write_str("0.")?;
+                    write_zeros(f, -exp)?;
+                    f.write_str(digits)
+                } else if exp < len {
+                    let (int_digits, frac_digits) = digits.split_at(exp as usize);
+                    write!(f, "{}.{}", int_digits, frac_digits)
+                } else {
+                    f.write_str(digits)?;
+                    write_zeros(f, exp - len)
+                }
+            } else {
+                let (first, rest) = digits.split_at(1);
+                f.write_str(first)?;
+                if !rest.is_empty() {
+                    write!(f, ".{}", rest)?;
+                }
+                write!(f, "e{}", sci_exp)
+            }
+        }
+    }
+
+    fn write_zeros(f: &mut fmt::Formatter<'_>, n: i32) -> fmt::Result {
+        for _ in 0..n {
+            f.write_str("0")?;
+        }
+        Ok(())
+    }
+
+    /// Parses the syntax of `str::parse::<f64>`, such as `-1.5e3`, `.5`,
+    /// `5.`, `+inf`, `infinity` or `NaN`.
+    fn parse<T: FloatExt>(s: &[u8]) -> Option<T> {
+        let (sign, s) = split_sign(s);
+        if s.eq_ignore_ascii_case(b"nan") {
+            return Some(T::NAN);
+        }
+        if s.eq_ignore_ascii_case(b"inf") || s.eq_ignore_ascii_case(b"infinity") {
+            return Some(if sign { T::NEG_INFINITY } else { T::INFINITY });
+        }
+        let (mantissa, exp) = match s.iter().position(|&c| c == b'e' || c == b'E') {
+            Some(i) => (&s[..i], parse_exp(&s[i + 1..])?),
+            None => (s, 0),
+        };
+        let (int_digits, frac_digits) = match mantissa.iter().position(|&c| c == b'.') {
+            Some(i) => (&mantissa[..i], &mantissa[i + 1..]),
+            None => (mantissa, &b""[..]),
+        };
+        if int_digits.is_empty() && frac_digits.is_empty() {
+            return None;
+        }
+        let preparsed = PreParsed {
+            sign,
+            int_digits,
+            frac_digits,
+            exp,
+        };
+        T::from_preparsed(preparsed).ok()
+    }
+
+    /// Parses the digits of an exponent, saturating it.
+    fn parse_exp(s: &[u8]) -> Option<i32> {
+        let (sign, digits) = split_sign(s);
+        if digits.is_empty() || !digits.iter().all(u8::is_ascii_digit) {
+            return None;
+        }
+        let abs_exp = digits.iter().fold(0i32, |acc, &c| {
+            acc.saturating_mul(10).saturating_add(i32::from(c - b'0'))
+        });
+        Some(if sign { -abs_exp } else { abs_exp })
+    }
+
+    fn split_sign(s: &[u8]) -> (bool, &[u8]) {
+        match s.split_first() {
+            Some((&b'-', rest)) => (true, rest),
+            Some((&b'+', rest)) => (false, rest),
+            _ => (false, s),
+        }
+    }
+}
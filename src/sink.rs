@@ -0,0 +1,493 @@
+//! Formatting without an output buffer.
+//!
+//! The `preformat_*_into` methods of `FloatExt` push the digits of a
+//! number into a `DigitSink` instead of writing them into a buffer
+//! provided by the caller, and return the rest of the number as a
+//! `Formatted`. This allows streaming the digits into ring buffers, DMA
+//! regions or hashing contexts.
+//!
+//! The exact modes generate their digits with the Dragon algorithm, which
+//! is slower than the one used by the `preformat_exact_*` methods, but
+//! never has to discard the digits it has generated. A digit is held back
+//! only while rounding may still change it, that is, the last digit that
+//! is not a 9 and the nines that follow it. The shortest mode, which has
+//! at most 17 digits, formats them into a `PREFORMAT_SHORTEST_BUF_LEN`
+//! buffer on the stack first, so they match `preformat_shortest` with any
+//! strategy.
+//!
+//! A sink can also ask for the digits from the least significant one (see
+//! `DigitSink::least_significant_first`), as wanted by display drivers
+//! that fill digit positions from the right or by BCD arithmetic. The
+//! exact modes then scale the number to an integer in a 2,560-bit bignum
+//! (320 bytes), round it to the requested precision and push the
+//! remainders of its divisions by ten, without a buffer for the digits.
+//!
+//! # Example
+//!
+//! ```
+//! use flt2dec2flt::sink::{DigitSink, Formatted};
+//! use flt2dec2flt::FloatExt as _;
+//!
+//! // Computes the sum of the digits.
+//! struct DigitSum(u32);
+//!
+//! impl DigitSink for DigitSum {
+//!     fn digit(&mut self, d: u8) {
+//!         self.0 += u32::from(d - b'0');
+//!     }
+//!
+//!     fn zeros(&mut self, _n: usize) {}
+//! }
+//!
+//! let mut sum = DigitSum(0);
+//! assert_eq!(12.75f64.preformat_shortest_into(&mut sum), Formatted::Finite(false, 2));
+//! assert_eq!(sum.0, 15);
+//!
+//! let mut sum = DigitSum(0);
+//! assert_eq!((-0.1f32).preformat_exact_exp_into(&mut sum, 30), Formatted::Finite(true, 0));
+//! assert_eq!(sum.0, 80); // 0.100000001490116119384765625
+//!
+//! // Fills 7-segment positions from the right.
+//! struct Display {
+//!     positions: [u8; 8],
+//!     next: usize,
+//! }
+//!
+//! impl DigitSink for Display {
+//!     fn digit(&mut self, d: u8) {
+//!         self.next -= 1;
+//!         self.positions[self.next] = d - b'0';
+//!     }
+//!
+//!     fn least_significant_first(&self) -> bool {
+//!         true
+//!     }
+//! }
+//!
+//! let mut display = Display {
+//!     positions: [0; 8],
+//!     next: 8,
+//! };
+//! assert_eq!(1250.0f64.preformat_exact_fixed_into(&mut display, 0), Formatted::Finite(false, 4));
+//! assert_eq!(display.positions, [0, 0, 0, 0, 1, 2, 5, 0]);
+//! ```
+
+use crate::core_num::bignum::Big32x80 as Big;
+use crate::core_num::flt2dec::strategy::dragon::{self, POW10};
+use crate::core_num::flt2dec::{
+    estimate_max_buf_len, estimator::estimate_scaling_factor, Decoded, FullDecoded, Sink,
+};
+use crate::PreFormatted;
+
+/// Receives the digits of a number, from the most significant one unless
+/// `least_significant_first` returns `true`.
+pub trait DigitSink {
+    /// Receives a digit, as an ASCII byte (`b'0'` to `b'9'`).
+    fn digit(&mut self, d: u8);
+
+    /// Receives `n` zeros.
+    fn zeros(&mut self, n: usize) {
+        for _ in 0..n {
+            self.digit(b'0');
+        }
+    }
+
+    /// Whether the digits are pushed from the least significant one, the
+    /// extra zeros first. The default is `false`.
+    fn least_significant_first(&self) -> bool {
+        false
+    }
+}
+
+impl<S: DigitSink + ?Sized> DigitSink for &mut S {
+    fn digit(&mut self, d: u8) {
+        (**self).digit(d);
+    }
+
+    fn zeros(&mut self, n: usize) {
+        (**self).zeros(n);
+    }
+
+    fn least_significant_first(&self) -> bool {
+        (**self).least_significant_first()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl DigitSink for alloc::vec::Vec<u8> {
+    fn digit(&mut self, d: u8) {
+        self.push(d);
+    }
+
+    fn zeros(&mut self, n: usize) {
+        self.resize(self.len() + n, b'0');
+    }
+}
+
+/// A pre-formatted number whose digits have been pushed into a
+/// `DigitSink`. It is like `PreFormatted`, without the digits.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Formatted {
+    /// The number is NaN.
+    NaN,
+    /// The number is infinity. The boolean specifies the sign.
+    Inf(bool),
+    /// The number is zero. The boolean specifies the sign.
+    Zero(bool),
+    /// The number is finite. The boolean specifies the sign and the `i16`
+    /// the exponent.
+    ///
+    /// The represented value is `sign 0.digits * 10 ^ exp`, where the
+    /// digits are the ones pushed into the sink.
+    Finite(bool, i16),
+}
+
+/// Pushes the digits of `preformatted` (including the extra zeros) into
+/// `sink`, in the order it asks for.
+pub(crate) fn push(preformatted: PreFormatted<'_>, sink: &mut impl DigitSink) -> Formatted {
+    match preformatted {
+        PreFormatted::NaN => Formatted::NaN,
+        PreFormatted::Inf(sign) => Formatted::Inf(sign),
+        PreFormatted::Zero(sign) => Formatted::Zero(sign),
+        PreFormatted::Finite(sign, digits, num_zeros, exp) => {
+            if sink.least_significant_first() {
+                if num_zeros != 0 {
+                    sink.zeros(num_zeros);
+                }
+                for &d in digits.iter().rev() {
+                    sink.digit(d);
+                }
+            } else {
+                for &d in digits {
+                    sink.digit(d);
+                }
+                if num_zeros != 0 {
+                    sink.zeros(num_zeros);
+                }
+            }
+            Formatted::Finite(sign, exp)
+        }
+    }
+}
+
+/// Pushes `ndigits` digits of a decoded number into `sink`, in the order
+/// it asks for, like `preformat_exact_exp`.
+pub(crate) fn format_exact_exp(
+    sign: bool,
+    full_decoded: FullDecoded,
+    sink: &mut impl DigitSink,
+    ndigits: usize,
+) -> Formatted {
+    assert!(ndigits > 0);
+    match full_decoded {
+        FullDecoded::Nan => Formatted::NaN,
+        FullDecoded::Infinite => Formatted::Inf(sign),
+        FullDecoded::Zero => Formatted::Zero(sign),
+        FullDecoded::Finite(ref decoded) if sink.least_significant_first() => {
+            let mut exact = Exact::new(decoded);
+            let mut exp = exact.exp();
+            let len = if exact.len > ndigits {
+                if exact.round_off(exact.len - ndigits) {
+                    // 999..999 rounds to 1000..000 with an increased exponent
+                    exp += 1;
+                    exact.int.div_rem_small(10);
+                }
+                ndigits
+            } else {
+                exact.len
+            };
+            push_zeros(sink, ndigits - len);
+            push_lsf(sink, &mut exact.int, len);
+            Formatted::Finite(sign, exp)
+        }
+        FullDecoded::Finite(ref decoded) => {
+            let maxlen = estimate_max_buf_len(decoded.exp);
+            let mut streaming = Streaming::new(&mut *sink, ndigits.min(maxlen));
+            let exp = dragon::format_exact_into(decoded, &mut streaming, i16::MIN);
+            let len = streaming.finish();
+            push_zeros(sink, ndigits - len);
+            Formatted::Finite(sign, exp)
+        }
+    }
+}
+
+/// Pushes the digits of a decoded number down to `10^-frac_digits` into
+/// `sink`, in the order it asks for, like `preformat_exact_fixed`.
+pub(crate) fn format_exact_fixed(
+    sign: bool,
+    full_decoded: FullDecoded,
+    sink: &mut impl DigitSink,
+    frac_digits: isize,
+) -> Formatted {
+    match full_decoded {
+        FullDecoded::Nan => Formatted::NaN,
+        FullDecoded::Infinite => Formatted::Inf(sign),
+        FullDecoded::Zero => Formatted::Zero(sign),
+        FullDecoded::Finite(ref decoded) if sink.least_significant_first() => {
+            let limit = crate::generic::fixed_limit(frac_digits);
+            let mut exact = Exact::new(decoded);
+            let mut exp = exact.exp();
+            // the number of digits below `10^limit`
+            let drop = exact.frac_len as i32 + i32::from(limit);
+            let len = if drop > exact.len as i32 {
+                0
+            } else if drop > 0 {
+                let kept = exact.len - drop as usize;
+                if exact.round_off(drop as usize) {
+                    exp += 1;
+                    kept + 1
+                } else {
+                    kept
+                }
+            } else {
+                exact.len
+            };
+            if len == 0 {
+                // Like `preformat_exact_fixed`, no digit has been generated.
+                return Formatted::Zero(sign);
+            }
+            // the same number of digits as `format_exact_fixed` below
+            let ndigits = if exp > 0 {
+                (frac_digits.max(0) as usize).saturating_add(exp as usize)
+            } else {
+                let maxlen = estimate_max_buf_len(decoded.exp);
+                ((i32::from(exp) - i32::from(limit)) as usize).min(maxlen)
+            };
+            push_zeros(sink, ndigits - len);
+            push_lsf(sink, &mut exact.int, len);
+            Formatted::Finite(sign, exp)
+        }
+        FullDecoded::Finite(ref decoded) => {
+            let limit = crate::generic::fixed_limit(frac_digits);
+            let maxlen = estimate_max_buf_len(decoded.exp);
+            let mut streaming = Streaming::new(&mut *sink, maxlen);
+            let exp = dragon::format_exact_into(decoded, &mut streaming, limit);
+            let len = streaming.finish();
+            if exp <= limit {
+                // Like `preformat_exact_fixed`, no digit has been generated.
+                Formatted::Zero(sign)
+            } else {
+                if exp > 0 {
+                    let ndigits = (frac_digits.max(0) as usize).saturating_add(exp as usize);
+                    push_zeros(sink, ndigits - len);
+                }
+                Formatted::Finite(sign, exp)
+            }
+        }
+    }
+}
+
+fn push_zeros<S: DigitSink + ?Sized>(sink: &mut S, n: usize) {
+    if n != 0 {
+        sink.zeros(n);
+    }
+}
+
+/// Pushes the `len` digits of `int` into `sink`, from the least
+/// significant one.
+fn push_lsf<S: DigitSink + ?Sized>(sink: &mut S, int: &mut Big, mut len: usize) {
+    while len != 0 {
+        let n = len.min(9);
+        let (_, mut rem) = int.div_rem_small(POW10[9]);
+        for _ in 0..n {
+            sink.digit(b'0' + (rem % 10) as u8);
+            rem /= 10;
+        }
+        len -= n;
+    }
+}
+
+/// The exact value of a finite number, `int * 10^-frac_len`.
+///
+/// Any `f64` fits in 2,547 bits this way (`(2^53 - 1) * 5^1074`), and
+/// `10^len` in 2,551 bits.
+struct Exact {
+    int: Big,
+    frac_len: usize,
+    /// The number of digits of `int`.
+    len: usize,
+}
+
+impl Exact {
+    fn new(decoded: &Decoded) -> Self {
+        // without trailing zero bits, `mant * 2^exp` has `-exp` fractional
+        // digits when `exp` is negative.
+        let tz = decoded.mant.trailing_zeros();
+        let mant = decoded.mant >> tz;
+        let exp = decoded.exp + tz as i16;
+        let mut int = Big::from_u64(mant);
+        let frac_len = if exp < 0 {
+            int.mul_pow5(-exp as usize);
+            -exp as usize
+        } else {
+            int.mul_pow2(exp as usize);
+            0
+        };
+
+        // `10^(k-1) < mant * 2^exp <= 10^(k+1)`, so `int` has at least
+        // `k + frac_len` digits, which is at least -1.
+        let k = estimate_scaling_factor(decoded.mant, decoded.exp);
+        let mut len = (i32::from(k) + frac_len as i32).max(0) as usize;
+        let mut pow10 = Big::from_small(1);
+        pow10.mul_pow5(len).mul_pow2(len);
+        while int >= pow10 {
+            pow10.mul_small(10);
+            len += 1;
+        }
+        Exact { int, frac_len, len }
+    }
+
+    /// The decimal exponent, such that the value is `0.digits * 10^exp`.
+    fn exp(&self) -> i16 {
+        (self.len as i32 - self.frac_len as i32) as i16
+    }
+
+    /// Removes the last `drop` (at most `len`) digits, rounding like
+    /// `dragon::format_exact`: half to even, and up when no digit is
+    /// left. Returns whether rounding up added a digit (which is then
+    /// not counted by `len`).
+    fn round_off(&mut self, drop: usize) -> bool {
+        debug_assert!(drop <= self.len);
+        // the last digits in chunks of 9, then the most significant ones
+        let mut rest = drop;
+        let mut sticky = false;
+        while rest > 9 {
+            let (_, rem) = self.int.div_rem_small(POW10[9]);
+            sticky |= rem != 0;
+            rest -= 9;
+        }
+        let (_, rem) = self.int.div_rem_small(POW10[rest]);
+        let half = POW10[rest] / 2;
+        self.len -= drop;
+
+        let up = rem > half
+            || (rem == half && (sticky || self.int.is_zero() || self.int.digits()[0] & 1 == 1));
+        if !up {
+            return false;
+        }
+        self.int.add_small(1);
+        let mut pow10 = Big::from_small(1);
+        pow10.mul_pow5(self.len).mul_pow2(self.len);
+        self.int == pow10
+    }
+}
+
+/// Passes the digits generated by Dragon to a `DigitSink`.
+///
+/// Rounding up only changes the last digit that is not a 9 and the nines
+/// after it, so those are held until a later digit makes them final.
+/// Grisu can also replace or discard the generated digits, so it cannot
+/// use this sink.
+struct Streaming<'a, S: ?Sized> {
+    sink: &'a mut S,
+    capacity: usize,
+    len: usize,
+    /// The held digit before the held nines. When it is `None`, all the
+    /// digits are held nines, so nothing has been pushed into `sink` yet.
+    held: Option<u8>,
+    nines: usize,
+}
+
+impl<'a, S: DigitSink + ?Sized> Streaming<'a, S> {
+    fn new(sink: &'a mut S, capacity: usize) -> Self {
+        Streaming {
+            sink,
+            capacity,
+            len: 0,
+            held: None,
+            nines: 0,
+        }
+    }
+
+    /// Pushes the held digits into the sink.
+    fn release(&mut self) {
+        if let Some(d) = self.held.take() {
+            self.sink.digit(d);
+        }
+        for _ in 0..self.nines {
+            self.sink.digit(b'9');
+        }
+        self.nines = 0;
+    }
+
+    /// Pushes the held digits into the sink and returns the number of
+    /// digits.
+    fn finish(mut self) -> usize {
+        self.release();
+        self.len
+    }
+}
+
+impl<S: DigitSink + ?Sized> Sink for Streaming<'_, S> {
+    fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn push(&mut self, d: u8) {
+        self.len += 1;
+        if d == b'9' {
+            self.nines += 1;
+        } else {
+            self.release();
+            self.held = Some(d);
+        }
+    }
+
+    fn zeros(&mut self, n: usize) {
+        if n != 0 {
+            self.release();
+            self.len += n;
+            push_zeros(self.sink, n - 1);
+            self.held = Some(b'0');
+        }
+    }
+
+    fn last(&self) -> Option<u8> {
+        if self.nines != 0 {
+            Some(b'9')
+        } else {
+            self.held
+        }
+    }
+
+    #[cfg(not(any(feature = "strategy-ryu", feature = "strategy-dragonbox")))]
+    fn set_last(&mut self, _d: u8) {
+        unreachable!("only Grisu replaces digits");
+    }
+
+    fn round_up(&mut self) -> Option<u8> {
+        let nines = core::mem::replace(&mut self.nines, 0);
+        match self.held {
+            Some(d) => {
+                if nines != 0 {
+                    self.sink.digit(d + 1);
+                    push_zeros(self.sink, nines - 1);
+                    self.held = Some(b'0');
+                } else {
+                    self.held = Some(d + 1);
+                }
+                None
+            }
+            None if nines != 0 => {
+                // 999..999 rounds to 1000..000 with an increased exponent
+                if nines != 1 {
+                    self.sink.digit(b'1');
+                    push_zeros(self.sink, nines - 2);
+                    self.held = Some(b'0');
+                } else {
+                    self.held = Some(b'1');
+                }
+                Some(b'0')
+            }
+            None => Some(b'1'),
+        }
+    }
+
+    fn clear(&mut self) {
+        unreachable!("only Grisu discards digits");
+    }
+}
@@ -0,0 +1,88 @@
+//! Conversions between floating point numbers and SQL `DECIMAL(p, s)`
+//! (or `NUMERIC(p, s)`) values.
+//!
+//! A `DECIMAL(precision, scale)` value is represented by an `i128` with at
+//! most `precision` decimal digits, of which `scale` are fractional, such
+//! as `12345` for `123.45` in a `DECIMAL(5, 2)` column. `precision` is
+//! between 1 and 38, and `scale` between 0 and `precision`.
+//!
+//! Floats are converted with their exact value, rounded with the given
+//! `RoundingMode`, so `0.1f32` is never mistaken for `0.1000000015`.
+//!
+//! # Example
+//!
+//! ```
+//! use flt2dec2flt::sql_decimal::{from_sql_decimal, to_sql_decimal};
+//! use flt2dec2flt::{DecimalOverflow, RoundingMode};
+//!
+//! assert_eq!(to_sql_decimal(2.675f64, 5, 2, RoundingMode::NearestEven), Ok(267));
+//! assert_eq!(to_sql_decimal(2.675f64, 5, 2, RoundingMode::Up), Ok(268));
+//! assert_eq!(to_sql_decimal(-2.5f64, 5, 0, RoundingMode::NearestAway), Ok(-3));
+//! assert_eq!(to_sql_decimal(-2.5f64, 5, 0, RoundingMode::Down), Ok(-3));
+//! assert_eq!(to_sql_decimal(-2.5f64, 5, 0, RoundingMode::TowardZero), Ok(-2));
+//! assert_eq!(
+//!     to_sql_decimal(999.995f64, 5, 2, RoundingMode::NearestEven),
+//!     Err(DecimalOverflow),
+//! );
+//!
+//! assert_eq!(from_sql_decimal::<f64>(12345, 5, 2), Ok(123.45));
+//! ```
+
+use crate::scaled::{from_scaled, split_i128, to_scaled};
+use crate::{DecimalOverflow, FloatExt, RoundingMode};
+
+/// Largest precision of a `DECIMAL(p, s)`.
+pub const MAX_PRECISION: u8 = 38;
+
+fn max_magnitude(precision: u8, scale: u8) -> u128 {
+    assert!(
+        (1..=MAX_PRECISION).contains(&precision) && scale <= precision,
+        "invalid DECIMAL({}, {})",
+        precision,
+        scale,
+    );
+    10u128.pow(u32::from(precision)) - 1
+}
+
+/// Converts `v` into a `DECIMAL(precision, scale)`, rounding with `mode`.
+///
+/// Fails if `v` is NaN or infinite, or if the rounded value has more than
+/// `precision - scale` integer digits.
+///
+/// Panics if `precision` is not between 1 and `MAX_PRECISION`, or if
+/// `scale` is greater than `precision`.
+pub fn to_sql_decimal<T: FloatExt>(
+    v: T,
+    precision: u8,
+    scale: u8,
+    mode: RoundingMode,
+) -> Result<i128, DecimalOverflow> {
+    let max = max_magnitude(precision, scale);
+    let (sign, magnitude) = to_scaled(v, i32::from(scale), mode).ok_or(DecimalOverflow)?;
+    if magnitude > max {
+        return Err(DecimalOverflow);
+    }
+    let value = magnitude as i128;
+    Ok(if sign { -value } else { value })
+}
+
+/// Converts a `DECIMAL(precision, scale)` into the nearest floating point
+/// number (rounding half to even).
+///
+/// Fails if `value` has more than `precision` digits. Every valid value
+/// is within the range of `f32`.
+///
+/// Panics if `precision` is not between 1 and `MAX_PRECISION`, or if
+/// `scale` is greater than `precision`.
+pub fn from_sql_decimal<T: FloatExt>(
+    value: i128,
+    precision: u8,
+    scale: u8,
+) -> Result<T, DecimalOverflow> {
+    let max = max_magnitude(precision, scale);
+    let (sign, magnitude) = split_i128(value);
+    if magnitude > max {
+        return Err(DecimalOverflow);
+    }
+    Ok(from_scaled(sign, magnitude, i64::from(scale)))
+}
@@ -0,0 +1,271 @@
+//! Dragonbox, by Junekey Jeon ("Dragonbox: A New Floating-Point Binary-to-
+//! Decimal Conversion Algorithm", 2020).
+//!
+//! Follows the reference implementation
+//! (<https://github.com/jk-jeon/dragonbox>, Apache-2.0 with LLVM exception
+//! or BSL-1.0) with its default policies: round to nearest, ties to even,
+//! both when choosing the interval and the digits.
+
+pub(crate) mod table;
+
+use self::table::{F32_CACHE, F32_MIN_K, F64_CACHE, F64_MIN_K};
+
+/// Returns `floor(log10(2^e))`; requires `-2620 <= e <= 2620`.
+fn floor_log10_pow2(e: i32) -> i32 {
+    (e * 315653) >> 20
+}
+
+/// Returns `floor(log2(10^e))`; requires `-1233 <= e <= 1233`.
+fn floor_log2_pow10(e: i32) -> i32 {
+    (e * 1741647) >> 19
+}
+
+/// Returns `floor(log10(2^e) - log10(4 / 3))`; requires `-2985 <= e <= 2936`.
+fn floor_log10_pow2_minus_log10_4_over_3(e: i32) -> i32 {
+    (e * 631305 - 261663) >> 21
+}
+
+/// Parameters of a binary format, with its cache of powers of ten.
+trait Format {
+    type Cache: Copy;
+
+    const SIG_BITS: u32;
+    const EXP_BITS: u32;
+    const BIAS: i32;
+    const KAPPA: i32;
+    /// `10^KAPPA`
+    const SMALL_DIVISOR: u32;
+    /// `10^(KAPPA + 1)`
+    const BIG_DIVISOR: u32;
+    /// Binary exponent where the round-up of the shorter interval case can
+    /// be a tie.
+    const SHORTER_INTERVAL_TIE_EXP: i32;
+
+    fn cache(k: i32) -> Self::Cache;
+    /// The most significant 64 bits of `cache`.
+    fn high(cache: Self::Cache) -> u64;
+
+    fn compute_mul(u: u64, cache: Self::Cache) -> (u64, bool);
+    fn compute_delta(cache: Self::Cache, beta: i32) -> u32;
+    fn compute_mul_parity(two_f: u64, cache: Self::Cache, beta: i32) -> (bool, bool);
+}
+
+struct F32;
+
+impl Format for F32 {
+    type Cache = u64;
+
+    const SIG_BITS: u32 = 23;
+    const EXP_BITS: u32 = 8;
+    const BIAS: i32 = 127;
+    const KAPPA: i32 = 1;
+    const SMALL_DIVISOR: u32 = 10;
+    const BIG_DIVISOR: u32 = 100;
+    const SHORTER_INTERVAL_TIE_EXP: i32 = -35;
+
+    fn cache(k: i32) -> u64 {
+        F32_CACHE[(k - F32_MIN_K) as usize]
+    }
+
+    fn high(cache: u64) -> u64 {
+        cache
+    }
+
+    fn compute_mul(u: u64, cache: u64) -> (u64, bool) {
+        // Upper 64 bits of the 96-bit product.
+        let r = ((u128::from(u) * u128::from(cache)) >> 32) as u64;
+        (r >> 32, r as u32 == 0)
+    }
+
+    fn compute_delta(cache: u64, beta: i32) -> u32 {
+        (cache >> (63 - beta)) as u32
+    }
+
+    fn compute_mul_parity(two_f: u64, cache: u64, beta: i32) -> (bool, bool) {
+        // Lower 64 bits of the 96-bit product.
+        let r = two_f.wrapping_mul(cache);
+        ((r >> (64 - beta)) & 1 != 0, (r >> (32 - beta)) as u32 == 0)
+    }
+}
+
+struct F64;
+
+impl Format for F64 {
+    type Cache = u128;
+
+    const SIG_BITS: u32 = 52;
+    const EXP_BITS: u32 = 11;
+    const BIAS: i32 = 1023;
+    const KAPPA: i32 = 2;
+    const SMALL_DIVISOR: u32 = 100;
+    const BIG_DIVISOR: u32 = 1000;
+    const SHORTER_INTERVAL_TIE_EXP: i32 = -77;
+
+    fn cache(k: i32) -> u128 {
+        F64_CACHE[(k - F64_MIN_K) as usize]
+    }
+
+    fn high(cache: u128) -> u64 {
+        (cache >> 64) as u64
+    }
+
+    fn compute_mul(u: u64, cache: u128) -> (u64, bool) {
+        // Upper 128 bits of the 192-bit product.
+        let u = u128::from(u);
+        let r = u * (cache >> 64) + ((u * (cache as u64 as u128)) >> 64);
+        ((r >> 64) as u64, r as u64 == 0)
+    }
+
+    fn compute_delta(cache: u128, beta: i32) -> u32 {
+        (Self::high(cache) >> (63 - beta)) as u32
+    }
+
+    fn compute_mul_parity(two_f: u64, cache: u128, beta: i32) -> (bool, bool) {
+        // Lower 128 bits of the 192-bit product.
+        let u = u128::from(two_f);
+        let r = ((u * (cache >> 64)) << 64).wrapping_add(u * (cache as u64 as u128));
+        let (high, low) = ((r >> 64) as u64, r as u64);
+        (
+            (high >> (64 - beta)) & 1 != 0,
+            (high << beta) | (low >> (64 - beta)) == 0,
+        )
+    }
+}
+
+fn remove_trailing_zeros(mut significand: u64, mut exponent: i32) -> (u64, i32) {
+    while significand % 10 == 0 {
+        significand /= 10;
+        exponent += 1;
+    }
+    (significand, exponent)
+}
+
+/// Converts the bits of a finite, non-zero float to the shortest
+/// `(significand, exponent)` such that `significand * 10^exponent` rounds to
+/// it.
+fn to_decimal<F: Format>(bits: u64) -> (u64, i32) {
+    let fraction = bits & ((1 << F::SIG_BITS) - 1);
+    let exponent_bits = ((bits >> F::SIG_BITS) & ((1 << F::EXP_BITS) - 1)) as i32;
+    // With round to nearest, ties to even, the endpoints of the interval
+    // are included when the significand is even.
+    let include_endpoints = fraction & 1 == 0;
+
+    let mut two_fc = fraction << 1;
+    let exponent;
+    if exponent_bits != 0 {
+        exponent = exponent_bits - F::BIAS - F::SIG_BITS as i32;
+        if two_fc == 0 {
+            // The lower neighbor is closer than the upper one. This is also
+            // done for the smallest normal number, whose interval is
+            // actually regular, because the result is the same.
+            return compute_nearest_shorter::<F>(exponent);
+        }
+        two_fc |= 1 << (F::SIG_BITS + 1);
+    } else {
+        exponent = 1 - F::BIAS - F::SIG_BITS as i32;
+    }
+    compute_nearest_normal::<F>(two_fc, exponent, include_endpoints)
+}
+
+fn compute_nearest_normal<F: Format>(
+    two_fc: u64,
+    exponent: i32,
+    include_endpoints: bool,
+) -> (u64, i32) {
+    // Step 1: Schubfach multiplier calculation.
+    let minus_k = floor_log10_pow2(exponent) - F::KAPPA;
+    let cache = F::cache(-minus_k);
+    let beta = exponent + floor_log2_pow10(-minus_k);
+
+    // 10^KAPPA <= deltai < 10^(KAPPA + 1)
+    let deltai = F::compute_delta(cache, beta);
+    let (zi, is_z_integer) = F::compute_mul((two_fc | 1) << beta, cache);
+
+    // Step 2: Try the larger divisor.
+    let mut significand = zi / u64::from(F::BIG_DIVISOR);
+    let mut r = (zi - u64::from(F::BIG_DIVISOR) * significand) as u32;
+
+    let found = if r < deltai {
+        // Exclude the right endpoint if necessary.
+        if r == 0 && is_z_integer && !include_endpoints {
+            significand -= 1;
+            r = F::BIG_DIVISOR;
+            false
+        } else {
+            true
+        }
+    } else if r > deltai {
+        false
+    } else {
+        // r == deltai, compare the fractional parts.
+        let (xi_parity, x_is_integer) = F::compute_mul_parity(two_fc - 1, cache, beta);
+        xi_parity || (x_is_integer && include_endpoints)
+    };
+    if found {
+        return remove_trailing_zeros(significand, minus_k + F::KAPPA + 1);
+    }
+
+    // Step 3: Find the significand with the smaller divisor.
+    significand *= 10;
+    let mut dist = r - (deltai / 2) + (F::SMALL_DIVISOR / 2);
+    let approx_y_parity = ((dist ^ (F::SMALL_DIVISOR / 2)) & 1) != 0;
+    let divisible_by_small_divisor = dist % F::SMALL_DIVISOR == 0;
+    dist /= F::SMALL_DIVISOR;
+    significand += u64::from(dist);
+
+    if divisible_by_small_divisor {
+        // y is either zi - epsiloni or one less, which can be told apart by
+        // the parity.
+        let (yi_parity, is_y_integer) = F::compute_mul_parity(two_fc, cache, beta);
+        if yi_parity != approx_y_parity {
+            significand -= 1;
+        } else if is_y_integer && significand % 2 != 0 {
+            // A tie, round to even.
+            significand -= 1;
+        }
+    }
+    (significand, minus_k + F::KAPPA)
+}
+
+fn compute_nearest_shorter<F: Format>(exponent: i32) -> (u64, i32) {
+    let minus_k = floor_log10_pow2_minus_log10_4_over_3(exponent);
+    let beta = exponent + floor_log2_pow10(-minus_k);
+    let cache = F::high(F::cache(-minus_k));
+
+    // The significand is even, so both endpoints are included.
+    let shift = 64 - F::SIG_BITS as i32 - 1 - beta;
+    let mut xi = (cache - (cache >> (F::SIG_BITS + 2))) >> shift;
+    let zi = (cache + (cache >> (F::SIG_BITS + 1))) >> shift;
+    // The left endpoint is an integer only for `2 <= exponent <= 3`.
+    if !(2..=3).contains(&exponent) {
+        xi += 1;
+    }
+
+    // Try the bigger divisor.
+    let significand = zi / 10;
+    if significand * 10 >= xi {
+        return remove_trailing_zeros(significand, minus_k + 1);
+    }
+
+    // Otherwise, compute the round-up of y.
+    let mut significand = ((cache >> (shift - 1)) + 1) / 2;
+    if exponent == F::SHORTER_INTERVAL_TIE_EXP && significand % 2 != 0 {
+        // A tie, round to even.
+        significand -= 1;
+    } else if significand < xi {
+        significand += 1;
+    }
+    (significand, minus_k)
+}
+
+/// Converts the bits of a finite, non-zero `f64` to the shortest
+/// `(significand, exponent)` such that `significand * 10^exponent` rounds to
+/// it.
+pub(super) fn f64_to_decimal(bits: u64) -> (u64, i32) {
+    to_decimal::<F64>(bits)
+}
+
+/// Same as `f64_to_decimal`, for `f32`.
+pub(super) fn f32_to_decimal(bits: u32) -> (u64, i32) {
+    to_decimal::<F32>(u64::from(bits))
+}
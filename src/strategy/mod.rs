@@ -0,0 +1,170 @@
+//! Selection of the algorithm behind `FloatExt::preformat_shortest`, at
+//! build time with the `strategy-*` features (see the crate documentation).
+//!
+//! Grisu with the Dragon fallback is always available, because exact modes
+//! and `Decoded` values that do not come straight from an `f32` or `f64`
+//! (such as those of `preformat_shortest_scale2` or `qformat`) need it. Ryu
+//! and Dragonbox only handle `f32` and `f64`, and are only compiled when
+//! selected.
+
+#[cfg(feature = "strategy-dragonbox")]
+pub(crate) mod dragonbox;
+#[cfg(all(feature = "strategy-ryu", not(feature = "strategy-dragonbox")))]
+pub(crate) mod ryu;
+
+use crate::core_num::flt2dec::Decoded;
+#[cfg(any(feature = "strategy-ryu", feature = "strategy-dragonbox"))]
+use crate::core_num::flt2dec::MAX_SIG_DIGITS;
+use crate::sealed::Sealed;
+
+/// Finds the shortest digits of `v`, whose decoded value is `decoded`.
+#[cfg(not(any(feature = "strategy-ryu", feature = "strategy-dragonbox")))]
+pub(crate) fn format_shortest<'a, T: Sealed>(
+    _v: T,
+    decoded: &Decoded,
+    buf: &'a mut [u8],
+) -> (&'a [u8], i16) {
+    format_shortest_decoded(decoded, buf)
+}
+
+/// Finds the shortest digits of `v`, whose decoded value is `decoded`.
+#[cfg(any(feature = "strategy-ryu", feature = "strategy-dragonbox"))]
+pub(crate) fn format_shortest<'a, T: Sealed>(
+    v: T,
+    _decoded: &Decoded,
+    buf: &'a mut [u8],
+) -> (&'a [u8], i16) {
+    assert!(buf.len() >= MAX_SIG_DIGITS);
+    let bits = v.to_bits_u64();
+    let (mut mantissa, exp) = if T::BITS == 32 {
+        f32_to_decimal(bits as u32)
+    } else {
+        f64_to_decimal(bits)
+    };
+
+    let mut len = 0;
+    let mut rem = mantissa;
+    while rem != 0 {
+        rem /= 10;
+        len += 1;
+    }
+    for digit in buf[..len].iter_mut().rev() {
+        *digit = b'0' + (mantissa % 10) as u8;
+        mantissa /= 10;
+    }
+    (&buf[..len], (exp + len as i32) as i16)
+}
+
+/// Finds the shortest digits of an arbitrary `Decoded` value.
+#[cfg(not(any(feature = "strategy-ryu", feature = "strategy-dragonbox")))]
+pub(crate) fn format_shortest_decoded<'a>(decoded: &Decoded, buf: &'a mut [u8]) -> (&'a [u8], i16) {
+    crate::core_num::flt2dec::strategy::grisu::format_shortest(decoded, buf)
+}
+
+/// Finds the shortest digits of an arbitrary `Decoded` value.
+///
+/// This uses the reference implementation instead of Grisu, so ties are
+/// rounded to even like with the selected strategy.
+#[cfg(any(feature = "strategy-ryu", feature = "strategy-dragonbox"))]
+pub(crate) fn format_shortest_decoded<'a>(decoded: &Decoded, buf: &'a mut [u8]) -> (&'a [u8], i16) {
+    format_shortest_reference(decoded, buf)
+}
+
+/// Finds the shortest digits of an arbitrary `Decoded` value with Dragon,
+/// to cross-check the selected strategy. With Ryu and Dragonbox, ties are
+/// rounded to even like they do.
+pub(crate) fn format_shortest_reference<'a>(
+    decoded: &Decoded,
+    buf: &'a mut [u8],
+) -> (&'a [u8], i16) {
+    let (len, exp) = {
+        let (digits, exp) =
+            crate::core_num::flt2dec::strategy::dragon::format_shortest(decoded, buf);
+        (digits.len(), exp)
+    };
+    #[cfg(any(feature = "strategy-ryu", feature = "strategy-dragonbox"))]
+    round_tie_to_even(decoded, &mut buf[..len], exp);
+    (&buf[..len], exp)
+}
+
+/// Finds the shortest digits of `v` like `format_shortest`, but with ties
+/// rounded to even whatever the selected strategy, as required by formats
+/// such as JavaScript's `toString`.
+pub(crate) fn format_shortest_even<'a, T: Sealed>(
+    v: T,
+    decoded: &Decoded,
+    buf: &'a mut [u8],
+) -> (&'a [u8], i16) {
+    let (len, exp) = {
+        let (digits, exp) = format_shortest(v, decoded, buf);
+        (digits.len(), exp)
+    };
+    // Ryu and Dragonbox already round ties to even.
+    #[cfg(not(any(feature = "strategy-ryu", feature = "strategy-dragonbox")))]
+    round_tie_to_even(decoded, &mut buf[..len], exp);
+    (&buf[..len], exp)
+}
+
+/// When the value of `decoded` is halfway between `0.digits * 10^exp`
+/// (which Grisu and Dragon pick) and the number one unit in the last place
+/// below it, takes the lower one if its last digit is even and it is still
+/// within the rounding interval.
+fn round_tie_to_even(decoded: &Decoded, digits: &mut [u8], exp: i16) {
+    use crate::core_num::bignum::Big32x40 as Big;
+    use core::cmp::Ordering;
+
+    let last = digits.len() - 1;
+    if (digits[last] - b'0') % 2 == 0 {
+        return;
+    }
+
+    // With `d` the digits as an integer and `j = exp - len`, the tie is
+    // `(2 * d - 1) * 10^j / 2` and the lower candidate is `10^j / 2` below
+    // it. Everything is scaled by powers of 2 and 5 to compare integers.
+    let j = i32::from(exp) - digits.len() as i32;
+    let mut tie = Big::from_small(0);
+    for &c in digits.iter() {
+        tie.mul_small(10).add_small(u32::from(c - b'0'));
+    }
+    tie.mul_small(2).sub(&Big::from_small(1));
+    let mut half_ulp = Big::from_small(1);
+    let mut value = Big::from_u64(decoded.mant);
+    let mut minus = Big::from_u64(decoded.minus);
+    if j >= 0 {
+        tie.mul_pow5(j as usize);
+        half_ulp.mul_pow5(j as usize);
+    } else {
+        value.mul_pow5(-j as usize);
+        minus.mul_pow5(-j as usize);
+    }
+    let shift = i32::from(decoded.exp) - (j - 1);
+    if shift >= 0 {
+        value.mul_pow2(shift as usize);
+        minus.mul_pow2(shift as usize);
+    } else {
+        tie.mul_pow2(-shift as usize);
+        half_ulp.mul_pow2(-shift as usize);
+    }
+
+    if value == tie {
+        match half_ulp.cmp(&minus) {
+            Ordering::Less => digits[last] -= 1,
+            Ordering::Equal if decoded.inclusive => digits[last] -= 1,
+            _ => {}
+        }
+    }
+}
+
+#[cfg(feature = "strategy-dragonbox")]
+use self::dragonbox::{f32_to_decimal, f64_to_decimal};
+
+#[cfg(all(feature = "strategy-ryu", not(feature = "strategy-dragonbox")))]
+fn f32_to_decimal(bits: u32) -> (u64, i32) {
+    let (mantissa, exp) = ryu::f2d(bits);
+    (u64::from(mantissa), exp)
+}
+
+#[cfg(all(feature = "strategy-ryu", not(feature = "strategy-dragonbox")))]
+fn f64_to_decimal(bits: u64) -> (u64, i32) {
+    ryu::d2d(bits)
+}
@@ -0,0 +1,349 @@
+//! Ryu, by Ulf Adams ("Ryū: fast float-to-string conversion", PLDI 2018).
+//!
+//! Translated from the reference implementation
+//! (<https://github.com/ulfjack/ryu>, Apache-2.0 or BSL-1.0), via the Rust
+//! port of the `ryu` crate, without `unsafe` code.
+
+pub(crate) mod table;
+
+use self::table::{DOUBLE_POW5_INV_SPLIT, DOUBLE_POW5_SPLIT};
+
+const DOUBLE_MANTISSA_BITS: u32 = 52;
+const DOUBLE_BIAS: i32 = 1023;
+const DOUBLE_POW5_INV_BITCOUNT: i32 = 125;
+const DOUBLE_POW5_BITCOUNT: i32 = 125;
+
+const FLOAT_MANTISSA_BITS: u32 = 23;
+const FLOAT_BIAS: i32 = 127;
+const FLOAT_POW5_INV_BITCOUNT: i32 = DOUBLE_POW5_INV_BITCOUNT - 64;
+const FLOAT_POW5_BITCOUNT: i32 = DOUBLE_POW5_BITCOUNT - 64;
+
+/// Returns `ceil(log2(5^e))` (1 for `e == 0`); requires `0 <= e <= 3528`.
+fn pow5bits(e: i32) -> i32 {
+    debug_assert!((0..=3528).contains(&e));
+    (((e as u32 * 1217359) >> 19) + 1) as i32
+}
+
+/// Returns `floor(log10(2^e))`; requires `0 <= e <= 1650`.
+fn log10_pow2(e: i32) -> u32 {
+    debug_assert!((0..=1650).contains(&e));
+    (e as u32 * 78913) >> 18
+}
+
+/// Returns `floor(log10(5^e))`; requires `0 <= e <= 2620`.
+fn log10_pow5(e: i32) -> u32 {
+    debug_assert!((0..=2620).contains(&e));
+    (e as u32 * 732923) >> 20
+}
+
+fn pow5_factor(mut value: u64) -> u32 {
+    let mut count = 0;
+    while value % 5 == 0 {
+        value /= 5;
+        count += 1;
+    }
+    count
+}
+
+/// Returns true if `value` is divisible by `5^p`.
+fn multiple_of_power_of_5(value: u64, p: u32) -> bool {
+    pow5_factor(value) >= p
+}
+
+/// Returns true if `value` is divisible by `2^p`.
+fn multiple_of_power_of_2(value: u64, p: u32) -> bool {
+    debug_assert!(p < 64);
+    value & ((1 << p) - 1) == 0
+}
+
+fn mul_shift_64(m: u64, mul: u128, j: u32) -> u64 {
+    let b0 = u128::from(m) * (mul as u64 as u128);
+    let b2 = u128::from(m) * (mul >> 64);
+    (((b0 >> 64) + b2) >> (j - 64)) as u64
+}
+
+fn mul_shift_32(m: u32, factor: u64, shift: i32) -> u32 {
+    debug_assert!(shift > 32);
+    let bits0 = u64::from(m) * (factor as u32 as u64);
+    let bits1 = u64::from(m) * (factor >> 32);
+    let shifted_sum = ((bits0 >> 32) + bits1) >> (shift - 32);
+    debug_assert!(shifted_sum <= u64::from(u32::MAX));
+    shifted_sum as u32
+}
+
+fn mul_pow5_inv_div_pow2(m: u32, q: u32, j: i32) -> u32 {
+    // The upper 64 bits of the `f64` table are `floor(2^x / 5^y)`, the
+    // inverse multipliers are that plus one.
+    mul_shift_32(m, (DOUBLE_POW5_INV_SPLIT[q as usize] >> 64) as u64 + 1, j)
+}
+
+fn mul_pow5_div_pow2(m: u32, i: u32, j: i32) -> u32 {
+    mul_shift_32(m, (DOUBLE_POW5_SPLIT[i as usize] >> 64) as u64, j)
+}
+
+/// Converts the bits of a finite, non-zero `f64` to the shortest
+/// `(mantissa, exponent)` such that `mantissa * 10^exponent` rounds to it.
+pub(super) fn d2d(bits: u64) -> (u64, i32) {
+    let ieee_mantissa = bits & ((1 << DOUBLE_MANTISSA_BITS) - 1);
+    let ieee_exponent = ((bits >> DOUBLE_MANTISSA_BITS) & 0x7FF) as u32;
+    let (e2, m2) = if ieee_exponent == 0 {
+        // We subtract 2 so that the bounds computation has 2 additional bits.
+        (
+            1 - DOUBLE_BIAS - DOUBLE_MANTISSA_BITS as i32 - 2,
+            ieee_mantissa,
+        )
+    } else {
+        (
+            ieee_exponent as i32 - DOUBLE_BIAS - DOUBLE_MANTISSA_BITS as i32 - 2,
+            (1 << DOUBLE_MANTISSA_BITS) | ieee_mantissa,
+        )
+    };
+    let accept_bounds = m2 & 1 == 0;
+
+    // Step 2: Determine the interval of valid decimal representations.
+    let mv = 4 * m2;
+    let mm_shift = (ieee_mantissa != 0 || ieee_exponent <= 1) as u64;
+    let mp = mv + 2;
+    let mm = mv - 1 - mm_shift;
+
+    // Step 3: Convert to a decimal power base using 128-bit arithmetic.
+    let mut vr;
+    let mut vp;
+    let mut vm;
+    let e10;
+    let mut vm_is_trailing_zeros = false;
+    let mut vr_is_trailing_zeros = false;
+    if e2 >= 0 {
+        let q = log10_pow2(e2) - (e2 > 3) as u32;
+        e10 = q as i32;
+        let k = DOUBLE_POW5_INV_BITCOUNT + pow5bits(q as i32) - 1;
+        let i = (-e2 + q as i32 + k) as u32;
+        let mul = DOUBLE_POW5_INV_SPLIT[q as usize];
+        vr = mul_shift_64(mv, mul, i);
+        vp = mul_shift_64(mp, mul, i);
+        vm = mul_shift_64(mm, mul, i);
+        if q <= 21 {
+            // Only one of mp, mv, and mm can be a multiple of 5, if any.
+            if mv % 5 == 0 {
+                vr_is_trailing_zeros = multiple_of_power_of_5(mv, q);
+            } else if accept_bounds {
+                vm_is_trailing_zeros = multiple_of_power_of_5(mm, q);
+            } else {
+                vp -= multiple_of_power_of_5(mp, q) as u64;
+            }
+        }
+    } else {
+        let q = log10_pow5(-e2) - (-e2 > 1) as u32;
+        e10 = q as i32 + e2;
+        let i = -e2 - q as i32;
+        let k = pow5bits(i) - DOUBLE_POW5_BITCOUNT;
+        let j = (q as i32 - k) as u32;
+        let mul = DOUBLE_POW5_SPLIT[i as usize];
+        vr = mul_shift_64(mv, mul, j);
+        vp = mul_shift_64(mp, mul, j);
+        vm = mul_shift_64(mm, mul, j);
+        if q <= 1 {
+            // {vr,vp,vm} is trailing zeros if {mv,mp,mm} has at least q
+            // trailing 0 bits. mv = 4 * m2, so it always has at least two.
+            vr_is_trailing_zeros = true;
+            if accept_bounds {
+                // mm = mv - 1 - mm_shift, so it has 1 trailing 0 bit iff mm_shift == 1.
+                vm_is_trailing_zeros = mm_shift == 1;
+            } else {
+                // mp = mv + 2, so it always has at least one trailing 0 bit.
+                vp -= 1;
+            }
+        } else if q < 63 {
+            // We want to know if the full product has at least q trailing
+            // zeros, which is the case if p2(mv) >= q (because -e2 >= q).
+            vr_is_trailing_zeros = multiple_of_power_of_2(mv, q);
+        }
+    }
+
+    // Step 4: Find the shortest decimal representation in the interval of
+    // valid representations.
+    let mut removed = 0;
+    let mut last_removed_digit = 0;
+    let output = if vm_is_trailing_zeros || vr_is_trailing_zeros {
+        // General case, which happens rarely (~0.7%).
+        while vp / 10 > vm / 10 {
+            vm_is_trailing_zeros &= vm % 10 == 0;
+            vr_is_trailing_zeros &= last_removed_digit == 0;
+            last_removed_digit = vr % 10;
+            vr /= 10;
+            vp /= 10;
+            vm /= 10;
+            removed += 1;
+        }
+        if vm_is_trailing_zeros {
+            while vm % 10 == 0 {
+                vr_is_trailing_zeros &= last_removed_digit == 0;
+                last_removed_digit = vr % 10;
+                vr /= 10;
+                vm /= 10;
+                removed += 1;
+            }
+        }
+        if vr_is_trailing_zeros && last_removed_digit == 5 && vr % 2 == 0 {
+            // Round even if the exact number is .....50..0.
+            last_removed_digit = 4;
+        }
+        // We need to take vr + 1 if vr is outside bounds or we need to round up.
+        vr + ((vr == vm && (!accept_bounds || !vm_is_trailing_zeros)) || last_removed_digit >= 5)
+            as u64
+    } else {
+        // Specialized for the common case (~99.3%).
+        let mut round_up = false;
+        // Optimization: remove two digits at a time (~86.2%).
+        if vp / 100 > vm / 100 {
+            round_up = vr % 100 >= 50;
+            vr /= 100;
+            vp /= 100;
+            vm /= 100;
+            removed += 2;
+        }
+        while vp / 10 > vm / 10 {
+            round_up = vr % 10 >= 5;
+            vr /= 10;
+            vp /= 10;
+            vm /= 10;
+            removed += 1;
+        }
+        // We need to take vr + 1 if vr is outside bounds or we need to round up.
+        vr + (vr == vm || round_up) as u64
+    };
+
+    (output, e10 + removed)
+}
+
+/// Converts the bits of a finite, non-zero `f32` to the shortest
+/// `(mantissa, exponent)` such that `mantissa * 10^exponent` rounds to it.
+pub(super) fn f2d(bits: u32) -> (u32, i32) {
+    let ieee_mantissa = bits & ((1 << FLOAT_MANTISSA_BITS) - 1);
+    let ieee_exponent = (bits >> FLOAT_MANTISSA_BITS) & 0xFF;
+    let (e2, m2) = if ieee_exponent == 0 {
+        // We subtract 2 so that the bounds computation has 2 additional bits.
+        (
+            1 - FLOAT_BIAS - FLOAT_MANTISSA_BITS as i32 - 2,
+            ieee_mantissa,
+        )
+    } else {
+        (
+            ieee_exponent as i32 - FLOAT_BIAS - FLOAT_MANTISSA_BITS as i32 - 2,
+            (1 << FLOAT_MANTISSA_BITS) | ieee_mantissa,
+        )
+    };
+    let accept_bounds = m2 & 1 == 0;
+
+    // Step 2: Determine the interval of valid decimal representations.
+    let mv = 4 * m2;
+    let mp = 4 * m2 + 2;
+    let mm_shift = (ieee_mantissa != 0 || ieee_exponent <= 1) as u32;
+    let mm = 4 * m2 - 1 - mm_shift;
+
+    // Step 3: Convert to a decimal power base using 64-bit arithmetic.
+    let mut vr;
+    let mut vp;
+    let mut vm;
+    let e10;
+    let mut vm_is_trailing_zeros = false;
+    let mut vr_is_trailing_zeros = false;
+    let mut last_removed_digit = 0;
+    if e2 >= 0 {
+        let q = log10_pow2(e2);
+        e10 = q as i32;
+        let k = FLOAT_POW5_INV_BITCOUNT + pow5bits(q as i32) - 1;
+        let i = -e2 + q as i32 + k;
+        vr = mul_pow5_inv_div_pow2(mv, q, i);
+        vp = mul_pow5_inv_div_pow2(mp, q, i);
+        vm = mul_pow5_inv_div_pow2(mm, q, i);
+        if q != 0 && (vp - 1) / 10 <= vm / 10 {
+            // We need to know one removed digit even if we are not going to
+            // loop below.
+            let l = FLOAT_POW5_INV_BITCOUNT + pow5bits(q as i32 - 1) - 1;
+            last_removed_digit = mul_pow5_inv_div_pow2(mv, q - 1, -e2 + q as i32 - 1 + l) % 10;
+        }
+        if q <= 9 {
+            // Only one of mp, mv, and mm can be a multiple of 5, if any.
+            if mv % 5 == 0 {
+                vr_is_trailing_zeros = multiple_of_power_of_5(u64::from(mv), q);
+            } else if accept_bounds {
+                vm_is_trailing_zeros = multiple_of_power_of_5(u64::from(mm), q);
+            } else {
+                vp -= multiple_of_power_of_5(u64::from(mp), q) as u32;
+            }
+        }
+    } else {
+        let q = log10_pow5(-e2);
+        e10 = q as i32 + e2;
+        let i = -e2 - q as i32;
+        let k = pow5bits(i) - FLOAT_POW5_BITCOUNT;
+        let mut j = q as i32 - k;
+        vr = mul_pow5_div_pow2(mv, i as u32, j);
+        vp = mul_pow5_div_pow2(mp, i as u32, j);
+        vm = mul_pow5_div_pow2(mm, i as u32, j);
+        if q != 0 && (vp - 1) / 10 <= vm / 10 {
+            j = q as i32 - 1 - (pow5bits(i + 1) - FLOAT_POW5_BITCOUNT);
+            last_removed_digit = mul_pow5_div_pow2(mv, (i + 1) as u32, j) % 10;
+        }
+        if q <= 1 {
+            // {vr,vp,vm} is trailing zeros if {mv,mp,mm} has at least q
+            // trailing 0 bits. mv = 4 * m2, so it always has at least two.
+            vr_is_trailing_zeros = true;
+            if accept_bounds {
+                // mm = mv - 1 - mm_shift, so it has 1 trailing 0 bit iff mm_shift == 1.
+                vm_is_trailing_zeros = mm_shift == 1;
+            } else {
+                // mp = mv + 2, so it always has at least one trailing 0 bit.
+                vp -= 1;
+            }
+        } else if q < 31 {
+            vr_is_trailing_zeros = multiple_of_power_of_2(u64::from(mv), q - 1);
+        }
+    }
+
+    // Step 4: Find the shortest decimal representation in the interval of
+    // valid representations.
+    let mut removed = 0;
+    let output = if vm_is_trailing_zeros || vr_is_trailing_zeros {
+        // General case, which happens rarely (~4.0%).
+        while vp / 10 > vm / 10 {
+            vm_is_trailing_zeros &= vm % 10 == 0;
+            vr_is_trailing_zeros &= last_removed_digit == 0;
+            last_removed_digit = vr % 10;
+            vr /= 10;
+            vp /= 10;
+            vm /= 10;
+            removed += 1;
+        }
+        if vm_is_trailing_zeros {
+            while vm % 10 == 0 {
+                vr_is_trailing_zeros &= last_removed_digit == 0;
+                last_removed_digit = vr % 10;
+                vr /= 10;
+                vm /= 10;
+                removed += 1;
+            }
+        }
+        if vr_is_trailing_zeros && last_removed_digit == 5 && vr % 2 == 0 {
+            // Round even if the exact number is .....50..0.
+            last_removed_digit = 4;
+        }
+        // We need to take vr + 1 if vr is outside bounds or we need to round up.
+        vr + ((vr == vm && (!accept_bounds || !vm_is_trailing_zeros)) || last_removed_digit >= 5)
+            as u32
+    } else {
+        // Specialized for the common case (~96.0%).
+        while vp / 10 > vm / 10 {
+            last_removed_digit = vr % 10;
+            vr /= 10;
+            vp /= 10;
+            vm /= 10;
+            removed += 1;
+        }
+        // We need to take vr + 1 if vr is outside bounds or we need to round up.
+        vr + (vr == vm || last_removed_digit >= 5) as u32
+    };
+
+    (output, e10 + removed)
+}
@@ -0,0 +1,320 @@
+//! Conversion of decimal numbers whose digits are not in contiguous
+//! slices, such as the digits of a rope or of a tokenizer.
+//!
+//! An `Accumulator` receives the digits one at a time (as ASCII characters
+//! or as values, which allows other numeral systems through a
+//! `DigitDecoder`) and keeps the first
+//! `MAX_DIGITS` significant ones in a fixed buffer. More digits only
+//! matter to know whether the number is above a halfway point between two
+//! floats, which needs at most 768 digits, so the others are replaced by a
+//! single `1` when any of them is not zero. The result is the same as
+//! `FloatExt::from_preparsed` with all the digits.
+//!
+//! # Example
+//!
+//! ```
+//! use flt2dec2flt::stream::{self, Accumulator};
+//!
+//! let mut acc = Accumulator::new(false);
+//! let chunks: [&[u8]; 2] = [b"12", b"3"];
+//! for &c in chunks.iter().flat_map(|chunk| chunk.iter()) {
+//!     acc.push_int_digit(c).unwrap();
+//! }
+//! acc.push_frac_digit(b'5').unwrap();
+//! assert_eq!(acc.finish::<f64>(-1), Ok(12.35));
+//!
+//! let v = stream::from_digits_iter::<f32, _, _>(true, b"25".iter().copied(), None, -1);
+//! assert_eq!(v, Ok(-2.5));
+//! ```
+
+use crate::{FloatExt, ParseFloatError, PreParsed};
+
+/// Number of significant digits kept by an `Accumulator`.
+pub const MAX_DIGITS: usize = 800;
+
+/// Digits of a decimal number received one at a time.
+///
+/// The integer digits have to be pushed before the fractional ones.
+#[derive(Clone)]
+pub struct Accumulator {
+    sign: bool,
+    /// The kept significant digits, with room for the sticky digit.
+    buf: [u8; MAX_DIGITS + 1],
+    len: usize,
+    /// How many of the kept digits are integer digits.
+    int_len: usize,
+    /// Added to the exponent: the number of dropped integer digits minus
+    /// the number of leading fractional zeros.
+    exp_shift: i64,
+    /// Whether any dropped digit is not zero.
+    sticky: bool,
+    /// The number of received digits.
+    num_digits: usize,
+    in_frac: bool,
+    error: Option<ParseFloatError>,
+}
+
+impl Accumulator {
+    /// Creates an accumulator without digits, for a number that is
+    /// negative if `sign`.
+    pub fn new(sign: bool) -> Self {
+        Self {
+            sign,
+            buf: [0; MAX_DIGITS + 1],
+            len: 0,
+            int_len: 0,
+            exp_shift: 0,
+            sticky: false,
+            num_digits: 0,
+            in_frac: false,
+            error: None,
+        }
+    }
+
+    /// Appends an integer digit.
+    ///
+    /// Fails with `ParseFloatError::InvalidDigit` if `c` is not an ASCII
+    /// decimal digit, which is reported again by `finish`.
+    ///
+    /// Panics if a fractional digit has already been pushed.
+    pub fn push_int_digit(&mut self, c: u8) -> Result<(), ParseFloatError> {
+        self.push_int(c.wrapping_sub(b'0'), c)
+    }
+
+    /// Appends a fractional digit.
+    ///
+    /// Fails with `ParseFloatError::InvalidDigit` if `c` is not an ASCII
+    /// decimal digit, which is reported again by `finish`.
+    pub fn push_frac_digit(&mut self, c: u8) -> Result<(), ParseFloatError> {
+        self.push_frac(c.wrapping_sub(b'0'), c)
+    }
+
+    /// Like `push_int_digit`, with the value of the digit (`0` to `9`)
+    /// instead of its ASCII character.
+    pub fn push_int_value(&mut self, d: u8) -> Result<(), ParseFloatError> {
+        self.push_int(d, d)
+    }
+
+    /// Like `push_frac_digit`, with the value of the digit (`0` to `9`)
+    /// instead of its ASCII character.
+    pub fn push_frac_value(&mut self, d: u8) -> Result<(), ParseFloatError> {
+        self.push_frac(d, d)
+    }
+
+    /// Appends the integer digit with value `d`, which was given as `byte`.
+    fn push_int(&mut self, d: u8, byte: u8) -> Result<(), ParseFloatError> {
+        assert!(!self.in_frac, "integer digit after fractional digits");
+        self.check(d, byte)?;
+        if self.len == 0 && d == 0 {
+            // Leading zero.
+        } else if self.len < MAX_DIGITS {
+            self.buf[self.len] = b'0' + d;
+            self.len += 1;
+            self.int_len += 1;
+        } else {
+            self.exp_shift += 1;
+            self.sticky |= d != 0;
+        }
+        Ok(())
+    }
+
+    /// Appends the fractional digit with value `d`, which was given as
+    /// `byte`.
+    fn push_frac(&mut self, d: u8, byte: u8) -> Result<(), ParseFloatError> {
+        self.in_frac = true;
+        self.check(d, byte)?;
+        if self.len == 0 && d == 0 {
+            self.exp_shift -= 1;
+        } else if self.len < MAX_DIGITS {
+            self.buf[self.len] = b'0' + d;
+            self.len += 1;
+        } else {
+            self.sticky |= d != 0;
+        }
+        Ok(())
+    }
+
+    /// Counts a digit, failing if its value `d` is invalid or if an invalid
+    /// digit has already been pushed.
+    fn check(&mut self, d: u8, byte: u8) -> Result<(), ParseFloatError> {
+        let index = self.num_digits;
+        self.num_digits += 1;
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        if d > 9 {
+            let error = ParseFloatError::InvalidDigit { byte, index };
+            self.error = Some(error);
+            return Err(error);
+        }
+        Ok(())
+    }
+
+    /// Converts the digits, times `10^exp`, into the nearest floating
+    /// point number, like `FloatExt::from_preparsed`.
+    pub fn finish<T: FloatExt>(mut self, exp: i32) -> Result<T, ParseFloatError> {
+        if let Some(error) = self.error {
+            return Err(error);
+        } else if self.num_digits == 0 {
+            return Err(ParseFloatError::EmptyDigits);
+        }
+        if self.sticky {
+            self.buf[self.len] = b'1';
+            self.len += 1;
+        }
+        let (int_digits, frac_digits) = if self.len == 0 {
+            (&b"0"[..], &b""[..])
+        } else {
+            self.buf[..self.len].split_at(self.int_len)
+        };
+        let exp = i64::from(exp) + self.exp_shift;
+        let preparsed = PreParsed {
+            sign: self.sign,
+            int_digits,
+            frac_digits,
+            exp: exp.max(i64::from(i32::MIN)).min(i64::from(i32::MAX)) as i32,
+        };
+        T::from_preparsed(preparsed)
+    }
+}
+
+/// How the digits of a `PreParsed` are encoded.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum DigitEncoding {
+    /// ASCII characters, `b'0'` to `b'9'`.
+    Ascii,
+    /// Digit values, `0` to `9`.
+    Value,
+}
+
+/// Converts `preparsed`, whose digits are encoded with `encoding`, into the
+/// nearest floating point number, like `FloatExt::from_preparsed`.
+///
+/// `ParseFloatError::InvalidDigit` reports the byte as found in
+/// `preparsed`.
+///
+/// # Example
+///
+/// ```
+/// use flt2dec2flt::stream::{self, DigitEncoding};
+/// use flt2dec2flt::PreParsed;
+///
+/// let preparsed = PreParsed {
+///     sign: false,
+///     int_digits: &[1, 2],
+///     frac_digits: &[5],
+///     exp: 0,
+/// };
+/// let v = stream::from_preparsed_encoded::<f64>(preparsed, DigitEncoding::Value);
+/// assert_eq!(v, Ok(12.5));
+/// ```
+pub fn from_preparsed_encoded<T: FloatExt>(
+    preparsed: PreParsed<'_>,
+    encoding: DigitEncoding,
+) -> Result<T, ParseFloatError> {
+    match encoding {
+        DigitEncoding::Ascii => T::from_preparsed(preparsed),
+        DigitEncoding::Value => {
+            let mut acc = Accumulator::new(preparsed.sign);
+            for &d in preparsed.int_digits {
+                acc.push_int_value(d)?;
+            }
+            for &d in preparsed.frac_digits {
+                acc.push_frac_value(d)?;
+            }
+            acc.finish(preparsed.exp)
+        }
+    }
+}
+
+/// Maps the characters of a numeral system to digit values.
+///
+/// Implemented by closures `Fn(char) -> Option<u8>`.
+pub trait DigitDecoder {
+    /// Returns the value (`0` to `9`) of digit `c`, or `None` if `c` is not
+    /// a digit.
+    fn decode(&self, c: char) -> Option<u8>;
+}
+
+impl<F: Fn(char) -> Option<u8>> DigitDecoder for F {
+    #[inline]
+    fn decode(&self, c: char) -> Option<u8> {
+        self(c)
+    }
+}
+
+/// Converts `sign int_digits.frac_digits * 10^exp`, whose digits are
+/// characters mapped to values by `decoder`, into the nearest floating point
+/// number, like `FloatExt::from_preparsed`.
+///
+/// When a character is not a digit (`decoder` returns `None` or a value
+/// above 9), `ParseFloatError::InvalidDigit` reports its first UTF-8 byte
+/// and its byte position.
+///
+/// # Example
+///
+/// ```
+/// use flt2dec2flt::stream;
+///
+/// // Arabic-Indic digits.
+/// let decoder = |c: char| match c {
+///     '\u{660}'..='\u{669}' => Some((u32::from(c) - 0x660) as u8),
+///     _ => None,
+/// };
+/// let v = stream::from_str_decoded::<f64, _>(false, "\u{661}\u{662}", "\u{665}", 0, decoder);
+/// assert_eq!(v, Ok(12.5));
+/// ```
+pub fn from_str_decoded<T, D>(
+    sign: bool,
+    int_digits: &str,
+    frac_digits: &str,
+    exp: i32,
+    decoder: D,
+) -> Result<T, ParseFloatError>
+where
+    T: FloatExt,
+    D: DigitDecoder,
+{
+    let decode = |base: usize, (i, c): (usize, char)| match decoder.decode(c) {
+        Some(d) if d <= 9 => Ok(d),
+        _ => {
+            let mut buf = [0; 4];
+            Err(ParseFloatError::InvalidDigit {
+                byte: c.encode_utf8(&mut buf).as_bytes()[0],
+                index: base + i,
+            })
+        }
+    };
+    let mut acc = Accumulator::new(sign);
+    for item in int_digits.char_indices() {
+        acc.push_int_value(decode(0, item)?)?;
+    }
+    for item in frac_digits.char_indices() {
+        acc.push_frac_value(decode(int_digits.len(), item)?)?;
+    }
+    acc.finish(exp)
+}
+
+/// Converts `sign int_digits.frac_digits * 10^exp`, whose digits are given
+/// by iterators, into the nearest floating point number, like
+/// `FloatExt::from_preparsed`.
+pub fn from_digits_iter<T, I, F>(
+    sign: bool,
+    int_digits: I,
+    frac_digits: F,
+    exp: i32,
+) -> Result<T, ParseFloatError>
+where
+    T: FloatExt,
+    I: IntoIterator<Item = u8>,
+    F: IntoIterator<Item = u8>,
+{
+    let mut acc = Accumulator::new(sign);
+    for c in int_digits {
+        acc.push_int_digit(c)?;
+    }
+    for c in frac_digits {
+        acc.push_frac_digit(c)?;
+    }
+    acc.finish(exp)
+}
@@ -0,0 +1,126 @@
+//! Writing numbers in the style of another number.
+//!
+//! A `Style` records how a number was written: its number of fractional
+//! digits and whether and how it had an exponent. Tools that rewrite
+//! numbers in existing text (such as configuration file editors or code
+//! formatters) can use it to write a new value without changing how the
+//! number looks.
+//!
+//! # Example
+//!
+//! ```
+//! use flt2dec2flt::grammar::Grammar;
+//! use flt2dec2flt::style::{write_styled, Style};
+//! use flt2dec2flt::write::WriteOptions;
+//!
+//! let mut buf = [0; 32];
+//!
+//! let style = Style::from_text(&Grammar::RUST, b"1.50E+03").unwrap();
+//! let s = write_styled(&mut buf, 2718.28f64, &style, &WriteOptions::DEFAULT);
+//! assert_eq!(s, Ok("2.72E+03"));
+//!
+//! let style = Style::from_text(&Grammar::RUST, b"0.250").unwrap();
+//! let s = write_styled(&mut buf, 0.5f64, &style, &WriteOptions::DEFAULT);
+//! assert_eq!(s, Ok("0.500"));
+//! ```
+
+use crate::grammar::{Grammar, Parsed};
+use crate::write::{write_positional, write_scientific, ExpFormat, WriteOptions};
+use crate::{BufferTooSmall, FloatExt, PreParsed, SyntaxError};
+
+/// How a number is written.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Style {
+    /// Number of fractional digits, of the significand in scientific
+    /// notation.
+    pub frac_digits: usize,
+    /// The exponent marker (such as `e` or `E`) when the number is in
+    /// scientific notation.
+    pub exp_marker: Option<u8>,
+    /// How the exponent is written.
+    pub exp_format: ExpFormat,
+}
+
+impl Style {
+    /// Captures the style of a pre-parsed number.
+    ///
+    /// The number is considered to be in scientific notation if its
+    /// exponent is not zero, and the spelling of the exponent is unknown,
+    /// so the marker is `e` and `ExpFormat::DEFAULT` is used.
+    pub fn from_preparsed(preparsed: &PreParsed<'_>) -> Self {
+        Style {
+            frac_digits: preparsed.frac_digits.len(),
+            exp_marker: if preparsed.exp != 0 { Some(b'e') } else { None },
+            exp_format: ExpFormat::DEFAULT,
+        }
+    }
+
+    /// Captures the style of the number `s`, parsed with `grammar`.
+    ///
+    /// A `+` before the exponent and leading zeros in the exponent (such as
+    /// in `1e+05`) are kept. Special values and hexadecimal numbers have
+    /// no fractional digits and no exponent.
+    pub fn from_text(grammar: &Grammar<'_>, s: &[u8]) -> Result<Self, SyntaxError> {
+        let mut style = Style {
+            frac_digits: 0,
+            exp_marker: None,
+            exp_format: ExpFormat::DEFAULT,
+        };
+        let preparsed = match grammar.parse(s)? {
+            Parsed::Finite(preparsed) => preparsed,
+            _ => return Ok(style),
+        };
+        style.frac_digits = preparsed.frac_digits.len();
+
+        // The exponent is what follows the last exponent marker.
+        if let Some(i) = s.iter().rposition(|c| grammar.exp_markers.contains(c)) {
+            style.exp_marker = Some(s[i]);
+            let mut exp = &s[i + 1..];
+            if let Some((&b'+', rest)) = exp.split_first() {
+                style.exp_format.plus_sign = true;
+                exp = rest;
+            } else if let Some((&b'-', rest)) = exp.split_first() {
+                exp = rest;
+            }
+            if exp.len() > 1 && exp[0] == b'0' {
+                style.exp_format.min_digits = exp.len();
+            }
+        }
+        Ok(style)
+    }
+}
+
+/// Writes `v` in `style`, rounding it to `style.frac_digits` fractional
+/// digits (half to even).
+///
+/// The significand is written with a single integer digit in scientific
+/// notation. `options.exp_marker` and `options.exp_format` are replaced by
+/// those of `style`.
+pub fn write_styled<'a, T: FloatExt>(
+    buf: &'a mut [u8],
+    v: T,
+    style: &Style,
+    options: &WriteOptions<'_>,
+) -> Result<&'a str, BufferTooSmall> {
+    let mut digits_buf = [0; crate::PREFORMAT_EXACT_FIXED_BASE_BUF_LEN];
+    match style.exp_marker {
+        Some(marker) => {
+            let marker = [marker];
+            let options = WriteOptions {
+                // A non-ASCII marker cannot be written alone.
+                exp_marker: core::str::from_utf8(&marker).unwrap_or(options.exp_marker),
+                exp_format: style.exp_format,
+                ..*options
+            };
+            let preformatted = v.preformat_exact_exp(&mut digits_buf, style.frac_digits + 1);
+            write_scientific(buf, preformatted, style.frac_digits, &options)
+        }
+        None => {
+            let preformatted = v.preformat_exact_fixed(
+                &mut digits_buf,
+                style.frac_digits.min(isize::MAX as usize) as isize,
+            );
+            write_positional(buf, preformatted, style.frac_digits, options)
+        }
+    }
+}
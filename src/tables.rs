@@ -0,0 +1,163 @@
+//! Self-test of the lookup tables (see `verify_tables`).
+
+use crate::core_num::bignum::Big32x40 as Big;
+use crate::core_num::dec2flt::table::POWER_OF_FIVE_128;
+use crate::core_num::flt2dec::strategy::dragon;
+use crate::core_num::flt2dec::strategy::grisu::CACHED_POW10;
+use crate::TableCorruption;
+
+/// Checks the lookup tables used by the conversions against values
+/// computed at run time, to detect memory corruption (such as bit flips in
+/// ROM) before trusting the results.
+///
+/// Tables of exact powers of ten are recomputed with big integers. Tables
+/// of rounded powers, which would take much longer to recompute, are
+/// compared against checksums of their contents. Only the tables of the
+/// selected shortest-mode strategy are checked.
+///
+/// Small tables that the compiler embeds in the code (`const` items) are
+/// not checked, as they are part of the code itself.
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(flt2dec2flt::verify_tables(), Ok(()));
+/// ```
+pub fn verify_tables() -> Result<(), TableCorruption> {
+    verify_dragon()?;
+    check_sum(
+        "grisu::CACHED_POW10",
+        volatile(&CACHED_POW10).fold(FNV_OFFSET, |h, (f, e, k)| {
+            let h = fnv(h, &f.to_le_bytes());
+            let h = fnv(h, &e.to_le_bytes());
+            fnv(h, &k.to_le_bytes())
+        }),
+        0xdd37_39c7_3818_f8ad,
+    )?;
+    check_sum(
+        "dec2flt::POWER_OF_FIVE_128",
+        volatile(&POWER_OF_FIVE_128).fold(FNV_OFFSET, |h, (hi, lo)| {
+            fnv(fnv(h, &hi.to_le_bytes()), &lo.to_le_bytes())
+        }),
+        0x8743_9ad8_c519_e733,
+    )?;
+    verify_strategy()
+}
+
+/// Recomputes the powers of ten of Dragon.
+fn verify_dragon() -> Result<(), TableCorruption> {
+    let mut pow10 = 1;
+    for (i, (p, twop)) in volatile(&dragon::POW10)
+        .zip(volatile(&dragon::TWOPOW10))
+        .enumerate()
+    {
+        if p != pow10 || twop != 2 * pow10 {
+            let table = if p != pow10 {
+                "dragon::POW10"
+            } else {
+                "dragon::TWOPOW10"
+            };
+            return Err(TableCorruption {
+                table,
+                index: Some(i),
+            });
+        }
+        pow10 = pow10.wrapping_mul(10);
+    }
+
+    let big_pows: [(&'static str, &[u32]); 5] = [
+        ("dragon::POW10TO16", &dragon::POW10TO16),
+        ("dragon::POW10TO32", &dragon::POW10TO32),
+        ("dragon::POW10TO64", &dragon::POW10TO64),
+        ("dragon::POW10TO128", &dragon::POW10TO128),
+        ("dragon::POW10TO256", &dragon::POW10TO256),
+    ];
+    let mut expected = Big::from_small(1);
+    let mut exp = 0;
+    for &(table, digits) in big_pows.iter() {
+        // 10^16, 10^32, ...
+        let next_exp = if exp == 0 { 16 } else { exp * 2 };
+        expected.mul_pow5(next_exp - exp).mul_pow2(next_exp - exp);
+        exp = next_exp;
+        let expected_digits = expected.digits();
+        let len = digits.len().max(expected_digits.len());
+        let mut digits = volatile(digits);
+        let mut expected_digits = expected_digits.iter().copied();
+        if let Some(i) = (0..len).find(|_| digits.next() != expected_digits.next()) {
+            return Err(TableCorruption {
+                table,
+                index: Some(i),
+            });
+        }
+    }
+    Ok(())
+}
+
+// The Grisu tables are in `core_num`, and checked above.
+#[cfg(not(any(feature = "strategy-ryu", feature = "strategy-dragonbox")))]
+#[allow(clippy::unnecessary_wraps)]
+fn verify_strategy() -> Result<(), TableCorruption> {
+    Ok(())
+}
+
+#[cfg(all(feature = "strategy-ryu", not(feature = "strategy-dragonbox")))]
+fn verify_strategy() -> Result<(), TableCorruption> {
+    use crate::strategy::ryu::table::{DOUBLE_POW5_INV_SPLIT, DOUBLE_POW5_SPLIT};
+
+    check_sum(
+        "ryu::DOUBLE_POW5_INV_SPLIT",
+        fnv_u128(&DOUBLE_POW5_INV_SPLIT),
+        0x9059_0ac8_a580_44b7,
+    )?;
+    check_sum(
+        "ryu::DOUBLE_POW5_SPLIT",
+        fnv_u128(&DOUBLE_POW5_SPLIT),
+        0x0a8f_348f_7cf6_1776,
+    )
+}
+
+#[cfg(feature = "strategy-dragonbox")]
+fn verify_strategy() -> Result<(), TableCorruption> {
+    use crate::strategy::dragonbox::table::{F32_CACHE, F64_CACHE};
+
+    let f32_sum = volatile(&F32_CACHE).fold(FNV_OFFSET, |h, v| fnv(h, &v.to_le_bytes()));
+    check_sum("dragonbox::F32_CACHE", f32_sum, 0x74d3_d37e_8b01_241a)?;
+    check_sum(
+        "dragonbox::F64_CACHE",
+        fnv_u128(&F64_CACHE),
+        0x4eed_893e_19b5_ebfa,
+    )
+}
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+
+/// FNV-1a, which detects any change of a single byte.
+fn fnv(mut hash: u64, bytes: &[u8]) -> u64 {
+    for &b in bytes {
+        hash ^= u64::from(b);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
+
+#[cfg(any(feature = "strategy-ryu", feature = "strategy-dragonbox"))]
+fn fnv_u128(table: &[u128]) -> u64 {
+    volatile(table).fold(FNV_OFFSET, |h, v| fnv(h, &v.to_le_bytes()))
+}
+
+fn check_sum(table: &'static str, actual: u64, expected: u64) -> Result<(), TableCorruption> {
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(TableCorruption { table, index: None })
+    }
+}
+
+/// Reads the elements of `table` with volatile reads, so they are loaded
+/// from memory instead of being folded into the checks at compile time.
+fn volatile<T: Copy>(table: &[T]) -> impl Iterator<Item = T> + '_ {
+    table.iter().map(|v| {
+        // SAFETY: `v` is a reference, so it is valid for reads and aligned.
+        unsafe { core::ptr::read_volatile(v) }
+    })
+}
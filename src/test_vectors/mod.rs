@@ -0,0 +1,89 @@
+//! Classic test vectors for float-string conversions.
+//!
+//! These are the hard cases that conversion algorithms are usually checked
+//! against, compiled into the crate so other implementations can validate
+//! themselves without network access or a reference implementation:
+//!
+//! * `paxson_f64` and `paxson_f32`: the stress inputs of V. Paxson, "A
+//!   Program for Testing IEEE Decimal-Binary Conversion", which are
+//!   extremely close to halfway between two numbers.
+//! * `classic_f64` and `classic_f32`: inputs that broke widely used parsers,
+//!   exact halfway points (with one more or less in the last digit) and
+//!   range limits, including overflow and underflow.
+//! * `shortest_f64` and `shortest_f32`: range limits, powers of two and ten
+//!   and the regression inputs of Ryu, with their shortest representation.
+//!
+//! All of them are only available with the `test-vectors` feature.
+//!
+//! # Example
+//!
+//! ```
+//! use flt2dec2flt::grammar::Grammar;
+//! use flt2dec2flt::test_vectors;
+//!
+//! for v in test_vectors::paxson_f64().chain(test_vectors::classic_f64()) {
+//!     let parsed = Grammar::RUST.parse(v.text.as_bytes()).unwrap();
+//!     let parsed: f64 = parsed.to_float().unwrap();
+//!     assert_eq!(parsed.to_bits(), v.bits, "{}", v.text);
+//! }
+//! ```
+
+mod table;
+
+use core::slice;
+
+/// A decimal text and the number it must be parsed into.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ParseVector {
+    /// The decimal text, in the syntax accepted by `str::parse`.
+    pub text: &'static str,
+    /// The bits of the result, rounded to nearest (ties to even). They are
+    /// the bits of an `f32` in the `f32` tables.
+    pub bits: u64,
+}
+
+/// A number and its shortest representation.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ShortestVector {
+    /// The bits of the number (positive and finite). They are the bits of
+    /// an `f32` in the `f32` tables.
+    pub bits: u64,
+    /// The significant digits, as returned by
+    /// `FloatExt::preformat_shortest`.
+    pub digits: &'static str,
+    /// The exponent, such that the number is `0.digits * 10^exp`.
+    pub exp: i16,
+}
+
+/// Returns the Paxson stress inputs for `f64`.
+pub fn paxson_f64() -> slice::Iter<'static, ParseVector> {
+    table::PAXSON_F64.iter()
+}
+
+/// Returns the Paxson stress inputs for `f32`.
+///
+/// Some of them are only hard with an unbounded exponent, and are
+/// subnormal `f32` numbers.
+pub fn paxson_f32() -> slice::Iter<'static, ParseVector> {
+    table::PAXSON_F32.iter()
+}
+
+/// Returns well-known hard inputs for `f64`.
+pub fn classic_f64() -> slice::Iter<'static, ParseVector> {
+    table::CLASSIC_F64.iter()
+}
+
+/// Returns well-known hard inputs for `f32`.
+pub fn classic_f32() -> slice::Iter<'static, ParseVector> {
+    table::CLASSIC_F32.iter()
+}
+
+/// Returns `f64` numbers with their shortest representation.
+pub fn shortest_f64() -> slice::Iter<'static, ShortestVector> {
+    table::SHORTEST_F64.iter()
+}
+
+/// Returns `f32` numbers with their shortest representation.
+pub fn shortest_f32() -> slice::Iter<'static, ShortestVector> {
+    table::SHORTEST_F32.iter()
+}
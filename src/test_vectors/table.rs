@@ -0,0 +1,256 @@
+//! Tables of test vectors.
+//!
+//! The expected results were computed with exact rational arithmetic,
+//! independently of this crate: `bits` is the text rounded to nearest (ties
+//! to even) and, for the shortest representations, `digits` are the fewest
+//! significant digits that round back to the number, and the closest of
+//! them to it. Numbers with two equally close candidates are left out.
+
+use super::{ParseVector, ShortestVector};
+
+// Stress inputs for conversion to 53-bit binary (Paxson, table 2).
+#[rustfmt::skip]
+pub(super) static PAXSON_F64: [ParseVector; 20] = [
+    ParseVector { text: "9e-265", bits: 0x091d05244fe5066a },
+    ParseVector { text: "85e-37", bits: 0x38a698ccdc60015a },
+    ParseVector { text: "623e+100", bits: 0x554640a62f3a83df },
+    ParseVector { text: "3571e+263", bits: 0x77462644c61d41aa },
+    ParseVector { text: "81661e+153", bits: 0x60b7ca8e3d68578e },
+    ParseVector { text: "920657e-23", bits: 0x3c653a9985dbde6c },
+    ParseVector { text: "87575437e-309", bits: 0x016e07320602056c },
+    ParseVector { text: "245540327e+122", bits: 0x5b01b6231e18c5cb },
+    ParseVector { text: "83356057653e+193", bits: 0x6a4544e6daee2a18 },
+    ParseVector { text: "619534293513e+124", bits: 0x5c210c20303fe0f1 },
+    ParseVector { text: "2335141086879e+218", bits: 0x6fc340a1c932c1ee },
+    ParseVector { text: "36167929443327e-159", bits: 0x21bce77c2b3328fc },
+    ParseVector { text: "609610927149051e-255", bits: 0x0e104273b18918b1 },
+    ParseVector { text: "3743626360493413e-165", bits: 0x20e8823a57adbef9 },
+    ParseVector { text: "94080055902682397e-242", bits: 0x11364981e39e66ca },
+    ParseVector { text: "899810892172646163e+283", bits: 0x7e6adf51fa055e03 },
+    ParseVector { text: "7120190517612959703e+120", bits: 0x5cc3220dcd5899fd },
+    ParseVector { text: "25188282901709339043e-252", bits: 0x0fa4059af3db2a84 },
+    ParseVector { text: "308984926168550152811e-52", bits: 0x39640de48676653b },
+    ParseVector { text: "6372891218502368041059e+64", bits: 0x51c067047dbb38fe },
+];
+
+// Stress inputs for conversion to 24-bit binary (Paxson, table 1).
+#[rustfmt::skip]
+pub(super) static PAXSON_F32: [ParseVector; 12] = [
+    ParseVector { text: "5e-20", bits: 0x1f6c1e4a },
+    ParseVector { text: "67e+14", bits: 0x59be6cea },
+    ParseVector { text: "985e+15", bits: 0x5d5ab6c4 },
+    ParseVector { text: "55895e-16", bits: 0x2cc4a9bd },
+    ParseVector { text: "7038531e-32", bits: 0x15ae43fd },
+    ParseVector { text: "702990899e-20", bits: 0x2cf757ca },
+    ParseVector { text: "25933168707e+13", bits: 0x665ba998 },
+    ParseVector { text: "596428896559e+20", bits: 0x743c3324 },
+    ParseVector { text: "7693e-42", bits: 0x0053c4f4 },
+    ParseVector { text: "996622e-44", bits: 0x006c85c4 },
+    ParseVector { text: "60419369e-46", bits: 0x0041ca76 },
+    ParseVector { text: "6930161142e-48", bits: 0x004b7678 },
+];
+
+// Inputs that broke widely used parsers, halfway points and range limits.
+#[rustfmt::skip]
+pub(super) static CLASSIC_F64: [ParseVector; 26] = [
+    ParseVector { text: "2.2250738585072012e-308", bits: 0x0010000000000000 },
+    ParseVector { text: "2.2250738585072011e-308", bits: 0x000fffffffffffff },
+    ParseVector { text: "2.2250738585072014e-308", bits: 0x0010000000000000 },
+    ParseVector { text: "2.225073858507201136057409796709131975934819546351645648e-308", bits: 0x000fffffffffffff },
+    ParseVector { text: "9007199254740993", bits: 0x4340000000000000 },
+    ParseVector { text: "9007199254740995", bits: 0x4340000000000002 },
+    ParseVector { text: "9007199254740992.9999999999999999", bits: 0x4340000000000000 },
+    ParseVector { text: "1e23", bits: 0x44b52d02c7e14af6 },
+    ParseVector { text: "100000000000000000000000", bits: 0x44b52d02c7e14af6 },
+    ParseVector { text: "8.98846567431158e307", bits: 0x7fe0000000000000 },
+    ParseVector { text: "1.7976931348623157e308", bits: 0x7fefffffffffffff },
+    ParseVector { text: "1.7976931348623158e308", bits: 0x7fefffffffffffff },
+    ParseVector { text: "1.7976931348623159e308", bits: 0x7ff0000000000000 },
+    ParseVector { text: "4.9406564584124654e-324", bits: 0x0000000000000001 },
+    ParseVector { text: "2.4703282292062327e-324", bits: 0x0000000000000000 },
+    ParseVector { text: "2.4703282292062328e-324", bits: 0x0000000000000001 },
+    ParseVector { text: "1.00000000000000011102230246251565404236316680908203125", bits: 0x3ff0000000000000 },
+    ParseVector { text: "1.00000000000000011102230246251565404236316680908203124", bits: 0x3ff0000000000000 },
+    ParseVector { text: "1.00000000000000011102230246251565404236316680908203126", bits: 0x3ff0000000000001 },
+    ParseVector { text: "0.1", bits: 0x3fb999999999999a },
+    ParseVector { text: "0.3", bits: 0x3fd3333333333333 },
+    ParseVector { text: "0.30000000000000004", bits: 0x3fd3333333333334 },
+    ParseVector { text: "1e-400", bits: 0x0000000000000000 },
+    ParseVector { text: "1e400", bits: 0x7ff0000000000000 },
+    ParseVector { text: "-0.0", bits: 0x8000000000000000 },
+    ParseVector { text: "123456789012345678901234567890e-10", bits: 0x43e56a95319d63e1 },
+];
+
+// Halfway points and range limits of `f32`.
+#[rustfmt::skip]
+pub(super) static CLASSIC_F32: [ParseVector; 17] = [
+    ParseVector { text: "1.00000005960464477539062499", bits: 0x3f800000 },
+    ParseVector { text: "1.000000059604644775390625", bits: 0x3f800000 },
+    ParseVector { text: "1.00000005960464477539062501", bits: 0x3f800001 },
+    ParseVector { text: "16777217", bits: 0x4b800000 },
+    ParseVector { text: "16777219", bits: 0x4b800002 },
+    ParseVector { text: "3.4028235e38", bits: 0x7f7fffff },
+    ParseVector { text: "3.4028236e38", bits: 0x7f800000 },
+    ParseVector { text: "3.40282357e38", bits: 0x7f800000 },
+    ParseVector { text: "1.4e-45", bits: 0x00000001 },
+    ParseVector { text: "7e-46", bits: 0x00000000 },
+    ParseVector { text: "7.1e-46", bits: 0x00000001 },
+    ParseVector { text: "1.1754942e-38", bits: 0x007fffff },
+    ParseVector { text: "1.17549435e-38", bits: 0x00800000 },
+    ParseVector { text: "0.1", bits: 0x3dcccccd },
+    ParseVector { text: "1e-50", bits: 0x00000000 },
+    ParseVector { text: "1e50", bits: 0x7f800000 },
+    ParseVector { text: "-0.0", bits: 0x80000000 },
+];
+
+// Range limits, powers of two and ten, and regression inputs of Ryu.
+#[rustfmt::skip]
+pub(super) static SHORTEST_F64: [ShortestVector; 76] = [
+    ShortestVector { bits: 0x0000000000000001, digits: "5", exp: -323 },
+    ShortestVector { bits: 0x0000000000000002, digits: "1", exp: -322 },
+    ShortestVector { bits: 0x0000000000000003, digits: "15", exp: -322 },
+    ShortestVector { bits: 0x000fffffffffffff, digits: "2225073858507201", exp: -307 },
+    ShortestVector { bits: 0x0010000000000001, digits: "2225073858507202", exp: -307 },
+    ShortestVector { bits: 0x7feffffffffffffe, digits: "17976931348623155", exp: 309 },
+    ShortestVector { bits: 0x3ff0000000000001, digits: "10000000000000002", exp: 1 },
+    ShortestVector { bits: 0x3fefffffffffffff, digits: "9999999999999999", exp: 0 },
+    ShortestVector { bits: 0x0010000000000000, digits: "22250738585072014", exp: -307 },
+    ShortestVector { bits: 0x0020000000000000, digits: "4450147717014403", exp: -307 },
+    ShortestVector { bits: 0x0170000000000000, digits: "9332636185032189", exp: -301 },
+    ShortestVector { bits: 0x39b0000000000000, digits: "7888609052210118", exp: -30 },
+    ShortestVector { bits: 0x3fe0000000000000, digits: "5", exp: 0 },
+    ShortestVector { bits: 0x3ff0000000000000, digits: "1", exp: 1 },
+    ShortestVector { bits: 0x4000000000000000, digits: "2", exp: 1 },
+    ShortestVector { bits: 0x4330000000000000, digits: "4503599627370496", exp: 16 },
+    ShortestVector { bits: 0x4340000000000000, digits: "9007199254740992", exp: 16 },
+    ShortestVector { bits: 0x4350000000000000, digits: "18014398509481984", exp: 17 },
+    ShortestVector { bits: 0x43e0000000000000, digits: "9223372036854776", exp: 19 },
+    ShortestVector { bits: 0x43f0000000000000, digits: "18446744073709552", exp: 20 },
+    ShortestVector { bits: 0x4630000000000000, digits: "12676506002282294", exp: 31 },
+    ShortestVector { bits: 0x7e70000000000000, digits: "10715086071862673", exp: 302 },
+    ShortestVector { bits: 0x7fe0000000000000, digits: "898846567431158", exp: 308 },
+    ShortestVector { bits: 0x3ddb7cdfd9d7bdbb, digits: "1", exp: -9 },
+    ShortestVector { bits: 0x3e112e0be826d695, digits: "1", exp: -8 },
+    ShortestVector { bits: 0x3e45798ee2308c3a, digits: "1", exp: -7 },
+    ShortestVector { bits: 0x3e7ad7f29abcaf48, digits: "1", exp: -6 },
+    ShortestVector { bits: 0x3eb0c6f7a0b5ed8d, digits: "1", exp: -5 },
+    ShortestVector { bits: 0x3ee4f8b588e368f1, digits: "1", exp: -4 },
+    ShortestVector { bits: 0x3f1a36e2eb1c432d, digits: "1", exp: -3 },
+    ShortestVector { bits: 0x3f50624dd2f1a9fc, digits: "1", exp: -2 },
+    ShortestVector { bits: 0x3f847ae147ae147b, digits: "1", exp: -1 },
+    ShortestVector { bits: 0x3fb999999999999a, digits: "1", exp: 0 },
+    ShortestVector { bits: 0x4024000000000000, digits: "1", exp: 2 },
+    ShortestVector { bits: 0x4059000000000000, digits: "1", exp: 3 },
+    ShortestVector { bits: 0x408f400000000000, digits: "1", exp: 4 },
+    ShortestVector { bits: 0x40c3880000000000, digits: "1", exp: 5 },
+    ShortestVector { bits: 0x40f86a0000000000, digits: "1", exp: 6 },
+    ShortestVector { bits: 0x412e848000000000, digits: "1", exp: 7 },
+    ShortestVector { bits: 0x416312d000000000, digits: "1", exp: 8 },
+    ShortestVector { bits: 0x4197d78400000000, digits: "1", exp: 9 },
+    ShortestVector { bits: 0x41cdcd6500000000, digits: "1", exp: 10 },
+    ShortestVector { bits: 0x4202a05f20000000, digits: "1", exp: 11 },
+    ShortestVector { bits: 0x42374876e8000000, digits: "1", exp: 12 },
+    ShortestVector { bits: 0x426d1a94a2000000, digits: "1", exp: 13 },
+    ShortestVector { bits: 0x42a2309ce5400000, digits: "1", exp: 14 },
+    ShortestVector { bits: 0x42d6bcc41e900000, digits: "1", exp: 15 },
+    ShortestVector { bits: 0x430c6bf526340000, digits: "1", exp: 16 },
+    ShortestVector { bits: 0x4341c37937e08000, digits: "1", exp: 17 },
+    ShortestVector { bits: 0x4376345785d8a000, digits: "1", exp: 18 },
+    ShortestVector { bits: 0x43abc16d674ec800, digits: "1", exp: 19 },
+    ShortestVector { bits: 0x43e158e460913d00, digits: "1", exp: 20 },
+    ShortestVector { bits: 0x4415af1d78b58c40, digits: "1", exp: 21 },
+    ShortestVector { bits: 0x444b1ae4d6e2ef50, digits: "1", exp: 22 },
+    ShortestVector { bits: 0x4480f0cf064dd592, digits: "1", exp: 23 },
+    ShortestVector { bits: 0x44b52d02c7e14af6, digits: "1", exp: 24 },
+    ShortestVector { bits: 0x44591d67fecc8000, digits: "18531501765868567", exp: 22 },
+    ShortestVector { bits: 0x46e4a1c85222906d, digits: "3347727380279489", exp: 34 },
+    ShortestVector { bits: 0x45300c520a43f0af, digits: "19400994884341945", exp: 26 },
+    ShortestVector { bits: 0x4830f0cf064dd592, digits: "5764607523034235", exp: 40 },
+    ShortestVector { bits: 0x4840f0cf064dd592, digits: "1152921504606847", exp: 41 },
+    ShortestVector { bits: 0x4850f0cf064dd592, digits: "2305843009213694", exp: 41 },
+    ShortestVector { bits: 0x0040000000000000, digits: "17800590868057611", exp: -306 },
+    ShortestVector { bits: 0x007fffffffffffff, digits: "28480945388892175", exp: -305 },
+    ShortestVector { bits: 0x0290000000000000, digits: "2446494580089078", exp: -295 },
+    ShortestVector { bits: 0x029fffffffffffff, digits: "48929891601781557", exp: -295 },
+    ShortestVector { bits: 0x435fffffffffffff, digits: "36028797018963964", exp: 17 },
+    ShortestVector { bits: 0x1330000000000000, digits: "2900835519859558", exp: -215 },
+    ShortestVector { bits: 0x133fffffffffffff, digits: "5801671039719115", exp: -215 },
+    ShortestVector { bits: 0x3a6fa7161a4d6e0c, digits: "3196104012172126", exp: -26 },
+    ShortestVector { bits: 0x3fc999999999999a, digits: "2", exp: 0 },
+    ShortestVector { bits: 0x3fd3333333333333, digits: "3", exp: 0 },
+    ShortestVector { bits: 0x3fd3333333333334, digits: "30000000000000004", exp: 0 },
+    ShortestVector { bits: 0x4340000000000001, digits: "9007199254740994", exp: 16 },
+    ShortestVector { bits: 0x7fefffffffffffff, digits: "17976931348623157", exp: 309 },
+    ShortestVector { bits: 0x419d6f3454000000, digits: "123456789", exp: 9 },
+];
+
+// Range limits, powers of two and ten, and regression inputs of Ryu.
+#[rustfmt::skip]
+pub(super) static SHORTEST_F32: [ShortestVector; 66] = [
+    ShortestVector { bits: 0x00000001, digits: "1", exp: -44 },
+    ShortestVector { bits: 0x00000002, digits: "3", exp: -44 },
+    ShortestVector { bits: 0x007fffff, digits: "11754942", exp: -37 },
+    ShortestVector { bits: 0x00800001, digits: "11754945", exp: -37 },
+    ShortestVector { bits: 0x7f7ffffe, digits: "34028233", exp: 39 },
+    ShortestVector { bits: 0x3f800001, digits: "10000001", exp: 1 },
+    ShortestVector { bits: 0x3f7fffff, digits: "99999994", exp: 0 },
+    ShortestVector { bits: 0x00800000, digits: "11754944", exp: -37 },
+    ShortestVector { bits: 0x01000000, digits: "23509887", exp: -37 },
+    ShortestVector { bits: 0x0d800000, digits: "7888609", exp: -30 },
+    ShortestVector { bits: 0x3f000000, digits: "5", exp: 0 },
+    ShortestVector { bits: 0x3f800000, digits: "1", exp: 1 },
+    ShortestVector { bits: 0x40000000, digits: "2", exp: 1 },
+    ShortestVector { bits: 0x4b000000, digits: "8388608", exp: 7 },
+    ShortestVector { bits: 0x4b800000, digits: "16777216", exp: 8 },
+    ShortestVector { bits: 0x4c000000, digits: "33554432", exp: 8 },
+    ShortestVector { bits: 0x71800000, digits: "12676506", exp: 31 },
+    ShortestVector { bits: 0x7f000000, digits: "17014118", exp: 39 },
+    ShortestVector { bits: 0x2edbe6ff, digits: "1", exp: -9 },
+    ShortestVector { bits: 0x3089705f, digits: "1", exp: -8 },
+    ShortestVector { bits: 0x322bcc77, digits: "1", exp: -7 },
+    ShortestVector { bits: 0x33d6bf95, digits: "1", exp: -6 },
+    ShortestVector { bits: 0x358637bd, digits: "1", exp: -5 },
+    ShortestVector { bits: 0x3727c5ac, digits: "1", exp: -4 },
+    ShortestVector { bits: 0x38d1b717, digits: "1", exp: -3 },
+    ShortestVector { bits: 0x3a83126f, digits: "1", exp: -2 },
+    ShortestVector { bits: 0x3c23d70a, digits: "1", exp: -1 },
+    ShortestVector { bits: 0x3dcccccd, digits: "1", exp: 0 },
+    ShortestVector { bits: 0x41200000, digits: "1", exp: 2 },
+    ShortestVector { bits: 0x42c80000, digits: "1", exp: 3 },
+    ShortestVector { bits: 0x447a0000, digits: "1", exp: 4 },
+    ShortestVector { bits: 0x461c4000, digits: "1", exp: 5 },
+    ShortestVector { bits: 0x47c35000, digits: "1", exp: 6 },
+    ShortestVector { bits: 0x49742400, digits: "1", exp: 7 },
+    ShortestVector { bits: 0x4b189680, digits: "1", exp: 8 },
+    ShortestVector { bits: 0x4cbebc20, digits: "1", exp: 9 },
+    ShortestVector { bits: 0x4e6e6b28, digits: "1", exp: 10 },
+    ShortestVector { bits: 0x501502f9, digits: "1", exp: 11 },
+    ShortestVector { bits: 0x510006a8, digits: "3436672", exp: 11 },
+    ShortestVector { bits: 0x63800000, digits: "47223665", exp: 22 },
+    ShortestVector { bits: 0x4c000001, digits: "33554436", exp: 8 },
+    ShortestVector { bits: 0x4c800b0d, digits: "67131496", exp: 8 },
+    ShortestVector { bits: 0x00d24584, digits: "19310392", exp: -37 },
+    ShortestVector { bits: 0x000000b0, digits: "247", exp: -42 },
+    ShortestVector { bits: 0x00d90b88, digits: "1993244", exp: -37 },
+    ShortestVector { bits: 0x45803f34, digits: "41039004", exp: 4 },
+    ShortestVector { bits: 0x4f9f24f7, digits: "53399997", exp: 10 },
+    ShortestVector { bits: 0x00424fe2, digits: "60898", exp: -38 },
+    ShortestVector { bits: 0x3a8722c3, digits: "10310042", exp: -2 },
+    ShortestVector { bits: 0x5c800041, digits: "2882326", exp: 18 },
+    ShortestVector { bits: 0x15ae43fd, digits: "7038531", exp: -25 },
+    ShortestVector { bits: 0x5d4cccfb, digits: "9223404", exp: 18 },
+    ShortestVector { bits: 0x4c800001, digits: "6710887", exp: 8 },
+    ShortestVector { bits: 0x00000007, digits: "1", exp: -43 },
+    ShortestVector { bits: 0x57800ed8, digits: "2816025", exp: 15 },
+    ShortestVector { bits: 0x5f000000, digits: "9223372", exp: 19 },
+    ShortestVector { bits: 0x700000f0, digits: "15846086", exp: 30 },
+    ShortestVector { bits: 0x5f23e9ac, digits: "11811161", exp: 20 },
+    ShortestVector { bits: 0x5e9502f9, digits: "5368709", exp: 19 },
+    ShortestVector { bits: 0x5e8012b1, digits: "46143166", exp: 19 },
+    ShortestVector { bits: 0x3c000028, digits: "7812537", exp: -2 },
+    ShortestVector { bits: 0x60cde861, digits: "118697725", exp: 21 },
+    ShortestVector { bits: 0x03aa2a50, digits: "100014165", exp: -35 },
+    ShortestVector { bits: 0x43480000, digits: "2", exp: 3 },
+    ShortestVector { bits: 0x3e99999a, digits: "3", exp: 0 },
+    ShortestVector { bits: 0x7f7fffff, digits: "34028235", exp: 39 },
+];
@@ -1,4 +1,9 @@
-use crate::{FloatExt, PreFormatted, PreParsed};
+// The conversions are exact, and so are the expected values.
+#![allow(clippy::float_cmp)]
+// Values halfway between two shortest representations are written exactly.
+#![allow(clippy::excessive_precision)]
+
+use crate::{core_num, FloatExt, ParseFloatError, PreFormatted, PreParsed};
 
 trait FloatApprox {
     fn approx(self, other: Self, error: Self) -> bool;
@@ -55,7 +60,8 @@ impl<F: Copy + FloatExt + FloatApprox + std::fmt::Display> Test<'_, F> {
         for &(num_frac_digits, preformatted) in self.preformatted_exact_fixed.iter() {
             let mut buf = std::vec![0; crate::PREFORMAT_EXACT_FIXED_BASE_BUF_LEN + num_frac_digits];
             assert_eq!(
-                self.value.preformat_exact_fixed(&mut buf, num_frac_digits),
+                self.value
+                    .preformat_exact_fixed(&mut buf, num_frac_digits as isize),
                 preformatted
             );
         }
@@ -71,7 +77,7 @@ fn test_f32() {
                 PreParsed {
                     sign: false,
                     int_digits: b"",
-                    frac_digits: b"",
+                    frac_digits: b"0",
                     exp: 0,
                 },
                 0.0,
@@ -97,7 +103,7 @@ fn test_f32() {
         preparsed: &[(
             PreParsed {
                 sign: true,
-                int_digits: b"",
+                int_digits: b"0",
                 frac_digits: b"",
                 exp: 0,
             },
@@ -362,7 +368,7 @@ fn test_f64() {
                 PreParsed {
                     sign: false,
                     int_digits: b"",
-                    frac_digits: b"",
+                    frac_digits: b"0",
                     exp: 0,
                 },
                 0.0,
@@ -388,7 +394,7 @@ fn test_f64() {
         preparsed: &[(
             PreParsed {
                 sign: true,
-                int_digits: b"",
+                int_digits: b"0",
                 frac_digits: b"",
                 exp: 0,
             },
@@ -478,3 +484,6976 @@ fn test_f64() {
     }
     .run();
 }
+
+#[test]
+fn test_exact_fixed_base_buf_len() {
+    // The smallest exponents need all of the base buffer.
+    for &(v, expected_exp) in &[
+        (f64::MIN_POSITIVE, -307),
+        (2.0 * f64::MIN_POSITIVE, -307),
+        (f64::from_bits(1), -323),
+    ] {
+        let mut buf = [0; crate::PREFORMAT_EXACT_FIXED_BASE_BUF_LEN];
+        let mut big_buf = [0; crate::PREFORMAT_EXACT_FIXED_BASE_BUF_LEN + 1100];
+        let preformatted = v.preformat_exact_fixed(&mut buf, 1100);
+        assert_eq!(preformatted, v.preformat_exact_fixed(&mut big_buf, 1100));
+        match preformatted {
+            PreFormatted::Finite(false, digits, _, exp) => {
+                assert_eq!(exp, expected_exp);
+                assert_ne!(digits[0], b'0');
+            }
+            _ => panic!("{:e}", v),
+        }
+    }
+}
+
+#[test]
+fn test_bignum() {
+    use crate::bignum::Big32x40;
+
+    let mut buf = [0; Big32x40::MAX_DECIMAL_LEN];
+
+    assert_eq!(Big32x40::zero().to_decimal(&mut buf), b"0");
+    assert_eq!(Big32x40::from_decimal(b"").unwrap(), Big32x40::zero());
+    assert_eq!(Big32x40::from_decimal(b"12a"), None);
+
+    let x = Big32x40::from_digits(&[0x89ab_cdef, 0x0123_4567, 0, 0]).unwrap();
+    assert_eq!(x, Big32x40::from_u64(0x0123_4567_89ab_cdef));
+    assert_eq!(x.to_decimal(&mut buf), b"81985529216486895");
+    assert_eq!(x.rem_small(1000), 895);
+
+    // 2^1280 - 1 is the largest value
+    let mut max = Big32x40::from_digits(&[u32::MAX; 40]).unwrap();
+    assert_eq!(max.bit_length(), 1280);
+    let max_decimal = std::string::ToString::to_string(&max);
+    assert_eq!(max_decimal.len(), Big32x40::MAX_DECIMAL_LEN);
+    assert_eq!(Big32x40::from_decimal(max_decimal.as_bytes()).unwrap(), max);
+    assert!(max.checked_mul_small(2).is_none());
+    assert_eq!(Big32x40::from_digits(&[1; 41]), None);
+
+    let mut y = Big32x40::from_u64(3);
+    y.mul_pow10(100);
+    let (q, r) = y.div_rem(&Big32x40::from_decimal(b"7").unwrap());
+    let mut check = q;
+    check.mul_small(7).add(&r);
+    assert_eq!(check, y);
+    assert_eq!(r, Big32x40::from_u32(5));
+
+    let mut square = Big32x40::from_u64(u64::MAX);
+    square.mul(&Big32x40::from_u64(u64::MAX));
+    assert_eq!(
+        square.to_decimal(&mut buf),
+        &b"340282366920938463426481119284349108225"[..]
+    );
+}
+
+#[test]
+fn test_diy_float() {
+    use crate::diy_float::{cached_power, Fp, CACHED_POW10_FIRST_E, CACHED_POW10_LAST_E};
+
+    assert_eq!(Fp { f: 1, e: 0 }.normalize(), Fp { f: 1 << 63, e: -63 });
+    assert_eq!(
+        Fp { f: 1, e: 3 }.normalize_to(0),
+        Some(Fp { f: 1 << 3, e: 0 })
+    );
+    assert_eq!(Fp { f: 1, e: 3 }.normalize_to(4), None);
+    assert_eq!(Fp { f: 1 << 63, e: 3 }.normalize_to(2), None);
+
+    // A power is found for every window allowed by the documentation.
+    for gamma in CACHED_POW10_FIRST_E..=CACHED_POW10_LAST_E {
+        let (k, c) = cached_power(gamma - 27, gamma).unwrap();
+        assert!(c.e >= gamma - 27 && c.e <= gamma);
+        assert!(c.f >> 63 == 1);
+        // 10^k ~= c.f * 2^c.e, so k * log2(10) ~= c.e + 64
+        let log2 = f64::from(k) * core::f64::consts::LOG2_10;
+        assert!((log2 - f64::from(c.e + 64)).abs() < 1.0);
+    }
+}
+
+#[test]
+fn test_estimator() {
+    use crate::bignum::Big32x40;
+    use crate::estimator::{
+        estimate_binary_exponent, estimate_decimal_exponent, estimate_scaling_factor,
+    };
+
+    // Compares `10^k` with `2^e` exactly.
+    fn cmp_pow10_pow2(k: i32, e: i32) -> core::cmp::Ordering {
+        let mut a = Big32x40::from_u32(1);
+        let mut b = Big32x40::from_u32(1);
+        if k >= 0 {
+            a.mul_pow10(k as usize);
+        } else {
+            b.mul_pow10(-k as usize);
+        }
+        if e >= 0 {
+            b.mul_pow2(e as usize);
+        } else {
+            a.mul_pow2(-e as usize);
+        }
+        a.cmp(&b)
+    }
+
+    for k in -150..=150 {
+        let (e_lo, e_hi) = estimate_binary_exponent(k);
+        let k = i32::from(k);
+        // 2^e_lo <= 10^(k-1) < 2^(e_lo+1)
+        assert_ne!(cmp_pow10_pow2(k - 1, e_lo), core::cmp::Ordering::Less);
+        assert_eq!(cmp_pow10_pow2(k - 1, e_lo + 1), core::cmp::Ordering::Less);
+        // 2^(e_hi-1) < 10^k <= 2^e_hi
+        assert_ne!(cmp_pow10_pow2(k, e_hi), core::cmp::Ordering::Greater);
+        assert_eq!(cmp_pow10_pow2(k, e_hi - 1), core::cmp::Ordering::Greater);
+    }
+
+    for e in -500..=500 {
+        let (k_lo, k_hi) = estimate_decimal_exponent(e);
+        let e = i32::from(e);
+        // 10^k_lo <= 2^(e-1) < 10^(k_lo+1)
+        assert_ne!(cmp_pow10_pow2(k_lo, e - 1), core::cmp::Ordering::Greater);
+        assert_eq!(
+            cmp_pow10_pow2(k_lo + 1, e - 1),
+            core::cmp::Ordering::Greater
+        );
+        // 10^(k_hi-1) < 2^e <= 10^k_hi
+        assert_ne!(cmp_pow10_pow2(k_hi, e), core::cmp::Ordering::Less);
+        assert_eq!(cmp_pow10_pow2(k_hi - 1, e), core::cmp::Ordering::Less);
+    }
+
+    for &v in [1.0f64, 9.99, 10.0, 1.0e-300, 1.0e300, 123_456.0, 5.0e-324].iter() {
+        let (mant, exp, _) = core_num::dec2flt::float::RawFloat::integer_decode(v);
+        let k0 = estimate_scaling_factor(mant, exp);
+        let k = v.log10().ceil() as i16;
+        assert!(k0 == k || k0 + 1 == k, "{} {} {}", v, k0, k);
+    }
+}
+
+#[test]
+fn test_from_preparsed_long_digits() {
+    fn parse(int_digits: &[u8], frac_digits: &[u8], exp: i32) -> f64 {
+        f64::from_preparsed(PreParsed {
+            sign: false,
+            int_digits,
+            frac_digits,
+            exp,
+        })
+        .unwrap()
+    }
+
+    // 2^53 + 1 is halfway between 2^53 and 2^53 + 2, so any non-zero
+    // digit after it rounds up, however far it is.
+    let mut frac = std::vec![b'0'; 5000];
+    assert_eq!(parse(b"9007199254740993", &frac, 0), 9007199254740992.0);
+    *frac.last_mut().unwrap() = b'1';
+    assert_eq!(parse(b"9007199254740993", &frac, 0), 9007199254740994.0);
+
+    // The same with many leading zeros.
+    let mut frac = std::vec![b'0'; 3000];
+    frac.extend_from_slice(b"9007199254740993");
+    frac.extend(core::iter::repeat(b'0').take(2000));
+    assert_eq!(parse(b"0", &frac, 3016), 9007199254740992.0);
+    frac.push(b'1');
+    assert_eq!(parse(b"0", &frac, 3016), 9007199254740994.0);
+
+    let v = f32::from_preparsed(PreParsed {
+        sign: true,
+        int_digits: b"16777217",
+        frac_digits: &frac[..3000],
+        exp: 0,
+    });
+    assert_eq!(v, Ok(-16777216.0));
+    let v = f32::from_preparsed(PreParsed {
+        sign: true,
+        int_digits: b"16777217",
+        frac_digits: &frac[..3001],
+        exp: 0,
+    });
+    assert_eq!(v, Ok(-16777218.0));
+
+    assert_eq!(parse(b"1", b"", i32::MAX), f64::INFINITY);
+    assert_eq!(parse(&[b'1'; 20000], b"", -19999), 10.0 / 9.0);
+
+    // Exponents beyond the range of `i16`.
+    let mut digits = std::vec![b'0'; 40001];
+    digits[0] = b'1';
+    assert_eq!(parse(&digits, b"", -40000), 1.0);
+    digits.reverse();
+    assert_eq!(parse(b"0", &digits, 40001), 1.0);
+    assert_eq!(parse(b"1", b"", -40000), 0.0);
+    assert_eq!(parse(b"0", b"", 40000), 0.0);
+}
+
+#[test]
+fn test_integer_only_parse() {
+    use crate::sealed::Sealed as _;
+
+    fn parse<T: FloatExt>(int_digits: &[u8], exp: i32) -> T {
+        T::from_preparsed(PreParsed {
+            sign: false,
+            int_digits,
+            frac_digits: b"",
+            exp,
+        })
+        .unwrap()
+    }
+
+    // Exact integers, whose bits are built without float arithmetic.
+    assert_eq!(parse::<f64>(b"0", 5).to_bits(), 0);
+    assert_eq!(parse::<f64>(b"1", 0), 1.0);
+    assert_eq!(parse::<f64>(b"123", 5), 12300000.0);
+    assert_eq!(parse::<f64>(b"9007199254740991", 0), 9007199254740991.0);
+    assert_eq!(parse::<f64>(b"9007199254740992", 0), 9007199254740992.0);
+    assert_eq!(parse::<f64>(b"9007199254740993", 0), 9007199254740992.0);
+    assert_eq!(parse::<f64>(b"9007199254740995", 0), 9007199254740996.0);
+    assert_eq!(parse::<f64>(b"9", 15), 9e15);
+    assert_eq!(parse::<f64>(b"1", 22), 1e22);
+    assert_eq!(parse::<f64>(b"5", -1), 0.5);
+    assert_eq!(parse::<f32>(b"16777215", 0), 16777215.0);
+    assert_eq!(parse::<f32>(b"16777216", 0), 16777216.0);
+    assert_eq!(parse::<f32>(b"16777217", 0), 16777216.0);
+    assert_eq!(parse::<f32>(b"1", 10), 1e10);
+
+    // `f32` to `f64` widening is done on the bits, so subnormals are kept
+    // even if the FPU flushes them.
+    for &bits in &[
+        0,
+        1,
+        0x7F_FFFF,
+        0x80_0000,
+        0x8000_0001,
+        0x7F80_0000,
+        0xFF80_0000,
+        0x7F7F_FFFF,
+    ] {
+        let v = f32::from_bits(bits);
+        assert_eq!(v.to_f64().to_bits(), f64::from(v).to_bits());
+    }
+    let mut state = 0x1234_5678_9abc_def0u64;
+    for _ in 0..10000 {
+        // xorshift64
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+
+        let v = f32::from_bits(state as u32);
+        if !v.is_nan() {
+            assert_eq!(v.to_f64().to_bits(), f64::from(v).to_bits());
+        }
+        let v = f32::from_bits(state as u32 & 0x807F_FFFF);
+        assert_eq!(v.to_f64().to_bits(), f64::from(v).to_bits());
+
+        let int = state >> (state % 64);
+        let digits = std::format!("{}", int);
+        let expected: f64 = digits.parse().unwrap();
+        assert_eq!(parse::<f64>(digits.as_bytes(), 0), expected);
+        let expected: f32 = digits.parse().unwrap();
+        assert_eq!(parse::<f32>(digits.as_bytes(), 0), expected);
+    }
+}
+
+#[test]
+fn test_shortest_strategy() {
+    use crate::core_num::flt2dec::decoder::{decode, DecodableFloat, FullDecoded};
+    use crate::core_num::flt2dec::strategy::dragon;
+
+    fn check<T: FloatExt + DecodableFloat>(v: T) {
+        let mut buf = [0; crate::PREFORMAT_SHORTEST_BUF_LEN];
+        let mut expected_buf = [0; crate::PREFORMAT_SHORTEST_BUF_LEN];
+        let (digits, exp) = match v.preformat_shortest(&mut buf) {
+            PreFormatted::Finite(_, digits, 0, exp) => (digits, exp),
+            r => panic!("{:?}", r),
+        };
+        let (expected, expected_exp) = match decode(v).1 {
+            FullDecoded::Finite(ref decoded) => dragon::format_shortest(decoded, &mut expected_buf),
+            _ => unreachable!(),
+        };
+        if (digits, exp) != (expected, expected_exp) {
+            // When two shortest representations are equally close, Dragon
+            // picks the larger one and Ryu and Dragonbox the even one.
+            if cfg!(not(any(
+                feature = "strategy-ryu",
+                feature = "strategy-dragonbox"
+            ))) {
+                panic!("{:?} != {:?}", (digits, exp), (expected, expected_exp));
+            }
+            assert_eq!((digits.len(), exp), (expected.len(), expected_exp));
+            let (last, expected_last) = (digits[digits.len() - 1], expected[expected.len() - 1]);
+            assert_eq!(digits[..digits.len() - 1], expected[..expected.len() - 1]);
+            assert_eq!(last + 1, expected_last);
+            assert_eq!(last % 2, 0);
+        }
+    }
+
+    for &v in &[
+        f64::MIN_POSITIVE,
+        f64::MAX,
+        f64::from_bits(1),
+        f64::from_bits(0x000F_FFFF_FFFF_FFFF),
+        1.0,
+        0.3,
+        5e-324,
+        9007199254740992.0,
+        1e23,
+        2f64.powi(-1022 + 52),
+    ] {
+        check(v);
+    }
+    for &v in &[
+        f32::MIN_POSITIVE,
+        f32::MAX,
+        f32::from_bits(1),
+        1.0,
+        0.3,
+        16777216.0,
+        1e10,
+    ] {
+        check(v);
+    }
+
+    let mut state = 0x1234_5678_9abc_def0u64;
+    for _ in 0..20000 {
+        // xorshift64
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+
+        let v = f64::from_bits(state & 0x7FFF_FFFF_FFFF_FFFF);
+        if v.is_finite() && v != 0.0 {
+            check(v);
+        }
+        let v = f32::from_bits(state as u32 & 0x7FFF_FFFF);
+        if v.is_finite() && v != 0.0 {
+            check(v);
+        }
+        // Powers of two, where the lower neighbor is closer.
+        let v = f64::from_bits((state % 0x7FF) << 52);
+        if v != 0.0 {
+            check(v);
+        }
+        let v = f32::from_bits(((state % 0xFF) as u32) << 23);
+        if v != 0.0 {
+            check(v);
+        }
+    }
+}
+
+#[test]
+fn test_parse_errors() {
+    fn parse(int_digits: &[u8], frac_digits: &[u8], exp: i32) -> Result<f64, ParseFloatError> {
+        f64::from_preparsed(PreParsed {
+            sign: false,
+            int_digits,
+            frac_digits,
+            exp,
+        })
+    }
+
+    assert_eq!(parse(b"", b"", 0), Err(ParseFloatError::EmptyDigits));
+    assert_eq!(
+        parse(b"1 ", b"", 0),
+        Err(ParseFloatError::InvalidDigit {
+            byte: b' ',
+            index: 1
+        })
+    );
+    assert_eq!(
+        parse(b"12", b"3.", 0),
+        Err(ParseFloatError::InvalidDigit {
+            byte: b'.',
+            index: 3
+        })
+    );
+    assert_eq!(parse(b"", &[b'1'; 400], 0), Ok(1.0 / 9.0));
+    assert_eq!(parse(b"1", b"", i32::MAX), Ok(f64::INFINITY));
+    assert_eq!(parse(b"1", b"", i32::MIN), Ok(0.0));
+
+    assert_eq!(
+        std::format!(
+            "{}",
+            ParseFloatError::InvalidDigit {
+                byte: b'x',
+                index: 2
+            }
+        ),
+        "invalid digit 'x' at index 2"
+    );
+}
+
+#[test]
+fn test_write_grammar_roundtrip() {
+    use crate::grammar::{Grammar, Parsed};
+    use crate::write::{
+        write_exponent, write_minimal, write_positional, write_scientific, ExpFormat, WriteOptions,
+    };
+    use crate::{BufferTooSmall, SyntaxError};
+
+    let opts = WriteOptions::DEFAULT;
+    let mut digits = [0; crate::PREFORMAT_SHORTEST_BUF_LEN];
+    let mut buf = [0; 32];
+    for &v in &[
+        0.0,
+        -0.0,
+        1.0,
+        -1.5,
+        0.1,
+        100.0,
+        123456.0,
+        1e21,
+        1e-7,
+        f64::MAX,
+        f64::MIN_POSITIVE,
+        5e-324,
+        f64::INFINITY,
+        f64::NEG_INFINITY,
+    ] {
+        let s = write_minimal(&mut buf, v.preformat_shortest(&mut digits), &opts).unwrap();
+        let back: f64 = Grammar::RUST
+            .parse(s.as_bytes())
+            .unwrap()
+            .to_float()
+            .unwrap();
+        assert_eq!(back.to_bits(), v.to_bits(), "{}", s);
+    }
+    let s = write_minimal(&mut buf, f64::NAN.preformat_shortest(&mut digits), &opts);
+    assert_eq!(s, Ok("NaN"));
+    assert_eq!(Grammar::RUST.parse(b"NaN"), Ok(Parsed::NaN));
+
+    let preformatted = PreFormatted::Finite(true, b"5", 3, 2);
+    let s = write_positional(&mut buf, preformatted, 0, &opts);
+    assert_eq!(s, Ok("-50.00"));
+    let s = write_scientific(&mut buf, preformatted, 0, &opts);
+    assert_eq!(s, Ok("-5.000e1"));
+    let s = write_positional(&mut buf[..5], preformatted, 0, &opts);
+    assert_eq!(
+        s,
+        Err(BufferTooSmall {
+            required: 6,
+            actual: 5
+        })
+    );
+
+    let opts = WriteOptions {
+        exp_marker: "E",
+        exp_format: ExpFormat {
+            plus_sign: true,
+            min_digits: 3,
+        },
+        ..WriteOptions::DEFAULT
+    };
+    let s = write_scientific(&mut buf, PreFormatted::Finite(false, b"1", 0, 22), 0, &opts);
+    assert_eq!(s, Ok("1E+021"));
+    let s = write_scientific(
+        &mut buf,
+        PreFormatted::Finite(true, b"5", 0, -323),
+        0,
+        &opts,
+    );
+    assert_eq!(s, Ok("-5E-324"));
+    assert_eq!(
+        write_exponent(&mut buf, i32::MIN, &opts.exp_format),
+        Ok("-2147483648")
+    );
+    assert_eq!(
+        write_exponent(&mut buf[..2], 0, &opts.exp_format),
+        Err(BufferTooSmall {
+            required: 4,
+            actual: 2
+        })
+    );
+
+    assert_eq!(Grammar::RUST.parse(b""), Err(SyntaxError { index: 0 }));
+    assert_eq!(Grammar::RUST.parse(b"."), Err(SyntaxError { index: 0 }));
+    assert_eq!(Grammar::RUST.parse(b"+.e1"), Err(SyntaxError { index: 1 }));
+    assert_eq!(Grammar::RUST.parse(b"1e"), Err(SyntaxError { index: 1 }));
+    assert_eq!(
+        Grammar::RUST.parse(b"5.").unwrap().to_float::<f32>(),
+        Ok(5.0)
+    );
+    assert_eq!(
+        Grammar::RUST.parse(b"1e99999").unwrap().to_float::<f64>(),
+        Ok(f64::INFINITY)
+    );
+    assert_eq!(
+        Grammar::RUST.parse(b"100000e-40000").unwrap(),
+        Parsed::Finite(PreParsed {
+            sign: false,
+            int_digits: b"100000",
+            frac_digits: b"",
+            exp: -40000,
+        })
+    );
+    let strict = Grammar {
+        require_int_digits: true,
+        require_frac_digits: true,
+        ..Grammar::RUST
+    };
+    assert_eq!(strict.parse(b".5"), Err(SyntaxError { index: 0 }));
+    assert_eq!(strict.parse(b"5."), Err(SyntaxError { index: 1 }));
+}
+
+#[test]
+fn test_lexical_compat() {
+    use crate::lexical_compat::{
+        parse, parse_partial, parse_with_options, write, write_with_options, Error,
+        ParseFloatOptions, ToLexical, WriteFloatOptions,
+    };
+
+    let mut buf = [0; f64::FORMATTED_SIZE_DECIMAL];
+    assert_eq!(write(0.0f64, &mut buf), b"0.0");
+    assert_eq!(write(-1.0f64, &mut buf), b"-1.0");
+    assert_eq!(write(123456789.0f64, &mut buf), b"123456789.0");
+    assert_eq!(write(12345678900.0f64, &mut buf), b"1.23456789e10");
+    assert_eq!(write(0.00001f64, &mut buf), b"0.00001");
+    assert_eq!(write(0.000001f64, &mut buf), b"1.0e-6");
+    assert_eq!(write(f64::NAN, &mut buf), b"NaN");
+    assert_eq!(write(f32::NEG_INFINITY, &mut buf), b"-inf");
+    assert_eq!(write(-f64::MIN_POSITIVE, &mut buf).len(), 24);
+
+    let options = WriteFloatOptions {
+        exponent: b'^',
+        decimal_point: b',',
+        trim_floats: true,
+        ..WriteFloatOptions::new()
+    };
+    assert_eq!(write_with_options(2.0f32, &mut buf, &options), b"2");
+    assert_eq!(
+        write_with_options(2.5e-10f32, &mut buf, &options),
+        b"2,5^-10"
+    );
+
+    assert_eq!(parse::<f64>(b"-1.5e3"), Ok(-1500.0));
+    assert_eq!(parse::<f32>(b"INF"), Ok(f32::INFINITY));
+    assert!(parse::<f32>(b"nan").unwrap().is_nan());
+    assert_eq!(parse::<f64>(b""), Err(Error::Empty(0)));
+    assert_eq!(parse::<f64>(b"-"), Err(Error::Empty(1)));
+    assert_eq!(parse::<f64>(b"1.5.2"), Err(Error::InvalidDigit(3)));
+    assert_eq!(parse_partial::<f64>(b"12abc"), Ok((12.0, 2)));
+
+    let options = ParseFloatOptions {
+        exponent: b'^',
+        decimal_point: b',',
+        ..ParseFloatOptions::new()
+    };
+    assert_eq!(parse_with_options::<f32>(b"2,5^-1", &options), Ok(0.25));
+}
+
+#[test]
+fn test_ryu_compat() {
+    use std::str::FromStr;
+
+    fn check<T: crate::ryu_compat::Float + ryu::Float + FromStr + PartialEq + core::fmt::Debug>(
+        v: T,
+    ) where
+        T::Err: core::fmt::Debug,
+    {
+        let mut buffer = crate::ryu_compat::Buffer::new();
+        let mut expected = ryu::Buffer::new();
+        let s = buffer.format(v);
+        let expected = expected.format(v);
+        if cfg!(any(
+            feature = "strategy-ryu",
+            feature = "strategy-dragonbox"
+        )) {
+            assert_eq!(s, expected);
+        } else if s != expected {
+            // When two shortest representations are equally close, Grisu
+            // rounds up and ryu rounds to even.
+            assert_eq!(s.len(), expected.len(), "{} {}", s, expected);
+            assert_eq!(T::from_str(s).unwrap(), v);
+        }
+    }
+
+    let mut state = 0x1234_5678_9abc_def0u64;
+    for _ in 0..20000 {
+        // xorshift64
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+
+        let v = f64::from_bits(state);
+        if !v.is_nan() {
+            check(v);
+        }
+        let v = f32::from_bits(state as u32);
+        if !v.is_nan() {
+            check(v);
+        }
+        // Values with few digits, around the notation thresholds
+        let v = (state % 1000) as f64 * 10f64.powi((state >> 32) as i32 % 40 - 20);
+        check(v);
+        check(v as f32);
+    }
+    for &v in &[
+        0.0,
+        -0.0,
+        1.0,
+        1e16,
+        1e15,
+        1e-5,
+        1e-4,
+        f64::MAX,
+        f64::MIN_POSITIVE,
+    ] {
+        check(v);
+    }
+    let mut buffer = crate::ryu_compat::Buffer::new();
+    assert_eq!(buffer.format(f32::NEG_INFINITY), "-inf");
+    assert_eq!(buffer.format(f64::NAN), "NaN");
+}
+
+#[cfg(feature = "embedded-io")]
+#[test]
+fn test_embedded_io() {
+    use crate::embedded_io_ext::{
+        write_minimal, write_positional, write_scientific, write_shortest, CHUNK_LEN,
+    };
+    use crate::write::WriteOptions;
+
+    // Records the length of each write, accepting at most 7 bytes at a
+    // time, and fails after `limit` bytes.
+    struct Uart {
+        buf: [u8; 1000],
+        len: usize,
+        writes: [usize; 100],
+        num_writes: usize,
+        limit: usize,
+    }
+
+    impl ::embedded_io::ErrorType for Uart {
+        type Error = ::embedded_io::ErrorKind;
+    }
+
+    impl ::embedded_io::Write for Uart {
+        fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+            if self.len >= self.limit {
+                return Err(::embedded_io::ErrorKind::Other);
+            }
+            let n = buf.len().min(7);
+            self.buf[self.len..(self.len + n)].copy_from_slice(&buf[..n]);
+            self.len += n;
+            self.writes[self.num_writes] = buf.len();
+            self.num_writes += 1;
+            Ok(n)
+        }
+
+        fn flush(&mut self) -> Result<(), Self::Error> {
+            Ok(())
+        }
+    }
+
+    let new_uart = |limit| Uart {
+        buf: [0; 1000],
+        len: 0,
+        writes: [0; 100],
+        num_writes: 0,
+        limit,
+    };
+
+    let mut uart = new_uart(1000);
+    write_shortest(&mut uart, -0.1f32).unwrap();
+    assert_eq!(&uart.buf[..uart.len], b"-0.1");
+    assert_eq!(&uart.writes[..uart.num_writes], [4]);
+
+    let mut uart = new_uart(1000);
+    write_shortest(&mut uart, f64::NAN).unwrap();
+    assert_eq!(&uart.buf[..uart.len], b"NaN");
+
+    let options = WriteOptions::DEFAULT;
+    let mut uart = new_uart(1000);
+    let preformatted = PreFormatted::Finite(false, b"15", 0, 100);
+    write_positional(&mut uart, preformatted, 0, &options).unwrap();
+    assert_eq!(uart.len, 100);
+    assert_eq!(&uart.buf[..3], b"150");
+    assert!(uart.buf[2..100].iter().all(|&c| c == b'0'));
+    // Chunks of `CHUNK_LEN`, each passed to `write` until it is written.
+    assert_eq!(uart.writes[0], CHUNK_LEN);
+    assert_eq!(uart.writes[1], CHUNK_LEN - 7);
+
+    let mut uart = new_uart(1000);
+    let preformatted = PreFormatted::Finite(true, b"15", 3, -100);
+    write_scientific(&mut uart, preformatted, 0, &options).unwrap();
+    assert_eq!(&uart.buf[..uart.len], b"-1.5000e-101");
+
+    let mut uart = new_uart(1000);
+    let preformatted = PreFormatted::Finite(false, b"125", 0, 21);
+    write_minimal(&mut uart, preformatted, &options).unwrap();
+    assert_eq!(&uart.buf[..uart.len], b"1.25e20");
+
+    // The first error is returned, and nothing is written after it.
+    let mut uart = new_uart(40);
+    let preformatted = PreFormatted::Finite(false, b"1", 0, 200);
+    assert_eq!(
+        write_positional(&mut uart, preformatted, 0, &options),
+        Err(::embedded_io::ErrorKind::Other)
+    );
+    assert_eq!(uart.len, 46);
+}
+
+#[cfg(feature = "half")]
+#[test]
+fn test_half() {
+    use crate::half_ext::{
+        BF16_EXACT_FIXED_BASE_BUF_LEN, BF16_SHORTEST_BUF_LEN, F16_EXACT_FIXED_BASE_BUF_LEN,
+        F16_SHORTEST_BUF_LEN,
+    };
+    use half::{bf16, f16};
+
+    fn parse<T: FloatExt>(digits: &[u8], exp: i32) -> T {
+        T::from_preparsed(PreParsed {
+            sign: false,
+            int_digits: b"",
+            frac_digits: digits,
+            exp,
+        })
+        .unwrap()
+    }
+
+    // Checks every `step`-th positive finite number, up to the one with
+    // `max_bits`.
+    fn check<T: FloatExt + PartialEq + core::fmt::Debug>(
+        max_bits: u64,
+        step: usize,
+        shortest_len: usize,
+        exact_len: usize,
+    ) {
+        for bits in (0..max_bits).step_by(step) {
+            let v = T::from_bits_u64(bits);
+            let mut buf = [0; 5];
+            match v.preformat_shortest(&mut buf[..shortest_len]) {
+                PreFormatted::Finite(false, digits, 0, exp) => {
+                    assert_eq!(parse::<T>(digits, exp.into()), v);
+                    // Shorter than `f64`, but with the same digits when
+                    // those are short enough.
+                    let mut buf64 = [0; crate::PREFORMAT_SHORTEST_BUF_LEN];
+                    if let PreFormatted::Finite(_, digits64, _, exp64) =
+                        v.to_f64().preformat_shortest(&mut buf64)
+                    {
+                        assert!(digits.len() <= digits64.len());
+                        if digits64.len() <= digits.len() {
+                            assert_eq!((digits, exp), (digits64, exp64));
+                        }
+                    }
+                }
+                PreFormatted::Zero(false) => assert_eq!(bits, 0),
+                other => panic!("{:?}", other),
+            }
+
+            // Every digit of the exact value, as `f64`.
+            let trim = |preformatted| match preformatted {
+                PreFormatted::Finite(_, digits, _, exp) => {
+                    let len = digits.iter().rposition(|&c| c != b'0').unwrap() + 1;
+                    (&digits[..len], exp)
+                }
+                _ => (&b""[..], 0),
+            };
+            let mut buf = [0; 200];
+            let mut buf64 = [0; crate::PREFORMAT_EXACT_FIXED_BASE_BUF_LEN];
+            assert_eq!(
+                trim(v.preformat_exact_exp(&mut buf[..exact_len], exact_len)),
+                trim(v.to_f64().preformat_exact_exp(&mut buf64, 200)),
+            );
+
+            // Halfway to the next number rounds to even, and any digit
+            // after it rounds up.
+            let next = T::from_bits_u64(bits + 1);
+            if next == T::INFINITY {
+                continue;
+            }
+            let mid = (v.to_f64() + next.to_f64()) / 2.0;
+            let mut buf = [0; crate::PREFORMAT_EXACT_FIXED_BASE_BUF_LEN];
+            let (digits, exp) = trim(mid.preformat_exact_exp(&mut buf, 200));
+            let even = if bits & 1 == 0 { v } else { next };
+            assert_eq!(parse::<T>(digits, exp.into()), even);
+            let mut above = [b'0'; 221];
+            above[..digits.len()].copy_from_slice(digits);
+            above[digits.len() + 20] = b'1';
+            assert_eq!(parse::<T>(&above[..=(digits.len() + 20)], exp.into()), next);
+        }
+    }
+
+    check::<f16>(
+        0x7C00,
+        1,
+        F16_SHORTEST_BUF_LEN,
+        F16_EXACT_FIXED_BASE_BUF_LEN,
+    );
+    check::<bf16>(
+        0x7F80,
+        5,
+        BF16_SHORTEST_BUF_LEN,
+        BF16_EXACT_FIXED_BASE_BUF_LEN,
+    );
+
+    assert_eq!(f16::NAN.preformat_shortest(&mut [0; 5]), PreFormatted::NaN);
+    assert_eq!(
+        f16::NEG_INFINITY.preformat_exact_exp(&mut [0; 5], 5),
+        PreFormatted::Inf(true)
+    );
+    assert_eq!(
+        (-f16::MIN_POSITIVE_SUBNORMAL).preformat_shortest(&mut [0; 5]),
+        PreFormatted::Finite(true, b"6", 0, -7)
+    );
+    assert_eq!(parse::<f16>(b"65520", 5), f16::INFINITY);
+    assert_eq!(parse::<f16>(b"298023223876953125", -7), f16::from_bits(0));
+    assert_eq!(parse::<f16>(b"298023223876953126", -7), f16::from_bits(1));
+    assert_eq!(
+        f16::from_bits(0x3555).round_to_sig_figs(2),
+        f16::from_f32(0.33)
+    );
+    assert_eq!(
+        (-bf16::MAX).preformat_shortest(&mut [0; 4]),
+        PreFormatted::Finite(true, b"339", 0, 39)
+    );
+    assert_eq!(parse::<bf16>(b"34", 39), bf16::INFINITY);
+    assert_eq!(
+        bf16::MIN_POSITIVE_SUBNORMAL.preformat_shortest(&mut [0; 4]),
+        PreFormatted::Finite(false, b"1", 0, -39)
+    );
+    assert_eq!(f16::from_decimal_parts(65504, 0), f16::MAX);
+    assert_eq!(f16::from_decimal_parts(6552, 1), f16::INFINITY);
+    assert_eq!(f16::from_decimal_parts(10, i32::MAX), f16::INFINITY);
+    assert_eq!(f16::from_decimal_parts(2, -8), f16::from_bits(0));
+    assert_eq!(bf16::from_decimal_parts(15, -1), bf16::from_f32(1.5));
+}
+
+#[cfg(feature = "heapless")]
+#[test]
+fn test_heapless() {
+    use crate::heapless_ext::{to_shortest_string, to_shortest_vec, write_minimal_string};
+    use crate::write::{WriteOptions, MINIMAL_SHORTEST_LEN};
+
+    let s = to_shortest_string::<_, MINIMAL_SHORTEST_LEN>(-f64::MIN_POSITIVE);
+    assert_eq!(s, "-2.2250738585072014e-308");
+    assert_eq!(s.len(), MINIMAL_SHORTEST_LEN);
+    assert_eq!(to_shortest_string::<_, 32>(f32::NAN), "NaN");
+    let v = to_shortest_vec::<_, MINIMAL_SHORTEST_LEN>(0.25f32);
+    assert_eq!(&v[..], b"0.25");
+
+    let s = write_minimal_string::<4>(PreFormatted::Inf(true), &WriteOptions::DEFAULT);
+    assert_eq!(s.unwrap(), "-inf");
+    let s = write_minimal_string::<3>(PreFormatted::Inf(true), &WriteOptions::DEFAULT);
+    assert!(s.is_err());
+}
+
+#[cfg(feature = "num-traits")]
+#[test]
+fn test_generic_float() {
+    use crate::generic_float::{
+        format_exact_exp_generic, format_exact_fixed_generic, format_shortest_generic,
+        parse_generic, GenericFloat,
+    };
+
+    fn roundtrip<T: GenericFloat>(v: T) -> T {
+        let mut buf = [0; crate::PREFORMAT_SHORTEST_BUF_LEN];
+        match format_shortest_generic(v, &mut buf) {
+            PreFormatted::Finite(sign, digits, 0, exp) => parse_generic(PreParsed {
+                sign,
+                int_digits: b"",
+                frac_digits: digits,
+                exp: exp.into(),
+            })
+            .unwrap(),
+            _ => unreachable!(),
+        }
+    }
+
+    assert_eq!(roundtrip(0.3f32), 0.3);
+    assert_eq!(roundtrip(-1.0e-300f64), -1.0e-300);
+
+    let mut buf = [0; crate::PREFORMAT_EXACT_FIXED_BASE_BUF_LEN + 2];
+    assert_eq!(
+        format_exact_exp_generic(2.5f64, &mut buf, 1),
+        PreFormatted::Finite(false, b"2", 0, 1)
+    );
+    assert_eq!(
+        format_exact_fixed_generic(2.5f32, &mut buf, 2),
+        PreFormatted::Finite(false, b"250", 0, 1)
+    );
+}
+
+#[cfg(feature = "bigdecimal")]
+#[test]
+fn test_bigdecimal() {
+    use crate::bigdecimal::{from_bigdecimal, to_bigdecimal};
+    use std::str::FromStr as _;
+    use std::string::ToString as _;
+
+    for &v in &[
+        0.0,
+        1.0,
+        -2.5,
+        0.1,
+        1e300,
+        f64::MAX,
+        f64::MIN_POSITIVE,
+        -5e-324,
+    ] {
+        let exact = to_bigdecimal(v).unwrap();
+        assert_eq!(from_bigdecimal::<f64>(&exact).to_bits(), v.to_bits());
+    }
+    assert_eq!(to_bigdecimal(f64::NAN), None);
+    assert_eq!(to_bigdecimal(f32::NEG_INFINITY), None);
+
+    let exact = to_bigdecimal(5e-324).unwrap();
+    assert_eq!(exact.digits(), 751);
+    assert!(exact
+        .to_string()
+        .starts_with("4.940656458412465441765687928682213"));
+    assert_eq!(to_bigdecimal(-0.75f32).unwrap().to_string(), "-0.75");
+    assert_eq!(to_bigdecimal(1024.0f32).unwrap().to_string(), "1024");
+
+    let v = bigdecimal_crate::BigDecimal::from_str("0.1").unwrap();
+    assert_eq!(from_bigdecimal::<f32>(&v), 0.1);
+    let v = bigdecimal_crate::BigDecimal::from_str("-1e-99999").unwrap();
+    assert_eq!(from_bigdecimal::<f64>(&v).to_bits(), (-0.0f64).to_bits());
+    let v = bigdecimal_crate::BigDecimal::from_str("1e99999").unwrap();
+    assert_eq!(from_bigdecimal::<f64>(&v), f64::INFINITY);
+
+    // Halfway between the two smallest subnormals (ties to even), and
+    // slightly below. Both need more digits than `from_preparsed` handles.
+    let half =
+        to_bigdecimal(5e-324).unwrap() * bigdecimal_crate::BigDecimal::from_str("1.5").unwrap();
+    assert_eq!(from_bigdecimal::<f64>(&half), 1e-323);
+    let tiny = bigdecimal_crate::BigDecimal::from_str("1e-1100").unwrap();
+    assert_eq!(from_bigdecimal::<f64>(&(&half - &tiny)), 5e-324);
+    let half = to_bigdecimal(1.0f32).unwrap() + to_bigdecimal(f32::EPSILON / 2.0).unwrap();
+    assert_eq!(from_bigdecimal::<f32>(&half), 1.0);
+    assert_eq!(from_bigdecimal::<f32>(&(&half + &tiny)), 1.0 + f32::EPSILON);
+}
+
+#[cfg(feature = "num")]
+#[test]
+fn test_rational() {
+    use num_bigint::BigInt;
+    use num_rational::BigRational;
+
+    for &v in &[0.0, -1.0, 0.1, 1e300, f64::MAX, f64::MIN_POSITIVE, -5e-324] {
+        let r = v.to_exact_rational().unwrap();
+        assert_eq!(r.denom().magnitude().count_ones(), 1);
+        assert_eq!(f64::from_rational(&r).to_bits(), v.to_bits());
+    }
+    assert_eq!(f64::INFINITY.to_exact_rational(), None);
+    assert_eq!(
+        (-0.0f32).to_exact_rational(),
+        Some(BigRational::from_integer(BigInt::from(0)))
+    );
+    assert_eq!(
+        1024.0f32.to_exact_rational(),
+        Some(BigRational::from_integer(BigInt::from(1024)))
+    );
+
+    let r = |n: i64, d: i64| BigRational::new(BigInt::from(n), BigInt::from(d));
+    assert_eq!(f32::from_rational(&r(1, 10)), 0.1);
+    assert_eq!(f64::from_rational(&r(2, -3)), -2.0 / 3.0);
+    // Ties to even
+    assert_eq!(f32::from_rational(&r((1 << 24) + 1, 1)), 16777216.0);
+    assert_eq!(f32::from_rational(&r((1 << 24) + 3, 1)), 16777220.0);
+    // Overflow and underflow
+    let big = BigRational::from_integer(BigInt::from(1) << 1100usize);
+    assert_eq!(f64::from_rational(&big), f64::INFINITY);
+    assert_eq!(f64::from_rational(&-big.recip()), -0.0);
+    let max_plus_half_ulp = f64::MAX.to_exact_rational().unwrap()
+        + BigRational::from_integer(BigInt::from(1) << 970usize);
+    assert_eq!(f64::from_rational(&max_plus_half_ulp), f64::INFINITY);
+    let min_half = (5e-324).to_exact_rational().unwrap() / BigInt::from(2);
+    assert_eq!(f64::from_rational(&min_half), 0.0);
+    assert_eq!(f64::from_rational(&(min_half * BigInt::from(3))), 1e-323);
+}
+
+#[cfg(feature = "serde_json")]
+#[test]
+fn test_json_number() {
+    use crate::json_number::{
+        from_number, from_number_lossless, to_number_exact, to_number_shortest,
+    };
+    use serde_json::Number;
+    use std::string::ToString as _;
+
+    let n = |s: &str| serde_json::from_str::<Number>(s).unwrap();
+    assert_eq!(from_number::<f64>(&n("0.1")), Ok((0.1, false)));
+    assert_eq!(from_number::<f64>(&n("-0")), Ok((-0.0, true)));
+    assert_eq!(from_number::<f32>(&n("1e300")), Ok((f32::INFINITY, false)));
+    assert_eq!(from_number::<f32>(&n("1024")), Ok((1024.0, true)));
+    assert_eq!(from_number::<f32>(&n("-1.25e-2")), Ok((-0.0125, false)));
+    assert_eq!(from_number_lossless::<f32>(&n("0.375")), Some(0.375));
+    assert_eq!(from_number_lossless::<f32>(&n("0.3")), None);
+
+    for &v in &[0.1f64, -2.5, 1e300, 5e-324, f64::MAX] {
+        let shortest = to_number_shortest(v).unwrap();
+        assert_eq!(from_number::<f64>(&shortest).unwrap().0, v);
+        // Without `arbitrary_precision`, the number is stored as an `f64`
+        let exact = to_number_exact(v).unwrap();
+        assert_eq!(from_number::<f64>(&exact).unwrap().0, v);
+    }
+    assert_eq!(to_number_shortest(0.1f32).unwrap().to_string(), "0.1");
+    assert_eq!(
+        to_number_exact(0.1f32).unwrap().to_string(),
+        "0.10000000149011612"
+    );
+    assert_eq!(to_number_exact(f32::NAN), None);
+}
+
+#[cfg(feature = "ordered-float")]
+#[test]
+fn test_ordered_float() {
+    use crate::ordered_float_ext::NotNanExt;
+    use ordered_float::{NotNan, OrderedFloat};
+
+    let mut buf = [0; crate::PREFORMAT_SHORTEST_BUF_LEN];
+    assert_eq!(
+        OrderedFloat(-0.00401f64).preformat_shortest(&mut buf),
+        PreFormatted::Finite(true, b"401", 0, -2)
+    );
+    assert_eq!(
+        OrderedFloat(f32::NAN).preformat_exact_exp(&mut buf, 3),
+        PreFormatted::NaN
+    );
+    let preparsed = PreParsed {
+        sign: false,
+        int_digits: b"1",
+        frac_digits: b"5",
+        exp: 0,
+    };
+    assert_eq!(
+        OrderedFloat::<f32>::from_preparsed(preparsed),
+        Ok(OrderedFloat(1.5))
+    );
+
+    let v = NotNan::new(12.34f32).unwrap();
+    let mut buf = [0; crate::PREFORMAT_EXACT_FIXED_BASE_BUF_LEN + 1];
+    assert_eq!(
+        v.preformat_exact_fixed(&mut buf, 1),
+        PreFormatted::Finite(false, b"123", 0, 2)
+    );
+    let preparsed = PreParsed {
+        sign: true,
+        int_digits: b"2",
+        frac_digits: b"",
+        exp: 0,
+    };
+    assert_eq!(
+        NotNan::<f64>::from_preparsed(preparsed),
+        Ok(NotNan::new(-2.0).unwrap())
+    );
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_exact_vec() {
+    use crate::{OwnedPreFormatted, PREFORMAT_EXACT_FIXED_BASE_BUF_LEN};
+
+    let mut buf = [0; PREFORMAT_EXACT_FIXED_BASE_BUF_LEN];
+    for &v in &[
+        0.1f64,
+        -123.456,
+        1e300,
+        f64::MIN_POSITIVE,
+        f64::from_bits(1),
+    ] {
+        for &n in &[1, 17, 800, 5000] {
+            let mut expected = alloc_digits(v.preformat_exact_exp(&mut buf, n));
+            assert_eq!(v.preformat_exact_exp_vec(n), expected, "{:e} {}", v, n);
+            if let OwnedPreFormatted::Finite(_, ref digits, _, _) = expected {
+                assert_eq!(digits.len(), n);
+            }
+            expected = alloc_digits(v.preformat_exact_fixed(&mut buf, n as isize));
+            assert_eq!(
+                v.preformat_exact_fixed_vec(n as isize),
+                expected,
+                "{:e} {}",
+                v,
+                n
+            );
+        }
+
+        // The full expansion converts back exactly and has no trailing zeros.
+        if let OwnedPreFormatted::Finite(sign, digits, 0, exp) = v.preformat_exact_full_vec() {
+            assert_ne!(digits.last(), Some(&b'0'));
+            let preparsed = PreParsed {
+                sign,
+                int_digits: &[],
+                frac_digits: &digits,
+                exp: exp.into(),
+            };
+            assert_eq!(f64::from_preparsed(preparsed), Ok(v));
+            let mut longer = v.preformat_exact_exp_vec(digits.len() + 10);
+            if let OwnedPreFormatted::Finite(_, ref mut longer, _, _) = longer {
+                assert!(longer[digits.len()..].iter().all(|&d| d == b'0'));
+                longer.truncate(digits.len());
+            }
+            assert_eq!(longer, OwnedPreFormatted::Finite(sign, digits, 0, exp));
+        } else {
+            panic!("{:e}", v);
+        }
+    }
+
+    assert_eq!(
+        0.0f32.preformat_exact_full_vec(),
+        OwnedPreFormatted::Zero(false)
+    );
+    assert_eq!(f32::NAN.preformat_exact_exp_vec(10), OwnedPreFormatted::NaN);
+    assert_eq!(
+        f32::NEG_INFINITY.preformat_exact_fixed_vec(10),
+        OwnedPreFormatted::Inf(true)
+    );
+}
+
+/// Converts `v` to an `OwnedPreFormatted` with its extra zeros in the digits.
+#[cfg(feature = "alloc")]
+fn alloc_digits(v: PreFormatted<'_>) -> crate::OwnedPreFormatted {
+    use crate::OwnedPreFormatted;
+
+    match OwnedPreFormatted::from(v) {
+        OwnedPreFormatted::Finite(sign, mut digits, zeros, exp) => {
+            digits.resize(digits.len() + zeros, b'0');
+            OwnedPreFormatted::Finite(sign, digits, 0, exp)
+        }
+        owned => owned,
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde_owned() {
+    use crate::{OwnedPreFormatted, OwnedPreParsed};
+
+    let mut buf = [0; crate::PREFORMAT_SHORTEST_BUF_LEN];
+    let owned = OwnedPreFormatted::from((-0.00401f64).preformat_shortest(&mut buf));
+    let json = serde_json::to_string(&owned).unwrap();
+    assert_eq!(json, r#"{"Finite":[true,"401",0,-2]}"#);
+    let back: OwnedPreFormatted = serde_json::from_str(&json).unwrap();
+    assert_eq!(back, owned);
+
+    let json = serde_json::to_string(&OwnedPreFormatted::Inf(false)).unwrap();
+    assert_eq!(json, r#"{"Inf":false}"#);
+
+    let owned = OwnedPreParsed {
+        sign: false,
+        int_digits: b"12".to_vec(),
+        frac_digits: b"".to_vec(),
+        exp: -1,
+    };
+    let json = serde_json::to_string(&owned).unwrap();
+    assert_eq!(
+        json,
+        r#"{"sign":false,"int_digits":"12","frac_digits":"","exp":-1}"#
+    );
+    let back: OwnedPreParsed = serde_json::from_str(&json).unwrap();
+    assert_eq!(f64::from_preparsed(back.as_preparsed()), Ok(1.2));
+
+    assert!(serde_json::from_str::<OwnedPreParsed>(
+        r#"{"sign":false,"int_digits":"1a","frac_digits":"","exp":0}"#
+    )
+    .is_err());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde_helpers() {
+    #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+    #[serde(crate = "serde")]
+    struct Values {
+        #[serde(with = "crate::serde_helpers::shortest")]
+        a: f64,
+        #[serde(with = "crate::serde_helpers::shortest")]
+        b: f32,
+    }
+
+    let values = Values {
+        a: -2.2250738585072014e-308,
+        b: f32::NEG_INFINITY,
+    };
+    let json = serde_json::to_string(&values).unwrap();
+    assert_eq!(json, r#"{"a":"-2.2250738585072014e-308","b":"-inf"}"#);
+    assert_eq!(serde_json::from_str::<Values>(&json).unwrap(), values);
+
+    assert!(serde_json::from_str::<Values>(r#"{"a":"1,5","b":"0"}"#).is_err());
+    assert!(serde_json::from_str::<Values>(r#"{"a":1.5,"b":"0"}"#).is_err());
+}
+
+#[test]
+fn test_display() {
+    use crate::display::{DebugFloats, Exp, Fixed, Shortest};
+    use std::format;
+
+    assert_eq!(format!("{}", Shortest(-0.0f32)), "-0");
+    assert_eq!(format!("{}", Shortest(f64::NEG_INFINITY)), "-inf");
+    assert_eq!(format!("{}", Shortest(123456.0f64)), "123456");
+    assert_eq!(format!("{}", Fixed(0.125f32, 2)), "0.12");
+    assert_eq!(format!("{}", Fixed(1e20f64, 1)), "100000000000000000000.0");
+    assert_eq!(format!("{}", Fixed(1.0f64, 900)).len(), 902);
+    assert_eq!(format!("{}", Exp(0.0f64, 3)), "0.00e0");
+    assert_eq!(format!("{}", Exp(5e-324f64, 1)), "5e-324");
+    assert_eq!(format!("{}", Exp(2.5f32, 0)), "2e0");
+
+    let values = [0.1f64, f64::NAN, -0.0, 1e100, f64::INFINITY];
+    assert_eq!(
+        format!("{:?}", DebugFloats::new(&values)),
+        "[0.1, NaN, -0, 1e100, inf]"
+    );
+    assert_eq!(format!("{:?}", DebugFloats::new(&[] as &[f32])), "[]");
+    let values = [1.5f32; 12];
+    let dump = DebugFloats {
+        separator: " ",
+        indices: true,
+        ..DebugFloats::new(&values[..])
+    };
+    assert!(format!("{}", dump).starts_with("0: 1.5 1: 1.5 2: 1.5"));
+    assert!(format!("{:?}", dump).ends_with(" 10: 1.5 11: 1.5]"));
+
+    #[cfg(feature = "ufmt")]
+    {
+        struct Buf(std::string::String);
+
+        impl ufmt::uWrite for Buf {
+            type Error = core::convert::Infallible;
+
+            fn write_str(&mut self, s: &str) -> Result<(), Self::Error> {
+                self.0.push_str(s);
+                Ok(())
+            }
+        }
+
+        let mut buf = Buf(std::string::String::new());
+        ufmt::uwrite!(
+            buf,
+            "{} {:?} {}",
+            Shortest(0.3f64),
+            Fixed(-1.5f32, 0),
+            Exp(1e10f64, 2)
+        )
+        .unwrap();
+        assert_eq!(buf.0, "0.3 -2 1.0e10");
+    }
+}
+
+#[test]
+fn test_decimal128() {
+    use crate::decimal128::{from_decimal128, to_decimal128};
+    use crate::DecimalOverflow;
+
+    assert_eq!(to_decimal128(0.5f64, 38, 0), Ok(0));
+    assert_eq!(to_decimal128(1.5f64, 38, 0), Ok(2));
+    assert_eq!(to_decimal128(-2.5f64, 38, 0), Ok(-2));
+    assert_eq!(to_decimal128(0.5000000000000001f64, 38, 0), Ok(1));
+    assert_eq!(to_decimal128(0.05f32, 38, 1), Ok(1));
+    assert_eq!(to_decimal128(1e-5f64, 38, 2), Ok(0));
+    assert_eq!(to_decimal128(-5e-324f64, 38, 2), Ok(0));
+    assert_eq!(to_decimal128(1e10f64, 38, -10), Ok(1));
+    assert_eq!(to_decimal128(4e10f64, 38, -11), Ok(0));
+    assert_eq!(to_decimal128(6e10f64, 38, -11), Ok(1));
+    assert_eq!(
+        to_decimal128(0.1f32, 38, 30),
+        Ok(100000001490116119384765625000)
+    );
+    assert_eq!(
+        to_decimal128(1e38f64, 38, 0),
+        Ok(99999999999999997748809823456034029568),
+    );
+    assert_eq!(to_decimal128(1.1e38f64, 38, 0), Err(DecimalOverflow));
+    assert_eq!(to_decimal128(99.5f64, 2, 0), Err(DecimalOverflow));
+    assert_eq!(to_decimal128(f64::MAX, 38, 0), Err(DecimalOverflow));
+    assert_eq!(
+        to_decimal128(f32::NEG_INFINITY, 38, 0),
+        Err(DecimalOverflow)
+    );
+
+    assert_eq!(from_decimal128::<f64>(1, 38, 1), Ok(0.1));
+    assert_eq!(from_decimal128::<f32>(16777217, 38, 0), Ok(16777216.0));
+    assert_eq!(from_decimal128::<f64>(-5, 38, 127), Ok(-5e-127));
+    assert_eq!(from_decimal128::<f32>(1, 38, 127), Ok(0.0));
+    assert_eq!(
+        from_decimal128::<f64>(99999999999999999999999999999999999999, 38, -127),
+        Ok(1e165),
+    );
+    assert_eq!(from_decimal128::<f64>(100, 2, 0), Err(DecimalOverflow));
+    assert_eq!(
+        from_decimal128::<f64>(i128::MIN, 38, 0),
+        Err(DecimalOverflow)
+    );
+    assert_eq!(from_decimal128::<f32>(-4, 38, -38), Err(DecimalOverflow));
+
+    for &v in &[1.0f64, -0.1, 123.456, 1e-20, 6.02214076e23] {
+        for scale in -30..=30 {
+            if let Ok(d) = to_decimal128(v, 38, scale) {
+                let back: f64 = from_decimal128(d, 38, scale).unwrap();
+                assert!((back - v).abs() <= 0.5 * 10f64.powi(-i32::from(scale)) * 1.000001);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_sql_decimal() {
+    use crate::sql_decimal::{from_sql_decimal, to_sql_decimal};
+    use crate::{DecimalOverflow, RoundingMode};
+
+    let modes = [
+        RoundingMode::NearestEven,
+        RoundingMode::NearestAway,
+        RoundingMode::TowardZero,
+        RoundingMode::Down,
+        RoundingMode::Up,
+    ];
+    let cases: &[(f64, [i128; 5])] = &[
+        (0.0, [0, 0, 0, 0, 0]),
+        (1.25, [12, 13, 12, 12, 13]),
+        (-1.25, [-12, -13, -12, -13, -12]),
+        (1.35, [14, 14, 13, 13, 14]),
+        (-1.35, [-14, -14, -13, -14, -13]),
+        (0.04, [0, 0, 0, 0, 1]),
+        (-0.04, [0, 0, 0, -1, 0]),
+        (1e-300, [0, 0, 0, 0, 1]),
+        (9999.9, [99999, 99999, 99998, 99998, 99999]),
+    ];
+    for &(v, expected) in cases {
+        for (&mode, &expected) in modes.iter().zip(expected.iter()) {
+            assert_eq!(
+                to_sql_decimal(v, 5, 1, mode),
+                Ok(expected),
+                "{} {:?}",
+                v,
+                mode
+            );
+        }
+    }
+    assert_eq!(
+        to_sql_decimal(9999.95, 5, 1, RoundingMode::NearestEven),
+        Err(DecimalOverflow),
+    );
+    assert_eq!(
+        to_sql_decimal(9999.95, 5, 1, RoundingMode::TowardZero),
+        Ok(99999),
+    );
+    assert_eq!(to_sql_decimal(-9999.95, 5, 1, RoundingMode::Up), Ok(-99999),);
+    assert_eq!(
+        to_sql_decimal(f32::NAN, 5, 1, RoundingMode::Up),
+        Err(DecimalOverflow),
+    );
+    assert_eq!(
+        to_sql_decimal(1.0f32 / 3.0, 38, 38, RoundingMode::NearestEven),
+        Ok(33333334326744079589843750000000000000),
+    );
+
+    assert_eq!(from_sql_decimal::<f32>(1, 1, 1), Ok(0.1));
+    assert_eq!(from_sql_decimal::<f32>(-10, 1, 1), Err(DecimalOverflow));
+    assert_eq!(
+        from_sql_decimal::<f32>(99999999999999999999999999999999999999, 38, 0),
+        Ok(1e38),
+    );
+}
+
+#[test]
+fn test_cbor() {
+    use crate::cbor::{from_decimal_fraction, to_decimal_fraction, DecimalFraction};
+
+    fn df(mantissa: i128, exponent: i64) -> DecimalFraction {
+        DecimalFraction { exponent, mantissa }
+    }
+
+    assert_eq!(to_decimal_fraction(-0.0f64), Some((df(0, 0), true)));
+    assert_eq!(to_decimal_fraction(1e20f64), Some((df(1, 20), true)));
+    assert_eq!(
+        to_decimal_fraction(0.1f32),
+        Some((df(100000001490116119384765625, -27), true)),
+    );
+    assert_eq!(to_decimal_fraction(0.1f64), Some((df(1, -1), false)));
+    assert_eq!(
+        to_decimal_fraction(2.0f64.powi(-20)),
+        Some((df(95367431640625, -20), true)),
+    );
+    assert_eq!(to_decimal_fraction(f32::INFINITY), None);
+    assert_eq!(to_decimal_fraction(f64::NAN), None);
+
+    assert_eq!(from_decimal_fraction::<f64>(df(0, i64::MAX)), (0.0, true));
+    assert_eq!(from_decimal_fraction::<f64>(df(-5, -1)), (-0.5, true));
+    assert_eq!(from_decimal_fraction::<f64>(df(5000, -4)), (0.5, true));
+    assert_eq!(
+        from_decimal_fraction::<f32>(df(16777217, 0)),
+        (16777216.0, false)
+    );
+    assert_eq!(
+        from_decimal_fraction::<f32>(df(1, 39)),
+        (f32::INFINITY, false)
+    );
+    assert_eq!(
+        from_decimal_fraction::<f64>(df(-1, i64::MAX)),
+        (f64::NEG_INFINITY, false)
+    );
+    assert_eq!(from_decimal_fraction::<f64>(df(1, i64::MIN)), (0.0, false));
+    assert_eq!(
+        from_decimal_fraction::<f64>(df(49406564584124654, -340)),
+        (5e-324, false),
+    );
+
+    // Exact values round-trip exactly
+    for &v in &[1.5f64, -3.0517578125e-5, 123456789.0, 1e-300, f64::MAX] {
+        if let Some((d, true)) = to_decimal_fraction(v) {
+            assert_eq!(from_decimal_fraction::<f64>(d), (v, true));
+        }
+        let (d, _) = to_decimal_fraction(v).unwrap();
+        assert_eq!(from_decimal_fraction::<f64>(d).0.to_bits(), v.to_bits());
+    }
+}
+
+#[test]
+fn test_proto3_json() {
+    use crate::proto3_json::{write, BUFFER_LEN};
+
+    let mut buf = [0; BUFFER_LEN];
+    assert_eq!(write(&mut buf, 0.0f64), "0");
+    assert_eq!(write(&mut buf, -0.0f32), "-0");
+    assert_eq!(write(&mut buf, 1.0f64), "1");
+    assert_eq!(write(&mut buf, -1.5f32), "-1.5");
+    assert_eq!(write(&mut buf, 1e-6f64), "0.000001");
+    assert_eq!(write(&mut buf, 9.99e-7f64), "9.99e-7");
+    assert_eq!(write(&mut buf, -9.99e-7f64), "-9.99e-7");
+    assert_eq!(write(&mut buf, 1e20f64), "100000000000000000000");
+    assert_eq!(write(&mut buf, 1e21f32), "1e+21");
+    assert_eq!(write(&mut buf, -1e21f64), "-1e+21");
+    assert_eq!(
+        write(&mut buf, f32::from_bits(1e21f32.to_bits() - 1)),
+        "999999950000000000000",
+    );
+    assert_eq!(write(&mut buf, f64::MAX), "1.7976931348623157e+308");
+    assert_eq!(
+        write(&mut buf, -1.2345678901234567e-6f64),
+        "-0.0000012345678901234567"
+    );
+    // Halfway between two shortest candidates, the even one is taken.
+    assert_eq!(
+        write(&mut buf, -1821959772618497.25f64),
+        "-1821959772618497.2"
+    );
+    assert_eq!(write(&mut buf, 140.890625f32), "140.89062");
+    assert_eq!(write(&mut buf, f32::NAN), "\"NaN\"");
+    assert_eq!(write(&mut buf, f32::INFINITY), "\"Infinity\"");
+}
+
+#[test]
+fn test_strtod() {
+    use crate::grammar::{strtod, Grammar, Parsed};
+    use crate::PreParsedHex;
+    use std::format;
+
+    let f64_cases: &[(&str, u64)] = &[
+        ("0x1.fffffffffffff7ffp1023", 0x7fef_ffff_ffff_ffff),
+        ("0x1.fffffffffffff8p1023", 0x7ff0_0000_0000_0000),
+        ("0x1p-1074", 1),
+        ("0x1p-1075", 0),
+        ("0x1.0000000000001p-1075", 1),
+        ("0x3p-1076", 1),
+        ("0x1.00000000000008p0", 0x3ff0_0000_0000_0000),
+        ("0x1.000000000000080000001p0", 0x3ff0_0000_0000_0001),
+        ("0x1.00000000000018p0", 0x3ff0_0000_0000_0002),
+        ("0x.8", 0x3fe0_0000_0000_0000),
+        ("0XA.Bp-2", 0x4005_6000_0000_0000),
+        ("0x0.00000000000000000000001p100", 0x4070_0000_0000_0000),
+        ("0x123456789abcdef123456789p0", 0x45b2_3456_789a_bcdf),
+        ("0x0.fffffffffffffp-1022", 0x000f_ffff_ffff_ffff),
+        ("0x1.ffffffffffffffp-1023", 0x0010_0000_0000_0000),
+        ("-0x0p5", 0x8000_0000_0000_0000),
+        ("0x1p-32768", 0),
+        ("0x1p32767", 0x7ff0_0000_0000_0000),
+    ];
+    for &(s, bits) in f64_cases {
+        let (v, len) = strtod::<f64>(s.as_bytes()).unwrap();
+        assert_eq!((v.to_bits(), len), (bits, s.len()), "{}", s);
+    }
+    let f32_cases: &[(&str, u32)] = &[
+        ("0x1.000001p0", 0x3f80_0000),
+        ("0x1.0000018p0", 0x3f80_0001),
+        ("0x1.0000010000000001p0", 0x3f80_0001),
+        ("0x1p-149", 1),
+        ("0x1.8p-150", 1),
+        ("0x1p-150", 0),
+        ("0x1.fffffep127", 0x7f7f_ffff),
+        ("0x1.ffffffp127", 0x7f80_0000),
+    ];
+    for &(s, bits) in f32_cases {
+        let (v, len) = strtod::<f32>(s.as_bytes()).unwrap();
+        assert_eq!((v.to_bits(), len), (bits, s.len()), "{}", s);
+    }
+
+    // Exact hexadecimal representations round-trip
+    let mut state = 0x0123_4567_89ab_cdefu64;
+    for _ in 0..10000 {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        let v = f64::from_bits(state);
+        if !v.is_finite() {
+            continue;
+        }
+        let frac = state & ((1 << 52) - 1);
+        let biased_exp = ((state >> 52) & 0x7ff) as i32;
+        let s = if biased_exp == 0 {
+            format!("{}0x0.{:013x}p-1022", if v < 0.0 { "-" } else { "" }, frac)
+        } else {
+            format!(
+                "{}0x1.{:013x}p{}",
+                if v < 0.0 { "-" } else { "" },
+                frac,
+                biased_exp - 1023
+            )
+        };
+        assert_eq!(strtod::<f64>(s.as_bytes()), Ok((v, s.len())), "{}", s);
+    }
+
+    assert_eq!(
+        Grammar::STRTOD.parse(b"-0x1.8P+3"),
+        Ok(Parsed::Hex(PreParsedHex {
+            sign: true,
+            int_digits: b"1",
+            frac_digits: b"8",
+            exp: 3,
+        })),
+    );
+    assert_eq!(strtod::<f64>(b"\t\n 12"), Ok((12.0, 5)));
+    assert_eq!(strtod::<f64>(b" +.5"), Ok((0.5, 4)));
+    assert_eq!(strtod::<f64>(b"0x1p"), Ok((1.0, 3)));
+    assert_eq!(strtod::<f64>(b"0x1.p1"), Ok((2.0, 6)));
+    assert_eq!(strtod::<f64>(b"-0x.p1"), Ok((-0.0, 2)));
+    assert_eq!(strtod::<f64>(b"0xg"), Ok((0.0, 1)));
+    assert_eq!(strtod::<f64>(b"5."), Ok((5.0, 2)));
+    assert_eq!(strtod::<f64>(b"."), Ok((0.0, 0)));
+    assert_eq!(strtod::<f64>(b"+"), Ok((0.0, 0)));
+    assert_eq!(strtod::<f64>(b"-INFINITY"), Ok((f64::NEG_INFINITY, 9)));
+    assert_eq!(strtod::<f64>(b"NaN(abc_12)x").unwrap().1, 11);
+    assert_eq!(strtod::<f64>(b"nan(abc").unwrap().1, 3);
+    assert_eq!(strtod::<f64>(b"nan()").unwrap().1, 5);
+    assert_eq!(strtod::<f64>(b"1e400"), Ok((f64::INFINITY, 5)));
+}
+
+#[test]
+fn test_postgres() {
+    use crate::postgres::{write_float4, write_float8, BUFFER_LEN};
+
+    let mut buf = [0; BUFFER_LEN];
+    let float8_cases: &[(f64, i32, &str)] = &[
+        (0.0, 1, "0"),
+        (-0.0, 1, "-0"),
+        (f64::NAN, 1, "NaN"),
+        (f64::INFINITY, 0, "Infinity"),
+        (1.0, 1, "1"),
+        (1e-4, 1, "0.0001"),
+        (1.2345e-5, 1, "1.2345e-05"),
+        (123456789012345.0, 1, "123456789012345"),
+        (-1.5e100, 1, "-1.5e+100"),
+        (f64::MAX, 1, "1.7976931348623157e+308"),
+        (5e-324, 1, "5e-324"),
+        (-1.2345678901234568e-5, 3, "-1.2345678901234568e-05"),
+        (1.0 / 3.0, 0, "0.333333333333333"),
+        (1.0 / 3.0, -10, "0.33333"),
+        (2.0 / 3.0, -15, "0.7"),
+        (1e15, 0, "1e+15"),
+        (999999999999999.9, 0, "1e+15"),
+        (100.0, -13, "1e+02"),
+        (f64::MAX, 0, "1.79769313486232e+308"),
+        // Ties between shortest candidates go to even, like with Ryu.
+        (-1821959772618497.25, 1, "-1.8219597726184972e+15"),
+    ];
+    for &(v, extra_float_digits, expected) in float8_cases {
+        assert_eq!(write_float8(&mut buf, v, extra_float_digits), expected);
+    }
+    let float4_cases: &[(f32, i32, &str)] = &[
+        (0.1, 1, "0.1"),
+        (0.1, 0, "0.1"),
+        (1.0 / 3.0, 1, "0.33333334"),
+        (1.0 / 3.0, 0, "0.333333"),
+        (100000.0, 1, "100000"),
+        (1000000.0, 1, "1e+06"),
+        (1e-5, 1, "1e-05"),
+        (f32::MAX, 1, "3.4028235e+38"),
+        (f32::MAX, 0, "3.40282e+38"),
+        (140.890625, 1, "140.89062"),
+        (5223.65625, 1, "5223.6562"),
+    ];
+    for &(v, extra_float_digits, expected) in float4_cases {
+        assert_eq!(write_float4(&mut buf, v, extra_float_digits), expected);
+    }
+}
+
+#[test]
+fn test_js() {
+    use crate::js::{to_fixed, to_precision, to_string, BUFFER_LEN};
+
+    let mut buf = [0; BUFFER_LEN];
+    let string_cases: &[(f64, &str)] = &[
+        (0.0, "0"),
+        (-0.0, "0"),
+        (1.0, "1"),
+        (-1.5, "-1.5"),
+        (0.1 + 0.2, "0.30000000000000004"),
+        (1e20, "100000000000000000000"),
+        (1e21, "1e+21"),
+        (0.000001, "0.000001"),
+        (1e-7, "1e-7"),
+        (f64::MAX, "1.7976931348623157e+308"),
+        (5e-324, "5e-324"),
+        // Halfway between two shortest candidates, the even one is taken.
+        (-1821959772618497.25, "-1821959772618497.2"),
+        (1710144665951985.25, "1710144665951985.2"),
+        (f64::NAN, "NaN"),
+        (f64::INFINITY, "Infinity"),
+        (f64::NEG_INFINITY, "-Infinity"),
+    ];
+    for &(v, expected) in string_cases {
+        assert_eq!(to_string(&mut buf, v), expected);
+    }
+
+    let fixed_cases: &[(f64, usize, &str)] = &[
+        (0.0, 2, "0.00"),
+        (-0.0, 2, "0.00"),
+        (1.005, 2, "1.00"),
+        (1.45, 1, "1.4"),
+        (0.5, 0, "1"),
+        (1.5, 0, "2"),
+        (2.5, 0, "3"),
+        (-1.5, 0, "-2"),
+        (0.125, 2, "0.13"),
+        (-0.0001, 2, "-0.00"),
+        (0.0001, 2, "0.00"),
+        (0.004, 2, "0.00"),
+        (0.005, 2, "0.01"),
+        (9.995, 2, "9.99"),
+        (99.5, 0, "100"),
+        (123.456, 0, "123"),
+        (1e20, 2, "100000000000000000000.00"),
+        (1e21, 2, "1e+21"),
+        (-1e21, 2, "-1e+21"),
+        (0.1, 30, "0.100000000000000005551115123126"),
+        (f64::NAN, 2, "NaN"),
+        (f64::NEG_INFINITY, 2, "-Infinity"),
+    ];
+    for &(v, fraction_digits, expected) in fixed_cases {
+        assert_eq!(to_fixed(&mut buf, v, fraction_digits), expected);
+    }
+    assert_eq!(to_fixed(&mut buf, -9.5e20, 100).len(), BUFFER_LEN);
+
+    let precision_cases: &[(f64, usize, &str)] = &[
+        (0.0, 1, "0"),
+        (0.0, 3, "0.00"),
+        (-0.0, 3, "0.00"),
+        (1.0, 3, "1.00"),
+        (123.456, 4, "123.5"),
+        (123.456, 2, "1.2e+2"),
+        (123456.0, 2, "1.2e+5"),
+        (0.000001234, 2, "0.0000012"),
+        (0.0000001234, 2, "1.2e-7"),
+        (1e-7, 1, "1e-7"),
+        (99.99, 2, "1.0e+2"),
+        (99.99, 3, "100"),
+        (2.5, 1, "3"),
+        (-2.5, 1, "-3"),
+        (1.45, 2, "1.4"),
+        (1e21, 3, "1.00e+21"),
+        (f64::NAN, 2, "NaN"),
+        (f64::INFINITY, 2, "Infinity"),
+    ];
+    for &(v, precision, expected) in precision_cases {
+        assert_eq!(to_precision(&mut buf, v, precision), expected);
+    }
+}
+
+#[test]
+fn test_java() {
+    use crate::java::{double_to_string, float_to_string, BUFFER_LEN};
+
+    let mut buf = [0; BUFFER_LEN];
+    let double_cases: &[(f64, &str)] = &[
+        (0.0, "0.0"),
+        (-0.0, "-0.0"),
+        (1.0, "1.0"),
+        (-1.5, "-1.5"),
+        (0.001, "0.001"),
+        (0.000999, "9.99E-4"),
+        (9999999.0, "9999999.0"),
+        (1e7, "1.0E7"),
+        (12345678.9, "1.23456789E7"),
+        (0.1 + 0.2, "0.30000000000000004"),
+        (f64::MAX, "1.7976931348623157E308"),
+        (f64::MIN_POSITIVE, "2.2250738585072014E-308"),
+        (-2.2250738585072014e-308, "-2.2250738585072014E-308"),
+        (5e-324, "4.9E-324"),
+        // Halfway between two shortest candidates, the even one is taken.
+        (-1821959772618497.25, "-1.8219597726184972E15"),
+        (f64::NAN, "NaN"),
+        (f64::INFINITY, "Infinity"),
+        (f64::NEG_INFINITY, "-Infinity"),
+    ];
+    for &(v, expected) in double_cases {
+        assert_eq!(double_to_string(&mut buf, v), expected);
+    }
+
+    let float_cases: &[(f32, &str)] = &[
+        (0.0, "0.0"),
+        (0.1, "0.1"),
+        (1.0 / 3.0, "0.33333334"),
+        (1e7, "1.0E7"),
+        (f32::MAX, "3.4028235E38"),
+        (1e-45, "1.4E-45"),
+        (140.890625, "140.89062"),
+        (f32::NEG_INFINITY, "-Infinity"),
+    ];
+    for &(v, expected) in float_cases {
+        assert_eq!(float_to_string(&mut buf, v), expected);
+    }
+}
+
+#[test]
+fn test_go() {
+    use crate::go::{format_float, parse_float, ParseError, SHORTEST_BUFFER_LEN};
+
+    let mut buf = [0; 64];
+    let f64_cases: &[(f64, u8, i32, &str)] = &[
+        (1.0, b'e', 5, "1.00000e+00"),
+        (1.0, b'f', 5, "1.00000"),
+        (1.0, b'g', 5, "1"),
+        (1.0, b'g', -1, "1"),
+        (20.0, b'g', -1, "20"),
+        (-1.0, b'e', 5, "-1.00000e+00"),
+        (0.0, b'e', 5, "0.00000e+00"),
+        (0.0, b'f', 5, "0.00000"),
+        (0.0, b'g', 5, "0"),
+        (0.0, b'e', -1, "0e+00"),
+        (-0.0, b'g', -1, "-0"),
+        (1e23, b'e', 17, "9.99999999999999916e+22"),
+        (1e23, b'E', -1, "1E+23"),
+        (1e23, b'f', -1, "100000000000000000000000"),
+        (1e23, b'g', -1, "1e+23"),
+        (1e23, b'G', 17, "9.9999999999999992E+22"),
+        (123456.0, b'g', -1, "123456"),
+        (1234567.0, b'g', -1, "1.234567e+06"),
+        (123456.0, b'g', 5, "1.2346e+05"),
+        (12.0, b'g', 2, "12"),
+        (0.0001, b'g', -1, "0.0001"),
+        (0.00001, b'g', -1, "1e-05"),
+        // Halfway between two shortest candidates, the even one is taken.
+        (-1821959772618497.25, b'g', -1, "-1.8219597726184972e+15"),
+        (1710144665951985.25, b'f', -1, "1710144665951985.2"),
+        (2.5, b'f', 0, "2"),
+        (3.5, b'f', 0, "4"),
+        (-0.001, b'f', 2, "-0.00"),
+        (1e-100, b'e', 2, "1.00e-100"),
+        (0.0, b'b', -1, "0p-1074"),
+        (-1.0, b'b', -1, "-4503599627370496p-52"),
+        (5e-324, b'b', -1, "1p-1074"),
+        (2f64.powi(60), b'b', -1, "4503599627370496p+8"),
+        (f64::NAN, b'g', -1, "NaN"),
+        (f64::INFINITY, b'f', 2, "+Inf"),
+        (f64::NEG_INFINITY, b'e', -1, "-Inf"),
+    ];
+    for &(v, fmt, prec, expected) in f64_cases {
+        assert_eq!(format_float(&mut buf, v, fmt, prec), Ok(expected));
+    }
+
+    let f32_cases: &[(f32, u8, i32, &str)] = &[
+        (1.0, b'b', -1, "8388608p-23"),
+        (1e-45, b'b', -1, "1p-149"),
+        (1e23, b'g', -1, "1e+23"),
+        (1e23, b'e', -1, "1e+23"),
+        (0.1, b'f', -1, "0.1"),
+        (0.1, b'f', 10, "0.1000000015"),
+        (140.890625, b'g', -1, "140.89062"),
+    ];
+    for &(v, fmt, prec, expected) in f32_cases {
+        assert_eq!(format_float(&mut buf, v, fmt, prec), Ok(expected));
+    }
+
+    let mut long_buf = [0; SHORTEST_BUFFER_LEN];
+    assert_eq!(
+        format_float(&mut long_buf, -f64::MIN_POSITIVE, b'f', -1)
+            .unwrap()
+            .len(),
+        SHORTEST_BUFFER_LEN,
+    );
+    let s = format_float(&mut long_buf, 0.1f64, b'f', 300).unwrap();
+    assert!(s.starts_with("0.1000000000000000055511151231257827"));
+    assert_eq!(s.len(), 302);
+    assert!(format_float(&mut buf, 0.1f64, b'f', 100).is_err());
+
+    assert_eq!(parse_float::<f64>(b"1e308"), Ok(1e308));
+    assert_eq!(parse_float::<f64>(b"+.5"), Ok(0.5));
+    assert_eq!(parse_float::<f64>(b"5."), Ok(5.0));
+    assert_eq!(parse_float::<f64>(b"0x1p-1074"), Ok(5e-324));
+    assert_eq!(parse_float::<f64>(b"0X.8P1"), Ok(1.0));
+    assert_eq!(parse_float::<f64>(b"1e-400"), Ok(0.0));
+    assert_eq!(parse_float::<f64>(b"inF"), Ok(f64::INFINITY));
+    assert_eq!(parse_float::<f32>(b"-infinity"), Ok(f32::NEG_INFINITY));
+    assert!(parse_float::<f64>(b"NaN").unwrap().is_nan());
+    assert_eq!(
+        parse_float::<f64>(b"2e308"),
+        Err(ParseError::Range { sign: false })
+    );
+    assert_eq!(
+        parse_float::<f32>(b"-1e39"),
+        Err(ParseError::Range { sign: true })
+    );
+    assert_eq!(
+        parse_float::<f64>(b"0x1p1024"),
+        Err(ParseError::Range { sign: false })
+    );
+    for s in [
+        &b""[..],
+        b"1e",
+        b" 1",
+        b"1x",
+        b"+nan",
+        b"-NaN",
+        b"0x1",
+        b"0x",
+        b"infi",
+    ]
+    .iter()
+    {
+        match parse_float::<f64>(s) {
+            Err(ParseError::Syntax(_)) => {}
+            r => panic!("{:?}: {:?}", s, r),
+        }
+    }
+}
+
+#[test]
+fn test_style() {
+    use crate::grammar::Grammar;
+    use crate::style::{write_styled, Style};
+    use crate::write::{ExpFormat, WriteOptions};
+
+    let style = Style::from_preparsed(&PreParsed {
+        sign: false,
+        int_digits: b"1",
+        frac_digits: b"25",
+        exp: -3,
+    });
+    assert_eq!(
+        style,
+        Style {
+            frac_digits: 2,
+            exp_marker: Some(b'e'),
+            exp_format: ExpFormat::DEFAULT,
+        },
+    );
+
+    let mut buf = [0; 64];
+    let cases: &[(&[u8], f64, &str)] = &[
+        (b"1", 2.5, "2"),
+        (b"1", 3.5, "4"),
+        (b"-1.000", 0.1, "0.100"),
+        (b"1.", 12.75, "13"),
+        (b".5", -0.25, "-0.2"),
+        (b"1e5", 12345.0, "1e4"),
+        (b"1.0E5", 12345.0, "1.2E4"),
+        (b"1.00e+05", 0.000123, "1.23e-04"),
+        (b"1.00e-005", 1e300, "1.00e300"),
+        (b"1.00e-005", 1e-3, "1.00e-003"),
+        (b"2.5e0", 0.0, "0.0e0"),
+        (b"inf", 1.5, "2"),
+        (b"0x1p3", 1.5, "2"),
+    ];
+    for &(text, v, expected) in cases {
+        let style = Style::from_text(&Grammar::STRTOD, text).unwrap();
+        assert_eq!(
+            write_styled(&mut buf, v, &style, &WriteOptions::DEFAULT),
+            Ok(expected)
+        );
+    }
+    let style = Style::from_text(&Grammar::RUST, b"1.5e+2").unwrap();
+    assert_eq!(
+        write_styled(&mut buf, f32::NEG_INFINITY, &style, &WriteOptions::DEFAULT),
+        Ok("-inf")
+    );
+    assert!(Style::from_text(&Grammar::JSON, b".5").is_err());
+}
+
+#[test]
+fn test_openmetrics() {
+    use crate::go::ParseError;
+    use crate::openmetrics::{parse, write, BUFFER_LEN};
+
+    let mut buf = [0; BUFFER_LEN];
+    let cases: &[(f64, &str)] = &[
+        (0.0, "0.0"),
+        (-0.0, "0.0"),
+        (1.0, "1.0"),
+        (-1.0, "-1.0"),
+        (123456.0, "123456.0"),
+        (1234567.0, "1.234567e+06"),
+        (0.0001, "0.0001"),
+        (0.00001, "1e-05"),
+        (-0.1, "-0.1"),
+        (f64::MAX, "1.7976931348623157e+308"),
+        (-2.2250738585072014e-308, "-2.2250738585072014e-308"),
+        // Halfway between two shortest candidates, the even one is taken.
+        (-1821959772618497.25, "-1.8219597726184972e+15"),
+        (f64::NAN, "NaN"),
+        (f64::INFINITY, "+Inf"),
+        (f64::NEG_INFINITY, "-Inf"),
+    ];
+    for &(v, expected) in cases {
+        assert_eq!(write(&mut buf, v), expected);
+        if !v.is_nan() {
+            assert_eq!(parse(expected.as_bytes()), Ok(v));
+        }
+    }
+
+    assert_eq!(parse(b"5."), Ok(5.0));
+    assert_eq!(parse(b"+1.5E3"), Ok(1500.0));
+    assert_eq!(parse(b"infinity"), Ok(f64::INFINITY));
+    assert!(parse(b"nan").unwrap().is_nan());
+    assert_eq!(parse(b"-1e309"), Err(ParseError::Range { sign: true }));
+    for s in [&b""[..], b"0x1p3", b"1_000", b" 1", b"1e"].iter() {
+        match parse(s) {
+            Err(ParseError::Syntax(_)) => {}
+            r => panic!("{:?}: {:?}", s, r),
+        }
+    }
+}
+
+#[test]
+fn test_lenient_grammar() {
+    use crate::grammar::{Grammar, Parsed};
+
+    let grammar = Grammar::LENIENT;
+    let parse = |s: &[u8]| grammar.parse(s).map(|p| p.to_float::<f64>().unwrap());
+    assert_eq!(parse(b"+1.5e3"), Ok(1500.0));
+    assert_eq!(parse(b".5"), Ok(0.5));
+    assert_eq!(parse(b"5."), Ok(5.0));
+    assert_eq!(parse(b"-Infinity"), Ok(f64::NEG_INFINITY));
+    assert!(parse(b"+NaN").unwrap().is_nan());
+    assert_eq!(parse(b"0x1F"), Ok(31.0));
+    assert_eq!(parse(b"1_000.000_5"), Ok(1000.0005));
+    assert_eq!(parse(b"1_0e1_0"), Ok(1e11));
+    assert_eq!(parse(b"0_0.0_0_1"), Ok(0.001));
+    assert_eq!(parse(b"0_0.0_0"), Ok(0.0));
+    assert_eq!(parse(b"-0_0"), Ok(-0.0));
+    assert_eq!(
+        grammar.parse(b"1_2.5"),
+        Ok(Parsed::Finite(PreParsed {
+            sign: false,
+            int_digits: b"1_2",
+            frac_digits: b"5",
+            exp: 0,
+        })),
+    );
+    for s in [
+        &b"_1"[..],
+        b"1_",
+        b"1__0",
+        b"1_.5",
+        b"1._5",
+        b"1e_5",
+        b"infinity",
+        b"nan",
+    ]
+    .iter()
+    {
+        assert!(grammar.parse(s).is_err(), "{:?}", s);
+    }
+
+    // Long numbers are rounded correctly.
+    let mut text = [b'0'; 150];
+    // 1 + 2^-53, halfway between 1 and the next number
+    let halfway = b"1.000000000000000111022302462515654042363166809082031_25";
+    text[..halfway.len()].copy_from_slice(halfway);
+    assert_eq!(parse(&text), Ok(1.0));
+    text[149] = b'1';
+    assert_eq!(parse(&text), Ok(1.0000000000000002));
+
+    let mut text = [b'0'; 150];
+    text[..2].copy_from_slice(b"1_");
+    text[141..].copy_from_slice(b"0_1e-1_41");
+    assert_eq!(parse(&text), Ok(1.0));
+    let mut text = [b'0'; 304];
+    text[..2].copy_from_slice(b"0.");
+    text[300..].copy_from_slice(b"1_25");
+    assert_eq!(parse(&text), Ok(1.25e-299));
+}
+
+#[test]
+fn test_corpus() {
+    use crate::corpus::{Generator, HardCaseKind};
+    use crate::grammar::Grammar;
+
+    let mut kinds = std::vec::Vec::new();
+    for case in Generator::new(0).take(4000) {
+        // The parser of `std` before Rust 1.55 rejects the inputs that need
+        // more than about 375 digits of intermediate precision.
+        if let Ok(parsed) = case.text().parse::<f64>() {
+            assert_eq!(parsed.to_bits(), case.value().to_bits(), "{:?}", case);
+        }
+        let parsed = Grammar::RUST.parse(case.text().as_bytes()).unwrap();
+        let parsed: f64 = parsed.to_float().unwrap();
+        assert_eq!(parsed.to_bits(), case.value().to_bits(), "{:?}", case);
+        if !kinds.contains(&case.kind()) {
+            kinds.push(case.kind());
+        }
+    }
+    assert_eq!(kinds.len(), 4);
+    assert!(kinds.contains(&HardCaseKind::NearHalfway));
+
+    // Exact halfway points between subnormals are the longest texts.
+    let max_len = Generator::new(1)
+        .take(4000)
+        .map(|case| case.text().len())
+        .max()
+        .unwrap();
+    assert!(max_len > 790 && max_len <= crate::corpus::MAX_TEXT_LEN);
+
+    let texts = |seed| {
+        Generator::new(seed)
+            .take(100)
+            .map(|case| std::string::String::from(case.text()))
+            .collect::<std::vec::Vec<_>>()
+    };
+    assert_eq!(texts(7), texts(7));
+    assert_ne!(texts(7), texts(8));
+}
+
+#[test]
+fn test_preformat_shortest_checked() {
+    use crate::{PreformatError, PREFORMAT_SHORTEST_BUF_LEN};
+
+    fn check<F: FloatExt + std::fmt::Debug>(v: F) {
+        let mut buf1 = [0; PREFORMAT_SHORTEST_BUF_LEN];
+        let mut buf2 = [0; PREFORMAT_SHORTEST_BUF_LEN];
+        assert_eq!(
+            v.preformat_shortest_checked(&mut buf1),
+            Ok(v.preformat_shortest(&mut buf2)),
+            "{:?}",
+            v
+        );
+    }
+
+    for &v in &[
+        0.0,
+        -0.0,
+        1.0,
+        5e-324,
+        f64::MIN_POSITIVE,
+        f64::MAX,
+        f64::NAN,
+    ] {
+        check(v);
+        check(v as f32);
+    }
+
+    let mut state = 0x1234_5678_9abc_def0u64;
+    for _ in 0..10000 {
+        // xorshift64
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+
+        check(f64::from_bits(state));
+        check(f32::from_bits(state as u32));
+        // Powers of two, where the lower neighbor is closer.
+        check(f64::from_bits((state % 0x7FF) << 52));
+        check(f32::from_bits(((state % 0xFF) as u32) << 23));
+    }
+
+    let mut buf = [0; 10];
+    assert!(matches!(
+        1.0f64.preformat_shortest_checked(&mut buf),
+        Err(PreformatError::BufferTooSmall(_))
+    ));
+}
+
+#[test]
+fn test_verify_tables() {
+    use crate::TableCorruption;
+    use std::string::ToString as _;
+
+    assert_eq!(crate::verify_tables(), Ok(()));
+
+    let err = TableCorruption {
+        table: "dragon::POW10",
+        index: Some(3),
+    };
+    assert_eq!(
+        err.to_string(),
+        "lookup table dragon::POW10 is corrupted at index 3"
+    );
+    let err = TableCorruption {
+        table: "grisu::CACHED_POW10",
+        index: None,
+    };
+    assert_eq!(
+        err.to_string(),
+        "lookup table grisu::CACHED_POW10 is corrupted"
+    );
+}
+
+#[test]
+fn test_predecoded() {
+    use crate::{PreDecoded, PREFORMAT_EXACT_FIXED_BASE_BUF_LEN};
+
+    fn check<F: FloatExt + std::fmt::Debug>(v: F) {
+        let decoded = PreDecoded::new(v);
+        let mut buf1 = [0; PREFORMAT_EXACT_FIXED_BASE_BUF_LEN];
+        let mut buf2 = [0; PREFORMAT_EXACT_FIXED_BASE_BUF_LEN];
+        assert_eq!(
+            decoded.preformat_shortest(&mut buf1),
+            v.preformat_shortest(&mut buf2),
+            "{:?}",
+            v
+        );
+        for &n in &[1, 3, 17, 40, 1000] {
+            assert_eq!(
+                decoded.preformat_exact_exp(&mut buf1, n),
+                v.preformat_exact_exp(&mut buf2, n),
+                "{:?} {}",
+                v,
+                n
+            );
+        }
+        for &n in &[-20, -1, 0, 2, 30, 2000] {
+            assert_eq!(
+                decoded.preformat_exact_fixed(&mut buf1, n),
+                v.preformat_exact_fixed(&mut buf2, n),
+                "{:?} {}",
+                v,
+                n
+            );
+            assert_eq!(
+                decoded.preformat_exact_limit(&mut buf1, n as i16),
+                v.preformat_exact_limit(&mut buf2, n as i16),
+                "{:?} {}",
+                v,
+                n
+            );
+        }
+    }
+
+    for &v in &[
+        0.0,
+        -0.0,
+        1.0,
+        f64::MIN_POSITIVE,
+        f64::MAX,
+        f64::NAN,
+        f64::NEG_INFINITY,
+    ] {
+        check(v);
+        check(v as f32);
+    }
+
+    let mut state = 0x1234_5678_9abc_def0u64;
+    for _ in 0..300 {
+        // xorshift64
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+
+        check(f64::from_bits(state));
+        check(f32::from_bits(state as u32));
+        // Subnormals and powers of two
+        check(f64::from_bits(state & 0x800F_FFFF_FFFF_FFFF));
+        check(f32::from_bits((state as u32) & 0xFF80_0000));
+    }
+}
+
+#[test]
+fn test_try_preformat() {
+    use crate::{BufferTooSmall, PreformatError, PREFORMAT_EXACT_FIXED_BASE_BUF_LEN};
+    use std::string::ToString as _;
+
+    let mut buf = [0; PREFORMAT_EXACT_FIXED_BASE_BUF_LEN];
+    let base = PREFORMAT_EXACT_FIXED_BASE_BUF_LEN;
+    let too_small = |required, actual| {
+        Err(PreformatError::BufferTooSmall(BufferTooSmall {
+            required,
+            actual,
+        }))
+    };
+
+    assert_eq!(
+        f64::MIN_POSITIVE.try_preformat_shortest(&mut buf[..16]),
+        too_small(17, 16)
+    );
+    assert!(f64::MIN_POSITIVE
+        .try_preformat_shortest(&mut buf[..17])
+        .is_ok());
+
+    assert_eq!(
+        0.1f64.try_preformat_exact_exp(&mut buf[..29], 30),
+        too_small(30, 29)
+    );
+    assert_eq!(
+        0.1f64.try_preformat_exact_exp(&mut buf, 0),
+        Err(PreformatError::ZeroDigits)
+    );
+    let exact = 0.1f64.try_preformat_exact_exp(&mut buf[..30], 30).unwrap();
+    assert_eq!(
+        exact,
+        PreFormatted::Finite(false, b"100000000000000005551115123126", 0, 0)
+    );
+    // No number has more significant digits than the base length.
+    assert_eq!(
+        f64::MIN_POSITIVE.try_preformat_exact_exp(&mut buf[..base - 1], 2000),
+        too_small(base, base - 1)
+    );
+    assert!(f64::from_bits(1)
+        .try_preformat_exact_exp(&mut buf, 2000)
+        .is_ok());
+
+    assert_eq!(
+        1.5f32.try_preformat_exact_fixed(&mut buf[..base - 1], 1),
+        too_small(base, base - 1)
+    );
+    let fixed = 1.5f32.try_preformat_exact_fixed(&mut buf, 3000).unwrap();
+    assert_eq!(fixed, 1.5f32.preformat_exact_fixed(&mut [0; 4000], 3000));
+    assert_eq!(
+        1.5f32.try_preformat_exact_limit(&mut buf[..10], 0),
+        too_small(base, 10)
+    );
+    assert_eq!(
+        1.5f32.try_preformat_exact_limit(&mut buf, 0),
+        Ok(PreFormatted::Finite(false, b"2", 0, 1))
+    );
+
+    assert_eq!(
+        too_small(base, 10).unwrap_err().to_string(),
+        "buffer too small: needs 828 bytes, got 10"
+    );
+    assert_eq!(
+        PreformatError::ZeroDigits.to_string(),
+        "zero significant digits requested"
+    );
+}
+
+#[test]
+fn test_exact_limit() {
+    let mut buf = [0; crate::PREFORMAT_EXACT_FIXED_BASE_BUF_LEN];
+    let cases: &[(f64, i16, PreFormatted<'_>)] = &[
+        (12345.0, 2, PreFormatted::Finite(false, b"123", 0, 5)),
+        (-12345.0, 0, PreFormatted::Finite(true, b"12345", 0, 5)),
+        (12350.0, 2, PreFormatted::Finite(false, b"124", 0, 5)),
+        (12250.0, 2, PreFormatted::Finite(false, b"122", 0, 5)),
+        (49.0, 2, PreFormatted::Zero(false)),
+        (-51.0, 2, PreFormatted::Finite(true, b"1", 0, 3)),
+        (0.125, -2, PreFormatted::Finite(false, b"12", 0, 0)),
+        (0.0, 3, PreFormatted::Zero(false)),
+        (1e300, 298, PreFormatted::Finite(false, b"100", 0, 301)),
+        (1e-300, -299, PreFormatted::Zero(false)),
+        (1e-300, -300, PreFormatted::Finite(false, b"1", 0, -299)),
+        (f64::INFINITY, 2, PreFormatted::Inf(false)),
+    ];
+    for &(v, limit, expected) in cases {
+        assert_eq!(v.preformat_exact_limit(&mut buf, limit), expected);
+    }
+
+    // Same as `preformat_exact_fixed` with a negative limit.
+    let mut fixed_buf = [0; crate::PREFORMAT_EXACT_FIXED_BASE_BUF_LEN + 20];
+    let mut state = 0x1234_5678_9abc_def0u64;
+    for _ in 0..10000 {
+        // xorshift64
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+
+        // Values between about 1e-13 and 1e13
+        let v = f64::from_bits(0x3D40_0000_0000_0000 + (state >> 6) % (0x0560 << 48));
+        let frac_digits = (state % 20) as usize;
+        assert_eq!(
+            v.preformat_exact_limit(&mut buf, -(frac_digits as i16)),
+            v.preformat_exact_fixed(&mut fixed_buf, frac_digits as isize),
+        );
+    }
+}
+
+#[test]
+fn test_exact_fixed_negative() {
+    let mut buf = [0; crate::PREFORMAT_EXACT_FIXED_BASE_BUF_LEN];
+    let cases: &[(f64, isize, PreFormatted<'_>)] = &[
+        (1234.5, -1, PreFormatted::Finite(false, b"123", 1, 4)),
+        (-1250.0, -2, PreFormatted::Finite(true, b"12", 2, 4)),
+        (1350.0, -2, PreFormatted::Finite(false, b"14", 2, 4)),
+        (999_999.0, -3, PreFormatted::Finite(false, b"1000", 3, 7)),
+        (499.9, -3, PreFormatted::Zero(false)),
+        (2500.0, -3, PreFormatted::Finite(false, b"2", 3, 4)),
+        (500.5, -3, PreFormatted::Finite(false, b"1", 3, 4)),
+        (1e300, -298, PreFormatted::Finite(false, b"100", 298, 301)),
+        (f64::MAX, -400, PreFormatted::Zero(false)),
+        (f64::NAN, -2, PreFormatted::NaN),
+        (-0.0, -2, PreFormatted::Zero(true)),
+    ];
+    for &(v, frac_digits, expected) in cases {
+        assert_eq!(v.preformat_exact_fixed(&mut buf, frac_digits), expected);
+    }
+
+    let preformatted = 1_234_567.0f64.preformat_exact_fixed(&mut buf, -3);
+    let mut out = [0; 32];
+    assert_eq!(
+        crate::write::write_positional(
+            &mut out,
+            preformatted,
+            0,
+            &crate::write::WriteOptions::DEFAULT,
+        ),
+        Ok("1235000"),
+    );
+}
+
+#[test]
+fn test_shortest_limit() {
+    let mut buf = [0; crate::PREFORMAT_EXACT_FIXED_BASE_BUF_LEN];
+    let cases: &[(f64, i16, (PreFormatted<'_>, bool))] = &[
+        (0.0, -2, (PreFormatted::Zero(false), false)),
+        (-0.0, 2, (PreFormatted::Zero(true), false)),
+        (f64::NAN, -2, (PreFormatted::NaN, false)),
+        (f64::NEG_INFINITY, -2, (PreFormatted::Inf(true), false)),
+        (1.5, -2, (PreFormatted::Finite(false, b"15", 0, 1), false)),
+        (1.25, -2, (PreFormatted::Finite(false, b"125", 0, 1), false)),
+        (1.125, -2, (PreFormatted::Finite(false, b"112", 0, 1), true)),
+        (-1.005, -2, (PreFormatted::Finite(true, b"100", 0, 1), true)),
+        (0.001, -2, (PreFormatted::Zero(false), true)),
+        (-0.009, -2, (PreFormatted::Finite(true, b"1", 0, -1), true)),
+        (1234.0, 2, (PreFormatted::Finite(false, b"12", 0, 4), true)),
+        (1200.0, 2, (PreFormatted::Finite(false, b"12", 0, 4), false)),
+        (1e300, 0, (PreFormatted::Finite(false, b"1", 0, 301), false)),
+    ];
+    for &(v, limit, expected) in cases {
+        assert_eq!(v.preformat_shortest_limit(&mut buf, limit), expected);
+    }
+
+    let v = 1.0f32 / 3.0;
+    assert_eq!(
+        v.preformat_shortest_limit(&mut buf, -4),
+        (PreFormatted::Finite(false, b"3333", 0, 0), true)
+    );
+    assert_eq!(
+        v.preformat_shortest_limit(&mut buf, -9),
+        (PreFormatted::Finite(false, b"33333334", 0, 0), false)
+    );
+}
+
+#[test]
+fn test_shortest_parts() {
+    let mut state = 0x1234_5678_9abc_def0u64;
+    for _ in 0..2000 {
+        // xorshift64
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+
+        let v = f64::from_bits(state);
+        let mut buf = [0; crate::PREFORMAT_SHORTEST_BUF_LEN];
+        match (v.to_shortest_parts(), v.preformat_shortest(&mut buf)) {
+            (None, PreFormatted::NaN) | (None, PreFormatted::Inf(_)) => {}
+            (Some((m, exp, sign)), PreFormatted::Finite(..)) => {
+                assert_ne!(m % 10, 0);
+                assert!(m < 10u64.pow(17));
+                let mut digits_buf = [0; 20];
+                let digits = std::format!("{}", m);
+                digits_buf[..digits.len()].copy_from_slice(digits.as_bytes());
+                let preparsed = PreParsed {
+                    sign,
+                    int_digits: &digits_buf[..digits.len()],
+                    frac_digits: b"",
+                    exp,
+                };
+                assert_eq!(f64::from_preparsed(preparsed), Ok(v));
+            }
+            r => panic!("{:?}", r),
+        }
+    }
+    assert_eq!(0.0f64.to_shortest_parts(), Some((0, 0, false)));
+    assert_eq!(
+        f64::MAX.to_shortest_parts(),
+        Some((17976931348623157, 292, false))
+    );
+    assert_eq!((-5e-324f64).to_shortest_parts(), Some((5, -324, true)));
+    assert_eq!(100.0f32.to_shortest_parts(), Some((1, 2, false)));
+}
+
+#[test]
+fn test_digit_sink() {
+    use crate::sink::{DigitSink, Formatted};
+
+    struct ArraySink {
+        buf: [u8; 900],
+        len: usize,
+    }
+
+    impl DigitSink for ArraySink {
+        fn digit(&mut self, d: u8) {
+            self.buf[self.len] = d;
+            self.len += 1;
+        }
+    }
+
+    let mut sink = ArraySink {
+        buf: [0; 900],
+        len: 0,
+    };
+    assert_eq!(
+        (-1.5e-3f64).preformat_shortest_into(&mut sink),
+        Formatted::Finite(true, -2)
+    );
+    assert_eq!(&sink.buf[..sink.len], b"15");
+
+    sink.len = 0;
+    assert_eq!(
+        0.1f64.preformat_exact_exp_into(&mut sink, 20),
+        Formatted::Finite(false, 0)
+    );
+    assert_eq!(&sink.buf[..sink.len], b"10000000000000000555");
+
+    sink.len = 0;
+    assert_eq!(
+        1e20f64.preformat_exact_fixed_into(&mut sink, 2),
+        Formatted::Finite(false, 21)
+    );
+    assert_eq!(&sink.buf[..sink.len], b"10000000000000000000000");
+
+    sink.len = 0;
+    assert_eq!(
+        f64::MIN_POSITIVE.preformat_exact_fixed_into(&mut sink, 2),
+        Formatted::Zero(false)
+    );
+    assert_eq!(f32::NAN.preformat_shortest_into(&mut sink), Formatted::NaN);
+    assert_eq!(
+        f32::NEG_INFINITY.preformat_exact_exp_into(&mut sink, 3),
+        Formatted::Inf(true)
+    );
+    assert_eq!(
+        (-0.0f32).preformat_shortest_into(&mut sink),
+        Formatted::Zero(true)
+    );
+    assert_eq!(sink.len, 0);
+
+    // Every digit of the smallest subnormal.
+    assert_eq!(
+        5e-324f64.preformat_exact_exp_into(&mut sink, 800),
+        Formatted::Finite(false, -323)
+    );
+    assert_eq!(sink.len, 800);
+    assert_eq!(&sink.buf[..4], b"4940");
+    assert!(sink.buf[751..800].iter().all(|&c| c == b'0'));
+
+    struct ReversedSink(ArraySink);
+
+    impl DigitSink for ReversedSink {
+        fn digit(&mut self, d: u8) {
+            self.0.digit(d);
+        }
+
+        fn least_significant_first(&self) -> bool {
+            true
+        }
+    }
+
+    let mut sink = ReversedSink(ArraySink {
+        buf: [0; 900],
+        len: 0,
+    });
+    assert_eq!(
+        (-1.5e-3f64).preformat_shortest_into(&mut sink),
+        Formatted::Finite(true, -2)
+    );
+    assert_eq!(&sink.0.buf[..sink.0.len], b"51");
+
+    sink.0.len = 0;
+    assert_eq!(
+        0.1f32.preformat_exact_exp_into(&mut (&mut sink), 30),
+        Formatted::Finite(false, 0)
+    );
+    assert_eq!(&sink.0.buf[..sink.0.len], b"000526567483911611094100000001");
+    assert_eq!(f32::NAN.preformat_shortest_into(&mut sink), Formatted::NaN);
+
+    // The streamed digits are the ones written into a buffer, reversed
+    // when they are asked for from the least significant one.
+    struct VecSink(std::vec::Vec<u8>, bool);
+
+    impl DigitSink for VecSink {
+        fn digit(&mut self, d: u8) {
+            self.0.push(d);
+        }
+
+        fn least_significant_first(&self) -> bool {
+            self.1
+        }
+    }
+
+    fn split(preformatted: PreFormatted<'_>, lsf: bool) -> (Formatted, std::vec::Vec<u8>) {
+        match preformatted {
+            PreFormatted::NaN => (Formatted::NaN, std::vec![]),
+            PreFormatted::Inf(sign) => (Formatted::Inf(sign), std::vec![]),
+            PreFormatted::Zero(sign) => (Formatted::Zero(sign), std::vec![]),
+            PreFormatted::Finite(sign, digits, num_zeros, exp) => {
+                let mut digits = digits.to_vec();
+                digits.resize(digits.len() + num_zeros, b'0');
+                if lsf {
+                    digits.reverse();
+                }
+                (Formatted::Finite(sign, exp), digits)
+            }
+        }
+    }
+
+    fn check<T: FloatExt + Copy + std::fmt::Debug>(v: T) {
+        let mut buf = [0; crate::PREFORMAT_EXACT_FIXED_BASE_BUF_LEN];
+        for &lsf in &[false, true] {
+            let mut sink = VecSink(std::vec![], lsf);
+            let formatted = v.preformat_shortest_into(&mut sink);
+            let expected = split(v.preformat_shortest(&mut buf), lsf);
+            assert_eq!((formatted, sink.0), expected, "{:?}", v);
+            for &n in &[1, 2, 17, 30, 1000] {
+                let mut sink = VecSink(std::vec![], lsf);
+                let formatted = v.preformat_exact_exp_into(&mut sink, n);
+                let expected = split(v.preformat_exact_exp(&mut buf, n), lsf);
+                assert_eq!((formatted, sink.0), expected, "{:?} {} {}", v, n, lsf);
+            }
+            for &n in &[-400, -3, -1, 0, 1, 2, 20, 400, 1100] {
+                let mut sink = VecSink(std::vec![], lsf);
+                let formatted = v.preformat_exact_fixed_into(&mut sink, n);
+                let expected = split(v.preformat_exact_fixed(&mut buf, n), lsf);
+                assert_eq!((formatted, sink.0), expected, "{:?} {} {}", v, n, lsf);
+            }
+        }
+    }
+
+    for &v in &[
+        0.5f64,
+        9.5,
+        0.95,
+        9.99,
+        0.0999,
+        999.9996,
+        1499.5,
+        0.05,
+        5.0,
+        25.0,
+        1e300,
+        f64::MIN_POSITIVE,
+        f64::from_bits(1),
+        f64::MAX,
+    ] {
+        check(v);
+        check(-v);
+    }
+    let mut state = 0x1234_5678_9abc_def0u64;
+    for _ in 0..2000 {
+        // xorshift64
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+
+        check(f64::from_bits(state));
+        check(f32::from_bits(state as u32));
+    }
+}
+
+#[test]
+fn test_round_to_sig_figs() {
+    let check = |v: f64, n: usize, expected: f64| {
+        assert_eq!(v.round_to_sig_figs(n).to_bits(), expected.to_bits());
+    };
+    let check_f32 = |v: f32, n: usize, expected: f32| {
+        assert_eq!(v.round_to_sig_figs(n).to_bits(), expected.to_bits());
+    };
+    check(0.0, 1, 0.0);
+    check_f32(-0.0, 3, -0.0);
+    assert!(f64::NAN.round_to_sig_figs(3).is_nan());
+    check_f32(f32::NEG_INFINITY, 3, f32::NEG_INFINITY);
+    check(5e-324, 1, 5e-324);
+    check(1.5e-323, 1, 1e-323);
+    check(f64::MAX, 2, f64::INFINITY);
+    check(1.7e308, 2, 1.7e308);
+    check_f32(f32::MAX, 1, 3e38);
+    check_f32(0.1, 20, 0.1);
+
+    // Compare with the standard library, which rounds exactly.
+    let mut state = 0x1234_5678_9abc_def0u64;
+    for _ in 0..10000 {
+        // xorshift64
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+
+        let v = f64::from_bits(state >> 1);
+        if v.is_nan() {
+            continue;
+        }
+        let n = (state % 17) as usize + 1;
+        let expected: f64 = std::format!("{:.*e}", n - 1, v).parse().unwrap();
+        let rounded = v.round_to_sig_figs(n);
+        assert_eq!(rounded.to_bits(), expected.to_bits(), "{} {}", v, n);
+
+        let v = v as f32;
+        let expected: f32 = std::format!("{:.*e}", n - 1, v).parse().unwrap();
+        let rounded = v.round_to_sig_figs(n);
+        assert_eq!(rounded.to_bits(), expected.to_bits(), "{} {}", v, n);
+    }
+}
+
+#[test]
+fn test_stochastic_rounding() {
+    let preparsed = |s: &'static str| {
+        let (int_digits, frac_digits) = s.split_at(s.find('.').unwrap());
+        PreParsed {
+            sign: false,
+            int_digits: int_digits.as_bytes(),
+            frac_digits: &frac_digits.as_bytes()[1..],
+            exp: 0,
+        }
+    };
+
+    // Exact values never call `random`.
+    for &s in &[
+        "0.0",
+        "1.5",
+        "0.1000000000000000055511151231257827021181583404541015625",
+    ] {
+        let v: f64 = f64::from_preparsed_stochastic(preparsed(s), || unreachable!()).unwrap();
+        assert_eq!(v, s.parse::<f64>().unwrap());
+    }
+    let v = PreParsed {
+        sign: true,
+        ..preparsed("0.0")
+    };
+    assert!(f32::from_preparsed_stochastic(v, || unreachable!())
+        .unwrap()
+        .is_sign_negative());
+    assert_eq!(
+        f32::from_preparsed_stochastic(preparsed("."), || 0),
+        Err(ParseFloatError::EmptyDigits)
+    );
+
+    // Smaller than the smallest subnormal, and overflow.
+    let tiny = PreParsed {
+        exp: -324,
+        ..preparsed("1.0")
+    };
+    assert_eq!(f64::from_preparsed_stochastic(tiny, || 0), Ok(5e-324));
+    assert_eq!(f64::from_preparsed_stochastic(tiny, || u64::MAX), Ok(0.0));
+    let huge = PreParsed {
+        exp: 400,
+        ..preparsed("1.0")
+    };
+    assert_eq!(
+        f64::from_preparsed_stochastic(huge, || 0),
+        Ok(f64::INFINITY)
+    );
+
+    // 1 + 2^-54, a quarter of the way between 1 and the next `f64`, is
+    // rounded up about a quarter of the time.
+    let v = preparsed("1.000000000000000055511151231257827021181583404541015625");
+    assert_eq!(
+        f64::from_preparsed_stochastic(v, || (1 << 62) - 1),
+        Ok(1.0 + f64::EPSILON)
+    );
+    assert_eq!(f64::from_preparsed_stochastic(v, || 1 << 62), Ok(1.0));
+    let mut state = 0x1234_5678_9abc_def0u64;
+    let mut num_up = 0;
+    for _ in 0..10000 {
+        let r = f64::from_preparsed_stochastic(v, || {
+            // xorshift64
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        });
+        if r.unwrap() != 1.0 {
+            num_up += 1;
+        }
+    }
+    assert!((2300..2700).contains(&num_up), "{}", num_up);
+}
+
+#[test]
+fn test_exact_exp_rounded() {
+    use crate::RoundingMode;
+
+    // Trailing zeros can be digits or extra zeros.
+    fn trimmed(preformatted: PreFormatted<'_>) -> (bool, &[u8], i16) {
+        match preformatted {
+            PreFormatted::Finite(sign, digits, _, exp) => {
+                let len = digits.iter().rposition(|&c| c != b'0').map_or(0, |i| i + 1);
+                (sign, &digits[..len], exp)
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    let mut buf = [0; crate::PREFORMAT_EXACT_FIXED_BASE_BUF_LEN];
+    assert_eq!(
+        f64::NAN.preformat_exact_exp_rounded(&mut buf, 3, RoundingMode::Up),
+        PreFormatted::NaN
+    );
+    assert_eq!(
+        (-0.0f32).preformat_exact_exp_rounded(&mut buf, 3, RoundingMode::Down),
+        PreFormatted::Zero(true)
+    );
+    // 0.66666666666666662965923251249478198587894439697265625
+    let cases: &[(RoundingMode, PreFormatted<'_>)] = &[
+        (
+            RoundingMode::Down,
+            PreFormatted::Finite(false, b"66666", 0, 0),
+        ),
+        (
+            RoundingMode::Up,
+            PreFormatted::Finite(false, b"66667", 0, 0),
+        ),
+        (
+            RoundingMode::TowardZero,
+            PreFormatted::Finite(false, b"66666", 0, 0),
+        ),
+        (
+            RoundingMode::NearestEven,
+            PreFormatted::Finite(false, b"66667", 0, 0),
+        ),
+    ];
+    for &(mode, expected) in cases {
+        assert_eq!(
+            (2.0f64 / 3.0).preformat_exact_exp_rounded(&mut buf, 5, mode),
+            expected
+        );
+    }
+    // Exact values are not rounded.
+    assert_eq!(
+        (-0.5f64).preformat_exact_exp_rounded(&mut buf, 4, RoundingMode::Down),
+        PreFormatted::Finite(true, b"5", 3, 0)
+    );
+    let mut exact_buf = [0; crate::PREFORMAT_EXACT_FIXED_BASE_BUF_LEN];
+    assert_eq!(
+        trimmed(5e-324f64.preformat_exact_exp_rounded(&mut buf, 800, RoundingMode::Up)),
+        trimmed(5e-324f64.preformat_exact_exp(&mut exact_buf, 800)),
+    );
+
+    let mut bound_buf = [0; crate::PREFORMAT_EXACT_FIXED_BASE_BUF_LEN];
+    let mut state = 0x1234_5678_9abc_def0u64;
+    for _ in 0..2000 {
+        // xorshift64
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+
+        let v = f64::from_bits(state);
+        if !v.is_finite() || v == 0.0 {
+            continue;
+        }
+        let num_digits = (state % 25) as usize + 1;
+
+        // Same as the default rounding.
+        assert_eq!(
+            trimmed(v.preformat_exact_exp_rounded(&mut buf, num_digits, RoundingMode::NearestEven)),
+            trimmed(v.preformat_exact_exp(&mut exact_buf, num_digits)),
+        );
+
+        // The bounds are around `v`, and nearest is one of them.
+        let down = v.preformat_exact_exp_rounded(&mut buf, num_digits, RoundingMode::Down);
+        let up = v.preformat_exact_exp_rounded(&mut bound_buf, num_digits, RoundingMode::Up);
+        let to_f64 = |preformatted| {
+            let (sign, digits, exp) = trimmed(preformatted);
+            f64::from_preparsed(PreParsed {
+                sign,
+                int_digits: b"",
+                frac_digits: digits,
+                exp: exp.into(),
+            })
+            .unwrap()
+        };
+        assert!(to_f64(down) <= v && v <= to_f64(up), "{:?}", v);
+        let nearest = trimmed(v.preformat_exact_exp(&mut exact_buf, num_digits));
+        assert!(
+            nearest == trimmed(down) || nearest == trimmed(up),
+            "{:?}",
+            v
+        );
+    }
+}
+
+#[test]
+fn test_shortest_in_interval() {
+    use crate::interval::shortest_in_interval;
+
+    let mut buf = [0; crate::PREFORMAT_EXACT_FIXED_BASE_BUF_LEN];
+    let cases: &[(f64, f64, PreFormatted<'_>)] = &[
+        (-0.0, -0.0, PreFormatted::Zero(true)),
+        (-0.0, 0.0, PreFormatted::Zero(false)),
+        (f64::NEG_INFINITY, f64::INFINITY, PreFormatted::Zero(false)),
+        (f64::INFINITY, f64::INFINITY, PreFormatted::Inf(false)),
+        (
+            f64::NEG_INFINITY,
+            f64::NEG_INFINITY,
+            PreFormatted::Inf(true),
+        ),
+        (1.5, f64::INFINITY, PreFormatted::Finite(false, b"2", 0, 1)),
+        (
+            f64::NEG_INFINITY,
+            -1.5,
+            PreFormatted::Finite(true, b"2", 0, 1),
+        ),
+        (1.25, 1.25, PreFormatted::Finite(false, b"125", 0, 1)),
+        (-2.5, -1.25, PreFormatted::Finite(true, b"2", 0, 1)),
+        (0.96, 0.99, PreFormatted::Finite(false, b"97", 0, 0)),
+        (0.95, 1.05, PreFormatted::Finite(false, b"1", 0, 1)),
+        (
+            0.1,
+            0.1,
+            PreFormatted::Finite(
+                false,
+                b"1000000000000000055511151231257827021181583404541015625",
+                0,
+                0,
+            ),
+        ),
+        (123.0, 129.0, PreFormatted::Finite(false, b"126", 0, 3)),
+        (123.0, 125.0, PreFormatted::Finite(false, b"124", 0, 3)),
+        (5e-324, 1e-323, PreFormatted::Finite(false, b"5", 0, -323)),
+        (
+            f64::MAX,
+            f64::INFINITY,
+            PreFormatted::Finite(false, b"2", 0, 309),
+        ),
+    ];
+    for &(lo, hi, expected) in cases {
+        assert_eq!(
+            shortest_in_interval(lo, hi, &mut buf),
+            expected,
+            "{:?}",
+            (lo, hi)
+        );
+    }
+    assert_eq!(
+        shortest_in_interval(0.1f32, 0.1f32, &mut buf),
+        PreFormatted::Finite(false, b"100000001490116119384765625", 0, 0)
+    );
+
+    // The shortest round-trip representation of a float lies within the
+    // interval of the numbers rounding to it.
+    let mut shortest_buf = [0; crate::PREFORMAT_SHORTEST_BUF_LEN];
+    let mut state = 0x1234_5678_9abc_def0u64;
+    for _ in 0..500 {
+        // xorshift64
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+
+        let lo = f64::from_bits(state >> 2);
+        let hi = f64::from_bits((state >> 2) + (state & 0xFFFF));
+        let preformatted = shortest_in_interval(lo, hi, &mut buf);
+        let (digits, exp) = match preformatted {
+            PreFormatted::Finite(false, digits, 0, exp) => (digits, exp),
+            _ => unreachable!(),
+        };
+        let v = f64::from_preparsed(PreParsed {
+            sign: false,
+            int_digits: b"",
+            frac_digits: digits,
+            exp: exp.into(),
+        })
+        .unwrap();
+        assert!(lo <= v && v <= hi, "{:?}", (lo, hi));
+        if lo == hi {
+            continue;
+        }
+        // A float inside the interval has a shortest representation with
+        // at least as many digits.
+        match v.preformat_shortest(&mut shortest_buf) {
+            PreFormatted::Finite(_, shortest, _, _) => {
+                assert!(digits.len() <= shortest.len(), "{:?}", (lo, hi))
+            }
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[test]
+fn test_exact_exp_with_error() {
+    let mut buf = [0; crate::PREFORMAT_EXACT_FIXED_BASE_BUF_LEN];
+    assert_eq!(
+        f64::NAN.preformat_exact_exp_with_error(&mut buf, 3),
+        (PreFormatted::NaN, 0)
+    );
+    assert_eq!(
+        f32::NEG_INFINITY.preformat_exact_exp_with_error(&mut buf, 3),
+        (PreFormatted::Inf(true), 0)
+    );
+    assert_eq!(
+        (-0.0f64).preformat_exact_exp_with_error(&mut buf, 3),
+        (PreFormatted::Zero(true), 0)
+    );
+
+    let cases: &[(f64, usize, &[u8], i16, u64)] = &[
+        (1.0, 1, b"1", 1, 1),
+        (1.0, 16, b"1000000000000000", 1, 1),
+        (1.0, 17, b"10000000000000000", 1, 2),
+        (-0.1, 17, b"10000000000000001", 0, 2),
+        (0.1, 30, b"100000000000000005551115123126", 0, 6938893903908),
+        (
+            0.1,
+            40,
+            b"1000000000000000055511151231257827021182",
+            0,
+            u64::MAX,
+        ),
+        (9.999999999999998, 15, b"100000000000000", 2, 1),
+        (5e-324, 1, b"5", -323, 3),
+        (5e-324, 3, b"494", -323, 248),
+        (f64::MAX, 17, b"17976931348623157", 309, 2),
+    ];
+    for &(v, num_digits, digits, exp, bound) in cases {
+        let (preformatted, error) = v.preformat_exact_exp_with_error(&mut buf, num_digits);
+        assert_eq!(
+            preformatted,
+            PreFormatted::Finite(v < 0.0, digits, 0, exp),
+            "{:?}",
+            v
+        );
+        assert_eq!(error, bound, "{:?}", (v, num_digits));
+    }
+
+    // Compared with integer arithmetic, with `v = mant * 2^bin_exp` and the
+    // result `d * 10^s` scaled by `2^36` and, when `s` is negative, `10^-s`.
+    let mut state = 0x1234_5678_9abc_def0u64;
+    for _ in 0..2000 {
+        // xorshift64
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+
+        let mant = (state >> 40) as u32 | 1 << 23;
+        let bin_exp = (state >> 8) as i32 % 12 - 23;
+        let num_digits = (state & 7) as usize + 1;
+        let v = mant as f32 * 2f32.powi(bin_exp);
+        let (preformatted, error) = v.preformat_exact_exp_with_error(&mut buf, num_digits);
+        let (digits, exp) = match preformatted {
+            PreFormatted::Finite(false, digits, 0, exp) => (digits, exp),
+            _ => unreachable!(),
+        };
+        let d = digits
+            .iter()
+            .fold(0u128, |acc, &c| acc * 10 + u128::from(c - b'0'));
+        let s = i32::from(exp) - num_digits as i32;
+        let pow10 = 10u128.pow(s.abs() as u32);
+        let x = u128::from(mant) << (bin_exp + 36);
+        let half_ulp = 1u128 << (bin_exp + 35);
+        let (x, half_ulp, d, unit) = if s >= 0 {
+            (x, half_ulp, (d * pow10) << 36, pow10 << 36)
+        } else {
+            (x * pow10, half_ulp * pow10, d << 36, 1 << 36)
+        };
+        let diff = if x > d { x - d } else { d - x };
+        let expected = (diff + half_ulp + unit - 1) / unit;
+        assert_eq!(u128::from(error), expected, "{:?}", (v, num_digits));
+    }
+}
+
+#[test]
+fn test_columns() {
+    use crate::columns::Column;
+    use crate::write::WriteOptions;
+
+    fn write_column<T: FloatExt>(
+        values: &[T],
+        max_frac_digits: usize,
+        options: &WriteOptions<'_>,
+    ) -> (Column, std::vec::Vec<std::string::String>) {
+        let column = Column::new(values, max_frac_digits, options);
+        let mut buf = [0; 64];
+        let lines = values
+            .iter()
+            .map(|&v| {
+                let cell = column.write(&mut buf, v, options).unwrap();
+                let mut line = std::string::String::new();
+                for _ in 0..column.padding(&cell) {
+                    line.push(' ');
+                }
+                line.push_str(cell.text);
+                line
+            })
+            .collect();
+        (column, lines)
+    }
+
+    let (column, lines) = write_column(&[1.0f64, 2.5, 0.25, -0.0], 10, &WriteOptions::DEFAULT);
+    assert_eq!(
+        column,
+        Column {
+            frac_digits: 2,
+            int_width: 2
+        }
+    );
+    assert_eq!(lines, [" 1.00", " 2.50", " 0.25", "-0.00"]);
+
+    let (column, lines) = write_column(&[100.0f32, 2e3, 7.0], 3, &WriteOptions::DEFAULT);
+    assert_eq!(
+        column,
+        Column {
+            frac_digits: 0,
+            int_width: 4
+        }
+    );
+    assert_eq!(lines, [" 100", "2000", "   7"]);
+
+    // Rounded to the maximum, with a carry
+    let (column, lines) = write_column(&[9.999f64, 0.1, 1.0 / 3.0], 2, &WriteOptions::DEFAULT);
+    assert_eq!(
+        column,
+        Column {
+            frac_digits: 2,
+            int_width: 2
+        }
+    );
+    assert_eq!(lines, ["10.00", " 0.10", " 0.33"]);
+
+    let (column, lines) = write_column(&[0.25f64, 123.0], 0, &WriteOptions::DEFAULT);
+    assert_eq!(
+        column,
+        Column {
+            frac_digits: 0,
+            int_width: 3
+        }
+    );
+    assert_eq!(lines, ["  0", "123"]);
+
+    let (column, lines) = write_column(
+        &[f64::NAN, -f64::INFINITY, 1.5, -0.75],
+        5,
+        &WriteOptions::DEFAULT,
+    );
+    assert_eq!(
+        column,
+        Column {
+            frac_digits: 2,
+            int_width: 4
+        }
+    );
+    assert_eq!(lines, [" NaN", "-inf", "   1.50", "  -0.75"]);
+
+    let options = WriteOptions {
+        minus_sign: "\u{2212}",
+        decimal_point: "\u{066B}",
+        signed_zero: true,
+        ..WriteOptions::DEFAULT
+    };
+    let (column, lines) = write_column(&[-1.5f64, 10.25, -0.0], 5, &options);
+    assert_eq!(
+        column,
+        Column {
+            frac_digits: 2,
+            int_width: 2
+        }
+    );
+    assert_eq!(
+        lines,
+        ["\u{2212}1\u{066B}50", "10\u{066B}25", "\u{2212}0\u{066B}00"]
+    );
+
+    assert_eq!(
+        Column::new::<f64>(&[], 5, &WriteOptions::DEFAULT),
+        Column {
+            frac_digits: 0,
+            int_width: 0
+        }
+    );
+}
+
+#[test]
+fn test_scale2() {
+    let mut buf1 = [0; crate::PREFORMAT_EXACT_FIXED_BASE_BUF_LEN + 8];
+    let mut buf2 = [0; crate::PREFORMAT_EXACT_FIXED_BASE_BUF_LEN + 8];
+
+    assert_eq!(
+        f64::NAN.preformat_shortest_scale2(&mut buf1, 5000),
+        PreFormatted::NaN
+    );
+    assert_eq!(
+        f32::NEG_INFINITY.preformat_shortest_scale2(&mut buf1, -5000),
+        PreFormatted::Inf(true)
+    );
+    assert_eq!(
+        (-0.0f64).preformat_exact_exp_scale2(&mut buf1, 3, i32::MAX),
+        PreFormatted::Zero(true)
+    );
+    assert_eq!(
+        1.0f64.preformat_shortest_scale2(&mut buf1, -1022),
+        PreFormatted::Finite(false, b"22250738585072014", 0, -307)
+    );
+    assert_eq!(
+        1.0f32.preformat_shortest_scale2(&mut buf1, -1000),
+        PreFormatted::Finite(false, b"9332636", 0, -301)
+    );
+    assert_eq!(
+        1.0f32.preformat_shortest_scale2(&mut buf1, 1023),
+        PreFormatted::Finite(false, b"8988466", 0, 308)
+    );
+    assert_eq!(
+        1.0f64.preformat_shortest_scale2(&mut buf1, 1023),
+        PreFormatted::Finite(false, b"898846567431158", 0, 308)
+    );
+    // Subnormal `f32`
+    assert_eq!(
+        1e-45f32.preformat_shortest_scale2(&mut buf1, 149),
+        PreFormatted::Finite(false, b"1", 0, 1)
+    );
+
+    // Compared with the scaled number, when it is normal.
+    let mut state = 0x1234_5678_9abc_def0u64;
+    for _ in 0..2000 {
+        // xorshift64
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+
+        let scale2 = (state >> 52) as i32 - 2048;
+        let v = f64::from_bits(state >> 1);
+        let scaled = v * 2f64.powi(scale2);
+        if v.is_normal() && scaled.is_normal() {
+            assert_eq!(
+                v.preformat_shortest_scale2(&mut buf1, scale2),
+                scaled.preformat_shortest(&mut buf2),
+                "{:?}",
+                (v, scale2)
+            );
+            assert_eq!(
+                v.preformat_exact_exp_scale2(&mut buf1, 30, scale2),
+                scaled.preformat_exact_exp(&mut buf2, 30),
+                "{:?}",
+                (v, scale2)
+            );
+            assert_eq!(
+                v.preformat_exact_fixed_scale2(&mut buf1, 5, scale2),
+                scaled.preformat_exact_fixed(&mut buf2, 5),
+                "{:?}",
+                (v, scale2)
+            );
+        }
+
+        let scale2 = scale2 % 256;
+        let v = f32::from_bits((state >> 32) as u32);
+        let scaled = v * 2f32.powi(scale2);
+        if v.is_normal() && scaled.is_normal() {
+            assert_eq!(
+                v.preformat_shortest_scale2(&mut buf1, scale2),
+                scaled.preformat_shortest(&mut buf2),
+                "{:?}",
+                (v, scale2)
+            );
+        }
+        if v.is_finite() {
+            assert_eq!(
+                v.preformat_shortest_scale2(&mut buf1, 0),
+                v.preformat_shortest(&mut buf2),
+                "{:?}",
+                v
+            );
+        }
+    }
+}
+
+#[test]
+fn test_qformat() {
+    use crate::qformat;
+    use crate::RoundingMode;
+
+    let mut buf = [0; qformat::EXACT_BUF_LEN];
+    let parse = |sign, int_digits: &[u8], frac_digits: &[u8], exp, frac_bits, mode| {
+        let preparsed = PreParsed {
+            sign,
+            int_digits,
+            frac_digits,
+            exp,
+        };
+        qformat::from_preparsed::<i128>(preparsed, frac_bits, mode)
+    };
+
+    assert_eq!(
+        qformat::preformat_exact(0i32, 15, &mut buf),
+        PreFormatted::Zero(false)
+    );
+    assert_eq!(
+        qformat::preformat_shortest(0i64, 15, &mut buf),
+        PreFormatted::Zero(false)
+    );
+    assert_eq!(
+        qformat::preformat_exact(i32::MIN, 31, &mut buf),
+        PreFormatted::Finite(true, b"1", 0, 1)
+    );
+    assert_eq!(
+        qformat::preformat_exact(-3i32, 0, &mut buf),
+        PreFormatted::Finite(true, b"3", 0, 1)
+    );
+    assert_eq!(
+        qformat::preformat_exact(1i64 << 40, 32, &mut buf),
+        PreFormatted::Finite(false, b"256", 0, 3)
+    );
+    assert_eq!(
+        qformat::preformat_exact(1i128, 128, &mut buf),
+        PreFormatted::Finite(
+            false,
+            b"293873587705571876992184134305561419454666389193021880377187926569604314863681793212890625",
+            0,
+            -38
+        )
+    );
+    assert_eq!(
+        qformat::preformat_shortest(i128::MAX, 128, &mut buf),
+        PreFormatted::Finite(false, b"499999999999999999999999999999999999997", 0, 0)
+    );
+    assert_eq!(
+        qformat::preformat_shortest(i128::MAX, 0, &mut buf),
+        PreFormatted::Finite(false, b"170141183460469231731687303715884105727", 0, 39)
+    );
+    // Q1.15, 0.1 is 3276.8 units, rounded to 3277
+    assert_eq!(
+        qformat::preformat_shortest(3277i32, 15, &mut buf),
+        PreFormatted::Finite(false, b"1", 0, 0)
+    );
+    assert_eq!(
+        qformat::preformat_exact(3277i32, 15, &mut buf),
+        PreFormatted::Finite(false, b"100006103515625", 0, 0)
+    );
+    // 0.00005 is halfway between 1 and 2 units of Q0.15, rounded to even
+    assert_eq!(
+        qformat::preformat_shortest(2i32, 15, &mut buf),
+        PreFormatted::Finite(false, b"6", 0, -4)
+    );
+    // Q15.16, 205887 is 3.1415863037109375
+    assert_eq!(
+        qformat::preformat_exact_fixed(205887i32, 16, &mut buf, 4),
+        PreFormatted::Finite(false, b"31416", 0, 1)
+    );
+    assert_eq!(
+        qformat::preformat_exact_fixed(-205887i32, 16, &mut buf, 20),
+        PreFormatted::Finite(true, b"31415863037109375", 4, 1)
+    );
+    assert_eq!(
+        qformat::preformat_exact_fixed(-205887i32, 16, &mut buf, -1),
+        PreFormatted::Zero(true)
+    );
+    assert_eq!(
+        qformat::preformat_exact_fixed(0i32, 16, &mut buf, 3),
+        PreFormatted::Zero(false)
+    );
+    // 0.5 is rounded up like with floats, 1.5 is rounded to even, 9.5
+    // carries into a new digit
+    assert_eq!(
+        qformat::preformat_exact_fixed(1i32, 1, &mut buf, 0),
+        PreFormatted::Finite(false, b"1", 0, 1)
+    );
+    assert_eq!(
+        qformat::preformat_exact_fixed(-1i32, 1, &mut buf, 0),
+        PreFormatted::Finite(true, b"1", 0, 1)
+    );
+    assert_eq!(
+        qformat::preformat_exact_fixed(5i32, 0, &mut buf, -1),
+        PreFormatted::Finite(false, b"1", 1, 2)
+    );
+    assert_eq!(
+        qformat::preformat_exact_fixed(3i32, 1, &mut buf, 0),
+        PreFormatted::Finite(false, b"2", 0, 1)
+    );
+    assert_eq!(
+        qformat::preformat_exact_fixed(19i32, 1, &mut buf, 0),
+        PreFormatted::Finite(false, b"1", 1, 2)
+    );
+    assert_eq!(
+        qformat::preformat_exact_fixed(i128::MAX, 0, &mut buf, isize::MIN),
+        PreFormatted::Zero(false)
+    );
+    assert_eq!(
+        qformat::preformat_exact_fixed(i128::MAX, 0, &mut buf, -38),
+        PreFormatted::Finite(false, b"2", 38, 39)
+    );
+    assert_eq!(
+        qformat::preformat_exact_fixed(123456i32, 0, &mut buf, -3),
+        PreFormatted::Finite(false, b"123", 3, 6)
+    );
+    assert_eq!(
+        qformat::preformat_exact_fixed(1i32, 0, &mut buf, isize::MAX),
+        PreFormatted::Finite(false, b"1", isize::MAX as usize - 1, 1)
+    );
+    // Same as with floats, on the values that both can represent exactly
+    // (either can give some trailing zeros as digits)
+    fn trim_zeros(preformatted: PreFormatted<'_>) -> PreFormatted<'_> {
+        match preformatted {
+            PreFormatted::Finite(sign, digits, num_zeros, exp) => {
+                let len = digits.iter().rposition(|&c| c != b'0').unwrap() + 1;
+                PreFormatted::Finite(sign, &digits[..len], num_zeros + digits.len() - len, exp)
+            }
+            other => other,
+        }
+    }
+    let mut float_buf = [0; crate::PREFORMAT_EXACT_FIXED_BASE_BUF_LEN + 30];
+    for &q in &[
+        1i64,
+        3,
+        5,
+        19,
+        25,
+        95,
+        123456,
+        999_999,
+        205887,
+        (1 << 53) - 1,
+    ] {
+        for &q in &[q, -q] {
+            for frac_bits in 0..=30 {
+                let v = q as f64 / (1u64 << frac_bits) as f64;
+                for num_frac_digits in -17..=30 {
+                    assert_eq!(
+                        trim_zeros(qformat::preformat_exact_fixed(
+                            q,
+                            frac_bits,
+                            &mut buf,
+                            num_frac_digits
+                        )),
+                        trim_zeros(v.preformat_exact_fixed(&mut float_buf, num_frac_digits)),
+                        "{} at {}",
+                        v,
+                        num_frac_digits,
+                    );
+                }
+            }
+        }
+    }
+
+    assert_eq!(
+        qformat::preformat_exact_exp(205887i32, 16, &mut buf, 3),
+        PreFormatted::Finite(false, b"314", 0, 1)
+    );
+    assert_eq!(
+        qformat::preformat_exact_exp(i32::MIN, 31, &mut buf, 5),
+        PreFormatted::Finite(true, b"1", 4, 1)
+    );
+    assert_eq!(
+        qformat::preformat_exact_exp(i128::MAX, 128, &mut buf, 1),
+        PreFormatted::Finite(false, b"5", 0, 0)
+    );
+
+    assert_eq!(
+        parse(false, b"", b"", 0, 10, RoundingMode::NearestEven),
+        Err(ParseFloatError::EmptyDigits)
+    );
+    assert_eq!(
+        parse(false, b"1", b"2x", 0, 10, RoundingMode::NearestEven),
+        Err(ParseFloatError::InvalidDigit {
+            byte: b'x',
+            index: 2
+        })
+    );
+    assert_eq!(parse(true, b"000", b"", 5, 10, RoundingMode::Up), Ok(0));
+    assert_eq!(
+        parse(false, b"25", b"", -1, 0, RoundingMode::NearestEven),
+        Ok(2)
+    );
+    assert_eq!(
+        parse(false, b"35", b"", -1, 0, RoundingMode::NearestEven),
+        Ok(4)
+    );
+    assert_eq!(
+        parse(false, b"25", b"", -1, 0, RoundingMode::NearestAway),
+        Ok(3)
+    );
+    assert_eq!(parse(true, b"25", b"", -1, 0, RoundingMode::Down), Ok(-3));
+    assert_eq!(
+        parse(true, b"25", b"", -1, 0, RoundingMode::TowardZero),
+        Ok(-2)
+    );
+    assert_eq!(parse(false, b"1", b"", -5000, 128, RoundingMode::Up), Ok(1));
+    assert_eq!(
+        parse(false, b"1", b"", -5000, 128, RoundingMode::NearestEven),
+        Ok(0)
+    );
+    assert_eq!(
+        parse(false, b"1", b"", 5000, 0, RoundingMode::NearestEven),
+        Err(ParseFloatError::ExponentOutOfRange)
+    );
+    assert_eq!(
+        parse(
+            true,
+            b"170141183460469231731687303715884105728",
+            b"",
+            0,
+            0,
+            RoundingMode::NearestEven
+        ),
+        Ok(i128::MIN)
+    );
+    assert_eq!(
+        parse(
+            false,
+            b"170141183460469231731687303715884105728",
+            b"",
+            0,
+            0,
+            RoundingMode::NearestEven
+        ),
+        Err(ParseFloatError::ExponentOutOfRange)
+    );
+    // Just above and below half a unit, with many digits
+    let mut digits = [b'0'; 1000];
+    digits[0] = b'5';
+    digits[999] = b'1';
+    assert_eq!(
+        parse(false, b"", &digits, 0, 0, RoundingMode::NearestEven),
+        Ok(1)
+    );
+    digits[0] = b'4';
+    digits[1..].iter_mut().for_each(|d| *d = b'9');
+    assert_eq!(
+        parse(false, b"", &digits, 0, 0, RoundingMode::NearestEven),
+        Ok(0)
+    );
+    assert_eq!(parse(false, b"", &digits, 0, 0, RoundingMode::Up), Ok(1));
+    let preparsed = PreParsed {
+        sign: false,
+        int_digits: b"40000",
+        frac_digits: b"",
+        exp: 0,
+    };
+    assert_eq!(
+        qformat::from_preparsed::<i32>(preparsed, 16, RoundingMode::NearestEven),
+        Err(ParseFloatError::ExponentOutOfRange)
+    );
+    assert_eq!(
+        qformat::from_preparsed::<i64>(preparsed, 16, RoundingMode::NearestEven),
+        Ok(40000 << 16)
+    );
+
+    // Round trips
+    let mut state = 0x1234_5678_9abc_def0u64;
+    for _ in 0..2000 {
+        // xorshift64
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+
+        let q = (state as i64) >> (state % 64);
+        let frac_bits = (state >> 40) as u32 % 100;
+        for &shortest in [false, true].iter() {
+            let (sign, digits, exp) = match if shortest {
+                qformat::preformat_shortest(q, frac_bits, &mut buf)
+            } else {
+                qformat::preformat_exact(q, frac_bits, &mut buf)
+            } {
+                PreFormatted::Finite(sign, digits, 0, exp) => (sign, digits, exp),
+                PreFormatted::Zero(false) if q == 0 => continue,
+                _ => unreachable!(),
+            };
+            let mode = if shortest {
+                RoundingMode::NearestEven
+            } else {
+                RoundingMode::Down
+            };
+            assert_eq!(
+                parse(sign, b"", digits, exp.into(), frac_bits, mode),
+                Ok(i128::from(q)),
+                "{:?}",
+                (q, frac_bits)
+            );
+            if shortest {
+                // Dropping the last digit does not round-trip, whatever the
+                // rounding of the other digits.
+                let len = digits.len() - 1;
+                let mut shorter = [0; qformat::EXACT_BUF_LEN];
+                shorter[..len].copy_from_slice(&digits[..len]);
+                for _ in 0..2 {
+                    let v = parse(
+                        sign,
+                        b"",
+                        &shorter[..len],
+                        exp.into(),
+                        frac_bits,
+                        RoundingMode::NearestEven,
+                    );
+                    assert_ne!(v, Ok(i128::from(q)), "{:?}", (q, frac_bits));
+                    if let Some(i) = shorter[..len].iter().rposition(|&d| d != b'9') {
+                        shorter[i] += 1;
+                        shorter[i + 1..len].iter_mut().for_each(|d| *d = b'0');
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_scaled_decimal() {
+    use crate::grammar::Grammar;
+    use crate::scaled_decimal::{self, ParseError, ScaledDecimal};
+    use crate::write::WriteOptions;
+    use crate::{BufferTooSmall, RoundingMode, SyntaxError};
+
+    let mut buf = [0; scaled_decimal::PREFORMAT_BUF_LEN];
+    assert_eq!(
+        ScaledDecimal::new(0, 2).preformat(&mut buf),
+        PreFormatted::Zero(false)
+    );
+    assert_eq!(
+        ScaledDecimal::new(-12300, 2).preformat(&mut buf),
+        PreFormatted::Finite(true, b"123", 0, 3)
+    );
+    assert_eq!(
+        ScaledDecimal::new(5, 3).preformat(&mut buf),
+        PreFormatted::Finite(false, b"5", 0, -2)
+    );
+    assert_eq!(
+        ScaledDecimal::new(i128::MIN, 0).preformat(&mut buf),
+        PreFormatted::Finite(true, b"170141183460469231731687303715884105728", 0, 39)
+    );
+
+    let mut buf = [0; 80];
+    let options = WriteOptions::DEFAULT;
+    fn write<'a>(
+        buf: &'a mut [u8],
+        mantissa: i128,
+        scale: u8,
+        separator: Option<&str>,
+    ) -> Result<&'a str, BufferTooSmall> {
+        ScaledDecimal::new(mantissa, scale).write(buf, separator, &WriteOptions::DEFAULT)
+    }
+    assert_eq!(write(&mut buf, 0, 0, None).unwrap(), "0");
+    assert_eq!(write(&mut buf, 0, 3, None).unwrap(), "0.000");
+    assert_eq!(write(&mut buf, 150, 2, None).unwrap(), "1.50");
+    assert_eq!(write(&mut buf, -7, 4, Some(",")).unwrap(), "-0.0007");
+    assert_eq!(write(&mut buf, 123, 0, Some(",")).unwrap(), "123");
+    assert_eq!(write(&mut buf, 1234, 0, Some(",")).unwrap(), "1,234");
+    assert_eq!(write(&mut buf, 123456, 1, Some("_")).unwrap(), "12_345.6");
+    assert_eq!(
+        write(&mut buf, i128::MIN, 0, Some(",")).unwrap(),
+        "-170,141,183,460,469,231,731,687,303,715,884,105,728"
+    );
+    assert_eq!(
+        write(&mut buf[..4], 12345, 2, None),
+        Err(BufferTooSmall {
+            required: 6,
+            actual: 4
+        })
+    );
+
+    let parse = |s: &[u8], scale, mode| ScaledDecimal::parse(&Grammar::RUST, s, scale, mode);
+    let ok = |mantissa, scale| Ok(ScaledDecimal::new(mantissa, scale));
+    assert_eq!(parse(b"0", 2, RoundingMode::NearestEven), ok(0, 2));
+    assert_eq!(parse(b"-0.001", 2, RoundingMode::NearestEven), ok(0, 2));
+    assert_eq!(parse(b"-0.001", 2, RoundingMode::Down), ok(-1, 2));
+    assert_eq!(parse(b"12.3", 3, RoundingMode::NearestEven), ok(12300, 3));
+    assert_eq!(
+        parse(b"1.2345e2", 1, RoundingMode::NearestEven),
+        ok(1234, 1)
+    );
+    assert_eq!(
+        parse(b"1.2355e2", 1, RoundingMode::NearestEven),
+        ok(1236, 1)
+    );
+    assert_eq!(
+        parse(b"1.23451e2", 1, RoundingMode::NearestEven),
+        ok(1235, 1)
+    );
+    assert_eq!(parse(b"0.5", 0, RoundingMode::NearestEven), ok(0, 0));
+    assert_eq!(parse(b"1.5", 0, RoundingMode::NearestEven), ok(2, 0));
+    assert_eq!(parse(b"0.5", 0, RoundingMode::Up), ok(1, 0));
+    assert_eq!(parse(b"4e-100", 2, RoundingMode::Up), ok(1, 2));
+    assert_eq!(parse(b"4e-100", 2, RoundingMode::NearestEven), ok(0, 2));
+    assert_eq!(parse(b"0e100", 2, RoundingMode::NearestEven), ok(0, 2));
+    assert_eq!(parse(b"0.000e-5", 2, RoundingMode::Up), ok(0, 2));
+    assert_eq!(
+        parse(
+            b"-170141183460469231731687303715884105728",
+            0,
+            RoundingMode::NearestEven
+        ),
+        ok(i128::MIN, 0)
+    );
+    assert_eq!(
+        parse(
+            b"170141183460469231731687303715884105727.4",
+            0,
+            RoundingMode::NearestEven
+        ),
+        ok(i128::MAX, 0)
+    );
+    assert_eq!(
+        parse(
+            b"170141183460469231731687303715884105727.5",
+            0,
+            RoundingMode::NearestEven
+        ),
+        Err(ParseError::Decimal(ParseFloatError::ExponentOutOfRange))
+    );
+    assert_eq!(
+        parse(b"1e38", 1, RoundingMode::NearestEven),
+        Err(ParseError::Decimal(ParseFloatError::ExponentOutOfRange))
+    );
+    assert_eq!(
+        parse(b"inf", 2, RoundingMode::NearestEven),
+        Err(ParseError::NotDecimal)
+    );
+    assert_eq!(
+        parse(b"1x", 2, RoundingMode::NearestEven),
+        Err(ParseError::Syntax(SyntaxError { index: 1 }))
+    );
+
+    let grammar = Grammar {
+        digit_separator: Some(b'_'),
+        ..Grammar::RUST
+    };
+    assert_eq!(
+        ScaledDecimal::parse(&grammar, b"1_000.2_5", 1, RoundingMode::NearestEven),
+        ok(10002, 1)
+    );
+
+    assert_eq!(
+        ScaledDecimal::from_preparsed(
+            PreParsed {
+                sign: false,
+                int_digits: b"1a",
+                frac_digits: b"",
+                exp: 0,
+            },
+            0,
+            RoundingMode::NearestEven
+        ),
+        Err(ParseFloatError::InvalidDigit {
+            byte: b'a',
+            index: 1
+        })
+    );
+
+    // Round trip.
+    let mut state = 0x1234_5678_9abc_def0u64;
+    for _ in 0..2000 {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        let mantissa =
+            (i128::from(state as i64) << 64 | i128::from(state.rotate_left(17))) >> (state % 127);
+        let scale = (state >> 32) as u8 % 45;
+        let v = ScaledDecimal::new(mantissa, scale);
+        let text = v.write(&mut buf, None, &options).unwrap();
+        assert_eq!(
+            ScaledDecimal::parse(&Grammar::RUST, text.as_bytes(), scale, RoundingMode::Down),
+            Ok(v)
+        );
+    }
+}
+
+#[test]
+fn test_simplest_fraction() {
+    use crate::fraction::{simplest_fraction, Fraction};
+
+    let fraction = |sign, num, den| Some(Fraction { sign, num, den });
+    assert_eq!(simplest_fraction(f64::NAN), None);
+    assert_eq!(simplest_fraction(f32::NEG_INFINITY), None);
+    assert_eq!(simplest_fraction(0.0f64), fraction(false, 0, 1));
+    assert_eq!(simplest_fraction(-0.0f32), fraction(true, 0, 1));
+    assert_eq!(simplest_fraction(3.0f64), fraction(false, 3, 1));
+    assert_eq!(simplest_fraction(-0.75f64), fraction(true, 3, 4));
+    assert_eq!(simplest_fraction(0.5f32), fraction(false, 1, 2));
+    assert_eq!(simplest_fraction(0.1f64), fraction(false, 1, 10));
+    assert_eq!(simplest_fraction(0.3f64), fraction(false, 3, 10));
+    assert_eq!(simplest_fraction(2.0f64 / 3.0), fraction(false, 2, 3));
+    assert_eq!(simplest_fraction(1.0f32 / 3.0), fraction(false, 1, 3));
+    assert_eq!(simplest_fraction(1e-5f64), fraction(false, 1, 100000));
+    assert_eq!(simplest_fraction(123.456f64), fraction(false, 15432, 125));
+    assert_eq!(
+        simplest_fraction(core::f64::consts::E),
+        fraction(false, 268876667, 98914198)
+    );
+    // The smallest integer that rounds to it.
+    assert_eq!(
+        simplest_fraction(1e20f64),
+        fraction(false, 99999999999999991808, 1)
+    );
+    assert_eq!(simplest_fraction(1e50f64), None);
+    assert_eq!(simplest_fraction(f64::MAX), None);
+    assert_eq!(simplest_fraction(5e-324f64), None);
+
+    // Small fractions are their own simplest fraction.
+    for den in 1..100u32 {
+        for num in 0..=den {
+            let v = f64::from(num) / f64::from(den);
+            let Fraction { num: n, den: d, .. } = simplest_fraction(v).unwrap();
+            assert_eq!(n * u128::from(den), d * u128::from(num), "{}/{}", num, den);
+            let v = f32::from(num as u16) / f32::from(den as u16);
+            let Fraction { num: n, den: d, .. } = simplest_fraction(v).unwrap();
+            assert_eq!(n * u128::from(den), d * u128::from(num), "{}/{}", num, den);
+        }
+    }
+}
+
+#[test]
+fn test_preparsed_normalization() {
+    let preparsed = |int_digits: &'static [u8], frac_digits: &'static [u8], exp| PreParsed {
+        sign: true,
+        int_digits,
+        frac_digits,
+        exp,
+    };
+
+    // (input, leading zeros trimmed, trailing zeros trimmed, significant digits)
+    type Digits = (&'static [u8], &'static [u8], i32);
+    let cases: &[(Digits, Digits, Digits, usize)] = &[
+        ((b"", b"", 5), (b"", b"", 0), (b"", b"", 0), 0),
+        ((b"000", b"", 5), (b"0", b"", 0), (b"0", b"", 0), 0),
+        ((b"", b"00", 5), (b"0", b"", 0), (b"0", b"", 0), 0),
+        ((b"0", b"0", 5), (b"0", b"", 0), (b"0", b"", 0), 0),
+        (
+            (b"012", b"340", 0),
+            (b"12", b"340", 0),
+            (b"012", b"34", 0),
+            4,
+        ),
+        ((b"00", b"0250", 3), (b"", b"250", 2), (b"00", b"025", 3), 2),
+        (
+            (b"1200", b"00", -1),
+            (b"1200", b"00", -1),
+            (b"12", b"", 1),
+            2,
+        ),
+        ((b"1200", b"", 0), (b"1200", b"", 0), (b"12", b"", 2), 2),
+        ((b"1", b"", 0), (b"1", b"", 0), (b"1", b"", 0), 1),
+    ];
+    for &(v, leading, trailing, significant) in cases {
+        let v = preparsed(v.0, v.1, v.2);
+        assert_eq!(
+            v.trim_leading_zeros(),
+            Ok(preparsed(leading.0, leading.1, leading.2))
+        );
+        assert_eq!(
+            v.trim_trailing_zeros(),
+            Ok(preparsed(trailing.0, trailing.1, trailing.2))
+        );
+        assert_eq!(v.significant_digits(), significant);
+    }
+
+    assert_eq!(
+        preparsed(b"", b"01", i32::MIN).trim_leading_zeros(),
+        Err(ParseFloatError::ExponentOutOfRange)
+    );
+    assert_eq!(
+        preparsed(b"10", b"", i32::MAX).trim_trailing_zeros(),
+        Err(ParseFloatError::ExponentOutOfRange)
+    );
+
+    let mut buf = [0; 16];
+    assert_eq!(
+        preparsed(b"12", b"345", 1).merge_digits(&mut buf),
+        Ok(preparsed(b"12345", b"", -2))
+    );
+    assert_eq!(
+        preparsed(b"", b"5", i32::MIN).merge_digits(&mut buf),
+        Err(ParseFloatError::ExponentOutOfRange)
+    );
+
+    // The value is preserved.
+    let v = preparsed(b"00120", b"0500", 7);
+    let expected = f64::from_preparsed(v);
+    let v = v
+        .trim_leading_zeros()
+        .unwrap()
+        .trim_trailing_zeros()
+        .unwrap();
+    assert_eq!(v, preparsed(b"120", b"05", 7));
+    assert_eq!(f64::from_preparsed(v), expected);
+    assert_eq!(
+        f64::from_preparsed(v.merge_digits(&mut buf).unwrap()),
+        expected
+    );
+}
+
+#[test]
+fn test_from_preparsed_scale2() {
+    use crate::bignum::Big32x40;
+
+    fn preparsed<'a>(int_digits: &'a [u8], frac_digits: &'a [u8], exp: i32) -> PreParsed<'a> {
+        PreParsed {
+            sign: false,
+            int_digits,
+            frac_digits,
+            exp,
+        }
+    }
+    let neg = |v: PreParsed<'static>| PreParsed { sign: true, ..v };
+
+    assert_eq!(
+        f64::from_preparsed_scale2(preparsed(b"", b"", 0), 0),
+        Err(ParseFloatError::EmptyDigits)
+    );
+    assert_eq!(
+        f64::from_preparsed_scale2(preparsed(b"1", b"2x", 0), 0),
+        Err(ParseFloatError::InvalidDigit {
+            byte: b'x',
+            index: 2
+        })
+    );
+    let zero = f64::from_preparsed_scale2(neg(preparsed(b"00", b"000", 7)), 5).unwrap();
+    assert_eq!(zero.to_bits(), (-0.0f64).to_bits());
+    assert_eq!(
+        f64::from_preparsed_scale2(preparsed(b"1", b"", 0), 1023),
+        Ok(2f64.powi(1023))
+    );
+    assert_eq!(
+        f64::from_preparsed_scale2(preparsed(b"1", b"", 0), 1024),
+        Ok(f64::INFINITY)
+    );
+    assert_eq!(
+        f32::from_preparsed_scale2(neg(preparsed(b"1", b"", 400)), 0),
+        Ok(f32::NEG_INFINITY)
+    );
+    assert_eq!(
+        f64::from_preparsed_scale2(preparsed(b"1", b"", -400), -100),
+        Ok(0.0)
+    );
+
+    // Subnormals, rounded once
+    let min = 5e-324f64;
+    let one = |exp2| f64::from_preparsed_scale2(preparsed(b"1", b"", 0), exp2);
+    assert_eq!(one(-1074), Ok(min));
+    assert_eq!(one(-1075), Ok(0.0));
+    assert_eq!(
+        f64::from_preparsed_scale2(preparsed(b"1", b"000001", 0), -1075),
+        Ok(min)
+    );
+    assert_eq!(
+        f64::from_preparsed_scale2(preparsed(b"3", b"", 0), -1076),
+        Ok(min)
+    );
+    assert_eq!(
+        f64::from_preparsed_scale2(preparsed(b"1", b"5", 0), -1074),
+        Ok(2.0 * min)
+    );
+    assert_eq!(
+        f64::from_preparsed_scale2(preparsed(b"2", b"5", 0), -1074),
+        Ok(2.0 * min)
+    );
+    assert_eq!(
+        f32::from_preparsed_scale2(preparsed(b"3", b"", 0), -151),
+        Ok(f32::from_bits(1))
+    );
+
+    // 10^k * 2^-k = 5^k
+    let mut buf = [0; Big32x40::MAX_DECIMAL_LEN];
+    for &k in [1i16, 22, 23, 100, 300, 400].iter() {
+        let mut pow5 = Big32x40::from_u32(1);
+        pow5.mul_pow5(k as usize);
+        let digits = pow5.to_decimal(&mut buf);
+        let expected = f64::from_preparsed(preparsed(digits, b"", 0));
+        assert_eq!(
+            f64::from_preparsed_scale2(preparsed(b"1", b"", k.into()), -i32::from(k)),
+            expected
+        );
+        assert_eq!(
+            f64::from_preparsed_scale2(preparsed(b"", b"1", (k + 1).into()), -i32::from(k)),
+            expected
+        );
+    }
+
+    // Long digit strings, 1/9 * 2^-100
+    let digits = std::vec![b'1'; 20_000];
+    assert_eq!(
+        f64::from_preparsed_scale2(preparsed(&digits, b"", -20_000), -100),
+        Ok(1.0 / 9.0 * 2f64.powi(-100))
+    );
+    assert_eq!(
+        f64::from_preparsed_scale2(preparsed(b"1", b"", -100_000), 332_193),
+        Err(ParseFloatError::ExponentOutOfRange)
+    );
+
+    // Scaling the shortest representation of normal numbers is exact.
+    let mut state = 0x1234_5678_9abc_def0u64;
+    let mut buf = [0; crate::PREFORMAT_SHORTEST_BUF_LEN];
+    for _ in 0..2000 {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        let v = f64::from_bits(state >> 1);
+        let scale2 = (state % 401) as i32 - 200;
+        let scaled = v * 2f64.powi(scale2);
+        if !v.is_normal() || !scaled.is_normal() {
+            continue;
+        }
+        let (digits, exp) = match v.preformat_shortest(&mut buf) {
+            PreFormatted::Finite(_, digits, _, exp) => (digits, exp),
+            _ => unreachable!(),
+        };
+        assert_eq!(
+            f64::from_preparsed_scale2(preparsed(b"", digits, exp.into()), scale2),
+            Ok(scaled),
+            "{:?} {}",
+            v,
+            scale2
+        );
+        let v = v as f32;
+        let scaled = v * 2f32.powi(scale2);
+        if !v.is_normal() || !scaled.is_normal() {
+            continue;
+        }
+        let (digits, exp) = match v.preformat_shortest(&mut buf) {
+            PreFormatted::Finite(_, digits, _, exp) => (digits, exp),
+            _ => unreachable!(),
+        };
+        assert_eq!(
+            f32::from_preparsed_scale2(preparsed(b"", digits, exp.into()), scale2),
+            Ok(scaled),
+            "{:?} {}",
+            v,
+            scale2
+        );
+    }
+}
+
+#[test]
+fn test_from_preparsed_rounded() {
+    use crate::clamp::cmp_exact;
+    use crate::RoundingMode;
+    use core::cmp::Ordering;
+
+    fn parse<T: FloatExt>(
+        sign: bool,
+        int_digits: &[u8],
+        frac_digits: &[u8],
+        exp: i32,
+        mode: RoundingMode,
+    ) -> T {
+        let preparsed = PreParsed {
+            sign,
+            int_digits,
+            frac_digits,
+            exp,
+        };
+        T::from_preparsed_rounded(preparsed, mode).unwrap()
+    }
+
+    let modes = [
+        RoundingMode::NearestEven,
+        RoundingMode::NearestAway,
+        RoundingMode::TowardZero,
+        RoundingMode::Down,
+        RoundingMode::Up,
+        RoundingMode::NearestOdd,
+        RoundingMode::ToOdd,
+    ];
+
+    // Random digits, checked against the nearest value and its neighbors.
+    let mut state = 0x1234_5678_9abc_def0u64;
+    for i in 0..3000 {
+        // xorshift64
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        let num_digits = 1 + (state % 30) as usize;
+        let digits = std::format!("{:030}", state as u128 * (state >> 20) as u128);
+        let digits = &digits.as_bytes()[..num_digits];
+        let exp = (state >> 40) as i32 % 700 - 350;
+        let sign = i % 2 == 1;
+        let preparsed = PreParsed {
+            sign,
+            int_digits: b"",
+            frac_digits: digits,
+            exp,
+        };
+        let nearest = f64::from_preparsed(preparsed).unwrap();
+        if nearest.is_infinite() || nearest == 0.0 {
+            continue;
+        }
+        let below = f64::from_bits(nearest.to_bits() - 1);
+        let above = f64::from_bits(nearest.to_bits() + 1);
+        // Neighbors toward negative and positive infinity.
+        let (down, up) = if sign { (above, below) } else { (below, above) };
+        let (expected_down, expected_up) = match cmp_exact(&preparsed, nearest).unwrap() {
+            Ordering::Less => (down, nearest),
+            Ordering::Equal => (nearest, nearest),
+            Ordering::Greater => (nearest, up),
+        };
+        let expected_toward_zero = if sign { expected_up } else { expected_down };
+        let rounded = |mode| parse::<f64>(sign, b"", digits, exp, mode);
+        assert_eq!(rounded(RoundingMode::NearestEven), nearest);
+        // Only differs on ties, where the nearest even value is toward zero.
+        let away = rounded(RoundingMode::NearestAway);
+        if away != nearest {
+            let expected_away = if sign { expected_down } else { expected_up };
+            assert_eq!(away, expected_away);
+            assert_eq!(nearest.to_bits() % 2, 0);
+        }
+        assert_eq!(rounded(RoundingMode::Down), expected_down);
+        assert_eq!(rounded(RoundingMode::Up), expected_up);
+        assert_eq!(rounded(RoundingMode::TowardZero), expected_toward_zero);
+        // Only differs on ties, where the nearest value is even.
+        let odd = rounded(RoundingMode::NearestOdd);
+        if odd != nearest {
+            assert_eq!(odd.to_bits() % 2, 1);
+            assert_eq!(nearest.to_bits() % 2, 0);
+        }
+        let expected_odd = if expected_down.to_bits() % 2 == 1 {
+            expected_down
+        } else {
+            expected_up
+        };
+        assert_eq!(rounded(RoundingMode::ToOdd), expected_odd);
+    }
+
+    // Exact values are not changed.
+    for &mode in modes.iter() {
+        assert_eq!(parse::<f64>(true, b"2", b"5", 0, mode), -2.5);
+        assert_eq!(parse::<f32>(false, b"", b"125", 0, mode), 0.125);
+        assert_eq!(parse::<f64>(true, b"0", b"", 0, mode).to_bits(), 1 << 63);
+    }
+
+    // 2^53 + 1 is halfway between 2^53 and 2^53 + 2.
+    let results = [
+        9007199254740992.0,
+        9007199254740994.0,
+        9007199254740992.0,
+        9007199254740992.0,
+        9007199254740994.0,
+        9007199254740994.0,
+        9007199254740994.0,
+    ];
+    for (&mode, &expected) in modes.iter().zip(results.iter()) {
+        assert_eq!(
+            parse::<f64>(false, b"9007199254740993", b"", 0, mode),
+            expected
+        );
+        let negative = parse::<f64>(true, b"9007199254740993", b"", 0, mode);
+        let expected = match mode {
+            RoundingMode::Down => -9007199254740994.0,
+            RoundingMode::Up => -9007199254740992.0,
+            _ => -expected,
+        };
+        assert_eq!(negative, expected);
+    }
+    // The same with a far non-zero digit.
+    let mut frac = std::vec![b'0'; 5000];
+    *frac.last_mut().unwrap() = b'1';
+    let results = [
+        16777218.0, 16777218.0, 16777216.0, 16777216.0, 16777218.0, 16777218.0, 16777218.0,
+    ];
+    for (&mode, &expected) in modes.iter().zip(results.iter()) {
+        assert_eq!(parse::<f32>(false, b"16777217", &frac, 0, mode), expected);
+    }
+
+    // Overflow and underflow.
+    let results = [
+        f64::INFINITY,
+        f64::INFINITY,
+        f64::MAX,
+        f64::MAX,
+        f64::INFINITY,
+        f64::INFINITY,
+        f64::MAX,
+    ];
+    for (&mode, &expected) in modes.iter().zip(results.iter()) {
+        assert_eq!(parse::<f64>(false, b"1", b"", 400, mode), expected);
+        assert_eq!(parse::<f64>(false, b"1", b"", i32::MAX, mode), expected);
+    }
+    // Just below the halfway point between `f64::MAX` and 2^1024.
+    let almost_overflow = b"179769313486231580793728971405303415079934132710037826936173778980444968292764750946649017977587207096330286416692887910946555547851940402630657488671505820681908902000708383676273854845817711531764475730270069855571366959622842914819860834936475292719074168444365510704342711559699508093042880177904174497791";
+    let results = [
+        f64::MAX,
+        f64::MAX,
+        f64::MAX,
+        f64::MAX,
+        f64::INFINITY,
+        f64::MAX,
+        f64::MAX,
+    ];
+    for (&mode, &expected) in modes.iter().zip(results.iter()) {
+        assert_eq!(parse::<f64>(false, almost_overflow, b"", 0, mode), expected);
+    }
+    let min = f64::from_bits(1);
+    let results = [0.0, 0.0, 0.0, 0.0, min, 0.0, min];
+    for (&mode, &expected) in modes.iter().zip(results.iter()) {
+        assert_eq!(parse::<f64>(false, b"1", b"", -400, mode), expected);
+        assert_eq!(parse::<f64>(false, b"1", b"", i32::MIN, mode), expected);
+    }
+    assert_eq!(
+        parse::<f64>(true, b"1", b"", -400, RoundingMode::Down),
+        -min
+    );
+    let v = parse::<f64>(true, b"1", b"", -400, RoundingMode::Up);
+    assert_eq!(v.to_bits(), (-0.0f64).to_bits());
+    // Half of the smallest subnormal.
+    let half_min = b"24703282292062327208828439643411068618252990130716238221279284125033775363510437593264991818081799618989828234772285886546332835517796989819938739800539093906315035659515570226392290858392449105184435931802849936536152500319370457678249219365623669863658480757001585769269903706311928279558551332927834338409351978015531246597263579574622766465272827220056374006485499977096599470454020828166226237857393450736339007967761930577506740176324673600968951340535537458516661134223766678604162159680461914467291840300530057530849048765391711386591646239524912623653881879636239373280423891018672348497668235089863388587925628302755995657524455507255189313690836254779186948667994968324049705821028513185451396213837722826145437693412532098591327667236328125";
+    let results = [0.0, min, 0.0, 0.0, min, min, min];
+    for (&mode, &expected) in modes.iter().zip(results.iter()) {
+        assert_eq!(parse::<f64>(false, b"", half_min, -323, mode), expected);
+    }
+    let results = [
+        f32::from_bits(1),
+        f32::from_bits(1),
+        0.0,
+        0.0,
+        f32::from_bits(1),
+        f32::from_bits(1),
+        f32::from_bits(1),
+    ];
+    for (&mode, &expected) in modes.iter().zip(results.iter()) {
+        assert_eq!(parse::<f32>(false, b"1", b"", -45, mode), expected);
+    }
+
+    assert_eq!(
+        f64::from_preparsed_rounded(
+            PreParsed {
+                sign: false,
+                int_digits: b"1",
+                frac_digits: b"x",
+                exp: 0,
+            },
+            RoundingMode::Up
+        ),
+        Err(ParseFloatError::InvalidDigit {
+            byte: b'x',
+            index: 1
+        })
+    );
+
+    // Rounding to odd and then to a narrower format rounds once, unlike
+    // rounding to nearest twice: halfway between 1 and the next `f32`
+    // rounds to it in `f64`, and then to even.
+    let above_half = b"000000059604644775390625000000000001";
+    assert_eq!(
+        parse::<f32>(false, b"1", above_half, 0, RoundingMode::NearestEven),
+        1.0000001
+    );
+    let v = parse::<f64>(false, b"1", above_half, 0, RoundingMode::NearestEven);
+    assert_eq!(v as f32, 1.0);
+    let v = parse::<f64>(false, b"1", above_half, 0, RoundingMode::ToOdd);
+    assert_eq!(v as f32, 1.0000001);
+    let mut state = 0x1234_5678_9abc_def0u64;
+    for _ in 0..3000 {
+        // xorshift64
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        let digits = std::format!("{:030}", state as u128 * (state >> 20) as u128);
+        let digits = &digits.as_bytes()[..1 + (state % 30) as usize];
+        let exp = (state >> 40) as i32 % 90 - 45;
+        let v = parse::<f64>(false, b"", digits, exp, RoundingMode::ToOdd);
+        assert_eq!(
+            v as f32,
+            parse::<f32>(false, b"", digits, exp, RoundingMode::NearestEven)
+        );
+    }
+}
+
+#[test]
+fn test_from_preparsed_report() {
+    use crate::Exactness;
+
+    fn report<T: FloatExt>(
+        sign: bool,
+        int_digits: &[u8],
+        frac_digits: &[u8],
+        exp: i32,
+    ) -> (T, Exactness) {
+        let preparsed = PreParsed {
+            sign,
+            int_digits,
+            frac_digits,
+            exp,
+        };
+        T::from_preparsed_report(preparsed).unwrap()
+    }
+
+    assert_eq!(report::<f64>(false, b"0", b"", 0), (0.0, Exactness::Exact));
+    assert_eq!(
+        report::<f64>(false, b"1", b"25", 0),
+        (1.25, Exactness::Exact)
+    );
+    assert_eq!(
+        report::<f32>(false, b"0", b"1", 0),
+        (0.1, Exactness::RoundedUp)
+    );
+    assert_eq!(
+        report::<f32>(true, b"0", b"1", 0),
+        (-0.1, Exactness::RoundedDown)
+    );
+    assert_eq!(
+        report::<f64>(false, b"0", b"3", 0),
+        (0.3, Exactness::RoundedDown)
+    );
+    assert_eq!(
+        report::<f64>(false, b"9007199254740993", b"", 0),
+        (9007199254740992.0, Exactness::RoundedDown)
+    );
+    // A far non-zero digit is seen.
+    let mut frac = std::vec![b'0'; 5000];
+    *frac.last_mut().unwrap() = b'1';
+    assert_eq!(
+        report::<f64>(true, b"1", &frac, 0),
+        (-1.0, Exactness::RoundedUp)
+    );
+
+    // Overflow and underflow.
+    assert_eq!(
+        report::<f64>(false, b"1", b"", 400),
+        (f64::INFINITY, Exactness::RoundedUp)
+    );
+    assert_eq!(
+        report::<f32>(true, b"1", b"", 39),
+        (f32::NEG_INFINITY, Exactness::RoundedDown)
+    );
+    assert_eq!(
+        report::<f64>(false, b"1", b"", -400),
+        (0.0, Exactness::RoundedDown)
+    );
+    let (v, exactness) = report::<f64>(true, b"1", b"", -400);
+    assert_eq!(
+        (v.to_bits(), exactness),
+        ((-0.0f64).to_bits(), Exactness::RoundedUp)
+    );
+}
+
+#[test]
+fn test_from_preparsed_interval() {
+    fn interval<T: FloatExt>(
+        sign: bool,
+        int_digits: &[u8],
+        frac_digits: &[u8],
+        exp: i32,
+    ) -> (T, T) {
+        let preparsed = PreParsed {
+            sign,
+            int_digits,
+            frac_digits,
+            exp,
+        };
+        T::from_preparsed_interval(preparsed).unwrap()
+    }
+
+    assert_eq!(interval::<f64>(false, b"1", b"5", 0), (1.5, 1.5));
+    assert_eq!(
+        interval::<f64>(false, b"0", b"3", 0),
+        (0.3, 0.30000000000000004)
+    );
+    assert_eq!(
+        interval::<f64>(true, b"0", b"3", 0),
+        (-0.30000000000000004, -0.3)
+    );
+    assert_eq!(
+        interval::<f32>(false, b"16777217", b"", 0),
+        (16777216.0, 16777218.0)
+    );
+    assert_eq!(
+        interval::<f32>(true, b"16777217", b"", 0),
+        (-16777218.0, -16777216.0)
+    );
+
+    // Zeros, underflow and overflow.
+    let (lo, hi) = interval::<f64>(true, b"0", b"", 0);
+    assert_eq!((lo.to_bits(), hi.to_bits()), (1 << 63, 1 << 63));
+    let min = f64::from_bits(1);
+    assert_eq!(interval::<f64>(false, b"1", b"", -400), (0.0, min));
+    let (lo, hi) = interval::<f64>(true, b"1", b"", -400);
+    assert_eq!((lo, hi.to_bits()), (-min, 1 << 63));
+    assert_eq!(
+        interval::<f64>(false, b"1", b"", -323),
+        (f64::from_bits(2), f64::from_bits(3))
+    );
+    assert_eq!(
+        interval::<f64>(false, b"1", b"", 400),
+        (f64::MAX, f64::INFINITY)
+    );
+    assert_eq!(
+        interval::<f32>(true, b"1", b"", 39),
+        (f32::NEG_INFINITY, f32::MIN)
+    );
+}
+
+#[test]
+fn test_from_preparsed_subnormals() {
+    use crate::SubnormalPolicy;
+
+    fn parse<T: FloatExt>(
+        sign: bool,
+        int_digits: &[u8],
+        frac_digits: &[u8],
+        exp: i32,
+        policy: SubnormalPolicy,
+    ) -> T {
+        let preparsed = PreParsed {
+            sign,
+            int_digits,
+            frac_digits,
+            exp,
+        };
+        T::from_preparsed_subnormals(preparsed, policy).unwrap()
+    }
+
+    let policies = [
+        SubnormalPolicy::Gradual,
+        SubnormalPolicy::FlushToZero,
+        SubnormalPolicy::RoundToNormal,
+    ];
+
+    // Not subnormal, unchanged by any policy.
+    for &policy in policies.iter() {
+        assert_eq!(parse::<f64>(false, b"1", b"5", 0, policy), 1.5);
+        assert_eq!(
+            parse::<f64>(false, b"2", b"2250738585072012", -308, policy),
+            f64::MIN_POSITIVE
+        );
+        assert_eq!(parse::<f32>(true, b"1", b"", 39, policy), f32::NEG_INFINITY);
+        let v = parse::<f64>(true, b"1", b"", -400, policy);
+        assert_eq!(v.to_bits(), (-0.0f64).to_bits());
+    }
+
+    let v = parse::<f64>(true, b"2", b"", -310, SubnormalPolicy::Gradual);
+    assert_eq!(v, -2e-310);
+    let v = parse::<f64>(true, b"2", b"", -310, SubnormalPolicy::FlushToZero);
+    assert_eq!(v.to_bits(), (-0.0f64).to_bits());
+    let v = parse::<f32>(false, b"1", b"", -40, SubnormalPolicy::FlushToZero);
+    assert_eq!(v.to_bits(), 0);
+
+    // Half of the smallest normal number is the threshold.
+    let round = SubnormalPolicy::RoundToNormal;
+    assert_eq!(
+        parse::<f64>(false, b"1", b"2", -308, round),
+        f64::MIN_POSITIVE
+    );
+    assert_eq!(parse::<f64>(false, b"1", b"", -308, round), 0.0);
+    assert_eq!(
+        parse::<f32>(false, b"6", b"", -39, round),
+        f32::MIN_POSITIVE
+    );
+    let v = parse::<f32>(true, b"5", b"8", -39, round);
+    assert_eq!(v.to_bits(), (-0.0f32).to_bits());
+
+    let mut buf = [0; 1000];
+    let half = f64::MIN_POSITIVE / 2.0;
+    let (digits, exp) = match half.preformat_exact_exp(&mut buf, 1000) {
+        PreFormatted::Finite(false, digits, _, exp) => (digits, i32::from(exp)),
+        _ => unreachable!(),
+    };
+    assert_eq!(parse::<f64>(false, b"0", digits, exp, round), 0.0);
+    let mut above = std::vec::Vec::from(digits);
+    above.push(b'1');
+    assert_eq!(
+        parse::<f64>(false, b"0", &above, exp, round),
+        f64::MIN_POSITIVE
+    );
+}
+
+#[test]
+fn test_stream() {
+    use crate::stream::{self, Accumulator};
+
+    fn parse<T: FloatExt>(int_digits: &[u8], frac_digits: &[u8], exp: i32) -> T {
+        // One digit at a time, from a chain of chunks.
+        let chunks = |digits: &[u8]| {
+            digits
+                .chunks(3)
+                .flatten()
+                .copied()
+                .collect::<std::vec::Vec<_>>()
+        };
+        stream::from_digits_iter(false, chunks(int_digits), chunks(frac_digits), exp).unwrap()
+    }
+
+    let cases: &[(&[u8], &[u8], i32)] = &[
+        (b"0", b"", 0),
+        (b"000", b"000", 5),
+        (b"1", b"", 0),
+        (b"12", b"34", 0),
+        (b"", b"0001", 10),
+        (b"9007199254740993", b"", 0),
+        (b"17976931348623157", b"", 292),
+        (b"", b"24703282292062328", -323),
+    ];
+    for &(int_digits, frac_digits, exp) in cases {
+        let preparsed = PreParsed {
+            sign: false,
+            int_digits,
+            frac_digits,
+            exp,
+        };
+        let expected = f64::from_preparsed(preparsed).unwrap();
+        assert_eq!(parse::<f64>(int_digits, frac_digits, exp), expected);
+        let expected = f32::from_preparsed(preparsed).unwrap();
+        assert_eq!(parse::<f32>(int_digits, frac_digits, exp), expected);
+    }
+
+    // Digits beyond `MAX_DIGITS` are only seen through the sticky digit.
+    let mut digits = std::vec![b'0'; 5000];
+    digits[..16].copy_from_slice(b"9007199254740993");
+    assert_eq!(parse::<f64>(&digits, b"", -4984), 9007199254740992.0);
+    assert_eq!(parse::<f64>(b"", &digits, 16), 9007199254740992.0);
+    *digits.last_mut().unwrap() = b'1';
+    assert_eq!(parse::<f64>(&digits, b"", -4984), 9007199254740994.0);
+    assert_eq!(parse::<f64>(b"", &digits, 16), 9007199254740994.0);
+    assert_eq!(
+        parse::<f64>(&digits[..20], &digits[20..], -4),
+        9007199254740994.0
+    );
+
+    let mut acc = Accumulator::new(true);
+    acc.push_int_digit(b'1').unwrap();
+    acc.push_frac_digit(b'5').unwrap();
+    assert_eq!(
+        acc.push_frac_digit(b'x'),
+        Err(ParseFloatError::InvalidDigit {
+            byte: b'x',
+            index: 2
+        })
+    );
+    assert!(acc.push_frac_digit(b'5').is_err());
+    assert_eq!(
+        acc.finish::<f64>(0),
+        Err(ParseFloatError::InvalidDigit {
+            byte: b'x',
+            index: 2
+        })
+    );
+    assert_eq!(
+        Accumulator::new(false).finish::<f64>(0),
+        Err(ParseFloatError::EmptyDigits)
+    );
+    let v = stream::from_digits_iter::<f64, _, _>(true, None, Some(b'0'), 0);
+    assert_eq!(v.map(f64::to_bits), Ok(1 << 63));
+}
+
+#[test]
+fn test_from_decimal_parts() {
+    fn check(mantissa: u64, exp10: i32) {
+        let digits = std::format!("{}", mantissa);
+        let preparsed = PreParsed {
+            sign: false,
+            int_digits: digits.as_bytes(),
+            frac_digits: b"",
+            exp: exp10,
+        };
+        let expected = f64::from_preparsed(preparsed).unwrap();
+        assert_eq!(
+            f64::from_decimal_parts(mantissa, exp10).to_bits(),
+            expected.to_bits(),
+            "{}e{}",
+            mantissa,
+            exp10
+        );
+        let expected = f32::from_preparsed(preparsed).unwrap();
+        assert_eq!(
+            f32::from_decimal_parts(mantissa, exp10).to_bits(),
+            expected.to_bits(),
+            "{}e{}",
+            mantissa,
+            exp10
+        );
+    }
+
+    let cases: &[(u64, i32)] = &[
+        (0, 0),
+        (0, 400),
+        (0, -400),
+        (1, 0),
+        (9007199254740993, 0),
+        (u64::MAX, 0),
+        (u64::MAX, -343),
+        (17976931348623157, 292),
+        (17976931348623159, 292),
+        (24703282292062327, -340),
+        (24703282292062328, -340),
+        (1, 309),
+        (1, -325),
+        (1, i32::MAX),
+        (1, i32::MIN),
+        // Halfway between `f64`s, left to the slow algorithm.
+        (9007199254740993, 1),
+        (2470328229206232721, -342),
+    ];
+    for &(mantissa, exp10) in cases {
+        check(mantissa, exp10);
+    }
+
+    let mut state = 0x1234_5678_9abc_def0u64;
+    for _ in 0..10000 {
+        // xorshift64
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+
+        let mantissa = state >> (state % 64);
+        let exp10 = ((state >> 48) % 720) as i32 - 360;
+        check(mantissa, exp10);
+    }
+}
+
+#[test]
+fn test_from_preparsed_encoded() {
+    use crate::stream::{self, DigitEncoding};
+
+    let cases: &[(&[u8], &[u8], i32)] = &[
+        (b"0", b"", 0),
+        (b"12", b"34", 0),
+        (b"", b"0001", 10),
+        (b"9007199254740993", b"", 0),
+        (b"", b"24703282292062328", -323),
+    ];
+    for &(int_digits, frac_digits, exp) in cases {
+        let preparsed = PreParsed {
+            sign: true,
+            int_digits,
+            frac_digits,
+            exp,
+        };
+        let expected = f64::from_preparsed(preparsed).unwrap();
+        let v = stream::from_preparsed_encoded::<f64>(preparsed, DigitEncoding::Ascii);
+        assert_eq!(v.map(f64::to_bits), Ok(expected.to_bits()));
+
+        let int_values: std::vec::Vec<u8> = int_digits.iter().map(|c| c - b'0').collect();
+        let frac_values: std::vec::Vec<u8> = frac_digits.iter().map(|c| c - b'0').collect();
+        let preparsed = PreParsed {
+            int_digits: &int_values,
+            frac_digits: &frac_values,
+            ..preparsed
+        };
+        let v = stream::from_preparsed_encoded::<f64>(preparsed, DigitEncoding::Value);
+        assert_eq!(v.map(f64::to_bits), Ok(expected.to_bits()));
+    }
+
+    let preparsed = PreParsed {
+        sign: false,
+        int_digits: &[1],
+        frac_digits: b"5",
+        exp: 0,
+    };
+    assert_eq!(
+        stream::from_preparsed_encoded::<f64>(preparsed, DigitEncoding::Value),
+        Err(ParseFloatError::InvalidDigit {
+            byte: b'5',
+            index: 1
+        })
+    );
+    assert_eq!(
+        stream::from_preparsed_encoded::<f64>(preparsed, DigitEncoding::Ascii),
+        Err(ParseFloatError::InvalidDigit { byte: 1, index: 0 })
+    );
+}
+
+#[test]
+fn test_from_str_decoded() {
+    use crate::stream::{self, DigitDecoder};
+
+    struct Devanagari;
+
+    impl DigitDecoder for Devanagari {
+        fn decode(&self, c: char) -> Option<u8> {
+            match c {
+                '\u{966}'..='\u{96F}' => Some((u32::from(c) - 0x966) as u8),
+                _ => None,
+            }
+        }
+    }
+
+    let arabic_indic = |c: char| match c {
+        '\u{660}'..='\u{669}' => Some((u32::from(c) - 0x660) as u8),
+        _ => None,
+    };
+    let localize = |digits: &str, zero: u32| -> std::string::String {
+        digits
+            .chars()
+            .map(|c| core::char::from_u32(zero + c.to_digit(10).unwrap()).unwrap())
+            .collect()
+    };
+
+    let cases: &[(&str, &str, i32)] = &[
+        ("0", "", 0),
+        ("12", "34", 0),
+        ("", "0001", 10),
+        ("9007199254740993", "", 0),
+        ("17976931348623157", "", 292),
+        ("", "24703282292062328", -323),
+    ];
+    for &(int_digits, frac_digits, exp) in cases {
+        let preparsed = PreParsed {
+            sign: false,
+            int_digits: int_digits.as_bytes(),
+            frac_digits: frac_digits.as_bytes(),
+            exp,
+        };
+        let expected = f64::from_preparsed(preparsed).unwrap();
+        let v = stream::from_str_decoded::<f64, _>(
+            false,
+            &localize(int_digits, 0x660),
+            &localize(frac_digits, 0x660),
+            exp,
+            arabic_indic,
+        );
+        assert_eq!(v, Ok(expected));
+        let v = stream::from_str_decoded::<f64, _>(
+            false,
+            &localize(int_digits, 0x966),
+            &localize(frac_digits, 0x966),
+            exp,
+            Devanagari,
+        );
+        assert_eq!(v, Ok(expected));
+    }
+
+    // Positions count UTF-8 bytes.
+    assert_eq!(
+        stream::from_str_decoded::<f64, _>(false, "\u{661}", "\u{662}x", 0, arabic_indic),
+        Err(ParseFloatError::InvalidDigit {
+            byte: b'x',
+            index: 4
+        })
+    );
+    assert_eq!(
+        stream::from_str_decoded::<f64, _>(false, "\u{661}\u{967}", "", 0, arabic_indic),
+        Err(ParseFloatError::InvalidDigit {
+            byte: 0xE0,
+            index: 2
+        })
+    );
+    assert_eq!(
+        stream::from_str_decoded::<f64, _>(false, "1", "", 0, |_| Some(10)),
+        Err(ParseFloatError::InvalidDigit {
+            byte: b'1',
+            index: 0
+        })
+    );
+    assert_eq!(
+        stream::from_str_decoded::<f64, _>(false, "", "", 0, arabic_indic),
+        Err(ParseFloatError::EmptyDigits)
+    );
+}
+
+#[test]
+fn test_from_preparsed_hex() {
+    use crate::PreParsedHex;
+
+    fn parse<T: FloatExt>(int_digits: &[u8], frac_digits: &[u8], exp: i32) -> T {
+        T::from_preparsed_hex(PreParsedHex {
+            sign: false,
+            int_digits,
+            frac_digits,
+            exp,
+        })
+        .unwrap()
+    }
+
+    assert_eq!(
+        f64::from_preparsed_hex(PreParsedHex {
+            sign: false,
+            int_digits: b"",
+            frac_digits: b"",
+            exp: 0,
+        }),
+        Err(ParseFloatError::EmptyDigits)
+    );
+    assert_eq!(
+        f64::from_preparsed_hex(PreParsedHex {
+            sign: false,
+            int_digits: b"1",
+            frac_digits: b"g",
+            exp: 0,
+        }),
+        Err(ParseFloatError::InvalidDigit {
+            byte: b'g',
+            index: 1
+        })
+    );
+    let v = f64::from_preparsed_hex(PreParsedHex {
+        sign: true,
+        int_digits: b"0",
+        frac_digits: b"000",
+        exp: 100,
+    });
+    assert_eq!(v.map(f64::to_bits), Ok((-0.0f64).to_bits()));
+
+    assert_eq!(parse::<f64>(b"1", b"8", 3), 12.0);
+    assert_eq!(parse::<f64>(b"", b"Ab", 8), 171.0);
+    assert_eq!(parse::<f32>(b"00ff", b"", -8), 0.99609375);
+
+    // Halfway cases, rounded to even, and up by a far sticky digit.
+    assert_eq!(parse::<f64>(b"1", b"00000000000008", 0), 1.0);
+    assert_eq!(
+        parse::<f64>(b"1", b"00000000000018", 0),
+        1.0 + 2.0 * f64::EPSILON
+    );
+    assert_eq!(
+        parse::<f64>(b"1", b"0000000000000800000000000000000001", 0),
+        1.0 + f64::EPSILON
+    );
+    assert_eq!(parse::<f32>(b"1ffffff", b"", 0), 33554432.0);
+
+    // Overflow, including by rounding.
+    assert_eq!(parse::<f64>(b"1", b"fffffffffffff", 1023), f64::MAX);
+    assert_eq!(parse::<f64>(b"1", b"fffffffffffff8", 1023), f64::INFINITY);
+    assert_eq!(parse::<f32>(b"1", b"fffffe", 127), f32::MAX);
+    assert_eq!(parse::<f32>(b"1", b"ffffff", 127), f32::INFINITY);
+    assert_eq!(parse::<f64>(b"1", b"", i32::MAX), f64::INFINITY);
+
+    // Subnormals and underflow.
+    let min = f64::from_bits(1);
+    assert_eq!(parse::<f64>(b"1", b"", -1074), min);
+    assert_eq!(parse::<f64>(b"1", b"", -1075), 0.0);
+    assert_eq!(parse::<f64>(b"1", b"000000000000000001", -1075), min);
+    assert_eq!(parse::<f64>(b"3", b"", -1076), min);
+    assert_eq!(parse::<f64>(b"3", b"", -1075), 2.0 * min);
+    assert_eq!(parse::<f64>(b"1", b"", i32::MIN), 0.0);
+    assert_eq!(
+        parse::<f64>(b"0", b"fffffffffffff8", -1022),
+        f64::MIN_POSITIVE
+    );
+    assert_eq!(parse::<f32>(b"1", b"", -149), f32::from_bits(1));
+    assert_eq!(parse::<f32>(b"1", b"8", -150), f32::from_bits(1));
+}
+
+#[test]
+fn test_from_preparsed_radix() {
+    use crate::PreParsedHex;
+    use std::vec::Vec;
+
+    fn parse<T: FloatExt>(
+        int_digits: &[u8],
+        frac_digits: &[u8],
+        exp: i32,
+        radix: u32,
+    ) -> Result<T, ParseFloatError> {
+        T::from_preparsed_radix(
+            PreParsed {
+                sign: false,
+                int_digits,
+                frac_digits,
+                exp,
+            },
+            radix,
+        )
+    }
+
+    fn to_radix(mut n: u64, radix: u32) -> Vec<u8> {
+        let mut digits = Vec::new();
+        while n != 0 {
+            let d = (n % u64::from(radix)) as u32;
+            digits.push(core::char::from_digit(d, radix).unwrap() as u8);
+            n /= u64::from(radix);
+        }
+        digits.reverse();
+        digits
+    }
+
+    // Integers and quotients of integers of up to 53 bits, which are
+    // correctly rounded by `as` and `/`.
+    let mut state = 0x1234_5678_9abc_def0u64;
+    for _ in 0..2000 {
+        // xorshift64
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+
+        let radix = (state % 35) as u32 + 2;
+        let n = ((state >> 11) >> (state >> 59)).max(1);
+        let digits = to_radix(n, radix);
+        let mut pow = 1u64;
+        let mut k = 0;
+        while pow * u64::from(radix) < 1 << 53 {
+            pow *= u64::from(radix);
+            k += 1;
+            let expected = (u128::from(n) * u128::from(pow)) as f64;
+            assert_eq!(parse::<f64>(&digits, b"", k, radix), Ok(expected));
+            let expected = n as f64 / pow as f64;
+            assert_eq!(parse::<f64>(&digits, b"", -k, radix), Ok(expected));
+            let (int_digits, frac_digits) = digits.split_at(digits.len() / 2);
+            let exp = frac_digits.len() as i32 - k;
+            assert_eq!(
+                parse::<f64>(int_digits, frac_digits, exp, radix),
+                Ok(expected)
+            );
+        }
+    }
+
+    for radix in 2..=36 {
+        assert_eq!(parse::<f64>(b"0", b"00", 5, radix), Ok(0.0));
+        assert_eq!(parse::<f32>(b"1", b"", 0, radix), Ok(1.0));
+        assert_eq!(parse::<f64>(b"10", b"", -1, radix), Ok(1.0));
+        assert_eq!(parse::<f64>(b"", b"1", 1, radix), Ok(1.0));
+        assert_eq!(parse::<f64>(b"1", b"", 100_000, radix), Ok(f64::INFINITY));
+        assert_eq!(parse::<f64>(b"1", b"", -100_000, radix), Ok(0.0));
+    }
+    assert_eq!(
+        parse::<f64>(b"", b"", 0, 7),
+        Err(ParseFloatError::EmptyDigits)
+    );
+    assert_eq!(
+        parse::<f64>(b"17", b"8", 0, 8),
+        Err(ParseFloatError::InvalidDigit {
+            byte: b'8',
+            index: 2
+        })
+    );
+    assert_eq!(
+        parse::<f64>(b"1z", b"", 0, 35),
+        Err(ParseFloatError::InvalidDigit {
+            byte: b'z',
+            index: 1
+        })
+    );
+    assert_eq!(parse::<f64>(b"Zz", b"", 0, 36), Ok(1295.0));
+    assert_eq!(parse::<f32>(b"", b"1", 0, 3), Ok(1.0 / 3.0));
+    assert_eq!(
+        parse::<f64>(b"1", b"fffffffffffff8", 0, 16),
+        f64::from_preparsed_hex(PreParsedHex {
+            sign: false,
+            int_digits: b"1",
+            frac_digits: b"fffffffffffff8",
+            exp: 0,
+        })
+    );
+    assert_eq!(parse::<f64>(b"1", b"", 3, 32), Ok(32768.0));
+    assert_eq!(
+        parse::<f64>(b"1", b"7", 0, 10),
+        f64::from_preparsed(PreParsed {
+            sign: false,
+            int_digits: b"1",
+            frac_digits: b"7",
+            exp: 0,
+        })
+    );
+
+    // Smallest subnormal, 3^-678 is above half of it and 3^-679 below.
+    assert_eq!(parse::<f64>(b"1", b"", -537, 4), Ok(5e-324));
+    assert_eq!(parse::<f64>(b"1", b"", -678, 3), Ok(5e-324));
+    assert_eq!(parse::<f64>(b"1", b"", -679, 3), Ok(0.0));
+    assert_eq!(parse::<f64>(b"1", b"", 647, 3), Ok(f64::INFINITY));
+    assert!(parse::<f64>(b"1", b"", 646, 3).unwrap().is_finite());
+
+    // 2^53 + 1, which is halfway between two floats, rounds to even, unless
+    // a later digit is not zero.
+    for &radix in &[3, 6, 7, 36] {
+        let mut digits = to_radix((1 << 53) + 1, radix);
+        let int_len = digits.len();
+        digits.resize(int_len + 2000, b'0');
+        let (int_digits, frac_digits) = digits.split_at(int_len);
+        assert_eq!(
+            parse::<f64>(int_digits, frac_digits, 0, radix),
+            Ok(9007199254740992.0)
+        );
+        *digits.last_mut().unwrap() = b'1';
+        let (int_digits, frac_digits) = digits.split_at(int_len);
+        assert_eq!(
+            parse::<f64>(int_digits, frac_digits, 0, radix),
+            Ok(9007199254740994.0)
+        );
+    }
+    let mut digits = std::vec![b'1'; 20_000];
+    assert_eq!(parse::<f64>(&digits, b"", -20_000, 6), Ok(0.2));
+    digits[0] = b'2';
+    assert_eq!(
+        parse::<f64>(&digits, b"", -20_000, 3),
+        Err(ParseFloatError::ExponentOutOfRange)
+    );
+}
+
+#[test]
+fn test_cmp_exact() {
+    use core::cmp::Ordering;
+
+    fn preparsed<'a>(
+        sign: bool,
+        int_digits: &'a [u8],
+        frac_digits: &'a [u8],
+        exp: i32,
+    ) -> PreParsed<'a> {
+        PreParsed {
+            sign,
+            int_digits,
+            frac_digits,
+            exp,
+        }
+    }
+
+    let zero = preparsed(false, b"0", b"", 0);
+    let neg_zero = preparsed(true, b"", b"000", 5);
+    let tenth = preparsed(false, b"0", b"1", 0);
+    let neg_tenth = preparsed(true, b"1", b"", -1);
+    let cases = [
+        (zero, neg_zero, Ordering::Equal),
+        (zero, tenth, Ordering::Less),
+        (neg_zero, tenth, Ordering::Less),
+        (zero, neg_tenth, Ordering::Greater),
+        (neg_tenth, tenth, Ordering::Less),
+        (tenth, preparsed(false, b"00", b"0100", 1), Ordering::Equal),
+        (tenth, preparsed(false, b"00", b"0100", 2), Ordering::Less),
+        (tenth, preparsed(false, b"0010", b"00", -2), Ordering::Equal),
+        (tenth, preparsed(false, b"", b"09999", 0), Ordering::Greater),
+        (neg_tenth, preparsed(true, b"", b"09999", 0), Ordering::Less),
+        (
+            tenth,
+            preparsed(false, b"", b"10000000000000001", 0),
+            Ordering::Less,
+        ),
+        (
+            preparsed(false, b"1", b"", i32::MAX),
+            preparsed(false, b"", b"1", i32::MIN),
+            Ordering::Greater,
+        ),
+        (
+            preparsed(false, b"123", b"45", 0),
+            preparsed(false, b"1", b"2345", 2),
+            Ordering::Equal,
+        ),
+        (
+            preparsed(false, b"123", b"45", 0),
+            preparsed(false, b"1", b"2346", 2),
+            Ordering::Less,
+        ),
+    ];
+    for &(a, b, expected) in cases.iter() {
+        assert_eq!(a.cmp_exact(&b), expected, "{:?} {:?}", a, b);
+        assert_eq!(b.cmp_exact(&a), expected.reverse(), "{:?} {:?}", b, a);
+        assert_eq!(a.cmp_exact(&a), Ordering::Equal);
+    }
+
+    // Agrees with the comparison of floats when they are exact.
+    let mut state = 0x1234_5678_9abc_def0u64;
+    let mut buf_a = [0; crate::PREFORMAT_SHORTEST_BUF_LEN];
+    let mut buf_b = [0; crate::PREFORMAT_SHORTEST_BUF_LEN];
+    for _ in 0..2000 {
+        let mut next = || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            f64::from_bits(state & !(0x7F0 << 52))
+        };
+        let (a, b) = (next(), next());
+        let b = if state % 4 == 0 { a } else { b };
+        let to_preparsed = |v: f64, buf| match v.preformat_shortest(buf) {
+            PreFormatted::Finite(sign, digits, _, exp) => preparsed(sign, b"", digits, exp.into()),
+            PreFormatted::Zero(sign) => preparsed(sign, b"0", b"", 0),
+            _ => unreachable!(),
+        };
+        let (pa, pb) = (to_preparsed(a, &mut buf_a), to_preparsed(b, &mut buf_b));
+        assert_eq!(
+            pa.cmp_exact(&pb),
+            a.partial_cmp(&b).unwrap(),
+            "{:?} {:?}",
+            a,
+            b
+        );
+    }
+}
+
+#[test]
+fn test_from_preparsed_clamped() {
+    use crate::Clamped;
+
+    fn preparsed<'a>(
+        sign: bool,
+        int_digits: &'a [u8],
+        frac_digits: &'a [u8],
+        exp: i32,
+    ) -> PreParsed<'a> {
+        PreParsed {
+            sign,
+            int_digits,
+            frac_digits,
+            exp,
+        }
+    }
+    fn clamped<T>(value: T, clamped: bool, out_of_range: bool) -> Clamped<T> {
+        Clamped {
+            value,
+            clamped,
+            out_of_range,
+        }
+    }
+
+    let half = preparsed(false, b"0", b"5", 0);
+    assert_eq!(
+        f64::from_preparsed_clamped(half, 0.0, 1.0),
+        Ok(clamped(0.5, false, false))
+    );
+    assert_eq!(
+        f64::from_preparsed_clamped(half, 0.5, 0.5),
+        Ok(clamped(0.5, false, false))
+    );
+    assert_eq!(
+        f64::from_preparsed_clamped(half, 1.0, 2.0),
+        Ok(clamped(1.0, true, true))
+    );
+    assert_eq!(
+        f32::from_preparsed_clamped(half, -1.0, 0.25),
+        Ok(clamped(0.25, true, true))
+    );
+
+    // 0.1 is below the nearest `f64`, to which it rounds.
+    let tenth = preparsed(false, b"0", b"1", 0);
+    assert_eq!(
+        f64::from_preparsed_clamped(tenth, 0.1, 1.0),
+        Ok(clamped(0.1, false, true))
+    );
+    assert_eq!(
+        f64::from_preparsed_clamped(tenth, 0.0, 0.1),
+        Ok(clamped(0.1, false, false))
+    );
+    assert_eq!(
+        f32::from_preparsed_clamped(tenth, 0.1, 1.0),
+        Ok(clamped(0.1, false, true))
+    );
+
+    // Zeros
+    let neg_zero = preparsed(true, b"0", b"", 0);
+    let v = f64::from_preparsed_clamped(neg_zero, 0.0, 1.0).unwrap();
+    assert!(!v.clamped && !v.out_of_range);
+    let tiny = preparsed(true, b"1", b"", -400);
+    assert_eq!(
+        f64::from_preparsed_clamped(tiny, 0.0, 1.0),
+        Ok(clamped(-0.0, false, true))
+    );
+
+    // Infinite bounds
+    let huge = preparsed(false, b"1", b"", 400);
+    assert_eq!(
+        f64::from_preparsed_clamped(huge, f64::NEG_INFINITY, f64::INFINITY),
+        Ok(clamped(f64::INFINITY, false, false))
+    );
+    assert_eq!(
+        f64::from_preparsed_clamped(huge, 0.0, f64::MAX),
+        Ok(clamped(f64::MAX, true, true))
+    );
+
+    assert_eq!(
+        f64::from_preparsed_clamped(preparsed(false, b"x", b"", 0), 0.0, 1.0),
+        Err(ParseFloatError::InvalidDigit {
+            byte: b'x',
+            index: 0
+        })
+    );
+}
+
+#[test]
+fn test_flush_to_zero() {
+    use crate::flush::FlushToZero;
+
+    let flush = |frac_digits, exp, signed_zero| FlushToZero {
+        threshold: PreParsed {
+            sign: true,
+            int_digits: b"",
+            frac_digits,
+            exp,
+        },
+        signed_zero,
+    };
+
+    // 0.1 is below the nearest `f64`
+    let tenth = flush(b"1", 0, true);
+    assert!(tenth.flushes(0.0f64));
+    assert!(tenth.flushes(-0.0f32));
+    assert!(tenth.flushes(-0.09f64));
+    assert!(!tenth.flushes(0.1f64));
+    assert!(!tenth.flushes(-0.1f32));
+    assert!(!tenth.flushes(f64::NAN));
+    assert!(!tenth.flushes(f64::NEG_INFINITY));
+    let tenth_plus = flush(b"1000000000000000055511151231257828", 0, true);
+    assert!(tenth_plus.flushes(0.1f64));
+    assert!(!tenth_plus.flushes(0.1f32));
+
+    let zero = flush(b"", 0, false);
+    assert!(!zero.flushes(0.0f64));
+    assert!(!zero.flushes(5e-324f64));
+
+    let mut buf = [0; crate::PREFORMAT_SHORTEST_BUF_LEN];
+    let v = -1e-17f64;
+    let preformatted = v.preformat_shortest(&mut buf);
+    assert_eq!(
+        flush(b"1", -9, true).apply(v, preformatted),
+        PreFormatted::Zero(true)
+    );
+    assert_eq!(
+        flush(b"1", -9, false).apply(v, preformatted),
+        PreFormatted::Zero(false)
+    );
+    assert_eq!(flush(b"1", -17, false).apply(v, preformatted), preformatted);
+    assert_eq!(
+        flush(b"1", -17, false).apply(-0.0, PreFormatted::Zero(true)),
+        PreFormatted::Zero(false)
+    );
+    assert_eq!(
+        flush(b"1", -17, true).apply(-0.0, PreFormatted::Zero(true)),
+        PreFormatted::Zero(true)
+    );
+    assert_eq!(
+        flush(b"1", 0, false).apply(f64::NAN, PreFormatted::NaN),
+        PreFormatted::NaN
+    );
+}
+
+#[test]
+fn test_preformatted_fixed() {
+    use crate::PreFormattedFixed;
+
+    let fixed = |digits, num_zeros, exp| {
+        PreFormattedFixed::from(PreFormatted::Finite(true, digits, num_zeros, exp))
+    };
+    // (digits, zeros, exp, point_index, int_len, frac_leading_zeros, int_zeros, frac_trailing_zeros, frac_len)
+    let cases: &[(&[u8], usize, i16, [usize; 6])] = &[
+        // 0.00123000
+        (b"123", 3, -2, [0, 0, 2, 0, 3, 8]),
+        // 12.3000
+        (b"123", 3, 2, [2, 2, 0, 0, 3, 4]),
+        // 123.000
+        (b"123", 3, 3, [3, 3, 0, 0, 3, 3]),
+        // 12300.0
+        (b"123", 3, 5, [3, 5, 0, 2, 1, 1]),
+        // 123000
+        (b"123", 3, 6, [3, 6, 0, 3, 0, 0]),
+        // 1230000
+        (b"123", 3, 7, [3, 7, 0, 4, 0, 0]),
+        // 0.5
+        (b"5", 0, 0, [0, 0, 0, 0, 0, 1]),
+    ];
+    for &(digits, num_zeros, exp, expected) in cases {
+        let [point_index, int_len, frac_leading_zeros, int_zeros, trailing, frac_len] = expected;
+        let fixed = fixed(digits, num_zeros, exp);
+        assert_eq!(
+            fixed,
+            PreFormattedFixed::Finite {
+                sign: true,
+                digits,
+                num_zeros,
+                point_index,
+                int_len,
+                frac_leading_zeros,
+            }
+        );
+        assert_eq!(fixed.int_zeros(), int_zeros);
+        assert_eq!(fixed.frac_trailing_zeros(), trailing);
+        assert_eq!(fixed.frac_len(), frac_len);
+    }
+
+    assert_eq!(
+        PreFormattedFixed::from(PreFormatted::NaN),
+        PreFormattedFixed::NaN
+    );
+    assert_eq!(
+        PreFormattedFixed::from(PreFormatted::Inf(true)),
+        PreFormattedFixed::Inf(true)
+    );
+    let zero = PreFormattedFixed::from(PreFormatted::Zero(true));
+    assert_eq!(zero, PreFormattedFixed::Zero(true));
+    assert_eq!(zero.frac_len(), 0);
+}
+
+#[cfg(feature = "test-vectors")]
+#[test]
+fn test_test_vectors() {
+    use crate::grammar::Grammar;
+    use crate::test_vectors::{self, ParseVector, ShortestVector};
+    use crate::PREFORMAT_SHORTEST_BUF_LEN;
+
+    fn check_parse<F: FloatExt + std::str::FromStr>(v: &ParseVector)
+    where
+        F::Err: std::fmt::Debug,
+    {
+        // The parser of `std` before Rust 1.55 rejects the inputs that need
+        // more than about 375 digits of intermediate precision.
+        if let Ok(parsed) = v.text.parse::<F>() {
+            assert_eq!(parsed.to_bits_u64(), v.bits, "{}", v.text);
+        }
+        let parsed: F = Grammar::RUST
+            .parse(v.text.as_bytes())
+            .unwrap()
+            .to_float()
+            .unwrap();
+        assert_eq!(parsed.to_bits_u64(), v.bits, "{}", v.text);
+    }
+
+    fn check_shortest<F: FloatExt>(v: &ShortestVector) {
+        let mut buf = [0; PREFORMAT_SHORTEST_BUF_LEN];
+        let value = F::from_bits_u64(v.bits);
+        assert_eq!(
+            value.preformat_shortest(&mut buf),
+            PreFormatted::Finite(false, v.digits.as_bytes(), 0, v.exp),
+            "{:?}",
+            v,
+        );
+    }
+
+    test_vectors::paxson_f64()
+        .chain(test_vectors::classic_f64())
+        .for_each(check_parse::<f64>);
+    test_vectors::paxson_f32()
+        .chain(test_vectors::classic_f32())
+        .for_each(check_parse::<f32>);
+    test_vectors::shortest_f64().for_each(check_shortest::<f64>);
+    test_vectors::shortest_f32().for_each(check_shortest::<f32>);
+}
+
+#[test]
+fn test_funcs() {
+    use crate::funcs;
+    use crate::{PREFORMAT_EXACT_FIXED_BASE_BUF_LEN, PREFORMAT_SHORTEST_BUF_LEN};
+
+    let mut buf1 = [0; PREFORMAT_EXACT_FIXED_BASE_BUF_LEN];
+    let mut buf2 = [0; PREFORMAT_EXACT_FIXED_BASE_BUF_LEN];
+    for &v in [0.1f64, -2.675, 1e300, 5e-324, f64::NAN, f64::INFINITY].iter() {
+        assert_eq!(
+            funcs::preformat_shortest_f64(v, &mut buf1),
+            v.preformat_shortest(&mut buf2),
+        );
+        assert_eq!(
+            funcs::preformat_exact_exp(v, &mut buf1, 20),
+            v.preformat_exact_exp(&mut buf2, 20),
+        );
+        assert_eq!(
+            funcs::preformat_exact_fixed_f64(v, &mut buf1, 3),
+            v.preformat_exact_fixed(&mut buf2, 3),
+        );
+        assert_eq!(
+            funcs::preformat_exact_limit_f64(v, &mut buf1, -3),
+            v.preformat_exact_limit(&mut buf2, -3),
+        );
+    }
+    let v = 0.3f32;
+    assert_eq!(
+        funcs::preformat_shortest_f32(v, &mut buf1),
+        v.preformat_shortest(&mut buf2),
+    );
+    assert_eq!(
+        funcs::try_preformat_shortest_f32(v, &mut buf1[..PREFORMAT_SHORTEST_BUF_LEN - 1]),
+        v.try_preformat_shortest(&mut buf2[..PREFORMAT_SHORTEST_BUF_LEN - 1]),
+    );
+    assert!(funcs::try_preformat_exact_exp_f64(1.0, &mut buf1, 0).is_err());
+
+    let preparsed = PreParsed {
+        sign: true,
+        int_digits: b"2",
+        frac_digits: b"675",
+        exp: 0,
+    };
+    assert_eq!(funcs::from_preparsed_f64(preparsed), Ok(-2.675));
+    assert_eq!(funcs::from_preparsed::<f32>(preparsed), Ok(-2.675));
+    assert_eq!(funcs::from_preparsed_f32(preparsed), Ok(-2.675));
+}
+
+#[test]
+fn test_write_fixed_width() {
+    use crate::write::{write_fixed_width, FixedWidth, Overflow, WriteOptions};
+
+    fn check(v: f64, format: &FixedWidth<'_>, options: &WriteOptions<'_>, expected: &str) {
+        let mut buf = [0; 64];
+        let s = write_fixed_width(&mut buf, v, format, options).unwrap();
+        assert_eq!(s, expected, "{}", v);
+        assert_eq!(s.chars().count(), format.width);
+    }
+
+    let mut format = FixedWidth {
+        width: 12,
+        frac_digits: 1,
+        group_separator: Some(","),
+        overflow: Overflow::Fill('#'),
+    };
+    let options = WriteOptions::DEFAULT;
+    check(1e7, &format, &options, "10,000,000.0");
+    check(123.45, &format, &options, "       123.5");
+    check(0.25, &format, &options, "         0.2");
+    check(-0.0, &format, &options, "        -0.0");
+    check(0.04, &format, &options, "         0.0");
+    check(f64::NAN, &format, &options, "         NaN");
+    check(f64::NEG_INFINITY, &format, &options, "        -inf");
+    check(-1e7, &format, &options, "############");
+    // Rounding adds a digit, and then a group.
+    check(99_999_999.96, &format, &options, "############");
+    check(999_999.96, &format, &options, " 1,000,000.0");
+
+    format.group_separator = None;
+    check(-1e7, &format, &options, " -10000000.0");
+
+    // Widths are counted in `char`s.
+    let options = WriteOptions {
+        minus_sign: "\u{2212}",
+        plus_sign: "+",
+        ..WriteOptions::DEFAULT
+    };
+    format.group_separator = Some("\u{202f}");
+    format.overflow = Overflow::Fill('\u{2588}');
+    check(-1e7, &format, &options, "\u{2588}".repeat(12).as_str());
+    check(-1e6, &format, &options, "\u{2212}1\u{202f}000\u{202f}000.0");
+    check(1e6, &format, &options, "+1\u{202f}000\u{202f}000.0");
+
+    format.width = 7;
+    format.frac_digits = 0;
+    format.group_separator = None;
+    format.overflow = Overflow::Scientific('#');
+    let options = WriteOptions::DEFAULT;
+    check(1e6, &format, &options, "1000000");
+    check(12_345_678.0, &format, &options, "1.235e7");
+    check(-12_345_678.0, &format, &options, "-1.23e7");
+    check(9_996_000.0, &format, &options, "9996000");
+    check(99_960_000.0, &format, &options, "9.996e7");
+    check(99_996_000.0, &format, &options, "1.000e8");
+    check(-1e300, &format, &options, " -1e300");
+    check(1e-300, &format, &options, "      0");
+    format.width = 5;
+    check(-1e300, &format, &options, "#####");
+    format.width = 0;
+    check(1.0, &format, &options, "");
+}
+
+#[test]
+fn test_compact() {
+    use crate::compact::{write_compact, CompactFormat, Suffix};
+    use crate::write::WriteOptions;
+    use crate::RoundingMode;
+
+    fn check<T: FloatExt>(v: T, format: &CompactFormat<'_>, expected: &str) {
+        let mut buf = [0; 64];
+        let s = write_compact(&mut buf, v, format, &WriteOptions::DEFAULT);
+        assert_eq!(s, Ok(expected));
+    }
+
+    let format = CompactFormat::SHORT_SCALE;
+    check(0.0, &format, "0");
+    check(-0.0, &format, "-0");
+    check(0.04, &format, "0");
+    check(0.05, &format, "0.1");
+    check(-12.34, &format, "-12.3");
+    check(999.96, &format, "1K");
+    check(1000.0, &format, "1K");
+    check(1050.0, &format, "1K");
+    check(1150.0, &format, "1.2K");
+    check(999_950_000.0, &format, "1B");
+    check(1.5e15, &format, "1500T");
+    check(123_456.0f32, &format, "123.5K");
+    check(f64::NAN, &format, "NaN");
+    check(f64::NEG_INFINITY, &format, "-inf");
+
+    let format = CompactFormat {
+        min_exp: 4,
+        min_frac_digits: 2,
+        max_frac_digits: 2,
+        rounding: RoundingMode::Up,
+        ..CompactFormat::SHORT_SCALE
+    };
+    check(0.0, &format, "0.00");
+    check(9999.0, &format, "9999.00");
+    check(9999.991, &format, "10.00K");
+    check(1001.0, &format, "1001.00");
+    check(10_001.0, &format, "10.01K");
+    check(-10_001.0, &format, "-10.00K");
+
+    let suffixes = [
+        Suffix {
+            exp: 8,
+            text: " crore",
+        },
+        Suffix {
+            exp: 5,
+            text: " lakh",
+        },
+    ];
+    let format = CompactFormat {
+        suffixes: &suffixes,
+        min_exp: 5,
+        ..CompactFormat::SHORT_SCALE
+    };
+    check(12_345.0, &format, "12345");
+    check(250_000.0, &format, "2.5 lakh");
+    check(99_990_000.0, &format, "999.9 lakh");
+    check(99_995_000.0, &format, "1 crore");
+    check(3.2e9, &format, "32 crore");
+}
+
+#[test]
+fn test_grammar_percent() {
+    use crate::grammar::{Grammar, Parsed, PERCENT_SIGNS};
+    use crate::SyntaxError;
+
+    let grammar = Grammar {
+        percent_signs: &PERCENT_SIGNS,
+        hex: true,
+        ..Grammar::RUST
+    };
+    let parse = |s: &str| grammar.parse(s.as_bytes()).unwrap().to_float::<f64>();
+    assert_eq!(parse("12.5%"), Ok(0.125));
+    assert_eq!(parse("7.3%"), Ok(0.073));
+    assert_eq!(parse("0.7%"), Ok(0.007));
+    assert_eq!(parse("25e2%"), Ok(25.0));
+    assert_eq!(parse("-5\u{2031}"), Ok(-0.0005));
+    assert_eq!(parse("1e-32768%"), Ok(0.0));
+    assert_eq!(parse("inf"), Ok(f64::INFINITY));
+    assert_eq!(
+        grammar.parse(b"12.5%").unwrap().to_float::<f32>(),
+        Ok(0.125)
+    );
+    assert_eq!(
+        grammar.parse(b"1.5%"),
+        Ok(Parsed::Finite(PreParsed {
+            sign: false,
+            int_digits: b"1",
+            frac_digits: b"5",
+            exp: -2,
+        })),
+    );
+
+    // Only after decimal numbers.
+    assert_eq!(grammar.parse(b"%"), Err(SyntaxError { index: 0 }));
+    assert_eq!(grammar.parse(b"inf%"), Err(SyntaxError { index: 3 }));
+    assert_eq!(grammar.parse(b"0x10%"), Err(SyntaxError { index: 4 }));
+    assert_eq!(grammar.parse_prefix(b"50%%").unwrap().1, 3);
+    assert_eq!(Grammar::RUST.parse(b"50%"), Err(SyntaxError { index: 2 }));
+
+    // Spellings are tried in order.
+    let spaced = Grammar {
+        percent_signs: &[(" %", 2), (" pct", 2)],
+        ..Grammar::RUST
+    };
+    let parsed = spaced.parse(b"2.5 pct").unwrap();
+    assert_eq!(parsed.to_float::<f64>(), Ok(0.025));
+    assert_eq!(spaced.parse_prefix(b"2.5 x").unwrap().1, 3);
+}
+
+#[test]
+fn test_minor_units() {
+    use crate::{DecimalOverflow, RoundingMode};
+
+    let even = RoundingMode::NearestEven;
+    assert_eq!(0.0f64.to_minor_units(2, even), Ok(0));
+    assert_eq!((-0.0f64).to_minor_units(u32::MAX, even), Ok(0));
+    assert_eq!(0.125f64.to_minor_units(2, even), Ok(12));
+    assert_eq!(
+        0.125f64.to_minor_units(2, RoundingMode::NearestAway),
+        Ok(13)
+    );
+    assert_eq!((-0.125f64).to_minor_units(2, RoundingMode::Down), Ok(-13));
+    assert_eq!(19.99f32.to_minor_units(2, even), Ok(1999));
+    assert_eq!(1e-300f64.to_minor_units(2, RoundingMode::Up), Ok(1));
+    assert_eq!(
+        1e-300f64.to_minor_units(u32::MAX, even),
+        Err(DecimalOverflow)
+    );
+    assert_eq!(f64::INFINITY.to_minor_units(2, even), Err(DecimalOverflow));
+
+    // Limits of `i64`.
+    assert_eq!(
+        (-9.223372036854776e18f64).to_minor_units(0, even),
+        Ok(i64::MIN)
+    );
+    assert_eq!(
+        9.223372036854776e18f64.to_minor_units(0, even),
+        Err(DecimalOverflow)
+    );
+    assert_eq!(
+        9.223372036854774e16f64.to_minor_units(2, even),
+        Ok(9_223_372_036_854_774_400),
+    );
+    assert_eq!(
+        9.223372036854778e16f64.to_minor_units(2, even),
+        Err(DecimalOverflow)
+    );
+
+    assert_eq!(f64::from_minor_units(0, 2).to_bits(), 0);
+    assert_eq!(f64::from_minor_units(i64::MAX, 18), 9.223372036854776);
+    assert_eq!(f64::from_minor_units(1, u32::MAX), 0.0);
+    assert_eq!(f32::from_minor_units(-1999, 2), -19.99);
+
+    // Numbers of cents with up to 15 digits round-trip.
+    let mut state = 0x1234_5678_9abc_def0u64;
+    for _ in 0..10000 {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        let cents = (state % 1_000_000_000_000_000) as i64 * if state >> 63 == 0 { 1 } else { -1 };
+        let v = f64::from_minor_units(cents, 2);
+        assert_eq!(v.to_minor_units(2, even), Ok(cents), "{}", cents);
+    }
+}
+
+#[test]
+fn test_to_exact_decimal_parts() {
+    use crate::scaled::exact_expansion;
+
+    // Compares with the digits of the exact expansion.
+    fn check<T: FloatExt + std::fmt::Debug>(v: T) {
+        let mut buf = [0; crate::PREFORMAT_EXACT_FIXED_BASE_BUF_LEN];
+        let expected = match exact_expansion(v, &mut buf) {
+            PreFormatted::Zero(_) => Some((0, 0)),
+            PreFormatted::Finite(sign, digits, _, exp) => std::str::from_utf8(digits)
+                .unwrap()
+                .parse::<i64>()
+                .ok()
+                .map(|m| if sign { -m } else { m })
+                .or(if sign && digits == b"9223372036854775808" {
+                    Some(i64::MIN)
+                } else {
+                    None
+                })
+                .map(|m| (m, (i32::from(exp) - digits.len() as i32) as i8)),
+            _ => None,
+        };
+        assert_eq!(v.to_exact_decimal_parts(), expected, "{:?}", v);
+    }
+
+    assert_eq!((-0.0f64).to_exact_decimal_parts(), Some((0, 0)));
+    assert_eq!(f64::NAN.to_exact_decimal_parts(), None);
+    assert_eq!(f32::NEG_INFINITY.to_exact_decimal_parts(), None);
+    assert_eq!(
+        (-9.223372036854776e18f64).to_exact_decimal_parts(),
+        Some((i64::MIN, 0))
+    );
+    assert_eq!(9.223372036854776e18f64.to_exact_decimal_parts(), None);
+    assert_eq!(1e22f64.to_exact_decimal_parts(), Some((1, 22)));
+    assert_eq!(1e23f64.to_exact_decimal_parts(), None);
+    // 2^-27 = 5^27 * 10^-27
+    assert_eq!(
+        2f64.powi(-27).to_exact_decimal_parts(),
+        Some((7_450_580_596_923_828_125, -27))
+    );
+    assert_eq!(f64::from_bits(1).to_exact_decimal_parts(), None);
+
+    for &v in [0.5f64, 1e-10, 123.0625, -1e18, 1.0 / 1024.0, 3e15].iter() {
+        check(v);
+    }
+    let mut state = 0x1234_5678_9abc_def0u64;
+    for _ in 0..2000 {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        // Small integers scaled by powers of 2 and 10.
+        let m = (state >> 40) as i64 - (1 << 23);
+        let shift = (state % 40) as i32 - 30;
+        let v = m as f64 * 2f64.powi(shift);
+        check(v);
+        check(v as f32);
+        check(v * 1e5);
+        check(f64::from_bits(state));
+    }
+}
+
+#[test]
+fn test_has_exact_decimal_within() {
+    use crate::scaled::exact_expansion;
+
+    // Compares with the number of digits of the exact expansion.
+    fn check<T: FloatExt + std::fmt::Debug>(v: T) {
+        let mut buf = [0; crate::PREFORMAT_EXACT_FIXED_BASE_BUF_LEN];
+        let len = match exact_expansion(v, &mut buf) {
+            PreFormatted::Zero(_) => 1,
+            PreFormatted::Finite(_, digits, _, _) => digits.len(),
+            _ => unreachable!(),
+        };
+        for &n in [len - 1, len, len + 1].iter() {
+            assert_eq!(v.has_exact_decimal_within(n), n >= len, "{:?} {}", v, n);
+        }
+    }
+
+    assert!(!0.0f64.has_exact_decimal_within(0));
+    assert!((-0.0f32).has_exact_decimal_within(1));
+    assert!(!f32::INFINITY.has_exact_decimal_within(usize::MAX));
+    assert!(f64::from_bits(1).has_exact_decimal_within(usize::MAX));
+    for &v in [
+        1.0,
+        0.1,
+        1e22,
+        1e23,
+        f64::MAX,
+        f64::MIN_POSITIVE,
+        f64::from_bits(1),
+        f64::from_bits(0x000F_FFFF_FFFF_FFFF),
+        2f64.powi(-1074 + 52),
+        5f64.powi(22) * 2f64.powi(900),
+    ]
+    .iter()
+    {
+        check(v);
+        check(-v);
+    }
+    check(f32::MAX);
+    check(f32::from_bits(1));
+
+    let mut state = 0x1234_5678_9abc_def0u64;
+    for _ in 0..2000 {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        check(
+            f64::from_bits(state & !(1 << 63))
+                .max(f64::MIN_POSITIVE)
+                .min(f64::MAX),
+        );
+        check(f32::from_bits(state as u32 & 0x7F7F_FFFF));
+        let m = (state >> 40) as f64;
+        check(m * 2f64.powi((state % 80) as i32 - 40));
+        check(m * 5f64.powi((state % 23) as i32) * 2f64.powi((state % 900) as i32));
+    }
+}
+
+#[test]
+fn test_nice() {
+    use crate::nice::{snap, NiceNumber, STEPS_1_2_25_5, STEPS_1_2_5};
+    use crate::RoundingMode;
+
+    let nice = |v: f64, steps: &[u32], mode| {
+        let n = snap(v, steps, mode).unwrap();
+        (n.step, n.exp, n.to_float::<f64>())
+    };
+    assert_eq!(nice(0.37, &STEPS_1_2_5, RoundingMode::Up), (5, -1, 0.5));
+    assert_eq!(nice(0.37, &STEPS_1_2_5, RoundingMode::Down), (2, -1, 0.2));
+    assert_eq!(
+        nice(0.37, &STEPS_1_2_5, RoundingMode::NearestEven),
+        (5, -1, 0.5)
+    );
+    assert_eq!(
+        nice(0.37, &STEPS_1_2_25_5, RoundingMode::NearestEven),
+        (25, -2, 0.25)
+    );
+    assert_eq!(nice(7.0, &STEPS_1_2_5, RoundingMode::Up), (1, 1, 10.0));
+    assert_eq!(
+        nice(7.0, &STEPS_1_2_5, RoundingMode::TowardZero),
+        (5, 0, 5.0)
+    );
+    assert_eq!(
+        nice(0.9, &STEPS_1_2_5, RoundingMode::NearestEven),
+        (1, 0, 1.0)
+    );
+    assert_eq!(nice(1.1, &STEPS_1_2_5, RoundingMode::Down), (1, 0, 1.0));
+    assert_eq!(nice(-1.1, &STEPS_1_2_5, RoundingMode::Down), (2, 0, -2.0));
+    assert_eq!(nice(-1.1, &STEPS_1_2_5, RoundingMode::Up), (1, 0, -1.0));
+    assert_eq!(
+        nice(2500.0, &STEPS_1_2_25_5, RoundingMode::Up),
+        (25, 2, 2500.0)
+    );
+    assert_eq!(
+        nice(2500.0, &STEPS_1_2_25_5, RoundingMode::Down),
+        (25, 2, 2500.0)
+    );
+    assert_eq!(nice(30.0, &[3], RoundingMode::Up), (3, 1, 30.0));
+
+    // Ties, and values just off them.
+    assert_eq!(
+        nice(1.5, &STEPS_1_2_5, RoundingMode::NearestEven),
+        (2, 0, 2.0)
+    );
+    assert_eq!(
+        nice(2.25, &STEPS_1_2_25_5, RoundingMode::NearestEven),
+        (2, 0, 2.0)
+    );
+    assert_eq!(
+        nice(2.25, &STEPS_1_2_25_5, RoundingMode::NearestAway),
+        (25, -1, 2.5)
+    );
+    assert_eq!(
+        nice(3.75, &STEPS_1_2_25_5, RoundingMode::NearestEven),
+        (5, 0, 5.0)
+    );
+    // 0.15 is slightly below the tie at 0.15.
+    assert_eq!(
+        nice(0.15, &STEPS_1_2_5, RoundingMode::NearestAway),
+        (1, -1, 0.1)
+    );
+    // 0.35 is slightly below the tie at 0.35.
+    assert_eq!(
+        nice(0.35, &STEPS_1_2_5, RoundingMode::NearestAway),
+        (2, -1, 0.2)
+    );
+    assert_eq!(
+        nice(3.5, &STEPS_1_2_5, RoundingMode::NearestEven),
+        (2, 0, 2.0)
+    );
+    assert_eq!(
+        nice(3.5, &STEPS_1_2_5, RoundingMode::NearestAway),
+        (5, 0, 5.0)
+    );
+    assert_eq!(
+        nice(1.5, &STEPS_1_2_5, RoundingMode::NearestOdd),
+        (1, 0, 1.0)
+    );
+    assert_eq!(
+        nice(2.25, &STEPS_1_2_25_5, RoundingMode::NearestOdd),
+        (25, -1, 2.5)
+    );
+    assert_eq!(nice(1.1, &STEPS_1_2_5, RoundingMode::ToOdd), (1, 0, 1.0));
+    assert_eq!(nice(4.0, &STEPS_1_2_5, RoundingMode::ToOdd), (5, 0, 5.0));
+
+    // The exact value of the number is compared, not a rounded one: `0.1`
+    // is slightly above 0.1 and `0.3` slightly below 0.3.
+    assert_eq!(nice(0.1, &[1], RoundingMode::Up), (1, 0, 1.0));
+    assert_eq!(nice(0.1, &[1], RoundingMode::Down), (1, -1, 0.1));
+    assert_eq!(nice(0.1, &STEPS_1_2_5, RoundingMode::Down), (1, -1, 0.1));
+    assert_eq!(nice(0.3, &[3], RoundingMode::Up), (3, -1, 0.3));
+    assert_eq!(nice(0.3, &[3], RoundingMode::Down), (3, -2, 0.03));
+
+    assert_eq!(
+        nice(f64::MAX, &STEPS_1_2_5, RoundingMode::Up).2,
+        f64::INFINITY
+    );
+    assert_eq!(
+        nice(f64::MAX, &STEPS_1_2_5, RoundingMode::Down),
+        (1, 308, 1e308)
+    );
+    assert_eq!(
+        nice(f64::from_bits(1), &STEPS_1_2_5, RoundingMode::Up),
+        (5, -324, 5e-324)
+    );
+    assert_eq!(snap(0.0f64, &STEPS_1_2_5, RoundingMode::Up), None);
+    assert_eq!(snap(f32::NAN, &STEPS_1_2_5, RoundingMode::Up), None);
+    assert_eq!(
+        snap(-0.7f32, &STEPS_1_2_5, RoundingMode::NearestEven),
+        Some(NiceNumber {
+            sign: true,
+            step: 5,
+            exp: -1,
+        }),
+    );
+
+    let mut buf = [0; 10];
+    let n = snap(0.37f64, &STEPS_1_2_25_5, RoundingMode::NearestEven).unwrap();
+    assert_eq!(
+        n.preformat(&mut buf),
+        PreFormatted::Finite(false, b"25", 0, 0)
+    );
+    let n = snap(-12345.0f64, &[20], RoundingMode::Down).unwrap();
+    assert_eq!(
+        n.preformat(&mut buf),
+        PreFormatted::Finite(true, b"2", 0, 5)
+    );
+
+    let mut state = 0x1234_5678_9abc_def0u64;
+    for _ in 0..1000 {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        let v = f64::from_bits(state & !(1 << 63));
+        if !v.is_finite() || v == 0.0 {
+            continue;
+        }
+        let (_, _, down) = nice(v, &STEPS_1_2_25_5, RoundingMode::Down);
+        let (_, _, up) = nice(v, &STEPS_1_2_25_5, RoundingMode::Up);
+        let (_, _, near) = nice(v, &STEPS_1_2_25_5, RoundingMode::NearestEven);
+        assert!(down <= v && v <= up, "{:e}", v);
+        assert!(up / down <= 2.0 || up.is_infinite(), "{:e}", v);
+        assert!(near == down || near == up, "{:e}", v);
+    }
+}
+
+#[test]
+fn test_scan() {
+    use crate::grammar::Grammar;
+    use crate::scan::{scan_into, ScanFormat};
+    use crate::{ScanError, ScanErrorKind};
+
+    let mut out = [0.0f64; 16];
+    assert_eq!(scan_into(b"", &mut out, &ScanFormat::CSV), Ok(0));
+    assert_eq!(scan_into(b" \n\t", &mut out, &ScanFormat::CSV), Ok(0));
+    // Empty fields are not skipped, but a trailing delimiter is allowed.
+    for &(text, index) in &[(&b" ,\n\t"[..], 1), (b"1,,2", 2), (b"1, \n,2", 4)] {
+        assert_eq!(
+            scan_into(text, &mut out, &ScanFormat::CSV),
+            Err(ScanError {
+                index,
+                kind: ScanErrorKind::Empty
+            })
+        );
+    }
+    assert_eq!(scan_into(b"1 ,2,\n3,", &mut out, &ScanFormat::CSV), Ok(3));
+    assert_eq!(out[..3], [1.0, 2.0, 3.0]);
+    assert_eq!(
+        scan_into(b"0.1,0.2,0.30000000000000004", &mut out, &ScanFormat::CSV),
+        Ok(3)
+    );
+    assert_eq!(out[..3], [0.1, 0.2, 0.1 + 0.2]);
+    // Tokens longer than a chunk, and delimiters at every position of one.
+    let text = b"123456789.25\t-1e-300 inf 1,2,3,4,5,6,7,8,9";
+    assert_eq!(scan_into(text, &mut out, &ScanFormat::CSV), Ok(12));
+    assert_eq!(
+        out[..12],
+        [
+            123456789.25,
+            -1e-300,
+            f64::INFINITY,
+            1.0,
+            2.0,
+            3.0,
+            4.0,
+            5.0,
+            6.0,
+            7.0,
+            8.0,
+            9.0
+        ]
+    );
+
+    assert_eq!(
+        scan_into(b"1,2", &mut out, &ScanFormat::WHITESPACE),
+        Err(ScanError {
+            index: 0,
+            kind: ScanErrorKind::Syntax
+        })
+    );
+    assert_eq!(
+        scan_into(b"1 2 3", &mut out[..2], &ScanFormat::WHITESPACE),
+        Err(ScanError {
+            index: 4,
+            kind: ScanErrorKind::Full
+        })
+    );
+    assert_eq!(out[..2], [1.0, 2.0]);
+    let mut digits = [b'1'; 20000];
+    digits[0] = b' ';
+    assert_eq!(scan_into(&digits, &mut out, &ScanFormat::WHITESPACE), Ok(1));
+    assert_eq!(out[0], f64::INFINITY);
+
+    let format = ScanFormat {
+        delimiters: b";\n",
+        whitespace: b"",
+        grammar: Grammar {
+            decimal_point: ",",
+            ..Grammar::RUST
+        },
+    };
+    let mut out = [0.0f32; 4];
+    assert_eq!(scan_into(b"1,5;-2,25\n3;", &mut out, &format), Ok(3));
+    assert_eq!(out[..3], [1.5, -2.25, 3.0]);
+    assert_eq!(
+        scan_into(b"1;\n2", &mut out, &format),
+        Err(ScanError {
+            index: 2,
+            kind: ScanErrorKind::Empty
+        })
+    );
+
+    #[cfg(feature = "alloc")]
+    {
+        use crate::scan::scan_vec;
+
+        let mut text = std::vec::Vec::new();
+        let mut expected = std::vec::Vec::new();
+        let mut state = 0x1234_5678_9abc_def0u64;
+        for _ in 0..1000 {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            let v = f64::from_bits(state);
+            if v.is_nan() {
+                continue;
+            }
+            text.extend_from_slice(std::format!("{:?}", v).as_bytes());
+            text.extend_from_slice(&b", \n\t"[..(state % 4 + 1) as usize]);
+            expected.push(v);
+        }
+        let mut out = std::vec![1.0f64];
+        assert_eq!(
+            scan_vec(&text, &mut out, &ScanFormat::CSV),
+            Ok(expected.len())
+        );
+        assert_eq!(out[0], 1.0);
+        assert_eq!(out[1..], expected[..]);
+    }
+}
+
+/// Multiplies the digits by `m`, with a leading digit for the carry.
+fn times(digits: &[u8], m: u8) -> std::vec::Vec<u8> {
+    let mut carry = 0;
+    let mut product = digits.to_vec();
+    for c in product.iter_mut().rev() {
+        let d = (*c - b'0') * m + carry;
+        *c = b'0' + d % 10;
+        carry = d / 10;
+    }
+    product.insert(0, b'0' + carry);
+    product
+}
+
+/// Checks that `parse` rounds the halfway point `0.digits * 10^exp`
+/// between `below` and `above` to the even one, and the numbers just below
+/// and above it (with even more digits) to `below` and `above`.
+fn check_halfway<T: Copy + PartialEq + core::fmt::Debug>(
+    digits: &[u8],
+    exp: i32,
+    (below, above): (T, T),
+    even: T,
+    parse: impl Fn(&[u8], i32) -> T,
+) {
+    assert_eq!(parse(digits, exp), even);
+    let mut digits = digits.to_vec();
+    let last = digits.len() - 1;
+    assert_ne!(digits[last], b'0');
+    digits.push(b'1');
+    assert_eq!(parse(&digits, exp), above);
+    digits[last] -= 1;
+    digits[last + 1] = b'9';
+    assert_eq!(parse(&digits, exp), below);
+    digits.resize(20_000, b'9');
+    assert_eq!(parse(&digits, exp), below);
+    digits[last] += 1;
+    digits[last + 1..].iter_mut().for_each(|c| *c = b'0');
+    *digits.last_mut().unwrap() = b'1';
+    assert_eq!(parse(&digits, exp), above);
+}
+
+#[test]
+fn test_x87() {
+    use crate::x87::{EXACT_FIXED_BASE_BUF_LEN, F80, SHORTEST_BUF_LEN};
+
+    fn f80(mantissa: u64, sign_exp: u16) -> F80 {
+        F80 { mantissa, sign_exp }
+    }
+
+    fn parse(sign: bool, int_digits: &[u8], frac_digits: &[u8], exp: i32) -> F80 {
+        F80::from_preparsed(PreParsed {
+            sign,
+            int_digits,
+            frac_digits,
+            exp,
+        })
+        .unwrap()
+    }
+
+    // The digits with the extra zeros.
+    fn expand(preformatted: PreFormatted<'_>) -> (bool, std::vec::Vec<u8>, i16) {
+        match preformatted {
+            PreFormatted::Finite(sign, digits, num_zeros, exp) => {
+                let mut digits = digits.to_vec();
+                digits.resize(digits.len() + num_zeros, b'0');
+                (sign, digits, exp)
+            }
+            other => panic!("{:?}", other),
+        }
+    }
+
+    let mut buf = [0; EXACT_FIXED_BASE_BUF_LEN];
+
+    // Shortest
+    let cases: &[(F80, &[u8], i16)] = &[
+        (f80(1 << 63, 0x3FFF), b"1", 1),
+        (
+            f80(0xC90F_DAA2_2168_C235, 0x4000),
+            b"31415926535897932385",
+            1,
+        ),
+        (f80(0xCCCC_CCCC_CCCC_CCCD, 0x3FFB), b"1", 0),
+        (f80(u64::MAX, 0x7FFE), b"1189731495357231765", 4933),
+        (f80(1 << 63, 0x0001), b"33621031431120935063", -4931),
+        // Pseudo-denormal
+        (f80(1 << 63, 0x0000), b"33621031431120935063", -4931),
+        (f80(1, 0x0000), b"4", -4950),
+    ];
+    for &(v, digits, exp) in cases {
+        assert_eq!(
+            v.preformat_shortest(&mut buf[..SHORTEST_BUF_LEN]),
+            PreFormatted::Finite(false, digits, 0, exp),
+        );
+    }
+    assert_eq!(
+        f80(1 << 63, 0xBFFF).preformat_shortest(&mut buf),
+        PreFormatted::Finite(true, b"1", 0, 1),
+    );
+    assert_eq!(
+        f80(0, 0x8000).preformat_shortest(&mut buf),
+        PreFormatted::Zero(true)
+    );
+    assert_eq!(
+        F80::NEG_INFINITY.preformat_shortest(&mut buf),
+        PreFormatted::Inf(true)
+    );
+    assert_eq!(F80::NAN.preformat_shortest(&mut buf), PreFormatted::NaN);
+    // Pseudo-infinity and unnormal
+    assert_eq!(
+        f80(0, 0x7FFF).preformat_shortest(&mut buf),
+        PreFormatted::NaN
+    );
+    assert_eq!(
+        f80(1, 0x3FFF).preformat_shortest(&mut buf),
+        PreFormatted::NaN
+    );
+
+    // Exact
+    let min = f80(1, 0x0000);
+    assert_eq!(
+        min.preformat_exact_exp(&mut buf, 25),
+        PreFormatted::Finite(false, b"3645199531882474602528406", 0, -4950),
+    );
+    match min.preformat_exact_fixed(&mut buf, 16445) {
+        PreFormatted::Finite(false, digits, 0, -4950) => {
+            assert_eq!(digits.len(), 11495);
+            assert!(digits.starts_with(b"36451995318824746025"));
+            assert!(digits.ends_with(b"03125"));
+        }
+        other => panic!("{:?}", other),
+    }
+    assert_eq!(
+        min.preformat_exact_fixed(&mut buf, 4949),
+        PreFormatted::Zero(false)
+    );
+    assert_eq!(
+        f80(0xC000_0000_0000_0000, 0x3FFF).preformat_exact_fixed(&mut buf, 0),
+        PreFormatted::Finite(false, b"2", 0, 1),
+    );
+    assert_eq!(
+        f80(0xA000_0000_0000_0000, 0x4000).preformat_exact_limit(&mut buf, 0),
+        PreFormatted::Finite(false, b"2", 0, 1),
+    );
+    assert_eq!(
+        expand(f80(1 << 63, 0x403E).preformat_exact_exp(&mut buf, 25)),
+        (false, b"9223372036854775808000000".to_vec(), 19),
+    );
+
+    // Parsing
+    assert_eq!(
+        parse(false, b"0", b"1", 0),
+        f80(0xCCCC_CCCC_CCCC_CCCD, 0x3FFB)
+    );
+    assert_eq!(parse(true, b"1", b"", 0), f80(1 << 63, 0xBFFF));
+    assert_eq!(
+        parse(false, b"1189731495357231765", b"", 4914),
+        f80(u64::MAX, 0x7FFE)
+    );
+    assert_eq!(parse(false, b"119", b"", 4930), F80::INFINITY);
+    assert_eq!(parse(true, b"119", b"", 4930), F80::NEG_INFINITY);
+    assert_eq!(parse(false, b"2", b"", -4951), min);
+    assert_eq!(parse(true, b"18", b"", -4952), f80(0, 0x8000));
+    assert_eq!(parse(false, b"0", b"", 0), f80(0, 0x0000));
+    // Ties to even, `1 + 2^-64` and `1 + 3 * 2^-64`
+    let tie = b"0000000000000000000542101086242752217003726400434970855712890625";
+    assert_eq!(parse(false, b"1", tie, 0), f80(1 << 63, 0x3FFF));
+    assert_eq!(
+        parse(false, b"1", &[&tie[..], b"1"].concat(), 0),
+        f80((1 << 63) + 1, 0x3FFF)
+    );
+    let tie = b"0000000000000000001626303258728256651011179201304912567138671875";
+    assert_eq!(parse(false, b"1", tie, 0), f80((1 << 63) + 2, 0x3FFF));
+    // With more digits than the bignums can hold, `2^-16446` and
+    // `3 * 2^-16446`.
+    let digits = match min.preformat_exact_fixed(&mut buf, 16445) {
+        PreFormatted::Finite(false, digits, 0, -4950) => digits.to_vec(),
+        other => panic!("{:?}", other),
+    };
+    let half = times(&digits, 5);
+    let three_halves = times(&half, 3);
+    let parse_frac = |digits: &[u8], exp| parse(false, b"", digits, exp);
+    check_halfway(&half, -4950, (f80(0, 0), min), f80(0, 0), parse_frac);
+    check_halfway(
+        &three_halves,
+        -4949,
+        (min, f80(2, 0)),
+        f80(2, 0),
+        parse_frac,
+    );
+    assert_eq!(
+        F80::from_preparsed(PreParsed {
+            sign: false,
+            int_digits: b"1",
+            frac_digits: b"",
+            exp: 20000,
+        }),
+        Ok(F80::INFINITY),
+    );
+
+    let bytes = [0x35, 0xC2, 0x68, 0x21, 0xA2, 0xDA, 0x0F, 0xC9, 0x00, 0x40];
+    assert_eq!(
+        F80::from_le_bytes(bytes),
+        f80(0xC90F_DAA2_2168_C235, 0x4000)
+    );
+    assert_eq!(F80::from_le_bytes(bytes).to_le_bytes(), bytes);
+
+    let mut state = 0x1234_5678_9abc_def0u64;
+    for _ in 0..300 {
+        // xorshift64
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+
+        // Round trips, with the shortest digits being the shortest
+        let biased_exp = (state % 0x7FFF) as u16;
+        let mantissa = if biased_exp == 0 {
+            state >> 1
+        } else {
+            state | 1 << 63
+        };
+        let v = f80(mantissa, biased_exp | (state >> 63 << 15) as u16);
+        let (sign, digits, exp) = match v.preformat_shortest(&mut buf) {
+            PreFormatted::Finite(sign, digits, 0, exp) => (sign, digits.to_vec(), exp),
+            other => panic!("{:?}", other),
+        };
+        assert_eq!(parse(sign, b"", &digits, exp.into()), v);
+        if digits.len() > 1 {
+            if let PreFormatted::Finite(_, shorter, _, exp) =
+                v.preformat_exact_exp(&mut buf, digits.len() - 1)
+            {
+                assert_ne!(parse(sign, b"", shorter, exp.into()), v);
+            }
+        }
+        match v.preformat_exact_exp(&mut buf, 30) {
+            PreFormatted::Finite(sign, digits, _, exp) => {
+                assert_eq!(parse(sign, b"", digits, exp.into()), v);
+            }
+            other => panic!("{:?}", other),
+        }
+
+        // Exact digits of numbers that are also `f64`
+        let x = f64::from_bits(state);
+        if x.is_normal() {
+            let biased_exp = (state >> 52 & 0x7FF) as u16 + 16383 - 1023;
+            let mantissa = (state & ((1 << 52) - 1) | 1 << 52) << 11;
+            let v = f80(mantissa, biased_exp | (state >> 63 << 15) as u16);
+            let mut buf_f64 = [0; 40];
+            assert_eq!(
+                expand(v.preformat_exact_exp(&mut buf, 40)),
+                expand(x.preformat_exact_exp(&mut buf_f64, 40)),
+            );
+        }
+    }
+}
+
+#[test]
+fn test_binary128() {
+    use crate::binary128::{
+        self, EXACT_FIXED_BASE_BUF_LEN, INFINITY, NAN, NEG_INFINITY, SHORTEST_BUF_LEN,
+    };
+    use crate::x87::F80;
+
+    fn parse(sign: bool, int_digits: &[u8], frac_digits: &[u8], exp: i32) -> u128 {
+        binary128::from_preparsed(PreParsed {
+            sign,
+            int_digits,
+            frac_digits,
+            exp,
+        })
+        .unwrap()
+    }
+
+    // The digits with the extra zeros.
+    fn expand(preformatted: PreFormatted<'_>) -> (bool, std::vec::Vec<u8>, i16) {
+        match preformatted {
+            PreFormatted::Finite(sign, digits, num_zeros, exp) => {
+                let mut digits = digits.to_vec();
+                digits.resize(digits.len() + num_zeros, b'0');
+                (sign, digits, exp)
+            }
+            other => panic!("{:?}", other),
+        }
+    }
+
+    let mut buf = [0; EXACT_FIXED_BASE_BUF_LEN];
+    let one = 0x3FFF << 112;
+    let max = 0x7FFE_FFFF_FFFF_FFFF_FFFF_FFFF_FFFF_FFFF;
+    let min_normal = 1 << 112;
+
+    // Shortest
+    let cases: &[(u128, &[u8], i16)] = &[
+        (one, b"1", 1),
+        (0x3FFB_9999_9999_9999_9999_9999_9999_999A, b"1", 0),
+        (
+            0x3FFD_5555_5555_5555_5555_5555_5555_5555,
+            b"3333333333333333333333333333333333",
+            0,
+        ),
+        (max, b"1189731495357231765085759326628007", 4933),
+        (min_normal, b"33621031431120935062626778173217526", -4931),
+        (1, b"6", -4965),
+    ];
+    for &(bits, digits, exp) in cases {
+        assert_eq!(
+            binary128::preformat_shortest(bits, &mut buf[..SHORTEST_BUF_LEN]),
+            PreFormatted::Finite(false, digits, 0, exp),
+        );
+        assert_eq!(parse(false, b"", digits, exp.into()), bits);
+    }
+    assert_eq!(
+        binary128::preformat_shortest(one | 1 << 127, &mut buf),
+        PreFormatted::Finite(true, b"1", 0, 1),
+    );
+    assert_eq!(
+        binary128::preformat_shortest(1 << 127, &mut buf),
+        PreFormatted::Zero(true)
+    );
+    assert_eq!(
+        binary128::preformat_shortest(NEG_INFINITY, &mut buf),
+        PreFormatted::Inf(true)
+    );
+    assert_eq!(
+        binary128::preformat_shortest(NAN, &mut buf),
+        PreFormatted::NaN
+    );
+
+    // Exact
+    assert_eq!(
+        binary128::preformat_exact_exp(1, &mut buf, 30),
+        PreFormatted::Finite(false, b"647517511943802511092443895823", 0, -4965),
+    );
+    match binary128::preformat_exact_fixed(1, &mut buf, 16494) {
+        PreFormatted::Finite(false, digits, 0, -4965) => {
+            assert_eq!(digits.len(), 11529);
+            assert!(digits.starts_with(b"64751751194380251109"));
+            assert!(digits.ends_with(b"15625"));
+        }
+        other => panic!("{:?}", other),
+    }
+    assert_eq!(
+        binary128::preformat_exact_limit(0x4000_4000 << 96, &mut buf, 0),
+        PreFormatted::Finite(false, b"2", 0, 1),
+    );
+
+    // Parsing
+    assert_eq!(parse(true, b"1", b"", 0), one | 1 << 127);
+    assert_eq!(parse(false, b"119", b"", 4930), INFINITY);
+    assert_eq!(parse(true, b"119", b"", 4930), NEG_INFINITY);
+    assert_eq!(parse(false, b"4", b"", -4966), 1);
+    assert_eq!(parse(false, b"3", b"", -4966), 0);
+    assert_eq!(parse(false, b"0", b"", 0), 0);
+    // Ties to even, `1 + 2^-113` and `1 + 3 * 2^-113`
+    let ties = [
+        (0x3F8E << 112, one, one + 1),
+        (0x3F8F << 112 | 1 << 111, one + 2, one + 2),
+    ];
+    for &(half_ulps, tie, above) in ties.iter() {
+        let (digits, exp) = match binary128::preformat_exact_fixed(half_ulps, &mut buf, 200) {
+            PreFormatted::Finite(false, digits, 0, exp) => (digits.to_vec(), exp),
+            other => panic!("{:?}", other),
+        };
+        let mut frac_digits = std::vec![b'0'; -exp as usize];
+        frac_digits.extend_from_slice(&digits);
+        assert_eq!(parse(false, b"1", &frac_digits, 0), tie);
+        frac_digits.push(b'1');
+        assert_eq!(parse(false, b"1", &frac_digits, 0), above);
+        // More digits than the bignums can hold.
+        let one_pos = frac_digits.len() - 1;
+        frac_digits.resize(20_000, b'0');
+        assert_eq!(parse(false, b"1", &frac_digits, 0), above);
+        frac_digits[one_pos] = b'0';
+        assert_eq!(parse(false, b"1", &frac_digits, 0), tie);
+        *frac_digits.last_mut().unwrap() = b'1';
+        assert_eq!(parse(false, b"1", &frac_digits, 0), above);
+    }
+    // Even when a halfway point with more digits is between the kept
+    // digits and the next number with as many digits, such as `2^-16495`
+    // and `3 * 2^-16495` (which are also too long themselves).
+    let digits = match binary128::preformat_exact_fixed(1, &mut buf, 16494) {
+        PreFormatted::Finite(false, digits, 0, -4965) => digits.to_vec(),
+        other => panic!("{:?}", other),
+    };
+    assert_eq!(parse(false, b"", &digits, -4965), 1);
+    // 2^-16495 * 10 and 3 * 2^-16495
+    let half = times(&digits, 5);
+    let three_halves = times(&half, 3);
+    let parse_frac = |digits: &[u8], exp| parse(false, b"", digits, exp);
+    check_halfway(&half, -4965, (0, 1), 0, parse_frac);
+    check_halfway(&three_halves, -4964, (1, 2), 2, parse_frac);
+
+    let mut state = 0x1234_5678_9abc_def0u64;
+    for _ in 0..300 {
+        // xorshift64
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+
+        // Round trips, with the shortest digits being the shortest
+        let high = state;
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        let bits = u128::from(high) << 64 | u128::from(state);
+        if bits & INFINITY == INFINITY {
+            continue;
+        }
+        let (sign, digits, exp) = match binary128::preformat_shortest(bits, &mut buf) {
+            PreFormatted::Finite(sign, digits, 0, exp) => (sign, digits.to_vec(), exp),
+            other => panic!("{:?}", other),
+        };
+        assert_eq!(parse(sign, b"", &digits, exp.into()), bits);
+        if digits.len() > 1 {
+            if let PreFormatted::Finite(_, shorter, _, exp) =
+                binary128::preformat_exact_exp(bits, &mut buf, digits.len() - 1)
+            {
+                assert_ne!(parse(sign, b"", shorter, exp.into()), bits);
+            }
+        }
+        match binary128::preformat_exact_exp(bits, &mut buf, 40) {
+            PreFormatted::Finite(sign, digits, _, exp) => {
+                assert_eq!(parse(sign, b"", digits, exp.into()), bits);
+            }
+            other => panic!("{:?}", other),
+        }
+
+        // Exact digits of numbers that are also `f64` or `F80`
+        let x = f64::from_bits(state);
+        if x.is_normal() {
+            let biased_exp = u128::from(state >> 52 & 0x7FF) + 16383 - 1023;
+            let frac = u128::from(state & ((1 << 52) - 1)) << 60;
+            let bits = u128::from(state >> 63) << 127 | biased_exp << 112 | frac;
+            let mut buf_f64 = [0; 40];
+            assert_eq!(
+                expand(binary128::preformat_exact_exp(bits, &mut buf, 40)),
+                expand(x.preformat_exact_exp(&mut buf_f64, 40)),
+            );
+        }
+        let biased_exp = (high % 0x7FFE) as u16 + 1;
+        let v = F80 {
+            mantissa: state | 1 << 63,
+            sign_exp: biased_exp,
+        };
+        let bits = u128::from(biased_exp) << 112 | u128::from(state & !(1 << 63)) << 49;
+        let mut buf_f80 = [0; 50];
+        assert_eq!(
+            expand(binary128::preformat_exact_exp(bits, &mut buf, 50)),
+            expand(v.preformat_exact_exp(&mut buf_f80, 50)),
+        );
+    }
+}
+
+#[test]
+fn test_custom_float() {
+    use crate::custom::{self, CustomFloat, Unpacked, EXACT_FIXED_BASE_BUF_LEN};
+
+    // `f32`, unpacked with the significand shifted to the left.
+    #[derive(Copy, Clone, Debug, PartialEq)]
+    struct MyF32(u32);
+
+    impl CustomFloat for MyF32 {
+        const SIG_BITS: u32 = 24;
+        const MIN_LSB_EXP: i32 = -149;
+        const MAX_EXP: i32 = 127;
+
+        fn unpack(self) -> Unpacked {
+            let sign = self.0 >> 31 != 0;
+            let biased_exp = (self.0 >> 23 & 0xFF) as i32;
+            let frac = u128::from(self.0 & 0x7F_FFFF);
+            if biased_exp == 0xFF {
+                if frac == 0 {
+                    Unpacked::Inf(sign)
+                } else {
+                    Unpacked::NaN
+                }
+            } else if biased_exp == 0 {
+                Unpacked::Finite(sign, frac << 8, -157)
+            } else {
+                Unpacked::Finite(sign, (frac | 1 << 23) << 8, biased_exp - 158)
+            }
+        }
+
+        fn pack(v: Unpacked) -> Self {
+            match v {
+                Unpacked::NaN => MyF32(f32::NAN.to_bits()),
+                Unpacked::Inf(sign) => MyF32(u32::from(sign) << 31 | 0x7F80_0000),
+                Unpacked::Finite(sign, q, k) => {
+                    let bits = if q >> 23 == 0 {
+                        q as u32
+                    } else {
+                        ((k + 150) as u32) << 23 | (q as u32 & 0x7F_FFFF)
+                    };
+                    MyF32(u32::from(sign) << 31 | bits)
+                }
+            }
+        }
+    }
+
+    // OCP E4M3, saturating.
+    #[derive(Copy, Clone, Debug, PartialEq)]
+    struct E4M3(u8);
+
+    impl CustomFloat for E4M3 {
+        const SIG_BITS: u32 = 4;
+        const MIN_LSB_EXP: i32 = -9;
+        const MAX_EXP: i32 = 8;
+
+        fn unpack(self) -> Unpacked {
+            let sign = self.0 >> 7 != 0;
+            let biased_exp = i32::from(self.0 >> 3 & 0xF);
+            let frac = u128::from(self.0 & 0x7);
+            if self.0 & 0x7F == 0x7F {
+                Unpacked::NaN
+            } else if biased_exp == 0 {
+                Unpacked::Finite(sign, frac, -9)
+            } else {
+                Unpacked::Finite(sign, frac | 0x8, biased_exp - 10)
+            }
+        }
+
+        fn pack(v: Unpacked) -> Self {
+            match v {
+                Unpacked::NaN => E4M3(0x7F),
+                Unpacked::Inf(sign) => E4M3(u8::from(sign) << 7 | 0x7E),
+                Unpacked::Finite(sign, q, k) => {
+                    let bits = if q >> 3 == 0 {
+                        q as u8
+                    } else {
+                        (((k + 10) as u8) << 3 | (q as u8 & 0x7)).min(0x7E)
+                    };
+                    E4M3(u8::from(sign) << 7 | bits)
+                }
+            }
+        }
+    }
+
+    // The digits can be split differently between `digits` and
+    // `num_zeros`.
+    #[track_caller]
+    fn assert_same_value(a: PreFormatted<'_>, b: PreFormatted<'_>) {
+        match (a, b) {
+            (
+                PreFormatted::Finite(sign_a, digits_a, num_zeros_a, exp_a),
+                PreFormatted::Finite(sign_b, digits_b, num_zeros_b, exp_b),
+            ) => {
+                let mut a = digits_a.to_vec();
+                a.resize(a.len() + num_zeros_a, b'0');
+                let mut b = digits_b.to_vec();
+                b.resize(b.len() + num_zeros_b, b'0');
+                assert_eq!((sign_a, a, exp_a), (sign_b, b, exp_b));
+            }
+            (a, b) => assert_eq!(a, b),
+        }
+    }
+
+    fn parse<T: CustomFloat>(sign: bool, digits: &[u8], exp: i32) -> T {
+        custom::from_preparsed(PreParsed {
+            sign,
+            int_digits: b"",
+            frac_digits: digits,
+            exp,
+        })
+        .unwrap()
+    }
+
+    let mut buf = [0; EXACT_FIXED_BASE_BUF_LEN];
+    let mut buf_f32 = [0; crate::PREFORMAT_EXACT_FIXED_BASE_BUF_LEN];
+
+    let mut state = 0x1234_5678_9abc_def0u64;
+    for _ in 0..2000 {
+        // xorshift64
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+
+        let x = f32::from_bits(state as u32);
+        let v = MyF32(state as u32);
+        let shortest = custom::preformat_shortest(v, &mut buf);
+        let expected = x.preformat_shortest(&mut buf_f32);
+        if shortest != expected {
+            // When two shortest representations are equally close, Dragon
+            // picks the larger one and Ryu and Dragonbox the even one.
+            if cfg!(not(any(
+                feature = "strategy-ryu",
+                feature = "strategy-dragonbox"
+            ))) {
+                panic!("{:?} != {:?}", shortest, expected);
+            }
+            match (shortest, expected) {
+                (
+                    PreFormatted::Finite(sign, digits, 0, exp),
+                    PreFormatted::Finite(_, expected, 0, expected_exp),
+                ) => {
+                    assert_eq!((digits.len(), exp), (expected.len(), expected_exp));
+                    assert_eq!(parse::<MyF32>(sign, digits, exp.into()), v);
+                }
+                other => panic!("{:?}", other),
+            }
+        }
+        assert_same_value(
+            custom::preformat_exact_exp(v, &mut buf, 20),
+            x.preformat_exact_exp(&mut buf_f32, 20),
+        );
+        let limit = (state >> 32) as i16 % 60;
+        assert_same_value(
+            custom::preformat_exact_limit(v, &mut buf, limit),
+            x.preformat_exact_limit(&mut buf_f32, limit),
+        );
+        if let PreFormatted::Finite(sign, digits, _, exp) =
+            custom::preformat_exact_exp(v, &mut buf, 12)
+        {
+            let y = f32::from_preparsed(PreParsed {
+                sign,
+                int_digits: b"",
+                frac_digits: digits,
+                exp: exp.into(),
+            })
+            .unwrap();
+            assert_eq!(parse::<MyF32>(sign, digits, exp.into()), MyF32(y.to_bits()));
+        }
+    }
+
+    // Every E4M3 number round-trips.
+    for bits in 0..=0xFF {
+        let v = E4M3(bits);
+        match custom::preformat_shortest(v, &mut buf) {
+            PreFormatted::Finite(sign, digits, 0, exp) => {
+                assert_eq!(parse::<E4M3>(sign, digits, exp.into()), v);
+            }
+            PreFormatted::Zero(sign) => assert_eq!(bits, u8::from(sign) << 7),
+            PreFormatted::NaN => assert_eq!(bits & 0x7F, 0x7F),
+            other => panic!("{:?}", other),
+        }
+    }
+    assert_eq!(
+        custom::preformat_shortest(E4M3(0x01), &mut buf),
+        PreFormatted::Finite(false, b"2", 0, -2),
+    );
+    assert_eq!(
+        custom::preformat_exact_fixed(E4M3(0x01), &mut buf, 10),
+        PreFormatted::Finite(false, b"19531250", 0, -2),
+    );
+    assert_eq!(parse::<E4M3>(false, b"47", 3), E4M3(0x7E));
+    assert_eq!(parse::<E4M3>(true, b"1", 10), E4M3(0xFE));
+    assert_eq!(parse::<E4M3>(false, b"9", -3), E4M3(0x00));
+}
+
+#[test]
+fn test_parts() {
+    use crate::core_num::flt2dec::decoder::{decode, FullDecoded};
+    use crate::core_num::flt2dec::strategy::dragon;
+    use crate::parts::{self, Parts, EXACT_FIXED_BASE_BUF_LEN};
+
+    // The digits can be split differently between `digits` and
+    // `num_zeros`.
+    fn expand(preformatted: PreFormatted<'_>) -> (bool, std::vec::Vec<u8>, i16) {
+        match preformatted {
+            PreFormatted::Finite(sign, digits, num_zeros, exp) => {
+                let mut digits = digits.to_vec();
+                digits.resize(digits.len() + num_zeros, b'0');
+                (sign, digits, exp)
+            }
+            other => panic!("{:?}", other),
+        }
+    }
+
+    let mut buf = [0; EXACT_FIXED_BASE_BUF_LEN];
+    let mut buf_f64 = [0; crate::PREFORMAT_EXACT_FIXED_BASE_BUF_LEN];
+
+    let mut state = 0x1234_5678_9abc_def0u64;
+    for _ in 0..2000 {
+        // xorshift64
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+
+        let x = f64::from_bits(state);
+        let (sign, decoded) = match decode(x) {
+            (sign, FullDecoded::Finite(decoded)) => (sign, decoded),
+            _ => continue,
+        };
+        let v = Parts {
+            sign,
+            mant: decoded.mant,
+            minus: decoded.minus,
+            plus: decoded.plus,
+            exp: decoded.exp,
+            inclusive: decoded.inclusive,
+        };
+
+        let (expected, expected_exp) = dragon::format_shortest(&decoded, &mut buf_f64);
+        assert_eq!(
+            parts::preformat_shortest_from_parts(v, &mut buf),
+            PreFormatted::Finite(sign, expected, 0, expected_exp),
+        );
+        assert_eq!(
+            expand(parts::preformat_exact_exp_from_parts(v, &mut buf, 25)),
+            expand(x.preformat_exact_exp(&mut buf_f64, 25)),
+        );
+        let limit = (state >> 32) as i16 % 400;
+        let preformatted = parts::preformat_exact_limit_from_parts(v, &mut buf, limit);
+        let expected = x.preformat_exact_limit(&mut buf_f64, limit);
+        if let PreFormatted::Zero(_) = expected {
+            assert_eq!(preformatted, expected);
+        } else {
+            assert_eq!(expand(preformatted), expand(expected));
+        }
+    }
+
+    // A wider interval gives fewer digits.
+    let mut v = Parts {
+        sign: true,
+        mant: 31_415_926,
+        minus: 1,
+        plus: 1,
+        exp: 0,
+        inclusive: false,
+    };
+    assert_eq!(
+        parts::preformat_shortest_from_parts(v, &mut buf),
+        PreFormatted::Finite(true, b"31415926", 0, 8),
+    );
+    v.minus = 30_000;
+    v.plus = 30_000;
+    assert_eq!(
+        parts::preformat_shortest_from_parts(v, &mut buf),
+        PreFormatted::Finite(true, b"314", 0, 8),
+    );
+    assert_eq!(
+        parts::preformat_exact_fixed_from_parts(v, &mut buf, 2),
+        PreFormatted::Finite(true, b"3141592600", 0, 8),
+    );
+
+    // Beyond the range of `f64`.
+    let mut v = Parts {
+        sign: false,
+        mant: 1,
+        minus: 1,
+        plus: 1,
+        exp: 16382,
+        inclusive: true,
+    };
+    assert_eq!(
+        parts::preformat_shortest_from_parts(v, &mut buf),
+        PreFormatted::Finite(false, b"3", 0, 4932),
+    );
+
+    v.mant = 0;
+    assert_eq!(
+        parts::preformat_exact_exp_from_parts(v, &mut buf, 3),
+        PreFormatted::Zero(false),
+    );
+}
+
+#[test]
+fn test_bits() {
+    use crate::bits;
+
+    fn preparsed(preformatted: PreFormatted<'_>) -> Option<PreParsed<'_>> {
+        match preformatted {
+            PreFormatted::Finite(sign, digits, _, exp) => Some(PreParsed {
+                sign,
+                int_digits: b"",
+                frac_digits: digits,
+                exp: exp.into(),
+            }),
+            PreFormatted::Zero(sign) => Some(PreParsed {
+                sign,
+                int_digits: b"0",
+                frac_digits: b"",
+                exp: 0,
+            }),
+            _ => None,
+        }
+    }
+
+    let mut buf = [0; crate::PREFORMAT_EXACT_FIXED_BASE_BUF_LEN + 20];
+    let mut expected_buf = [0; crate::PREFORMAT_EXACT_FIXED_BASE_BUF_LEN + 20];
+
+    let mut state = 0x1234_5678_9abc_def0u64;
+    for i in 0..4000 {
+        // xorshift64
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+
+        // Also exponents at the ends of the range.
+        let x = match i % 4 {
+            0 => state & 0x800F_FFFF_FFFF_FFFF,
+            1 => state | 0x7FF0_0000_0000_0000,
+            _ => state,
+        };
+        let v = f64::from_bits(x);
+        let limit = (state >> 40) as i16 % 400;
+        assert_eq!(
+            bits::preformat_shortest_f64(x, &mut buf),
+            v.preformat_shortest(&mut expected_buf),
+        );
+        assert_eq!(
+            bits::preformat_exact_exp_f64(x, &mut buf, 20),
+            v.preformat_exact_exp(&mut expected_buf, 20),
+        );
+        assert_eq!(
+            bits::preformat_exact_fixed_f64(x, &mut buf, 20),
+            v.preformat_exact_fixed(&mut expected_buf, 20),
+        );
+        assert_eq!(
+            bits::preformat_exact_limit_f64(x, &mut buf, limit),
+            v.preformat_exact_limit(&mut expected_buf, limit),
+        );
+        if let Some(p) = preparsed(bits::preformat_exact_exp_f64(x, &mut buf, 17)) {
+            assert_eq!(bits::from_preparsed_f64(p), Ok(x));
+        }
+
+        let x = match i % 4 {
+            0 => state as u32 & 0x807F_FFFF,
+            1 => state as u32 | 0x7F80_0000,
+            _ => state as u32,
+        };
+        let v = f32::from_bits(x);
+        let limit = limit % 60;
+        assert_eq!(
+            bits::preformat_shortest_f32(x, &mut buf),
+            v.preformat_shortest(&mut expected_buf),
+        );
+        assert_eq!(
+            bits::preformat_exact_exp_f32(x, &mut buf, 20),
+            v.preformat_exact_exp(&mut expected_buf, 20),
+        );
+        assert_eq!(
+            bits::preformat_exact_fixed_f32(x, &mut buf, 20),
+            v.preformat_exact_fixed(&mut expected_buf, 20),
+        );
+        assert_eq!(
+            bits::preformat_exact_limit_f32(x, &mut buf, limit),
+            v.preformat_exact_limit(&mut expected_buf, limit),
+        );
+        if let Some(p) = preparsed(bits::preformat_shortest_f32(x, &mut buf)) {
+            assert_eq!(bits::from_preparsed_f32(p), Ok(x));
+        }
+    }
+
+    let parse_f64 = |sign, digits: &[u8], exp| {
+        bits::from_preparsed_f64(PreParsed {
+            sign,
+            int_digits: digits,
+            frac_digits: b"",
+            exp,
+        })
+    };
+    assert_eq!(parse_f64(true, b"0", 0), Ok(0x8000_0000_0000_0000));
+    assert_eq!(parse_f64(true, b"1", 400), Ok(0xFFF0_0000_0000_0000));
+    assert_eq!(parse_f64(false, b"1", -400), Ok(0));
+    assert_eq!(parse_f64(false, b"5", -324), Ok(1));
+    assert_eq!(parse_f64(false, b"", 0), Err(ParseFloatError::EmptyDigits));
+}
+
+#[test]
+fn test_ieee_decimal() {
+    use crate::grammar::Parsed;
+    use crate::ieee_decimal::{self, Encoding, BUF_LEN};
+    use crate::DecimalOverflow;
+
+    let finite = |sign, digits, exp| PreFormatted::Finite(sign, digits, 0, exp);
+    let one = finite(false, b"1", 1);
+    let max = finite(false, b"9999999999999999", 385);
+    let encode64 = ieee_decimal::encode_decimal64;
+    let encode128 = ieee_decimal::encode_decimal128;
+
+    assert_eq!(encode64(one, Encoding::Bid), Ok(0x31C0_0000_0000_0001));
+    assert_eq!(encode64(one, Encoding::Dpd), Ok(0x2238_0000_0000_0001));
+    assert_eq!(encode64(max, Encoding::Bid), Ok(0x77FB_86F2_6FC0_FFFF));
+    assert_eq!(encode64(max, Encoding::Dpd), Ok(0x77FC_FF3F_CFF3_FCFF));
+    assert_eq!(
+        encode128(one, Encoding::Bid),
+        Ok(0x3040_0000_0000_0000_0000_0000_0000_0001)
+    );
+    assert_eq!(
+        encode128(one, Encoding::Dpd),
+        Ok(0x2208_0000_0000_0000_0000_0000_0000_0001)
+    );
+    assert_eq!(
+        encode64(PreFormatted::Zero(true), Encoding::Bid),
+        Ok(0xB1C0_0000_0000_0000)
+    );
+    assert_eq!(
+        encode64(PreFormatted::Inf(true), Encoding::Dpd),
+        Ok(0xF800_0000_0000_0000)
+    );
+    assert_eq!(
+        encode128(PreFormatted::NaN, Encoding::Bid),
+        Ok(0x7C00_0000_0000_0000_0000_0000_0000_0000)
+    );
+    // Trailing zeros are kept as long as they fit.
+    assert_eq!(
+        encode64(PreFormatted::Finite(false, b"15", 30, 1), Encoding::Bid),
+        encode64(finite(false, b"1500000000000000", 1), Encoding::Bid),
+    );
+    // Zeros are added or removed to fit the exponent range.
+    assert_eq!(
+        encode64(finite(false, b"1", 385), Encoding::Bid),
+        encode64(finite(false, b"1000000000000000", 385), Encoding::Bid),
+    );
+    assert_eq!(
+        encode64(finite(false, b"100", -396), Encoding::Dpd),
+        encode64(finite(false, b"10", -396), Encoding::Dpd),
+    );
+    assert_eq!(
+        encode64(finite(false, b"1", 386), Encoding::Bid),
+        Err(DecimalOverflow)
+    );
+    assert_eq!(
+        encode64(finite(false, b"12", -397), Encoding::Bid),
+        Err(DecimalOverflow)
+    );
+    assert_eq!(
+        encode64(finite(false, b"12345678901234567", 0), Encoding::Dpd),
+        Err(DecimalOverflow)
+    );
+
+    let mut buf = [0; BUF_LEN];
+    let finite_parsed = |sign, int_digits, exp| {
+        Parsed::Finite(PreParsed {
+            sign,
+            int_digits,
+            frac_digits: b"",
+            exp,
+        })
+    };
+    assert_eq!(
+        ieee_decimal::decode_decimal64(0x77FC_FF3F_CFF3_FCFF, Encoding::Dpd, &mut buf),
+        finite_parsed(false, b"9999999999999999", 369),
+    );
+    assert_eq!(
+        ieee_decimal::decode_decimal64(0xB1C0_0000_0000_0000, Encoding::Bid, &mut buf),
+        finite_parsed(true, b"0", 0),
+    );
+    // A non-canonical BID significand is zero.
+    assert_eq!(
+        ieee_decimal::decode_decimal64(0x6C7F_FFFF_FFFF_FFFF, Encoding::Bid, &mut buf),
+        finite_parsed(false, b"0", 1),
+    );
+    assert_eq!(
+        ieee_decimal::decode_decimal128(0x7E00 << 112, Encoding::Dpd, &mut buf),
+        Parsed::NaN,
+    );
+    assert_eq!(
+        ieee_decimal::decode_decimal128(0xF800 << 112, Encoding::Bid, &mut buf),
+        Parsed::Inf(true),
+    );
+
+    // Every declet.
+    for n in 0..1000 {
+        let digits = std::format!("{}", n);
+        let v = PreFormatted::Finite(false, digits.as_bytes(), 0, digits.len() as i16);
+        let bits = encode64(v, Encoding::Dpd).unwrap();
+        assert_eq!(
+            ieee_decimal::decode_decimal64(bits, Encoding::Dpd, &mut buf),
+            Parsed::Finite(PreParsed {
+                sign: false,
+                int_digits: digits.as_bytes(),
+                frac_digits: b"",
+                exp: 0,
+            }),
+        );
+    }
+
+    let mut digits_buf = [0; 34];
+    let mut state = 0x1234_5678_9abc_def0u64;
+    for _ in 0..2000 {
+        // xorshift64
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+
+        let v = f64::from_bits(state);
+        if !v.is_finite() {
+            continue;
+        }
+        for &encoding in &[Encoding::Bid, Encoding::Dpd] {
+            let preformatted = v.preformat_exact_exp(&mut digits_buf, 16);
+            // Every finite `f64` is in the range of decimal64.
+            let bits = encode64(preformatted, encoding).unwrap();
+            let parsed = ieee_decimal::decode_decimal64(bits, encoding, &mut buf);
+            let expected = match preformatted {
+                PreFormatted::Finite(sign, digits, _, exp) => f64::from_preparsed(PreParsed {
+                    sign,
+                    int_digits: b"",
+                    frac_digits: digits,
+                    exp: exp.into(),
+                }),
+                _ => Ok(v),
+            };
+            assert_eq!(parsed.to_float::<f64>(), expected);
+
+            // 34 digits are enough to round-trip.
+            let preformatted = v.preformat_exact_exp(&mut digits_buf, 34);
+            let bits = encode128(preformatted, encoding).unwrap();
+            let parsed = ieee_decimal::decode_decimal128(bits, encoding, &mut buf);
+            assert_eq!(parsed.to_float::<f64>(), Ok(v));
+        }
+    }
+}
+
+#[test]
+fn test_double_double() {
+    use crate::double_double::{DoubleDouble, EXACT_FIXED_BASE_BUF_LEN};
+
+    // The digits can be split differently between `digits` and
+    // `num_zeros`.
+    fn expand(preformatted: PreFormatted<'_>) -> (bool, std::vec::Vec<u8>, i16) {
+        match preformatted {
+            PreFormatted::Finite(sign, digits, num_zeros, exp) => {
+                let mut digits = digits.to_vec();
+                digits.resize(digits.len() + num_zeros, b'0');
+                (sign, digits, exp)
+            }
+            other => panic!("{:?}", other),
+        }
+    }
+
+    fn parse(sign: bool, digits: &[u8], exp: i32) -> DoubleDouble {
+        DoubleDouble::from_preparsed(PreParsed {
+            sign,
+            int_digits: b"",
+            frac_digits: digits,
+            exp,
+        })
+        .unwrap()
+    }
+
+    let dd = |hi, lo| DoubleDouble { hi, lo };
+    let mut buf = [0; EXACT_FIXED_BASE_BUF_LEN];
+    let mut buf_f64 = [0; crate::PREFORMAT_EXACT_FIXED_BASE_BUF_LEN];
+
+    let mut state = 0x1234_5678_9abc_def0u64;
+    for _ in 0..2000 {
+        // xorshift64
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+
+        let hi = f64::from_bits(state);
+        if !hi.is_finite() {
+            continue;
+        }
+        assert_eq!(
+            expand(dd(hi, 0.0).preformat_exact_exp(&mut buf, 30)),
+            expand(hi.preformat_exact_exp(&mut buf_f64, 30)),
+        );
+
+        // A canonical pair, whose `lo` is less than half a unit of `hi`
+        // and at most 54 bits below it.
+        let unit_exp = (((state >> 52) & 0x7FF) as i64).max(1) - 1075;
+        let lo_exp = unit_exp - 54;
+        if lo_exp < -1022 {
+            continue;
+        }
+        let lo_m = (state >> 11).rotate_left(17) & ((1 << 53) - 1);
+        let lo = lo_m as f64 * f64::from_bits(((lo_exp + 1023) as u64) << 52);
+        let lo = if state & 2 == 0 { lo } else { -lo };
+        let v = dd(hi, lo);
+        match v.preformat_shortest(&mut buf) {
+            PreFormatted::Finite(sign, digits, 0, exp) => {
+                assert_eq!(parse(sign, digits, exp.into()), v);
+            }
+            PreFormatted::Zero(_) => assert_eq!(hi, 0.0),
+            other => panic!("{:?}", other),
+        }
+        if let PreFormatted::Finite(sign, digits, _, exp) = v.preformat_exact_exp(&mut buf, 34) {
+            assert_eq!(parse(sign, digits, exp.into()), v);
+        }
+    }
+
+    // `lo` far below `hi`, rounded to 107 bits (half to even).
+    let tie = f64::from_bits((1023 - 107) << 52);
+    let above_tie = f64::from_bits((1023 - 107) << 52 | 1);
+    let unit = f64::from_bits((1023 - 106) << 52);
+    assert_eq!(
+        expand(dd(1.0, tie).preformat_exact_exp(&mut buf, 40)),
+        expand(dd(1.0, 0.0).preformat_exact_exp(&mut buf_f64, 40)),
+    );
+    assert_eq!(
+        expand(dd(1.0, above_tie).preformat_exact_exp(&mut buf, 40)),
+        expand(dd(1.0, unit).preformat_exact_exp(&mut buf_f64, 40)),
+    );
+    assert_eq!(
+        dd(1.0, -1e-300).preformat_shortest(&mut buf),
+        PreFormatted::Finite(false, b"1", 0, 1),
+    );
+    // Not canonical.
+    assert_eq!(
+        dd(1.0, 1.0).preformat_shortest(&mut buf),
+        PreFormatted::Finite(false, b"2", 0, 1),
+    );
+    assert_eq!(
+        dd(-0.5, 1.0).preformat_shortest(&mut buf),
+        PreFormatted::Finite(false, b"5", 0, 0),
+    );
+
+    assert_eq!(
+        dd(-0.0, -0.0).preformat_shortest(&mut buf),
+        PreFormatted::Zero(true)
+    );
+    assert_eq!(
+        dd(1.0, -1.0).preformat_shortest(&mut buf),
+        PreFormatted::Zero(false)
+    );
+    assert_eq!(
+        dd(1.0, f64::NEG_INFINITY).preformat_shortest(&mut buf),
+        PreFormatted::Inf(true)
+    );
+    assert_eq!(
+        dd(f64::INFINITY, f64::NEG_INFINITY).preformat_shortest(&mut buf),
+        PreFormatted::NaN
+    );
+
+    assert_eq!(parse(false, b"18", 309), dd(f64::INFINITY, 0.0));
+    assert_eq!(parse(true, b"1", -400), dd(-0.0, -0.0));
+    assert_eq!(parse(false, b"1", -320), dd(1e-321, 0.0));
+    let max = parse(false, b"17976931348623158", 309);
+    assert_eq!(max.hi, f64::MAX);
+    assert!(max.lo > 0.0);
+}
@@ -0,0 +1,1045 @@
+//! Digit generation and parsing for binary formats wider than `f64`, with
+//! significands of up to 126 bits and 15-bit exponents (the x87 extended
+//! precision format and binary128).
+//!
+//! These are the Dragon algorithms of `core_num::flt2dec::strategy::dragon`
+//! and the exact division of `generic::from_preparsed_scale2`, with a
+//! bignum that covers the whole exponent range (`Big32x530`, which takes
+//! about 2 KiB of stack for each number).
+
+use core::cmp::Ordering;
+
+use crate::core_num::bignum::Big32x530 as Big;
+use crate::core_num::flt2dec::strategy::dragon::{POW10, TWOPOW10};
+use crate::core_num::flt2dec::{Sink, SliceSink};
+use crate::rounding::Remainder;
+use crate::{ParseFloatError, PreFormatted, PreParsed, RoundingMode};
+
+/// Like `core_num::flt2dec::decoder::Decoded`, with a wider significand
+/// and exponent.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Decoded {
+    /// The scaled mantissa.
+    pub(crate) mant: u128,
+    /// The lower error range.
+    pub(crate) minus: u128,
+    /// The upper error range.
+    pub(crate) plus: u128,
+    /// The shared exponent in base 2.
+    pub(crate) exp: i32,
+    /// Whether the error range is inclusive.
+    pub(crate) inclusive: bool,
+}
+
+impl Decoded {
+    /// Decodes the finite non-zero `m * 2^e`, whose lower neighbor is
+    /// closer than the upper one if `lower_closer` (that is, when `m` is
+    /// the smallest significand of a binade above the smallest one).
+    pub(crate) fn new(m: u128, e: i32, lower_closer: bool) -> Self {
+        debug_assert!(m != 0 && m >> 126 == 0);
+        let inclusive = m & 1 == 0;
+        if lower_closer {
+            Decoded {
+                mant: m << 2,
+                minus: 1,
+                plus: 2,
+                exp: e - 2,
+                inclusive,
+            }
+        } else {
+            Decoded {
+                mant: m << 1,
+                minus: 1,
+                plus: 1,
+                exp: e - 1,
+                inclusive,
+            }
+        }
+    }
+}
+
+/// Like `core_num::flt2dec::decoder::FullDecoded`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum FullDecoded {
+    Nan,
+    Infinite,
+    Zero,
+    Finite(Decoded),
+}
+
+/// A binary floating point format.
+pub(crate) struct Format {
+    /// Number of significand bits, including the integer bit.
+    pub(crate) sig_bits: u32,
+    /// Binary exponent of the least significant bit of subnormals.
+    pub(crate) min_lsb_exp: i32,
+    /// Binary exponent of the largest finite value.
+    pub(crate) max_exp: i32,
+}
+
+/// How a number is rounded to a `Format`.
+pub(crate) enum Rounding<F> {
+    Mode(RoundingMode),
+    /// Up (in magnitude) when the dropped fraction of the unit in the last
+    /// place is at least `(random + 1) / 2^64`, and down otherwise.
+    Stochastic(Random<F>),
+}
+
+/// A random `u64`, only generated when it is needed.
+pub(crate) enum Random<F> {
+    Pending(F),
+    Ready(u64),
+}
+
+impl<F: FnOnce() -> u64> Random<F> {
+    fn get(&mut self) -> u64 {
+        let v = match core::mem::replace(self, Random::Ready(0)) {
+            Random::Pending(random) => random(),
+            Random::Ready(v) => v,
+        };
+        *self = Random::Ready(v);
+        v
+    }
+}
+
+/// A number rounded to a `Format`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Rounded {
+    Zero,
+    /// `q * 2^k`, where `q` has `sig_bits` bits, or less if `k` is
+    /// `min_lsb_exp` (subnormals).
+    Finite(u128, i32),
+    Infinite,
+}
+
+pub(crate) fn preformat_shortest(
+    sign: bool,
+    full_decoded: FullDecoded,
+    buf: &mut [u8],
+) -> PreFormatted<'_> {
+    match full_decoded {
+        FullDecoded::Nan => PreFormatted::NaN,
+        FullDecoded::Infinite => PreFormatted::Inf(sign),
+        FullDecoded::Zero => PreFormatted::Zero(sign),
+        FullDecoded::Finite(ref decoded) => {
+            let (digits, exp) = format_shortest(decoded, buf);
+            PreFormatted::Finite(sign, digits, 0, exp)
+        }
+    }
+}
+
+pub(crate) fn preformat_exact_exp(
+    sign: bool,
+    full_decoded: FullDecoded,
+    buf: &mut [u8],
+    ndigits: usize,
+) -> PreFormatted<'_> {
+    match full_decoded {
+        FullDecoded::Nan => PreFormatted::NaN,
+        FullDecoded::Infinite => PreFormatted::Inf(sign),
+        FullDecoded::Zero => PreFormatted::Zero(sign),
+        FullDecoded::Finite(ref decoded) => {
+            // Like `generic::preformat_exact_exp_decoded`
+            let maxlen = estimate_max_buf_len(decoded.exp);
+            let trunc = if ndigits < maxlen { ndigits } else { maxlen };
+
+            let (digits, exp) = format_exact(decoded, &mut buf[..trunc], i16::MIN);
+            PreFormatted::Finite(sign, digits, ndigits - digits.len(), exp)
+        }
+    }
+}
+
+pub(crate) fn preformat_exact_fixed(
+    sign: bool,
+    full_decoded: FullDecoded,
+    buf: &mut [u8],
+    frac_digits: isize,
+) -> PreFormatted<'_> {
+    // Like `generic::preformat_exact_fixed_decoded`
+    let limit = -(frac_digits as i64).max(-0x7FFF).min(0x8000) as i16;
+    match preformat_exact_limit(sign, full_decoded, buf, limit) {
+        PreFormatted::Finite(sign, digits, _, exp) => {
+            let num_zeros = if exp > 0 {
+                let ndigits = (frac_digits.max(0) as usize).saturating_add(exp as usize);
+                ndigits - digits.len()
+            } else {
+                0
+            };
+            PreFormatted::Finite(sign, digits, num_zeros, exp)
+        }
+        other => other,
+    }
+}
+
+pub(crate) fn preformat_exact_limit(
+    sign: bool,
+    full_decoded: FullDecoded,
+    buf: &mut [u8],
+    limit: i16,
+) -> PreFormatted<'_> {
+    match full_decoded {
+        FullDecoded::Nan => PreFormatted::NaN,
+        FullDecoded::Infinite => PreFormatted::Inf(sign),
+        FullDecoded::Zero => PreFormatted::Zero(sign),
+        FullDecoded::Finite(ref decoded) => {
+            // Like `generic::preformat_exact_limit_decoded`
+            let maxlen = estimate_max_buf_len(decoded.exp);
+            let (digits, exp) = format_exact(decoded, &mut buf[..maxlen], limit);
+            if exp <= limit {
+                PreFormatted::Zero(sign)
+            } else {
+                let ndigits = (i32::from(exp) - i32::from(limit)) as usize;
+                PreFormatted::Finite(sign, digits, ndigits.saturating_sub(digits.len()), exp)
+            }
+        }
+    }
+}
+
+/// Like `core_num::flt2dec::estimate_max_buf_len`, for the 128-bit `mant`
+/// of `Decoded` (which has up to 39 digits, instead of 20).
+pub(crate) fn estimate_max_buf_len(exp: i32) -> usize {
+    40 + ((if exp < 0 { -12 } else { 5 } * exp) >> 4) as usize
+}
+
+fn big_from_u128(v: u128) -> Big {
+    let mut x = Big::from_u64((v >> 64) as u64);
+    x.mul_pow2(64).add(&Big::from_u64(v as u64));
+    x
+}
+
+fn mul_pow10(x: &mut Big, n: usize) -> &mut Big {
+    x.mul_pow5(n).mul_pow2(n)
+}
+
+fn div_2pow10(x: &mut Big, mut n: usize) -> &mut Big {
+    let largest = POW10.len() - 1;
+    while n > largest {
+        x.div_rem_small(POW10[largest]);
+        n -= largest;
+    }
+    x.div_rem_small(TWOPOW10[n]);
+    x
+}
+
+/// Returns `(2, 4, 8) * scale`.
+fn multiples(scale: &Big) -> (Big, Big, Big) {
+    let mut scale2 = scale.clone();
+    scale2.mul_pow2(1);
+    let mut scale4 = scale.clone();
+    scale4.mul_pow2(2);
+    let mut scale8 = scale.clone();
+    scale8.mul_pow2(3);
+    (scale2, scale4, scale8)
+}
+
+// only usable when `x < 16 * scale`
+fn div_rem_upto_16(x: &mut Big, scale: &Big, multiples: &(Big, Big, Big)) -> u8 {
+    let (ref scale2, ref scale4, ref scale8) = *multiples;
+    let mut d = 0;
+    if *x >= *scale8 {
+        x.sub(scale8);
+        d += 8;
+    }
+    if *x >= *scale4 {
+        x.sub(scale4);
+        d += 4;
+    }
+    if *x >= *scale2 {
+        x.sub(scale2);
+        d += 2;
+    }
+    if *x >= *scale {
+        x.sub(scale);
+        d += 1;
+    }
+    debug_assert!(*x < *scale);
+    d
+}
+
+/// Finds `k_0` such that `10^(k_0-1) < x * 2^exp <= 10^(k_0+1)`, like
+/// `core_num::flt2dec::estimator::estimate_scaling_factor`.
+fn estimate_scaling_factor(x: &Big, exp: i32) -> i32 {
+    // 2^(nbits-1) <= x < 2^nbits
+    let nbits = x.bit_length() as i64;
+    // 1292913986 = floor(2^32 * log_10 2), which gives the exact floor
+    // for the exponents of the supported formats.
+    (((nbits + i64::from(exp)) * 1_292_913_986) >> 32) as i32
+}
+
+/// Like `core_num::flt2dec::strategy::dragon::format_shortest`.
+fn format_shortest<'a>(d: &Decoded, buf: &'a mut [u8]) -> (&'a [u8], i16) {
+    let mut sink = SliceSink::new(buf);
+
+    // `a.cmp(&b) < rounding` is `if d.inclusive {a <= b} else {a < b}`
+    let rounding = if d.inclusive {
+        Ordering::Greater
+    } else {
+        Ordering::Equal
+    };
+
+    // `v = mant / scale`, `low = (mant - minus) / scale` and
+    // `high = (mant + plus) / scale`.
+    let mut mant = big_from_u128(d.mant);
+    let mut minus = big_from_u128(d.minus);
+    let mut plus = big_from_u128(d.plus);
+    let mut k = estimate_scaling_factor(mant.clone().add(&plus), d.exp);
+    let mut scale = Big::from_small(1);
+    if d.exp < 0 {
+        scale.mul_pow2(-d.exp as usize);
+    } else {
+        mant.mul_pow2(d.exp as usize);
+        minus.mul_pow2(d.exp as usize);
+        plus.mul_pow2(d.exp as usize);
+    }
+
+    // divide `mant` by `10^k`. now `scale / 10 < mant + plus <= scale * 10`.
+    if k >= 0 {
+        mul_pow10(&mut scale, k as usize);
+    } else {
+        mul_pow10(&mut mant, -k as usize);
+        mul_pow10(&mut minus, -k as usize);
+        mul_pow10(&mut plus, -k as usize);
+    }
+
+    // now `scale < mant + plus <= scale * 10`.
+    if scale.cmp(mant.clone().add(&plus)) < rounding {
+        k += 1;
+    } else {
+        mant.mul_small(10);
+        minus.mul_small(10);
+        plus.mul_small(10);
+    }
+
+    let multiples = multiples(&scale);
+    let mut down;
+    let mut up;
+    loop {
+        let d = div_rem_upto_16(&mut mant, &scale, &multiples);
+        debug_assert!(d < 10);
+        sink.push(b'0' + d);
+
+        // stop and round `down` when `mant < minus` (or `<=`), or `up`
+        // when `scale < mant + plus` (or `<=`).
+        down = mant.cmp(&minus) < rounding;
+        up = scale.cmp(mant.clone().add(&plus)) < rounding;
+        if down || up {
+            break;
+        }
+
+        mant.mul_small(10);
+        minus.mul_small(10);
+        plus.mul_small(10);
+    }
+
+    if up && (!down || *mant.mul_pow2(1) >= scale) {
+        if let Some(c) = sink.round_up() {
+            sink.push(c);
+            k += 1;
+        }
+    }
+
+    (sink.into_digits(), k as i16)
+}
+
+/// Like `core_num::flt2dec::strategy::dragon::format_exact`.
+fn format_exact<'a>(d: &Decoded, buf: &'a mut [u8], limit: i16) -> (&'a [u8], i16) {
+    let mut sink = SliceSink::new(buf);
+    let exp = format_exact_into(big_from_u128(d.mant), d.exp, &mut sink, limit);
+    (sink.into_digits(), exp)
+}
+
+/// Like `core_num::flt2dec::strategy::dragon::format_exact_into`, for
+/// `mant * 2^exp`.
+fn format_exact_into<S: Sink>(mut mant: Big, exp: i32, sink: &mut S, limit: i16) -> i16 {
+    let limit = i32::from(limit);
+
+    // `v = mant / scale`.
+    let mut k = estimate_scaling_factor(&mant, exp);
+    let mut scale = Big::from_small(1);
+    if exp < 0 {
+        scale.mul_pow2(-exp as usize);
+    } else {
+        mant.mul_pow2(exp as usize);
+    }
+
+    // divide `mant` by `10^k`. now `scale / 10 < mant <= scale * 10`.
+    if k >= 0 {
+        mul_pow10(&mut scale, k as usize);
+    } else {
+        mul_pow10(&mut mant, -k as usize);
+    }
+
+    // fixup when `mant + plus >= scale`, where `plus / scale = 10^-sink.capacity() / 2`.
+    if *div_2pow10(&mut scale.clone(), sink.capacity()).add(&mant) >= scale {
+        k += 1;
+    } else {
+        mant.mul_small(10);
+    }
+
+    // shorten the buffer to the last-digit limitation, to avoid double
+    // rounding.
+    let len = if k < limit {
+        0
+    } else {
+        ((k - limit) as usize).min(sink.capacity())
+    };
+
+    if len > 0 {
+        let multiples = multiples(&scale);
+        for i in 0..len {
+            if mant.is_zero() {
+                // following digits are all zeroes.
+                sink.zeros(len - i);
+                return k as i16;
+            }
+
+            let d = div_rem_upto_16(&mut mant, &scale, &multiples);
+            debug_assert!(d < 10);
+            sink.push(b'0' + d);
+            mant.mul_small(10);
+        }
+    }
+
+    // round half to even.
+    let order = mant.cmp(scale.mul_small(5));
+    if order == Ordering::Greater
+        || (order == Ordering::Equal && sink.last().map_or(true, |c| c & 1 == 1))
+    {
+        if let Some(c) = sink.round_up() {
+            k += 1;
+            if k > limit && len < sink.capacity() {
+                sink.push(c);
+            }
+        }
+    }
+
+    k as i16
+}
+
+/// Divides `u` by `v`, when the quotient is below `2^bits`.
+fn div_rem(u: &Big, v: &Big, bits: u32) -> (u128, Big) {
+    debug_assert!(bits <= 128);
+    let mut rem = u.clone();
+    // `step = v * 2^i` in each step.
+    let mut step = v.clone();
+    step.mul_pow2(bits as usize - 1);
+    let mut q = 0;
+    for i in (0..bits).rev() {
+        if rem >= step {
+            rem.sub(&step);
+            q |= 1 << i;
+        }
+        step.div_rem_small(2);
+    }
+    (q, rem)
+}
+
+/// Converts `preparsed` into `format`, rounding half to even, like
+/// `generic::from_preparsed_scale2`.
+pub(crate) fn from_preparsed(
+    preparsed: PreParsed<'_>,
+    format: &Format,
+) -> Result<Rounded, ParseFloatError> {
+    from_preparsed_rounded(preparsed, format, RoundingMode::NearestEven)
+}
+
+/// Converts `preparsed` into `format`, rounding with `mode`.
+///
+/// Values that overflow become the largest finite number instead of
+/// infinity when `mode` rounds them toward zero.
+pub(crate) fn from_preparsed_rounded(
+    preparsed: PreParsed<'_>,
+    format: &Format,
+    mode: RoundingMode,
+) -> Result<Rounded, ParseFloatError> {
+    from_preparsed_scaled(preparsed, 0, format, mode)
+}
+
+/// Converts `preparsed * 2^scale2` into `format`, rounding with `mode`.
+pub(crate) fn from_preparsed_scaled(
+    preparsed: PreParsed<'_>,
+    scale2: i64,
+    format: &Format,
+    mode: RoundingMode,
+) -> Result<Rounded, ParseFloatError> {
+    let mut rounding = Rounding::<fn() -> u64>::Mode(mode);
+    from_preparsed_rounding(preparsed, scale2, format, &mut rounding)
+}
+
+/// Converts `preparsed * 2^scale2` into `format`, rounding with
+/// `rounding`.
+///
+/// Digits that do not fit in the bignums are only compared with the
+/// digits of the float or halfway point (or stochastic threshold) between
+/// the kept ones and the next number with as many digits, if any. Fails
+/// with `ParseFloatError::ExponentOutOfRange` if `scale2` cancels so many
+/// orders of magnitude that the exponent does not fit in the bignums.
+pub(crate) fn from_preparsed_rounding<F: FnOnce() -> u64>(
+    preparsed: PreParsed<'_>,
+    scale2: i64,
+    format: &Format,
+    rounding: &mut Rounding<F>,
+) -> Result<Rounded, ParseFloatError> {
+    if preparsed.int_digits.is_empty() && preparsed.frac_digits.is_empty() {
+        return Err(ParseFloatError::EmptyDigits);
+    }
+    let all_digits = preparsed.int_digits.iter().chain(preparsed.frac_digits);
+    if let Some((index, &byte)) = all_digits.enumerate().find(|(_, c)| !c.is_ascii_digit()) {
+        return Err(ParseFloatError::InvalidDigit { byte, index });
+    }
+
+    let preparsed = preparsed.trim_leading_zeros()?.trim_trailing_zeros()?;
+    let num_digits = preparsed.int_digits.len() + preparsed.frac_digits.len();
+    if preparsed.significant_digits() == 0 {
+        return Ok(Rounded::Zero);
+    }
+    let min_lsb_exp = i64::from(format.min_lsb_exp);
+    let max_exp = i64::from(format.max_exp);
+
+    // value = d * 10^exp10 * 2^scale2, with `d` of `num_digits` digits
+    let exp10 = i64::from(preparsed.exp) - preparsed.frac_digits.len() as i64;
+    // Bounds of the base 2 logarithm of the value, with
+    // 3.32 < log2(10) < 3.33.
+    let log10_lo = exp10 + num_digits as i64 - 1;
+    let log10_hi = log10_lo + 1;
+    let log2_lo = if log10_lo >= 0 {
+        log10_lo * 332 / 100
+    } else {
+        log10_lo * 333 / 100 - 1
+    } + scale2;
+    let log2_hi = if log10_hi >= 0 {
+        log10_hi * 333 / 100 + 1
+    } else {
+        log10_hi * 332 / 100
+    } + scale2;
+    let sign = preparsed.sign;
+    if log2_lo > max_exp + 1 {
+        return Ok(overflow(sign, format, rounding));
+    } else if log2_hi < min_lsb_exp - 1 {
+        match *rounding {
+            // Less than half of the smallest subnormal.
+            Rounding::Mode(mode) => {
+                return Ok(if mode.round_up(sign, false, Remainder::BelowHalf) {
+                    Rounded::Finite(1, format.min_lsb_exp)
+                } else {
+                    Rounded::Zero
+                });
+            }
+            // Less than 2^-64 times the smallest subnormal, which is never
+            // rounded up.
+            Rounding::Stochastic(_) if log2_hi < min_lsb_exp - 64 => {
+                return Ok(Rounded::Zero);
+            }
+            Rounding::Stochastic(_) => {}
+        }
+    }
+
+    // value = u / v * 2^(exp10 + scale2), where `u` and `v` have at most
+    // `MAX_BITS` bits (with 2.32 < log2(5) < 2.33), which leaves room for
+    // the shifts below.
+    const MAX_BITS: i64 = 16_800;
+    // Whether `n` digits times 10^(len - n) fit, where `len` is the number
+    // of digits of the integer part of the value.
+    let len = exp10 + num_digits as i64;
+    let fits = |n: i64| {
+        let exp10 = len - n;
+        let pow5_bits = exp10.abs() * 233 / 100 + 1;
+        let digits_bits = n * 333 / 100 + 1;
+        if exp10 >= 0 {
+            digits_bits + pow5_bits <= MAX_BITS
+        } else {
+            digits_bits <= MAX_BITS && pow5_bits <= MAX_BITS
+        }
+    };
+    let digits = || preparsed.int_digits.iter().chain(preparsed.frac_digits);
+    let from_digits = |n: usize| {
+        let mut d = Big::from_small(0);
+        for &c in digits().take(n) {
+            d.mul_small(10).add_small(u32::from(c - b'0'));
+        }
+        d
+    };
+    if fits(num_digits as i64) {
+        let (u, v) = mul_pow5(from_digits(num_digits), exp10);
+        return Ok(divide(u, v, exp10 + scale2, sign, format, rounding));
+    }
+
+    // The most digits `n` that fit with one more digit.
+    let (mut n, mut too_many) = (0, num_digits as i64);
+    while too_many - n > 1 {
+        let mid = n + (too_many - n) / 2;
+        if fits(mid + 1) {
+            n = mid;
+        } else {
+            too_many = mid;
+        }
+    }
+    if n < 60 {
+        // Only possible when `scale2` cancels thousands of orders of
+        // magnitude, since `n` is over 2000 otherwise.
+        return Err(ParseFloatError::ExponentOutOfRange);
+    }
+    // value is between x = t * 10^(len - n) and (t + 1) * 10^(len - n),
+    // where `t` is the first `n` digits (the last digit is not zero). The
+    // rounding only changes at floats and halfway points between them (or
+    // at the thresholds of stochastic rounding), which are too far apart
+    // for more than one of them to be between the bounds. So the value is
+    // rounded like a number just above `x` if it is below the next one of
+    // them, `b`, and like `b` or a number just above it otherwise.
+    let exp10 = len - n - 1;
+    let mut t = from_digits(n as usize);
+    let mut upper = t.clone();
+    t.mul_small(10);
+    upper.add_small(1).mul_small(10);
+    let (u, v) = mul_pow5(t, exp10);
+    let lower = Quotient::new(u, v, exp10 + scale2, format);
+    // b = (qb + fb / 2^bits) * 2^kb, or the float above `x` when it comes
+    // first.
+    let kb = lower.k;
+    let (bits, qb, fb) = match *rounding {
+        Rounding::Mode(_) => match lower.fraction(1) {
+            (0, _) => (1, lower.q, 1),
+            _ => (1, lower.q + 1, 0),
+        },
+        Rounding::Stochastic(ref mut random) => {
+            let threshold = u128::from(random.get()) + 1;
+            let (frac, _) = lower.fraction(64);
+            if frac >= threshold || threshold >> 64 != 0 {
+                (64, lower.q + 1, 0)
+            } else {
+                (64, lower.q, threshold)
+            }
+        }
+    };
+
+    let (u, v) = mul_pow5(upper, exp10);
+    let upper = Quotient::new(u, v, exp10 + scale2, format);
+    if upper.k == kb {
+        let (frac, exact) = upper.fraction(bits);
+        if (upper.q, frac) < (qb, fb) || ((upper.q, frac) == (qb, fb) && exact) {
+            return Ok(finish(lower, sign, format, rounding, true));
+        }
+    }
+
+    // Compares the digits with the ones of b / 2^scale2 = mant * 2^exp.
+    let mut mant = big_from_u128(qb);
+    mant.mul_pow2(bits as usize).add(&big_from_u128(fb));
+    let exp = kb - i64::from(bits) - scale2;
+    let mant_bits = mant.bit_length() as i64;
+    if mant_bits + exp.abs() > MAX_BITS + 128 {
+        // Like above, only possible with a large `scale2`.
+        return Err(ParseFloatError::ExponentOutOfRange);
+    }
+    let mut compare = Compare {
+        int_digits: preparsed.int_digits,
+        frac_digits: preparsed.frac_digits,
+        capacity: (mant_bits + exp.abs() + 1) as usize,
+        len: 0,
+        last: None,
+        order: Ordering::Equal,
+    };
+    let b_len = format_exact_into(mant, exp as i32, &mut compare, i16::MIN);
+    let order = match len.cmp(&i64::from(b_len)) {
+        Ordering::Equal => compare.finish(),
+        order => order,
+    };
+
+    let boundary = if qb >> format.sig_bits != 0 {
+        debug_assert!(fb == 0);
+        Quotient {
+            q: qb >> 1,
+            rem: Big::from_small(0),
+            v: Big::from_small(1),
+            k: kb + 1,
+        }
+    } else {
+        let mut denom = Big::from_small(1);
+        denom.mul_pow2(bits as usize);
+        Quotient {
+            q: qb,
+            rem: big_from_u128(fb),
+            v: denom,
+            k: kb,
+        }
+    };
+    Ok(match order {
+        Ordering::Less => finish(lower, sign, format, rounding, true),
+        Ordering::Equal => finish(boundary, sign, format, rounding, false),
+        Ordering::Greater => finish(boundary, sign, format, rounding, true),
+    })
+}
+
+/// A `Sink` that compares the digits of a number with `int_digits` and
+/// `frac_digits` (padded with zeros), as if they had the same exponent.
+///
+/// The digits are exact, so they are never rounded up.
+struct Compare<'a> {
+    int_digits: &'a [u8],
+    frac_digits: &'a [u8],
+    capacity: usize,
+    len: usize,
+    last: Option<u8>,
+    /// The order of `int_digits` and `frac_digits` with respect to the
+    /// digits received so far.
+    order: Ordering,
+}
+
+impl Compare<'_> {
+    fn digit(&self, i: usize) -> u8 {
+        match i.checked_sub(self.int_digits.len()) {
+            None => self.int_digits[i],
+            Some(i) => self.frac_digits.get(i).copied().unwrap_or(b'0'),
+        }
+    }
+
+    /// Returns the order of the digits with respect to all the received
+    /// ones.
+    fn finish(&self) -> Ordering {
+        // The last digit is not zero.
+        let num_digits = self.int_digits.len() + self.frac_digits.len();
+        if self.order == Ordering::Equal && num_digits > self.len {
+            Ordering::Greater
+        } else {
+            self.order
+        }
+    }
+}
+
+impl Sink for Compare<'_> {
+    fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn push(&mut self, d: u8) {
+        if self.order == Ordering::Equal {
+            self.order = self.digit(self.len).cmp(&d);
+        }
+        self.len += 1;
+        self.last = Some(d);
+    }
+
+    fn zeros(&mut self, n: usize) {
+        if n == 0 {
+            return;
+        }
+        let num_digits = self.int_digits.len() + self.frac_digits.len();
+        let end = (self.len + n).min(num_digits);
+        if self.order == Ordering::Equal && (self.len..end).any(|i| self.digit(i) != b'0') {
+            self.order = Ordering::Greater;
+        }
+        self.len += n;
+        self.last = Some(b'0');
+    }
+
+    fn last(&self) -> Option<u8> {
+        self.last
+    }
+
+    #[cfg(not(any(feature = "strategy-ryu", feature = "strategy-dragonbox")))]
+    fn set_last(&mut self, _: u8) {
+        unreachable!();
+    }
+
+    fn round_up(&mut self) -> Option<u8> {
+        unreachable!();
+    }
+
+    fn clear(&mut self) {
+        unreachable!();
+    }
+}
+
+/// Returns `(u, v)` such that `u / v = d * 5^exp`.
+fn mul_pow5(mut d: Big, exp: i64) -> (Big, Big) {
+    let mut v = Big::from_small(1);
+    if exp >= 0 {
+        d.mul_pow5(exp as usize);
+    } else {
+        v.mul_pow5(-exp as usize);
+    }
+    (d, v)
+}
+
+/// Converts `preparsed`, whose digits and exponent are in base `radix`
+/// (between 2 and 36, not a power of two), into `format`, rounding half to
+/// even.
+///
+/// Fails with `ParseFloatError::ExponentOutOfRange` if the exponent or the
+/// digits do not fit in the bignums.
+pub(crate) fn from_preparsed_radix(
+    preparsed: PreParsed<'_>,
+    radix: u32,
+    format: &Format,
+) -> Result<Rounded, ParseFloatError> {
+    debug_assert!((2..=36).contains(&radix) && !radix.is_power_of_two());
+    if preparsed.int_digits.is_empty() && preparsed.frac_digits.is_empty() {
+        return Err(ParseFloatError::EmptyDigits);
+    }
+    let all_digits = preparsed.int_digits.iter().chain(preparsed.frac_digits);
+    if let Some((index, &byte)) = all_digits
+        .enumerate()
+        .find(|(_, &c)| char::from(c).to_digit(radix).is_none())
+    {
+        return Err(ParseFloatError::InvalidDigit { byte, index });
+    }
+
+    let preparsed = preparsed.trim_leading_zeros()?.trim_trailing_zeros()?;
+    if preparsed.significant_digits() == 0 {
+        return Ok(Rounded::Zero);
+    }
+    let min_lsb_exp = i64::from(format.min_lsb_exp);
+    let max_exp = i64::from(format.max_exp);
+
+    // value = d * radix^exp_r, with `d` of `num_digits` digits, between
+    // radix^(len - 1) and radix^len
+    let digits = preparsed.int_digits.iter().chain(preparsed.frac_digits);
+    let mut num_digits = preparsed.int_digits.len() + preparsed.frac_digits.len();
+    let mut exp_r = i64::from(preparsed.exp) - preparsed.frac_digits.len() as i64;
+    let len = exp_r + num_digits as i64;
+    // Bounds of the base 2 logarithm of the value, with
+    // radix_bits - 1 <= log2(radix) < radix_bits.
+    let radix_bits = i64::from(32 - radix.leading_zeros());
+    let log2_lo = if len > 0 {
+        (len - 1) * (radix_bits - 1)
+    } else {
+        (len - 1) * radix_bits
+    };
+    let log2_hi = if len >= 0 {
+        len * radix_bits
+    } else {
+        len * (radix_bits - 1)
+    };
+    if log2_lo > max_exp + 1 {
+        return Ok(Rounded::Infinite);
+    } else if log2_hi < min_lsb_exp - 1 {
+        // Less than half of the smallest subnormal.
+        return Ok(Rounded::Zero);
+    }
+
+    // With an even radix, floats and halfway points between them, which
+    // are multiples of 2^(min_lsb_exp - 1), are also multiples of
+    // radix^(min_lsb_exp - 1), so the digits below it are replaced by a
+    // single `1` (the last digit is not zero).
+    let mut sticky = false;
+    if radix % 2 == 0 {
+        let keep = len - (min_lsb_exp - 1);
+        if keep <= 0 {
+            return Ok(Rounded::Zero);
+        } else if keep < num_digits as i64 {
+            exp_r += num_digits as i64 - keep - 1;
+            num_digits = keep as usize;
+            sticky = true;
+        }
+    }
+
+    // value = u / v * 2^(a * exp_r), with radix = 2^a * s, where `u` and
+    // `v` have at most `MAX_BITS` bits.
+    const MAX_BITS: i64 = 16_800;
+    let a = radix.trailing_zeros();
+    let s = radix >> a;
+    let pow_bits = exp_r.abs() * radix_bits;
+    let digits_bits = (num_digits as i64 + 1) * radix_bits;
+    let (u_bits, v_bits) = if exp_r >= 0 {
+        (digits_bits + pow_bits, 1)
+    } else {
+        (digits_bits, pow_bits)
+    };
+    if u_bits > MAX_BITS || v_bits > MAX_BITS {
+        return Err(ParseFloatError::ExponentOutOfRange);
+    }
+    let mut u = Big::from_small(0);
+    for &c in digits.take(num_digits) {
+        u.mul_small(radix)
+            .add_small(char::from(c).to_digit(radix).unwrap());
+    }
+    if sticky {
+        u.mul_small(radix).add_small(1);
+    }
+    let mut v = Big::from_small(1);
+    if exp_r >= 0 {
+        mul_pow(&mut u, s, exp_r as usize);
+    } else {
+        mul_pow(&mut v, s, -exp_r as usize);
+    }
+
+    Ok(divide(
+        u,
+        v,
+        i64::from(a) * exp_r,
+        preparsed.sign,
+        format,
+        &mut Rounding::<fn() -> u64>::Mode(RoundingMode::NearestEven),
+    ))
+}
+
+/// Multiplies `x` by `s^n`.
+fn mul_pow(x: &mut Big, s: u32, mut n: usize) {
+    // The largest power of `s` that fits in a `u32`, with `s <= 35`.
+    let mut chunk = 1;
+    let mut chunk_exp = 0;
+    while chunk <= u32::MAX / s {
+        chunk *= s;
+        chunk_exp += 1;
+    }
+    while n >= chunk_exp {
+        x.mul_small(chunk);
+        n -= chunk_exp;
+    }
+    x.mul_small(s.pow(n as u32));
+}
+
+/// Rounds `u / v * 2^exp2` to `format` with `rounding`.
+///
+/// The value has to be close to the range of `format`, and `u` and `v`
+/// need room for the shifts to its exponents.
+fn divide<F: FnOnce() -> u64>(
+    u: Big,
+    v: Big,
+    exp2: i64,
+    sign: bool,
+    format: &Format,
+    rounding: &mut Rounding<F>,
+) -> Rounded {
+    let quotient = Quotient::new(u, v, exp2, format);
+    finish(quotient, sign, format, rounding, false)
+}
+
+/// `(q + rem / v) * 2^k`, where `q` has `sig_bits` bits (or less for
+/// subnormals) and `rem < v`.
+struct Quotient {
+    q: u128,
+    rem: Big,
+    v: Big,
+    k: i64,
+}
+
+impl Quotient {
+    /// Divides `u / v * 2^exp2`.
+    fn new(mut u: Big, mut v: Big, exp2: i64, format: &Format) -> Self {
+        let sig_bits = i64::from(format.sig_bits);
+        let min_lsb_exp = i64::from(format.min_lsb_exp);
+
+        // q = floor(u / v * 2^(exp2 - k)), with `sig_bits` bits (or less
+        // for subnormals), since `u / v` is between
+        // 2^(bits(u) - bits(v) - 1) and 2^(bits(u) - bits(v) + 1).
+        let log2_ratio = u.bit_length() as i64 - v.bit_length() as i64;
+        let mut k = (log2_ratio + exp2 - sig_bits).max(min_lsb_exp);
+        let shift = exp2 - k;
+        if shift >= 0 {
+            u.mul_pow2(shift as usize);
+        } else {
+            v.mul_pow2(-shift as usize);
+        }
+        let (mut q, mut rem) = div_rem(&u, &v, format.sig_bits + 1);
+        if q >> format.sig_bits != 0 {
+            v.mul_pow2(1);
+            k += 1;
+            let (q1, rem1) = div_rem(&u, &v, format.sig_bits + 1);
+            q = q1;
+            rem = rem1;
+        }
+        Quotient { q, rem, v, k }
+    }
+
+    /// Returns `floor(rem / v * 2^bits)` and whether it is exact.
+    fn fraction(&self, bits: u32) -> (u128, bool) {
+        let mut rem = self.rem.clone();
+        rem.mul_pow2(bits as usize);
+        let (f, rest) = div_rem(&rem, &self.v, bits);
+        (f, rest.is_zero())
+    }
+}
+
+/// Rounds a `Quotient` like `divide`, or a number just above it if
+/// `just_above`.
+fn finish<F: FnOnce() -> u64>(
+    quotient: Quotient,
+    sign: bool,
+    format: &Format,
+    rounding: &mut Rounding<F>,
+    just_above: bool,
+) -> Rounded {
+    let sig_bits = i64::from(format.sig_bits);
+    let max_exp = i64::from(format.max_exp);
+    let Quotient {
+        mut q,
+        rem,
+        v,
+        mut k,
+    } = quotient;
+
+    if round_up(sign, q & 1 == 1, rem, &v, rounding, just_above) {
+        q += 1;
+        if q >> format.sig_bits != 0 {
+            q >>= 1;
+            k += 1;
+        }
+    }
+    if k + sig_bits - 1 > max_exp {
+        return overflow(sign, format, rounding);
+    }
+    if q == 0 {
+        return Rounded::Zero;
+    }
+    Rounded::Finite(q, k as i32)
+}
+
+/// Whether the quotient of `divide`, which is odd if `odd`, has to be
+/// incremented for the dropped fraction `r / v` (or a fraction just above
+/// it if `just_above`).
+fn round_up<F: FnOnce() -> u64>(
+    sign: bool,
+    odd: bool,
+    mut r: Big,
+    v: &Big,
+    rounding: &mut Rounding<F>,
+    just_above: bool,
+) -> bool {
+    match *rounding {
+        Rounding::Mode(mode) => {
+            r.mul_pow2(1);
+            let remainder = if r.is_zero() {
+                Remainder::Zero
+            } else {
+                match r.cmp(v) {
+                    Ordering::Less => Remainder::BelowHalf,
+                    Ordering::Equal => Remainder::Half,
+                    Ordering::Greater => Remainder::AboveHalf,
+                }
+            };
+            let remainder = match (remainder, just_above) {
+                (Remainder::Zero, true) => Remainder::BelowHalf,
+                (Remainder::Half, true) => Remainder::AboveHalf,
+                (remainder, _) => remainder,
+            };
+            mode.round_up(sign, odd, remainder)
+        }
+        // A fraction just above zero is below any threshold.
+        Rounding::Stochastic(_) if r.is_zero() => false,
+        Rounding::Stochastic(ref mut random) => {
+            // `r / v * 2^64 = f + rest / v`, compared with the threshold.
+            let threshold = u128::from(random.get()) + 1;
+            r.mul_pow2(64);
+            let (f, _) = div_rem(&r, v, 64);
+            f >= threshold
+        }
+    }
+}
+
+/// The result of a number beyond the largest finite number of `format`.
+fn overflow<F>(sign: bool, format: &Format, rounding: &Rounding<F>) -> Rounded {
+    let up = match *rounding {
+        Rounding::Mode(mode) => mode.round_up(sign, true, Remainder::AboveHalf),
+        Rounding::Stochastic(_) => true,
+    };
+    if up {
+        Rounded::Infinite
+    } else {
+        let max_q = (1 << format.sig_bits) - 1;
+        Rounded::Finite(max_q, format.max_exp - format.sig_bits as i32 + 1)
+    }
+}
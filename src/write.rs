@@ -0,0 +1,676 @@
+//! Assembly of pre-formatted numbers into strings.
+//!
+//! `PreFormatted` values can be turned into text in many ways. This module
+//! provides the common ones (positional and scientific notation) with
+//! configurable spellings, writing into a caller-provided buffer.
+
+use core::fmt;
+use core::ops::RangeInclusive;
+
+use crate::{BufferTooSmall, FloatExt, PreFormatted, PreFormattedFixed};
+
+/// Maximum length of the string written by `write_minimal` for the result
+/// of `FloatExt::preformat_shortest`, with `WriteOptions::DEFAULT`.
+///
+/// That is a sign, 17 digits, a decimal point and an exponent such as
+/// `e-308`.
+pub const MINIMAL_SHORTEST_LEN: usize = crate::PREFORMAT_SHORTEST_BUF_LEN + 7;
+
+/// Spellings used when writing numbers.
+///
+/// # Example
+///
+/// ```
+/// use flt2dec2flt::write::{write_scientific, WriteOptions};
+/// use flt2dec2flt::PreFormatted;
+///
+/// let options = WriteOptions {
+///     decimal_point: ",",
+///     exp_marker: " * 10^",
+///     ..WriteOptions::DEFAULT
+/// };
+/// let mut buf = [0; 32];
+/// let s = write_scientific(&mut buf, PreFormatted::Finite(true, b"125", 0, 21), 0, &options);
+/// assert_eq!(s, Ok("-1,25 * 10^20"));
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct WriteOptions<'a> {
+    /// Written for NaN.
+    pub nan: &'a str,
+    /// Written for infinity, after the sign.
+    pub inf: &'a str,
+    /// Written before negative numbers.
+    pub minus_sign: &'a str,
+    /// Written before positive numbers.
+    pub plus_sign: &'a str,
+    /// Whether negative zero is written with `minus_sign`.
+    pub signed_zero: bool,
+    /// Written between the integer and the fractional digits.
+    pub decimal_point: &'a str,
+    /// Written between the significand and the exponent in scientific
+    /// notation.
+    pub exp_marker: &'a str,
+    /// How the exponent is written in scientific notation.
+    pub exp_format: ExpFormat,
+}
+
+impl WriteOptions<'static> {
+    /// Default spellings: `NaN`, `inf`, `-`, no plus sign, signed zeros,
+    /// `.` and `e`.
+    pub const DEFAULT: Self = Self {
+        nan: "NaN",
+        inf: "inf",
+        minus_sign: "-",
+        plus_sign: "",
+        signed_zero: true,
+        decimal_point: ".",
+        exp_marker: "e",
+        exp_format: ExpFormat::DEFAULT,
+    };
+}
+
+impl Default for WriteOptions<'static> {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// How an exponent is written.
+///
+/// # Example
+///
+/// ```
+/// use flt2dec2flt::write::{write_exponent, ExpFormat};
+///
+/// let mut buf = [0; 8];
+/// assert_eq!(write_exponent(&mut buf, -308, &ExpFormat::DEFAULT), Ok("-308"));
+///
+/// // C's `printf("%e")` style
+/// let c_style = ExpFormat {
+///     plus_sign: true,
+///     min_digits: 2,
+/// };
+/// assert_eq!(write_exponent(&mut buf, 5, &c_style), Ok("+05"));
+/// assert_eq!(write_exponent(&mut buf, -123, &c_style), Ok("-123"));
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ExpFormat {
+    /// Whether `+` is written before non-negative exponents.
+    pub plus_sign: bool,
+    /// Minimum number of digits, padding with zeros at the left.
+    pub min_digits: usize,
+}
+
+impl ExpFormat {
+    /// No plus sign and no padding, such as `21` or `-308`.
+    pub const DEFAULT: Self = Self {
+        plus_sign: false,
+        min_digits: 1,
+    };
+}
+
+impl Default for ExpFormat {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Destination of the bytes written by `positional` and `scientific`.
+pub(crate) trait Output {
+    fn push_bytes(&mut self, s: &[u8]);
+
+    fn push(&mut self, b: u8) {
+        self.push_bytes(&[b]);
+    }
+
+    fn push_str(&mut self, s: &str) {
+        self.push_bytes(s.as_bytes());
+    }
+
+    fn push_zeros(&mut self, mut n: usize) {
+        const ZEROS: [u8; 32] = [b'0'; 32];
+        while n > ZEROS.len() {
+            self.push_bytes(&ZEROS);
+            n -= ZEROS.len();
+        }
+        self.push_bytes(&ZEROS[..n]);
+    }
+
+    /// Writes the decimal representation of `v`.
+    fn push_u64(&mut self, mut v: u64) {
+        let mut tmp = [0; 20];
+        let mut i = tmp.len();
+        loop {
+            i -= 1;
+            tmp[i] = b'0' + (v % 10) as u8;
+            v /= 10;
+            if v == 0 {
+                break;
+            }
+        }
+        self.push_bytes(&tmp[i..]);
+    }
+
+    fn push_exponent(&mut self, exp: i32, format: &ExpFormat) {
+        if exp < 0 {
+            self.push(b'-');
+        } else if format.plus_sign {
+            self.push(b'+');
+        }
+        let abs_exp = i64::from(exp).abs() as u64;
+        let mut num_digits: usize = 1;
+        let mut rest = abs_exp / 10;
+        while rest != 0 {
+            num_digits += 1;
+            rest /= 10;
+        }
+        if num_digits < format.min_digits {
+            self.push_zeros(format.min_digits - num_digits);
+        }
+        self.push_u64(abs_exp);
+    }
+}
+
+/// Writes bytes into a buffer, counting how many would have been written
+/// when the buffer is too small.
+pub(crate) struct Cursor<'a> {
+    buf: &'a mut [u8],
+    len: usize,
+}
+
+impl<'a> Cursor<'a> {
+    pub(crate) fn new(buf: &'a mut [u8]) -> Self {
+        Self { buf, len: 0 }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    pub(crate) fn finish(self) -> Result<&'a str, BufferTooSmall> {
+        if self.len > self.buf.len() {
+            Err(BufferTooSmall {
+                required: self.len,
+                actual: self.buf.len(),
+            })
+        } else {
+            // Only `str`s and ASCII bytes are written.
+            Ok(core::str::from_utf8(&self.buf[..self.len]).unwrap())
+        }
+    }
+}
+
+impl Output for Cursor<'_> {
+    fn push_bytes(&mut self, s: &[u8]) {
+        if let Some(dst) = self.buf.get_mut(self.len..) {
+            let n = dst.len().min(s.len());
+            dst[..n].copy_from_slice(&s[..n]);
+        }
+        self.len = self.len.saturating_add(s.len());
+    }
+
+    fn push_zeros(&mut self, n: usize) {
+        if let Some(dst) = self.buf.get_mut(self.len..) {
+            for b in dst.iter_mut().take(n) {
+                *b = b'0';
+            }
+        }
+        self.len = self.len.saturating_add(n);
+    }
+}
+
+/// Counts the written `char`s.
+pub(crate) struct CharCount(pub(crate) usize);
+
+impl Output for CharCount {
+    fn push_bytes(&mut self, s: &[u8]) {
+        self.0 += s.iter().filter(|&&b| b & 0xC0 != 0x80).count();
+    }
+}
+
+/// Forwards bytes to a `fmt::Write`, keeping the first error.
+pub(crate) struct FmtOutput<'a, W: ?Sized> {
+    writer: &'a mut W,
+    pub(crate) result: fmt::Result,
+}
+
+impl<'a, W: fmt::Write + ?Sized> FmtOutput<'a, W> {
+    pub(crate) fn new(writer: &'a mut W) -> Self {
+        Self {
+            writer,
+            result: Ok(()),
+        }
+    }
+}
+
+impl<W: fmt::Write + ?Sized> Output for FmtOutput<'_, W> {
+    fn push_bytes(&mut self, s: &[u8]) {
+        if self.result.is_ok() {
+            // Only `str`s and ASCII bytes are written.
+            self.result = self.writer.write_str(core::str::from_utf8(s).unwrap());
+        }
+    }
+}
+
+/// Writes the sign of a number, returning `false` if the number is NaN
+/// (which has been written entirely).
+fn write_sign(
+    out: &mut impl Output,
+    preformatted: &PreFormatted<'_>,
+    options: &WriteOptions<'_>,
+) -> bool {
+    let negative = match *preformatted {
+        PreFormatted::NaN => {
+            out.push_str(options.nan);
+            return false;
+        }
+        PreFormatted::Inf(sign) | PreFormatted::Finite(sign, ..) => sign,
+        PreFormatted::Zero(sign) => sign && options.signed_zero,
+    };
+    out.push_str(if negative {
+        options.minus_sign
+    } else {
+        options.plus_sign
+    });
+    true
+}
+
+pub(crate) fn positional(
+    out: &mut impl Output,
+    preformatted: PreFormatted<'_>,
+    min_frac_digits: usize,
+    options: &WriteOptions<'_>,
+) {
+    positional_grouped(out, preformatted, min_frac_digits, None, options);
+}
+
+/// Like `positional`, writing `group_separator` (if any) between groups of
+/// three integer digits.
+fn positional_grouped(
+    out: &mut impl Output,
+    preformatted: PreFormatted<'_>,
+    min_frac_digits: usize,
+    group_separator: Option<&str>,
+    options: &WriteOptions<'_>,
+) {
+    if !write_sign(out, &preformatted, options) {
+        return;
+    }
+    let fixed = PreFormattedFixed::from(preformatted);
+    let (digits, point_index, int_len, frac_leading_zeros) = match fixed {
+        PreFormattedFixed::NaN => unreachable!(),
+        PreFormattedFixed::Inf(_) => {
+            out.push_str(options.inf);
+            return;
+        }
+        PreFormattedFixed::Zero(_) => (&b""[..], 0, 1, 0),
+        PreFormattedFixed::Finite {
+            digits,
+            point_index,
+            int_len,
+            frac_leading_zeros,
+            ..
+        } => (digits, point_index, int_len, frac_leading_zeros),
+    };
+
+    let frac_digits = fixed.frac_len();
+    if int_len == 0 {
+        // 0.000ddd
+        out.push(b'0');
+    } else if let Some(separator) = group_separator {
+        // ddd,d00,0
+        let int_digits = digits[..point_index]
+            .iter()
+            .cloned()
+            .chain(core::iter::repeat(b'0'))
+            .take(int_len);
+        for (i, d) in int_digits.enumerate() {
+            if i != 0 && (int_len - i) % 3 == 0 {
+                out.push_str(separator);
+            }
+            out.push(d);
+        }
+    } else {
+        // ddd000
+        out.push_bytes(&digits[..point_index]);
+        out.push_zeros(int_len - point_index);
+    }
+    if frac_digits != 0 || min_frac_digits > 0 {
+        // .000ddd000
+        out.push_str(options.decimal_point);
+        out.push_zeros(frac_leading_zeros);
+        out.push_bytes(&digits[point_index..]);
+        out.push_zeros(fixed.frac_trailing_zeros());
+    }
+    if frac_digits < min_frac_digits {
+        out.push_zeros(min_frac_digits - frac_digits);
+    }
+}
+
+pub(crate) fn scientific(
+    out: &mut impl Output,
+    preformatted: PreFormatted<'_>,
+    min_frac_digits: usize,
+    options: &WriteOptions<'_>,
+) {
+    if !write_sign(out, &preformatted, options) {
+        return;
+    }
+    let (digits, zeros, exp) = match preformatted {
+        PreFormatted::NaN => unreachable!(),
+        PreFormatted::Inf(_) => {
+            out.push_str(options.inf);
+            return;
+        }
+        PreFormatted::Zero(_) => (&b"0"[..], 0, 1),
+        PreFormatted::Finite(_, digits, zeros, exp) => (digits, zeros, exp),
+    };
+
+    let frac_digits = digits.len() - 1 + zeros;
+    out.push(digits[0]);
+    if frac_digits > 0 || min_frac_digits > 0 {
+        out.push_str(options.decimal_point);
+    }
+    out.push_bytes(&digits[1..]);
+    out.push_zeros(zeros);
+    if frac_digits < min_frac_digits {
+        out.push_zeros(min_frac_digits - frac_digits);
+    }
+    out.push_str(options.exp_marker);
+    out.push_exponent(i32::from(exp) - 1, &options.exp_format);
+}
+
+/// Writes `preformatted` in positional notation (such as `1234.5` or
+/// `0.00012`), with at least `min_frac_digits` fractional digits.
+///
+/// The decimal point is omitted when there are no fractional digits.
+///
+/// # Example
+///
+/// ```
+/// use flt2dec2flt::write::{write_positional, WriteOptions};
+/// use flt2dec2flt::PreFormatted;
+///
+/// let mut buf = [0; 32];
+/// let s = write_positional(&mut buf, PreFormatted::Finite(false, b"12345", 0, 4), 0, &WriteOptions::DEFAULT);
+/// assert_eq!(s, Ok("1234.5"));
+///
+/// let s = write_positional(&mut buf, PreFormatted::Finite(false, b"12", 0, -3), 0, &WriteOptions::DEFAULT);
+/// assert_eq!(s, Ok("0.00012"));
+///
+/// let s = write_positional(&mut buf, PreFormatted::Finite(false, b"3", 0, 2), 1, &WriteOptions::DEFAULT);
+/// assert_eq!(s, Ok("30.0"));
+///
+/// let s = write_positional(&mut buf, PreFormatted::Zero(true), 0, &WriteOptions::DEFAULT);
+/// assert_eq!(s, Ok("-0"));
+/// ```
+pub fn write_positional<'a>(
+    buf: &'a mut [u8],
+    preformatted: PreFormatted<'_>,
+    min_frac_digits: usize,
+    options: &WriteOptions<'_>,
+) -> Result<&'a str, BufferTooSmall> {
+    let mut cursor = Cursor::new(buf);
+    positional(&mut cursor, preformatted, min_frac_digits, options);
+    cursor.finish()
+}
+
+/// Writes `preformatted` in scientific notation (such as `1.2345e3` or
+/// `1.2e-4`), with at least `min_frac_digits` fractional digits in the
+/// significand.
+///
+/// The decimal point is omitted when there are no fractional digits.
+///
+/// # Example
+///
+/// ```
+/// use flt2dec2flt::write::{write_scientific, WriteOptions};
+/// use flt2dec2flt::PreFormatted;
+///
+/// let mut buf = [0; 32];
+/// let s = write_scientific(&mut buf, PreFormatted::Finite(false, b"12345", 0, 4), 0, &WriteOptions::DEFAULT);
+/// assert_eq!(s, Ok("1.2345e3"));
+///
+/// let s = write_scientific(&mut buf, PreFormatted::Finite(false, b"12", 0, -3), 0, &WriteOptions::DEFAULT);
+/// assert_eq!(s, Ok("1.2e-4"));
+///
+/// let s = write_scientific(&mut buf, PreFormatted::Finite(false, b"3", 0, 1), 2, &WriteOptions::DEFAULT);
+/// assert_eq!(s, Ok("3.00e0"));
+/// ```
+pub fn write_scientific<'a>(
+    buf: &'a mut [u8],
+    preformatted: PreFormatted<'_>,
+    min_frac_digits: usize,
+    options: &WriteOptions<'_>,
+) -> Result<&'a str, BufferTooSmall> {
+    let mut cursor = Cursor::new(buf);
+    scientific(&mut cursor, preformatted, min_frac_digits, options);
+    cursor.finish()
+}
+
+/// Writes `preformatted` in positional or scientific notation, whichever
+/// is shorter (positional in case of a tie).
+///
+/// Combined with `FloatExt::preformat_shortest`, this produces the shortest
+/// string that round-trips.
+///
+/// # Example
+///
+/// ```
+/// use flt2dec2flt::write::{write_minimal, WriteOptions};
+/// use flt2dec2flt::PreFormatted;
+///
+/// let mut buf = [0; 32];
+/// let s = write_minimal(&mut buf, PreFormatted::Finite(false, b"125", 0, 3), &WriteOptions::DEFAULT);
+/// assert_eq!(s, Ok("125"));
+///
+/// let s = write_minimal(&mut buf, PreFormatted::Finite(false, b"125", 0, 21), &WriteOptions::DEFAULT);
+/// assert_eq!(s, Ok("1.25e20"));
+///
+/// let s = write_minimal(&mut buf, PreFormatted::Finite(false, b"125", 0, -5), &WriteOptions::DEFAULT);
+/// assert_eq!(s, Ok("1.25e-6"));
+/// ```
+pub fn write_minimal<'a>(
+    buf: &'a mut [u8],
+    preformatted: PreFormatted<'_>,
+    options: &WriteOptions<'_>,
+) -> Result<&'a str, BufferTooSmall> {
+    let mut cursor = Cursor::new(buf);
+    minimal(&mut cursor, preformatted, options);
+    cursor.finish()
+}
+
+pub(crate) fn minimal(
+    out: &mut impl Output,
+    preformatted: PreFormatted<'_>,
+    options: &WriteOptions<'_>,
+) {
+    let mut positional_len = Cursor::new(&mut []);
+    positional(&mut positional_len, preformatted, 0, options);
+    let mut scientific_len = Cursor::new(&mut []);
+    scientific(&mut scientific_len, preformatted, 0, options);
+
+    if positional_len.len() <= scientific_len.len() {
+        positional(out, preformatted, 0, options);
+    } else {
+        scientific(out, preformatted, 0, options);
+    }
+}
+
+/// Writes `preformatted` in positional notation if its exponent in
+/// scientific notation is within `positional_exps`, or in scientific
+/// notation otherwise. Zero is always written in positional notation.
+///
+/// This is how most languages choose the notation, for example
+/// JavaScript uses `-6..=20`.
+///
+/// # Example
+///
+/// ```
+/// use flt2dec2flt::write::{write_general, WriteOptions};
+/// use flt2dec2flt::PreFormatted;
+///
+/// let mut buf = [0; 32];
+/// let s = write_general(&mut buf, PreFormatted::Finite(false, b"125", 0, 3), -4..=5, 1, &WriteOptions::DEFAULT);
+/// assert_eq!(s, Ok("125.0"));
+///
+/// let s = write_general(&mut buf, PreFormatted::Finite(false, b"125", 0, 7), -4..=5, 1, &WriteOptions::DEFAULT);
+/// assert_eq!(s, Ok("1.25e6"));
+///
+/// let s = write_general(&mut buf, PreFormatted::Finite(false, b"1", 0, -4), -4..=5, 1, &WriteOptions::DEFAULT);
+/// assert_eq!(s, Ok("1.0e-5"));
+/// ```
+pub fn write_general<'a>(
+    buf: &'a mut [u8],
+    preformatted: PreFormatted<'_>,
+    positional_exps: RangeInclusive<i32>,
+    min_frac_digits: usize,
+    options: &WriteOptions<'_>,
+) -> Result<&'a str, BufferTooSmall> {
+    let mut cursor = Cursor::new(buf);
+    match preformatted {
+        PreFormatted::Finite(_, _, _, exp) if !positional_exps.contains(&(i32::from(exp) - 1)) => {
+            scientific(&mut cursor, preformatted, min_frac_digits, options);
+        }
+        _ => positional(&mut cursor, preformatted, min_frac_digits, options),
+    }
+    cursor.finish()
+}
+
+/// A layout of exactly `width` `char`s, for `write_fixed_width`.
+///
+/// # Example
+///
+/// ```
+/// use flt2dec2flt::write::{write_fixed_width, FixedWidth, Overflow, WriteOptions};
+///
+/// let mut format = FixedWidth {
+///     width: 9,
+///     frac_digits: 2,
+///     group_separator: Some(","),
+///     overflow: Overflow::Fill('#'),
+/// };
+/// let options = WriteOptions::DEFAULT;
+/// let mut buf = [0; 32];
+/// assert_eq!(write_fixed_width(&mut buf, 1234.5, &format, &options), Ok(" 1,234.50"));
+/// assert_eq!(write_fixed_width(&mut buf, -1234.5, &format, &options), Ok("-1,234.50"));
+/// assert_eq!(write_fixed_width(&mut buf, -12345.0, &format, &options), Ok("#########"));
+///
+/// format.overflow = Overflow::Scientific('#');
+/// assert_eq!(write_fixed_width(&mut buf, -12345.0, &format, &options), Ok("-1.2345e4"));
+/// assert_eq!(write_fixed_width(&mut buf, 1234567.0, &format, &options), Ok("1.23457e6"));
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FixedWidth<'a> {
+    /// Number of `char`s of the written text.
+    pub width: usize,
+    /// Number of fractional digits in positional notation.
+    pub frac_digits: usize,
+    /// Written between groups of three integer digits in positional
+    /// notation, such as `,` for `1,234.50`.
+    pub group_separator: Option<&'a str>,
+    /// What is written when the number does not fit in positional
+    /// notation.
+    pub overflow: Overflow,
+}
+
+/// What `write_fixed_width` writes when a number does not fit.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Overflow {
+    /// Fills the width with the marker, such as `#####` in spreadsheets.
+    Fill(char),
+    /// Switches to scientific notation, with as many significant digits as
+    /// fit (up to 17). If not even one does, fills the width with the
+    /// marker.
+    Scientific(char),
+}
+
+/// Writes `v` in exactly `format.width` `char`s, right-aligned with
+/// spaces.
+///
+/// The number is written in positional notation with `format.frac_digits`
+/// fractional digits (rounding to nearest, ties to even). When that does
+/// not fit, including the sign and the group separators, `format.overflow`
+/// decides what is written instead, so a number is never truncated. NaN
+/// and infinities are written as in `write_positional`.
+///
+/// The result only depends on `v`, `format` and `options`, which makes
+/// columns of numbers keep their width.
+pub fn write_fixed_width<'a, T: FloatExt>(
+    buf: &'a mut [u8],
+    v: T,
+    format: &FixedWidth<'_>,
+    options: &WriteOptions<'_>,
+) -> Result<&'a str, BufferTooSmall> {
+    let mut digits_buf = [0; crate::PREFORMAT_EXACT_FIXED_BASE_BUF_LEN];
+    let frac_digits = format.frac_digits.min(isize::MAX as usize);
+    let preformatted = v.preformat_exact_fixed(&mut digits_buf, frac_digits as isize);
+
+    let mut cursor = Cursor::new(buf);
+    let mut len = CharCount(0);
+    positional_grouped(
+        &mut len,
+        preformatted,
+        frac_digits,
+        format.group_separator,
+        options,
+    );
+    if len.0 <= format.width {
+        push_spaces(&mut cursor, format.width - len.0);
+        positional_grouped(
+            &mut cursor,
+            preformatted,
+            frac_digits,
+            format.group_separator,
+            options,
+        );
+        return cursor.finish();
+    }
+
+    let marker = match format.overflow {
+        Overflow::Fill(marker) => marker,
+        Overflow::Scientific(marker) => {
+            for num_digits in (1..=crate::PREFORMAT_SHORTEST_BUF_LEN).rev() {
+                let preformatted = v.preformat_exact_exp(&mut digits_buf, num_digits);
+                let mut len = CharCount(0);
+                scientific(&mut len, preformatted, num_digits - 1, options);
+                if len.0 <= format.width {
+                    push_spaces(&mut cursor, format.width - len.0);
+                    scientific(&mut cursor, preformatted, num_digits - 1, options);
+                    return cursor.finish();
+                }
+            }
+            marker
+        }
+    };
+    let mut marker_buf = [0; 4];
+    let marker = marker.encode_utf8(&mut marker_buf);
+    for _ in 0..format.width {
+        cursor.push_str(marker);
+    }
+    cursor.finish()
+}
+
+fn push_spaces(out: &mut impl Output, n: usize) {
+    for _ in 0..n {
+        out.push(b' ');
+    }
+}
+
+/// Writes an exponent, with its sign and at least `format.min_digits`
+/// digits.
+///
+/// This is what `write_scientific` writes after `WriteOptions::exp_marker`,
+/// and does not depend on `core::fmt`. 12 bytes are always enough when
+/// `format.min_digits` is at most 11.
+///
+/// See `ExpFormat` for an example.
+pub fn write_exponent<'a>(
+    buf: &'a mut [u8],
+    exp: i32,
+    format: &ExpFormat,
+) -> Result<&'a str, BufferTooSmall> {
+    let mut cursor = Cursor::new(buf);
+    cursor.push_exponent(exp, format);
+    cursor.finish()
+}
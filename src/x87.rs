@@ -0,0 +1,203 @@
+//! Support for the 80-bit extended precision format of the x87 FPU.
+//!
+//! `F80` holds the two parts of the format: the 64-bit significand, with
+//! an explicit integer bit, and the sign and 15-bit biased exponent. Its
+//! digits are generated from the exact value, and parsing rounds once,
+//! directly to 64 bits of precision, without going through `f64`.
+//!
+//! Encodings that current FPUs reject (pseudo-NaNs, pseudo-infinities and
+//! unnormals) are formatted as NaN, and pseudo-denormals as their value.
+//! Parsing only produces canonical encodings.
+//!
+//! # Example
+//!
+//! ```
+//! use flt2dec2flt::x87::{F80, SHORTEST_BUF_LEN};
+//! use flt2dec2flt::{PreFormatted, PreParsed};
+//!
+//! let pi = F80 {
+//!     mantissa: 0xC90F_DAA2_2168_C235,
+//!     sign_exp: 0x4000,
+//! };
+//! let mut buf = [0; SHORTEST_BUF_LEN];
+//! assert_eq!(
+//!     pi.preformat_shortest(&mut buf),
+//!     PreFormatted::Finite(false, b"31415926535897932385", 0, 1),
+//! );
+//!
+//! let preparsed = PreParsed {
+//!     sign: false,
+//!     int_digits: b"0",
+//!     frac_digits: b"1",
+//!     exp: 0,
+//! };
+//! let v = F80::from_preparsed(preparsed).unwrap();
+//! assert_eq!(v.to_le_bytes(), [0xCD, 0xCC, 0xCC, 0xCC, 0xCC, 0xCC, 0xCC, 0xCC, 0xFB, 0x3F]);
+//! // The exact value is 0.1000000000000000000013552527156...
+//! let mut buf = [0; 24];
+//! assert_eq!(
+//!     v.preformat_exact_exp(&mut buf, 24),
+//!     PreFormatted::Finite(false, b"100000000000000000001355", 0, 0),
+//! );
+//! ```
+
+use crate::wide::{self, Decoded, FullDecoded, Rounded};
+use crate::{ParseFloatError, PreFormatted, PreParsed};
+
+/// Minimum buffer size that has to be passed to `F80::preformat_shortest`.
+pub const SHORTEST_BUF_LEN: usize = 21;
+
+/// Minimum base buffer size that has to be passed to
+/// `F80::preformat_exact_fixed` (see `PREFORMAT_EXACT_FIXED_BASE_BUF_LEN`).
+// `wide::estimate_max_buf_len` of the smallest exponent
+// (-16446, for the smallest positive normal `F80`).
+pub const EXACT_FIXED_BASE_BUF_LEN: usize = 12374;
+
+const_assert!(40 + ((12 * 16446) >> 4) == EXACT_FIXED_BASE_BUF_LEN as i32);
+
+const FORMAT: wide::Format = wide::Format {
+    sig_bits: 64,
+    min_lsb_exp: -16445,
+    max_exp: 16383,
+};
+
+/// An x87 80-bit extended precision number.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct F80 {
+    /// The significand, including the integer bit (bit 63).
+    pub mantissa: u64,
+    /// The sign (bit 15) and the biased exponent.
+    pub sign_exp: u16,
+}
+
+impl F80 {
+    /// Positive infinity.
+    pub const INFINITY: Self = Self {
+        mantissa: 1 << 63,
+        sign_exp: 0x7FFF,
+    };
+
+    /// Negative infinity.
+    pub const NEG_INFINITY: Self = Self {
+        mantissa: 1 << 63,
+        sign_exp: 0xFFFF,
+    };
+
+    /// The default quiet NaN.
+    pub const NAN: Self = Self {
+        mantissa: 0xC000_0000_0000_0000,
+        sign_exp: 0x7FFF,
+    };
+
+    /// Reads the number from its 10-byte little-endian memory layout.
+    pub fn from_le_bytes(bytes: [u8; 10]) -> Self {
+        let mut mantissa = [0; 8];
+        mantissa.copy_from_slice(&bytes[..8]);
+        Self {
+            mantissa: u64::from_le_bytes(mantissa),
+            sign_exp: u16::from_le_bytes([bytes[8], bytes[9]]),
+        }
+    }
+
+    /// Returns the 10-byte little-endian memory layout of the number.
+    pub fn to_le_bytes(self) -> [u8; 10] {
+        let mut bytes = [0; 10];
+        bytes[..8].copy_from_slice(&self.mantissa.to_le_bytes());
+        bytes[8..].copy_from_slice(&self.sign_exp.to_le_bytes());
+        bytes
+    }
+
+    fn decode(self) -> (bool, FullDecoded) {
+        let sign = self.sign_exp >> 15 != 0;
+        let biased_exp = i32::from(self.sign_exp & 0x7FFF);
+        let m = self.mantissa;
+        let integer_bit = m >> 63 != 0;
+        let full_decoded = if biased_exp == 0x7FFF {
+            if m == 1 << 63 {
+                FullDecoded::Infinite
+            } else {
+                FullDecoded::Nan
+            }
+        } else if biased_exp != 0 && !integer_bit {
+            // Unnormal.
+            FullDecoded::Nan
+        } else if m == 0 {
+            FullDecoded::Zero
+        } else {
+            // Denormals (and pseudo-denormals) have the exponent of the
+            // smallest normals.
+            let e = biased_exp.max(1) - 16383 - 63;
+            let lower_closer = m == 1 << 63 && biased_exp > 1;
+            FullDecoded::Finite(Decoded::new(u128::from(m), e, lower_closer))
+        };
+        (sign, full_decoded)
+    }
+
+    /// Pre-formats `self` with the lowest number of significant digits
+    /// that round-trip, like `FloatExt::preformat_shortest`.
+    ///
+    /// `buf` must be at least `SHORTEST_BUF_LEN` long.
+    pub fn preformat_shortest(self, buf: &mut [u8]) -> PreFormatted<'_> {
+        let (sign, full_decoded) = self.decode();
+        wide::preformat_shortest(sign, full_decoded, buf)
+    }
+
+    /// Pre-formats `self` with an exact number of significant digits, like
+    /// `FloatExt::preformat_exact_exp`.
+    ///
+    /// `buf` must be at least `num_digits` long (or
+    /// `EXACT_FIXED_BASE_BUF_LEN`, if that is smaller), and `num_digits`
+    /// must not be zero.
+    pub fn preformat_exact_exp(self, buf: &mut [u8], num_digits: usize) -> PreFormatted<'_> {
+        let (sign, full_decoded) = self.decode();
+        wide::preformat_exact_exp(sign, full_decoded, buf, num_digits)
+    }
+
+    /// Pre-formats `self` with an exact number of fractional digits, like
+    /// `FloatExt::preformat_exact_fixed`.
+    ///
+    /// `buf` must be at least `EXACT_FIXED_BASE_BUF_LEN` long, whatever
+    /// `num_frac_digits` is.
+    pub fn preformat_exact_fixed(self, buf: &mut [u8], num_frac_digits: isize) -> PreFormatted<'_> {
+        let (sign, full_decoded) = self.decode();
+        wide::preformat_exact_fixed(sign, full_decoded, buf, num_frac_digits)
+    }
+
+    /// Pre-formats `self` with the digits above `10^limit`, like
+    /// `FloatExt::preformat_exact_limit`.
+    ///
+    /// `buf` must be at least `EXACT_FIXED_BASE_BUF_LEN` long.
+    pub fn preformat_exact_limit(self, buf: &mut [u8], limit: i16) -> PreFormatted<'_> {
+        let (sign, full_decoded) = self.decode();
+        wide::preformat_exact_limit(sign, full_decoded, buf, limit)
+    }
+
+    /// Converts a pre-parsed decimal into the nearest `F80`, rounding half
+    /// to even.
+    ///
+    /// Any number of digits is accepted.
+    pub fn from_preparsed(preparsed: PreParsed<'_>) -> Result<Self, ParseFloatError> {
+        let sign = preparsed.sign;
+        let sign_bit = u16::from(sign) << 15;
+        let v = match wide::from_preparsed(preparsed, &FORMAT)? {
+            Rounded::Zero => Self {
+                mantissa: 0,
+                sign_exp: sign_bit,
+            },
+            Rounded::Finite(q, k) => {
+                // Normals have the integer bit set, denormals have the
+                // exponent of the smallest normals and a biased exponent of 0.
+                let biased_exp = if q >> 63 != 0 { k + 16446 } else { 0 };
+                Self {
+                    mantissa: q as u64,
+                    sign_exp: sign_bit | biased_exp as u16,
+                }
+            }
+            Rounded::Infinite => Self {
+                mantissa: 1 << 63,
+                sign_exp: sign_bit | 0x7FFF,
+            },
+        };
+        Ok(v)
+    }
+}